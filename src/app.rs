@@ -1,21 +1,43 @@
+use crate::components::alerts::Alerts;
+use crate::components::benchmark::Benchmark;
+use crate::components::bookmarks::Bookmarks;
+use crate::components::bulk::{save_csv, BulkAction, BulkSelection};
+use crate::components::focus::FocusFollower;
+use crate::components::heavy_hitters::HeavyHittersView;
+use crate::components::i18n::{self, tr, tr_format};
+use crate::components::layout::{Layout, LayoutRequest, Tab};
+use crate::components::limits::LimitEditor;
+use crate::components::overlay::MiniOverlay;
 use crate::components::process_selector::ProcessSelector;
-use crate::components::process_view::{self, state::ProcessView};
-use crate::components::settings::{show_settings_window, Settings, UpdateMode};
+use crate::components::process_view::{
+    report::format_duration,
+    state::{PlotGuide, PlotOverlays, ProcessView, SampleGap},
+    ui::{availability_strip, leak_summary, sparkline},
+};
+use crate::components::profiles::{Profile, ProfileRequest, Profiles};
+use crate::components::recording::RecordingView;
+use crate::components::settings::{
+    show_guide_lines, show_interval_override, show_settings_ui, show_settings_window,
+    DeadMatchAction, Settings, UpdateMode,
+};
+use crate::components::slo::Slos;
+use crate::events::{AppEvent, EventBus, LogSink};
 use crate::metrics::process::{MetricType, ProcessIdentifier, SortType};
-use crate::metrics::{self, Metrics};
+use crate::metrics::{Metrics, GENERAL_STATS_PID};
 use log::info;
-use std::sync::{Arc, RwLock};
-use std::thread;
-use std::time::Duration;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
 use sysinfo::Pid;
 
+/// Samples drawn in the CPU sparkline of a side panel entry
+const SPARKLINE_SAMPLES: usize = 60;
+
 #[derive(serde::Deserialize, serde::Serialize, Default)]
 #[serde(default)]
 pub struct ProcessMonitorApp {
     #[serde(skip)]
-    pub metrics: Arc<RwLock<Metrics>>,
+    pub metrics: Metrics,
     pub monitored_processes: Vec<ProcessIdentifier>,
-    #[serde(skip)]
     pub process_selector: ProcessSelector,
     pub process_view: ProcessView,
     settings: Settings,
@@ -24,26 +46,90 @@ pub struct ProcessMonitorApp {
     #[serde(skip)]
     scroll_target: Option<Pid>,
     current_metric: MetricType,
+    #[serde(skip)]
+    bookmarks: Bookmarks,
+    layout: Layout,
+    alerts: Alerts,
+    slos: Slos,
+    benchmark: Benchmark,
+    profiles: Profiles,
+    focus: FocusFollower,
+    heavy_hitters: HeavyHittersView,
+    recording: RecordingView,
+    mini_overlay: MiniOverlay,
+    /// Active process the dock layout was last synced to
+    #[serde(skip)]
+    synced_active_process: Option<ProcessIdentifier>,
+    /// When each process tab was last on screen, for auto-pause
+    #[serde(skip)]
+    last_viewed: HashMap<ProcessIdentifier, Instant>,
+    #[serde(skip)]
+    limits: LimitEditor,
+    /// Side panel entries checked for bulk actions
+    #[serde(skip)]
+    bulk: BulkSelection,
+    #[serde(skip)]
+    events: EventBus,
+    /// Last sampler tick published on the event bus
+    #[serde(skip)]
+    published_tick: u64,
+    /// Inactive entries whose archive could not be written, kept until removed by hand
+    #[serde(skip)]
+    archive_errors: HashMap<ProcessIdentifier, String>,
+    /// Pop-out or attach asked for from a menu inside the dock
+    #[serde(skip)]
+    layout_request: Option<LayoutRequest>,
 }
 
 impl ProcessMonitorApp {
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
         if let Some(storage) = cc.storage {
             let mut app: Self = eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
-            let metrics =
+            app.metrics =
                 Metrics::new(app.settings.history_length, app.settings.update_interval_ms);
-            {
-                app.metrics = metrics;
-                for process in app.monitored_processes.clone() {
-                    app.metrics.write().unwrap().add_selected_process(process);
-                }
+            for process in app.monitored_processes.clone() {
+                app.metrics.add_selected_process(process);
+            }
+            app.metrics
+                .set_exclude_rules(app.settings.exclude_rules.clone());
+            app.metrics
+                .set_watch_rules(app.settings.watch_rules.clone());
+            for (identifier, interval_ms) in &app.settings.interval_overrides {
+                app.metrics.set_interval_override(
+                    identifier,
+                    Some(Duration::from_millis(*interval_ms as u64)),
+                );
             }
+            app.metrics
+                .set_adaptive_sampling(app.settings.adaptive_sampling);
+            app.metrics.set_oom_projection(app.settings.oom_projection);
+            app.metrics.set_leak_detection(app.settings.leak_detection);
+            app.metrics
+                .set_sample_thermals(app.settings.show_cpu_thermals);
+            app.metrics
+                .set_flight_recorder(app.settings.flight_recorder.clone());
+            app.metrics
+                .set_influx_push(app.settings.influx_push.clone());
+            app.events.subscribe(LogSink);
+            app.start_services();
             app
         } else {
-            ProcessMonitorApp {
+            let mut app = ProcessMonitorApp {
                 metrics: Metrics::new(100, 10000),
                 ..Default::default()
-            }
+            };
+            app.events.subscribe(LogSink);
+            app.start_services();
+            app
+        }
+    }
+
+    /// Control server and InfluxDB pusher, which idles until enabled in the settings
+    fn start_services(&self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            crate::control::spawn_server(self.metrics.clone());
+            crate::metrics::influx::spawn(self.metrics.clone());
         }
     }
 }
@@ -56,119 +142,949 @@ impl eframe::App for ProcessMonitorApp {
 
     /// Called each time the UI needs repainting, which may be many times per second.
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.metrics.update();
         self.settings.apply(ctx);
+        if self.mini_overlay.enabled {
+            self.show_mini_overlay(ctx);
+            return;
+        }
+        self.mini_overlay.reshape(ctx);
+        if let Some(change) = self.focus.poll(&self.monitored_processes) {
+            if let Some(previous) = change
+                .unfollow
+                .as_ref()
+                .filter(|previous| !self.settings.is_pinned(previous))
+            {
+                self.remove_monitored_proc(previous);
+            }
+            self.add_monitored_proc(change.follow.clone());
+            self.active_process = Some(change.follow);
+        }
 
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
-                ui.menu_button("Menu", |ui| {
-                    if ui.button("Quit").clicked() {
+                ui.menu_button(tr("Menu"), |ui| {
+                    ui.menu_button(tr("Profiles"), |ui| self.profiles.show_menu(ui));
+                    if ui
+                        .button(tr("▣ Mini overlay"))
+                        .on_hover_text(tr(
+                            "Shrink the window to an always-on-top strip with the CPU and \
+                             memory of the active process, redrawn only on new samples",
+                        ))
+                        .clicked()
+                    {
+                        self.mini_overlay.toggle();
+                        ui.close_menu();
+                    }
+                    if ui.button(tr("Quit")).clicked() {
                         ctx.send_viewport_cmd(egui::ViewportCommand::Close);
                     }
                 });
 
                 ui.add_space(16.0);
-                if ui.button("⚙").clicked() {
+                if ui.button("⚙").on_hover_text(tr("Settings")).clicked() {
                     self.settings.show();
                 }
                 ui.add_space(4.0);
+                if ui
+                    .button("🔖")
+                    .on_hover_text(tr("Bookmarks (press B to bookmark now)"))
+                    .clicked()
+                {
+                    self.bookmarks.show_list = !self.bookmarks.show_list;
+                }
+                ui.add_space(4.0);
+                let held = self.alerts.held_count();
+                let alerts_button = if held > 0 {
+                    ui.button(format!("🔔 {held}")).on_hover_text(format!(
+                        "{}: {held}",
+                        tr("Alert rules, alerts held during quiet hours")
+                    ))
+                } else {
+                    ui.button("🔔").on_hover_text(tr("Alert rules"))
+                };
+                if alerts_button.clicked() {
+                    self.alerts.toggle_window();
+                }
+                ui.add_space(4.0);
+                if ui.button("🎯").on_hover_text(tr("SLO targets")).clicked() {
+                    self.slos.toggle_window();
+                }
+                ui.add_space(4.0);
+                if ui
+                    .button("⚖")
+                    .on_hover_text(tr("Compare two recorded runs"))
+                    .clicked()
+                {
+                    self.benchmark.toggle_window();
+                }
+                ui.add_space(4.0);
+                if ui
+                    .button("🔥")
+                    .on_hover_text(tr("Heaviest processes system-wide"))
+                    .clicked()
+                {
+                    self.heavy_hitters.toggle_window();
+                }
+                ui.add_space(4.0);
+                if !cfg!(target_arch = "wasm32")
+                    && ui
+                        .button("📼")
+                        .on_hover_text(tr(
+                            "Open the flight log of another, possibly crashed tvis instance",
+                        ))
+                        .clicked()
+                {
+                    self.recording.toggle_window();
+                }
+                ui.add_space(4.0);
+                if !cfg!(target_arch = "wasm32")
+                    && ui
+                        .add_enabled(!self.monitored_processes.is_empty(), egui::Button::new("🖼"))
+                        .on_hover_text(tr(
+                            "Save CPU and memory plots of all monitored processes as one image",
+                        ))
+                        .clicked()
+                {
+                    self.process_view.export_overview(
+                        &self.metrics,
+                        &self.monitored_processes,
+                        &self.settings,
+                    );
+                }
+                self.focus.show_toggle(ui);
+                ui.add_space(4.0);
                 if ui
                     .button("⟲")
-                    .on_hover_text("Clear current process data")
+                    .on_hover_text(tr("Clear current process data"))
                     .clicked()
                 {
                     if let Some(identifier) = &self.active_process {
-                        let mut metrics = self.metrics.write().unwrap();
-                        metrics.clear_process_data(identifier);
+                        self.metrics.clear_process_data(identifier);
                     }
                 }
+
+                if self.metrics.safe_mode {
+                    ui.add_space(16.0);
+                    ui.colored_label(ui.visuals().warn_fg_color, tr("⚠ Safe mode"))
+                        .on_hover_text(tr_format(
+                            "{} processes running. Only monitored process trees are refreshed \
+                             every tick, new processes are picked up on a periodic full scan \
+                             and the process list is searched on demand.",
+                            &[&self.metrics.system_process_count],
+                        ));
+                }
             });
         });
 
-        show_settings_window(ctx, &mut self.settings, self.metrics.clone());
+        if show_settings_window(ctx, &mut self.settings, &mut self.metrics) {
+            self.layout.open(Tab::Settings);
+        }
 
-        let mut to_remove = None;
-        egui::SidePanel::left("process_list")
-            .resizable(true)
-            .min_width(150.0)
-            .max_width(800.0)
-            .default_width(200.0)
-            .show(ctx, |ui| {
-                ui.heading("Monitored Processes");
-                ui.add_space(4.0);
+        self.alerts.sync(&mut self.metrics);
+        self.publish_metrics_events();
+        self.dispatch_events();
 
-                // Process selector
-                if let Some(proc) = self.process_selector.show(ui, self.metrics.clone()) {
-                    self.add_monitored_proc(proc);
-                };
+        let cpu_count = self.metrics.monitor.cpu_count();
+        self.alerts
+            .show(ctx, &self.monitored_processes, &self.settings, cpu_count);
+        self.slos
+            .show(ctx, &self.monitored_processes, &self.settings);
+        self.benchmark.show(ctx, &self.settings);
+        self.recording.show(ctx, &self.settings);
+        self.profiles.show(ctx);
+        match self.profiles.take_request() {
+            Some(ProfileRequest::Apply(profile)) => self.apply_profile(&profile),
+            Some(ProfileRequest::SaveCurrent(name)) => {
+                let profile = Profile::capture(
+                    name,
+                    &self.monitored_processes,
+                    &self.settings,
+                    &self.alerts.rules,
+                );
+                self.profiles.set_status(Ok(tr_format(
+                    "Saved \"{}\", {}",
+                    &[&profile.name, &profile.summary()],
+                )));
+                self.profiles.active = Some(profile.name.clone());
+                self.profiles.insert(profile);
+            }
+            None => {}
+        }
 
-                // Process list with remove buttons
-                for (i, process) in self.monitored_processes.iter().enumerate() {
-                    ui.horizontal(|ui| {
-                        let is_active = self.active_process.as_ref() == Some(process);
+        self.process_view.show_image_export(ctx);
+        self.process_view
+            .show_history_summary(ctx, &self.metrics, &self.settings);
+        self.process_view
+            .show_inspection_diff(ctx, &self.metrics, &self.settings);
+        self.limits.show(ctx, &mut self.metrics, &self.settings);
 
-                        let response = ui.selectable_label(is_active, process.to_string());
-                        if response.clicked() {
-                            self.active_process = Some(process.clone());
-                        }
+        let to_add = self.heavy_hitters.show(
+            ctx,
+            &self.metrics.heavy_hitters,
+            &self.monitored_processes,
+            &self.settings,
+            self.metrics.monitor.cpu_count(),
+        );
+        if let Some(process) = to_add {
+            self.add_monitored_proc(process);
+        }
 
-                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                            if ui.small_button("❌").clicked() {
-                                if self.active_process.as_ref() == Some(process) {
-                                    self.active_process = None;
-                                }
-                                to_remove = Some((i, process.clone()));
-                            }
-                        });
+        if !ctx.wants_keyboard_input()
+            && !self.bookmarks.is_pending()
+            && ctx.input(|i| i.key_pressed(egui::Key::B))
+        {
+            self.bookmarks
+                .begin(&self.metrics, self.active_process.clone());
+        }
+        // Focus traversal doesn't reach the dock tabs, these walk the process tabs
+        let step = ctx.input_mut(|i| {
+            if i.consume_key(egui::Modifiers::COMMAND, egui::Key::PageDown) {
+                1
+            } else if i.consume_key(egui::Modifiers::COMMAND, egui::Key::PageUp) {
+                -1
+            } else {
+                0
+            }
+        });
+        if step != 0 && !self.monitored_processes.is_empty() {
+            let count = self.monitored_processes.len() as isize;
+            let next = match self
+                .active_process
+                .as_ref()
+                .and_then(|active| self.monitored_processes.iter().position(|p| p == active))
+            {
+                Some(index) => (index as isize + step).rem_euclid(count),
+                None if step > 0 => 0,
+                None => count - 1,
+            };
+            self.active_process = Some(self.monitored_processes[next as usize].clone());
+        }
+        if let Some(process) = self.bookmarks.show(ctx, &mut self.metrics) {
+            if self.monitored_processes.contains(&process) {
+                self.active_process = Some(process);
+            }
+        }
+
+        self.show_detached(ctx);
+
+        // Only the dock is taken out, the detached views stay readable from the tabs
+        let mut dock_state = std::mem::replace(
+            &mut self.layout.dock_state,
+            egui_dock::DockState::new(Vec::new()),
+        );
+        egui_dock::DockArea::new(&mut dock_state)
+            .style(egui_dock::Style::from_egui(ctx.style().as_ref()))
+            .show(ctx, &mut AppTabs { app: self });
+        self.layout.dock_state = dock_state;
+        if let Some(request) = self.layout_request.take() {
+            if let LayoutRequest::Detach(identifier) = &request {
+                if self.active_process.as_ref() == Some(identifier) {
+                    self.active_process = None;
+                }
+            }
+            self.layout.apply(request);
+        }
+        self.sync_watched();
+        self.sync_layout();
+        self.auto_pause();
+        self.expire_dead_matches();
+
+        if self.settings.update_mode == UpdateMode::Continuous {
+            // Change mode rendering
+            ctx.request_repaint();
+        }
+    }
+}
+
+impl ProcessMonitorApp {
+    /// The whole window in overlay mode. Events, alerts and the housekeeping of
+    /// the entries keep running, the rest of the UI is skipped.
+    fn show_mini_overlay(&mut self, ctx: &egui::Context) {
+        let identifier = self
+            .active_process
+            .clone()
+            .or_else(|| self.monitored_processes.first().cloned());
+        if let Some(identifier) = &identifier {
+            self.last_viewed.insert(identifier.clone(), Instant::now());
+        }
+        if self
+            .mini_overlay
+            .show(ctx, &self.metrics, identifier.as_ref(), &self.settings)
+        {
+            self.mini_overlay.toggle();
+            ctx.request_repaint();
+        }
+        self.alerts.sync(&mut self.metrics);
+        self.publish_metrics_events();
+        self.dispatch_events();
+        self.auto_pause();
+        self.expire_dead_matches();
+    }
+
+    fn show_process_list(&mut self, ui: &mut egui::Ui) {
+        ui.heading(tr("Monitored Processes"));
+        ui.add_space(4.0);
+
+        // Process selector
+        if let Some(proc) = self
+            .process_selector
+            .show(ui, &self.metrics, &self.settings)
+        {
+            self.add_monitored_proc(proc);
+        };
+
+        self.bulk.retain(&self.monitored_processes);
+        match self
+            .bulk
+            .show_actions(ui, &mut self.metrics, &self.monitored_processes)
+        {
+            Some(BulkAction::Remove(identifiers)) => {
+                for identifier in &identifiers {
+                    self.remove_monitored_proc(identifier);
+                }
+            }
+            Some(BulkAction::Group(group)) => self.add_monitored_proc(group),
+            None => {}
+        }
+
+        // Process list with remove buttons, pinned entries first
+        let mut to_remove = None;
+        let mut entries = self.monitored_processes.clone();
+        entries.sort_by_key(|process| !self.settings.is_pinned(process));
+        for process in &entries {
+            ui.horizontal(|ui| {
+                self.bulk.show_checkbox(ui, process);
+                let is_active = self.active_process.as_ref() == Some(process);
+
+                let response = ui
+                    .selectable_label(is_active, self.settings.label(process))
+                    .on_hover_text(process.to_string());
+                if response.clicked() {
+                    self.active_process = Some(process.clone());
+                }
+                response.context_menu(|ui| self.show_entry_menu(ui, process));
+                let paused = self.metrics.is_paused(process);
+                if paused
+                    && ui
+                        .small_button(tr("⏸ paused — click to resume"))
+                        .on_hover_text(tr("Not sampled since the tab was hidden for too long"))
+                        .clicked()
+                {
+                    self.metrics.resume_process(process);
+                    self.last_viewed.insert(process.clone(), Instant::now());
+                }
+                let cpu_count = self.metrics.monitor.cpu_count();
+                let (finished, inactive_since, leak, cpu) = self
+                    .metrics
+                    .get_process_data(process)
+                    .map_or((false, None, None, Vec::new()), |data| {
+                        let history = data
+                            .genereal
+                            .history
+                            .get_cpu_history(&GENERAL_STATS_PID)
+                            .unwrap_or_default();
+                        let cpu = history[history.len().saturating_sub(SPARKLINE_SAMPLES)..]
+                            .iter()
+                            .map(|&cpu| self.settings.cpu_scale.apply(cpu, cpu_count))
+                            .collect();
+                        (
+                            data.finished.is_some(),
+                            data.inactive_since,
+                            data.genereal.stats.leak,
+                            cpu,
+                        )
                     });
+                if finished {
+                    ui.weak("⏹")
+                        .on_hover_text(tr("Process exited, its data is frozen"));
+                }
+                if let Some(since) = inactive_since {
+                    ui.weak("💤").on_hover_text(format!(
+                        "{} ({})",
+                        tr("No matching process, showing the last-known data"),
+                        format_duration(since.elapsed())
+                    ));
+                }
+                if let Some(leak) = leak {
+                    ui.colored_label(ui.visuals().warn_fg_color, "💧")
+                        .on_hover_text(leak_summary(&leak));
                 }
+                if let Some(severity) = self.alerts.firing_severity(process) {
+                    ui.colored_label(self.alerts.colors.get(severity), "⚠")
+                        .on_hover_text(tr_format("{} alert firing", &[&tr(severity.label())]));
+                }
+
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if self.settings.is_pinned(process) {
+                        if ui
+                            .small_button("📌")
+                            .on_hover_text(tr("Pinned, click to unpin"))
+                            .clicked()
+                        {
+                            self.settings.toggle_pin(process);
+                        }
+                    } else if ui.small_button("❌").clicked() {
+                        to_remove = Some(process.clone());
+                    }
+                    if let Some(&current) = cpu.last() {
+                        ui.monospace(format!("{:>6}", i18n::percent(current, 1)));
+                        // Scaled to the window's peak, with a floor so an idle process stays flat
+                        let peak = cpu.iter().copied().fold(5.0, f32::max);
+                        sparkline(ui, &cpu, peak, self.settings.series_colors.cpu, 60.0)
+                            .on_hover_text(tr_format(
+                                "CPU over the last {} samples, peak {} ({})",
+                                &[
+                                    &cpu.len(),
+                                    &i18n::percent(peak, 1),
+                                    &tr(self.settings.cpu_scale.label()),
+                                ],
+                            ));
+                    }
+                });
+            });
+        }
+
+        if let Some(process) = to_remove {
+            self.remove_monitored_proc(&process);
+        }
+    }
+
+    /// Right-click menu of a side panel entry: alias and pin
+    fn show_entry_menu(&mut self, ui: &mut egui::Ui, process: &ProcessIdentifier) {
+        let mut alias = self
+            .settings
+            .aliases
+            .get(process)
+            .cloned()
+            .unwrap_or_default();
+        ui.horizontal(|ui| {
+            ui.label(tr("Alias:"));
+            let edit =
+                ui.add(egui::TextEdit::singleline(&mut alias).hint_text(process.to_string()));
+            if edit.changed() {
+                self.settings.set_alias(process, alias);
+            }
+            if edit.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter)) {
+                ui.close_menu();
+            }
+        });
+        let pin = if self.settings.is_pinned(process) {
+            tr("Unpin")
+        } else {
+            tr("📌 Pin")
+        };
+        if ui.button(pin).clicked() {
+            self.settings.toggle_pin(process);
+            ui.close_menu();
+        }
+        if self.layout.is_detached(process) {
+            if ui.button(tr("⮪ Attach")).clicked() {
+                self.layout_request = Some(LayoutRequest::Attach(process.clone()));
+                ui.close_menu();
+            }
+        } else if ui
+            .button(tr("⧉ Pop out"))
+            .on_hover_text(tr("Show in its own window"))
+            .clicked()
+        {
+            self.layout_request = Some(LayoutRequest::Detach(process.clone()));
+            ui.close_menu();
+        }
+    }
+
+    /// Process views popped out into their own OS windows. Closing a window
+    /// brings its view back as a tab.
+    fn show_detached(&mut self, ctx: &egui::Context) {
+        for view in self.layout.detached.clone() {
+            let identifier = &view.identifier;
+            let viewport_id = egui::ViewportId::from_hash_of(("detached", identifier));
+            // Picking a detached entry in the side panel raises its window
+            if self.active_process.as_ref() == Some(identifier)
+                && self.active_process != self.synced_active_process
+            {
+                ctx.send_viewport_cmd_to(viewport_id, egui::ViewportCommand::Focus);
+            }
+            let title = format!("tvis - {}", self.settings.label(identifier));
+            let level = if view.always_on_top {
+                egui::WindowLevel::AlwaysOnTop
+            } else {
+                egui::WindowLevel::Normal
+            };
+            let builder = egui::ViewportBuilder::default()
+                .with_title(&title)
+                .with_inner_size([480.0, 360.0])
+                .with_window_level(level);
+            let (always_on_top, attach) =
+                ctx.show_viewport_immediate(viewport_id, builder, |ctx, class| {
+                    let mut always_on_top = view.always_on_top;
+                    let mut attach = false;
+                    let mut contents = |ui: &mut egui::Ui| {
+                        ui.horizontal(|ui| {
+                            ui.checkbox(&mut always_on_top, tr("📌 Always on top"));
+                            attach = ui
+                                .button(tr("⮪ Attach"))
+                                .on_hover_text(tr("Move back into a tab of the main window"))
+                                .clicked();
+                        });
+                        ui.separator();
+                        egui::ScrollArea::vertical()
+                            .id_salt(("detached_scroll_area", identifier))
+                            .show(ui, |ui| self.show_process_tab(ui, identifier));
+                    };
+                    if class == egui::ViewportClass::Embedded {
+                        // Without native windows (web), the view floats in the main one
+                        let mut open = true;
+                        egui::Window::new(&title)
+                            .id(egui::Id::new(viewport_id))
+                            .open(&mut open)
+                            .show(ctx, &mut contents);
+                        (always_on_top, attach || !open)
+                    } else {
+                        egui::CentralPanel::default().show(ctx, contents);
+                        let closed = ctx.input(|input| input.viewport().close_requested());
+                        (always_on_top, attach || closed)
+                    }
+                });
+            if attach {
+                self.layout.attach(identifier);
+                self.active_process = Some(identifier.clone());
+            } else if let Some(detached) = self
+                .layout
+                .detached
+                .iter_mut()
+                .find(|detached| &detached.identifier == identifier)
+            {
+                detached.always_on_top = always_on_top;
+            }
+        }
+    }
 
-                if let Some((idx, process)) = to_remove {
-                    self.monitored_processes.remove(idx);
-                    let mut metrics = self.metrics.write().unwrap();
-                    metrics.remove_selected_process(&process);
+    fn show_process_tab(&mut self, ui: &mut egui::Ui, identifier: &ProcessIdentifier) {
+        self.last_viewed.insert(identifier.clone(), Instant::now());
+        let process_data = self.metrics.get_process_data(identifier).cloned();
+        let availability = self.metrics.availability(identifier).cloned();
+        let tick = self.metrics.tick;
+        let swap = self.metrics.swap;
+        let thermals = self
+            .settings
+            .show_cpu_thermals
+            .then(|| self.metrics.cpu_thermals.clone());
+        let paused = self.metrics.is_paused(identifier);
+        let cpu_count = self.metrics.monitor.cpu_count();
+        let limit = self.metrics.limit(identifier).copied();
+        let interval = self.metrics.interval_of(identifier);
+        let bookmarks = self.bookmarks.markers(&self.metrics, identifier);
+        if paused {
+            ui.horizontal(|ui| {
+                ui.colored_label(ui.visuals().warn_fg_color, tr("⏸ Sampling paused"));
+                if ui.button(tr("Resume")).clicked() {
+                    self.metrics.resume_process(identifier);
                 }
             });
+        }
+        self.limits
+            .show_button(ui, identifier, limit, &self.settings);
+        show_interval_override(ui, identifier, &mut self.settings, &mut self.metrics);
+        show_guide_lines(ui, identifier, &mut self.settings);
+        if ui
+            .button(tr("🔖 Bookmark"))
+            .on_hover_text(tr(
+                "Mark this moment on the plots of this process and in its exports (B)",
+            ))
+            .clicked()
+            && !self.bookmarks.is_pending()
+        {
+            self.bookmarks
+                .begin(&self.metrics, Some(identifier.clone()));
+        }
+        if let Some(availability) = &availability {
+            availability_strip(ui, availability);
+        }
+        self.slos.show_gauges(ui, identifier, &self.settings);
+        if let Some(process_data) = &process_data {
+            self.benchmark.show_record_buttons(
+                ui,
+                identifier,
+                process_data,
+                interval,
+                &self.settings,
+            );
+        }
+        let inactive_since = process_data.as_ref().and_then(|data| data.inactive_since);
+        if let Some(since) = inactive_since {
+            ui.weak(format!(
+                "💤 {} ({})",
+                tr("No matching process, showing the last-known data"),
+                format_duration(since.elapsed())
+            ));
+            if let Some(err) = self.archive_errors.get(identifier) {
+                ui.colored_label(ui.visuals().error_fg_color, err);
+            }
+        }
+        if let Some(process_data) = process_data {
+            let mut markers = bookmarks;
+            markers.extend(self.process_view.event_markers(&process_data, tick));
+            markers.extend(self.alerts.markers(identifier, tick));
+            let mut guides: Vec<PlotGuide> = self
+                .settings
+                .guide_lines
+                .get(identifier)
+                .into_iter()
+                .flatten()
+                .map(|guide| PlotGuide {
+                    metric: guide.metric,
+                    value: guide.value,
+                    label: tr("Guide").to_string(),
+                    color: None,
+                })
+                .collect();
+            guides.extend(self.alerts.guides(identifier));
+            let overlays = PlotOverlays {
+                markers,
+                limit,
+                guides,
+                tick,
+                swap,
+                gaps: SampleGap::of(&process_data),
+                thermals,
+                interval,
+            };
+            ui.scope(|ui| {
+                if inactive_since.is_some() {
+                    ui.multiply_opacity(0.5);
+                }
+                self.process_view.show_process(
+                    ui,
+                    identifier,
+                    &process_data,
+                    &self.settings,
+                    &overlays,
+                    cpu_count,
+                );
+            });
+            if let Some((pid, signal)) = self.process_view.signal_request.take() {
+                let result = self.metrics.monitor.signal(pid, signal);
+                self.process_view.signal_error = result.err().map(|err| (pid, err));
+            }
+        } else {
+            ui.group(|ui| {
+                ui.heading(self.settings.label(identifier));
+                ui.label(tr("Process not found"));
+            });
+        }
+    }
 
-        egui::CentralPanel::default().show(ctx, |ui| {
-            ui.heading("Process Monitor");
-
-            // Display process information
-            if let Some(identifier) = &self.active_process {
-                let monitored_processes = {
-                    self.metrics
-                        .read()
-                        .unwrap()
-                        .get_process_data(identifier)
-                        .cloned()
-                };
-                if let Some(process_data) = monitored_processes {
-                    &self
-                        .process_view
-                        .show_process(ui, &identifier, &process_data, &self.settings);
-                } else {
-                    ui.group(|ui| {
-                        ui.heading(identifier.to_string());
-                        ui.label("Process not found");
+    /// Lists the entries watch rules added to the sampler and forgets the ones
+    /// they removed; everything else changes both lists at once
+    fn sync_watched(&mut self) {
+        let sampled = self.metrics.get_monitored_processes();
+        let added: Vec<ProcessIdentifier> = sampled
+            .iter()
+            .filter(|identifier| !self.monitored_processes.contains(identifier))
+            .cloned()
+            .collect();
+        let removed: Vec<ProcessIdentifier> = self
+            .monitored_processes
+            .iter()
+            .filter(|identifier| !sampled.contains(identifier))
+            .cloned()
+            .collect();
+        self.monitored_processes.extend(added);
+        for identifier in &removed {
+            self.remove_monitored_proc(identifier);
+        }
+    }
+
+    /// Keeps the dock tabs and `active_process` pointing at the same process
+    fn sync_layout(&mut self) {
+        let monitored_processes = &self.monitored_processes;
+        self.layout.dock_state.retain_tabs(|tab| match tab {
+            Tab::Process(identifier) => monitored_processes.contains(identifier),
+            _ => true,
+        });
+        self.layout
+            .detached
+            .retain(|view| monitored_processes.contains(&view.identifier));
+
+        if self.active_process != self.synced_active_process {
+            if let Some(identifier) = self
+                .active_process
+                .as_ref()
+                .filter(|identifier| !self.layout.is_detached(identifier))
+            {
+                self.layout.open(Tab::Process(identifier.clone()));
+            }
+        } else if let Some(identifier) = self.layout.focused_process() {
+            self.active_process = Some(identifier);
+        }
+        if self.active_process != self.synced_active_process {
+            self.events
+                .publish(AppEvent::SelectionChanged(self.active_process.clone()));
+        }
+        self.synced_active_process = self.active_process.clone();
+    }
+
+    /// Publishes the sampler ticks and process changes since the last frame
+    fn publish_metrics_events(&mut self) {
+        if self.metrics.tick == self.published_tick {
+            return;
+        }
+        for identifier in &self.monitored_processes {
+            let Some(process_data) = self.metrics.get_process_data(identifier) else {
+                continue;
+            };
+            for event in &process_data.events {
+                if event.tick > self.published_tick {
+                    self.events.publish(AppEvent::Process {
+                        identifier: identifier.clone(),
+                        event: event.clone(),
                     });
                 }
-            } else if !self.monitored_processes.is_empty() {
-                ui.label("Select a process from the list to view details");
             }
+        }
+        self.published_tick = self.metrics.tick;
+        self.events.publish(AppEvent::SampleAdded {
+            tick: self.metrics.tick,
         });
+    }
 
-        if self.settings.update_mode == UpdateMode::Continuous {
-            // Change mode rendering
-            ctx.request_repaint();
+    /// Lets the components react to pending events, then forwards them to the sinks
+    fn dispatch_events(&mut self) {
+        while let Some(event) = self.events.pop() {
+            if let AppEvent::SampleAdded { .. } = event {
+                for alert in self.alerts.update(&self.metrics, self.settings.cpu_scale) {
+                    self.events.publish(AppEvent::AlertFired(alert));
+                }
+                self.slos.update(&self.metrics);
+            }
+            self.events.notify(&event);
+        }
+    }
+
+    /// Pauses sampling of processes whose tab was not viewed for `Settings::auto_pause_minutes`
+    fn auto_pause(&mut self) {
+        let Some(minutes) = self.settings.auto_pause_minutes else {
+            return;
+        };
+        let limit = Duration::from_secs(minutes * 60);
+        let now = Instant::now();
+        for identifier in &self.monitored_processes {
+            let last_viewed = *self.last_viewed.entry(identifier.clone()).or_insert(now);
+            if now - last_viewed < limit {
+                continue;
+            }
+            if !self.metrics.is_paused(identifier) {
+                info!("Auto-pausing {}", identifier.to_string());
+                self.metrics.pause_process(identifier);
+            }
+        }
+    }
+
+    /// Archives or removes name-based entries that had no matching process
+    /// for longer than the dead match policy allows
+    fn expire_dead_matches(&mut self) {
+        let policy = self.settings.dead_matches;
+        if policy.action == DeadMatchAction::Keep {
+            return;
+        }
+        let limit = Duration::from_secs(policy.after_minutes * 60);
+        // A match coming back gets another try at archiving once it dies again
+        self.archive_errors.retain(|identifier, _| {
+            self.metrics
+                .get_process_data(identifier)
+                .is_some_and(|data| data.inactive_since.is_some())
+        });
+        let expired: Vec<ProcessIdentifier> = self
+            .monitored_processes
+            .iter()
+            .filter(|identifier| !self.settings.is_pinned(identifier))
+            .filter(|identifier| !self.archive_errors.contains_key(identifier))
+            .filter(|identifier| {
+                self.metrics
+                    .get_process_data(identifier)
+                    .and_then(|data| data.inactive_since)
+                    .is_some_and(|since| since.elapsed() >= limit)
+            })
+            .cloned()
+            .collect();
+        for identifier in expired {
+            if policy.action == DeadMatchAction::Archive {
+                let seconds = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs();
+                let name: String = identifier
+                    .to_string()
+                    .chars()
+                    .map(|c| if c.is_alphanumeric() { c } else { '_' })
+                    .collect();
+                // Next to the flight recorder's log, so archives don't land in
+                // whatever directory tvis was started from
+                let directory =
+                    std::path::Path::new(self.settings.flight_recorder.directory.trim());
+                let path = directory
+                    .join(format!("tvis_archive_{name}_{seconds}.csv"))
+                    .display()
+                    .to_string();
+                let created = if directory.as_os_str().is_empty() {
+                    Ok(())
+                } else {
+                    std::fs::create_dir_all(directory)
+                        .map_err(|err| format!("Failed to create {}: {err}", directory.display()))
+                };
+                let saved = created.and_then(|()| {
+                    save_csv(&self.metrics, std::slice::from_ref(&identifier), &path)
+                });
+                match saved {
+                    Ok(rows) => info!(
+                        "Archived {} samples of {} to {path}",
+                        rows,
+                        identifier.to_string()
+                    ),
+                    Err(err) => {
+                        // Kept so the data isn't lost, and not retried every frame
+                        self.archive_errors
+                            .insert(identifier, format!("Archiving to {path} failed: {err}"));
+                        continue;
+                    }
+                }
+            }
+            info!("Removing {}, no process matched it", identifier.to_string());
+            self.remove_monitored_proc(&identifier);
+        }
+    }
+
+    pub fn remove_monitored_proc(&mut self, proc: &ProcessIdentifier) {
+        if self.active_process.as_ref() == Some(proc) {
+            self.active_process = None;
+        }
+        self.monitored_processes
+            .retain(|monitored| monitored != proc);
+        self.settings.pinned.retain(|pinned| pinned != proc);
+        self.archive_errors.remove(proc);
+        self.metrics.remove_selected_process(proc);
+    }
+
+    /// Switches to the watch list of `profile`, keeping pinned entries, and takes
+    /// over its sampling intervals, alert rules and theme
+    fn apply_profile(&mut self, profile: &Profile) {
+        let identifiers = profile.identifiers();
+        for identifier in self.monitored_processes.clone() {
+            if !identifiers.contains(&identifier) && !self.settings.is_pinned(&identifier) {
+                self.remove_monitored_proc(&identifier);
+            }
+        }
+        for identifier in identifiers {
+            self.add_monitored_proc(identifier);
+        }
+        self.settings.update_interval_ms = profile.update_interval_ms;
+        self.metrics
+            .set_update_interval(profile.update_interval_ms as u64);
+        for identifier in self.settings.interval_overrides.keys() {
+            self.metrics.set_interval_override(identifier, None);
+        }
+        self.settings.interval_overrides.clear();
+        for (identifier, interval_ms) in profile.interval_overrides() {
+            self.metrics.set_interval_override(
+                &identifier,
+                Some(Duration::from_millis(interval_ms as u64)),
+            );
+            self.settings
+                .interval_overrides
+                .insert(identifier, interval_ms);
+        }
+        self.alerts.rules = profile.alerts.iter().map(|alert| alert.to_rule()).collect();
+        self.settings.theme = profile.theme;
+        self.profiles.active = Some(profile.name.clone());
+        self.profiles
+            .set_status(Ok(tr_format("Applied \"{}\"", &[&profile.name])));
+        info!("Applied profile {}", profile.name);
+    }
+
+    /// Overrides the saved interval and history length, e.g. from the command line
+    pub fn set_sampling(
+        &mut self,
+        update_interval_ms: Option<usize>,
+        history_length: Option<usize>,
+    ) {
+        if let Some(update_interval_ms) = update_interval_ms {
+            self.settings.update_interval_ms = update_interval_ms;
+            self.metrics.set_update_interval(update_interval_ms as u64);
+        }
+        if let Some(history_length) = history_length {
+            self.settings.history_length = history_length;
+            self.metrics.set_history_len(history_length);
         }
     }
-}
 
-impl ProcessMonitorApp {
     pub fn add_monitored_proc(&mut self, proc: ProcessIdentifier) {
         if !self.monitored_processes.contains(&proc) {
             self.monitored_processes.push(proc.clone());
             self.active_process = Some(proc.clone());
-            self.metrics.write().unwrap().add_selected_process(proc);
+            self.metrics.add_selected_process(proc);
+        }
+    }
+}
+
+struct AppTabs<'a> {
+    app: &'a mut ProcessMonitorApp,
+}
+
+impl egui_dock::TabViewer for AppTabs<'_> {
+    type Tab = Tab;
+
+    fn title(&mut self, tab: &mut Tab) -> egui::WidgetText {
+        match tab {
+            Tab::Processes => tr("Monitored Processes").into(),
+            Tab::Process(identifier) => self.app.settings.label(identifier).into(),
+            Tab::Settings => tr("⚙ Settings").into(),
+        }
+    }
+
+    /// Kept apart from the title, which changes with the language
+    fn id(&mut self, tab: &mut Tab) -> egui::Id {
+        match tab {
+            Tab::Processes => egui::Id::new("processes_tab"),
+            Tab::Process(identifier) => egui::Id::new(("process_tab", &*identifier)),
+            Tab::Settings => egui::Id::new("settings_tab"),
+        }
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, tab: &mut Tab) {
+        match tab {
+            Tab::Processes => self.app.show_process_list(ui),
+            Tab::Process(identifier) => self.app.show_process_tab(ui, identifier),
+            Tab::Settings => show_settings_ui(ui, &mut self.app.settings, &mut self.app.metrics),
+        }
+    }
+
+    fn context_menu(
+        &mut self,
+        ui: &mut egui::Ui,
+        tab: &mut Tab,
+        _surface: egui_dock::SurfaceIndex,
+        _node: egui_dock::NodeIndex,
+    ) {
+        if let Tab::Process(identifier) = tab {
+            if ui.button(tr("⧉ Pop out")).clicked() {
+                self.app.layout_request = Some(LayoutRequest::Detach(identifier.clone()));
+                ui.close_menu();
+            }
+        }
+    }
+
+    fn closeable(&mut self, tab: &mut Tab) -> bool {
+        !matches!(tab, Tab::Processes)
+    }
+
+    fn on_close(&mut self, tab: &mut Tab) -> bool {
+        if let Tab::Process(identifier) = tab {
+            if self.app.active_process.as_ref() == Some(identifier) {
+                self.app.active_process = None;
+                self.app.synced_active_process = None;
+            }
         }
+        true
     }
 }
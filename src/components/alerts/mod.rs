@@ -0,0 +1,8 @@
+mod quiet;
+mod sound;
+mod state;
+mod ui;
+
+pub use crate::metrics::alerts::{AlertKey, AlertRule, QuietHours, Severity};
+pub use quiet::QuietSettings;
+pub use state::{Alerts, FiredAlert, SeverityColors, SoundAlert};
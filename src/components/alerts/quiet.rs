@@ -0,0 +1,163 @@
+use crate::metrics::alerts::QuietHours;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+
+/// How long the OS do-not-disturb state is trusted before asking again
+#[cfg(not(target_arch = "wasm32"))]
+const DND_CACHE: Duration = Duration::from_secs(60);
+
+/// When the sound and the alert commands hold back. Alerts still land in the
+/// alert log, marked as held, so nothing fired overnight is lost.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct QuietSettings {
+    pub sound: QuietHours,
+    /// Applied by the worker, which runs the commands
+    pub command: QuietHours,
+    /// Keep the sound silent while the OS is in do-not-disturb
+    pub respect_dnd: bool,
+    #[serde(skip)]
+    dnd: Option<Arc<DndWatcher>>,
+}
+
+/// Thread asking the OS for its do-not-disturb state every [`DND_CACHE`], as
+/// the settings tools take too long to run when an alert fires. Stops once the
+/// last handle is dropped.
+#[derive(Debug)]
+struct DndWatcher {
+    running: Arc<AtomicBool>,
+    /// Latest state, off until the first answer
+    dnd: Arc<AtomicBool>,
+}
+
+impl DndWatcher {
+    fn spawn() -> Self {
+        let running = Arc::new(AtomicBool::new(true));
+        let dnd = Arc::new(AtomicBool::new(false));
+        // Without threads on the web, the answer is known right away
+        #[cfg(target_arch = "wasm32")]
+        dnd.store(do_not_disturb(), Ordering::Relaxed);
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let (running, dnd) = (Arc::clone(&running), Arc::clone(&dnd));
+            std::thread::spawn(move || {
+                while running.load(Ordering::Relaxed) {
+                    dnd.store(do_not_disturb(), Ordering::Relaxed);
+                    std::thread::sleep(DND_CACHE);
+                }
+            });
+        }
+        Self { running, dnd }
+    }
+}
+
+impl Drop for DndWatcher {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
+
+impl QuietSettings {
+    pub fn sound_silenced(&mut self) -> bool {
+        self.sound.contains_now() || (self.respect_dnd && self.do_not_disturb())
+    }
+
+    /// OS do-not-disturb state as last seen by the watcher, started on first use
+    fn do_not_disturb(&mut self) -> bool {
+        self.dnd
+            .get_or_insert_with(|| Arc::new(DndWatcher::spawn()))
+            .dnd
+            .load(Ordering::Relaxed)
+    }
+}
+
+/// GNOME's "Do Not Disturb" hides notification banners, Xfce has its own
+/// setting, and KDE Plasma and other notification daemons implementing the
+/// freedesktop `Inhibited` property report it there. Other desktops count as
+/// never in do-not-disturb.
+#[cfg(target_os = "linux")]
+fn do_not_disturb() -> bool {
+    let gnome = || {
+        command_output(
+            "gsettings",
+            &["get", "org.gnome.desktop.notifications", "show-banners"],
+        )
+        .is_some_and(|output| output.trim() == "false")
+    };
+    let xfce = || {
+        command_output(
+            "xfconf-query",
+            &["-c", "xfce4-notifyd", "-p", "/do-not-disturb"],
+        )
+        .is_some_and(|output| output.trim() == "true")
+    };
+    // e.g. "b true"
+    let freedesktop = || {
+        command_output(
+            "busctl",
+            &[
+                "--user",
+                "get-property",
+                "org.freedesktop.Notifications",
+                "/org/freedesktop/Notifications",
+                "org.freedesktop.Notifications",
+                "Inhibited",
+            ],
+        )
+        .is_some_and(|output| output.trim() == "b true")
+    };
+    gnome() || xfce() || freedesktop()
+}
+
+#[cfg(target_os = "macos")]
+fn do_not_disturb() -> bool {
+    command_output(
+        "defaults",
+        &[
+            "-currentHost",
+            "read",
+            "com.apple.notificationcenterui",
+            "doNotDisturb",
+        ],
+    )
+    .is_some_and(|output| output.trim() == "1")
+}
+
+/// Notifications turned off in the Windows settings
+#[cfg(target_os = "windows")]
+fn do_not_disturb() -> bool {
+    command_output(
+        "reg",
+        &[
+            "query",
+            r"HKCU\Software\Microsoft\Windows\CurrentVersion\Notifications\Settings",
+            "/v",
+            "NOC_GLOBAL_SETTING_TOASTS_ENABLED",
+        ],
+    )
+    .is_some_and(|output| output.split_whitespace().last() == Some("0x0"))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn do_not_disturb() -> bool {
+    false
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// `HH:MM` of `minute` after midnight
+pub fn format_minute(minute: u32) -> String {
+    format!("{:02}:{:02}", minute / 60, minute % 60)
+}
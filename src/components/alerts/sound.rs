@@ -0,0 +1,93 @@
+use super::state::SoundAlert;
+
+/// Plays the alert sound on a background thread so the UI never blocks on the player
+#[cfg(not(target_arch = "wasm32"))]
+pub fn play(sound: &SoundAlert) {
+    let sound = sound.clone();
+    std::thread::spawn(move || {
+        for _ in 0..sound.repeat.max(1) {
+            if let Err(err) = play_once(&sound) {
+                log::warn!("Failed to play alert sound: {err}");
+                return;
+            }
+        }
+    });
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn play(_sound: &SoundAlert) {
+    log::warn!("Alert sounds are not supported on the web");
+}
+
+#[cfg(target_os = "linux")]
+fn play_once(sound: &SoundAlert) -> std::io::Result<()> {
+    use std::process::Command;
+    let path = if sound.path.is_empty() {
+        "/usr/share/sounds/freedesktop/stereo/alarm-clock-elapsed.oga"
+    } else {
+        &sound.path
+    };
+    // paplay volume is linear in 0..=65536
+    let volume = (sound.volume.clamp(0.0, 1.0) * 65536.0) as u32;
+    let status = Command::new("paplay")
+        .arg(format!("--volume={volume}"))
+        .arg(path)
+        .status()
+        .or_else(|_| Command::new("aplay").arg("-q").arg(path).status())?;
+    check_status(status)
+}
+
+#[cfg(target_os = "macos")]
+fn play_once(sound: &SoundAlert) -> std::io::Result<()> {
+    let path = if sound.path.is_empty() {
+        "/System/Library/Sounds/Sosumi.aiff"
+    } else {
+        &sound.path
+    };
+    let status = std::process::Command::new("afplay")
+        .arg("-v")
+        .arg(sound.volume.clamp(0.0, 1.0).to_string())
+        .arg(path)
+        .status()?;
+    check_status(status)
+}
+
+#[cfg(target_os = "windows")]
+fn play_once(sound: &SoundAlert) -> std::io::Result<()> {
+    let script = if sound.path.is_empty() {
+        "[System.Media.SystemSounds]::Hand.Play(); Start-Sleep -Milliseconds 500".to_string()
+    } else {
+        format!(
+            "(New-Object Media.SoundPlayer '{}').PlaySync()",
+            sound.path.replace('\'', "''")
+        )
+    };
+    let status = std::process::Command::new("powershell")
+        .args(["-NoProfile", "-Command", &script])
+        .status()?;
+    check_status(status)
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "windows",
+    target_arch = "wasm32"
+)))]
+fn play_once(_sound: &SoundAlert) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "no sound player for this platform",
+    ))
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+fn check_status(status: std::process::ExitStatus) -> std::io::Result<()> {
+    if status.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(format!(
+            "player exited with {status}"
+        )))
+    }
+}
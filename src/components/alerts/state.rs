@@ -0,0 +1,235 @@
+use super::quiet::QuietSettings;
+use super::sound;
+use super::{AlertKey, AlertRule, Severity};
+use crate::components::i18n::{number, percent, tr, tr_format};
+use crate::components::process_view::state::{PlotGuide, PlotMarker};
+use crate::components::settings::CpuScale;
+use crate::metrics::alerts::Alert;
+use crate::metrics::process::{MetricType, ProcessIdentifier};
+use crate::metrics::Metrics;
+use std::time::Instant;
+
+/// Color of each severity on plot markers, badges, the alert log and image exports
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct SeverityColors {
+    pub info: egui::Color32,
+    pub warning: egui::Color32,
+    pub critical: egui::Color32,
+}
+
+impl Default for SeverityColors {
+    fn default() -> Self {
+        Self {
+            info: egui::Color32::from_rgb(0x42, 0xa5, 0xf5),
+            warning: egui::Color32::from_rgb(0xff, 0xb3, 0x00),
+            critical: egui::Color32::from_rgb(0xe5, 0x39, 0x35),
+        }
+    }
+}
+
+impl SeverityColors {
+    pub fn get(&self, severity: Severity) -> egui::Color32 {
+        match severity {
+            Severity::Info => self.info,
+            Severity::Warning => self.warning,
+            Severity::Critical => self.critical,
+        }
+    }
+
+    pub(super) fn get_mut(&mut self, severity: Severity) -> &mut egui::Color32 {
+        match severity {
+            Severity::Info => &mut self.info,
+            Severity::Warning => &mut self.warning,
+            Severity::Critical => &mut self.critical,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FiredAlert {
+    pub identifier: ProcessIdentifier,
+    pub severity: Severity,
+    pub message: String,
+    pub fired_at: Instant,
+    /// Sampling tick the alert fired on
+    pub tick: u64,
+    /// Its sound or command was held back by quiet hours or do-not-disturb,
+    /// until acknowledged in the alert log
+    pub held: bool,
+}
+
+/// Audible notification for critical alerts
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+pub struct SoundAlert {
+    pub enabled: bool,
+    /// Sound file to play, the platform alarm sound when empty
+    pub path: String,
+    pub volume: f32,
+    pub repeat: u32,
+}
+
+impl Default for SoundAlert {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: String::new(),
+            volume: 0.8,
+            repeat: 1,
+        }
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+#[serde(default)]
+pub struct Alerts {
+    pub rules: Vec<AlertRule>,
+    pub sound: SoundAlert,
+    pub colors: SeverityColors,
+    pub quiet: QuietSettings,
+    #[serde(skip)]
+    pub log: Vec<FiredAlert>,
+    #[serde(skip)]
+    pub(super) show_window: bool,
+    #[serde(skip)]
+    last_tick: u64,
+    /// Rules above their threshold on the last tick, as seen by the worker
+    #[serde(skip)]
+    pub(super) firing: Vec<AlertKey>,
+    #[serde(skip)]
+    pub(super) draft: Option<AlertRule>,
+}
+
+impl Alerts {
+    pub fn toggle_window(&mut self) {
+        self.show_window = !self.show_window;
+    }
+
+    /// Hands the rules and the command quiet hours to the worker, which checks
+    /// them after every tick, once they were changed here
+    pub fn sync(&self, metrics: &mut Metrics) {
+        if metrics.controls().alert_rules != self.rules {
+            metrics.set_alert_rules(self.rules.clone());
+        }
+        if metrics.controls().command_quiet_hours != self.quiet.command {
+            metrics.set_command_quiet_hours(self.quiet.command);
+        }
+    }
+
+    /// Picks up the alerts the worker fired since the last call and plays the
+    /// sound for critical ones. Returns them with CPU values in `cpu_scale`.
+    pub fn update(&mut self, metrics: &Metrics, cpu_scale: CpuScale) -> Vec<FiredAlert> {
+        if metrics.tick == self.last_tick {
+            return Vec::new();
+        }
+        let cpu_count = metrics.monitor.cpu_count();
+        let mut fired: Vec<FiredAlert> = metrics
+            .alerts
+            .iter()
+            .filter(|alert| alert.tick > self.last_tick)
+            .map(|alert| FiredAlert {
+                identifier: alert.identifier.clone(),
+                severity: alert.severity,
+                message: message(alert, cpu_scale, cpu_count),
+                fired_at: alert.fired_at,
+                tick: alert.tick,
+                held: alert.held,
+            })
+            .collect();
+        self.last_tick = metrics.tick;
+        self.firing = metrics.firing.clone();
+
+        if self.sound.enabled && fired.iter().any(|a| a.severity == Severity::Critical) {
+            if self.quiet.sound_silenced() {
+                for alert in &mut fired {
+                    alert.held |= alert.severity == Severity::Critical;
+                }
+            } else {
+                sound::play(&self.sound);
+            }
+        }
+        self.log.extend(fired.iter().cloned());
+        fired
+    }
+
+    /// Markers at the ticks alerts for `identifier` fired, in their severity color
+    pub fn markers(&self, identifier: &ProcessIdentifier, current_tick: u64) -> Vec<PlotMarker> {
+        self.log
+            .iter()
+            .filter(|alert| &alert.identifier == identifier)
+            .map(|alert| PlotMarker {
+                samples_ago: current_tick.saturating_sub(alert.tick),
+                label: tr(alert.severity.label()).to_string(),
+                color: Some(self.colors.get(alert.severity)),
+                highlighted: false,
+            })
+            .collect()
+    }
+
+    /// Thresholds of the enabled rules for `identifier`, in their severity color
+    pub fn guides(&self, identifier: &ProcessIdentifier) -> Vec<PlotGuide> {
+        self.rules
+            .iter()
+            .filter(|rule| rule.enabled && &rule.identifier == identifier)
+            .map(|rule| PlotGuide {
+                metric: rule.metric,
+                value: rule.threshold,
+                label: tr_format("{} alert", &[&tr(rule.severity.label())]),
+                color: Some(self.colors.get(rule.severity)),
+            })
+            .collect()
+    }
+
+    /// Alerts held back by quiet hours that were not acknowledged yet
+    pub fn held_count(&self) -> usize {
+        self.log.iter().filter(|alert| alert.held).count()
+    }
+
+    pub(super) fn acknowledge_held(&mut self) {
+        for alert in &mut self.log {
+            alert.held = false;
+        }
+    }
+
+    /// Highest severity currently firing for `identifier`
+    pub fn firing_severity(&self, identifier: &ProcessIdentifier) -> Option<Severity> {
+        self.rules
+            .iter()
+            .filter(|rule| rule.enabled && self.firing.contains(&rule.key()))
+            .filter(|rule| &rule.identifier == identifier)
+            .map(|rule| rule.severity)
+            .max()
+    }
+}
+
+/// Log line of `alert`, with CPU values in `cpu_scale`
+fn message(alert: &Alert, cpu_scale: CpuScale, cpu_count: usize) -> String {
+    let mut message = match alert.metric {
+        MetricType::Cpu => {
+            let cpu = |value: f64| percent(cpu_scale.apply(value as f32, cpu_count), 1);
+            tr_format(
+                "CPU {} > {} ({})",
+                &[
+                    &cpu(alert.value),
+                    &cpu(alert.threshold),
+                    &tr(cpu_scale.label()),
+                ],
+            )
+        }
+        MetricType::Memory => {
+            let megabytes = |value: f64| number(value / (1024.0 * 1024.0), 1);
+            tr_format(
+                "Memory {} MB > {} MB",
+                &[&megabytes(alert.value), &megabytes(alert.threshold)],
+            )
+        }
+        MetricType::Gpu => tr_format(
+            "GPU {} > {}",
+            &[&percent(alert.value, 1), &percent(alert.threshold, 1)],
+        ),
+        MetricType::Processes => tr_format("Processes {} > {}", &[&alert.value, &alert.threshold]),
+    };
+    if let Some(swap) = alert.swap_percent {
+        message = tr_format("{} while swap is {} used", &[&message, &percent(swap, 0)]);
+    }
+    message
+}
@@ -0,0 +1,358 @@
+use super::quiet::format_minute;
+use super::sound;
+use super::state::{Alerts, SeverityColors};
+use super::{AlertRule, QuietHours, Severity};
+use crate::components::i18n::{number, percent, tr, tr_format};
+use crate::components::settings::Settings;
+use crate::metrics::process::{MetricType, ProcessIdentifier};
+
+impl Alerts {
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        monitored_processes: &[ProcessIdentifier],
+        settings: &Settings,
+        cpu_count: usize,
+    ) {
+        if !self.show_window {
+            return;
+        }
+
+        let mut open = true;
+        egui::Window::new(tr("🔔 Alerts"))
+            .open(&mut open)
+            .resizable(true)
+            .show(ctx, |ui| {
+                self.show_rules(ui, monitored_processes, settings, cpu_count);
+                ui.separator();
+                self.show_sound(ui);
+                ui.separator();
+                if !cfg!(target_arch = "wasm32") {
+                    self.show_quiet(ui);
+                    ui.separator();
+                }
+                self.show_colors(ui);
+                ui.separator();
+                self.show_log(ui, settings);
+            });
+        self.show_window = open;
+    }
+
+    fn show_rules(
+        &mut self,
+        ui: &mut egui::Ui,
+        monitored_processes: &[ProcessIdentifier],
+        settings: &Settings,
+        cpu_count: usize,
+    ) {
+        ui.heading(tr("Rules"));
+        let unit = settings.memory_unit;
+        let bytes_per_unit = 1.0 / unit.format_value(1.0).0 as f64;
+
+        let mut to_remove = None;
+        for (i, rule) in self.rules.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut rule.enabled, "");
+                let text = match rule.metric {
+                    MetricType::Cpu => tr_format(
+                        "{} CPU > {} ({})",
+                        &[
+                            &settings.label(&rule.identifier),
+                            &percent(
+                                settings.cpu_scale.apply(rule.threshold as f32, cpu_count),
+                                1,
+                            ),
+                            &tr(settings.cpu_scale.label()),
+                        ],
+                    ),
+                    MetricType::Memory => tr_format(
+                        "{} Memory > {} {}",
+                        &[
+                            &settings.label(&rule.identifier),
+                            &number(rule.threshold / bytes_per_unit, 1),
+                            &unit.format_value(0.0).1,
+                        ],
+                    ),
+                    MetricType::Gpu => tr_format(
+                        "{} GPU > {}",
+                        &[
+                            &settings.label(&rule.identifier),
+                            &percent(rule.threshold, 1),
+                        ],
+                    ),
+                    MetricType::Processes => tr_format(
+                        "{} Processes > {}",
+                        &[&settings.label(&rule.identifier), &rule.threshold],
+                    ),
+                };
+                let text = match rule.min_swap_percent {
+                    Some(min) if min > 0.0 => {
+                        tr_format("{} while swap is above {}", &[&text, &percent(min, 0)])
+                    }
+                    Some(_) => tr_format("{} while swapping", &[&text]),
+                    None => text,
+                };
+                let color = self.colors.get(rule.severity);
+                if self.firing.contains(&rule.key()) {
+                    ui.colored_label(color, text);
+                } else {
+                    ui.label(text);
+                }
+                ui.colored_label(color, tr(rule.severity.label()));
+                if !rule.command.trim().is_empty() {
+                    ui.weak("▶")
+                        .on_hover_text(format!("{} {}", tr("Runs:"), rule.command));
+                }
+                if ui.small_button("❌").clicked() {
+                    to_remove = Some(i);
+                }
+            });
+        }
+        if let Some(i) = to_remove {
+            self.rules.remove(i);
+        }
+
+        let Some(draft) = &mut self.draft else {
+            if ui.button(tr("Add Rule")).clicked() {
+                if let Some(identifier) = monitored_processes.first() {
+                    self.draft = Some(AlertRule::new(identifier.clone(), MetricType::Cpu, 80.0));
+                }
+            }
+            return;
+        };
+
+        ui.group(|ui| {
+            egui::ComboBox::from_label(tr("Process"))
+                .selected_text(settings.label(&draft.identifier))
+                .show_ui(ui, |ui| {
+                    for identifier in monitored_processes {
+                        ui.selectable_value(
+                            &mut draft.identifier,
+                            identifier.clone(),
+                            settings.label(identifier),
+                        );
+                    }
+                });
+            ui.horizontal(|ui| {
+                ui.label(tr("Metric:"));
+                ui.selectable_value(&mut draft.metric, MetricType::Cpu, tr("CPU"));
+                ui.selectable_value(&mut draft.metric, MetricType::Memory, tr("Memory"));
+                ui.selectable_value(&mut draft.metric, MetricType::Gpu, tr("GPU"));
+                ui.selectable_value(&mut draft.metric, MetricType::Processes, tr("Processes"));
+            });
+            ui.horizontal(|ui| {
+                ui.label(tr("Above:"));
+                match draft.metric {
+                    // Entered in the configured CPU scale, stored per core like `current_cpu`
+                    MetricType::Cpu => {
+                        let scale = settings.cpu_scale;
+                        let mut value = scale.apply(draft.threshold as f32, cpu_count);
+                        if ui
+                            .add(egui::DragValue::new(&mut value).suffix("%"))
+                            .on_hover_text(tr(scale.label()))
+                            .changed()
+                        {
+                            draft.threshold = scale.to_per_core(value, cpu_count) as f64;
+                        }
+                    }
+                    MetricType::Gpu => {
+                        ui.add(egui::DragValue::new(&mut draft.threshold).suffix("%"));
+                    }
+                    MetricType::Processes => {
+                        ui.add(egui::DragValue::new(&mut draft.threshold).speed(1.0));
+                    }
+                    MetricType::Memory => {
+                        let mut value = draft.threshold / bytes_per_unit;
+                        let suffix = format!(" {}", unit.format_value(0.0).1);
+                        if ui
+                            .add(egui::DragValue::new(&mut value).suffix(suffix))
+                            .changed()
+                        {
+                            draft.threshold = value * bytes_per_unit;
+                        }
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                let mut swapping = draft.min_swap_percent.is_some();
+                ui.checkbox(&mut swapping, tr("Only while swap use is above"))
+                    .on_hover_text(tr(
+                        "Alert when the machine is pushed into swap rather than on size alone; \
+                         at 0% any swap in use counts",
+                    ));
+                let mut min = draft.min_swap_percent.unwrap_or(0.0);
+                ui.add_enabled(
+                    swapping,
+                    egui::DragValue::new(&mut min).range(0.0..=99.0).suffix("%"),
+                );
+                draft.min_swap_percent = swapping.then_some(min);
+            });
+            ui.horizontal(|ui| {
+                ui.label(tr("Severity:"));
+                for severity in Severity::ALL {
+                    ui.selectable_value(&mut draft.severity, severity, tr(severity.label()));
+                }
+            });
+            if !cfg!(target_arch = "wasm32") {
+                ui.horizontal(|ui| {
+                    ui.label(tr("Run:"));
+                    ui.add(
+                        egui::TextEdit::singleline(&mut draft.command)
+                            .hint_text("jstack $TVIS_PID > dump.txt"),
+                    )
+                    .on_hover_text(tr(
+                        "Shell command run when the rule fires. Environment: TVIS_IDENTIFIER, \
+                         TVIS_SEVERITY, TVIS_MESSAGE, TVIS_PID (heaviest process), TVIS_PIDS, \
+                         TVIS_CPU (%), TVIS_MEMORY (bytes), TVIS_GPU (%), \
+                         TVIS_SWAP_USED (bytes)",
+                    ));
+                });
+            }
+        });
+        ui.horizontal(|ui| {
+            if ui.button(tr("Save")).clicked() {
+                self.rules.extend(self.draft.take());
+            }
+            if ui.button(tr("Cancel")).clicked() {
+                self.draft = None;
+            }
+        });
+    }
+
+    fn show_sound(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(
+            &mut self.sound.enabled,
+            tr("Play a sound when a critical alert fires"),
+        );
+        ui.add_enabled_ui(self.sound.enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(tr("Sound file:"));
+                ui.text_edit_singleline(&mut self.sound.path)
+                    .on_hover_text(tr("Leave empty for the system alarm sound"));
+            });
+            ui.horizontal(|ui| {
+                ui.label(tr("Volume:"));
+                ui.add(egui::Slider::new(&mut self.sound.volume, 0.0..=1.0));
+            });
+            ui.horizontal(|ui| {
+                ui.label(tr("Repeat:"));
+                ui.add(
+                    egui::Slider::new(&mut self.sound.repeat, 1..=10)
+                        .suffix(format!(" {}", tr("times"))),
+                );
+            });
+            if ui.button(tr("Test")).clicked() {
+                sound::play(&self.sound);
+            }
+        });
+    }
+
+    fn show_quiet(&mut self, ui: &mut egui::Ui) {
+        ui.label(tr("Quiet hours:")).on_hover_text(tr(
+            "Alerts still land in the log below, marked 🌙 until acknowledged",
+        ));
+        for (label, hours) in [
+            (tr("Sound"), &mut self.quiet.sound),
+            (tr("Commands"), &mut self.quiet.command),
+        ] {
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut hours.enabled, label);
+                ui.add_enabled_ui(hours.enabled, |ui| show_window(ui, hours));
+            });
+        }
+        ui.checkbox(
+            &mut self.quiet.respect_dnd,
+            tr("Keep the sound silent in do-not-disturb"),
+        )
+        .on_hover_text(tr(
+            "Notification settings of GNOME, KDE Plasma, Xfce, macOS and Windows",
+        ));
+    }
+
+    fn show_colors(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label(tr("Colors:"));
+            for severity in Severity::ALL {
+                ui.label(tr(severity.label()));
+                egui::color_picker::color_edit_button_srgba(
+                    ui,
+                    self.colors.get_mut(severity),
+                    egui::color_picker::Alpha::Opaque,
+                );
+            }
+            if ui
+                .small_button("↺")
+                .on_hover_text(tr("Restore the default colors"))
+                .clicked()
+            {
+                self.colors = SeverityColors::default();
+            }
+        })
+        .response
+        .on_hover_text(tr(
+            "Used for plot markers, sidebar badges, the alert log and saved images",
+        ));
+    }
+
+    fn show_log(&mut self, ui: &mut egui::Ui, settings: &Settings) {
+        ui.horizontal(|ui| {
+            ui.heading(tr("Fired"));
+            if ui.small_button(tr("Clear")).clicked() {
+                self.log.clear();
+            }
+            let held = self.held_count();
+            if held > 0 {
+                ui.label(tr_format("🌙 {} held", &[&held]));
+                if ui.small_button(tr("Acknowledge")).clicked() {
+                    self.acknowledge_held();
+                }
+            }
+        });
+        egui::ScrollArea::vertical()
+            .max_height(200.0)
+            .show(ui, |ui| {
+                for alert in self.log.iter().rev() {
+                    let ago = alert.fired_at.elapsed().as_secs();
+                    ui.horizontal(|ui| {
+                        if alert.held {
+                            ui.label("🌙")
+                                .on_hover_text(tr("Held back by quiet hours or do-not-disturb"));
+                        }
+                        ui.colored_label(
+                            self.colors.get(alert.severity),
+                            format!("[{}]", tr(alert.severity.label())),
+                        );
+                        ui.label(tr_format(
+                            "{}: {} ({}m {}s ago)",
+                            &[
+                                &settings.label(&alert.identifier),
+                                &alert.message,
+                                &(ago / 60),
+                                &(ago % 60),
+                            ],
+                        ));
+                    });
+                }
+            });
+    }
+}
+
+/// Start and end of a quiet window as `HH:MM` fields
+fn show_window(ui: &mut egui::Ui, hours: &mut QuietHours) {
+    ui.label(tr("from"));
+    ui.add(minute_field(&mut hours.start));
+    ui.label(tr("to"));
+    ui.add(minute_field(&mut hours.end));
+}
+
+fn minute_field(minute: &mut u32) -> egui::DragValue<'_> {
+    egui::DragValue::new(minute)
+        .range(0..=24 * 60 - 1)
+        .speed(5.0)
+        .custom_formatter(|minute, _| format_minute(minute as u32))
+        .custom_parser(|text| {
+            let (hours, minutes) = text.trim().split_once(':')?;
+            let minute = hours.parse::<u32>().ok()? * 60 + minutes.parse::<u32>().ok()?;
+            Some(minute as f64)
+        })
+}
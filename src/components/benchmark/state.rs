@@ -0,0 +1,107 @@
+use crate::metrics::process::ProcessData;
+use crate::metrics::GENERAL_STATS_PID;
+use std::time::Duration;
+
+/// Aggregate CPU and memory of one run, from its first sample with a live
+/// process to its last, so two runs line up at their start
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+pub struct RecordedRun {
+    /// Label of the identifier the run was recorded from
+    pub label: String,
+    /// Seconds between two samples
+    pub interval: f64,
+    pub cpu: Vec<f32>,
+    pub memory: Vec<usize>,
+}
+
+/// Figures compared between two runs
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RunStats {
+    pub peak_cpu: f32,
+    pub avg_cpu: f32,
+    pub peak_memory: usize,
+    pub avg_memory: usize,
+    pub duration: Duration,
+}
+
+impl RecordedRun {
+    /// Takes the aggregate history of `data`, None before any process was seen
+    pub fn capture(label: String, data: &ProcessData, interval: Duration) -> Option<Self> {
+        let history = &data.genereal.history;
+        let cpu = history
+            .get_cpu_history(&GENERAL_STATS_PID)
+            .unwrap_or_default();
+        let memory = history
+            .get_memory_history(&GENERAL_STATS_PID)
+            .unwrap_or_default();
+        let len = cpu.len().min(memory.len());
+        // Samples without a live process are zero on both
+        let alive = |i: &usize| cpu[*i] > 0.0 || memory[*i] > 0;
+        let start = (0..len).find(alive)?;
+        let end = (0..len).rfind(alive)? + 1;
+        Some(Self {
+            label,
+            interval: interval.as_secs_f64(),
+            cpu: cpu[start..end].to_vec(),
+            memory: memory[start..end].to_vec(),
+        })
+    }
+
+    pub fn stats(&self) -> RunStats {
+        let samples = self.cpu.len().max(1);
+        RunStats {
+            peak_cpu: self.cpu.iter().copied().fold(0.0, f32::max),
+            avg_cpu: self.cpu.iter().sum::<f32>() / samples as f32,
+            peak_memory: self.memory.iter().copied().max().unwrap_or_default(),
+            avg_memory: self.memory.iter().sum::<usize>() / samples,
+            duration: Duration::from_secs_f64(self.cpu.len() as f64 * self.interval),
+        }
+    }
+}
+
+/// Which side of the comparison a run is recorded into
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Slot {
+    Baseline,
+    Candidate,
+}
+
+impl Slot {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Slot::Baseline => "A",
+            Slot::Candidate => "B",
+        }
+    }
+}
+
+/// Before/after comparison of two recorded runs. Both are persisted, so a
+/// baseline recorded before a rebuild is still there to compare against.
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+#[serde(default)]
+pub struct Benchmark {
+    pub baseline: Option<RecordedRun>,
+    pub candidate: Option<RecordedRun>,
+    #[serde(skip)]
+    pub show_window: bool,
+}
+
+impl Benchmark {
+    pub fn toggle_window(&mut self) {
+        self.show_window = !self.show_window;
+    }
+
+    pub fn run(&self, slot: Slot) -> Option<&RecordedRun> {
+        match slot {
+            Slot::Baseline => self.baseline.as_ref(),
+            Slot::Candidate => self.candidate.as_ref(),
+        }
+    }
+
+    pub fn record(&mut self, slot: Slot, run: Option<RecordedRun>) {
+        match slot {
+            Slot::Baseline => self.baseline = run,
+            Slot::Candidate => self.candidate = run,
+        }
+    }
+}
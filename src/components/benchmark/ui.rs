@@ -0,0 +1,234 @@
+use super::state::{Benchmark, RecordedRun, RunStats, Slot};
+use crate::components::i18n::{tr, tr_format};
+use crate::components::process_view::report::format_duration;
+use crate::components::settings::Settings;
+use crate::metrics::process::{ProcessData, ProcessIdentifier};
+use std::time::Duration;
+
+impl Benchmark {
+    /// "Record as A / B" buttons of `identifier`, shown above its plots
+    pub fn show_record_buttons(
+        &mut self,
+        ui: &mut egui::Ui,
+        identifier: &ProcessIdentifier,
+        data: &ProcessData,
+        interval: Duration,
+        settings: &Settings,
+    ) {
+        ui.horizontal(|ui| {
+            ui.label(tr("⚖ Record run as:"));
+            for slot in [Slot::Baseline, Slot::Candidate] {
+                let hover = match slot {
+                    Slot::Baseline => "Keep this run as the baseline to compare against",
+                    Slot::Candidate => "Keep this run as the one compared to the baseline",
+                };
+                if ui
+                    .small_button(slot.label())
+                    .on_hover_text(tr(hover))
+                    .clicked()
+                {
+                    let run = RecordedRun::capture(settings.label(identifier), data, interval);
+                    self.record(slot, run);
+                }
+            }
+            if self.baseline.is_some()
+                && self.candidate.is_some()
+                && ui.small_button(tr("Compare")).clicked()
+            {
+                self.show_window = true;
+            }
+        });
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, settings: &Settings) {
+        if !self.show_window {
+            return;
+        }
+
+        let mut open = true;
+        egui::Window::new(tr("⚖ Benchmark"))
+            .open(&mut open)
+            .resizable(true)
+            .show(ctx, |ui| {
+                for slot in [Slot::Baseline, Slot::Candidate] {
+                    ui.horizontal(|ui| {
+                        ui.strong(slot.label());
+                        match self.run(slot) {
+                            Some(run) => {
+                                ui.label(tr_format(
+                                    "{}, {} samples over {}",
+                                    &[
+                                        &run.label,
+                                        &run.cpu.len(),
+                                        &format_duration(run.stats().duration),
+                                    ],
+                                ));
+                                if ui.small_button("❌").clicked() {
+                                    self.record(slot, None);
+                                }
+                            }
+                            None => {
+                                ui.weak(tr("Not recorded, use \"Record run as\" in a process tab"));
+                            }
+                        }
+                    });
+                }
+                let (Some(baseline), Some(candidate)) = (&self.baseline, &self.candidate) else {
+                    return;
+                };
+                ui.separator();
+                show_diff(ui, &baseline.stats(), &candidate.stats(), settings);
+                ui.separator();
+                show_overlay(ui, baseline, candidate, settings);
+            });
+        self.show_window = open;
+    }
+}
+
+/// Unit a compared figure is shown in
+#[derive(Clone, Copy)]
+enum Figure {
+    Cpu,
+    Memory,
+    Duration,
+}
+
+impl Figure {
+    fn format(self, value: f64, settings: &Settings) -> String {
+        match self {
+            Figure::Cpu => format!("{value:.1}%"),
+            Figure::Memory => {
+                let (value, unit) = settings.memory_unit.format_value(value as f32);
+                format!("{value:.1} {unit}")
+            }
+            Figure::Duration => format_duration(Duration::from_secs_f64(value)),
+        }
+    }
+}
+
+/// A, B and the change of every figure, colored by whether B got cheaper
+fn show_diff(ui: &mut egui::Ui, baseline: &RunStats, candidate: &RunStats, settings: &Settings) {
+    let rows = [
+        (
+            "Peak CPU",
+            baseline.peak_cpu as f64,
+            candidate.peak_cpu as f64,
+            Figure::Cpu,
+        ),
+        (
+            "Avg CPU",
+            baseline.avg_cpu as f64,
+            candidate.avg_cpu as f64,
+            Figure::Cpu,
+        ),
+        (
+            "Peak memory",
+            baseline.peak_memory as f64,
+            candidate.peak_memory as f64,
+            Figure::Memory,
+        ),
+        (
+            "Avg memory",
+            baseline.avg_memory as f64,
+            candidate.avg_memory as f64,
+            Figure::Memory,
+        ),
+        (
+            "Duration",
+            baseline.duration.as_secs_f64(),
+            candidate.duration.as_secs_f64(),
+            Figure::Duration,
+        ),
+    ];
+    egui::Grid::new("benchmark_diff")
+        .num_columns(5)
+        .striped(true)
+        .show(ui, |ui| {
+            for header in ["", "A", "B", "Δ", "Δ %"] {
+                ui.strong(header);
+            }
+            ui.end_row();
+            for (label, a, b, figure) in rows {
+                let format = |value: f64| figure.format(value, settings);
+                let delta = b - a;
+                let color = if delta < 0.0 {
+                    egui::Color32::from_rgb(0x43, 0xa0, 0x47)
+                } else if delta > 0.0 {
+                    ui.visuals().error_fg_color
+                } else {
+                    ui.visuals().text_color()
+                };
+                let sign = if delta < 0.0 { "-" } else { "+" };
+                ui.label(tr(label));
+                ui.label(format(a));
+                ui.label(format(b));
+                ui.colored_label(color, format!("{sign}{}", format(delta.abs())));
+                if a != 0.0 {
+                    ui.colored_label(color, format!("{:+.1}%", delta / a * 100.0));
+                } else {
+                    ui.weak("–");
+                }
+                ui.end_row();
+            }
+        });
+}
+
+/// CPU and memory of both runs against the time since each run's start
+fn show_overlay(
+    ui: &mut egui::Ui,
+    baseline: &RecordedRun,
+    candidate: &RecordedRun,
+    settings: &Settings,
+) {
+    let unit = settings.memory_unit.format_value(0.0).1;
+    let cpu = |run: &RecordedRun| -> Vec<f64> { run.cpu.iter().map(|&cpu| cpu as f64).collect() };
+    let memory = |run: &RecordedRun| -> Vec<f64> {
+        run.memory
+            .iter()
+            .map(|&bytes| settings.memory_unit.format_value(bytes as f32).0 as f64)
+            .collect()
+    };
+    let plots = [
+        (
+            "CPU (%)".to_string(),
+            settings.series_colors.cpu,
+            cpu(baseline),
+            cpu(candidate),
+        ),
+        (
+            format!("{} ({unit})", tr("Memory")),
+            settings.series_colors.memory,
+            memory(baseline),
+            memory(candidate),
+        ),
+    ];
+    for (y_label, color, a, b) in plots {
+        ui.label(&y_label);
+        let points = |values: &[f64], interval: f64| -> Vec<[f64; 2]> {
+            values
+                .iter()
+                .enumerate()
+                .map(|(i, &value)| [i as f64 * interval, value])
+                .collect()
+        };
+        egui_plot::Plot::new(("benchmark_overlay", &y_label))
+            .height(120.0)
+            .include_y(0.0)
+            .x_axis_label(tr("Seconds since start"))
+            .legend(egui_plot::Legend::default())
+            .show(ui, |plot_ui| {
+                plot_ui.line(
+                    egui_plot::Line::new(points(&a, baseline.interval))
+                        .color(color.gamma_multiply(0.6))
+                        .style(egui_plot::LineStyle::dashed_dense())
+                        .name(format!("A: {}", baseline.label)),
+                );
+                plot_ui.line(
+                    egui_plot::Line::new(points(&b, candidate.interval))
+                        .color(color)
+                        .width(2.0)
+                        .name(format!("B: {}", candidate.label)),
+                );
+            });
+    }
+}
@@ -0,0 +1,63 @@
+use crate::components::process_view::state::PlotMarker;
+use crate::metrics::annotations::Annotation;
+use crate::metrics::process::ProcessIdentifier;
+use crate::metrics::Metrics;
+use std::time::SystemTime;
+
+/// Prompt and jump list of the annotations, which are stored in [`Metrics`]
+/// so the exports can include them
+#[derive(Default)]
+pub struct Bookmarks {
+    /// Bookmark highlighted on the plots after a jump
+    pub selected: Option<usize>,
+    pub show_list: bool,
+    pending: Option<(u64, Option<ProcessIdentifier>)>,
+    pub(super) label: String,
+    pub(super) focus_label: bool,
+}
+
+impl Bookmarks {
+    /// Starts a bookmark at the current tick of `metrics`; the label is asked
+    /// for before it is stored
+    pub fn begin(&mut self, metrics: &Metrics, process: Option<ProcessIdentifier>) {
+        self.pending = Some((metrics.tick, process));
+        self.label = format!("Bookmark {}", metrics.annotations().len() + 1);
+        self.focus_label = true;
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    pub(super) fn commit(&mut self, metrics: &mut Metrics) {
+        if let Some((tick, identifier)) = self.pending.take() {
+            metrics.annotate(Annotation {
+                label: std::mem::take(&mut self.label),
+                tick,
+                identifier,
+                created_at: SystemTime::now(),
+            });
+        }
+    }
+
+    pub(super) fn cancel(&mut self) {
+        self.pending = None;
+        self.label.clear();
+    }
+
+    /// Plot markers for the bookmarks of `identifier` relative to the current tick
+    pub fn markers(&self, metrics: &Metrics, identifier: &ProcessIdentifier) -> Vec<PlotMarker> {
+        metrics
+            .annotations()
+            .iter()
+            .enumerate()
+            .filter(|(_, annotation)| annotation.applies_to(identifier))
+            .map(|(i, annotation)| PlotMarker {
+                samples_ago: metrics.tick.saturating_sub(annotation.tick),
+                label: annotation.label.clone(),
+                color: None,
+                highlighted: self.selected == Some(i),
+            })
+            .collect()
+    }
+}
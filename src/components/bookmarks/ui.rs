@@ -0,0 +1,101 @@
+use super::state::Bookmarks;
+use crate::components::i18n::{tr, tr_format};
+use crate::metrics::process::ProcessIdentifier;
+use crate::metrics::Metrics;
+
+impl Bookmarks {
+    /// Shows the label prompt and the jump list.
+    /// Returns the process to switch to when a bookmark is clicked.
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        metrics: &mut Metrics,
+    ) -> Option<ProcessIdentifier> {
+        if self.is_pending() {
+            let mut open = true;
+            egui::Window::new(tr("🔖 New Bookmark"))
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(tr("Label:"));
+                        let response = ui.text_edit_singleline(&mut self.label);
+                        if self.focus_label {
+                            response.request_focus();
+                            self.focus_label = false;
+                        }
+                        if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                            self.commit(metrics);
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        if ui.button(tr("Save")).clicked() {
+                            self.commit(metrics);
+                        }
+                        if ui.button(tr("Cancel")).clicked()
+                            || ui.input(|i| i.key_pressed(egui::Key::Escape))
+                        {
+                            open = false;
+                        }
+                    });
+                });
+            if !open {
+                self.cancel();
+            }
+        }
+
+        if !self.show_list {
+            return None;
+        }
+
+        let mut jump_to = None;
+        let mut to_remove = None;
+        egui::Window::new(tr("🔖 Bookmarks"))
+            .open(&mut self.show_list)
+            .resizable(true)
+            .show(ctx, |ui| {
+                if metrics.annotations().is_empty() {
+                    ui.label(tr("Press B to bookmark the current moment"));
+                }
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        for (i, bookmark) in metrics.annotations().iter().enumerate().rev() {
+                            ui.horizontal(|ui| {
+                                let ago =
+                                    bookmark.created_at.elapsed().unwrap_or_default().as_secs();
+                                let text = tr_format(
+                                    "{} ({}m {}s ago)",
+                                    &[&bookmark.label, &(ago / 60), &(ago % 60)],
+                                );
+                                if ui
+                                    .selectable_label(self.selected == Some(i), text)
+                                    .clicked()
+                                {
+                                    self.selected = Some(i);
+                                    jump_to = bookmark.identifier.clone();
+                                }
+                                if let Some(process) = &bookmark.identifier {
+                                    ui.weak(process.to_string());
+                                }
+                                ui.with_layout(
+                                    egui::Layout::right_to_left(egui::Align::Center),
+                                    |ui| {
+                                        if ui.small_button("❌").clicked() {
+                                            to_remove = Some(i);
+                                        }
+                                    },
+                                );
+                            });
+                        }
+                    });
+            });
+
+        if let Some(i) = to_remove {
+            metrics.remove_annotation(i);
+            self.selected = None;
+        }
+
+        jump_to
+    }
+}
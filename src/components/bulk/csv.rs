@@ -0,0 +1,58 @@
+use crate::metrics::annotations::labels_at;
+use crate::metrics::process::ProcessIdentifier;
+use crate::metrics::{Metrics, GENERAL_STATS_PID};
+use std::fmt::Write;
+
+/// Writes the aggregate history of every identifier to `path`, one row per
+/// sample, returning the number of rows written
+pub fn save_csv(
+    metrics: &Metrics,
+    identifiers: &[ProcessIdentifier],
+    path: &str,
+) -> Result<usize, String> {
+    let mut csv =
+        String::from("identifier,seconds_ago,cpu_percent,memory_bytes,gpu_percent,annotation\n");
+    let mut rows = 0;
+    for identifier in identifiers {
+        let Some(data) = metrics.get_process_data(identifier) else {
+            continue;
+        };
+        let history = &data.genereal.history;
+        let cpu = history
+            .get_cpu_history(&GENERAL_STATS_PID)
+            .unwrap_or_default();
+        let memory = history
+            .get_memory_history(&GENERAL_STATS_PID)
+            .unwrap_or_default();
+        let gpu = history
+            .get_gpu_history(&GENERAL_STATS_PID)
+            .unwrap_or_default();
+        let len = cpu.len().min(memory.len()).min(gpu.len());
+        let interval = metrics.interval_of(identifier);
+        let name = escape(&identifier.to_string());
+        for i in 0..len {
+            let seconds_ago = (len - 1 - i) as f64 * interval.as_secs_f64();
+            let annotation = labels_at(metrics.annotations(), identifier, interval, len - 1 - i);
+            let _ = writeln!(
+                csv,
+                "{name},{seconds_ago:.3},{:.2},{},{:.2},{}",
+                cpu[i],
+                memory[i],
+                gpu[i],
+                escape(&annotation)
+            );
+        }
+        rows += len;
+    }
+    std::fs::write(path, csv).map_err(|err| err.to_string())?;
+    Ok(rows)
+}
+
+/// Quotes a field containing a separator or quote
+fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -0,0 +1,10 @@
+mod csv;
+#[cfg(not(target_arch = "wasm32"))]
+mod parquet;
+mod state;
+mod ui;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use self::parquet::save_parquet;
+pub use csv::save_csv;
+pub use state::{BulkAction, BulkSelection};
@@ -0,0 +1,140 @@
+use crate::metrics::process::{ProcessHistory, ProcessIdentifier};
+use crate::metrics::{Metrics, GENERAL_STATS_PID};
+use parquet::basic::Compression;
+use parquet::data_type::{ByteArray, ByteArrayType, DataType, FloatType, Int32Type, Int64Type};
+use parquet::errors::ParquetError;
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::{SerializedFileWriter, SerializedRowGroupWriter};
+use parquet::schema::parser::parse_message_type;
+use std::fs::File;
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+use sysinfo::Pid;
+
+/// One row per sample of every process, `pid` 0 holding the aggregate of the
+/// tree. `ts` is the wall-clock time of the sample, in UTC.
+const SCHEMA: &str = "
+message tvis_history {
+    REQUIRED BYTE_ARRAY identifier (UTF8);
+    REQUIRED INT32 pid (INTEGER(32,false));
+    REQUIRED INT64 ts (TIMESTAMP(MILLIS,true));
+    REQUIRED FLOAT cpu_percent;
+    REQUIRED INT64 memory_bytes;
+    REQUIRED FLOAT gpu_percent;
+}
+";
+
+/// Columns of one identifier, written as one row group
+#[derive(Default)]
+struct Columns {
+    pid: Vec<i32>,
+    ts: Vec<i64>,
+    cpu: Vec<f32>,
+    memory: Vec<i64>,
+    gpu: Vec<f32>,
+}
+
+impl Columns {
+    /// Appends the history of `pid`, with `times` the wall-clock times of the
+    /// samples of its tree in Unix milliseconds
+    fn push(&mut self, history: &ProcessHistory, pid: &Pid, times: &[i64]) {
+        let cpu = history.get_cpu_history(pid).unwrap_or_default();
+        let memory = history.get_memory_history(pid).unwrap_or_default();
+        let gpu = history.get_gpu_history(pid).unwrap_or_default();
+        let len = cpu.len().min(memory.len()).min(gpu.len()).min(times.len());
+        // Histories of children that started late are shorter, the newest
+        // sample of each is aligned with the newest of the tree
+        let (cpu, memory, gpu, times) = (
+            &cpu[cpu.len() - len..],
+            &memory[memory.len() - len..],
+            &gpu[gpu.len() - len..],
+            &times[times.len() - len..],
+        );
+        for i in 0..len {
+            self.pid.push(pid.as_u32() as i32);
+            self.ts.push(times[i]);
+            self.cpu.push(cpu[i]);
+            self.memory.push(memory[i] as i64);
+            self.gpu.push(gpu[i]);
+        }
+    }
+}
+
+/// Writes the aggregate and per-process history of every identifier to `path`
+/// as a Snappy-compressed Parquet file, returning the number of rows written
+pub fn save_parquet(
+    metrics: &Metrics,
+    identifiers: &[ProcessIdentifier],
+    path: &str,
+) -> Result<usize, String> {
+    write(metrics, identifiers, path).map_err(|err| err.to_string())
+}
+
+fn write(
+    metrics: &Metrics,
+    identifiers: &[ProcessIdentifier],
+    path: &str,
+) -> Result<usize, ParquetError> {
+    let schema = Arc::new(parse_message_type(SCHEMA)?);
+    let properties = Arc::new(
+        WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .set_created_by(format!("tvis {}", env!("CARGO_PKG_VERSION")))
+            .build(),
+    );
+    let file = File::create(path)?;
+    let mut writer = SerializedFileWriter::new(file, schema, properties)?;
+    let mut rows = 0;
+    for identifier in identifiers {
+        let Some(data) = metrics.get_process_data(identifier) else {
+            continue;
+        };
+        // Recorded per sample, so gaps left by a late worker show in the timestamps
+        let times: Vec<i64> = data
+            .genereal
+            .history
+            .get_sample_times(&GENERAL_STATS_PID)
+            .unwrap_or_default()
+            .iter()
+            .map(|at| {
+                at.duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_millis() as i64
+            })
+            .collect();
+        let mut columns = Columns::default();
+        columns.push(&data.genereal.history, &GENERAL_STATS_PID, &times);
+        let mut pids: Vec<&Pid> = data.history.pids().collect();
+        pids.sort();
+        for pid in pids {
+            columns.push(&data.history, pid, &times);
+        }
+        if columns.pid.is_empty() {
+            continue;
+        }
+        let name = ByteArray::from(identifier.to_string().as_str());
+        let mut row_group = writer.next_row_group()?;
+        write_column::<ByteArrayType>(&mut row_group, &vec![name; columns.pid.len()])?;
+        write_column::<Int32Type>(&mut row_group, &columns.pid)?;
+        write_column::<Int64Type>(&mut row_group, &columns.ts)?;
+        write_column::<FloatType>(&mut row_group, &columns.cpu)?;
+        write_column::<Int64Type>(&mut row_group, &columns.memory)?;
+        write_column::<FloatType>(&mut row_group, &columns.gpu)?;
+        row_group.close()?;
+        rows += columns.pid.len();
+    }
+    writer.close()?;
+    Ok(rows)
+}
+
+/// Writes the next column of the schema, which must be of type `T`
+fn write_column<T: DataType>(
+    row_group: &mut SerializedRowGroupWriter<'_, File>,
+    values: &[T::T],
+) -> Result<(), ParquetError> {
+    let mut column = row_group
+        .next_column()?
+        .ok_or_else(|| ParquetError::General("more columns written than in the schema".into()))?;
+    column.typed::<T>().write_batch(values, None, None)?;
+    column.close()
+}
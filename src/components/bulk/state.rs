@@ -0,0 +1,43 @@
+use crate::metrics::process::ProcessIdentifier;
+use std::collections::BTreeSet;
+
+/// Change to the monitored list picked from the bulk actions
+pub enum BulkAction {
+    Remove(Vec<ProcessIdentifier>),
+    /// Start monitoring a group of the checked entries
+    Group(ProcessIdentifier),
+}
+
+/// Monitored identifiers checked in the side panel for bulk actions
+#[derive(Default)]
+pub struct BulkSelection {
+    pub(super) checked: BTreeSet<ProcessIdentifier>,
+    /// Outcome of the last CSV or Parquet export
+    pub(super) status: Option<Result<String, String>>,
+}
+
+impl BulkSelection {
+    /// Unchecks identifiers that are no longer monitored
+    pub fn retain(&mut self, monitored: &[ProcessIdentifier]) {
+        self.checked
+            .retain(|identifier| monitored.contains(identifier));
+    }
+
+    pub(super) fn checked(&self) -> Vec<ProcessIdentifier> {
+        self.checked.iter().cloned().collect()
+    }
+
+    /// The checked entries as one group, with nested groups flattened
+    pub(super) fn group(&self) -> ProcessIdentifier {
+        let mut members = Vec::new();
+        for identifier in &self.checked {
+            match identifier {
+                ProcessIdentifier::Group(inner) => members.extend(inner.iter().cloned()),
+                other => members.push(other.clone()),
+            }
+        }
+        members.sort();
+        members.dedup();
+        ProcessIdentifier::Group(members)
+    }
+}
@@ -0,0 +1,123 @@
+use super::state::{BulkAction, BulkSelection};
+use crate::components::i18n::{tr, tr_format};
+use crate::metrics::process::ProcessIdentifier;
+use crate::metrics::Metrics;
+
+impl BulkSelection {
+    /// Checkbox adding `identifier` to the selection, shown in its side panel row
+    pub fn show_checkbox(&mut self, ui: &mut egui::Ui, identifier: &ProcessIdentifier) {
+        let mut checked = self.checked.contains(identifier);
+        if ui.checkbox(&mut checked, "").changed() {
+            if checked {
+                self.checked.insert(identifier.clone());
+            } else {
+                self.checked.remove(identifier);
+            }
+        }
+    }
+
+    /// Select all toggle and the actions on the checked entries
+    pub fn show_actions(
+        &mut self,
+        ui: &mut egui::Ui,
+        metrics: &mut Metrics,
+        monitored: &[ProcessIdentifier],
+    ) -> Option<BulkAction> {
+        if monitored.is_empty() {
+            return None;
+        }
+        let mut action = None;
+        ui.horizontal_wrapped(|ui| {
+            let all_checked = self.checked.len() == monitored.len();
+            if ui
+                .small_button(tr(if all_checked { "☐ None" } else { "☑ All" }))
+                .clicked()
+            {
+                self.checked = if all_checked {
+                    Default::default()
+                } else {
+                    monitored.iter().cloned().collect()
+                };
+            }
+            if self.checked.is_empty() {
+                return;
+            }
+            ui.weak(tr_format("{} selected", &[&self.checked.len()]));
+            if ui.small_button(tr("❌ Remove")).clicked() {
+                action = Some(BulkAction::Remove(self.checked()));
+                self.checked.clear();
+            }
+            if ui
+                .small_button(tr("⟲ Clear"))
+                .on_hover_text(tr("Clear the history of the selected entries"))
+                .clicked()
+            {
+                for identifier in &self.checked {
+                    metrics.clear_process_data(identifier);
+                }
+            }
+            if ui
+                .add_enabled(
+                    self.checked.len() > 1,
+                    egui::Button::new(tr("⊞ Group")).small(),
+                )
+                .on_hover_text(tr("Monitor the selected entries as one aggregate"))
+                .clicked()
+            {
+                action = Some(BulkAction::Group(self.group()));
+            }
+            if !cfg!(target_arch = "wasm32")
+                && ui
+                    .small_button("💾 CSV")
+                    .on_hover_text(tr(
+                        "Export the history of the selected entries to a CSV file",
+                    ))
+                    .clicked()
+            {
+                self.export_csv(metrics);
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            if ui
+                .small_button("💾 Parquet")
+                .on_hover_text(tr(
+                    "Export the history of the selected entries and of each of their \
+                     processes to a compressed Parquet file, for polars or pandas",
+                ))
+                .clicked()
+            {
+                self.export_parquet(metrics);
+            }
+        });
+        if let Some(status) = &self.status {
+            match status {
+                Ok(message) => ui.weak(message),
+                Err(err) => ui.colored_label(ui.visuals().error_fg_color, err),
+            };
+        }
+        action
+    }
+
+    fn export_csv(&mut self, metrics: &Metrics) {
+        let path = format!("tvis_processes_{}.csv", unix_seconds());
+        self.status = Some(
+            super::csv::save_csv(metrics, &self.checked(), &path)
+                .map(|rows| tr_format("Saved {} samples to {}", &[&rows, &path])),
+        );
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn export_parquet(&mut self, metrics: &Metrics) {
+        let path = format!("tvis_processes_{}.parquet", unix_seconds());
+        self.status = Some(
+            super::parquet::save_parquet(metrics, &self.checked(), &path)
+                .map(|rows| tr_format("Saved {} rows to {}", &[&rows, &path])),
+        );
+    }
+}
+
+fn unix_seconds() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
@@ -0,0 +1,4 @@
+mod state;
+mod ui;
+
+pub use state::{FocusChange, FocusFollower};
@@ -0,0 +1,110 @@
+use crate::metrics::process::ProcessIdentifier;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use sysinfo::Pid;
+
+/// How often the focused window is looked up
+pub const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Monitors whichever process owns the focused window, switching the active view to it
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+#[serde(default)]
+pub struct FocusFollower {
+    pub enabled: bool,
+    /// Background lookup of the focused window, running while enabled
+    #[serde(skip)]
+    lookup: Option<FocusLookup>,
+    /// Identifier of the last focused process
+    #[serde(skip)]
+    focused: Option<ProcessIdentifier>,
+    /// Whether `focused` was added by the follower, so it's removed when focus moves on
+    #[serde(skip)]
+    added: bool,
+}
+
+/// Focus moved to another process
+pub struct FocusChange {
+    pub follow: ProcessIdentifier,
+    /// Entry added for the previously focused process, to stop monitoring
+    pub unfollow: Option<ProcessIdentifier>,
+}
+
+/// Thread asking the platform for the focused window every [`POLL_INTERVAL`],
+/// as `xprop` and `lsappinfo` are too slow to run on the UI thread
+struct FocusLookup {
+    running: Arc<AtomicBool>,
+    /// Owner of the focused window on the last lookup
+    focused: Arc<Mutex<Option<Pid>>>,
+}
+
+impl FocusLookup {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn spawn() -> Self {
+        let running = Arc::new(AtomicBool::new(true));
+        let focused = Arc::new(Mutex::new(None));
+        let (thread_running, thread_focused) = (Arc::clone(&running), Arc::clone(&focused));
+        std::thread::spawn(move || {
+            while thread_running.load(Ordering::Relaxed) {
+                let pid = crate::metrics::process::focused_pid();
+                *thread_focused.lock().unwrap() = pid;
+                std::thread::sleep(POLL_INTERVAL);
+            }
+        });
+        Self { running, focused }
+    }
+
+    /// The web has no focused process to look up
+    #[cfg(target_arch = "wasm32")]
+    fn spawn() -> Self {
+        Self {
+            running: Arc::new(AtomicBool::new(false)),
+            focused: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    fn focused(&self) -> Option<Pid> {
+        *self.focused.lock().unwrap()
+    }
+}
+
+impl Drop for FocusLookup {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
+
+impl FocusFollower {
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+        self.lookup = None;
+        self.focused = None;
+        self.added = false;
+    }
+
+    /// Follows the result of the latest background lookup. Focusing tvis itself
+    /// keeps the previous process, so its plots stay inspectable.
+    pub fn poll(&mut self, monitored: &[ProcessIdentifier]) -> Option<FocusChange> {
+        if !self.enabled {
+            return None;
+        }
+        let pid = self
+            .lookup
+            .get_or_insert_with(FocusLookup::spawn)
+            .focused()?;
+        if sysinfo::get_current_pid().ok() == Some(pid) {
+            return None;
+        }
+        let identifier = ProcessIdentifier::Pid(pid);
+        if self.focused.as_ref() == Some(&identifier) {
+            return None;
+        }
+        let unfollow = self.focused.take().filter(|_| self.added);
+        self.added = !monitored.contains(&identifier);
+        self.focused = Some(identifier.clone());
+        Some(FocusChange {
+            follow: identifier,
+            unfollow,
+        })
+    }
+}
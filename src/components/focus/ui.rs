@@ -0,0 +1,22 @@
+use super::state::{FocusFollower, POLL_INTERVAL};
+
+impl FocusFollower {
+    /// Top bar toggle, keeping the UI polling while enabled
+    pub fn show_toggle(&mut self, ui: &mut egui::Ui) {
+        if cfg!(target_arch = "wasm32") {
+            return;
+        }
+        if ui
+            .selectable_label(self.enabled, "🪟")
+            .on_hover_text(
+                "Follow the focused window: monitor the process owning it and switch to its view",
+            )
+            .clicked()
+        {
+            self.toggle();
+        }
+        if self.enabled {
+            ui.ctx().request_repaint_after(POLL_INTERVAL);
+        }
+    }
+}
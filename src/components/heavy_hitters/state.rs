@@ -0,0 +1,24 @@
+/// Window listing the heaviest processes seen system-wide
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct HeavyHittersView {
+    /// How far back peaks are looked up
+    pub lookback_minutes: u64,
+    #[serde(skip)]
+    pub show_window: bool,
+}
+
+impl Default for HeavyHittersView {
+    fn default() -> Self {
+        Self {
+            lookback_minutes: 5,
+            show_window: false,
+        }
+    }
+}
+
+impl HeavyHittersView {
+    pub fn toggle_window(&mut self) {
+        self.show_window = !self.show_window;
+    }
+}
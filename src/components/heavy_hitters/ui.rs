@@ -0,0 +1,97 @@
+use super::state::HeavyHittersView;
+use crate::components::i18n::{tr, tr_format};
+use crate::components::settings::Settings;
+use crate::metrics::heavy_hitters::{HeavyHitter, HeavyHitters, RETENTION};
+use crate::metrics::process::ProcessIdentifier;
+use std::time::Duration;
+
+impl HeavyHittersView {
+    /// Shows the top CPU and memory consumers over the lookback window.
+    /// Returns a process name to start monitoring when "Add" is clicked.
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        heavy_hitters: &HeavyHitters,
+        monitored_processes: &[ProcessIdentifier],
+        settings: &Settings,
+        cpu_count: usize,
+    ) -> Option<ProcessIdentifier> {
+        if !self.show_window {
+            return None;
+        }
+
+        let mut to_add = None;
+        egui::Window::new(tr("🔥 Heavy Hitters"))
+            .open(&mut self.show_window)
+            .resizable(true)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(tr("Last"));
+                    ui.add(
+                        egui::DragValue::new(&mut self.lookback_minutes)
+                            .range(1..=RETENTION.as_secs() / 60)
+                            .suffix(" min"),
+                    );
+                });
+                if heavy_hitters.is_empty() {
+                    ui.label(tr("No samples yet"));
+                    return;
+                }
+                let window = Duration::from_secs(self.lookback_minutes * 60);
+
+                ui.separator();
+                ui.strong(tr("CPU"));
+                let cpu = heavy_hitters.cpu_peaks(window);
+                show_peaks(
+                    ui,
+                    "heavy_hitters_cpu",
+                    &cpu,
+                    monitored_processes,
+                    &mut to_add,
+                    |peak| format!("{:.1}%", settings.cpu_scale.apply(peak, cpu_count)),
+                );
+
+                ui.separator();
+                ui.strong(tr("Memory"));
+                let memory = heavy_hitters.memory_peaks(window);
+                show_peaks(
+                    ui,
+                    "heavy_hitters_memory",
+                    &memory,
+                    monitored_processes,
+                    &mut to_add,
+                    |peak| {
+                        let (value, unit) = settings.memory_unit.format_value(peak as f32);
+                        format!("{value:.1} {unit}")
+                    },
+                );
+            });
+
+        to_add
+    }
+}
+
+fn show_peaks<T: Copy>(
+    ui: &mut egui::Ui,
+    id: &str,
+    peaks: &[HeavyHitter<T>],
+    monitored_processes: &[ProcessIdentifier],
+    to_add: &mut Option<ProcessIdentifier>,
+    format: impl Fn(T) -> String,
+) {
+    egui::Grid::new(id).striped(true).show(ui, |ui| {
+        for hitter in peaks {
+            let identifier = ProcessIdentifier::Name(hitter.name.clone());
+            let ago = hitter.at.elapsed().as_secs();
+            ui.label(&hitter.name);
+            ui.label(format(hitter.peak));
+            ui.weak(tr_format("{}m {}s ago", &[&(ago / 60), &(ago % 60)]));
+            if monitored_processes.contains(&identifier) {
+                ui.weak(tr("monitored"));
+            } else if ui.small_button(tr("Add")).clicked() {
+                *to_add = Some(identifier);
+            }
+            ui.end_row();
+        }
+    });
+}
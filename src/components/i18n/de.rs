@@ -0,0 +1,939 @@
+/// German translations, keyed by the English text
+pub(super) const STRINGS: &[(&str, &str)] = &[
+    // Menu and toolbar
+    ("Menu", "Menü"),
+    ("Profiles", "Profile"),
+    ("Quit", "Beenden"),
+    ("Settings", "Einstellungen"),
+    ("Bookmarks (press B to bookmark now)", "Lesezeichen (B setzt eines)"),
+    ("Alert rules", "Alarmregeln"),
+    (
+        "Alert rules, alerts held during quiet hours",
+        "Alarmregeln, in Ruhezeiten zurückgehaltene Alarme",
+    ),
+    ("SLO targets", "SLO-Ziele"),
+    ("Compare two recorded runs", "Zwei aufgezeichnete Läufe vergleichen"),
+    ("Heaviest processes system-wide", "Systemweit aufwendigste Prozesse"),
+    (
+        "Save CPU and memory plots of all monitored processes as one image",
+        "CPU- und Speicherdiagramme aller überwachten Prozesse als ein Bild speichern",
+    ),
+    ("Clear current process data", "Daten des aktuellen Prozesses löschen"),
+    ("▣ Mini overlay", "▣ Mini-Overlay"),
+    (
+        "Shrink the window to an always-on-top strip with the CPU and \
+         memory of the active process, redrawn only on new samples",
+        "Fenster zu einer Leiste im Vordergrund mit CPU und Speicher des aktiven \
+         Prozesses verkleinern, nur bei neuen Messwerten neu gezeichnet",
+    ),
+    (
+        "Drag to move, double-click to return to the full window",
+        "Zum Verschieben ziehen, Doppelklick kehrt zum vollen Fenster zurück",
+    ),
+    ("No process monitored", "Kein Prozess überwacht"),
+    ("Back to the full window", "Zurück zum vollen Fenster"),
+    ("CPU {} Memory {} {}", "CPU {} Speicher {} {}"),
+    ("⚠ Safe mode", "⚠ Abgesicherter Modus"),
+    (
+        "{} processes running. Only monitored process trees are refreshed every tick, new \
+         processes are picked up on a periodic full scan and the process list is searched on \
+         demand.",
+        "{} Prozesse laufen. Nur überwachte Prozessbäume werden bei jedem Takt aktualisiert, \
+         neue Prozesse werden bei einem regelmäßigen vollständigen Scan erkannt und die \
+         Prozessliste wird bei Bedarf durchsucht.",
+    ),
+    ("{} alert firing", "Alarm aktiv: {}"),
+    (
+        "CPU over the last {} samples, peak {} ({})",
+        "CPU über die letzten {} Messwerte, Spitze {} ({})",
+    ),
+    // Side panel and tabs
+    ("Monitored Processes", "Überwachte Prozesse"),
+    ("⏸ paused — click to resume", "⏸ pausiert – zum Fortsetzen klicken"),
+    (
+        "Not sampled since the tab was hidden for too long",
+        "Nicht erfasst, da der Tab zu lange verborgen war",
+    ),
+    (
+        "Process exited, its data is frozen",
+        "Prozess beendet, seine Daten sind eingefroren",
+    ),
+    (
+        "No matching process, showing the last-known data",
+        "Kein passender Prozess, die zuletzt bekannten Daten werden angezeigt",
+    ),
+    ("Pinned, click to unpin", "Angeheftet, zum Lösen klicken"),
+    ("Alias:", "Alias:"),
+    ("Unpin", "Lösen"),
+    ("📌 Pin", "📌 Anheften"),
+    ("⮪ Attach", "⮪ Andocken"),
+    ("⧉ Pop out", "⧉ Abkoppeln"),
+    ("Show in its own window", "In einem eigenen Fenster anzeigen"),
+    ("📌 Always on top", "📌 Immer im Vordergrund"),
+    (
+        "Move back into a tab of the main window",
+        "Zurück in einen Tab des Hauptfensters",
+    ),
+    ("⏸ Sampling paused", "⏸ Erfassung pausiert"),
+    ("Resume", "Fortsetzen"),
+    ("🔖 Bookmark", "🔖 Lesezeichen"),
+    (
+        "Mark this moment on the plots of this process and in its exports (B)",
+        "Diesen Zeitpunkt in den Diagrammen und Exporten dieses Prozesses markieren (B)",
+    ),
+    ("Process not found", "Prozess nicht gefunden"),
+    // Process view
+    ("CPU", "CPU"),
+    ("Memory", "Speicher"),
+    ("GPU", "GPU"),
+    ("Processes", "Prozesse"),
+    (
+        "Processes and threads of the tree over time",
+        "Prozesse und Threads des Baums im Zeitverlauf",
+    ),
+    ("Switches & faults", "Wechsel & Seitenfehler"),
+    ("CPU states", "CPU-Zustände"),
+    ("Window:", "Zeitfenster:"),
+    ("All", "Alle"),
+    ("CPU Usage:", "CPU-Auslastung:"),
+    ("Peak:", "Spitze:"),
+    ("AVG CPU:", "Ø CPU:"),
+    ("Show:", "Anzeigen:"),
+    ("Timeline", "Zeitverlauf"),
+    ("Histogram", "Histogramm"),
+    ("Memory Usage:", "Speichernutzung:"),
+    ("AVG memory:", "Ø Speicher:"),
+    ("OS peak:", "Spitze laut OS:"),
+    ("System swap:", "Auslagerung des Systems:"),
+    (
+        "The system is paging memory out to swap",
+        "Das System lagert Speicher aus",
+    ),
+    ("Share of system:", "Anteil am System:"),
+    ("Share of system", "Anteil am System"),
+    (
+        "The tree's CPU in percent of what every process on the machine \
+         used at that moment, not of its capacity: near 100% the tree is \
+         the load, low while the machine is busy something else is",
+        "CPU des Baums in Prozent dessen, was alle Prozesse der Maschine in \
+         diesem Moment genutzt haben, nicht ihrer Kapazität: nahe 100 % ist der \
+         Baum die Last, niedrig bei ausgelasteter Maschine ist es etwas anderes",
+    ),
+    ("CPU package temperature", "Package-Temperatur der CPU"),
+    ("🌡 No temperature sensor", "🌡 Kein Temperatursensor"),
+    ("Average clock of the cores", "Durchschnittlicher Takt der Kerne"),
+    ("⚡ Clock unavailable", "⚡ Takt nicht verfügbar"),
+    ("GPU Usage:", "GPU-Auslastung:"),
+    ("AVG GPU:", "Ø GPU:"),
+    ("Processes:", "Prozesse:"),
+    ("Threads:", "Threads:"),
+    // Settings
+    ("⚙ Settings", "⚙ Einstellungen"),
+    ("Close", "Schließen"),
+    ("Dock as tab", "Als Tab andocken"),
+    ("UI Scale:", "UI-Skalierung:"),
+    ("Font Size:", "Schriftgröße:"),
+    ("Graph Scale Margin:", "Diagrammrand:"),
+    ("Extra margin above peak", "Zusätzlicher Rand über der Spitze"),
+    ("Adaptive:", "Adaptiv:"),
+    (
+        "Sample faster while a monitored tree is busy",
+        "Schneller erfassen, solange ein überwachter Baum ausgelastet ist",
+    ),
+    ("above", "über"),
+    (
+        "Auto-pause hidden processes after",
+        "Verborgene Prozesse automatisch pausieren nach",
+    ),
+    (
+        "Stop sampling processes whose tab was not viewed, keeping their history",
+        "Prozesse, deren Tab nicht angesehen wurde, nicht mehr erfassen; ihr Verlauf bleibt erhalten",
+    ),
+    (
+        "When no process matches a name:",
+        "Wenn kein Prozess zu einem Namen passt:",
+    ),
+    (
+        "Pinned entries are always kept",
+        "Angeheftete Einträge bleiben immer erhalten",
+    ),
+    ("Keep grayed", "Ausgegraut behalten"),
+    ("Archive to CSV", "Als CSV archivieren"),
+    ("Remove", "Entfernen"),
+    ("after", "nach"),
+    ("Theme:", "Design:"),
+    ("🌙 Dark", "🌙 Dunkel"),
+    ("🌞 Light", "🌞 Hell"),
+    ("◐ High contrast", "◐ Hoher Kontrast"),
+    ("Language:", "Sprache:"),
+    (
+        "Also sets the decimal and thousands separators",
+        "Legt auch Dezimal- und Tausendertrennzeichen fest",
+    ),
+    ("Update Mode:", "Aktualisierung:"),
+    ("Continuous", "Fortlaufend"),
+    ("Reactive", "Bei Eingabe"),
+    ("Memory Unit:", "Speichereinheit:"),
+    ("Bytes", "Bytes"),
+    ("CPU Scale:", "CPU-Skala:"),
+    ("% of one core", "% eines Kerns"),
+    ("% of machine", "% der Maschine"),
+    (
+        "CPU temperature and clock above the CPU plot",
+        "CPU-Temperatur und -Takt über dem CPU-Diagramm",
+    ),
+    (
+        "Thermal throttling often explains a drop in CPU usage. \
+         Not every machine exposes a package temperature.",
+        "Thermische Drosselung erklärt oft eine sinkende CPU-Auslastung. \
+         Nicht jede Maschine stellt eine Package-Temperatur bereit.",
+    ),
+    ("Smoothing:", "Glättung:"),
+    ("Off", "Aus"),
+    ("Moving average", "Gleitender Durchschnitt"),
+    ("Average of {}", "Mittelwert über {}"),
+    // Flight log
+    (
+        "Open the flight log of another, possibly crashed tvis instance",
+        "Den Flugschreiber einer anderen, womöglich abgestürzten tvis-Instanz öffnen",
+    ),
+    ("📼 Flight log", "📼 Flugschreiber-Log"),
+    ("Directory:", "Verzeichnis:"),
+    ("Open", "Öffnen"),
+    (
+        "Read the flight log of another tvis instance, rotated files included, without \
+         writing to it",
+        "Das Log des Flugschreibers einer anderen tvis-Instanz samt rotierter Dateien \
+         lesen, ohne hineinzuschreiben",
+    ),
+    ("Identifier", "Bezeichner"),
+    ("Seconds since the first sample", "Sekunden seit dem ersten Messwert"),
+    ("Killed by a signal:", "Durch ein Signal beendet:"),
+    (
+        "{} identifiers over {}, last sample {} ago",
+        "{} Bezeichner über {}, letzter Messwert vor {}",
+    ),
+    // Alerts
+    ("🔔 Alerts", "🔔 Alarme"),
+    ("Rules", "Regeln"),
+    ("Info", "Info"),
+    ("Warning", "Warnung"),
+    ("Critical", "Kritisch"),
+    ("Runs:", "Führt aus:"),
+    ("Add Rule", "Regel hinzufügen"),
+    ("Process", "Prozess"),
+    ("Metric:", "Messgröße:"),
+    ("Above:", "Über:"),
+    ("Only while swap use is above", "Nur solange die Auslagerung über"),
+    (
+        "Alert when the machine is pushed into swap rather than on size alone; at 0% \
+         any swap in use counts",
+        "Alarm, wenn die Maschine in die Auslagerung gedrängt wird, statt nur nach \
+         Größe; bei 0 % zählt jede genutzte Auslagerung",
+    ),
+    ("Severity:", "Schweregrad:"),
+    ("Run:", "Ausführen:"),
+    (
+        "Shell command run when the rule fires. Environment: TVIS_IDENTIFIER, \
+         TVIS_SEVERITY, TVIS_MESSAGE, TVIS_PID (heaviest process), TVIS_PIDS, TVIS_CPU \
+         (%), TVIS_MEMORY (bytes), TVIS_GPU (%), TVIS_SWAP_USED (bytes)",
+        "Shell-Befehl, der beim Auslösen der Regel läuft. Umgebung: TVIS_IDENTIFIER, \
+         TVIS_SEVERITY, TVIS_MESSAGE, TVIS_PID (aufwendigster Prozess), TVIS_PIDS, \
+         TVIS_CPU (%), TVIS_MEMORY (Bytes), TVIS_GPU (%), TVIS_SWAP_USED (Bytes)",
+    ),
+    ("Save", "Speichern"),
+    ("Cancel", "Abbrechen"),
+    ("Play a sound when a critical alert fires", "Bei kritischem Alarm einen Ton abspielen"),
+    ("Sound file:", "Tondatei:"),
+    ("Leave empty for the system alarm sound", "Leer lassen für den Alarmton des Systems"),
+    ("Volume:", "Lautstärke:"),
+    ("Repeat:", "Wiederholen:"),
+    ("times", "Mal"),
+    ("Test", "Testen"),
+    ("Quiet hours:", "Ruhezeiten:"),
+    (
+        "Alerts still land in the log below, marked 🌙 until acknowledged",
+        "Alarme landen weiterhin im Protokoll unten, mit 🌙 markiert bis zur Bestätigung",
+    ),
+    ("Sound", "Ton"),
+    ("Commands", "Befehle"),
+    ("Keep the sound silent in do-not-disturb", "Ton bei „Nicht stören“ stumm lassen"),
+    (
+        "Notification settings of GNOME, KDE Plasma, Xfce, macOS and Windows",
+        "Benachrichtigungseinstellungen von GNOME, KDE Plasma, Xfce, macOS und Windows",
+    ),
+    ("Colors:", "Farben:"),
+    ("Restore the default colors", "Standardfarben wiederherstellen"),
+    (
+        "Used for plot markers, sidebar badges, the alert log and saved images",
+        "Für Diagrammmarkierungen, Seitenleisten-Abzeichen, das Alarmprotokoll und \
+         gespeicherte Bilder",
+    ),
+    ("Fired", "Ausgelöst"),
+    ("Clear", "Leeren"),
+    ("Acknowledge", "Bestätigen"),
+    (
+        "Held back by quiet hours or do-not-disturb",
+        "Durch Ruhezeiten oder „Nicht stören“ zurückgehalten",
+    ),
+    ("from", "von"),
+    ("to", "bis"),
+    ("{} CPU > {} ({})", "{} CPU > {} ({})"),
+    ("{} Memory > {} {}", "{} Speicher > {} {}"),
+    ("{} GPU > {}", "{} GPU > {}"),
+    ("{} Processes > {}", "{} Prozesse > {}"),
+    ("{} while swap is above {}", "{}, solange die Auslagerung über {} liegt"),
+    ("{} while swapping", "{}, solange ausgelagert wird"),
+    ("🌙 {} held", "🌙 {} zurückgehalten"),
+    ("{}: {} ({}m {}s ago)", "{}: {} (vor {}m {}s)"),
+    ("{} alert", "Alarm: {}"),
+    ("Memory {} MB > {} MB", "Speicher {} MB > {} MB"),
+    ("CPU {} > {} ({})", "CPU {} > {} ({})"),
+    ("GPU {} > {}", "GPU {} > {}"),
+    ("Processes {} > {}", "Prozesse {} > {}"),
+    ("{} while swap is {} used", "{}, solange die Auslagerung zu {} belegt ist"),
+    // Process selector
+    ("Add Process", "Prozess hinzufügen"),
+    ("Select Process", "Prozess auswählen"),
+    ("Search:", "Suche:"),
+    ("Save this search", "Diese Suche speichern"),
+    ("By Name", "Nach Name"),
+    ("By PID", "Nach PID"),
+    ("By Container", "Nach Container"),
+    ("By cgroup", "Nach cgroup"),
+    (
+        "A cgroup path or systemd unit, e.g. nginx.service",
+        "Ein cgroup-Pfad oder eine systemd-Unit, z. B. nginx.service",
+    ),
+    ("By User", "Nach Benutzer"),
+    (
+        "Everything an account runs, by user name or id",
+        "Alles, was ein Konto ausführt, nach Benutzername oder -ID",
+    ),
+    ("By Service", "Nach Dienst"),
+    (
+        "A Windows service by its service name, e.g. Spooler",
+        "Ein Windows-Dienst nach seinem Dienstnamen, z. B. Spooler",
+    ),
+    (
+        "A launchd job by its label, e.g. com.apple.Spotlight",
+        "Ein launchd-Job nach seinem Label, z. B. com.apple.Spotlight",
+    ),
+    ("Sort by:", "Sortieren nach:"),
+    ("Relevance", "Relevanz"),
+    ("Name", "Name"),
+    ("PID", "PID"),
+    ("Container", "Container"),
+    ("cgroup", "cgroup"),
+    ("Service", "Dienst"),
+    ("User", "Benutzer"),
+    ("🔍 Search", "🔍 Suchen"),
+    ("Scan all processes once", "Alle Prozesse einmal durchsuchen"),
+    ("Type a name or PID, then Search", "Namen oder PID eingeben, dann Suchen"),
+    ("More matches, refine the search", "Weitere Treffer, Suche verfeinern"),
+    ("processes", "Prozesse"),
+    ("Save as:", "Speichern als:"),
+    ("Delete", "Löschen"),
+    ("⚠ {} processes, listing on demand", "⚠ {} Prozesse, Auflistung auf Anfrage"),
+    (
+        "⚠ Linux truncates process names to {} characters, \"{}\" will be matched against the \
+         executable name",
+        "⚠ Linux kürzt Prozessnamen auf {} Zeichen, \"{}\" wird mit dem Namen der ausführbaren \
+         Datei abgeglichen",
+    ),
+    (
+        "Search, {} highlighted, {} of {}, Enter to monitor",
+        "Suche, {} hervorgehoben, {} von {}, Enter zum Überwachen",
+    ),
+    ("Monitor {}, {} of {}", "{} überwachen, {} von {}"),
+    ("{} ({} processes)", "{} ({} Prozesse)"),
+    ("Monitor \"{}\"", "\"{}\" überwachen"),
+    // SLO targets
+    ("🎯 SLO Targets", "🎯 SLO-Ziele"),
+    ("Targets", "Ziele"),
+    ("No targets yet", "Noch keine Ziele"),
+    ("Burn %", "Verbrauch %"),
+    ("Add Target", "Ziel hinzufügen"),
+    ("Average below:", "Durchschnitt unter:"),
+    ("Over:", "Über:"),
+    ("waiting for samples", "warte auf Messwerte"),
+    (
+        "Share of samples on which the window average met the target",
+        "Anteil der Messwerte, bei denen der Durchschnitt des Zeitfensters das Ziel \
+         erreichte",
+    ),
+    ("memory", "Speicher"),
+    ("avg {} ({} burn)", "Ø {} ({} verbraucht)"),
+    ("{} compliant", "{} eingehalten"),
+    ("{} avg {} < {} over {} min", "{} Ø {} < {} über {} min"),
+    // Profiles
+    ("No profiles yet", "Noch keine Profile"),
+    ("Manage…", "Verwalten…"),
+    ("👤 Profiles", "👤 Profile"),
+    ("Save current setup as:", "Aktuelle Einrichtung speichern als:"),
+    (
+        "Monitored identifiers, sampling intervals, alert rules and theme",
+        "Überwachte Bezeichner, Erfassungsintervalle, Alarmregeln und Design",
+    ),
+    (
+        "No profiles yet, save the current setup or import a file",
+        "Noch keine Profile, aktuelle Einrichtung speichern oder eine Datei importieren",
+    ),
+    ("Apply", "Anwenden"),
+    ("Update", "Aktualisieren"),
+    ("Replace with the current setup", "Durch die aktuelle Einrichtung ersetzen"),
+    ("Export", "Exportieren"),
+    ("Save as TOML in the working directory", "Als TOML im Arbeitsverzeichnis speichern"),
+    ("Import:", "Importieren:"),
+    ("Import", "Importieren"),
+    ("Saved", "Gespeichert:"),
+    ("Exporting is not available in the browser", "Exportieren ist im Browser nicht verfügbar"),
+    ("Importing is not available in the browser", "Importieren ist im Browser nicht verfügbar"),
+    ("{} processes, {} alerts, every {} ms", "{} Prozesse, {} Alarme, alle {} ms"),
+    ("Imported \"{}\", {}", "\"{}\" importiert, {}"),
+    ("Saved \"{}\", {}", "\"{}\" gespeichert, {}"),
+    ("Applied \"{}\"", "\"{}\" angewendet"),
+    // Bulk actions
+    ("☐ None", "☐ Keine"),
+    ("☑ All", "☑ Alle"),
+    ("❌ Remove", "❌ Entfernen"),
+    ("⟲ Clear", "⟲ Leeren"),
+    ("Clear the history of the selected entries", "Verlauf der ausgewählten Einträge löschen"),
+    ("⊞ Group", "⊞ Gruppieren"),
+    (
+        "Monitor the selected entries as one aggregate",
+        "Die ausgewählten Einträge als eine Gesamtheit überwachen",
+    ),
+    (
+        "Export the history of the selected entries to a CSV file",
+        "Verlauf der ausgewählten Einträge in eine CSV-Datei exportieren",
+    ),
+    (
+        "Export the history of the selected entries and of each of their processes to a \
+         compressed Parquet file, for polars or pandas",
+        "Verlauf der ausgewählten Einträge und jedes ihrer Prozesse in eine \
+         komprimierte Parquet-Datei exportieren, für polars oder pandas",
+    ),
+    ("{} selected", "{} ausgewählt"),
+    ("Saved {} samples to {}", "{} Messwerte in {} gespeichert"),
+    ("Saved {} rows to {}", "{} Zeilen in {} gespeichert"),
+    // Bookmarks
+    ("🔖 New Bookmark", "🔖 Neues Lesezeichen"),
+    ("Label:", "Bezeichnung:"),
+    ("🔖 Bookmarks", "🔖 Lesezeichen"),
+    (
+        "Press B to bookmark the current moment",
+        "B drücken, um den aktuellen Moment als Lesezeichen zu setzen",
+    ),
+    ("{} ({}m {}s ago)", "{} (vor {}m {}s)"),
+    // Resource limits
+    ("⛔ Limit", "⛔ Begrenzen"),
+    (
+        "Cap the CPU or memory of the whole tree (cgroup or Job Object)",
+        "CPU oder Speicher des ganzen Baums begrenzen (cgroup oder Job Object)",
+    ),
+    ("Limited to", "Begrenzt auf"),
+    ("⛔ Resource Limit", "⛔ Ressourcengrenze"),
+    (
+        "Needs root or a delegated cgroup v2 hierarchy",
+        "Benötigt root oder eine delegierte cgroup-v2-Hierarchie",
+    ),
+    ("Remove limit", "Grenze entfernen"),
+    ("Limit removed", "Grenze entfernt"),
+    // Benchmark
+    ("⚖ Record run as:", "⚖ Lauf aufzeichnen als:"),
+    (
+        "Keep this run as the baseline to compare against",
+        "Diesen Lauf als Vergleichsbasis behalten",
+    ),
+    (
+        "Keep this run as the one compared to the baseline",
+        "Diesen Lauf als den mit der Basis verglichenen behalten",
+    ),
+    ("Compare", "Vergleichen"),
+    ("⚖ Benchmark", "⚖ Benchmark"),
+    (
+        "Not recorded, use \"Record run as\" in a process tab",
+        "Nicht aufgezeichnet, „Lauf aufzeichnen als“ in einem Prozess-Tab nutzen",
+    ),
+    ("Peak CPU", "CPU-Spitze"),
+    ("Avg CPU", "Ø CPU"),
+    ("Peak memory", "Speicherspitze"),
+    ("Avg memory", "Ø Speicher"),
+    ("Duration", "Dauer"),
+    ("Seconds since start", "Sekunden seit Start"),
+    ("{}, {} samples over {}", "{}, {} Messwerte über {}"),
+    // Heavy hitters
+    ("🔥 Heavy Hitters", "🔥 Aufwendigste Prozesse"),
+    ("Last", "Letzte"),
+    ("No samples yet", "Noch keine Messwerte"),
+    ("monitored", "überwacht"),
+    ("Add", "Hinzufügen"),
+    ("{}m {}s ago", "vor {}m {}s"),
+    // Process view
+    ("📋 Copy stats", "📋 Werte kopieren"),
+    (
+        "Copy stats and top children as a Markdown table",
+        "Werte und wichtigste Kindprozesse als Markdown-Tabelle kopieren",
+    ),
+    ("📊 Summary", "📊 Zusammenfassung"),
+    ("Totals over the whole retained history", "Summen über den gesamten gespeicherten Verlauf"),
+    ("📸 Snapshot now", "📸 Jetzt Schnappschuss"),
+    (
+        "Freeze the current data, take a second snapshot later to see what changed",
+        "Aktuelle Daten einfrieren; später einen zweiten Schnappschuss aufnehmen, um \
+         Änderungen zu sehen",
+    ),
+    (
+        "Freeze the current data and compare it with the previous snapshot",
+        "Aktuelle Daten einfrieren und mit dem vorigen Schnappschuss vergleichen",
+    ),
+    ("Total Processes:", "Prozesse gesamt:"),
+    ("Total Threads:", "Threads gesamt:"),
+    (
+        "Monitored: {} | Samples: {} | Coverage: {}",
+        "Überwacht: {} | Messwerte: {} | Abdeckung: {}",
+    ),
+    (
+        "Samples with a live process out of all expected samples. Gaps come from the \
+         process not running or the sampler falling behind.",
+        "Messwerte mit laufendem Prozess von allen erwarteten Messwerten. Lücken \
+         entstehen, wenn der Prozess nicht läuft oder die Erfassung zurückfällt.",
+    ),
+    ("Uptime: not running", "Laufzeit: läuft nicht"),
+    ("Uptime:", "Laufzeit:"),
+    (
+        "Since the oldest running process of the tree started",
+        "Seit dem Start des ältesten laufenden Prozesses des Baums",
+    ),
+    ("Restarts:", "Neustarts:"),
+    (
+        "Matching processes started after monitoring began, each one is also listed \
+         under Events",
+        "Passende Prozesse, die nach Beginn der Überwachung gestartet sind; jeder steht \
+         auch unter Ereignisse",
+    ),
+    ("Snapshot taken {} ago", "Schnappschuss vor {} aufgenommen"),
+    ("{} CPU", "{}: CPU"),
+    ("{} CPU share", "{}: CPU-Anteil"),
+    ("{} GPU", "{}: GPU"),
+    ("{} Memory growth", "{}: Speicherwachstum"),
+    ("{} Memory", "{}: Speicher"),
+    ("{} processes", "{}: Prozesse"),
+    ("{} / {} ({}) CPU", "{} / {} ({}): CPU"),
+    ("{} / {} ({}) {}", "{} / {} ({}): {}"),
+    ("{} / {} ({}) GPU", "{} / {} ({}): GPU"),
+    ("{} CPU ({})", "{}: CPU ({})"),
+    (
+        "{} (PID: {}) was killed by {} ({}m {}s ago)",
+        "{} (PID: {}) wurde beendet durch {} (vor {}m {}s)",
+    ),
+    ("{} (PID: {}) started ({}m {}s ago)", "{} (PID: {}) gestartet (vor {}m {}s)"),
+    ("{} (PID: {}) exited ({}m {}s ago)", "{} (PID: {}) beendet (vor {}m {}s)"),
+    ("{} (PID: {}) restarted ({}m {}s ago)", "{} (PID: {}) neu gestartet (vor {}m {}s)"),
+    ("{} of {} match", "{} von {} passen"),
+    ("…and {} more", "…und {} weitere"),
+    ("⏹ Finished {} ago", "⏹ Vor {} beendet"),
+    ("{} (PID: {}) was killed by {}, {} ago", "{} (PID: {}) wurde durch {} beendet, vor {}"),
+    ("and {} more, see Events", "und {} weitere, siehe Ereignisse"),
+    ("🔒 {} processes need elevation", "🔒 {} Prozesse erfordern erhöhte Rechte"),
+    ("Relaunch as {}", "Neu starten als {}"),
+    ("{} ago", "vor {}"),
+    ("{} open sockets", "{} offene Sockets"),
+    ("tvis overview", "tvis-Übersicht"),
+    ("Over the retained history, {}", "Über den gespeicherten Verlauf, {}"),
+    ("Above {} ({})", "Über {} ({})"),
+    ("{} {}, {} ago", "{} {}, vor {}"),
+    ("{} and {} samples apart", "{} und {} Messwerte auseinander"),
+    ("{} plots", "{} Diagramme"),
+    ("{} missed samples", "{} verpasste Messwerte"),
+    (
+        "Up on {} of the last {} samples\n{} of {} samples since monitoring started",
+        "Aktiv bei {} der letzten {} Messwerte\n{} von {} Messwerten seit Beginn der Überwachung",
+    ),
+    ("Thread {}, PID {}, CPU {}, memory {} {}", "Thread {}, PID {}, CPU {}, Speicher {} {}"),
+    ("Process {}, PID {}, CPU {}, memory {} {}", "Prozess {}, PID {}, CPU {}, Speicher {} {}"),
+    (", priority: {}, nice {}", ", Priorität: {}, Nice {}"),
+    ("⚡ {}: {} processes", "⚡ {}: {} Prozesse"),
+    ("Reaches {} {}", "Erreicht {} {}"),
+    ("{} in {}", "{} in {}"),
+    ("{}: not growing", "{}: wächst nicht"),
+    ("Memory trend {} {}/s over the last {} min", "Speichertrend {} {}/s über die letzten {} min"),
+    (
+        "Possible leak: memory growing {} MB/min for {}",
+        "Mögliches Leck: Speicher wächst um {} MB/min, seit {}",
+    ),
+    (
+        "Switches: {}/s voluntary, {}/s involuntary | Faults: {}/s minor, {}/s major",
+        "Wechsel: {}/s freiwillig, {}/s unfreiwillig | Seitenfehler: {}/s leicht, {}/s schwer",
+    ),
+    (
+        "CPU states: {} user, {} system, {} I/O wait",
+        "CPU-Zustände: {} Benutzer, {} System, {} E/A-Wartezeit",
+    ),
+    // Process cards
+    ("CPU share (% of system usage)", "CPU-Anteil (% der Systemauslastung)"),
+    (
+        "Sum of the lifetime peak RSS the OS reports for each live process, including \
+         peaks before monitoring started",
+        "Summe der vom OS gemeldeten lebenslangen RSS-Spitzen jedes laufenden \
+         Prozesses, einschließlich Spitzen vor Beginn der Überwachung",
+    ),
+    ("💧 possible leak", "💧 mögliches Leck"),
+    ("Absolute", "Absolut"),
+    ("Growth rate", "Wachstumsrate"),
+    (
+        "Change in memory per second between consecutive samples. A slow leak shows up \
+         as a rate that stays above zero.",
+        "Speicheränderung pro Sekunde zwischen aufeinanderfolgenden Messwerten. Ein \
+         langsames Leck zeigt sich als Rate, die über null bleibt.",
+    ),
+    ("Growth:", "Wachstum:"),
+    ("AVG growth:", "Ø Wachstum:"),
+    ("Memory growth", "Speicherwachstum"),
+    ("Threads", "Threads"),
+    ("Events", "Ereignisse"),
+    ("Show on plots", "In Diagrammen anzeigen"),
+    ("No child processes started or exited yet", "Noch keine Kindprozesse gestartet oder beendet"),
+    ("Average CPU", "Durchschnittliche CPU"),
+    ("Current CPU", "Aktuelle CPU"),
+    ("Priority", "Priorität"),
+    // Process details
+    ("Ascending, click for descending", "Aufsteigend, klicken für absteigend"),
+    ("Descending, click for ascending", "Absteigend, klicken für aufsteigend"),
+    ("🔍 Filter by name or PID", "🔍 Nach Name oder PID filtern"),
+    ("Top", "Erste"),
+    (
+        "Show only the first processes in the sort order",
+        "Nur die ersten Prozesse der Sortierung anzeigen",
+    ),
+    ("Thread", "Thread"),
+    ("🔒 Needs elevation", "🔒 Erfordert erhöhte Rechte"),
+    ("Owned by another user, unavailable:", "Gehört einem anderen Benutzer, nicht verfügbar:"),
+    (
+        "command line, working directory, executable, environment and some memory \
+         figures",
+        "Befehlszeile, Arbeitsverzeichnis, ausführbare Datei, Umgebung und einige \
+         Speicherwerte",
+    ),
+    (
+        "working directory, executable and environment",
+        "Arbeitsverzeichnis, ausführbare Datei und Umgebung",
+    ),
+    (
+        "command line, working directory and environment",
+        "Befehlszeile, Arbeitsverzeichnis und Umgebung",
+    ),
+    ("Priority:", "Priorität:"),
+    ("Realtime", "Echtzeit"),
+    ("High", "Hoch"),
+    ("Normal", "Normal"),
+    ("Low", "Niedrig"),
+    ("Idle", "Leerlauf"),
+    ("Priority class and nice value", "Prioritätsklasse und Nice-Wert"),
+    ("Parent:", "Elternprozess:"),
+    ("Parent: None", "Elternprozess: keiner"),
+    ("Current CPU:", "Aktuelle CPU:"),
+    ("Lifetime peak RSS reported by the OS", "Vom OS gemeldete lebenslange RSS-Spitze"),
+    ("Child processes: {} | Threads: {}", "Kindprozesse: {} | Threads: {}"),
+    (
+        "Hide these crashes, they stay in the events",
+        "Diese Abstürze ausblenden, sie bleiben in den Ereignissen",
+    ),
+    // Process plots
+    ("Copy path", "Pfad kopieren"),
+    ("🔍 Core dump", "🔍 Speicherabbild"),
+    (
+        "Ask systemd-coredump where the dump was stored",
+        "systemd-coredump fragen, wo das Abbild gespeichert wurde",
+    ),
+    (
+        "Run duration: {} | Children spawned: {}",
+        "Laufdauer: {} | Gestartete Kindprozesse: {}",
+    ),
+    ("CPU avg: {} | peak: {}", "Ø CPU: {} | Spitze: {}"),
+    ("Memory avg: {} | peak: {}", "Ø Speicher: {} | Spitze: {}"),
+    ("📋 Copy summary", "📋 Zusammenfassung kopieren"),
+    ("💾 Save summary", "💾 Zusammenfassung speichern"),
+    ("Saved to", "Gespeichert in"),
+    ("⏸ Suspended", "⏸ Angehalten"),
+    ("▶ Resume", "▶ Fortsetzen"),
+    ("Send SIGCONT", "SIGCONT senden"),
+    ("⏸ Suspend", "⏸ Anhalten"),
+    (
+        "Send SIGSTOP, freezing the process until resumed",
+        "SIGSTOP senden, der Prozess ruht bis zur Fortsetzung",
+    ),
+    ("They belong to other users, unavailable:", "Sie gehören anderen Benutzern, nicht verfügbar:"),
+    ("administrator", "Administrator"),
+    (
+        "Start tvis again with the same arguments and close this one",
+        "tvis mit denselben Argumenten neu starten und diese Instanz schließen",
+    ),
+    ("Unavailable (needs elevation)", "Nicht verfügbar (erfordert erhöhte Rechte)"),
+    ("Unknown", "Unbekannt"),
+    ("Details", "Details"),
+    ("Process exited", "Prozess beendet"),
+    ("Command line:", "Befehlszeile:"),
+    ("Working directory:", "Arbeitsverzeichnis:"),
+    ("Executable:", "Ausführbare Datei:"),
+    ("User:", "Benutzer:"),
+    ("Started:", "Gestartet:"),
+    ("Nice:", "Nice:"),
+    ("CPU affinity:", "CPU-Affinität:"),
+    ("📋 Copy command line", "📋 Befehlszeile kopieren"),
+    ("↻ Refresh", "↻ Aktualisieren"),
+    ("✏ Change priority", "✏ Priorität ändern"),
+    (
+        "Renice the process or restrict it to some CPUs",
+        "Nice-Wert des Prozesses ändern oder ihn auf einige CPUs beschränken",
+    ),
+    ("Show environment", "Umgebung anzeigen"),
+    (
+        "Environment variables can contain secrets",
+        "Umgebungsvariablen können Geheimnisse enthalten",
+    ),
+    ("Environment", "Umgebung"),
+    ("📋 Copy environment", "📋 Umgebung kopieren"),
+    ("Network connections", "Netzwerkverbindungen"),
+    ("Proto", "Proto"),
+    ("Local", "Lokal"),
+    ("Remote", "Entfernt"),
+    ("State", "Zustand"),
+    ("▶ Follow live", "▶ Live folgen"),
+    ("Snap back to the newest samples", "Zurück zu den neuesten Messwerten"),
+    ("🔍 Zoom & pan", "🔍 Zoomen & verschieben"),
+    (
+        "Stop following and explore the whole retained history: drag or scroll to pan, \
+         ctrl+scroll to zoom, double-click to reset",
+        "Nicht mehr folgen und den gesamten gespeicherten Verlauf erkunden: ziehen oder \
+         scrollen zum Verschieben, Strg+Scrollen zum Zoomen, Doppelklick zum \
+         Zurücksetzen",
+    ),
+    // Summaries and exports
+    ("💾 Save image", "💾 Bild speichern"),
+    ("Save this plot as PNG or SVG", "Dieses Diagramm als PNG oder SVG speichern"),
+    ("Summary:", "Zusammenfassung:"),
+    ("CPU time", "CPU-Zeit"),
+    ("CPU-seconds consumed by the whole tree", "Vom ganzen Baum verbrauchte CPU-Sekunden"),
+    ("Max concurrent processes", "Höchstzahl gleichzeitiger Prozesse"),
+    ("Memory high-water mark", "Speicher-Höchststand"),
+    ("Restarts", "Neustarts"),
+    ("Snapshot diff:", "Schnappschuss-Vergleich:"),
+    ("CPU {} | Memory {} | Processes {}", "CPU {} | Speicher {} | Prozesse {}"),
+    ("Added", "Hinzugekommen"),
+    ("Removed", "Entfernt"),
+    ("Δ Avg CPU", "Δ Ø CPU"),
+    ("Δ Memory", "Δ Speicher"),
+    ("Clear snapshots", "Schnappschüsse löschen"),
+    ("Start over from a new first snapshot", "Mit einem neuen ersten Schnappschuss neu beginnen"),
+    ("💾 Save Image", "💾 Bild speichern"),
+    ("Format:", "Format:"),
+    ("File:", "Datei:"),
+    // Scheduling and priority
+    (
+        "Context switch and page fault rates. Involuntary switches point at CPU \
+         contention, major faults at thrashing.",
+        "Raten der Kontextwechsel und Seitenfehler. Unfreiwillige Wechsel deuten auf \
+         CPU-Konkurrenz, schwere Seitenfehler auf Thrashing.",
+    ),
+    (
+        "CPU time split into user, system and I/O wait, stacked. I/O wait needs delay \
+         accounting (sysctl kernel.task_delayacct=1).",
+        "CPU-Zeit gestapelt nach Benutzer, System und E/A-Wartezeit. Die E/A-Wartezeit \
+         braucht Delay Accounting (sysctl kernel.task_delayacct=1).",
+    ),
+    (
+        "Share of the samples in the time window at each CPU level, telling rare spikes \
+         from a constant load",
+        "Anteil der Messwerte im Zeitfenster je CPU-Stufe, um seltene Spitzen von \
+         Dauerlast zu unterscheiden",
+    ),
+    ("Uptime", "Verfügbarkeit"),
+    ("samples", "Messwerte"),
+    ("suspended", "angehalten"),
+    ("needs elevation", "braucht erhöhte Rechte"),
+    (
+        "Sort the processes by priority to find them",
+        "Prozesse nach Priorität sortieren, um sie zu finden",
+    ),
+    ("in", "in"),
+    ("for", "für"),
+    (
+        "Lower runs first. Windows uses the closest priority class",
+        "Niedriger läuft zuerst. Windows nimmt die nächstgelegene Prioritätsklasse",
+    ),
+    (
+        "Lower runs first. Going below the current value needs root",
+        "Niedriger läuft zuerst. Unter den aktuellen Wert zu gehen braucht root",
+    ),
+    ("Allowed CPUs:", "Erlaubte CPUs:"),
+    ("Nice not changed:", "Nice nicht geändert:"),
+    ("Affinity not changed:", "Affinität nicht geändert:"),
+    ("Nothing changed", "Nichts geändert"),
+    ("Applied", "Angewendet:"),
+    ("None", "Keine"),
+    (
+        "Waiting for a second sample of the switch and fault counters",
+        "Warte auf einen zweiten Messwert der Wechsel- und Seitenfehlerzähler",
+    ),
+    ("Context switches (/s)", "Kontextwechsel (/s)"),
+    ("Page faults (/s)", "Seitenfehler (/s)"),
+    ("eBPF counters unavailable:", "eBPF-Zähler nicht verfügbar:"),
+    ("Syscalls (/s)", "Systemaufrufe (/s)"),
+    ("Syscalls", "Systemaufrufe"),
+    (
+        "Waiting for a second sample of the CPU times",
+        "Warte auf einen zweiten Messwert der CPU-Zeiten",
+    ),
+    ("I/O wait", "E/A-Wartezeit"),
+    ("% of samples", "% der Messwerte"),
+    ("Projection", "Prognose"),
+    ("Limit", "Grenze"),
+    ("Voluntary", "Freiwillig"),
+    ("Involuntary", "Unfreiwillig"),
+    ("Minor", "Leicht"),
+    ("Major", "Schwer"),
+    ("Sent", "Gesendet"),
+    ("Received", "Empfangen"),
+    ("System", "System"),
+    // Sampling, exports and discovery rules
+    (
+        "Drawn as a second line over the raw samples",
+        "Als zweite Linie über den Rohwerten gezeichnet",
+    ),
+    ("Project memory growth over", "Speicherwachstum hochrechnen über"),
+    (
+        "Fit the memory trend and estimate when it runs out",
+        "Den Speichertrend anpassen und abschätzen, wann er ausgeht",
+    ),
+    ("System OOM", "System-OOM"),
+    ("Flag possible leaks growing over", "Mögliche Lecks markieren bei Wachstum über"),
+    (
+        "Badge trees whose memory trend stays above the rate for the whole time",
+        "Bäume markieren, deren Speichertrend die ganze Zeit über der Rate bleibt",
+    ),
+    ("Flight recorder", "Flugschreiber"),
+    (
+        "Append every sample to a log file as it is taken, flushed on every tick, so \
+         the data leading up to a crash is kept",
+        "Jeden Messwert sofort an eine Logdatei anhängen und bei jedem Takt schreiben, \
+         damit die Daten vor einem Absturz erhalten bleiben",
+    ),
+    ("working directory", "Arbeitsverzeichnis"),
+    ("Rotate at", "Rotieren bei"),
+    ("keeping", "behalte"),
+    ("files", "Dateien"),
+    ("Writing to", "Schreibe nach"),
+    ("Push to InfluxDB", "An InfluxDB senden"),
+    (
+        "Send every sample in line protocol to an InfluxDB write endpoint or a Grafana \
+         Live push endpoint",
+        "Jeden Messwert im Line Protocol an einen InfluxDB-Schreibendpunkt oder einen \
+         Grafana-Live-Push-Endpunkt senden",
+    ),
+    ("Authorization:", "Autorisierung:"),
+    ("Measurement:", "Measurement:"),
+    ("Batches of", "Pakete zu"),
+    ("lines", "Zeilen"),
+    ("or every", "oder alle"),
+    ("Per-process lines", "Zeilen pro Prozess"),
+    (
+        "Also send every process of each tree, tagged with its PID and name",
+        "Auch jeden Prozess jedes Baums senden, markiert mit PID und Name",
+    ),
+    ("Nothing sent yet", "Noch nichts gesendet"),
+    ("Update Interval:", "Aktualisierungsintervall:"),
+    ("Time between updates", "Zeit zwischen Aktualisierungen"),
+    ("Retention:", "Aufbewahrung:"),
+    ("points", "Punkte"),
+    ("Samples kept per graph", "Gespeicherte Messwerte pro Diagramm"),
+    ("↩ Revert", "↩ Zurücksetzen"),
+    ("Line Colors:", "Linienfarben:"),
+    ("Children:", "Kindprozesse:"),
+    (
+        "Cycled through by the plots of child processes",
+        "Reihum für die Diagramme der Kindprozesse verwendet",
+    ),
+    ("Display Window:", "Anzeigefenster:"),
+    ("Newest samples plotted", "Gezeichnete neueste Messwerte"),
+    ("Whole history", "Ganzer Verlauf"),
+    ("⏱ Own interval", "⏱ Eigenes Intervall"),
+    (
+        "Sample this identifier at its own rate instead of the global interval",
+        "Diesen Bezeichner in eigenem Takt statt im globalen Intervall messen",
+    ),
+    ("Guides", "Hilfslinien"),
+    ("Guide", "Hilfslinie"),
+    ("➕ Add guide", "➕ Hilfslinie hinzufügen"),
+    (
+        "Thresholds of enabled alert rules are drawn too",
+        "Schwellen aktiver Alarmregeln werden ebenfalls gezeichnet",
+    ),
+    (
+        "Sampling cost grows with the number of processes on the system",
+        "Der Messaufwand wächst mit der Zahl der Prozesse im System",
+    ),
+    ("Child Discovery", "Kindprozess-Erkennung"),
+    ("Identifier:", "Bezeichner:"),
+    ("Name globs:", "Namensmuster:"),
+    ("PIDs:", "PIDs:"),
+    ("Direct", "Direkte"),
+    ("Full tree", "Ganzer Baum"),
+    ("Depth", "Tiefe"),
+    ("Parents:", "Eltern:"),
+    ("Include parent chain", "Elternkette einschließen"),
+    (
+        "Also monitor the processes that started the matched ones",
+        "Auch die Prozesse überwachen, die die gefundenen gestartet haben",
+    ),
+    ("Add rule", "Regel hinzufügen"),
+    ("Watch Rules", "Beobachtungsregeln"),
+    (
+        "Processes whose name or command line matches are monitored as they appear, \
+         each as its own pid: entry",
+        "Prozesse, deren Name oder Befehlszeile passt, werden beim Erscheinen \
+         überwacht, jeder als eigener pid:-Eintrag",
+    ),
+    ("removed on exit", "wird beim Beenden entfernt"),
+    ("Pattern:", "Muster:"),
+    (
+        "* matches any run of characters, ? a single one",
+        "* passt auf beliebig viele Zeichen, ? auf genau eines",
+    ),
+    ("Remove on exit", "Beim Beenden entfernen"),
+    (
+        "Drop the entry and its history once the process exited",
+        "Eintrag und Verlauf verwerfen, sobald der Prozess beendet ist",
+    ),
+    ("names", "Namen"),
+    ("all children", "alle Kindprozesse"),
+    ("grandchildren", "Enkelprozesse"),
+    ("depth", "Tiefe"),
+    ("with its parent chain", "mit seiner Elternkette"),
+    ("Sampling every {} ms", "Messung alle {} ms"),
+    (
+        "⚠ The history only spans {} min, raise the retention to detect leaks",
+        "⚠ Der Verlauf umfasst nur {} min, für die Leckerkennung die Aufbewahrung erhöhen",
+    ),
+    (
+        "⚠ Drops the oldest {} points of every graph",
+        "⚠ Verwirft die ältesten {} Punkte jedes Diagramms",
+    ),
+    ("Back to {} ms, {} points", "Zurück zu {} ms, {} Punkte"),
+    (
+        "{} samples/s, history spans {}m {}s, then tree averages up to {}h",
+        "{} Messwerte/s, Verlauf umfasst {}m {}s, danach Baum-Mittelwerte bis zu {}h",
+    ),
+    (
+        "Estimated overhead: {} of one core ({} ms per sample), {} {} of history for {} series",
+        "Geschätzter Aufwand: {} eines Kerns ({} ms pro Messwert), {} {} Verlauf für {} Reihen",
+    ),
+    (
+        "tvis currently uses {} CPU and {} {}. Sampling cost grows with the number of processes \
+         on the system; longer intervals and fewer monitored processes reduce it.",
+        "tvis verbraucht gerade {} CPU und {} {}. Der Messaufwand wächst mit der Zahl der \
+         Prozesse im System; längere Intervalle und weniger überwachte Prozesse senken ihn.",
+    ),
+    ("{}: skip {}", "{}: überspringe {}"),
+];
@@ -0,0 +1,190 @@
+//! Translations of the UI strings and locale-aware number formatting.
+//!
+//! Strings are looked up by their English text, so `tr("Quit")` reads like the
+//! literal it replaces and an untranslated string falls back to English. The
+//! language is global, set once per frame from the settings, like egui's style.
+
+mod de;
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::LazyLock;
+
+static CURRENT: AtomicU8 = AtomicU8::new(Language::English as u8);
+
+static GERMAN: LazyLock<HashMap<&'static str, &'static str>> =
+    LazyLock::new(|| de::STRINGS.iter().copied().collect());
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Language {
+    #[default]
+    English,
+    German,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::German];
+
+    /// Name of the language in itself
+    pub fn label(&self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::German => "Deutsch",
+        }
+    }
+
+    /// Language of the environment (`LC_ALL`, `LC_MESSAGES` or `LANG`), English
+    /// when it isn't translated
+    pub fn detect() -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+                .iter()
+                .filter_map(|name| std::env::var(name).ok())
+                .find(|value| !value.is_empty())
+                .unwrap_or_default();
+            if locale.starts_with("de") {
+                return Language::German;
+            }
+        }
+        Language::English
+    }
+
+    /// `text` in this language, or `text` itself without a translation
+    pub fn translate(&self, text: &'static str) -> &'static str {
+        match self {
+            Language::English => text,
+            Language::German => GERMAN.get(text).copied().unwrap_or(text),
+        }
+    }
+
+    fn decimal_separator(&self) -> char {
+        match self {
+            Language::English => '.',
+            Language::German => ',',
+        }
+    }
+
+    fn group_separator(&self) -> char {
+        match self {
+            Language::English => ',',
+            Language::German => '.',
+        }
+    }
+
+    /// `value` with `decimals` digits and the separators of this language,
+    /// e.g. "1,234.5" in English and "1.234,5" in German
+    pub fn number(&self, value: f64, decimals: usize) -> String {
+        let formatted = format!("{:.*}", decimals, value.abs());
+        let (integer, fraction) = formatted
+            .split_once('.')
+            .unwrap_or((formatted.as_str(), ""));
+        let mut out = String::with_capacity(formatted.len() + integer.len() / 3 + 1);
+        // "-0.0" would read as a negative value
+        if value.is_sign_negative() && formatted.bytes().any(|b| b.is_ascii_digit() && b != b'0') {
+            out.push('-');
+        }
+        for (i, digit) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                out.push(self.group_separator());
+            }
+            out.push(digit);
+        }
+        if !fraction.is_empty() {
+            out.push(self.decimal_separator());
+            out.push_str(fraction);
+        }
+        out
+    }
+
+    /// `value` as a percentage, "12.5%" in English and "12,5 %" in German
+    pub fn percent(&self, value: f64, decimals: usize) -> String {
+        let number = self.number(value, decimals);
+        match self {
+            Language::English => format!("{number}%"),
+            Language::German => format!("{number} %"),
+        }
+    }
+}
+
+/// Makes `language` the one of every following [`tr`] and formatting call
+pub fn set_language(language: Language) {
+    CURRENT.store(language as u8, Ordering::Relaxed);
+}
+
+pub fn language() -> Language {
+    match CURRENT.load(Ordering::Relaxed) {
+        value if value == Language::German as u8 => Language::German,
+        _ => Language::English,
+    }
+}
+
+/// `text` in the current language
+pub fn tr(text: &'static str) -> &'static str {
+    language().translate(text)
+}
+
+/// `template` in the current language with each `{}` replaced by the next of
+/// `args`. Whole sentences are translated this way, so a language can put the
+/// values where its grammar wants them instead of gluing translated fragments.
+pub fn tr_format(template: &'static str, args: &[&dyn std::fmt::Display]) -> String {
+    fill(tr(template), args)
+}
+
+fn fill(template: &str, args: &[&dyn std::fmt::Display]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut parts = template.split("{}");
+    out.push_str(parts.next().unwrap_or_default());
+    for part in parts {
+        if let Some(arg) = args.next() {
+            out.push_str(&arg.to_string());
+        }
+        out.push_str(part);
+    }
+    out
+}
+
+/// `value` with the separators of the current language
+pub fn number(value: impl Into<f64>, decimals: usize) -> String {
+    language().number(value.into(), decimals)
+}
+
+/// `value` as a percentage in the current language
+pub fn percent(value: impl Into<f64>, decimals: usize) -> String {
+    language().percent(value.into(), decimals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_and_strings_follow_the_language() {
+        assert_eq!(Language::English.number(1234567.891, 2), "1,234,567.89");
+        assert_eq!(Language::German.number(1234567.891, 2), "1.234.567,89");
+        assert_eq!(Language::English.number(-1234.0, 0), "-1,234");
+        assert_eq!(Language::English.number(999.96, 1), "1,000.0");
+        // Rounded to zero, the sign would be noise
+        assert_eq!(Language::German.number(-0.01, 1), "0,0");
+        assert_eq!(Language::English.percent(12.5, 1), "12.5%");
+        assert_eq!(Language::German.percent(12.5, 1), "12,5 %");
+
+        assert_eq!(Language::German.translate("Quit"), "Beenden");
+        assert_eq!(Language::English.translate("Quit"), "Quit");
+        // Untranslated strings fall back to English
+        assert_eq!(
+            Language::German.translate("no such string"),
+            "no such string"
+        );
+    }
+
+    #[test]
+    fn templates_take_their_values_in_order() {
+        let template = Language::German.translate("{} ago");
+        assert_eq!(fill(template, &[&"5m 3s"]), "vor 5m 3s");
+        assert_eq!(fill("{} of {} match", &[&2, &7]), "2 of 7 match");
+        // A missing value leaves its slot empty rather than panicking
+        assert_eq!(fill("{} and {}", &[&1]), "1 and ");
+    }
+}
@@ -0,0 +1,116 @@
+use crate::metrics::process::ProcessIdentifier;
+use egui_dock::{DockState, NodeIndex};
+
+/// Content of a dock tab
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+pub enum Tab {
+    Processes,
+    Process(ProcessIdentifier),
+    Settings,
+}
+
+/// Process view popped out of the dock into its own OS window
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+pub struct DetachedView {
+    pub identifier: ProcessIdentifier,
+    pub always_on_top: bool,
+}
+
+/// Change to the layout asked for while the dock is being drawn, applied after it
+#[derive(Debug, Clone)]
+pub enum LayoutRequest {
+    Detach(ProcessIdentifier),
+    Attach(ProcessIdentifier),
+}
+
+/// Dock arrangement of the main window, persisted between sessions
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct Layout {
+    pub dock_state: DockState<Tab>,
+    #[serde(default)]
+    pub detached: Vec<DetachedView>,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self {
+            dock_state: DockState::new(vec![Tab::Processes]),
+            detached: Vec::new(),
+        }
+    }
+}
+
+impl Layout {
+    /// Focuses `tab` if it is already open, otherwise opens it next to the process tabs
+    pub fn open(&mut self, tab: Tab) {
+        if let Some((surface, node, tab_index)) = self.dock_state.find_tab(&tab) {
+            self.dock_state.set_active_tab((surface, node, tab_index));
+            self.dock_state
+                .set_focused_node_and_surface((surface, node));
+            return;
+        }
+
+        let process_leaf = self
+            .dock_state
+            .iter_all_tabs()
+            .find(|(_, tab)| matches!(tab, Tab::Process(_)))
+            .map(|(location, _)| location);
+        if let Some(location) = process_leaf {
+            self.dock_state.set_focused_node_and_surface(location);
+            self.dock_state.push_to_focused_leaf(tab);
+        } else if self
+            .dock_state
+            .main_surface()
+            .root_node()
+            .is_some_and(|node| !node.is_empty())
+        {
+            self.dock_state
+                .main_surface_mut()
+                .split_right(NodeIndex::root(), 0.25, vec![tab]);
+        } else {
+            self.dock_state.push_to_first_leaf(tab);
+        }
+    }
+
+    pub fn close(&mut self, tab: &Tab) {
+        self.dock_state.retain_tabs(|t| t != tab);
+    }
+
+    /// Process shown in the focused tab, if that tab is a process view
+    pub fn focused_process(&mut self) -> Option<ProcessIdentifier> {
+        match self.dock_state.find_active_focused() {
+            Some((_, Tab::Process(identifier))) => Some(identifier.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn is_detached(&self, identifier: &ProcessIdentifier) -> bool {
+        self.detached
+            .iter()
+            .any(|view| &view.identifier == identifier)
+    }
+
+    /// Moves the tab of `identifier` out into its own window
+    pub fn detach(&mut self, identifier: &ProcessIdentifier) {
+        self.close(&Tab::Process(identifier.clone()));
+        if !self.is_detached(identifier) {
+            self.detached.push(DetachedView {
+                identifier: identifier.clone(),
+                always_on_top: false,
+            });
+        }
+    }
+
+    /// Closes the window of `identifier` and brings it back as a tab
+    pub fn attach(&mut self, identifier: &ProcessIdentifier) {
+        self.detached.retain(|view| &view.identifier != identifier);
+        self.open(Tab::Process(identifier.clone()));
+    }
+
+    pub fn apply(&mut self, request: LayoutRequest) {
+        match request {
+            LayoutRequest::Detach(identifier) => self.detach(&identifier),
+            LayoutRequest::Attach(identifier) => self.attach(&identifier),
+        }
+    }
+}
@@ -0,0 +1,22 @@
+use crate::metrics::process::limits::ResourceLimit;
+use crate::metrics::process::ProcessIdentifier;
+
+/// Window editing the OS-enforced limit of one monitored identifier
+#[derive(Default)]
+pub struct LimitEditor {
+    /// Identifier being edited and the limit to apply
+    pub(super) editing: Option<(ProcessIdentifier, ResourceLimit)>,
+    /// Outcome of the last apply or remove
+    pub(super) status: Option<Result<String, String>>,
+}
+
+impl LimitEditor {
+    pub fn open(&mut self, identifier: ProcessIdentifier, current: Option<ResourceLimit>) {
+        let limit = current.unwrap_or(ResourceLimit {
+            cpu_percent: Some(100.0),
+            memory_bytes: None,
+        });
+        self.editing = Some((identifier, limit));
+        self.status = None;
+    }
+}
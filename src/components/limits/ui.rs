@@ -0,0 +1,131 @@
+use super::state::LimitEditor;
+use crate::components::i18n::tr;
+use crate::components::settings::Settings;
+use crate::metrics::process::limits::ResourceLimit;
+use crate::metrics::process::ProcessIdentifier;
+use crate::metrics::Metrics;
+
+impl LimitEditor {
+    /// "Limit" button and the current limit of `identifier`, shown above its plots
+    pub fn show_button(
+        &mut self,
+        ui: &mut egui::Ui,
+        identifier: &ProcessIdentifier,
+        current: Option<ResourceLimit>,
+        settings: &Settings,
+    ) {
+        if cfg!(target_arch = "wasm32") {
+            return;
+        }
+        ui.horizontal(|ui| {
+            if ui
+                .small_button(tr("⛔ Limit"))
+                .on_hover_text(tr(
+                    "Cap the CPU or memory of the whole tree (cgroup or Job Object)",
+                ))
+                .clicked()
+            {
+                self.open(identifier.clone(), current);
+            }
+            if let Some(limit) = current {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    format!("{} {}", tr("Limited to"), describe(&limit, settings)),
+                );
+            }
+        });
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, metrics: &mut Metrics, settings: &Settings) {
+        let Some((identifier, limit)) = &mut self.editing else {
+            return;
+        };
+
+        let unit = settings.memory_unit;
+        let bytes_per_unit = 1.0 / unit.format_value(1.0).0 as f64;
+        let mut open = true;
+        let mut action = None;
+        egui::Window::new(tr("⛔ Resource Limit"))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(identifier.to_string());
+                ui.horizontal(|ui| {
+                    let mut enabled = limit.cpu_percent.is_some();
+                    if ui.checkbox(&mut enabled, tr("CPU")).changed() {
+                        limit.cpu_percent = enabled.then_some(100.0);
+                    }
+                    if let Some(percent) = &mut limit.cpu_percent {
+                        ui.add(
+                            egui::DragValue::new(percent)
+                                .range(1.0..=10_000.0)
+                                .suffix(tr("% of one core")),
+                        );
+                    }
+                });
+                ui.horizontal(|ui| {
+                    let mut enabled = limit.memory_bytes.is_some();
+                    if ui.checkbox(&mut enabled, tr("Memory")).changed() {
+                        limit.memory_bytes = enabled.then_some(1024 * 1024 * 1024);
+                    }
+                    if let Some(bytes) = &mut limit.memory_bytes {
+                        let mut value = *bytes as f64 / bytes_per_unit;
+                        let suffix = format!(" {}", unit.format_value(0.0).1);
+                        if ui
+                            .add(egui::DragValue::new(&mut value).suffix(suffix))
+                            .changed()
+                        {
+                            *bytes = (value * bytes_per_unit) as usize;
+                        }
+                    }
+                });
+                if cfg!(target_os = "linux") {
+                    ui.weak(tr("Needs root or a delegated cgroup v2 hierarchy"));
+                }
+                ui.horizontal(|ui| {
+                    if ui.button(tr("Apply")).clicked() {
+                        action = Some(Some(*limit));
+                    }
+                    if ui.button(tr("Remove limit")).clicked() {
+                        action = Some(None);
+                    }
+                });
+                match &self.status {
+                    Some(Ok(message)) => {
+                        ui.label(message);
+                    }
+                    Some(Err(err)) => {
+                        ui.colored_label(ui.visuals().error_fg_color, err);
+                    }
+                    None => {}
+                }
+            });
+
+        if let Some(action) = action {
+            self.status = Some(match action {
+                Some(limit) => metrics
+                    .set_limit(identifier, limit)
+                    .map(|()| format!("{} {}", tr("Limited to"), describe(&limit, settings))),
+                None => metrics
+                    .remove_limit(identifier)
+                    .map(|()| tr("Limit removed").to_string()),
+            });
+        }
+        if !open {
+            self.editing = None;
+        }
+    }
+}
+
+fn describe(limit: &ResourceLimit, settings: &Settings) -> String {
+    let mut parts = Vec::new();
+    if let Some(percent) = limit.cpu_percent {
+        parts.push(format!("{percent:.0}% CPU"));
+    }
+    if let Some(bytes) = limit.memory_bytes {
+        let (value, unit) = settings.memory_unit.format_value(bytes as f32);
+        parts.push(format!("{value:.1} {unit}"));
+    }
+    parts.join(", ")
+}
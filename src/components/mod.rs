@@ -1,3 +1,16 @@
+pub mod alerts;
+pub mod benchmark;
+pub mod bookmarks;
+pub mod bulk;
+pub mod focus;
+pub mod heavy_hitters;
+pub mod i18n;
+pub mod layout;
+pub mod limits;
+pub mod overlay;
 pub mod process_selector;
 pub mod process_view;
+pub mod profiles;
+pub mod recording;
 pub mod settings;
+pub mod slo;
@@ -0,0 +1,94 @@
+use crate::metrics::Metrics;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+
+/// Size of the window in overlay mode, in points
+pub const OVERLAY_SIZE: egui::Vec2 = egui::vec2(260.0, 64.0);
+
+/// Minimum size of the main window, as set in `main.rs`
+const MAIN_MIN_SIZE: egui::Vec2 = egui::vec2(300.0, 220.0);
+
+/// Size the main window returns to when its previous one is unknown
+const MAIN_DEFAULT_SIZE: egui::Vec2 = egui::vec2(800.0, 600.0);
+
+/// Longest wait of the waker for the next sample
+#[cfg(not(target_arch = "wasm32"))]
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Shrinks the main window into a borderless always-on-top strip with the
+/// figures of the active process, repainted only when a new sample arrives
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+#[serde(default)]
+pub struct MiniOverlay {
+    pub enabled: bool,
+    /// Size of the main window before it shrank into the overlay
+    #[serde(skip)]
+    restore_size: Option<egui::Vec2>,
+    /// Whether the window has the overlay's shape
+    #[serde(skip)]
+    shaped: bool,
+    /// Shared with the waker thread, which repaints while it is set
+    #[serde(skip)]
+    waking: Option<Arc<AtomicBool>>,
+}
+
+impl MiniOverlay {
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+    }
+
+    /// Gives the window the shape of the overlay, or back the one of the main
+    /// window, once after each toggle
+    pub fn reshape(&mut self, ctx: &egui::Context) {
+        if self.enabled == self.shaped {
+            return;
+        }
+        self.shaped = self.enabled;
+        if let Some(waking) = &self.waking {
+            waking.store(self.enabled, Ordering::Relaxed);
+        }
+        if ctx.embed_viewports() {
+            return;
+        }
+        if self.enabled {
+            self.restore_size =
+                ctx.input(|input| input.viewport().inner_rect.map(|rect| rect.size()));
+            ctx.send_viewport_cmd(egui::ViewportCommand::MinInnerSize(OVERLAY_SIZE * 0.5));
+            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(OVERLAY_SIZE));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Decorations(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(
+                egui::WindowLevel::AlwaysOnTop,
+            ));
+        } else {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Decorations(true));
+            ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(
+                egui::WindowLevel::Normal,
+            ));
+            ctx.send_viewport_cmd(egui::ViewportCommand::MinInnerSize(MAIN_MIN_SIZE));
+            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(
+                self.restore_size.take().unwrap_or(MAIN_DEFAULT_SIZE),
+            ));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        }
+    }
+
+    /// Schedules the next repaint for the next sample, instead of every frame
+    pub(super) fn wake_on_samples(&mut self, ctx: &egui::Context, metrics: &Metrics) {
+        #[cfg(target_arch = "wasm32")]
+        ctx.request_repaint_after(metrics.current_interval);
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.waking.is_none() {
+            let waking = Arc::new(AtomicBool::new(self.enabled));
+            self.waking = Some(Arc::clone(&waking));
+            // A handle of its own, woken by every snapshot the worker publishes
+            let (ctx, mut metrics) = (ctx.clone(), metrics.clone());
+            std::thread::spawn(move || loop {
+                if metrics.wait(POLL_INTERVAL) && waking.load(Ordering::Relaxed) {
+                    ctx.request_repaint();
+                }
+            });
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use super::state::MiniOverlay;
+use crate::components::i18n::{number, percent, tr, tr_format};
+use crate::components::process_view::ui::sparkline;
+use crate::components::settings::Settings;
+use crate::metrics::process::ProcessIdentifier;
+use crate::metrics::{Metrics, GENERAL_STATS_PID};
+
+/// Samples drawn in the overlay sparkline
+const SPARKLINE_SAMPLES: usize = 60;
+
+impl MiniOverlay {
+    /// Fills the window with the figures of `identifier`.
+    /// Returns true when the user asked for the full window back.
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        metrics: &Metrics,
+        identifier: Option<&ProcessIdentifier>,
+        settings: &Settings,
+    ) -> bool {
+        self.reshape(ctx);
+        self.wake_on_samples(ctx, metrics);
+
+        let figures = identifier.and_then(|identifier| {
+            let data = metrics.get_process_data(identifier)?;
+            let cpu_count = metrics.monitor.cpu_count();
+            let history = data
+                .genereal
+                .history
+                .get_cpu_history(&GENERAL_STATS_PID)
+                .unwrap_or_default();
+            let cpu: Vec<f32> = history[history.len().saturating_sub(SPARKLINE_SAMPLES)..]
+                .iter()
+                .map(|&cpu| settings.cpu_scale.apply(cpu, cpu_count))
+                .collect();
+            Some((
+                settings.label(identifier),
+                cpu,
+                data.genereal.stats.current_memory,
+            ))
+        });
+
+        let mut leave = false;
+        egui::CentralPanel::default()
+            .frame(egui::Frame::window(&ctx.style()).inner_margin(6.0))
+            .show(ctx, |ui| {
+                // Without decorations, the whole strip moves the window
+                let background = ui.interact(
+                    ui.max_rect(),
+                    ui.id().with("overlay_drag"),
+                    egui::Sense::click_and_drag(),
+                );
+                if background.drag_started() {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::StartDrag);
+                }
+                if background.double_clicked() {
+                    leave = true;
+                }
+                background.on_hover_text(tr(
+                    "Drag to move, double-click to return to the full window",
+                ));
+
+                ui.horizontal(|ui| {
+                    match &figures {
+                        Some((label, _, _)) => ui.strong(label),
+                        None => ui.weak(tr("No process monitored")),
+                    };
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        leave |= ui
+                            .small_button("⤢")
+                            .on_hover_text(tr("Back to the full window"))
+                            .clicked();
+                    });
+                });
+                let Some((_, cpu, memory)) = &figures else {
+                    return;
+                };
+                ui.horizontal(|ui| {
+                    let current = cpu.last().copied().unwrap_or_default();
+                    let (memory, unit) = settings.memory_unit.format_value(*memory as f32);
+                    ui.monospace(tr_format(
+                        "CPU {} Memory {} {}",
+                        &[
+                            &format!("{:>6}", percent(current, 1)),
+                            &number(memory, 1),
+                            &unit,
+                        ],
+                    ));
+                    let peak = cpu.iter().copied().fold(5.0, f32::max);
+                    let width = ui.available_width();
+                    sparkline(ui, cpu, peak, settings.series_colors.cpu, width);
+                });
+            });
+        leave
+    }
+}
@@ -0,0 +1,141 @@
+//! Fuzzy matching of the search box against process names, in the style of
+//! skim/fzf: the query characters must appear in order, and matches on word
+//! starts or in one run score higher than scattered ones.
+
+/// Score of one matched character
+const MATCH: i64 = 16;
+/// Extra score when a character directly follows the previous match
+const CONSECUTIVE: i64 = 15;
+/// Extra score for matching the first character of a word
+const WORD_START: i64 = 10;
+/// Penalty per skipped candidate character between two matches, capped per gap
+const GAP: i64 = 1;
+const MAX_GAP_PENALTY: i64 = 10;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuzzyMatch {
+    pub score: i64,
+    /// Indices of the matched characters of the candidate, ascending
+    pub positions: Vec<usize>,
+}
+
+/// Matches `query` against `candidate`, ignoring case. Whitespace separates
+/// terms that must all match, in any order. An empty query matches everything.
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<FuzzyMatch> {
+    let candidate: Vec<char> = candidate.chars().collect();
+    let lower: Vec<char> = candidate
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+    let mut total = FuzzyMatch {
+        score: 0,
+        positions: Vec::new(),
+    };
+    for term in query.split_whitespace() {
+        let term: Vec<char> = term.chars().flat_map(char::to_lowercase).collect();
+        let best = match_term(&term, &candidate, &lower)?;
+        total.score += best.score;
+        total.positions.extend(best.positions);
+    }
+    total.positions.sort_unstable();
+    total.positions.dedup();
+    Some(total)
+}
+
+/// Best greedy alignment of `term`, trying every occurrence of its first character
+fn match_term(term: &[char], candidate: &[char], lower: &[char]) -> Option<FuzzyMatch> {
+    let first = *term.first()?;
+    lower
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| **c == first)
+        .filter_map(|(start, _)| align(term, candidate, lower, start))
+        .max_by_key(|found| found.score)
+}
+
+fn align(term: &[char], candidate: &[char], lower: &[char], start: usize) -> Option<FuzzyMatch> {
+    let mut positions = Vec::with_capacity(term.len());
+    let mut score = 0;
+    let mut next = start;
+    for c in term {
+        let offset = lower[next..].iter().position(|candidate| candidate == c)?;
+        let position = next + offset;
+        score += MATCH;
+        if is_word_start(candidate, position) {
+            score += WORD_START;
+        }
+        match positions.last() {
+            Some(&previous) if position == previous + 1 => score += CONSECUTIVE,
+            Some(&previous) => {
+                score -= (GAP * (position - previous - 1) as i64).min(MAX_GAP_PENALTY)
+            }
+            None => {}
+        }
+        positions.push(position);
+        next = position + 1;
+    }
+    Some(FuzzyMatch { score, positions })
+}
+
+/// First character, after a separator, or an uppercase letter after a lowercase one
+fn is_word_start(candidate: &[char], position: usize) -> bool {
+    let Some(previous) = position.checked_sub(1).map(|i| candidate[i]) else {
+        return true;
+    };
+    let current = candidate[position];
+    !previous.is_alphanumeric() || (previous.is_lowercase() && current.is_uppercase())
+}
+
+/// `items` that match `query`, best first, with their matched positions
+pub fn rank<T>(
+    query: &str,
+    items: impl IntoIterator<Item = (String, T)>,
+) -> Vec<(String, T, Vec<usize>)> {
+    let mut ranked: Vec<(i64, String, T, Vec<usize>)> = items
+        .into_iter()
+        .filter_map(|(text, item)| {
+            fuzzy_match(query, &text).map(|found| (found.score, text, item, found.positions))
+        })
+        .collect();
+    // Shorter texts first among equal scores, so "chrome" beats "chrome_crashpad"
+    ranked.sort_by(|a, b| {
+        b.0.cmp(&a.0)
+            .then(a.1.len().cmp(&b.1.len()))
+            .then(a.1.cmp(&b.1))
+    });
+    ranked
+        .into_iter()
+        .map(|(_, text, item, positions)| (text, item, positions))
+        .collect()
+}
+
+/// `text` with the characters at `positions` in the strong text color
+pub fn highlighted(ui: &egui::Ui, text: &str, positions: &[usize]) -> egui::text::LayoutJob {
+    let font_id = egui::TextStyle::Button.resolve(ui.style());
+    let normal = egui::TextFormat::simple(font_id.clone(), ui.visuals().text_color());
+    let strong = egui::TextFormat {
+        underline: egui::Stroke::new(1.0, ui.visuals().strong_text_color()),
+        ..egui::TextFormat::simple(font_id, ui.visuals().strong_text_color())
+    };
+    let mut job = egui::text::LayoutJob::default();
+    let mut positions = positions.iter().peekable();
+    let mut run = String::new();
+    let mut run_matched = false;
+    for (i, c) in text.chars().enumerate() {
+        let matched = positions.next_if(|&&position| position == i).is_some();
+        if matched != run_matched && !run.is_empty() {
+            job.append(
+                &run,
+                0.0,
+                if run_matched { &strong } else { &normal }.clone(),
+            );
+            run.clear();
+        }
+        run_matched = matched;
+        run.push(c);
+    }
+    if !run.is_empty() {
+        job.append(&run, 0.0, if run_matched { strong } else { normal });
+    }
+    job
+}
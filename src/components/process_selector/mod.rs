@@ -1,4 +1,5 @@
+mod fuzzy;
 mod state;
 mod ui;
 
-pub use state::ProcessSelector;
+pub use state::{ProcessSelector, SavedSearch, SearchMode};
@@ -1,12 +1,128 @@
-use std::sync::Arc;
+use std::collections::HashMap;
 
-use egui::mutex::RwLock;
+use sysinfo::Pid;
 
-use crate::metrics::Metrics;
+use crate::metrics::process::ProcessIdentifier;
 
-#[derive(Default)]
+#[derive(Default, PartialEq, Clone, Copy, serde::Deserialize, serde::Serialize)]
+pub enum SearchMode {
+    #[default]
+    Name,
+    Pid,
+    Container,
+    Cgroup,
+    /// Windows services and launchd jobs
+    Service,
+    /// Accounts owning processes
+    User,
+}
+
+impl SearchMode {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SearchMode::Name => "Name",
+            SearchMode::Pid => "PID",
+            SearchMode::Container => "Container",
+            SearchMode::Cgroup => "cgroup",
+            SearchMode::Service => "Service",
+            SearchMode::User => "User",
+        }
+    }
+}
+
+/// Order of the candidates below the search box
+#[derive(Default, PartialEq, Clone, Copy, serde::Deserialize, serde::Serialize)]
+pub enum CandidateSort {
+    /// Best match of the search first
+    #[default]
+    Relevance,
+    /// Busiest first
+    Cpu,
+    /// Largest first
+    Memory,
+    Name,
+}
+
+impl CandidateSort {
+    pub const ALL: [CandidateSort; 4] = [
+        CandidateSort::Relevance,
+        CandidateSort::Cpu,
+        CandidateSort::Memory,
+        CandidateSort::Name,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CandidateSort::Relevance => "Relevance",
+            CandidateSort::Cpu => "CPU",
+            CandidateSort::Memory => "Memory",
+            CandidateSort::Name => "Name",
+        }
+    }
+}
+
+/// A named search query shown as a chip in the selector
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+pub struct SavedSearch {
+    pub name: String,
+    pub term: String,
+    pub mode: SearchMode,
+}
+
+/// Candidates of one search mode as of sampler tick `tick`. Listed from `Metrics`
+/// once per tick, so the result list is built every frame without its lock.
+pub(super) struct CandidateSnapshot {
+    pub tick: u64,
+    pub mode: SearchMode,
+    /// Text shown and identifier monitored, in listing order
+    pub candidates: Vec<(String, ProcessIdentifier)>,
+    /// Current CPU and memory in bytes of the candidates that have them
+    pub usage: HashMap<ProcessIdentifier, (f32, u64)>,
+}
+
+#[derive(Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
 pub struct ProcessSelector {
+    #[serde(skip)]
     pub show: bool,
+    #[serde(skip)]
     pub search: String,
-    pub search_by_pid: bool,
+    pub search_mode: SearchMode,
+    pub sort: CandidateSort,
+    pub saved_searches: Vec<SavedSearch>,
+    /// Name typed for the search being saved
+    #[serde(skip)]
+    pub(super) saving: Option<String>,
+    /// Process list from the last explicit scan, used instead of live
+    /// listing while the sampler is in safe mode
+    #[serde(skip)]
+    pub(super) scan_results: Option<Vec<(String, Pid)>>,
+    #[serde(skip)]
+    pub(super) snapshot: Option<CandidateSnapshot>,
+    /// Result picked by Enter, moved with the arrow keys
+    #[serde(skip)]
+    pub(super) highlighted: usize,
+}
+
+impl ProcessSelector {
+    pub(super) fn save_search(&mut self, name: String) {
+        let search = SavedSearch {
+            name,
+            term: self.search.clone(),
+            mode: self.search_mode,
+        };
+        match self
+            .saved_searches
+            .iter_mut()
+            .find(|s| s.name == search.name)
+        {
+            Some(existing) => *existing = search,
+            None => self.saved_searches.push(search),
+        }
+    }
+
+    pub(super) fn apply_search(&mut self, search: &SavedSearch) {
+        self.search = search.term.clone();
+        self.search_mode = search.mode;
+    }
 }
@@ -1,20 +1,88 @@
-use std::sync::{Arc, RwLock};
+use std::collections::HashMap;
 
 use crate::{
-    metrics::{process::ProcessIdentifier, Metrics},
-    ProcessMonitorApp,
+    components::{
+        i18n::{percent, tr, tr_format},
+        process_view::ui::copy_menu,
+        settings::Settings,
+    },
+    metrics::{
+        process::{cgroup, container, service, ProcessIdentifier, ProcessMonitor, COMM_MAX_LEN},
+        Metrics,
+    },
 };
 
-use super::state::ProcessSelector;
+use super::fuzzy;
+use super::state::{CandidateSnapshot, CandidateSort, ProcessSelector, SearchMode};
+
+/// Results listed at once in safe mode, where a scan can match thousands
+const MAX_SCAN_RESULTS: usize = 200;
+/// Command line characters shown after a process in the PID list
+const MAX_CMD_CHARS: usize = 120;
+
+/// One line of the result list, `positions` are the characters matching the search
+struct Entry {
+    text: String,
+    identifier: ProcessIdentifier,
+    positions: Vec<usize>,
+    /// Current CPU and memory in bytes, of every process of a name
+    usage: Option<(f32, u64)>,
+}
+
+impl Entry {
+    fn direct(text: String, identifier: ProcessIdentifier) -> Self {
+        Self {
+            text,
+            identifier,
+            positions: Vec::new(),
+            usage: None,
+        }
+    }
+
+    fn ranked(
+        search: &str,
+        candidates: impl IntoIterator<Item = (String, ProcessIdentifier)>,
+    ) -> Vec<Self> {
+        fuzzy::rank(search, candidates)
+            .into_iter()
+            .map(|(text, identifier, positions)| Self {
+                text,
+                identifier,
+                positions,
+                usage: None,
+            })
+            .collect()
+    }
+}
+
+/// Orders the matches, keeping the ranking among equal ones
+fn sort_entries(entries: &mut [Entry], sort: CandidateSort) {
+    let usage = |entry: &Entry| entry.usage.unwrap_or_default();
+    match sort {
+        CandidateSort::Relevance => {}
+        CandidateSort::Cpu => entries.sort_by(|a, b| usage(b).0.total_cmp(&usage(a).0)),
+        CandidateSort::Memory => entries.sort_by_key(|entry| std::cmp::Reverse(usage(entry).1)),
+        CandidateSort::Name => entries.sort_by_cached_key(|entry| entry.text.to_lowercase()),
+    }
+}
+
+fn shorten(text: &str) -> String {
+    match text.char_indices().nth(MAX_CMD_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
 
 impl ProcessSelector {
     pub fn show(
         &mut self,
         ui: &mut egui::Ui,
-        metrics: Arc<RwLock<Metrics>>,
+        metrics: &Metrics,
+        settings: &Settings,
     ) -> Option<ProcessIdentifier> {
         if !self.show {
-            if ui.button("Add Process").clicked() {
+            self.snapshot = None;
+            if ui.button(tr("Add Process")).clicked() {
                 self.show = true;
                 self.search.clear();
             }
@@ -23,86 +91,469 @@ impl ProcessSelector {
 
         let mut new_proc = None;
 
-        egui::Window::new("Select Process")
+        egui::Window::new(tr("Select Process"))
             .collapsible(false)
             .resizable(true)
             .show(ui.ctx(), |ui| {
+                // Taken before the search box sees them, which would move its cursor
+                let (up, down) = ui.input_mut(|input| {
+                    (
+                        input.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+                        input.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+                    )
+                });
+                let mut enter = false;
+                let mut search_response = None;
                 ui.horizontal(|ui| {
-                    ui.label("Search:");
+                    ui.label(tr("Search:"));
                     let response = ui.text_edit_singleline(&mut self.search);
-                    if ui.small_button("❌").clicked() {
-                        self.show = false;
+                    search_response = Some(response.clone());
+                    if response.changed() {
+                        self.highlighted = 0;
+                    }
+                    // Only from the search box, which gives up its focus on Enter,
+                    // not while a saved search is being named
+                    enter = self.saving.is_none()
+                        && (response.has_focus() || response.lost_focus())
+                        && ui.input(|input| input.key_pressed(egui::Key::Enter));
+                    if self.saving.is_none() && ui.memory(|memory| memory.focused().is_none()) {
+                        response.request_focus();
+                    }
+                    if ui
+                        .add_enabled(!self.search.is_empty(), egui::Button::new("⭐").small())
+                        .on_hover_text(tr("Save this search"))
+                        .clicked()
+                    {
+                        self.saving = Some(self.search.clone());
                     }
-                    if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                    if ui.small_button("❌").clicked() {
                         self.show = false;
                     }
                 });
+                // Closes from any focused widget, not only the search box
+                if self.saving.is_none() && ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                    self.show = false;
+                }
+
+                self.show_saved_searches(ui);
 
                 ui.horizontal(|ui| {
-                    ui.radio_value(&mut self.search_by_pid, false, "By Name");
-                    ui.radio_value(&mut self.search_by_pid, true, "By PID");
+                    ui.radio_value(&mut self.search_mode, SearchMode::Name, tr("By Name"));
+                    ui.radio_value(&mut self.search_mode, SearchMode::Pid, tr("By PID"));
+                    ui.radio_value(
+                        &mut self.search_mode,
+                        SearchMode::Container,
+                        tr("By Container"),
+                    );
+                    if cfg!(target_os = "linux") {
+                        ui.radio_value(&mut self.search_mode, SearchMode::Cgroup, tr("By cgroup"))
+                            .on_hover_text(tr("A cgroup path or systemd unit, e.g. nginx.service"));
+                    }
+                    ui.radio_value(&mut self.search_mode, SearchMode::User, tr("By User"))
+                        .on_hover_text(tr("Everything an account runs, by user name or id"));
+                    if cfg!(any(windows, target_os = "macos")) {
+                        ui.radio_value(
+                            &mut self.search_mode,
+                            SearchMode::Service,
+                            tr("By Service"),
+                        )
+                        .on_hover_text(tr(if cfg!(windows) {
+                            "A Windows service by its service name, e.g. Spooler"
+                        } else {
+                            "A launchd job by its label, e.g. com.apple.Spotlight"
+                        }));
+                    }
                 });
 
+                if matches!(self.search_mode, SearchMode::Name | SearchMode::Pid) {
+                    ui.horizontal(|ui| {
+                        ui.label(tr("Sort by:"));
+                        for sort in CandidateSort::ALL {
+                            ui.selectable_value(&mut self.sort, sort, tr(sort.label()));
+                        }
+                    });
+                }
+
+                let (safe_mode, process_count, cpu_count, tick) = (
+                    metrics.safe_mode,
+                    metrics.system_process_count,
+                    metrics.monitor.cpu_count(),
+                    metrics.tick,
+                );
+                let usage_text = |(cpu, memory): (f32, u64)| {
+                    let (memory, unit) = settings.memory_unit.format_value(memory as f32);
+                    format!(
+                        "{} · {memory:.1} {unit}",
+                        percent(settings.cpu_scale.apply(cpu, cpu_count), 1)
+                    )
+                };
+                let on_demand = safe_mode
+                    && !matches!(
+                        self.search_mode,
+                        SearchMode::Container
+                            | SearchMode::Cgroup
+                            | SearchMode::Service
+                            | SearchMode::User
+                    );
+                if on_demand {
+                    ui.horizontal(|ui| {
+                        ui.colored_label(
+                            ui.visuals().warn_fg_color,
+                            tr_format("⚠ {} processes, listing on demand", &[&process_count]),
+                        );
+                        if ui
+                            .button(tr("🔍 Search"))
+                            .on_hover_text(tr("Scan all processes once"))
+                            .clicked()
+                        {
+                            self.scan_results = Some(ProcessMonitor::list_processes());
+                        }
+                    });
+                }
+
+                let search = self.search.trim();
+                if self.search_mode == SearchMode::Name
+                    && cfg!(target_os = "linux")
+                    && search.len() > COMM_MAX_LEN
+                {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        tr_format(
+                            "⚠ Linux truncates process names to {} characters, \"{}\" \
+                             will be matched against the executable name",
+                            &[&COMM_MAX_LEN, &search],
+                        ),
+                    );
+                }
+
                 ui.separator();
 
+                let (entries, truncated) = if on_demand {
+                    self.scan_entries()
+                } else {
+                    self.refresh_snapshot(metrics, tick);
+                    (self.entries(), false)
+                };
+                if entries.is_empty() {
+                    self.highlighted = 0;
+                } else {
+                    if down {
+                        self.highlighted += 1;
+                    }
+                    if up {
+                        self.highlighted = self.highlighted.saturating_sub(1);
+                    }
+                    self.highlighted = self.highlighted.min(entries.len() - 1);
+                }
+                // Focus stays in the search box while the arrows move the
+                // highlight, so the box tells screen readers what is highlighted
+                if let (Some(response), Some(entry)) =
+                    (&search_response, entries.get(self.highlighted))
+                {
+                    response.widget_info(|| {
+                        let mut info = egui::WidgetInfo::text_edit(true, "", &self.search);
+                        info.label = Some(tr_format(
+                            "Search, {} highlighted, {} of {}, Enter to monitor",
+                            &[&entry.text, &(self.highlighted + 1), &entries.len()],
+                        ));
+                        info
+                    });
+                }
+                if enter {
+                    if let Some(entry) = entries.get(self.highlighted) {
+                        new_proc = Some(entry.identifier.clone());
+                        self.show = false;
+                    }
+                }
+
                 egui::ScrollArea::vertical()
                     .max_height(300.0)
                     .show(ui, |ui| {
-                        let search_term = self.search.to_lowercase();
-                        if self.search_by_pid {
-                            // Search by PID
-                            {
-                                let monitor = &metrics.read().unwrap().monitor;
-                                if !search_term.is_empty() {
-                                    let proc = ProcessIdentifier::from(search_term.as_str());
-                                    if let Some(pid) = &proc.to_pid() {
-                                        if let Some(process) = monitor.get_process_by_pid(pid) {
-                                            let display_text = format!(
-                                                "{} (PID: {})",
-                                                process.name().to_string_lossy(),
-                                                pid
-                                            );
-                                            if ui.button(&display_text).clicked() {
-                                                new_proc = Some(proc);
-                                                self.show = false;
-                                            }
-                                        }
-                                    }
-                                }
-
-                                // Show all processes with PIDs
-                                for (name, pid) in monitor.get_all_processes_with_pid() {
-                                    let display_text = format!("{} (PID: {})", name, pid);
-                                    if search_term.is_empty()
-                                        || display_text.to_lowercase().contains(&search_term)
-                                        || pid.to_string().contains(&search_term)
-                                    {
-                                        if ui.button(&display_text).clicked() {
-                                            new_proc = Some(ProcessIdentifier::Pid(pid));
-                                            self.show = false;
-                                        }
+                        if on_demand && self.search.trim().is_empty() {
+                            ui.weak(tr("Type a name or PID, then Search"));
+                        }
+                        for (i, entry) in entries.iter().enumerate() {
+                            let text = fuzzy::highlighted(ui, &entry.text, &entry.positions);
+                            let usage = entry.usage.map(usage_text);
+                            let response = ui
+                                .horizontal(|ui| {
+                                    let response = ui.selectable_label(i == self.highlighted, text);
+                                    if let Some(usage) = &usage {
+                                        ui.weak(usage);
                                     }
-                                }
+                                    response
+                                })
+                                .inner;
+                            response.widget_info(|| {
+                                egui::WidgetInfo::selected(
+                                    egui::WidgetType::SelectableLabel,
+                                    true,
+                                    i == self.highlighted,
+                                    tr_format(
+                                        "Monitor {}, {} of {}",
+                                        &[
+                                            &usage.as_ref().map_or(entry.text.clone(), |usage| {
+                                                format!("{}, {usage}", entry.text)
+                                            }),
+                                            &(i + 1),
+                                            &entries.len(),
+                                        ],
+                                    ),
+                                )
+                            });
+                            if (up || down) && i == self.highlighted {
+                                response.scroll_to_me(None);
                             }
-                        } else {
-                            // Original search by name
-                            let monitor = &metrics.read().unwrap().monitor;
-                            let processes = monitor.get_all_processes();
-                            for process_name in processes {
-                                if search_term.is_empty()
-                                    || process_name.to_lowercase().contains(&search_term)
-                                {
-                                    if ui.button(&process_name).clicked() {
-                                        let identifier = ProcessIdentifier::Name(process_name);
-                                        new_proc = Some(identifier);
-                                        self.show = false;
-                                    }
-                                }
+                            if response.clicked() {
+                                new_proc = Some(entry.identifier.clone());
+                                self.show = false;
+                            }
+                            if let Some(pid) = entry.identifier.to_pid() {
+                                copy_menu(&response, pid);
                             }
                         }
-                    });
+                        if truncated {
+                            ui.weak(tr("More matches, refine the search"));
+                        }
+                    })
+                    .inner
             });
 
         new_proc
     }
+
+    /// Lists the candidates of the current search mode again once the sampler
+    /// ticked or the mode changed
+    fn refresh_snapshot(&mut self, metrics: &Metrics, tick: u64) {
+        if self
+            .snapshot
+            .as_ref()
+            .is_some_and(|snapshot| snapshot.tick == tick && snapshot.mode == self.search_mode)
+        {
+            return;
+        }
+        let monitor = &metrics.monitor;
+        let mut usage = HashMap::new();
+        let candidates: Vec<(String, ProcessIdentifier)> = match self.search_mode {
+            SearchMode::Name => {
+                for (name, name_usage) in monitor.usage_by_name() {
+                    usage.insert(ProcessIdentifier::Name(name), name_usage);
+                }
+                monitor
+                    .get_all_processes()
+                    .into_iter()
+                    .map(|name| (name.clone(), ProcessIdentifier::Name(name)))
+                    .collect()
+            }
+            SearchMode::Pid => monitor
+                .get_all_processes_with_pid()
+                .into_iter()
+                .map(|(name, pid)| {
+                    let process = monitor.get_process_by_pid(&pid);
+                    let cmd = process
+                        .map(|process| {
+                            process
+                                .cmd()
+                                .iter()
+                                .map(|arg| arg.to_string_lossy())
+                                .collect::<Vec<_>>()
+                                .join(" ")
+                        })
+                        .unwrap_or_default();
+                    if let Some(process) = process {
+                        usage.insert(
+                            ProcessIdentifier::Pid(pid),
+                            (process.cpu_usage(), process.memory()),
+                        );
+                    }
+                    let text = format!("{name} (PID: {pid}) {}", shorten(&cmd));
+                    (text.trim_end().to_string(), ProcessIdentifier::Pid(pid))
+                })
+                .collect(),
+            SearchMode::Container => monitor
+                .get_all_containers()
+                .into_iter()
+                .map(|(id, name)| {
+                    let text = format!("{} ({})", name, &id[..container::SHORT_ID_LEN]);
+                    (text, ProcessIdentifier::Container(name))
+                })
+                .collect(),
+            SearchMode::Cgroup => cgroup::running_services()
+                .into_iter()
+                .map(|path| (path.clone(), ProcessIdentifier::Cgroup(path)))
+                .collect(),
+            SearchMode::User => monitor
+                .get_all_users()
+                .into_iter()
+                .map(|(name, count)| {
+                    let text = tr_format("{} ({} processes)", &[&name, &count]);
+                    (text, ProcessIdentifier::User(name))
+                })
+                .collect(),
+            SearchMode::Service => service::running_services()
+                .into_iter()
+                .map(|service| {
+                    let text = if service.display_name == service.name {
+                        service.name.clone()
+                    } else {
+                        format!("{} ({})", service.display_name, service.name)
+                    };
+                    (text, ProcessIdentifier::Service(service.name))
+                })
+                .collect(),
+        };
+        self.snapshot = Some(CandidateSnapshot {
+            tick,
+            mode: self.search_mode,
+            candidates,
+            usage,
+        });
+    }
+
+    /// Result list of the current search mode: what was typed, when it can be
+    /// monitored as is, followed by the fuzzy matches of the snapshot, best first
+    fn entries(&self) -> Vec<Entry> {
+        let Some(snapshot) = &self.snapshot else {
+            return Vec::new();
+        };
+        let search = self.search.trim();
+        let direct = (!search.is_empty())
+            .then(|| match self.search_mode {
+                SearchMode::Name => snapshot
+                    .candidates
+                    .iter()
+                    .all(|(name, _)| name != search)
+                    .then(|| {
+                        Entry::direct(
+                            tr_format("Monitor \"{}\"", &[&search]),
+                            ProcessIdentifier::Name(search.to_string()),
+                        )
+                    }),
+                SearchMode::Pid => None,
+                SearchMode::Container => Some(Entry::direct(
+                    format!("container:{search}"),
+                    ProcessIdentifier::Container(search.to_string()),
+                )),
+                SearchMode::Cgroup => {
+                    // Paths contain a slash, anything else is taken as a unit name
+                    let text = if search.contains('/') {
+                        format!("cgroup:{search}")
+                    } else {
+                        format!("unit:{search}")
+                    };
+                    let identifier = ProcessIdentifier::from(text.as_str());
+                    Some(Entry::direct(text, identifier))
+                }
+                SearchMode::User => Some(Entry::direct(
+                    format!("user:{search}"),
+                    ProcessIdentifier::User(search.to_string()),
+                )),
+                SearchMode::Service => Some(Entry::direct(
+                    format!("service:{search}"),
+                    ProcessIdentifier::Service(search.to_string()),
+                )),
+            })
+            .flatten();
+        let mut ranked = Entry::ranked(search, snapshot.candidates.iter().cloned());
+        for entry in &mut ranked {
+            entry.usage = snapshot.usage.get(&entry.identifier).copied();
+        }
+        sort_entries(&mut ranked, self.sort);
+        direct.into_iter().chain(ranked).collect()
+    }
+
+    /// Safe mode listing: matches from the last explicit scan, never the live table.
+    /// Also returns whether matches were left out.
+    fn scan_entries(&self) -> (Vec<Entry>, bool) {
+        let search = self.search.trim();
+        if search.is_empty() {
+            return (Vec::new(), false);
+        }
+        let direct = match self.search_mode {
+            SearchMode::Pid => search
+                .parse::<usize>()
+                .ok()
+                .map(|pid| ProcessIdentifier::Pid(pid.into())),
+            _ => Some(ProcessIdentifier::Name(search.to_string())),
+        };
+        let mut entries: Vec<Entry> = direct
+            .map(|identifier| {
+                Entry::direct(tr_format("Monitor \"{}\"", &[&identifier]), identifier)
+            })
+            .into_iter()
+            .collect();
+        let Some(results) = &self.scan_results else {
+            return (entries, false);
+        };
+        let candidates = results.iter().map(|(name, pid)| {
+            let identifier = match self.search_mode {
+                SearchMode::Pid => ProcessIdentifier::Pid(*pid),
+                _ => ProcessIdentifier::Name(name.clone()),
+            };
+            (format!("{} (PID: {})", name, pid), identifier)
+        });
+        let mut ranked = Entry::ranked(search, candidates);
+        sort_entries(&mut ranked, self.sort);
+        let truncated = ranked.len() > MAX_SCAN_RESULTS;
+        entries.extend(ranked.into_iter().take(MAX_SCAN_RESULTS));
+        (entries, truncated)
+    }
+
+    fn show_saved_searches(&mut self, ui: &mut egui::Ui) {
+        if let Some(name) = &mut self.saving {
+            let mut save = None;
+            ui.horizontal(|ui| {
+                ui.label(tr("Save as:"));
+                let response = ui.text_edit_singleline(name);
+                response.request_focus();
+                if ui.small_button(tr("Save")).clicked()
+                    || (response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)))
+                {
+                    save = Some(Some(name.trim().to_string()));
+                }
+                if ui.small_button(tr("Cancel")).clicked()
+                    || ui.input(|i| i.key_pressed(egui::Key::Escape))
+                {
+                    save = Some(None);
+                }
+            });
+            match save {
+                Some(Some(name)) if !name.is_empty() => {
+                    self.save_search(name);
+                    self.saving = None;
+                }
+                Some(_) => self.saving = None,
+                None => {}
+            }
+        }
+
+        if self.saved_searches.is_empty() {
+            return;
+        }
+        let mut to_apply = None;
+        let mut to_remove = None;
+        ui.horizontal_wrapped(|ui| {
+            for (i, search) in self.saved_searches.iter().enumerate() {
+                let active = self.search == search.term && self.search_mode == search.mode;
+                let response = ui
+                    .selectable_label(active, &search.name)
+                    .on_hover_text(format!("{}: {}", tr(search.mode.label()), search.term));
+                if response.clicked() {
+                    to_apply = Some(search.clone());
+                }
+                response.context_menu(|ui| {
+                    if ui.button(tr("Delete")).clicked() {
+                        to_remove = Some(i);
+                        ui.close_menu();
+                    }
+                });
+            }
+        });
+        if let Some(search) = to_apply {
+            self.apply_search(&search);
+        }
+        if let Some(i) = to_remove {
+            self.saved_searches.remove(i);
+        }
+    }
 }
@@ -0,0 +1,164 @@
+use super::state::{ImageExport, ImageFormat, PlotPanel};
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use std::error::Error;
+use std::sync::Once;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const WIDTH: u32 = 1024;
+const PANEL_HEIGHT: u32 = 480;
+/// Space for the overview title above stacked panels
+const HEADER_HEIGHT: u32 = 48;
+const FONT: &str = "sans-serif";
+
+/// Renders the exported plot to `export.path`
+pub fn save_image(export: &ImageExport) -> Result<(), String> {
+    register_font();
+    let size = (WIDTH, image_height(export));
+    let result = match export.format {
+        ImageFormat::Png => draw(
+            BitMapBackend::new(&export.path, size).into_drawing_area(),
+            export,
+        ),
+        ImageFormat::Svg => draw(
+            SVGBackend::new(&export.path, size).into_drawing_area(),
+            export,
+        ),
+    };
+    result.map_err(|err| err.to_string())
+}
+
+/// Gives plotters egui's bundled font so rendering doesn't depend on system fonts
+fn register_font() {
+    static REGISTER: Once = Once::new();
+    REGISTER.call_once(|| {
+        let fonts = egui::FontDefinitions::default();
+        let Some(data) = fonts.font_data.get("Ubuntu-Light") else {
+            return;
+        };
+        let bytes: &'static [u8] = match &data.font {
+            std::borrow::Cow::Borrowed(bytes) => bytes,
+            std::borrow::Cow::Owned(bytes) => Box::leak(bytes.clone().into_boxed_slice()),
+        };
+        let _ = plotters::style::register_font(FONT, FontStyle::Normal, bytes);
+    });
+}
+
+fn image_height(export: &ImageExport) -> u32 {
+    let panels = export.panels.len().max(1) as u32;
+    if panels > 1 {
+        HEADER_HEIGHT + panels * PANEL_HEIGHT
+    } else {
+        PANEL_HEIGHT
+    }
+}
+
+fn draw<DB>(root: DrawingArea<DB, Shift>, export: &ImageExport) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    root.fill(&WHITE)?;
+
+    let body = if export.panels.len() > 1 {
+        let (header, body) = root.split_vertically(HEADER_HEIGHT);
+        header.titled(
+            &format!("{} at {}", export.title, format_utc(export.taken_at)),
+            (FONT, 28),
+        )?;
+        body
+    } else {
+        root.clone()
+    };
+    let areas = body.split_evenly((export.panels.len().max(1), 1));
+    for (area, panel) in areas.iter().zip(&export.panels) {
+        draw_panel(area, panel, export)?;
+    }
+    root.present()?;
+    Ok(())
+}
+
+fn draw_panel<DB>(
+    area: &DrawingArea<DB, Shift>,
+    panel: &PlotPanel,
+    export: &ImageExport,
+) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    let interval = panel.interval.as_secs_f64();
+    let span = panel.values.len().saturating_sub(1) as f64 * interval;
+    let max_y = panel.values.iter().copied().fold(0.0, f64::max).max(1.0) * 1.1;
+    let end = export.taken_at;
+    let start = end - Duration::from_secs_f64(span);
+
+    let mut chart = ChartBuilder::on(area)
+        .caption(&panel.title, (FONT, 24))
+        .margin(16)
+        .x_label_area_size(48)
+        .y_label_area_size(72)
+        .build_cartesian_2d(-span.max(interval)..0.0, 0.0..max_y)?;
+    chart
+        .configure_mesh()
+        .x_desc(format!(
+            "Seconds before {} (from {})",
+            format_utc(end),
+            format_utc(start)
+        ))
+        .y_desc(&panel.y_label)
+        .label_style((FONT, 14))
+        .draw()?;
+
+    let first = panel.values.len().saturating_sub(1) as f64;
+    chart.draw_series(LineSeries::new(
+        panel
+            .values
+            .iter()
+            .enumerate()
+            .map(|(i, &value)| ((i as f64 - first) * interval, value)),
+        BLUE.stroke_width(2),
+    ))?;
+
+    for marker in &panel.markers {
+        let x = -(marker.samples_ago as f64) * interval;
+        let color = marker.color.unwrap_or(egui::Color32::GRAY);
+        let color = RGBColor(color.r(), color.g(), color.b());
+        chart.draw_series(LineSeries::new([(x, 0.0), (x, max_y)], color))?;
+        if !marker.label.is_empty() {
+            chart.draw_series(std::iter::once(Text::new(
+                marker.label.clone(),
+                (x, max_y),
+                (FONT, 14).into_font().color(&color),
+            )))?;
+        }
+    }
+    Ok(())
+}
+
+/// `YYYY-MM-DD HH:MM:SS UTC`, without pulling in a date crate
+fn format_utc(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let (days, day_seconds) = (seconds / 86_400, seconds % 86_400);
+
+    // Civil date from days since the epoch (Howard Hinnant's algorithm)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02} UTC",
+        day_seconds / 3600,
+        day_seconds / 60 % 60,
+        day_seconds % 60
+    )
+}
@@ -0,0 +1,185 @@
+use crate::metrics::process::{ProcessData, ProcessInfo};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use sysinfo::Pid;
+
+/// Snapshots kept per identifier, the two compared by the diff
+pub const KEPT_INSPECTIONS: usize = 2;
+
+/// Copy of the data of an identifier frozen by "Snapshot now"
+#[derive(Debug, Clone)]
+pub struct Inspection {
+    pub taken_at: Instant,
+    pub data: ProcessData,
+}
+
+impl Inspection {
+    pub fn new(data: &ProcessData) -> Self {
+        Self {
+            taken_at: Instant::now(),
+            data: data.clone(),
+        }
+    }
+}
+
+/// A process found in both snapshots
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessDelta {
+    pub pid: Pid,
+    pub name: String,
+    /// Change of the average CPU over the retained history, in raw percent
+    pub avg_cpu: f32,
+    pub memory: i64,
+}
+
+/// What changed in a tree between two snapshots, threads left out
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotDiff {
+    pub elapsed: Duration,
+    /// Samples taken between the two snapshots
+    pub samples: u64,
+    pub added: Vec<(Pid, String)>,
+    pub removed: Vec<(Pid, String)>,
+    /// Largest CPU change first
+    pub changed: Vec<ProcessDelta>,
+    pub cpu: f32,
+    pub memory: i64,
+    pub processes: i64,
+}
+
+impl SnapshotDiff {
+    pub fn new(before: &Inspection, after: &Inspection) -> Self {
+        let processes = |data: &ProcessData| -> HashMap<Pid, ProcessInfo> {
+            data.processes_stats
+                .iter()
+                .filter(|process| !process.is_thread)
+                .map(|process| (process.pid, process.clone()))
+                .collect()
+        };
+        let old = processes(&before.data);
+        let new = processes(&after.data);
+        let named = |process: &ProcessInfo| (process.pid, process.name.to_string());
+
+        let mut added: Vec<_> = new
+            .values()
+            .filter(|process| !old.contains_key(&process.pid))
+            .map(named)
+            .collect();
+        let mut removed: Vec<_> = old
+            .values()
+            .filter(|process| !new.contains_key(&process.pid))
+            .map(named)
+            .collect();
+        added.sort();
+        removed.sort();
+
+        let mut changed: Vec<ProcessDelta> = new
+            .values()
+            .filter_map(|process| {
+                let previous = old.get(&process.pid)?;
+                Some(ProcessDelta {
+                    pid: process.pid,
+                    name: process.name.to_string(),
+                    avg_cpu: process.avg_cpu - previous.avg_cpu,
+                    memory: process.current_memory as i64 - previous.current_memory as i64,
+                })
+            })
+            .collect();
+        changed.sort_by(|a, b| {
+            b.avg_cpu
+                .abs()
+                .total_cmp(&a.avg_cpu.abs())
+                .then(b.memory.abs().cmp(&a.memory.abs()))
+                .then(a.pid.cmp(&b.pid))
+        });
+
+        let (old_stats, new_stats) = (&before.data.genereal.stats, &after.data.genereal.stats);
+        Self {
+            elapsed: after.taken_at.saturating_duration_since(before.taken_at),
+            samples: after
+                .data
+                .session
+                .samples
+                .saturating_sub(before.data.session.samples),
+            added,
+            removed,
+            changed,
+            cpu: new_stats.current_cpu - old_stats.current_cpu,
+            memory: new_stats.current_memory as i64 - old_stats.current_memory as i64,
+            processes: new_stats.process_count as i64 - old_stats.process_count as i64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::process::{ProcessData, ProcessInfo};
+
+    fn process_info(pid: u32, name: &str, cpu: f32, memory: usize) -> ProcessInfo {
+        ProcessInfo {
+            name: name.into(),
+            pid: Pid::from_u32(pid),
+            parent_pid: None,
+            is_thread: false,
+            current_cpu: cpu,
+            avg_cpu: cpu,
+            peak_cpu: cpu,
+            current_memory: memory,
+            peak_memory: memory,
+            avg_memory: memory,
+            os_peak_memory: None,
+            gpu: Default::default(),
+            sched: None,
+            suspended: false,
+            restricted: false,
+            scheduling: None,
+        }
+    }
+
+    #[test]
+    fn snapshot_diff_lists_added_removed_and_changed_processes() {
+        let snapshot = |processes: Vec<ProcessInfo>, samples: u64| {
+            let mut data = ProcessData::default();
+            data.session.samples = samples;
+            for process in &processes {
+                data.genereal.stats.current_cpu += process.current_cpu;
+                data.genereal.stats.current_memory += process.current_memory;
+                data.genereal.stats.process_count += 1;
+            }
+            data.processes_stats = processes;
+            Inspection::new(&data)
+        };
+        let mut thread = process_info(4, "worker", 50.0, 0);
+        thread.is_thread = true;
+        let before = snapshot(
+            vec![
+                process_info(1, "main", 10.0, 100),
+                process_info(2, "old", 5.0, 50),
+                process_info(3, "idle", 0.0, 10),
+            ],
+            10,
+        );
+        let after = snapshot(
+            vec![
+                process_info(1, "main", 30.0, 80),
+                process_info(3, "idle", 0.0, 10),
+                process_info(5, "new", 1.0, 20),
+                thread,
+            ],
+            25,
+        );
+        let diff = SnapshotDiff::new(&before, &after);
+        assert_eq!(diff.samples, 15);
+        assert_eq!(diff.added, vec![(Pid::from_u32(5), "new".to_string())]);
+        assert_eq!(diff.removed, vec![(Pid::from_u32(2), "old".to_string())]);
+        assert_eq!(diff.changed.len(), 2);
+        assert_eq!(diff.changed[0].pid, Pid::from_u32(1));
+        assert_eq!(diff.changed[0].avg_cpu, 20.0);
+        assert_eq!(diff.changed[0].memory, -20);
+        assert_eq!(diff.changed[1].memory, 0);
+        assert_eq!(diff.cpu, 66.0);
+        assert_eq!(diff.memory, -50);
+        assert_eq!(diff.processes, 1);
+    }
+}
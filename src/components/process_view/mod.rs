@@ -1,3 +1,7 @@
+#[cfg(not(target_arch = "wasm32"))]
+pub mod export;
+pub mod inspection;
+pub mod report;
 pub mod state;
 pub mod ui;
 
@@ -0,0 +1,318 @@
+use crate::components::settings::Settings;
+use crate::metrics::process::{Availability, ProcessData, ProcessIdentifier, RunSummary};
+use crate::metrics::GENERAL_STATS_PID;
+use std::fmt::Write;
+use std::time::Duration;
+
+/// Number of children listed in the copied stats
+pub const TOP_CHILDREN: usize = 10;
+
+/// CPU levels, in the displayed scale, whose time above is reported in the summary
+pub const CPU_THRESHOLDS: [f32; 3] = [50.0, 80.0, 95.0];
+
+/// Totals over the retained history of an identifier
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistorySummary {
+    /// Time covered by the retained samples
+    pub duration: Duration,
+    /// CPU time consumed by the whole tree
+    pub cpu_seconds: f64,
+    /// Time spent above each of [`CPU_THRESHOLDS`]
+    pub time_above: [Duration; 3],
+    pub max_processes: u32,
+    pub peak_memory: usize,
+    /// How long ago `peak_memory` was sampled
+    pub peak_memory_ago: Duration,
+    /// Times a process matched again after none did
+    pub restarts: usize,
+}
+
+impl HistorySummary {
+    /// `cpu` converts the raw CPU of a sample to the displayed scale, which
+    /// the thresholds are compared against
+    pub fn new(
+        process_data: &ProcessData,
+        availability: Option<&Availability>,
+        interval: Duration,
+        cpu: impl Fn(f32) -> f32,
+    ) -> Self {
+        let history = &process_data.genereal.history;
+        let cpu_history = history
+            .get_cpu_history(&GENERAL_STATS_PID)
+            .unwrap_or_default();
+        let memory_history = history
+            .get_memory_history(&GENERAL_STATS_PID)
+            .unwrap_or_default();
+        let processes = history
+            .get_count_history(&GENERAL_STATS_PID)
+            .map(|(processes, _)| processes)
+            .unwrap_or_default();
+
+        let mut time_above = [Duration::ZERO; 3];
+        for value in cpu_history.iter().map(|value| cpu(*value)) {
+            for (threshold, time) in CPU_THRESHOLDS.iter().zip(&mut time_above) {
+                if value > *threshold {
+                    *time += interval;
+                }
+            }
+        }
+        let (peak_index, peak_memory) = memory_history
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, memory)| **memory)
+            .map_or((0, 0), |(i, memory)| (i, *memory));
+        let restarts = availability.map_or(0, |availability| {
+            availability
+                .history()
+                .windows(2)
+                .filter(|pair| !pair[0] && pair[1])
+                .count()
+        });
+
+        Self {
+            duration: interval * cpu_history.len().saturating_sub(1) as u32,
+            cpu_seconds: cpu_history
+                .iter()
+                .map(|value| *value as f64 / 100.0 * interval.as_secs_f64())
+                .sum(),
+            time_above,
+            max_processes: processes.iter().copied().max().unwrap_or_default(),
+            peak_memory,
+            peak_memory_ago: interval * memory_history.len().saturating_sub(peak_index + 1) as u32,
+            restarts,
+        }
+    }
+}
+
+/// Formats the totals of a finished run as a Markdown table
+pub fn summary_markdown(
+    identifier: &ProcessIdentifier,
+    summary: &RunSummary,
+    settings: &Settings,
+    cpu_count: usize,
+) -> String {
+    let cpu = |value: f32| settings.cpu_scale.apply(value, cpu_count);
+    let memory = |bytes: usize| {
+        let (value, unit) = settings.memory_unit.format_value(bytes as f32);
+        format!("{value:.1} {unit}")
+    };
+
+    let mut out = String::new();
+    let _ = writeln!(out, "### {} (finished)", settings.label(identifier));
+    let _ = writeln!(out);
+    let _ = writeln!(out, "| Metric | Value |");
+    let _ = writeln!(out, "|---|---:|");
+    let _ = writeln!(
+        out,
+        "| Run duration | {} |",
+        format_duration(summary.duration)
+    );
+    let _ = writeln!(
+        out,
+        "| Avg CPU ({}) | {:.1}% |",
+        settings.cpu_scale.label(),
+        cpu(summary.avg_cpu)
+    );
+    let _ = writeln!(
+        out,
+        "| Peak CPU ({}) | {:.1}% |",
+        settings.cpu_scale.label(),
+        cpu(summary.peak_cpu)
+    );
+    let _ = writeln!(out, "| Avg memory | {} |", memory(summary.avg_memory));
+    let _ = writeln!(out, "| Peak memory | {} |", memory(summary.peak_memory));
+    let _ = writeln!(out, "| Children spawned | {} |", summary.children);
+    out
+}
+
+/// Formats the general stats and the heaviest children as Markdown tables
+pub fn stats_markdown(
+    identifier: &ProcessIdentifier,
+    process_data: &ProcessData,
+    settings: &Settings,
+    cpu_count: usize,
+) -> String {
+    let stats = &process_data.genereal.stats;
+    let cpu = |value: f32| settings.cpu_scale.apply(value, cpu_count);
+    let memory = |bytes: usize| {
+        let (value, unit) = settings.memory_unit.format_value(bytes as f32);
+        format!("{value:.1} {unit}")
+    };
+
+    let mut out = String::new();
+    let _ = writeln!(out, "### {}", settings.label(identifier));
+    let _ = writeln!(out);
+    let _ = writeln!(
+        out,
+        "Processes: {} | Threads: {}",
+        stats.process_count, stats.thread_count
+    );
+    let _ = writeln!(out);
+    let _ = writeln!(out, "| Metric | Current | Avg | Peak | P95 |");
+    let _ = writeln!(out, "|---|---:|---:|---:|---:|");
+    let _ = writeln!(
+        out,
+        "| CPU ({}) | {:.1}% | {:.1}% | {:.1}% | {:.1}% |",
+        settings.cpu_scale.label(),
+        cpu(stats.current_cpu),
+        cpu(stats.avg_cpu),
+        cpu(stats.peak_cpu),
+        cpu(stats.cpu_distribution.p95 as f32)
+    );
+    let _ = writeln!(
+        out,
+        "| Memory | {} | {} | {} | {} |",
+        memory(stats.current_memory),
+        memory(stats.avg_memory),
+        memory(stats.peak_memory),
+        memory(stats.memory_distribution.p95 as usize)
+    );
+    if stats.os_peak_memory > 0 {
+        let _ = writeln!(
+            out,
+            "| Memory (OS lifetime peak) | - | - | {} | - |",
+            memory(stats.os_peak_memory)
+        );
+    }
+    let _ = writeln!(
+        out,
+        "| GPU | {:.1}% | {:.1}% | {:.1}% | - |",
+        stats.current_gpu, stats.avg_gpu, stats.peak_gpu
+    );
+
+    let mut children: Vec<_> = process_data
+        .processes_stats
+        .iter()
+        .filter(|process| !process.is_thread)
+        .collect();
+    if children.is_empty() {
+        return out;
+    }
+    children.sort_by(|a, b| b.avg_cpu.total_cmp(&a.avg_cpu));
+
+    let _ = writeln!(out);
+    let _ = writeln!(
+        out,
+        "| Name | PID | Avg CPU | Peak CPU | Memory | Peak Memory |"
+    );
+    let _ = writeln!(out, "|---|---:|---:|---:|---:|---:|");
+    for process in children.iter().take(TOP_CHILDREN) {
+        let _ = writeln!(
+            out,
+            "| {} | {} | {:.1}% | {:.1}% | {} | {} |",
+            process.name.replace('|', "\\|"),
+            process.pid,
+            cpu(process.avg_cpu),
+            cpu(process.peak_cpu),
+            memory(process.current_memory),
+            memory(process.peak_memory)
+        );
+    }
+    if children.len() > TOP_CHILDREN {
+        let _ = writeln!(
+            out,
+            "\n_{} more processes not shown_",
+            children.len() - TOP_CHILDREN
+        );
+    }
+    out
+}
+
+/// Spoken summary of a plotted series for screen readers, e.g.
+/// `CPU (%): rising, avg 34.0, now 52.1`. The newer half of `values` is
+/// compared with the older one, a change under a tenth of their range counts
+/// as steady so noise doesn't flip the trend.
+pub fn trend_readout(label: &str, values: &[f64]) -> String {
+    let Some(&last) = values.last() else {
+        return format!("{label}: no samples");
+    };
+    let mean = |values: &[f64]| values.iter().sum::<f64>() / values.len().max(1) as f64;
+    let (older, newer) = values.split_at(values.len() / 2);
+    let (min, max) = values
+        .iter()
+        .fold((f64::MAX, f64::MIN), |(min, max), &value| {
+            (min.min(value), max.max(value))
+        });
+    let change = mean(newer) - mean(older);
+    let trend = if older.is_empty() || change.abs() <= (max - min) / 10.0 {
+        "steady"
+    } else if change > 0.0 {
+        "rising"
+    } else {
+        "falling"
+    };
+    format!("{label}: {trend}, avg {:.1}, now {last:.1}", mean(values))
+}
+
+/// `1h 02m 03s`, or `2m 03s` under an hour
+pub fn format_duration(duration: std::time::Duration) -> String {
+    let seconds = duration.as_secs();
+    if seconds >= 3600 {
+        format!(
+            "{}h {:02}m {:02}s",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        )
+    } else {
+        format!("{}m {:02}s", seconds / 60, seconds % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::process::{Availability, ProcessData, ProcessGeneral, ProcessHistory};
+    use crate::metrics::GENERAL_STATS_PID;
+
+    #[test]
+    fn history_summary_totals_the_retained_samples() {
+        let mut data = ProcessData {
+            genereal: ProcessGeneral {
+                stats: Default::default(),
+                history: ProcessHistory::new(10),
+            },
+            ..Default::default()
+        };
+        let history = &mut data.genereal.history;
+        for (cpu, memory, processes) in [(40.0, 10, 1), (90.0, 30, 3), (100.0, 20, 2), (60.0, 5, 1)]
+        {
+            history.update_cpu(*GENERAL_STATS_PID, cpu);
+            history.update_memory(*GENERAL_STATS_PID, memory);
+            history.update_counts(*GENERAL_STATS_PID, processes, processes);
+        }
+        let mut availability = Availability::new(10);
+        for up in [true, false, true, true, false, false, true] {
+            availability.record(up);
+        }
+
+        let interval = Duration::from_secs(2);
+        let summary = HistorySummary::new(&data, Some(&availability), interval, |value| value);
+        assert_eq!(summary.duration, Duration::from_secs(6));
+        assert!((summary.cpu_seconds - 5.8).abs() < 1e-9);
+        assert_eq!(
+            summary.time_above,
+            [
+                Duration::from_secs(6),
+                Duration::from_secs(4),
+                Duration::from_secs(2)
+            ]
+        );
+        assert_eq!(summary.max_processes, 3);
+        assert_eq!(summary.peak_memory, 30);
+        assert_eq!(summary.peak_memory_ago, Duration::from_secs(4));
+        assert_eq!(summary.restarts, 2);
+    }
+
+    #[test]
+    fn trend_readout_names_the_direction_of_a_plot() {
+        assert_eq!(
+            trend_readout("CPU (%)", &[10.0, 20.0, 40.0, 50.0]),
+            "CPU (%): rising, avg 30.0, now 50.0"
+        );
+        assert!(trend_readout("Memory (MB)", &[50.0, 40.0, 20.0, 10.0]).contains("falling"));
+        // Noise around a level is not a trend
+        assert!(trend_readout("CPU (%)", &[10.0, 30.0, 11.0, 29.0]).contains("steady"));
+        assert_eq!(trend_readout("GPU (%)", &[]), "GPU (%): no samples");
+    }
+}
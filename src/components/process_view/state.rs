@@ -1,9 +1,399 @@
-use crate::metrics::process::{MetricType, ProcessHistory, ProcessIdentifier, SortType};
-use sysinfo::Pid;
+use super::inspection::Inspection;
+use crate::metrics::process::limits::ResourceLimit;
+use crate::metrics::process::{
+    MetricType, Priority, ProcessData, ProcessDetails, ProcessEventKind, ProcessIdentifier,
+    ProcessInfo, Socket, SortType,
+};
+use crate::metrics::thermal::CpuThermals;
+use crate::metrics::{SwapUsage, GENERAL_STATS_PID};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use sysinfo::{Pid, Signal};
+
+/// Narrows down the children list of one identifier
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct ChildrenFilter {
+    /// Matched against the name, ignoring case, and the PID
+    #[serde(skip)]
+    pub text: String,
+    /// Cards listed before the rest is collapsed, all when `None`
+    pub top_n: Option<usize>,
+}
+
+impl ChildrenFilter {
+    pub fn matches(&self, process: &ProcessInfo) -> bool {
+        let text = self.text.trim();
+        text.is_empty()
+            || process.name.to_lowercase().contains(&text.to_lowercase())
+            || process.pid.to_string().contains(text)
+    }
+}
+
+/// Filled in by the thread waiting on the password prompt of an elevated
+/// relaunch, empty until the prompt returns
+pub type RelaunchOutcome = Arc<Mutex<Option<Result<(), String>>>>;
 
 #[derive(serde::Deserialize, serde::Serialize, Debug, Default)]
 pub struct ProcessView {
     pub sort_type: SortType,
+    /// Children listed from the smallest value of `sort_type` up
+    #[serde(default)]
+    pub sort_ascending: bool,
+    /// Filter and top-N limit of the children list of each identifier
+    #[serde(default)]
+    pub children_filters: HashMap<ProcessIdentifier, ChildrenFilter>,
+    /// Metric of the tree's plot, and of the child cards without their own
     pub current_metric: MetricType,
+    /// Metric picked on a child card, by PID
+    #[serde(skip)]
+    pub card_metrics: HashMap<Pid, MetricType>,
     pub scroll_target: Option<ProcessIdentifier>,
+    pub time_window: TimeWindow,
+    pub show_events: bool,
+    /// Plot memory as growth per second instead of the absolute value
+    #[serde(default)]
+    pub memory_rate: bool,
+    /// Plot context switch and page fault rates below the selected metric
+    #[serde(default)]
+    pub show_sched: bool,
+    /// Plot CPU time split into user, system and I/O wait, stacked
+    #[serde(default)]
+    pub show_cpu_states: bool,
+    /// Show how the CPU samples of the time window spread instead of plotting them over time
+    #[serde(default)]
+    pub cpu_histogram: bool,
+    /// Plot the tree's share of the CPU the whole system used instead of its usage
+    #[serde(default)]
+    pub cpu_share: bool,
+    /// Plot waiting to be saved as an image
+    #[serde(skip)]
+    pub image_export: Option<ImageExport>,
+    /// Plots holding a past viewport, with the worker tick they were frozen at
+    #[serde(skip)]
+    pub frozen_plots: HashMap<egui::Id, u64>,
+    /// Identifier whose "Summary" window is open
+    #[serde(skip)]
+    pub history_summary: Option<ProcessIdentifier>,
+    /// Frozen copies taken by "Snapshot now", oldest first, at most
+    /// [`super::inspection::KEPT_INSPECTIONS`] per identifier
+    #[serde(skip)]
+    pub inspections: HashMap<ProcessIdentifier, VecDeque<Inspection>>,
+    /// Identifier whose snapshot diff window is open
+    #[serde(skip)]
+    pub inspection_diff: Option<ProcessIdentifier>,
+    /// Outcome of the last "Save summary" of a finished process
+    #[serde(skip)]
+    pub summary_status: Option<Result<String, String>>,
+    /// Details of processes with an open "Details" expander, None once the process exited
+    #[serde(skip)]
+    pub details: HashMap<Pid, Option<ProcessDetails>>,
+    /// Open sockets of processes with an open "Network connections" expander
+    #[serde(skip)]
+    pub sockets: HashMap<Pid, Result<Vec<Socket>, String>>,
+    /// Where the core dumps of crashed processes are stored, looked up on "Core dump"
+    #[serde(skip)]
+    pub core_dumps: HashMap<Pid, Result<String, String>>,
+    /// Tick up to which the crash banner of each identifier was dismissed
+    #[serde(skip)]
+    pub dismissed_crashes: HashMap<ProcessIdentifier, u64>,
+    /// Suspend or resume clicked on a process card, sent by the app
+    #[serde(skip)]
+    pub signal_request: Option<(Pid, Signal)>,
+    /// Process whose last suspend or resume failed, with the error
+    #[serde(skip)]
+    pub signal_error: Option<(Pid, String)>,
+    /// Nice value and affinity being edited in the "Details" of a process
+    #[serde(skip)]
+    pub priority_draft: Option<(Pid, Priority)>,
+    /// Outcome of the last priority change, with the process it was applied to
+    #[serde(skip)]
+    pub priority_status: Option<(Pid, Result<String, String>)>,
+    /// Outcome of the last "Relaunch elevated"
+    #[serde(skip)]
+    pub relaunch: Option<RelaunchOutcome>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageFormat {
+    Png,
+    Svg,
+}
+
+impl ImageFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Svg => "svg",
+        }
+    }
+}
+
+/// One plot of an image export
+#[derive(Debug, Clone)]
+pub struct PlotPanel {
+    pub title: String,
+    pub y_label: String,
+    pub values: Vec<f64>,
+    /// Markers within `values`, drawn as vertical lines in their color
+    pub markers: Vec<PlotMarker>,
+    /// Time between two of `values`
+    pub interval: Duration,
+}
+
+/// Snapshot of plot data taken when "Save image" was clicked.
+/// Overview exports stack one panel per plot into a single tall image.
+#[derive(Debug, Clone)]
+pub struct ImageExport {
+    pub title: String,
+    pub panels: Vec<PlotPanel>,
+    /// Time of the newest sample
+    pub taken_at: std::time::SystemTime,
+    pub path: String,
+    pub format: ImageFormat,
+    /// Outcome of the last save attempt
+    pub status: Option<Result<String, String>>,
+}
+
+impl ImageExport {
+    pub fn new(title: String, panels: Vec<PlotPanel>) -> Self {
+        let taken_at = std::time::SystemTime::now();
+        let seconds = taken_at
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let file_name: String = title
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '_' })
+            .collect();
+        let format = ImageFormat::Png;
+        Self {
+            path: format!("tvis_{file_name}_{seconds}.{}", format.extension()),
+            title,
+            panels,
+            taken_at,
+            format,
+            status: None,
+        }
+    }
+
+    /// Switches the format, keeping the file extension in sync
+    pub fn set_format(&mut self, format: ImageFormat) {
+        if let Some(stem) = self.path.strip_suffix(self.format.extension()) {
+            self.path = format!("{stem}{}", format.extension());
+        }
+        self.format = format;
+    }
+}
+
+/// Lines drawn over the plots of one identifier
+#[derive(Debug, Clone, Default)]
+pub struct PlotOverlays {
+    pub markers: Vec<PlotMarker>,
+    /// OS-enforced limit, drawn as reference lines on the aggregate plots
+    pub limit: Option<ResourceLimit>,
+    /// Guide lines and alert thresholds of the identifier
+    pub guides: Vec<PlotGuide>,
+    /// Worker tick the plots are drawn at, anchors frozen plots
+    pub tick: u64,
+    /// System swap, shown next to the memory of the tree
+    pub swap: SwapUsage,
+    pub gaps: Vec<SampleGap>,
+    /// CPU temperature and clock, shown above the CPU plot when enabled
+    pub thermals: Option<CpuThermals>,
+    /// Time between samples of the identifier, its override or the global one
+    pub interval: Duration,
+}
+
+/// Samples the worker missed before a sample, because a tick ran late
+#[derive(Debug, Clone, Copy)]
+pub struct SampleGap {
+    /// Distance of the sample after the gap from the newest sample
+    pub samples_ago: u64,
+    pub missed: u32,
+}
+
+impl SampleGap {
+    /// Gaps in the aggregate history of a tree, newest first
+    pub fn of(process_data: &ProcessData) -> Vec<SampleGap> {
+        let missed = process_data
+            .genereal
+            .history
+            .get_missed_history(&GENERAL_STATS_PID)
+            .unwrap_or_default();
+        missed
+            .iter()
+            .rev()
+            .enumerate()
+            .filter(|(_, &missed)| missed > 0)
+            .map(|(samples_ago, &missed)| SampleGap {
+                samples_ago: samples_ago as u64,
+                missed,
+            })
+            .collect()
+    }
+}
+
+/// Vertical line drawn on the plots at a past sample
+#[derive(Debug, Clone)]
+pub struct PlotMarker {
+    /// Distance from the newest sample
+    pub samples_ago: u64,
+    pub label: String,
+    /// Marker color, the weak text color when `None`
+    pub color: Option<egui::Color32>,
+    pub highlighted: bool,
+}
+
+/// Horizontal line drawn on the aggregate plot of its metric
+#[derive(Debug, Clone)]
+pub struct PlotGuide {
+    pub metric: MetricType,
+    /// Percent for CPU and GPU, bytes for memory, a count for processes
+    pub value: f64,
+    pub label: String,
+    /// Color once the newest sample is above the line, the warning color when `None`
+    pub color: Option<egui::Color32>,
+}
+
+/// How much of the recent history the plots show
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum TimeWindow {
+    OneMinute,
+    FiveMinutes,
+    FifteenMinutes,
+    #[default]
+    All,
+}
+
+impl TimeWindow {
+    pub const ALL: [TimeWindow; 4] = [
+        TimeWindow::OneMinute,
+        TimeWindow::FiveMinutes,
+        TimeWindow::FifteenMinutes,
+        TimeWindow::All,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TimeWindow::OneMinute => "1 m",
+            TimeWindow::FiveMinutes => "5 m",
+            TimeWindow::FifteenMinutes => "15 m",
+            TimeWindow::All => "All",
+        }
+    }
+
+    /// Number of the newest samples covered by the window, `display_len` for `All`.
+    /// Counted on the recorded sample times, further back at the clock's interval.
+    pub fn samples(&self, clock: SampleClock<'_>, display_len: usize) -> usize {
+        let span = match self {
+            TimeWindow::OneMinute => Duration::from_secs(60),
+            TimeWindow::FiveMinutes => Duration::from_secs(5 * 60),
+            TimeWindow::FifteenMinutes => Duration::from_secs(15 * 60),
+            TimeWindow::All => return display_len,
+        };
+        let per_interval = |span: Duration| {
+            (span.as_secs_f64() / clock.interval.as_secs_f64().max(0.001)) as usize
+        };
+        let (Some(&first), Some(&newest)) = (clock.times.first(), clock.times.last()) else {
+            return per_interval(span).max(2);
+        };
+        let start = newest.checked_sub(span).unwrap_or(std::time::UNIX_EPOCH);
+        let samples = if first <= start {
+            clock.times.len() - clock.times.partition_point(|&time| time < start)
+        } else {
+            let before = first.duration_since(start).unwrap_or_default();
+            clock.times.len() + per_interval(before)
+        };
+        samples.max(2)
+    }
+}
+
+/// Newest samples shown by a plot
+#[derive(Debug, Clone, Copy)]
+pub struct PlotWindow {
+    pub len: usize,
+    /// Time between two of the samples
+    pub interval: Duration,
+}
+
+/// When the samples of an identifier were taken, to cut time windows
+#[derive(Debug, Clone, Copy)]
+pub struct SampleClock<'a> {
+    /// Wall-clock times of the recorded samples, oldest first
+    pub times: &'a [SystemTime],
+    /// Time between samples of the identifier, assumed past the recorded times
+    pub interval: Duration,
+}
+
+impl SampleClock<'_> {
+    /// Mean time between the newest `samples`, the interval without recorded times
+    pub fn spacing(&self, samples: usize) -> Duration {
+        let recent = &self.times[self.times.len().saturating_sub(samples)..];
+        match (recent.first(), recent.last()) {
+            (Some(first), Some(last)) if recent.len() > 1 => last
+                .duration_since(*first)
+                .map(|span| span / (recent.len() - 1) as u32)
+                .unwrap_or(self.interval),
+            _ => self.interval,
+        }
+    }
+}
+
+impl ProcessView {
+    /// Child start/exit markers for the plots, when enabled
+    pub fn event_markers(&self, process_data: &ProcessData, current_tick: u64) -> Vec<PlotMarker> {
+        if !self.show_events {
+            return Vec::new();
+        }
+        process_data
+            .events
+            .iter()
+            .map(|event| PlotMarker {
+                samples_ago: current_tick.saturating_sub(event.tick),
+                label: event
+                    .crash
+                    .map(|crash| crash.describe())
+                    .unwrap_or_default(),
+                color: Some(match event.kind {
+                    _ if event.crash.is_some() => egui::Color32::from_rgb(0xd5, 0x00, 0xf9),
+                    ProcessEventKind::Started => egui::Color32::from_rgb(0x4c, 0xaf, 0x50),
+                    ProcessEventKind::Exited => egui::Color32::from_rgb(0xe5, 0x39, 0x35),
+                    ProcessEventKind::Restarted => egui::Color32::from_rgb(0xff, 0x98, 0x00),
+                }),
+                highlighted: event.crash.is_some(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn time_windows_follow_the_recorded_sample_times() {
+        let start = std::time::UNIX_EPOCH + Duration::from_secs(1_000_000);
+        // 200 ms apart, five minutes of samples
+        let times: Vec<SystemTime> = (0..1500)
+            .map(|i| start + Duration::from_millis(200 * i))
+            .collect();
+        let clock = SampleClock {
+            times: &times,
+            interval: Duration::from_secs(1),
+        };
+        assert_eq!(TimeWindow::OneMinute.samples(clock, 1500), 301);
+        assert_eq!(clock.spacing(301), Duration::from_millis(200));
+        // Past the recorded times at the interval
+        assert_eq!(TimeWindow::FifteenMinutes.samples(clock, 1500), 1500 + 600);
+        assert_eq!(TimeWindow::All.samples(clock, 1500), 1500);
+
+        let clock = SampleClock {
+            times: &[],
+            interval: Duration::from_millis(200),
+        };
+        assert_eq!(TimeWindow::OneMinute.samples(clock, 10), 300);
+        assert_eq!(clock.spacing(300), Duration::from_millis(200));
+    }
 }
@@ -1,12 +1,28 @@
-use std::sync::{Arc, RwLock};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-use sysinfo::Pid;
+use sysinfo::{Pid, Signal};
 
-use crate::components::process_view::state::ProcessView;
-use crate::components::settings::Settings;
-use crate::metrics::process::{MetricType, ProcessData, ProcessIdentifier, SortType};
+use crate::components::i18n::{number, percent, tr, tr_format};
+use crate::components::process_view::inspection::{Inspection, SnapshotDiff, KEPT_INSPECTIONS};
+use crate::components::process_view::report::{
+    format_duration, stats_markdown, summary_markdown, trend_readout, HistorySummary,
+    CPU_THRESHOLDS,
+};
+use crate::components::process_view::state::{
+    ImageExport, ImageFormat, PlotGuide, PlotMarker, PlotOverlays, PlotPanel, PlotWindow,
+    ProcessView, SampleClock, SampleGap, TimeWindow,
+};
+use crate::components::settings::{Settings, Smoothing};
+use crate::metrics::process::{
+    access, core_dump, set_affinity, set_nice, sockets, Availability, Distribution, Histogram,
+    LeakSuspect, MemoryProjection, MetricType, Priority, PriorityClass, ProcessData,
+    ProcessDetails, ProcessEventKind, ProcessGeneralStats, ProcessHistory, ProcessIdentifier,
+    ProcessInfo, RunSummary, SchedRates, SortType, NICE_RANGE,
+};
+use crate::metrics::thermal::CpuThermals;
 use crate::metrics::{Metrics, GENERAL_STATS_PID};
-use crate::ProcessMonitorApp;
 
 impl ProcessView {
     pub fn show_process(
@@ -15,19 +31,157 @@ impl ProcessView {
         process_identifier: &ProcessIdentifier,
         process_data: &ProcessData,
         settings: &Settings,
+        overlays: &PlotOverlays,
+        cpu_count: usize,
     ) {
+        let cpu = |value: f32| settings.cpu_scale.apply(value, cpu_count);
+        let layers = PlotLayers {
+            markers: &overlays.markers,
+            smoothing: settings.smoothing,
+            limit: None,
+            guides: &[],
+            frozen: None,
+            projection: None,
+            gaps: &overlays.gaps,
+            color: settings.series_colors.metric(self.current_metric),
+            label: "",
+        };
+        let limit = overlays.limit.unwrap_or_default();
+        let times = process_data
+            .genereal
+            .history
+            .get_sample_times(&GENERAL_STATS_PID)
+            .unwrap_or_default();
+        let clock = SampleClock {
+            times: &times,
+            interval: overlays.interval,
+        };
+        let window_len = self.time_window.samples(
+            clock,
+            settings.display_len(process_data.history.history_len),
+        );
+        // The CPU, memory and GPU of the tree reach further back with the tier averages
+        let retained_len = process_data
+            .genereal
+            .history
+            .retained_len(&GENERAL_STATS_PID)
+            .max(process_data.history.history_len);
+        let tiered_window_len = self
+            .time_window
+            .samples(clock, settings.display_len(retained_len));
+        let window = PlotWindow {
+            len: window_len,
+            interval: clock.spacing(window_len),
+        };
+        let tiered_window = PlotWindow {
+            len: tiered_window_len,
+            interval: clock.spacing(tiered_window_len),
+        };
         ui.group(|ui| {
-            ui.heading(process_identifier.to_string());
+            ui.horizontal(|ui| {
+                ui.heading(settings.label(process_identifier));
+                if ui
+                    .button(tr("📋 Copy stats"))
+                    .on_hover_text(tr("Copy stats and top children as a Markdown table"))
+                    .clicked()
+                {
+                    ui.ctx().copy_text(stats_markdown(
+                        process_identifier,
+                        process_data,
+                        settings,
+                        cpu_count,
+                    ));
+                }
+                if ui
+                    .button(tr("📊 Summary"))
+                    .on_hover_text(tr("Totals over the whole retained history"))
+                    .clicked()
+                {
+                    self.history_summary = Some(process_identifier.clone());
+                }
+                let inspections = self
+                    .inspections
+                    .entry(process_identifier.clone())
+                    .or_default();
+                let hint = if inspections.is_empty() {
+                    "Freeze the current data, take a second snapshot later to see what changed"
+                } else {
+                    "Freeze the current data and compare it with the previous snapshot"
+                };
+                if ui
+                    .button(tr("📸 Snapshot now"))
+                    .on_hover_text(tr(hint))
+                    .clicked()
+                {
+                    inspections.push_back(Inspection::new(process_data));
+                    if inspections.len() > KEPT_INSPECTIONS {
+                        inspections.pop_front();
+                    }
+                    if inspections.len() == KEPT_INSPECTIONS {
+                        self.inspection_diff = Some(process_identifier.clone());
+                    }
+                }
+                if let Some(first) = inspections.front().filter(|_| inspections.len() == 1) {
+                    ui.weak(tr_format(
+                        "Snapshot taken {} ago",
+                        &[&format_duration(first.taken_at.elapsed())],
+                    ));
+                }
+            });
+            if let Some(summary) = &process_data.finished {
+                self.show_finished(ui, process_identifier, summary, settings, cpu_count);
+            }
+            self.show_crashes(ui, process_identifier, process_data);
             ui.horizontal(|ui| {
                 ui.vertical(|ui| {
                     ui.label(format!(
-                        "Total Processes: {}",
+                        "{} {}",
+                        tr("Total Processes:"),
                         process_data.genereal.stats.process_count
                     ));
                     ui.label(format!(
-                        "Total Threads: {}",
+                        "{} {}",
+                        tr("Total Threads:"),
                         process_data.genereal.stats.thread_count
                     ));
+                    self.show_restricted(ui, process_data.genereal.stats.restricted_processes);
+                    show_extreme_priorities(ui, &process_data.genereal.stats);
+                    let session = &process_data.session;
+                    ui.label(tr_format(
+                        "Monitored: {} | Samples: {} | Coverage: {}",
+                        &[
+                            &format_duration(session.duration()),
+                            &session.samples,
+                            &percent(session.coverage(), 1),
+                        ],
+                    ))
+                    .on_hover_text(tr(
+                        "Samples with a live process out of all expected samples. \
+                         Gaps come from the process not running or the sampler falling behind.",
+                    ));
+                    ui.horizontal(|ui| {
+                        ui.label(
+                            session
+                                .uptime()
+                                .map_or(tr("Uptime: not running").to_string(), |uptime| {
+                                    format!("{} {}", tr("Uptime:"), format_duration(uptime))
+                                }),
+                        )
+                        .on_hover_text(tr("Since the oldest running process of the tree started"));
+                        if !matches!(process_identifier, ProcessIdentifier::Pid(_)) {
+                            ui.label(" | ");
+                            let restarts = format!("{} {}", tr("Restarts:"), session.restarts);
+                            let response = if session.restarts > 0 {
+                                ui.colored_label(ui.visuals().warn_fg_color, restarts)
+                            } else {
+                                ui.label(restarts)
+                            };
+                            response.on_hover_text(tr(
+                                "Matching processes started after monitoring began, \
+                                 each one is also listed under Events",
+                            ));
+                        }
+                    });
                 });
             });
             ui.add_space(8.0);
@@ -38,57 +192,180 @@ impl ProcessView {
                     .stroke(ui.style().visuals.widgets.noninteractive.bg_stroke)
                     .show(ui, |ui| {
                         ui.horizontal(|ui| {
-                            if ui
-                                .selectable_label(self.current_metric == MetricType::Cpu, "CPU")
-                                .clicked()
-                            {
-                                self.current_metric = MetricType::Cpu;
-                            }
-                            if ui
-                                .selectable_label(
-                                    self.current_metric == MetricType::Memory,
-                                    "Memory",
-                                )
-                                .clicked()
-                            {
-                                self.current_metric = MetricType::Memory;
+                            if let Some(metric) = metric_toggle(ui, self.current_metric) {
+                                self.current_metric = metric;
                             }
                         });
                     });
+                if cfg!(target_os = "linux") {
+                    ui.add_space(8.0);
+                    ui.checkbox(&mut self.show_sched, tr("Switches & faults"))
+                        .on_hover_text(tr(
+                            "Context switch and page fault rates. Involuntary switches point at \
+                             CPU contention, major faults at thrashing.",
+                        ));
+                    ui.checkbox(&mut self.show_cpu_states, tr("CPU states"))
+                        .on_hover_text(tr(
+                            "CPU time split into user, system and I/O wait, stacked. I/O wait \
+                             needs delay accounting (sysctl kernel.task_delayacct=1).",
+                        ));
+                }
+                ui.add_space(8.0);
+                ui.label(tr("Window:"));
+                for window in TimeWindow::ALL {
+                    if ui
+                        .selectable_label(self.time_window == window, tr(window.label()))
+                        .clicked()
+                    {
+                        self.time_window = window;
+                    }
+                }
             });
             ui.add_space(3.0);
             // Plot based on general metric
             match self.current_metric {
                 MetricType::Cpu => {
+                    let stats = &process_data.genereal.stats;
                     ui.horizontal(|ui| {
-                        ui.label(format!(
-                            "CPU Usage: {:.1}%",
-                            process_data.genereal.stats.current_cpu
-                        ));
+                        let cpu = |value: f32| percent(cpu(value), 1);
+                        ui.label(format!("{} {}", tr("CPU Usage:"), cpu(stats.current_cpu)));
                         ui.label(" | ");
-                        ui.label(format!(
-                            "Peak: {:.1}%",
-                            process_data.genereal.stats.peak_cpu
-                        ));
+                        ui.label(format!("{} {}", tr("Peak:"), cpu(stats.peak_cpu)));
+                        ui.label(" | ");
+                        ui.label(format!("{} {}", tr("AVG CPU:"), cpu(stats.avg_cpu)));
                         ui.label(" | ");
                         ui.label(format!(
-                            "AVG CPU: {:.1}%",
-                            process_data.genereal.stats.avg_cpu
-                        ));
+                            "{} {}",
+                            tr("Share of system:"),
+                            percent(stats.cpu_share, 0)
+                        ))
+                        .on_hover_text(tr(SHARE_HINT));
+                    });
+                    ui.horizontal(|ui| {
+                        let distribution = &stats.cpu_distribution;
+                        let cpu = |value: f64| percent(cpu(value as f32), 1);
+                        ui.label(format!("P50: {}", cpu(distribution.p50)));
+                        ui.label(" | ");
+                        ui.label(format!("P95: {}", cpu(distribution.p95)));
+                        ui.label(" | ");
+                        ui.label(format!("P99: {}", cpu(distribution.p99)));
+                        ui.label(" | ");
+                        ui.label(format!("σ: {}", cpu(distribution.stddev)));
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label(tr("Show:"));
+                        let timeline = !self.cpu_histogram && !self.cpu_share;
+                        if ui.selectable_label(timeline, tr("Timeline")).clicked() {
+                            (self.cpu_histogram, self.cpu_share) = (false, false);
+                        }
+                        if ui
+                            .selectable_label(self.cpu_histogram, tr("Histogram"))
+                            .on_hover_text(tr(
+                                "Share of the samples in the time window at each CPU level, \
+                                 telling rare spikes from a constant load",
+                            ))
+                            .clicked()
+                        {
+                            (self.cpu_histogram, self.cpu_share) = (true, false);
+                        }
+                        if ui
+                            .selectable_label(self.cpu_share, tr("Share of system"))
+                            .on_hover_text(tr(SHARE_HINT))
+                            .clicked()
+                        {
+                            (self.cpu_histogram, self.cpu_share) = (false, true);
+                        }
                     });
+                    if let Some(thermals) = &overlays.thermals {
+                        thermal_strip(ui, thermals, window_len, settings);
+                    }
                     ui.add_space(2.0);
-                    plot_metric(
-                        ui,
-                        "cpu_plot_general_process",
-                        100.0,
-                        process_data
+                    let history: Vec<f32> = process_data
+                        .genereal
+                        .history
+                        .get_cpu_history_tiered(&GENERAL_STATS_PID)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(cpu)
+                        .collect();
+                    if self.cpu_share {
+                        let share = process_data
                             .genereal
                             .history
-                            .get_cpu_history(&*GENERAL_STATS_PID)
-                            .unwrap_or_default(),
-                        process_data.genereal.history.history_len,
-                        process_data.genereal.stats.peak_cpu * (1.0 + settings.graph_scale_margin),
-                    );
+                            .get_cpu_share_history(&GENERAL_STATS_PID)
+                            .unwrap_or_default();
+                        let plot_id =
+                            egui::Id::new((process_identifier, "cpu_share_plot_general_process"));
+                        let y_label = tr("CPU share (% of system usage)");
+                        let frozen = ui
+                            .horizontal(|ui| {
+                                self.image_button(
+                                    ui,
+                                    tr_format(
+                                        "{} CPU share",
+                                        &[&settings.label(process_identifier)],
+                                    ),
+                                    y_label,
+                                    &share,
+                                    layers.markers,
+                                    window,
+                                );
+                                self.follow_toggle(ui, plot_id, overlays.tick)
+                            })
+                            .inner;
+                        plot_metric(
+                            ui,
+                            plot_id,
+                            100.0,
+                            share,
+                            window_len,
+                            100.0,
+                            &layers.with_frozen(frozen).with_label(y_label),
+                        );
+                    } else if self.cpu_histogram {
+                        let samples: Vec<f64> = history[history.len().saturating_sub(window_len)..]
+                            .iter()
+                            .map(|&value| value as f64)
+                            .collect();
+                        plot_histogram(
+                            ui,
+                            (process_identifier, "cpu_histogram_general_process"),
+                            &samples,
+                            &format!("CPU ({})", tr(settings.cpu_scale.label())),
+                            layers.color,
+                        );
+                    } else {
+                        let plot_id =
+                            egui::Id::new((process_identifier, "cpu_plot_general_process"));
+                        let frozen = ui
+                            .horizontal(|ui| {
+                                self.image_button(
+                                    ui,
+                                    tr_format("{} CPU", &[&settings.label(process_identifier)]),
+                                    &format!("CPU ({})", tr(settings.cpu_scale.label())),
+                                    &history,
+                                    layers.markers,
+                                    tiered_window,
+                                );
+                                self.follow_toggle(ui, plot_id, overlays.tick)
+                            })
+                            .inner;
+                        plot_metric(
+                            ui,
+                            plot_id,
+                            100.0,
+                            history,
+                            tiered_window_len,
+                            cpu(stats.peak_cpu) * (1.0 + settings.graph_scale_margin),
+                            &layers
+                                .with_limit(limit.cpu_percent.map(|percent| cpu(percent) as f64))
+                                .with_guides(&guides_for(overlays, MetricType::Cpu, |percent| {
+                                    cpu(percent as f32) as f64
+                                }))
+                                .with_frozen(frozen)
+                                .with_label(&format!("CPU ({})", tr(settings.cpu_scale.label()))),
+                        );
+                    }
                 }
                 MetricType::Memory => {
                     ui.horizontal(|ui| {
@@ -102,81 +379,429 @@ impl ProcessView {
                             .memory_unit
                             .format_value(process_data.genereal.stats.avg_memory as f32);
 
-                        ui.label(format!("Memory Usage: {:.1} {}", current_memory, unit));
+                        ui.label(format!(
+                            "{} {} {unit}",
+                            tr("Memory Usage:"),
+                            number(current_memory, 1)
+                        ));
+                        ui.label(" | ");
+                        ui.label(format!("{} {} {unit}", tr("Peak:"), number(peak_memory, 1)));
+                        ui.label(" | ");
+                        ui.label(format!(
+                            "{} {} {unit}",
+                            tr("AVG memory:"),
+                            number(avg_memory, 1)
+                        ));
+                        let os_peak = process_data.genereal.stats.os_peak_memory;
+                        if os_peak > 0 {
+                            let (os_peak, _) = settings.memory_unit.format_value(os_peak as f32);
+                            ui.label(" | ");
+                            ui.label(format!("{} {} {unit}", tr("OS peak:"), number(os_peak, 1)))
+                                .on_hover_text(tr(
+                                    "Sum of the lifetime peak RSS the OS reports for each live \
+                                     process, including peaks before monitoring started",
+                                ));
+                        }
+                        if let Some(projection) = process_data.genereal.stats.memory_projection {
+                            ui.label(" | ");
+                            show_memory_projection(ui, &projection, settings);
+                        }
+                        if let Some(leak) = process_data.genereal.stats.leak {
+                            ui.label(" | ");
+                            ui.colored_label(ui.visuals().warn_fg_color, tr("💧 possible leak"))
+                                .on_hover_text(leak_summary(&leak));
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        let distribution = &process_data.genereal.stats.memory_distribution;
+                        let format = |bytes: f64| settings.memory_unit.format_value(bytes as f32);
+                        let unit = format(0.0).1;
+                        let format = |bytes: f64| number(format(bytes).0, 1);
+                        ui.label(format!("P50: {} {unit}", format(distribution.p50)));
+                        ui.label(" | ");
+                        ui.label(format!("P95: {} {unit}", format(distribution.p95)));
+                        ui.label(" | ");
+                        ui.label(format!("P99: {} {unit}", format(distribution.p99)));
+                        ui.label(" | ");
+                        ui.label(format!("σ: {} {unit}", format(distribution.stddev)));
+                    });
+                    let swap = overlays.swap;
+                    if swap.total > 0 {
+                        ui.horizontal(|ui| {
+                            let format =
+                                |bytes: f64| settings.memory_unit.format_value(bytes as f32);
+                            let (used, unit) = format(swap.used as f64);
+                            ui.label(format!(
+                                "{} {} / {} {unit} ({})",
+                                tr("System swap:"),
+                                number(used, 1),
+                                number(format(swap.total as f64).0, 1),
+                                percent(swap.percent(), 0)
+                            ));
+                            if swap.growth > 0.0 {
+                                ui.label(" | ");
+                                ui.colored_label(
+                                    ui.visuals().warn_fg_color,
+                                    format!("+{} {unit}/s", number(format(swap.growth).0, 1)),
+                                )
+                                .on_hover_text(tr("The system is paging memory out to swap"));
+                            }
+                        });
+                    }
+                    let history = memory_series(
+                        &process_data.genereal.history,
+                        &GENERAL_STATS_PID,
+                        self.memory_rate,
+                        overlays.interval,
+                        settings,
+                    )
+                    .unwrap_or_default();
+                    let unit = settings.memory_unit.format_value(0.0).1;
+                    ui.horizontal(|ui| {
+                        ui.label(tr("Show:"));
+                        ui.selectable_value(&mut self.memory_rate, false, tr("Absolute"));
+                        ui.selectable_value(&mut self.memory_rate, true, tr("Growth rate"))
+                            .on_hover_text(tr(
+                                "Change in memory per second between consecutive samples. \
+                                 A slow leak shows up as a rate that stays above zero.",
+                            ));
+                        if self.memory_rate {
+                            let window = &history[history.len().saturating_sub(window_len)..];
+                            let avg = window.iter().sum::<f32>() / window.len().max(1) as f32;
+                            ui.label(" | ");
+                            ui.label(format!(
+                                "{} {:.2} {unit}/s",
+                                tr("Growth:"),
+                                history.last().copied().unwrap_or_default()
+                            ));
+                            ui.label(" | ");
+                            ui.label(format!("{} {:.2} {unit}/s", tr("AVG growth:"), avg));
+                        }
+                    });
+                    let projection = process_data
+                        .genereal
+                        .stats
+                        .memory_projection
+                        .filter(|_| !self.memory_rate)
+                        .map(|projection| {
+                            settings
+                                .memory_unit
+                                .format_value(projection.bytes_per_sample as f32)
+                                .0 as f64
+                        });
+                    let guides = if self.memory_rate {
+                        Vec::new()
+                    } else {
+                        guides_for(overlays, MetricType::Memory, |bytes| {
+                            settings.memory_unit.format_value(bytes as f32).0 as f64
+                        })
+                    };
+                    let (title, y_label, max_value, limit) = if self.memory_rate {
+                        (
+                            tr_format("{} Memory growth", &[&settings.label(process_identifier)]),
+                            format!("{} ({unit}/s)", tr("Memory growth")),
+                            abs_max(&history),
+                            None,
+                        )
+                    } else {
+                        (
+                            tr_format("{} Memory", &[&settings.label(process_identifier)]),
+                            format!("{} ({unit})", tr("Memory")),
+                            settings
+                                .memory_unit
+                                .format_value(process_data.genereal.stats.peak_memory as f32)
+                                .0,
+                            limit.memory_bytes.map(|bytes| {
+                                settings.memory_unit.format_value(bytes as f32).0 as f64
+                            }),
+                        )
+                    };
+                    let plot_id =
+                        egui::Id::new((process_identifier, "memory_plot_general_process"));
+                    let frozen = ui
+                        .horizontal(|ui| {
+                            self.image_button(
+                                ui,
+                                title,
+                                &y_label,
+                                &history,
+                                layers.markers,
+                                if self.memory_rate {
+                                    window
+                                } else {
+                                    tiered_window
+                                },
+                            );
+                            self.follow_toggle(ui, plot_id, overlays.tick)
+                        })
+                        .inner;
+                    plot_metric(
+                        ui,
+                        plot_id,
+                        100.0,
+                        history,
+                        if self.memory_rate {
+                            window_len
+                        } else {
+                            tiered_window_len
+                        },
+                        max_value * (1.0 + settings.graph_scale_margin),
+                        &layers
+                            .with_limit(limit)
+                            .with_guides(&guides)
+                            .with_frozen(frozen)
+                            .with_projection(projection)
+                            .with_label(&y_label),
+                    );
+                }
+                MetricType::Gpu => {
+                    ui.horizontal(|ui| {
+                        let stats = &process_data.genereal.stats;
+                        let (gpu_memory, unit) =
+                            settings.memory_unit.format_value(stats.gpu_memory as f32);
+                        ui.label(format!(
+                            "{} {}",
+                            tr("GPU Usage:"),
+                            percent(stats.current_gpu, 1)
+                        ));
                         ui.label(" | ");
-                        ui.label(format!("Peak: {:.1} {}", peak_memory, unit));
+                        ui.label(format!("{} {}", tr("Peak:"), percent(stats.peak_gpu, 1)));
                         ui.label(" | ");
-                        ui.label(format!("AVG memory: {:.1} {}", avg_memory, unit));
+                        ui.label(format!("{} {}", tr("AVG GPU:"), percent(stats.avg_gpu, 1)));
+                        ui.label(" | ");
+                        ui.label(format!("VRAM: {} {unit}", number(gpu_memory, 1)));
                     });
+                    ui.add_space(2.0);
                     let history = process_data
                         .genereal
                         .history
-                        .get_memory_history(&*GENERAL_STATS_PID)
+                        .get_gpu_history_tiered(&GENERAL_STATS_PID)
                         .unwrap_or_default();
-                    let history: Vec<f32> = history
-                        .iter()
-                        .map(|&x| settings.memory_unit.format_value(x as f32).0)
-                        .collect();
-                    let peak_memory = settings
-                        .memory_unit
-                        .format_value(process_data.genereal.stats.peak_memory as f32)
-                        .0;
+                    let plot_id = egui::Id::new((process_identifier, "gpu_plot_general_process"));
+                    let frozen = ui
+                        .horizontal(|ui| {
+                            self.image_button(
+                                ui,
+                                tr_format("{} GPU", &[&settings.label(process_identifier)]),
+                                "GPU (%)",
+                                &history,
+                                layers.markers,
+                                tiered_window,
+                            );
+                            self.follow_toggle(ui, plot_id, overlays.tick)
+                        })
+                        .inner;
                     plot_metric(
                         ui,
-                        "memory_plot_general_process",
+                        plot_id,
                         100.0,
                         history,
-                        process_data.genereal.history.history_len,
-                        peak_memory * (1.0 + settings.graph_scale_margin),
+                        tiered_window_len,
+                        process_data.genereal.stats.peak_gpu * (1.0 + settings.graph_scale_margin),
+                        &layers
+                            .with_guides(&guides_for(overlays, MetricType::Gpu, |percent| percent))
+                            .with_frozen(frozen)
+                            .with_label("GPU (%)"),
+                    );
+                }
+                MetricType::Processes => {
+                    let stats = &process_data.genereal.stats;
+                    let (processes, threads) = process_data
+                        .genereal
+                        .history
+                        .get_count_history(&GENERAL_STATS_PID)
+                        .unwrap_or_default();
+                    let peak_processes = processes.iter().copied().max().unwrap_or_default();
+                    let peak_threads = threads.iter().copied().max().unwrap_or_default();
+                    let series = |counts: Vec<u32>| -> Vec<f32> {
+                        counts.into_iter().map(|count| count as f32).collect()
+                    };
+                    let (processes, threads) = (series(processes), series(threads));
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{} {}", tr("Processes:"), stats.process_count));
+                        ui.label(" | ");
+                        ui.label(format!("{} {peak_processes}", tr("Peak:")));
+                        ui.label(" | ");
+                        ui.label(format!(
+                            "{} {}",
+                            tr("Threads:"),
+                            number(stats.thread_count as f64, 0)
+                        ));
+                        ui.label(" | ");
+                        ui.label(format!("{} {}", tr("Peak:"), number(peak_threads, 0)));
+                    });
+                    ui.add_space(2.0);
+                    let plot_id =
+                        egui::Id::new((process_identifier, "processes_plot_general_process"));
+                    let frozen = ui
+                        .horizontal(|ui| {
+                            self.image_button(
+                                ui,
+                                tr_format("{} processes", &[&settings.label(process_identifier)]),
+                                tr("Processes"),
+                                &processes,
+                                layers.markers,
+                                window,
+                            );
+                            self.follow_toggle(ui, plot_id, overlays.tick)
+                        })
+                        .inner;
+                    let margin = 1.0 + settings.graph_scale_margin;
+                    plot_metric(
+                        ui,
+                        plot_id,
+                        100.0,
+                        processes,
+                        window_len,
+                        peak_processes as f32 * margin,
+                        &layers
+                            .with_guides(&guides_for(overlays, MetricType::Processes, |count| {
+                                count
+                            }))
+                            .with_frozen(frozen)
+                            .with_label(tr("Processes")),
+                    );
+                    // Threads share the frozen range, so both plots pan together
+                    ui.label(tr("Threads"));
+                    plot_metric(
+                        ui,
+                        (process_identifier, "threads_plot_general_process"),
+                        60.0,
+                        threads,
+                        window_len,
+                        peak_threads as f32 * margin,
+                        &layers
+                            .with_frozen(frozen)
+                            .with_color(layers.color.gamma_multiply(0.6))
+                            .with_label(tr("Threads")),
                     );
                 }
             }
 
+            if self.show_sched {
+                show_sched_plots(ui, process_identifier, process_data, settings, window_len);
+            }
+            if self.show_cpu_states {
+                show_cpu_state_plot(ui, process_identifier, process_data, settings, window_len);
+            }
+
+            ui.collapsing(
+                format!("{} ({})", tr("Events"), process_data.events.len()),
+                |ui| {
+                    ui.checkbox(&mut self.show_events, tr("Show on plots"));
+                    egui::ScrollArea::vertical()
+                        .max_height(200.0)
+                        .id_salt("events_scroll_area")
+                        .show(ui, |ui| {
+                            if process_data.events.is_empty() {
+                                ui.label(tr("No child processes started or exited yet"));
+                            }
+                            for event in process_data.events.iter().rev() {
+                                let ago = event.at.elapsed().as_secs();
+                                let (minutes, seconds) = (ago / 60, ago % 60);
+                                let text = match (event.kind, event.crash) {
+                                    (_, Some(crash)) => tr_format(
+                                        "{} (PID: {}) was killed by {} ({}m {}s ago)",
+                                        &[
+                                            &event.name,
+                                            &event.pid,
+                                            &crash.describe(),
+                                            &minutes,
+                                            &seconds,
+                                        ],
+                                    ),
+                                    (ProcessEventKind::Started, _) => tr_format(
+                                        "{} (PID: {}) started ({}m {}s ago)",
+                                        &[&event.name, &event.pid, &minutes, &seconds],
+                                    ),
+                                    (ProcessEventKind::Exited, _) => tr_format(
+                                        "{} (PID: {}) exited ({}m {}s ago)",
+                                        &[&event.name, &event.pid, &minutes, &seconds],
+                                    ),
+                                    (ProcessEventKind::Restarted, _) => tr_format(
+                                        "{} (PID: {}) restarted ({}m {}s ago)",
+                                        &[&event.name, &event.pid, &minutes, &seconds],
+                                    ),
+                                };
+                                match event.kind {
+                                    _ if event.crash.is_some() => {
+                                        ui.colored_label(ui.visuals().error_fg_color, text)
+                                    }
+                                    ProcessEventKind::Started => ui.label(text),
+                                    ProcessEventKind::Exited => ui.weak(text),
+                                    ProcessEventKind::Restarted => {
+                                        ui.colored_label(ui.visuals().warn_fg_color, text)
+                                    }
+                                };
+                            }
+                        });
+                },
+            );
+
             if !process_data.processes_stats.is_empty() {
-                ui.collapsing("Processes", |ui| {
+                ui.collapsing(tr("Processes"), |ui| {
                     ui.horizontal(|ui| {
-                        ui.label("Sort by:");
-                        if ui
-                            .selectable_label(self.sort_type == SortType::AvgCpu, "Average CPU")
-                            .clicked()
-                        {
-                            self.sort_type = SortType::AvgCpu;
-                        }
-                        if ui
-                            .selectable_label(self.sort_type == SortType::Memory, "Memory")
-                            .clicked()
-                        {
-                            self.sort_type = SortType::Memory;
+                        ui.label(tr("Sort by:"));
+                        egui::ComboBox::from_id_salt("children_sort")
+                            .selected_text(tr(self.sort_type.label()))
+                            .show_ui(ui, |ui| {
+                                for sort_type in SortType::ALL {
+                                    if ui
+                                        .selectable_label(
+                                            self.sort_type == sort_type,
+                                            tr(sort_type.label()),
+                                        )
+                                        .clicked()
+                                    {
+                                        self.sort_type = sort_type;
+                                        self.sort_ascending = sort_type.ascending_by_default();
+                                    }
+                                }
+                            });
+                        let (arrow, hint) = if self.sort_ascending {
+                            ("⬆", "Ascending, click for descending")
+                        } else {
+                            ("⬇", "Descending, click for ascending")
+                        };
+                        if ui.small_button(arrow).on_hover_text(tr(hint)).clicked() {
+                            self.sort_ascending = !self.sort_ascending;
                         }
                     });
 
-                    let mut processes = process_data.processes_stats.iter().collect::<Vec<_>>();
-
-                    match self.sort_type {
-                        SortType::AvgCpu => {
-                            processes.sort_by(|&a, &b| {
-                                let a_avg = process_data
-                                    .history
-                                    .get_cpu_history(&a.pid)
-                                    .map(|h| h.iter().sum::<f32>() / h.len() as f32)
-                                    .unwrap_or(0.0);
-                                let b_avg = process_data
-                                    .history
-                                    .get_cpu_history(&b.pid)
-                                    .map(|h| h.iter().sum::<f32>() / h.len() as f32)
-                                    .unwrap_or(0.0);
-                                b_avg
-                                    .partial_cmp(&a_avg)
-                                    .unwrap_or(std::cmp::Ordering::Equal)
-                            });
-                        }
-                        SortType::Memory => {
-                            processes.sort_by(|&a, &b| {
-                                b.current_memory
-                                    .partial_cmp(&a.current_memory)
-                                    .unwrap_or(std::cmp::Ordering::Equal)
-                            });
+                    let filter = self
+                        .children_filters
+                        .entry(process_identifier.clone())
+                        .or_default();
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut filter.text)
+                                .hint_text(tr("🔍 Filter by name or PID"))
+                                .desired_width(160.0),
+                        );
+                        let mut limited = filter.top_n.is_some();
+                        ui.checkbox(&mut limited, tr("Top"))
+                            .on_hover_text(tr("Show only the first processes in the sort order"));
+                        let mut top_n = filter.top_n.unwrap_or(DEFAULT_TOP_N);
+                        if limited {
+                            ui.add(egui::DragValue::new(&mut top_n).range(1..=1000));
                         }
+                        filter.top_n = limited.then_some(top_n);
+                    });
+                    let filter = filter.clone();
+
+                    let mut processes = process_data
+                        .processes_stats
+                        .iter()
+                        .filter(|process| filter.matches(process))
+                        .collect::<Vec<_>>();
+                    sort_children(&mut processes, self.sort_type, self.sort_ascending);
+                    let (shown, rest) =
+                        processes.split_at(filter.top_n.unwrap_or(usize::MAX).min(processes.len()));
+                    if processes.len() < process_data.processes_stats.len() {
+                        ui.weak(tr_format(
+                            "{} of {} match",
+                            &[&processes.len(), &process_data.processes_stats.len()],
+                        ));
                     }
 
                     let scroll_area_id = ui.make_persistent_id("processes_scroll_area");
@@ -184,17 +809,67 @@ impl ProcessView {
                         .max_height(500.0)
                         .id_salt(scroll_area_id);
 
+                    // The rest is opened to reach a card scrolled to from a plot or a link
+                    let target_hidden = self.scroll_target.as_ref().is_some_and(|target| {
+                        rest.iter()
+                            .any(|process| target.to_pid() == Some(process.pid))
+                    });
                     scroll.show(ui, |ui| {
-                        for process in processes {
+                        let mut show_card = |ui: &mut egui::Ui, process: &ProcessInfo| {
+                            let child_color = settings.series_colors.child(process.pid);
                             let response = ui.group(|ui| {
-                                if process.is_thread {
-                                    ui.heading(&format!("{} (Thread)", process.name));
+                                let title = if process.is_thread {
+                                    format!("{} ({})", process.name, tr("Thread"))
                                 } else {
-                                    ui.heading(&process.name);
+                                    process.name.to_string()
+                                };
+                                let mut title = egui::RichText::new(title).heading();
+                                if let Some(color) = process
+                                    .scheduling
+                                    .and_then(|(_, class)| priority_color(ui, class))
+                                {
+                                    title = title.color(color);
                                 }
+                                let heading = ui.label(title).interact(egui::Sense::click());
+                                heading.widget_info(|| {
+                                    egui::WidgetInfo::labeled(
+                                        egui::WidgetType::Label,
+                                        true,
+                                        card_readout(process, settings, cpu_count),
+                                    )
+                                });
+                                copy_menu(&heading, process.pid);
                                 ui.horizontal(|ui| {
-                                    ui.label(format!("PID: {}", process.pid));
+                                    let pid = ui.label(format!("PID: {}", process.pid));
+                                    copy_menu(&pid.interact(egui::Sense::click()), process.pid);
                                     ui.label(" | ");
+                                    if process.restricted {
+                                        ui.colored_label(
+                                            ui.visuals().warn_fg_color,
+                                            tr("🔒 Needs elevation"),
+                                        )
+                                        .on_hover_text(
+                                            format!(
+                                                "{} {}",
+                                                tr("Owned by another user, unavailable:"),
+                                                tr(access::UNAVAILABLE)
+                                            ),
+                                        );
+                                        ui.label(" | ");
+                                    }
+                                    if let Some((nice, class)) = process.scheduling {
+                                        let text = format!(
+                                            "{} {} ({nice})",
+                                            tr("Priority:"),
+                                            tr(class.label())
+                                        );
+                                        match priority_color(ui, class) {
+                                            Some(color) => ui.colored_label(color, text),
+                                            None => ui.label(text),
+                                        }
+                                        .on_hover_text(tr("Priority class and nice value"));
+                                        ui.label(" | ");
+                                    }
                                     if let Some(parent_pid) = process.parent_pid {
                                         let parent_exists = process_data
                                             .processes_stats
@@ -202,44 +877,117 @@ impl ProcessView {
                                             .any(|p| p.pid == parent_pid);
 
                                         if parent_exists {
-                                            if ui.link(format!("Parent: {}", parent_pid)).clicked()
+                                            if ui
+                                                .link(format!("{} {}", tr("Parent:"), parent_pid))
+                                                .clicked()
                                             {
                                                 self.scroll_target =
                                                     Some(ProcessIdentifier::Pid(parent_pid));
                                             }
                                         } else {
-                                            ui.label(format!("Parent: {}", parent_pid));
+                                            ui.label(format!("{} {}", tr("Parent:"), parent_pid));
                                         }
                                     } else {
-                                        ui.label("Parent: None");
+                                        ui.label(tr("Parent: None"));
                                     }
                                 });
+                                if !process.is_thread {
+                                    self.show_suspend(ui, process.pid, process.suspended);
+                                    self.show_details(ui, process, cpu_count);
+                                    self.show_sockets(ui, process.pid);
+                                }
+                                if let Some(sched) = process.sched.filter(|_| self.show_sched) {
+                                    ui.label(sched_summary(&sched));
+                                }
 
-                                match self.current_metric {
+                                // A card follows the metric of the tree until one is picked for it
+                                let metric = self
+                                    .card_metrics
+                                    .get(&process.pid)
+                                    .copied()
+                                    .unwrap_or(self.current_metric);
+                                ui.horizontal(|ui| {
+                                    if let Some(picked) = metric_toggle(ui, metric) {
+                                        if picked == self.current_metric {
+                                            self.card_metrics.remove(&process.pid);
+                                        } else {
+                                            self.card_metrics.insert(process.pid, picked);
+                                        }
+                                    }
+                                });
+                                match metric {
                                     MetricType::Cpu => {
                                         ui.horizontal(|ui| {
                                             ui.label(format!(
-                                                "Current CPU: {:.1}%",
-                                                process.current_cpu
+                                                "{} {:.1}%",
+                                                tr("Current CPU:"),
+                                                cpu(process.current_cpu)
                                             ));
                                             ui.label(" | ");
-                                            ui.label(format!("Peak: {:.1}%", process.peak_cpu));
+                                            ui.label(format!(
+                                                "{} {:.1}%",
+                                                tr("Peak:"),
+                                                cpu(process.peak_cpu)
+                                            ));
                                             ui.label(" | ");
-                                            ui.label(format!("Avg CPU: {:.1}%", process.avg_cpu));
+                                            ui.label(format!(
+                                                "{} {:.1}%",
+                                                tr("AVG CPU:"),
+                                                cpu(process.avg_cpu)
+                                            ));
                                         });
                                         ui.add_space(2.0);
                                         if let Some(cpu_history) =
                                             process_data.history.get_cpu_history(&process.pid)
                                         {
+                                            let cpu_history: Vec<f32> =
+                                                cpu_history.into_iter().map(cpu).collect();
                                             let max_cpu =
                                                 cpu_history.iter().copied().fold(0.0, f32::max);
+                                            let plot_id = egui::Id::new((
+                                                process_identifier,
+                                                format!("cpu_plot_{}", process.pid),
+                                            ));
+                                            let frozen = ui
+                                                .horizontal(|ui| {
+                                                    self.image_button(
+                                                        ui,
+                                                        tr_format(
+                                                            "{} / {} ({}) CPU",
+                                                            &[
+                                                                &settings.label(process_identifier),
+                                                                &process.name,
+                                                                &process.pid,
+                                                            ],
+                                                        ),
+                                                        &format!(
+                                                            "CPU ({})",
+                                                            tr(settings.cpu_scale.label())
+                                                        ),
+                                                        &cpu_history,
+                                                        layers.markers,
+                                                        window,
+                                                    );
+                                                    self.follow_toggle(ui, plot_id, overlays.tick)
+                                                })
+                                                .inner;
                                             plot_metric(
                                                 ui,
-                                                format!("cpu_plot_{}", process.pid),
+                                                plot_id,
                                                 80.0,
                                                 cpu_history.clone(),
-                                                process_data.history.history_len,
+                                                window_len,
                                                 max_cpu * (1.0 + settings.graph_scale_margin),
+                                                &layers
+                                                    .with_frozen(frozen)
+                                                    .with_color(child_color)
+                                                    .with_label(&tr_format(
+                                                        "{} CPU ({})",
+                                                        &[
+                                                            &process.name,
+                                                            &tr(settings.cpu_scale.label()),
+                                                        ],
+                                                    )),
                                             );
                                         }
                                     }
@@ -256,39 +1004,179 @@ impl ProcessView {
                                                 .format_value(process.avg_memory as f32);
 
                                             ui.label(format!(
-                                                "Memory Usage: {:.1} {}",
-                                                current_memory, unit
+                                                "{} {:.1} {}",
+                                                tr("Memory Usage:"),
+                                                current_memory,
+                                                unit
                                             ));
                                             ui.label(" | ");
-                                            ui.label(format!("Peak: {:.1} {}", peak_memory, unit));
+                                            ui.label(format!(
+                                                "{} {:.1} {}",
+                                                tr("Peak:"),
+                                                peak_memory,
+                                                unit
+                                            ));
                                             ui.label(" | ");
                                             ui.label(format!(
-                                                "AVG memory: {:.1} {}",
-                                                avg_memory, unit
+                                                "{} {:.1} {}",
+                                                tr("AVG memory:"),
+                                                avg_memory,
+                                                unit
                                             ));
+                                            if let Some(os_peak) = process.os_peak_memory {
+                                                let (os_peak, _) = settings
+                                                    .memory_unit
+                                                    .format_value(os_peak as f32);
+                                                ui.label(" | ");
+                                                ui.label(format!(
+                                                    "{} {:.1} {}",
+                                                    tr("OS peak:"),
+                                                    os_peak,
+                                                    unit
+                                                ))
+                                                .on_hover_text(tr(
+                                                    "Lifetime peak RSS reported by the OS",
+                                                ));
+                                            }
                                         });
                                         ui.add_space(5.0);
-                                        if let Some(memory_history) =
-                                            process_data.history.get_memory_history(&process.pid)
-                                        {
-                                            let memory_history: Vec<f32> = memory_history
-                                                .iter()
-                                                .map(|&x| {
-                                                    settings.memory_unit.format_value(x as f32).0
+                                        if let Some(memory_history) = memory_series(
+                                            &process_data.history,
+                                            &process.pid,
+                                            self.memory_rate,
+                                            overlays.interval,
+                                            settings,
+                                        ) {
+                                            let max_memory = abs_max(&memory_history);
+                                            let (metric, unit) = if self.memory_rate {
+                                                (tr("Memory growth"), "/s")
+                                            } else {
+                                                (tr("Memory"), "")
+                                            };
+                                            let plot_id = egui::Id::new((
+                                                process_identifier,
+                                                format!("child_memory_plot_{}", process.pid),
+                                            ));
+                                            let frozen = ui
+                                                .horizontal(|ui| {
+                                                    self.image_button(
+                                                        ui,
+                                                        tr_format(
+                                                            "{} / {} ({}) {}",
+                                                            &[
+                                                                &settings.label(process_identifier),
+                                                                &process.name,
+                                                                &process.pid,
+                                                                &metric,
+                                                            ],
+                                                        ),
+                                                        &format!(
+                                                            "{metric} ({}{unit})",
+                                                            settings
+                                                                .memory_unit
+                                                                .format_value(0.0)
+                                                                .1
+                                                        ),
+                                                        &memory_history,
+                                                        layers.markers,
+                                                        window,
+                                                    );
+                                                    self.follow_toggle(ui, plot_id, overlays.tick)
                                                 })
-                                                .collect();
-                                            let max_memory =
-                                                memory_history.iter().copied().fold(0.0, f32::max);
+                                                .inner;
                                             plot_metric(
                                                 ui,
-                                                format!("child_memory_plot_{}", process.pid),
+                                                plot_id,
                                                 80.0,
                                                 memory_history,
-                                                process_data.history.history_len,
+                                                window_len,
                                                 max_memory * (1.0 + settings.graph_scale_margin),
+                                                &layers
+                                                    .with_frozen(frozen)
+                                                    .with_color(child_color)
+                                                    .with_label(&format!(
+                                                        "{} {metric} ({}{unit})",
+                                                        process.name,
+                                                        settings.memory_unit.format_value(0.0).1
+                                                    )),
+                                            );
+                                        }
+                                    }
+                                    MetricType::Gpu => {
+                                        ui.horizontal(|ui| {
+                                            let (gpu_memory, unit) = settings
+                                                .memory_unit
+                                                .format_value(process.gpu.memory as f32);
+                                            ui.label(format!(
+                                                "{} {:.1}%",
+                                                tr("GPU Usage:"),
+                                                process.gpu.utilization
+                                            ));
+                                            ui.label(" | ");
+                                            ui.label(format!("VRAM: {:.1} {}", gpu_memory, unit));
+                                        });
+                                        ui.add_space(2.0);
+                                        if let Some(gpu_history) =
+                                            process_data.history.get_gpu_history(&process.pid)
+                                        {
+                                            let max_gpu =
+                                                gpu_history.iter().copied().fold(0.0, f32::max);
+                                            let plot_id = egui::Id::new((
+                                                process_identifier,
+                                                format!("gpu_plot_{}", process.pid),
+                                            ));
+                                            let frozen = ui
+                                                .horizontal(|ui| {
+                                                    self.image_button(
+                                                        ui,
+                                                        tr_format(
+                                                            "{} / {} ({}) GPU",
+                                                            &[
+                                                                &settings.label(process_identifier),
+                                                                &process.name,
+                                                                &process.pid,
+                                                            ],
+                                                        ),
+                                                        "GPU (%)",
+                                                        &gpu_history,
+                                                        layers.markers,
+                                                        window,
+                                                    );
+                                                    self.follow_toggle(ui, plot_id, overlays.tick)
+                                                })
+                                                .inner;
+                                            plot_metric(
+                                                ui,
+                                                plot_id,
+                                                80.0,
+                                                gpu_history,
+                                                window_len,
+                                                max_gpu * (1.0 + settings.graph_scale_margin),
+                                                &layers
+                                                    .with_frozen(frozen)
+                                                    .with_color(child_color)
+                                                    .with_label(&format!(
+                                                        "{} GPU (%)",
+                                                        process.name
+                                                    )),
                                             );
                                         }
                                     }
+                                    MetricType::Processes => {
+                                        let pid = Some(process.pid);
+                                        let children = process_data
+                                            .processes_stats
+                                            .iter()
+                                            .filter(|child| child.parent_pid == pid);
+                                        let threads = children
+                                            .clone()
+                                            .filter(|child| child.is_thread)
+                                            .count();
+                                        ui.label(tr_format(
+                                            "Child processes: {} | Threads: {}",
+                                            &[&(children.count() - threads), &threads],
+                                        ));
+                                    }
                                 }
                             });
 
@@ -302,13 +1190,1723 @@ impl ProcessView {
                                     self.scroll_target = None;
                                 }
                             }
+                        };
+                        for process in shown {
+                            show_card(ui, process);
+                        }
+                        if !rest.is_empty() {
+                            egui::CollapsingHeader::new(tr_format("…and {} more", &[&rest.len()]))
+                                .id_salt((process_identifier, "children_rest"))
+                                .open(target_hidden.then_some(true))
+                                .show(ui, |ui| {
+                                    for process in rest {
+                                        show_card(ui, process);
+                                    }
+                                });
+                        }
+                    });
+                });
+            }
+        });
+    }
+}
+
+impl ProcessView {
+    /// Frozen totals of an exited process, with buttons to copy or save them
+    fn show_finished(
+        &mut self,
+        ui: &mut egui::Ui,
+        identifier: &ProcessIdentifier,
+        summary: &RunSummary,
+        settings: &Settings,
+        cpu_count: usize,
+    ) {
+        let cpu = |value: f32| settings.cpu_scale.apply(value, cpu_count);
+        let memory = |bytes: usize| {
+            let (value, unit) = settings.memory_unit.format_value(bytes as f32);
+            format!("{value:.1} {unit}")
+        };
+        ui.group(|ui| {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                tr_format(
+                    "⏹ Finished {} ago",
+                    &[&format_duration(
+                        summary.ended_at.elapsed().unwrap_or_default(),
+                    )],
+                ),
+            );
+            ui.label(tr_format(
+                "Run duration: {} | Children spawned: {}",
+                &[&format_duration(summary.duration), &summary.children],
+            ));
+            ui.label(tr_format(
+                "CPU avg: {} | peak: {}",
+                &[
+                    &percent(cpu(summary.avg_cpu), 1),
+                    &percent(cpu(summary.peak_cpu), 1),
+                ],
+            ));
+            ui.label(tr_format(
+                "Memory avg: {} | peak: {}",
+                &[&memory(summary.avg_memory), &memory(summary.peak_memory)],
+            ));
+            ui.horizontal(|ui| {
+                let markdown = || summary_markdown(identifier, summary, settings, cpu_count);
+                if ui.small_button(tr("📋 Copy summary")).clicked() {
+                    ui.ctx().copy_text(markdown());
+                }
+                if !cfg!(target_arch = "wasm32") && ui.small_button(tr("💾 Save summary")).clicked()
+                {
+                    let file_name: String = identifier
+                        .to_string()
+                        .chars()
+                        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+                        .collect();
+                    let seconds = summary
+                        .ended_at
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .unwrap_or_default()
+                        .as_secs();
+                    let path = format!("tvis_{file_name}_summary_{seconds}.md");
+                    self.summary_status = Some(
+                        std::fs::write(&path, markdown())
+                            .map(|()| format!("{} {path}", tr("Saved to")))
+                            .map_err(|err| err.to_string()),
+                    );
+                }
+                match &self.summary_status {
+                    Some(Ok(message)) => {
+                        ui.weak(message);
+                    }
+                    Some(Err(err)) => {
+                        ui.colored_label(ui.visuals().error_fg_color, err);
+                    }
+                    None => {}
+                }
+            });
+        });
+    }
+
+    /// Banner listing the processes of the tree killed by a signal since it was
+    /// last dismissed, newest first
+    fn show_crashes(
+        &mut self,
+        ui: &mut egui::Ui,
+        identifier: &ProcessIdentifier,
+        process_data: &ProcessData,
+    ) {
+        let dismissed = self.dismissed_crashes.get(identifier).copied();
+        let crashes: Vec<_> = process_data
+            .events
+            .iter()
+            .rev()
+            .filter(|event| dismissed.is_none_or(|tick| event.tick > tick))
+            .filter_map(|event| Some((event, event.crash?)))
+            .collect();
+        let Some((newest, _)) = crashes.first() else {
+            return;
+        };
+        let newest_tick = newest.tick;
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                ui.colored_label(
+                    ui.visuals().error_fg_color,
+                    format!("💥 {} {}", tr("Killed by a signal:"), crashes.len()),
+                );
+                if ui
+                    .small_button("✖")
+                    .on_hover_text(tr("Hide these crashes, they stay in the events"))
+                    .clicked()
+                {
+                    self.dismissed_crashes
+                        .insert(identifier.clone(), newest_tick);
+                }
+            });
+            for (event, crash) in crashes.iter().take(MAX_CRASHES_SHOWN) {
+                ui.horizontal(|ui| {
+                    ui.colored_label(
+                        ui.visuals().error_fg_color,
+                        tr_format(
+                            "{} (PID: {}) was killed by {}, {} ago",
+                            &[
+                                &event.name,
+                                &event.pid,
+                                &crash.describe(),
+                                &format_duration(event.at.elapsed()),
+                            ],
+                        ),
+                    );
+                    if !crash.core_dumped {
+                        return;
+                    }
+                    match self.core_dumps.get(&event.pid) {
+                        Some(Ok(path)) => {
+                            ui.monospace(path.as_str());
+                            if ui
+                                .small_button("📋")
+                                .on_hover_text(tr("Copy path"))
+                                .clicked()
+                            {
+                                ui.ctx().copy_text(path.clone());
+                            }
+                        }
+                        Some(Err(err)) => {
+                            ui.weak(err.as_str());
+                        }
+                        None => {}
+                    }
+                    if ui
+                        .small_button(tr("🔍 Core dump"))
+                        .on_hover_text(tr("Ask systemd-coredump where the dump was stored"))
+                        .clicked()
+                    {
+                        self.core_dumps.insert(event.pid, core_dump(event.pid));
+                    }
+                });
+            }
+            if crashes.len() > MAX_CRASHES_SHOWN {
+                ui.weak(tr_format(
+                    "and {} more, see Events",
+                    &[&(crashes.len() - MAX_CRASHES_SHOWN)],
+                ));
+            }
+        });
+    }
+
+    /// Suspended state of one process with its suspend or resume button,
+    /// the buttons are hidden where stopping processes isn't supported
+    fn show_suspend(&mut self, ui: &mut egui::Ui, pid: Pid, suspended: bool) {
+        let supported = !cfg!(target_arch = "wasm32")
+            && [Signal::Stop, Signal::Continue]
+                .iter()
+                .all(|signal| sysinfo::SUPPORTED_SIGNALS.contains(signal));
+        if !supported && !suspended {
+            return;
+        }
+        ui.horizontal(|ui| {
+            if suspended {
+                ui.colored_label(ui.visuals().warn_fg_color, tr("⏸ Suspended"));
+            }
+            if !supported {
+                return;
+            }
+            let (text, hover, signal) = if suspended {
+                ("▶ Resume", "Send SIGCONT", Signal::Continue)
+            } else {
+                (
+                    "⏸ Suspend",
+                    "Send SIGSTOP, freezing the process until resumed",
+                    Signal::Stop,
+                )
+            };
+            if ui.small_button(tr(text)).on_hover_text(tr(hover)).clicked() {
+                self.signal_request = Some((pid, signal));
+            }
+            if let Some((_, err)) = self
+                .signal_error
+                .as_ref()
+                .filter(|(failed, _)| *failed == pid)
+            {
+                ui.colored_label(ui.visuals().error_fg_color, err);
+            }
+        });
+    }
+
+    /// Warning for processes of other users read as partly empty, with a button
+    /// relaunching tvis elevated where that's supported
+    fn show_restricted(&mut self, ui: &mut egui::Ui, restricted: usize) {
+        if restricted == 0 {
+            return;
+        }
+        ui.horizontal(|ui| {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                tr_format("🔒 {} processes need elevation", &[&restricted]),
+            )
+            .on_hover_text(format!(
+                "{} {}",
+                tr("They belong to other users, unavailable:"),
+                tr(access::UNAVAILABLE)
+            ));
+            let admin = if cfg!(windows) {
+                tr("administrator")
+            } else {
+                "root"
+            };
+            let outcome = self
+                .relaunch
+                .as_ref()
+                .map(|relaunch| relaunch.lock().unwrap().clone());
+            if access::can_relaunch()
+                && ui
+                    .add_enabled(
+                        !matches!(outcome, Some(None)),
+                        egui::Button::new(tr_format("Relaunch as {}", &[&admin])).small(),
+                    )
+                    .on_hover_text(tr(
+                        "Start tvis again with the same arguments and close this one",
+                    ))
+                    .clicked()
+            {
+                // The prompt blocks until the password is entered, which would
+                // freeze the window
+                let relaunch = Arc::new(Mutex::new(None));
+                let (outcome, ctx) = (Arc::clone(&relaunch), ui.ctx().clone());
+                std::thread::spawn(move || {
+                    *outcome.lock().unwrap() = Some(access::relaunch_elevated());
+                    ctx.request_repaint();
+                });
+                self.relaunch = Some(relaunch);
+            }
+            match outcome {
+                Some(Some(Ok(()))) => ui.ctx().send_viewport_cmd(egui::ViewportCommand::Close),
+                Some(Some(Err(err))) => {
+                    ui.colored_label(ui.visuals().error_fg_color, err);
+                }
+                Some(None) => {
+                    ui.spinner();
+                }
+                None => {}
+            }
+        });
+    }
+
+    /// "Details" expander of one process: command line, working directory,
+    /// executable, user, start time, nice value and CPU affinity, read when opened
+    /// and dropped when closed. The environment is only read on request since it
+    /// can hold secrets.
+    fn show_details(&mut self, ui: &mut egui::Ui, process: &ProcessInfo, cpu_count: usize) {
+        let pid = process.pid;
+        let unknown = tr(if process.restricted {
+            "Unavailable (needs elevation)"
+        } else {
+            "Unknown"
+        });
+        let response = egui::CollapsingHeader::new(tr("Details"))
+            .id_salt(("process_details", pid))
+            .show(ui, |ui| {
+                let details = self
+                    .details
+                    .entry(pid)
+                    .or_insert_with(|| ProcessDetails::collect(pid, false));
+                let Some(current) = details else {
+                    ui.weak(tr("Process exited"));
+                    return;
+                };
+                let path = |path: &Option<std::path::PathBuf>| {
+                    path.as_ref()
+                        .map_or(unknown.to_string(), |path| path.display().to_string())
+                };
+                let command_line = current.cmd.join(" ");
+                egui::Grid::new(("process_details_grid", pid))
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label(tr("Command line:"));
+                        ui.add(
+                            egui::Label::new(egui::RichText::new(&command_line).monospace()).wrap(),
+                        );
+                        ui.end_row();
+                        ui.label(tr("Working directory:"));
+                        ui.monospace(path(&current.cwd));
+                        ui.end_row();
+                        ui.label(tr("Executable:"));
+                        ui.monospace(path(&current.exe));
+                        ui.end_row();
+                        ui.label(tr("User:"));
+                        ui.label(current.user.as_deref().unwrap_or(tr("Unknown")));
+                        ui.end_row();
+                        ui.label(tr("Started:"));
+                        ui.label(current.started_at.map_or(tr("Unknown").to_string(), |at| {
+                            tr_format(
+                                "{} ago",
+                                &[&format_duration(at.elapsed().unwrap_or_default())],
+                            )
+                        }));
+                        ui.end_row();
+                        ui.label(tr("Nice:"));
+                        ui.label(
+                            current
+                                .priority
+                                .nice
+                                .map_or(tr("Unknown").to_string(), |nice| nice.to_string()),
+                        );
+                        ui.end_row();
+                        ui.label(tr("CPU affinity:"));
+                        ui.label(
+                            current
+                                .priority
+                                .affinity
+                                .as_deref()
+                                .map_or(tr("Unknown").to_string(), format_cpus),
+                        );
+                        ui.end_row();
+                    });
+
+                let mut reload = None;
+                ui.horizontal(|ui| {
+                    if ui.small_button(tr("📋 Copy command line")).clicked() {
+                        ui.ctx().copy_text(command_line.clone());
+                    }
+                    if ui.small_button(tr("↻ Refresh")).clicked() {
+                        reload = Some(current.environ.is_some());
+                    }
+                    let editing = self
+                        .priority_draft
+                        .as_ref()
+                        .is_some_and(|(draft_pid, _)| *draft_pid == pid);
+                    if !cfg!(target_arch = "wasm32")
+                        && !editing
+                        && ui
+                            .small_button(tr("✏ Change priority"))
+                            .on_hover_text(tr("Renice the process or restrict it to some CPUs"))
+                            .clicked()
+                    {
+                        self.priority_draft = Some((pid, current.priority.clone()));
+                        self.priority_status = None;
+                    }
+                    if current.environ.is_none()
+                        && ui
+                            .small_button(tr("Show environment"))
+                            .on_hover_text(tr("Environment variables can contain secrets"))
+                            .clicked()
+                    {
+                        reload = Some(true);
+                    }
+                });
+                if let Some(applied) = show_priority_editor(
+                    ui,
+                    &mut self.priority_draft,
+                    pid,
+                    &current.priority,
+                    cpu_count,
+                ) {
+                    self.priority_status = Some((pid, applied));
+                    reload = Some(current.environ.is_some());
+                }
+                if let Some((_, status)) = self
+                    .priority_status
+                    .as_ref()
+                    .filter(|(changed, _)| *changed == pid)
+                {
+                    match status {
+                        Ok(message) => ui.label(message),
+                        Err(err) => ui.colored_label(ui.visuals().error_fg_color, err),
+                    };
+                }
+                if let Some(environ) = &current.environ {
+                    ui.collapsing(format!("{} ({})", tr("Environment"), environ.len()), |ui| {
+                        if ui.small_button(tr("📋 Copy environment")).clicked() {
+                            ui.ctx().copy_text(environ.join("\n"));
                         }
+                        egui::ScrollArea::vertical()
+                            .max_height(200.0)
+                            .id_salt(("process_environ", pid))
+                            .show(ui, |ui| {
+                                for variable in environ {
+                                    ui.monospace(variable);
+                                }
+                            });
                     });
+                }
+                if let Some(with_environ) = reload {
+                    *details = ProcessDetails::collect(pid, with_environ);
+                }
+            });
+        if response.body_returned.is_none() {
+            self.details.remove(&pid);
+        }
+    }
+
+    /// "Network connections" expander of one process, listing its TCP and UDP
+    /// sockets. Read when opened and on "Refresh" only, dropped when closed.
+    fn show_sockets(&mut self, ui: &mut egui::Ui, pid: Pid) {
+        let response = egui::CollapsingHeader::new(tr("Network connections"))
+            .id_salt(("process_sockets", pid))
+            .show(ui, |ui| {
+                let listing = self.sockets.entry(pid).or_insert_with(|| sockets(pid));
+                let mut refresh = false;
+                ui.horizontal(|ui| {
+                    match listing {
+                        Ok(list) => ui.label(tr_format("{} open sockets", &[&list.len()])),
+                        Err(err) => ui.colored_label(ui.visuals().error_fg_color, err.as_str()),
+                    };
+                    refresh = ui.small_button(tr("↻ Refresh")).clicked();
+                });
+                if let Ok(list) = listing {
+                    if !list.is_empty() {
+                        egui::ScrollArea::vertical()
+                            .max_height(200.0)
+                            .id_salt(("process_sockets_scroll", pid))
+                            .show(ui, |ui| {
+                                egui::Grid::new(("process_sockets_grid", pid))
+                                    .num_columns(4)
+                                    .striped(true)
+                                    .show(ui, |ui| {
+                                        ui.strong(tr("Proto"));
+                                        ui.strong(tr("Local"));
+                                        ui.strong(tr("Remote"));
+                                        ui.strong(tr("State"));
+                                        ui.end_row();
+                                        for socket in list.iter() {
+                                            ui.label(socket.protocol.label());
+                                            ui.monospace(socket.local.to_string());
+                                            ui.monospace(
+                                                socket.remote.map_or("-".to_string(), |remote| {
+                                                    remote.to_string()
+                                                }),
+                                            );
+                                            ui.label(
+                                                socket.state.map_or("", |state| state.label()),
+                                            );
+                                            ui.end_row();
+                                        }
+                                    });
+                            });
+                    }
+                }
+                if refresh {
+                    *listing = sockets(pid);
+                }
+            });
+        if response.body_returned.is_none() {
+            self.sockets.remove(&pid);
+        }
+    }
+
+    /// Zoom & pan / follow live toggle of one plot, returning the samples taken since
+    /// it stopped following
+    fn follow_toggle(&mut self, ui: &mut egui::Ui, plot: egui::Id, tick: u64) -> Option<u64> {
+        let frozen_at = self.frozen_plots.get(&plot).copied();
+        let (text, hover) = match frozen_at {
+            Some(_) => ("▶ Follow live", "Snap back to the newest samples"),
+            None => (
+                "🔍 Zoom & pan",
+                "Stop following and explore the whole retained history: \
+                 drag or scroll to pan, ctrl+scroll to zoom, double-click to reset",
+            ),
+        };
+        if ui.small_button(tr(text)).on_hover_text(tr(hover)).clicked() {
+            match frozen_at {
+                Some(_) => self.frozen_plots.remove(&plot),
+                None => self.frozen_plots.insert(plot, tick),
+            };
+        }
+        self.frozen_plots
+            .get(&plot)
+            .map(|frozen_at| tick.saturating_sub(*frozen_at))
+    }
+
+    /// "Save image" button for a plot, snapshotting the visible part of `history`
+    fn image_button<T: Into<f64> + Copy>(
+        &mut self,
+        ui: &mut egui::Ui,
+        title: String,
+        y_label: &str,
+        history: &[T],
+        markers: &[PlotMarker],
+        window: PlotWindow,
+    ) {
+        if cfg!(target_arch = "wasm32") {
+            return;
+        }
+        if ui
+            .small_button(tr("💾 Save image"))
+            .on_hover_text(tr("Save this plot as PNG or SVG"))
+            .clicked()
+        {
+            let values: Vec<f64> = history[history.len().saturating_sub(window.len)..]
+                .iter()
+                .map(|&value| value.into())
+                .collect();
+            let panel = PlotPanel {
+                title: title.clone(),
+                y_label: y_label.to_string(),
+                markers: markers
+                    .iter()
+                    .filter(|marker| (marker.samples_ago as usize) < values.len())
+                    .cloned()
+                    .collect(),
+                interval: window.interval,
+                values,
+            };
+            self.image_export = Some(ImageExport::new(title, vec![panel]));
+        }
+    }
+
+    /// Snapshots the aggregate CPU and memory plots of every monitored
+    /// identifier into a single overview export
+    pub fn export_overview(
+        &mut self,
+        metrics: &Metrics,
+        identifiers: &[ProcessIdentifier],
+        settings: &Settings,
+    ) {
+        let cpu_count = metrics.monitor.cpu_count();
+        let mut panels = Vec::new();
+        for identifier in identifiers {
+            let Some(data) = metrics.get_process_data(identifier) else {
+                continue;
+            };
+            let retained_len = data
+                .genereal
+                .history
+                .retained_len(&GENERAL_STATS_PID)
+                .max(data.history.history_len);
+            let times = data
+                .genereal
+                .history
+                .get_sample_times(&GENERAL_STATS_PID)
+                .unwrap_or_default();
+            let clock = SampleClock {
+                times: &times,
+                interval: metrics.interval_of(identifier),
+            };
+            let window_len = self
+                .time_window
+                .samples(clock, settings.display_len(retained_len));
+            let interval = clock.spacing(window_len);
+            let visible = |len: usize| len.saturating_sub(window_len);
+
+            let cpu = data
+                .genereal
+                .history
+                .get_cpu_history_tiered(&GENERAL_STATS_PID)
+                .unwrap_or_default();
+            panels.push(PlotPanel {
+                title: tr_format("{} CPU", &[&settings.label(identifier)]),
+                y_label: format!("CPU ({})", tr(settings.cpu_scale.label())),
+                values: cpu[visible(cpu.len())..]
+                    .iter()
+                    .map(|&value| settings.cpu_scale.apply(value, cpu_count) as f64)
+                    .collect(),
+                markers: Vec::new(),
+                interval,
+            });
+
+            let memory = data
+                .genereal
+                .history
+                .get_memory_history_tiered(&GENERAL_STATS_PID)
+                .unwrap_or_default();
+            panels.push(PlotPanel {
+                title: tr_format("{} Memory", &[&settings.label(identifier)]),
+                y_label: format!(
+                    "{} ({})",
+                    tr("Memory"),
+                    settings.memory_unit.format_value(0.0).1
+                ),
+                values: memory[visible(memory.len())..]
+                    .iter()
+                    .map(|&value| settings.memory_unit.format_value(value as f32).0 as f64)
+                    .collect(),
+                markers: Vec::new(),
+                interval,
+            });
+        }
+        self.image_export = Some(ImageExport::new(tr("tvis overview").to_string(), panels));
+    }
+
+    pub fn show_history_summary(
+        &mut self,
+        ctx: &egui::Context,
+        metrics: &Metrics,
+        settings: &Settings,
+    ) {
+        let Some(identifier) = &self.history_summary else {
+            return;
+        };
+        let Some(process_data) = metrics.get_process_data(identifier) else {
+            self.history_summary = None;
+            return;
+        };
+        let cpu_count = metrics.monitor.cpu_count();
+        let summary = HistorySummary::new(
+            process_data,
+            metrics.availability(identifier),
+            metrics.interval_of(identifier),
+            |value| settings.cpu_scale.apply(value, cpu_count),
+        );
+        let mut open = true;
+        egui::Window::new(format!(
+            "📊 {} {}",
+            tr("Summary:"),
+            settings.label(identifier)
+        ))
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.weak(tr_format(
+                "Over the retained history, {}",
+                &[&format_duration(summary.duration)],
+            ));
+            egui::Grid::new("history_summary")
+                .num_columns(2)
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label(tr("CPU time"));
+                    ui.label(format!("{:.1} s", summary.cpu_seconds))
+                        .on_hover_text(tr("CPU-seconds consumed by the whole tree"));
+                    ui.end_row();
+                    for (threshold, time) in CPU_THRESHOLDS.iter().zip(summary.time_above) {
+                        ui.label(tr_format(
+                            "Above {} ({})",
+                            &[&percent(*threshold, 0), &tr(settings.cpu_scale.label())],
+                        ));
+                        ui.label(format_duration(time));
+                        ui.end_row();
+                    }
+                    ui.label(tr("Max concurrent processes"));
+                    ui.label(summary.max_processes.to_string());
+                    ui.end_row();
+                    let (peak, unit) = settings
+                        .memory_unit
+                        .format_value(summary.peak_memory as f32);
+                    ui.label(tr("Memory high-water mark"));
+                    ui.label(tr_format(
+                        "{} {}, {} ago",
+                        &[
+                            &number(peak, 1),
+                            &unit,
+                            &format_duration(summary.peak_memory_ago),
+                        ],
+                    ));
+                    ui.end_row();
+                    ui.label(tr("Restarts"));
+                    ui.label(summary.restarts.to_string());
+                    ui.end_row();
+                });
+        });
+        if !open {
+            self.history_summary = None;
+        }
+    }
+
+    /// Processes added and removed and the per-process changes between the two
+    /// snapshots of an identifier
+    pub fn show_inspection_diff(
+        &mut self,
+        ctx: &egui::Context,
+        metrics: &Metrics,
+        settings: &Settings,
+    ) {
+        let Some(identifier) = &self.inspection_diff else {
+            return;
+        };
+        let diff = match self.inspections.get(identifier) {
+            Some(inspections) if inspections.len() == KEPT_INSPECTIONS => {
+                SnapshotDiff::new(&inspections[0], &inspections[1])
+            }
+            _ => {
+                self.inspection_diff = None;
+                return;
+            }
+        };
+        let cpu_count = metrics.monitor.cpu_count();
+        let cpu = |value: f32| value.signum() * settings.cpu_scale.apply(value.abs(), cpu_count);
+        let memory = |bytes: i64| {
+            let (value, unit) = settings
+                .memory_unit
+                .format_value(bytes.unsigned_abs() as f32);
+            let sign = if bytes < 0 { "-" } else { "+" };
+            format!("{sign}{value:.1} {unit}")
+        };
+        let mut open = true;
+        let mut clear = false;
+        egui::Window::new(format!(
+            "📸 {} {}",
+            tr("Snapshot diff:"),
+            settings.label(identifier)
+        ))
+        .open(&mut open)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            ui.weak(tr_format(
+                "{} and {} samples apart",
+                &[&format_duration(diff.elapsed), &diff.samples],
+            ));
+            ui.label(tr_format(
+                "CPU {} | Memory {} | Processes {}",
+                &[
+                    &format!(
+                        "{}{}",
+                        if diff.cpu < 0.0 { "" } else { "+" },
+                        percent(cpu(diff.cpu), 1)
+                    ),
+                    &memory(diff.memory),
+                    &format!("{:+}", diff.processes),
+                ],
+            ));
+            for (title, processes) in [("Added", &diff.added), ("Removed", &diff.removed)] {
+                if processes.is_empty() {
+                    continue;
+                }
+                ui.collapsing(format!("{} ({})", tr(title), processes.len()), |ui| {
+                    for (pid, name) in processes {
+                        ui.label(format!("{name} ({pid})"));
+                    }
+                });
+            }
+            ui.separator();
+            egui::ScrollArea::vertical()
+                .max_height(400.0)
+                .show(ui, |ui| {
+                    egui::Grid::new("inspection_diff")
+                        .num_columns(4)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.strong(tr("PID"));
+                            ui.strong(tr("Name"));
+                            ui.strong(tr("Δ Avg CPU"));
+                            ui.strong(tr("Δ Memory"));
+                            ui.end_row();
+                            for delta in &diff.changed {
+                                ui.label(delta.pid.to_string());
+                                ui.label(&delta.name);
+                                ui.label(format!("{:+.1}%", cpu(delta.avg_cpu)));
+                                ui.label(memory(delta.memory));
+                                ui.end_row();
+                            }
+                        });
                 });
+            if ui
+                .button(tr("Clear snapshots"))
+                .on_hover_text(tr("Start over from a new first snapshot"))
+                .clicked()
+            {
+                clear = true;
             }
         });
+        if clear {
+            self.inspections.remove(identifier);
+        }
+        if !open || clear {
+            self.inspection_diff = None;
+        }
+    }
+
+    pub fn show_image_export(&mut self, ctx: &egui::Context) {
+        let Some(export) = &mut self.image_export else {
+            return;
+        };
+        let mut open = true;
+        let mut close = false;
+        egui::Window::new(tr("💾 Save Image"))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(&export.title);
+                if export.panels.len() > 1 {
+                    ui.weak(tr_format("{} plots", &[&export.panels.len()]));
+                }
+                ui.horizontal(|ui| {
+                    ui.label(tr("Format:"));
+                    for format in [ImageFormat::Png, ImageFormat::Svg] {
+                        if ui
+                            .selectable_label(export.format == format, format.extension())
+                            .clicked()
+                        {
+                            export.set_format(format);
+                        }
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label(tr("File:"));
+                    ui.text_edit_singleline(&mut export.path);
+                });
+                ui.horizontal(|ui| {
+                    if ui.button(tr("Save")).clicked() {
+                        #[cfg(not(target_arch = "wasm32"))]
+                        {
+                            export.status = Some(
+                                super::export::save_image(export)
+                                    .map(|()| format!("{} {}", tr("Saved to"), export.path)),
+                            );
+                        }
+                    }
+                    if ui.button(tr("Close")).clicked() {
+                        close = true;
+                    }
+                });
+                match &export.status {
+                    Some(Ok(message)) => {
+                        ui.label(message);
+                    }
+                    Some(Err(err)) => {
+                        ui.colored_label(ui.visuals().error_fg_color, err);
+                    }
+                    None => {}
+                }
+            });
+        if !open || close {
+            self.image_export = None;
+        }
+    }
+}
+
+/// Green/red strip of the samples in the history window on which `availability`'s
+/// identifier had a live process, with the uptime percentage
+pub fn availability_strip(ui: &mut egui::Ui, availability: &Availability) {
+    let history = availability.history();
+    let Some(uptime) = availability.uptime() else {
+        return;
+    };
+    ui.horizontal(|ui| {
+        let label = ui.label(format!("{} {uptime:.1}%", tr("Uptime")));
+        let size = egui::vec2(ui.available_width(), label.rect.height() * 0.6);
+        let (rect, response) = ui.allocate_exact_size(size, egui::Sense::hover());
+        let up_color = egui::Color32::from_rgb(0x43, 0xa0, 0x47);
+        let down_color = ui.visuals().error_fg_color;
+        let sample_width = rect.width() / history.len() as f32;
+        // One rectangle per run of equal samples
+        let mut start = 0;
+        for end in 1..=history.len() {
+            if end < history.len() && history[end] == history[start] {
+                continue;
+            }
+            let segment = egui::Rect::from_x_y_ranges(
+                rect.left() + start as f32 * sample_width..=rect.left() + end as f32 * sample_width,
+                rect.y_range(),
+            );
+            let color = if history[start] { up_color } else { down_color };
+            ui.painter().rect_filled(segment, 0.0, color);
+            start = end;
+        }
+        response.on_hover_text(tr_format(
+            "Up on {} of the last {} samples\n{} of {} samples since monitoring started",
+            &[
+                &percent(uptime, 1),
+                &history.len(),
+                &availability.up,
+                &availability.total,
+            ],
+        ));
+    });
+}
+
+/// What a screen reader says when a process card is focused
+fn card_readout(process: &ProcessInfo, settings: &Settings, cpu_count: usize) -> String {
+    let (memory, unit) = settings
+        .memory_unit
+        .format_value(process.current_memory as f32);
+    let template = if process.is_thread {
+        "Thread {}, PID {}, CPU {}, memory {} {}"
+    } else {
+        "Process {}, PID {}, CPU {}, memory {} {}"
+    };
+    let mut readout = tr_format(
+        template,
+        &[
+            &process.name,
+            &process.pid,
+            &percent(settings.cpu_scale.apply(process.current_cpu, cpu_count), 1),
+            &number(memory, 1),
+            &unit,
+        ],
+    );
+    if let Some((nice, class)) = process.scheduling {
+        readout.push_str(&tr_format(
+            ", priority: {}, nice {}",
+            &[&tr(class.label()), &nice],
+        ));
+    }
+    if process.suspended {
+        readout.push_str(&format!(", {}", tr("suspended")));
+    }
+    if process.restricted {
+        readout.push_str(&format!(", {}", tr("needs elevation")));
+    }
+    readout
+}
+
+/// Realtime processes can starve the rest of the system and idle ones get
+/// starved, both are what a priority inversion looks like
+fn priority_color(ui: &egui::Ui, class: PriorityClass) -> Option<egui::Color32> {
+    match class {
+        PriorityClass::Realtime => Some(ui.visuals().error_fg_color),
+        PriorityClass::Idle => Some(ui.visuals().warn_fg_color),
+        _ => None,
+    }
+}
+
+/// Count of the processes of the tree running at an extreme priority
+fn show_extreme_priorities(ui: &mut egui::Ui, stats: &ProcessGeneralStats) {
+    for (count, class) in [
+        (stats.realtime_processes, PriorityClass::Realtime),
+        (stats.idle_processes, PriorityClass::Idle),
+    ] {
+        if count > 0 {
+            if let Some(color) = priority_color(ui, class) {
+                ui.colored_label(
+                    color,
+                    tr_format("⚡ {}: {} processes", &[&tr(class.label()), &count]),
+                )
+                .on_hover_text(tr("Sort the processes by priority to find them"));
+            }
+        }
+    }
+}
+
+/// Buttons picking the metric of a plot, returns the one clicked
+fn metric_toggle(ui: &mut egui::Ui, selected: MetricType) -> Option<MetricType> {
+    let mut picked = None;
+    for (metric, label) in [
+        (MetricType::Cpu, tr("CPU")),
+        (MetricType::Memory, tr("Memory")),
+        (MetricType::Gpu, tr("GPU")),
+        (MetricType::Processes, tr("Processes")),
+    ] {
+        let mut response = ui.selectable_label(selected == metric, label);
+        if metric == MetricType::Processes {
+            response = response.on_hover_text(tr("Processes and threads of the tree over time"));
+        }
+        if response.clicked() {
+            picked = Some(metric);
+        }
+    }
+    picked
+}
+
+/// Cards listed when "Top" is first checked
+const DEFAULT_TOP_N: usize = 20;
+
+/// Explains the CPU share next to its figure and on its plot
+const SHARE_HINT: &str = "The tree's CPU in percent of what every process on the machine \
+                          used at that moment, not of its capacity: near 100% the tree is \
+                          the load, low while the machine is busy something else is";
+
+/// Package temperature drawn as a warning, where most CPUs start throttling
+const HOT_CELSIUS: f32 = 90.0;
+
+const THERMAL_COLOR: egui::Color32 = egui::Color32::from_rgb(0xff, 0x98, 0x00);
+
+/// Crashes listed in the banner, the rest are in the events
+const MAX_CRASHES_SHOWN: usize = 3;
+
+/// Orders the cards of a tree. Threads count for the process they are listed under.
+fn sort_children(processes: &mut [&ProcessInfo], sort_type: SortType, ascending: bool) {
+    let mut threads: HashMap<Pid, usize> = HashMap::new();
+    if sort_type == SortType::ThreadCount {
+        for process in processes.iter().filter(|process| process.is_thread) {
+            if let Some(parent) = process.parent_pid {
+                *threads.entry(parent).or_default() += 1;
+            }
+        }
     }
+    processes.sort_by(|a, b| {
+        let order = match sort_type {
+            SortType::AvgCpu => a.avg_cpu.total_cmp(&b.avg_cpu),
+            SortType::CurrentCpu => a.current_cpu.total_cmp(&b.current_cpu),
+            SortType::PeakCpu => a.peak_cpu.total_cmp(&b.peak_cpu),
+            SortType::Memory => a.current_memory.cmp(&b.current_memory),
+            SortType::PeakMemory => a.peak_memory.cmp(&b.peak_memory),
+            SortType::Pid => a.pid.cmp(&b.pid),
+            SortType::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            SortType::ThreadCount => threads
+                .get(&a.pid)
+                .unwrap_or(&0)
+                .cmp(threads.get(&b.pid).unwrap_or(&0)),
+            // Threads and unreadable processes last
+            SortType::Priority => match (a.scheduling, b.scheduling) {
+                (Some((a_nice, a_class)), Some((b_nice, b_class))) => {
+                    (a_class, a_nice).cmp(&(b_class, b_nice))
+                }
+                (a, b) => b.is_some().cmp(&a.is_some()),
+            },
+        };
+        if ascending {
+            order
+        } else {
+            order.reverse()
+        }
+    });
 }
+
+/// Right-click menu on `response` copying the PID, command line or executable
+/// path of `pid`, e.g. for `gdb -p` or `strace`. The last two are read on click.
+pub fn copy_menu(response: &egui::Response, pid: Pid) {
+    response.context_menu(|ui| {
+        if ui.button("📋 Copy PID").clicked() {
+            ui.ctx().copy_text(pid.to_string());
+            ui.close_menu();
+        }
+        if ui.button("📋 Copy command line").clicked() {
+            if let Some(details) = ProcessDetails::collect(pid, false) {
+                ui.ctx().copy_text(details.cmd.join(" "));
+            }
+            ui.close_menu();
+        }
+        if ui.button("📋 Copy executable path").clicked() {
+            if let Some(exe) = ProcessDetails::collect(pid, false).and_then(|details| details.exe) {
+                ui.ctx().copy_text(exe.display().to_string());
+            }
+            ui.close_menu();
+        }
+    });
+}
+
+/// Package temperature and CPU clock with their trend over the window, to
+/// tell a throttled CPU from a tree that simply has less to do
+fn thermal_strip(
+    ui: &mut egui::Ui,
+    thermals: &CpuThermals,
+    window_len: usize,
+    settings: &Settings,
+) {
+    let skipped = |len: usize| len.saturating_sub(window_len);
+    ui.horizontal(|ui| {
+        let width = (ui.available_width() / 2.0 - 120.0).max(40.0);
+        match thermals.temperature {
+            Some(temperature) => {
+                let text = format!("🌡 {} °C", number(temperature, 0));
+                let label = if temperature >= HOT_CELSIUS {
+                    ui.colored_label(ui.visuals().warn_fg_color, text)
+                } else {
+                    ui.label(text)
+                };
+                label.on_hover_text(tr("CPU package temperature"));
+                let temperatures: Vec<f32> = thermals
+                    .temperatures
+                    .iter()
+                    .skip(skipped(thermals.temperatures.len()))
+                    .copied()
+                    .collect();
+                let peak = temperatures.iter().copied().fold(HOT_CELSIUS, f32::max);
+                sparkline(ui, &temperatures, peak, THERMAL_COLOR, width);
+            }
+            None => {
+                ui.weak(tr("🌡 No temperature sensor"));
+            }
+        }
+        ui.label(" | ");
+        if thermals.frequency > 0 {
+            ui.label(format!(
+                "⚡ {} GHz",
+                number(thermals.frequency as f64 / 1000.0, 2)
+            ))
+            .on_hover_text(tr("Average clock of the cores"));
+            let frequencies: Vec<f32> = thermals
+                .frequencies
+                .iter()
+                .skip(skipped(thermals.frequencies.len()))
+                .map(|&frequency| frequency as f32)
+                .collect();
+            let peak = frequencies.iter().copied().fold(0.0, f32::max);
+            sparkline(ui, &frequencies, peak, settings.series_colors.cpu, width);
+        } else {
+            ui.weak(tr("⚡ Clock unavailable"));
+        }
+    });
+}
+
+/// Tiny line of `values` scaled to `max`, `width` points wide and one row high
+pub fn sparkline(
+    ui: &mut egui::Ui,
+    values: &[f32],
+    max: f32,
+    color: egui::Color32,
+    width: f32,
+) -> egui::Response {
+    let size = egui::vec2(width, ui.spacing().interact_size.y * 0.8);
+    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::hover());
+    if values.len() < 2 || max <= 0.0 {
+        return response;
+    }
+    let step = rect.width() / (values.len() - 1) as f32;
+    let points: Vec<egui::Pos2> = values
+        .iter()
+        .enumerate()
+        .map(|(i, &value)| {
+            egui::pos2(
+                rect.left() + i as f32 * step,
+                rect.bottom() - (value / max).clamp(0.0, 1.0) * rect.height(),
+            )
+        })
+        .collect();
+    ui.painter()
+        .add(egui::Shape::line(points, egui::Stroke::new(1.0, color)));
+    response
+}
+
+/// M4 downsampling: the first, minimum, maximum and last sample of each of
+/// `buckets` equal slices, in order, as (index, value) pairs
+pub(crate) fn downsample_m4(
+    values: &[f64],
+    buckets: usize,
+) -> impl Iterator<Item = (usize, f64)> + '_ {
+    let chunk_len = if values.len() <= buckets * 4 {
+        1
+    } else {
+        values.len().div_ceil(buckets)
+    };
+    values
+        .chunks(chunk_len)
+        .enumerate()
+        .flat_map(move |(bucket, chunk)| {
+            let (mut min, mut max) = (0, 0);
+            for (i, value) in chunk.iter().enumerate() {
+                if *value < chunk[min] {
+                    min = i;
+                }
+                if *value > chunk[max] {
+                    max = i;
+                }
+            }
+            let mut picked = [0, min, max, chunk.len() - 1];
+            picked.sort_unstable();
+            (0..picked.len())
+                .filter(move |&k| k == 0 || picked[k - 1] != picked[k])
+                .map(move |k| (bucket * chunk_len + picked[k], chunk[picked[k]]))
+        })
+}
+
+/// Memory history of `pid` in the configured unit, or its growth per second when `rate`
+fn memory_series(
+    history: &ProcessHistory,
+    pid: &Pid,
+    rate: bool,
+    interval: Duration,
+    settings: &Settings,
+) -> Option<Vec<f32>> {
+    let to_unit = |bytes: f32| settings.memory_unit.format_value(bytes).0;
+    if rate {
+        history
+            .get_memory_rate(pid, interval)
+            .map(|rates| rates.into_iter().map(to_unit).collect())
+    } else {
+        history
+            .get_memory_history_tiered(pid)
+            .map(|history| history.into_iter().map(|x| to_unit(x as f32)).collect())
+    }
+}
+
+/// "OOM in 2h 10m 00s" or "Reaches 4.0 GB in …" with the fitted trend on hover
+fn show_memory_projection(ui: &mut egui::Ui, projection: &MemoryProjection, settings: &Settings) {
+    let (per_second, unit) = settings
+        .memory_unit
+        .format_value(projection.bytes_per_second as f32);
+    let target = match settings.oom_projection.limit_bytes {
+        Some(limit) => {
+            let (limit, unit) = settings.memory_unit.format_value(limit as f32);
+            tr_format("Reaches {} {}", &[&number(limit, 1), &unit])
+        }
+        None => "OOM".to_string(),
+    };
+    let response = match projection.eta {
+        Some(eta) if eta < Duration::from_secs(3600) => ui.colored_label(
+            ui.visuals().error_fg_color,
+            tr_format("{} in {}", &[&target, &format_duration(eta)]),
+        ),
+        Some(eta) => ui.label(tr_format("{} in {}", &[&target, &format_duration(eta)])),
+        None => ui.label(tr_format("{}: not growing", &[&target])),
+    };
+    response.on_hover_text(tr_format(
+        "Memory trend {} {}/s over the last {} min",
+        &[
+            &format!("{per_second:+.2}"),
+            &unit,
+            &(settings.oom_projection.window_secs / 60),
+        ],
+    ));
+}
+
+/// "Possible leak: memory growing 12.3 MB/min for 14m 2s"
+pub fn leak_summary(leak: &LeakSuspect) -> String {
+    tr_format(
+        "Possible leak: memory growing {} MB/min for {}",
+        &[
+            &number(leak.mb_per_min(), 1),
+            &format_duration(leak.since.elapsed()),
+        ],
+    )
+}
+
+/// "Switches: 12/s voluntary, 3/s involuntary | Faults: 150/s minor, 0/s major"
+fn sched_summary(sched: &SchedRates) -> String {
+    tr_format(
+        "Switches: {}/s voluntary, {}/s involuntary | Faults: {}/s minor, {}/s major",
+        &[
+            &number(sched.voluntary_switches, 0),
+            &number(sched.involuntary_switches, 0),
+            &number(sched.minor_faults, 0),
+            &number(sched.major_faults, 0),
+        ],
+    )
+}
+
+/// Nice value and per-CPU checkboxes of the priority being edited for `pid`.
+/// Returns the outcome once "Apply" was clicked.
+fn show_priority_editor(
+    ui: &mut egui::Ui,
+    priority_draft: &mut Option<(Pid, Priority)>,
+    pid: Pid,
+    current: &Priority,
+    cpu_count: usize,
+) -> Option<Result<String, String>> {
+    let (_, draft) = priority_draft
+        .as_mut()
+        .filter(|(draft_pid, _)| *draft_pid == pid)?;
+    let mut apply = false;
+    let mut cancel = false;
+    ui.group(|ui| {
+        if let Some(nice) = &mut draft.nice {
+            ui.horizontal(|ui| {
+                ui.label(tr("Nice:"));
+                ui.add(egui::DragValue::new(nice).range(NICE_RANGE))
+                    .on_hover_text(tr(if cfg!(windows) {
+                        "Lower runs first. Windows uses the closest priority class"
+                    } else {
+                        "Lower runs first. Going below the current value needs root"
+                    }));
+            });
+        }
+        if let Some(affinity) = &mut draft.affinity {
+            ui.label(tr("Allowed CPUs:"));
+            ui.horizontal_wrapped(|ui| {
+                let last = affinity.iter().copied().max().unwrap_or(0);
+                for cpu in 0..cpu_count.max(last + 1) {
+                    let mut allowed = affinity.contains(&cpu);
+                    if ui.checkbox(&mut allowed, cpu.to_string()).changed() {
+                        if allowed {
+                            affinity.push(cpu);
+                            affinity.sort_unstable();
+                        } else {
+                            affinity.retain(|&other| other != cpu);
+                        }
+                    }
+                }
+            });
+        }
+        ui.horizontal(|ui| {
+            apply = ui.button(tr("Apply")).clicked();
+            cancel = ui.button(tr("Cancel")).clicked();
+        });
+    });
+    if cancel {
+        *priority_draft = None;
+    }
+    if !apply {
+        return None;
+    }
+    let (_, draft) = priority_draft.take()?;
+    Some(apply_priority(pid, draft, current))
+}
+
+/// Applies what changed from `current` to `draft`, stopping at the first failure
+fn apply_priority(pid: Pid, draft: Priority, current: &Priority) -> Result<String, String> {
+    let mut changed = Vec::new();
+    if let Some(nice) = draft.nice.filter(|&nice| current.nice != Some(nice)) {
+        set_nice(pid, nice).map_err(|err| format!("{} {err}", tr("Nice not changed:")))?;
+        changed.push(format!("nice {nice}"));
+    }
+    if let Some(affinity) = draft
+        .affinity
+        .filter(|cpus| current.affinity.as_ref() != Some(cpus))
+    {
+        set_affinity(pid, &affinity)
+            .map_err(|err| format!("{} {err}", tr("Affinity not changed:")))?;
+        changed.push(format!("CPUs {}", format_cpus(&affinity)));
+    }
+    Ok(if changed.is_empty() {
+        tr("Nothing changed").to_string()
+    } else {
+        format!("{} {}", tr("Applied"), changed.join(", "))
+    })
+}
+
+/// CPUs as ranges, "0-3, 6"
+fn format_cpus(cpus: &[usize]) -> String {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for &cpu in cpus {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == cpu => *end = cpu,
+            _ => ranges.push((cpu, cpu)),
+        }
+    }
+    if ranges.is_empty() {
+        return tr("None").to_string();
+    }
+    ranges
+        .iter()
+        .map(|&(start, end)| {
+            if start == end {
+                start.to_string()
+            } else {
+                format!("{start}-{end}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Context switch and page fault rates of the whole tree, two lines per plot
+fn show_sched_plots(
+    ui: &mut egui::Ui,
+    process_identifier: &ProcessIdentifier,
+    process_data: &ProcessData,
+    settings: &Settings,
+    window_len: usize,
+) {
+    let history = process_data
+        .genereal
+        .history
+        .get_sched_history(&GENERAL_STATS_PID)
+        .unwrap_or_default();
+    let Some(current) = process_data.genereal.stats.sched else {
+        ui.weak(tr(
+            "Waiting for a second sample of the switch and fault counters",
+        ));
+        return;
+    };
+    ui.label(sched_summary(&current));
+    let visuals = ui.visuals().clone();
+    let series = |rate: fn(&SchedRates) -> f32| history.iter().map(rate).collect::<Vec<f32>>();
+    let mut plots: Vec<RatePlot> = vec![
+        (
+            "switches",
+            tr("Context switches (/s)").to_string(),
+            vec![
+                (
+                    "Voluntary",
+                    series(|rates| rates.voluntary_switches),
+                    settings.series_colors.cpu,
+                ),
+                (
+                    "Involuntary",
+                    series(|rates| rates.involuntary_switches),
+                    visuals.warn_fg_color,
+                ),
+            ],
+        ),
+        (
+            "faults",
+            tr("Page faults (/s)").to_string(),
+            vec![
+                (
+                    "Minor",
+                    series(|rates| rates.minor_faults),
+                    settings.series_colors.memory,
+                ),
+                (
+                    "Major",
+                    series(|rates| rates.major_faults),
+                    visuals.error_fg_color,
+                ),
+            ],
+        ),
+    ];
+    plots.extend(ebpf_plots(ui, &history, settings));
+    for (id, y_label, lines) in plots {
+        ui.label(y_label);
+        egui_plot::Plot::new((process_identifier, id, "sched_plot"))
+            .height(80.0)
+            .include_x(0.0)
+            .include_x(window_len as f64)
+            .include_y(0.0)
+            .legend(egui_plot::Legend::default())
+            .allow_drag(false)
+            .allow_zoom(false)
+            .allow_scroll(false)
+            .allow_double_click_reset(false)
+            .show(ui, |plot_ui| {
+                for (name, values, color) in lines {
+                    let visible = &values[values.len().saturating_sub(window_len)..];
+                    let start_x = (window_len - visible.len()) as f64;
+                    let points: Vec<[f64; 2]> = visible
+                        .iter()
+                        .enumerate()
+                        .map(|(i, &value)| [start_x + i as f64, value as f64])
+                        .collect();
+                    plot_ui.line(egui_plot::Line::new(points).color(color).name(tr(name)));
+                }
+            });
+    }
+}
+
+/// Id, axis label and lines of a plot below the selected metric
+type RatePlot = (
+    &'static str,
+    String,
+    Vec<(&'static str, Vec<f32>, egui::Color32)>,
+);
+
+/// Syscall and TCP plots from the eBPF counters, or why they are missing
+#[cfg(all(target_os = "linux", feature = "ebpf"))]
+fn ebpf_plots(ui: &mut egui::Ui, history: &[SchedRates], settings: &Settings) -> Vec<RatePlot> {
+    let probes = match crate::metrics::process::ebpf::probes() {
+        Ok(probes) => probes,
+        Err(err) => {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                format!("⚠ {} {err}", tr("eBPF counters unavailable:")),
+            );
+            return Vec::new();
+        }
+    };
+    let series = |rate: fn(&SchedRates) -> f32| history.iter().map(rate).collect::<Vec<f32>>();
+    let mut plots = vec![(
+        "syscalls",
+        tr("Syscalls (/s)").to_string(),
+        vec![(
+            "Syscalls",
+            series(|rates| rates.syscalls),
+            settings.series_colors.cpu,
+        )],
+    )];
+    match probes.tcp_error() {
+        Some(err) => {
+            ui.weak(err);
+        }
+        None => {
+            let unit = settings.memory_unit.format_value(0.0).1;
+            let bytes = |rate: fn(&SchedRates) -> f32| {
+                history
+                    .iter()
+                    .map(|rates| settings.memory_unit.format_value(rate(rates)).0)
+                    .collect::<Vec<f32>>()
+            };
+            plots.push((
+                "tcp",
+                format!("TCP ({unit}/s)"),
+                vec![
+                    (
+                        "Sent",
+                        bytes(|rates| rates.tcp_sent),
+                        settings.series_colors.cpu,
+                    ),
+                    (
+                        "Received",
+                        bytes(|rates| rates.tcp_received),
+                        settings.series_colors.memory,
+                    ),
+                ],
+            ));
+        }
+    }
+    plots
+}
+
+#[cfg(not(all(target_os = "linux", feature = "ebpf")))]
+fn ebpf_plots(_ui: &mut egui::Ui, _history: &[SchedRates], _settings: &Settings) -> Vec<RatePlot> {
+    Vec::new()
+}
+
+/// Name, stacked value and color of a band of the CPU state plot
+type StateBand = (&'static str, fn(&SchedRates) -> f32, egui::Color32);
+
+/// User, system and I/O wait time of the whole tree stacked on one plot, so the
+/// top edge is all the time the tree spent running or blocked on disk
+fn show_cpu_state_plot(
+    ui: &mut egui::Ui,
+    process_identifier: &ProcessIdentifier,
+    process_data: &ProcessData,
+    settings: &Settings,
+    window_len: usize,
+) {
+    let history = process_data
+        .genereal
+        .history
+        .get_sched_history(&GENERAL_STATS_PID)
+        .unwrap_or_default();
+    let Some(current) = process_data.genereal.stats.sched else {
+        ui.weak(tr("Waiting for a second sample of the CPU times"));
+        return;
+    };
+    ui.label(tr_format(
+        "CPU states: {} user, {} system, {} I/O wait",
+        &[
+            &percent(current.user_cpu, 1),
+            &percent(current.system_cpu, 1),
+            &percent(current.iowait, 1),
+        ],
+    ));
+    let visuals = ui.visuals().clone();
+    let visible = &history[history.len().saturating_sub(window_len)..];
+    let start_x = (window_len - visible.len()) as f64;
+    // Each band is filled down to the axis and drawn over the one stacked on it
+    let bands: [StateBand; 3] = [
+        (
+            "I/O wait",
+            |rates| rates.user_cpu + rates.system_cpu + rates.iowait,
+            visuals.error_fg_color,
+        ),
+        (
+            "System",
+            |rates| rates.user_cpu + rates.system_cpu,
+            visuals.warn_fg_color,
+        ),
+        ("User", |rates| rates.user_cpu, settings.series_colors.cpu),
+    ];
+    egui_plot::Plot::new((process_identifier, "cpu_state_plot"))
+        .height(120.0)
+        .include_x(0.0)
+        .include_x(window_len as f64)
+        .include_y(0.0)
+        .legend(egui_plot::Legend::default())
+        .allow_drag(false)
+        .allow_zoom(false)
+        .allow_scroll(false)
+        .allow_double_click_reset(false)
+        .y_axis_formatter(|mark, _| format!("{:.0}%", mark.value))
+        .show(ui, |plot_ui| {
+            for (name, stacked, color) in bands {
+                let points: Vec<[f64; 2]> = visible
+                    .iter()
+                    .enumerate()
+                    .map(|(i, rates)| [start_x + i as f64, stacked(rates) as f64])
+                    .collect();
+                plot_ui.line(
+                    egui_plot::Line::new(points)
+                        .color(color)
+                        .fill(0.0)
+                        .name(tr(name)),
+                );
+            }
+        });
+}
+
+/// Largest magnitude in `values`, so negative rates still fit the plot
+fn abs_max(values: &[f32]) -> f32 {
+    values.iter().fold(0.0, |max, value| max.max(value.abs()))
+}
+
+/// Guides of `metric`, converted to the plot's unit
+fn guides_for(
+    overlays: &PlotOverlays,
+    metric: MetricType,
+    to_plot: impl Fn(f64) -> f64,
+) -> Vec<PlotGuide> {
+    overlays
+        .guides
+        .iter()
+        .filter(|guide| guide.metric == metric)
+        .map(|guide| PlotGuide {
+            value: to_plot(guide.value),
+            ..guide.clone()
+        })
+        .collect()
+}
+
+/// Drawn on top of a plot's samples
+#[derive(Clone, Copy)]
+struct PlotLayers<'a> {
+    markers: &'a [PlotMarker],
+    smoothing: Smoothing,
+    /// Enforced limit, in the plot's unit
+    limit: Option<f64>,
+    /// Guide lines, with their values in the plot's unit
+    guides: &'a [PlotGuide],
+    /// Samples taken since the plot stopped following the newest sample, which
+    /// draws the whole retained history and unlocks horizontal zoom and pan
+    frozen: Option<u64>,
+    /// Trend in the plot's unit per sample, drawn ahead of the newest sample
+    projection: Option<f64>,
+    /// Samples the worker missed, the line breaks over them
+    gaps: &'a [SampleGap],
+    color: egui::Color32,
+    /// Names the plot to screen readers, with a readout of its trend
+    label: &'a str,
+}
+
+impl<'a> PlotLayers<'a> {
+    fn with_limit(self, limit: Option<f64>) -> Self {
+        Self { limit, ..self }
+    }
+
+    fn with_guides(self, guides: &'a [PlotGuide]) -> Self {
+        Self { guides, ..self }
+    }
+
+    fn with_frozen(self, frozen: Option<u64>) -> Self {
+        Self { frozen, ..self }
+    }
+
+    fn with_projection(self, projection: Option<f64>) -> Self {
+        Self { projection, ..self }
+    }
+
+    fn with_color(self, color: egui::Color32) -> Self {
+        Self { color, ..self }
+    }
+
+    fn with_label(self, label: &'a str) -> Self {
+        Self { label, ..self }
+    }
+}
+
+/// Share of `samples` (CPU percents) in each 5% of `[0, 100]`, or of a wider
+/// range when they exceed it, with their P50 and P95 marked
+fn plot_histogram(
+    ui: &mut egui::Ui,
+    id: impl std::hash::Hash,
+    samples: &[f64],
+    x_label: &str,
+    color: egui::Color32,
+) {
+    const BUCKETS: usize = 20;
+    let max = (samples.iter().copied().fold(100.0, f64::max) / 100.0).ceil() * 100.0;
+    let histogram = Histogram::from_samples(samples, max, BUCKETS);
+    let total = histogram.total().max(1) as f64;
+    let width = histogram.bucket_width;
+    let bars = histogram
+        .counts
+        .iter()
+        .enumerate()
+        .map(|(i, &count)| {
+            egui_plot::Bar::new((i as f64 + 0.5) * width, count as f64 * 100.0 / total)
+                .width(width * 0.9)
+        })
+        .collect();
+    let distribution = Distribution::from_samples(samples.iter().copied());
+    let readout = format!(
+        "{x_label} histogram of {} samples: median {:.1}%, 95th percentile {:.1}%",
+        samples.len(),
+        distribution.p50,
+        distribution.p95
+    );
+    let response = egui_plot::Plot::new(id)
+        .height(100.0)
+        .include_x(0.0)
+        .include_x(max)
+        .include_y(0.0)
+        .x_axis_label(x_label)
+        .y_axis_label(tr("% of samples"))
+        .allow_drag(false)
+        .allow_zoom(false)
+        .allow_scroll(false)
+        .allow_boxed_zoom(false)
+        .show(ui, |plot_ui| {
+            plot_ui.bar_chart(
+                egui_plot::BarChart::new(bars)
+                    .color(color)
+                    .element_formatter(Box::new(move |bar, _| {
+                        format!(
+                            "{:.0}–{:.0}%: {:.1}% of samples",
+                            bar.argument - width / 2.0,
+                            bar.argument + width / 2.0,
+                            bar.value
+                        )
+                    })),
+            );
+            if samples.is_empty() {
+                return;
+            }
+            for (label, value) in [("P50", distribution.p50), ("P95", distribution.p95)] {
+                plot_ui.vline(
+                    egui_plot::VLine::new(value)
+                        .name(format!("{label}: {value:.1}%"))
+                        .color(plot_ui.ctx().style().visuals.weak_text_color())
+                        .style(egui_plot::LineStyle::dashed_loose()),
+                );
+            }
+        });
+    response
+        .response
+        .widget_info(|| egui::WidgetInfo::labeled(egui::WidgetType::Other, true, &readout));
+}
+
 fn plot_metric<T>(
     ui: &mut egui::Ui,
     id: impl std::hash::Hash,
@@ -316,6 +2914,7 @@ fn plot_metric<T>(
     history: Vec<T>,
     max_points: usize,
     max_value: T,
+    layers: &PlotLayers<'_>,
 ) where
     T: Into<f64> + Copy,
 {
@@ -327,20 +2926,212 @@ fn plot_metric<T>(
         .include_x(max_points as f64)
         .include_y(0.0)
         .include_y(max_value.into())
-        .allow_drag(false)
-        .allow_zoom(false)
-        .allow_scroll(false)
-        .allow_boxed_zoom(false)
-        .allow_double_click_reset(false);
+        .include_y(layers.limit.unwrap_or_default())
+        .allow_boxed_zoom(false);
+    let plot = layers
+        .guides
+        .iter()
+        .fold(plot, |plot, guide| plot.include_y(guide.value));
+    // A frozen plot keeps its x range (and any zoom into it) while samples shift in,
+    // a following one is reset every frame to end at the newest sample
+    let is_frozen = layers.frozen.is_some();
+    let plot = if is_frozen {
+        plot.auto_bounds([false, true].into())
+            .allow_drag([true, false])
+            .allow_zoom([true, false])
+            .allow_scroll([true, false])
+            .allow_double_click_reset(true)
+    } else {
+        plot.allow_drag(false)
+            .allow_zoom(false)
+            .allow_scroll(false)
+            .allow_double_click_reset(false)
+            .reset()
+    };
+    let shift = layers.frozen.unwrap_or_default() as f64;
 
-    plot.show(ui, |plot_ui| {
-        let start_x = (max_points - history.len()) as f64;
-        let points: Vec<[f64; 2]> = history
-            .iter()
-            .enumerate()
-            .map(|(i, &y)| [start_x + i as f64, y.into()])
-            .collect();
+    let to_f64 = |values: &[T]| -> Vec<f64> { values.iter().map(|&value| value.into()).collect() };
+    let smoothing = layers.smoothing;
+    // Four points per bucket keep every spike while staying near two points per pixel
+    let buckets = (ui.available_width() / 2.0).max(1.0) as usize;
+    let readout = trend_readout(
+        layers.label,
+        &to_f64(&history[history.len().saturating_sub(max_points)..]),
+    );
+    let newest = history.last().map(|&value| value.into());
+    let response = plot.show(ui, |plot_ui| {
+        // The newest sample sits at `max_points - 1 - shift`
+        let first_x = max_points as f64 - history.len() as f64 - shift;
+        let bounds = plot_ui.plot_bounds();
+        // A frozen plot can be panned anywhere in the history; only the samples in view
+        // are downsampled, so zooming in brings back the detail
+        let visible = if is_frozen && bounds.is_valid_x() {
+            let index = |x: f64| ((x - first_x).max(0.0) as usize).min(history.len());
+            let end = (index(bounds.max()[0]) + 2).min(history.len());
+            index(bounds.min()[0]).saturating_sub(1).min(end)..end
+        } else {
+            history.len().saturating_sub(max_points)..history.len()
+        };
+        // Only the samples in view are converted and smoothed, the smoothing
+        // starting early enough to be warmed up at the first of them
+        let warm_up = visible.start.saturating_sub(smoothing.warm_up());
+        let values = to_f64(&history[warm_up..visible.end]);
+        let smoothed = smoothing.apply(&values);
+        // x of the first sample after each gap
+        let gap_x = |gap: &SampleGap| max_points as f64 - 1.0 - gap.samples_ago as f64 - shift;
+        // One run of points between two gaps per line, so a gap isn't drawn as
+        // a slope between the samples around it
+        let to_lines = |values: &[f64]| -> Vec<Vec<[f64; 2]>> {
+            let mut lines = vec![Vec::new()];
+            for (i, y) in downsample_m4(&values[visible.start - warm_up..], buckets) {
+                let x = first_x + (visible.start + i) as f64;
+                let previous = lines.last().and_then(|line: &Vec<[f64; 2]>| line.last());
+                if previous.is_some_and(|previous| {
+                    layers.gaps.iter().any(|gap| {
+                        let gap_x = gap_x(gap);
+                        previous[0] < gap_x && gap_x <= x
+                    })
+                }) {
+                    lines.push(Vec::new());
+                }
+                lines.last_mut().unwrap().push([x, y]);
+            }
+            lines
+        };
+
+        for line in to_lines(&values) {
+            plot_ui.line(egui_plot::Line::new(line).width(2.0).color(layers.color));
+        }
+        if let Some(smoothed) = &smoothed {
+            for line in to_lines(smoothed) {
+                plot_ui.line(
+                    egui_plot::Line::new(line)
+                        .width(1.5)
+                        .color(plot_ui.ctx().style().visuals.warn_fg_color)
+                        .name(smoothing.label()),
+                );
+            }
+        }
+
+        let top = max_value.into();
+        for gap in layers.gaps {
+            let x = gap_x(gap);
+            if x - 1.0 < first_x {
+                continue;
+            }
+            plot_ui.polygon(
+                egui_plot::Polygon::new(vec![[x - 1.0, 0.0], [x, 0.0], [x, top], [x - 1.0, top]])
+                    .fill_color(
+                        plot_ui
+                            .ctx()
+                            .style()
+                            .visuals
+                            .error_fg_color
+                            .gamma_multiply(0.2),
+                    )
+                    .stroke(egui::Stroke::NONE)
+                    .name(tr_format("{} missed samples", &[&gap.missed])),
+            );
+        }
+
+        if let (Some(slope), Some(last)) = (layers.projection, newest) {
+            // A quarter of the window ahead, not below zero for a shrinking tree
+            let last_x = first_x + (history.len() - 1) as f64;
+            let ahead = max_points as f64 / 4.0;
+            plot_ui.line(
+                egui_plot::Line::new(vec![
+                    [last_x, last],
+                    [last_x + ahead, (last + slope * ahead).max(0.0)],
+                ])
+                .color(layers.color)
+                .style(egui_plot::LineStyle::dashed_loose())
+                .name(tr("Projection")),
+            );
+        }
+
+        if let Some(limit) = layers.limit {
+            plot_ui.hline(
+                egui_plot::HLine::new(limit)
+                    .color(plot_ui.ctx().style().visuals.error_fg_color)
+                    .style(egui_plot::LineStyle::dashed_dense())
+                    .name(tr("Limit")),
+            );
+        }
+
+        for guide in layers.guides {
+            let visuals = &plot_ui.ctx().style().visuals;
+            let exceeded_color = guide.color.unwrap_or(visuals.warn_fg_color);
+            let exceeded = newest.is_some_and(|newest| newest > guide.value);
+            let color = match (exceeded, guide.color) {
+                (true, _) => exceeded_color,
+                (false, Some(color)) => color.gamma_multiply(0.5),
+                (false, None) => visuals.weak_text_color(),
+            };
+            plot_ui.hline(
+                egui_plot::HLine::new(guide.value)
+                    .color(color)
+                    .width(if exceeded { 2.0 } else { 1.0 })
+                    .style(egui_plot::LineStyle::dashed_loose())
+                    .name(&guide.label),
+            );
+        }
 
-        plot_ui.line(egui_plot::Line::new(points).width(2.0));
+        for marker in layers.markers {
+            let x = max_points as f64 - 1.0 - marker.samples_ago as f64 - shift;
+            // Following plots fit their bounds to what is drawn, so older markers are left out
+            if x < -shift && !is_frozen {
+                continue;
+            }
+            let color = if marker.highlighted {
+                plot_ui.ctx().style().visuals.selection.bg_fill
+            } else {
+                marker
+                    .color
+                    .unwrap_or_else(|| plot_ui.ctx().style().visuals.weak_text_color())
+            };
+            plot_ui.vline(
+                egui_plot::VLine::new(x)
+                    .color(color)
+                    .width(if marker.highlighted { 2.0 } else { 1.0 })
+                    .style(egui_plot::LineStyle::dashed_loose()),
+            );
+            if !marker.label.is_empty() {
+                plot_ui.text(
+                    egui_plot::Text::new(
+                        egui_plot::PlotPoint::new(x, max_value.into()),
+                        &marker.label,
+                    )
+                    .color(color)
+                    .anchor(egui::Align2::LEFT_TOP),
+                );
+            }
+        }
     });
+    response
+        .response
+        .widget_info(|| egui::WidgetInfo::labeled(egui::WidgetType::Other, true, &readout));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn downsampling_keeps_the_extremes_of_each_bucket_in_order() {
+        // Short enough to draw every sample
+        let values = [3.0, 1.0, 2.0];
+        assert_eq!(
+            downsample_m4(&values, 1).collect::<Vec<_>>(),
+            vec![(0, 3.0), (1, 1.0), (2, 2.0)]
+        );
+
+        let values: Vec<f64> = (0..100).map(|i| if i == 37 { 50.0 } else { 1.0 }).collect();
+        let points: Vec<_> = downsample_m4(&values, 2).collect();
+        // The spike survives, along with the first and last sample of both halves
+        assert_eq!(
+            points,
+            vec![(0, 1.0), (37, 50.0), (49, 1.0), (50, 1.0), (99, 1.0)]
+        );
+        assert!(points.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
 }
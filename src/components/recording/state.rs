@@ -0,0 +1,73 @@
+use crate::metrics::recorder::{read_logs, RecordedRow};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Flight logs of another tvis instance, possibly one that crashed or ran on a
+/// machine that went down, read once and never written to
+pub struct Recording {
+    /// Rows of each identifier, oldest first
+    pub series: BTreeMap<String, Vec<RecordedRow>>,
+    /// Time of the first and last row
+    pub first_ms: u64,
+    pub last_ms: u64,
+}
+
+impl Recording {
+    pub fn open(directory: &Path) -> Result<Self, String> {
+        let rows = read_logs(directory)?;
+        let (Some(first), Some(last)) = (rows.first(), rows.last()) else {
+            return Err(format!(
+                "The flight log in {} is empty",
+                directory.display()
+            ));
+        };
+        let (first_ms, last_ms) = (first.unix_ms, last.unix_ms);
+        let mut series: BTreeMap<String, Vec<RecordedRow>> = BTreeMap::new();
+        for row in rows {
+            series
+                .entry(row.sample.identifier.clone())
+                .or_default()
+                .push(row);
+        }
+        Ok(Self {
+            series,
+            first_ms,
+            last_ms,
+        })
+    }
+}
+
+/// Window browsing an opened [`Recording`]
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+#[serde(default)]
+pub struct RecordingView {
+    /// Directory of the logs, the one this instance records into when empty
+    pub directory: String,
+    #[serde(skip)]
+    pub recording: Option<Recording>,
+    #[serde(skip)]
+    pub error: Option<String>,
+    /// Identifier plotted
+    #[serde(skip)]
+    pub selected: Option<String>,
+    #[serde(skip)]
+    pub show_window: bool,
+}
+
+impl RecordingView {
+    pub fn toggle_window(&mut self) {
+        self.show_window = !self.show_window;
+    }
+
+    /// Reads the logs in `directory`, replacing the recording shown
+    pub fn open(&mut self, directory: &Path) {
+        match Recording::open(directory) {
+            Ok(recording) => {
+                self.selected = recording.series.keys().next().cloned();
+                self.recording = Some(recording);
+                self.error = None;
+            }
+            Err(err) => self.error = Some(err),
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use super::state::{Recording, RecordingView};
+use crate::components::i18n::{tr, tr_format};
+use crate::components::process_view::{report::format_duration, ui::downsample_m4};
+use crate::components::settings::Settings;
+use crate::metrics::recorder::RecordedRow;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+impl RecordingView {
+    /// Shows the flight log opened from the directory field, read-only
+    pub fn show(&mut self, ctx: &egui::Context, settings: &Settings) {
+        if !self.show_window {
+            return;
+        }
+
+        let mut open = true;
+        egui::Window::new(tr("📼 Flight log"))
+            .open(&mut open)
+            .resizable(true)
+            .show(ctx, |ui| {
+                let recording_directory = settings.flight_recorder.directory.trim();
+                ui.horizontal(|ui| {
+                    ui.label(tr("Directory:"));
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.directory)
+                            .hint_text(match recording_directory {
+                                "" => tr("working directory"),
+                                directory => directory,
+                            })
+                            .desired_width(240.0),
+                    );
+                    if ui
+                        .button(tr("Open"))
+                        .on_hover_text(tr(
+                            "Read the flight log of another tvis instance, rotated files \
+                             included, without writing to it",
+                        ))
+                        .clicked()
+                    {
+                        let directory = match self.directory.trim() {
+                            "" => recording_directory.to_string(),
+                            directory => directory.to_string(),
+                        };
+                        self.open(Path::new(&directory));
+                    }
+                });
+                if let Some(err) = &self.error {
+                    ui.colored_label(ui.visuals().error_fg_color, err);
+                }
+                let Some(recording) = &self.recording else {
+                    return;
+                };
+                ui.weak(summary(recording));
+                egui::ComboBox::from_label(tr("Identifier"))
+                    .selected_text(self.selected.as_deref().unwrap_or_default())
+                    .show_ui(ui, |ui| {
+                        for identifier in recording.series.keys() {
+                            ui.selectable_value(
+                                &mut self.selected,
+                                Some(identifier.clone()),
+                                identifier,
+                            );
+                        }
+                    });
+                let Some(rows) = self
+                    .selected
+                    .as_ref()
+                    .and_then(|identifier| recording.series.get(identifier))
+                else {
+                    return;
+                };
+                ui.separator();
+                show_plots(ui, recording, rows, settings);
+            });
+        self.show_window = open;
+    }
+}
+
+/// "3 identifiers over 2h 10m 00s, last sample 5m 00s ago"
+fn summary(recording: &Recording) -> String {
+    let now_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    tr_format(
+        "{} identifiers over {}, last sample {} ago",
+        &[
+            &recording.series.len(),
+            &format_duration(Duration::from_millis(
+                recording.last_ms - recording.first_ms,
+            )),
+            &format_duration(Duration::from_millis(
+                now_ms.saturating_sub(recording.last_ms),
+            )),
+        ],
+    )
+}
+
+/// CPU, memory and process count of one identifier against the time since the
+/// first row of the recording, panned and zoomed together, annotations marked
+fn show_plots(ui: &mut egui::Ui, recording: &Recording, rows: &[RecordedRow], settings: &Settings) {
+    let unit = settings.memory_unit.format_value(0.0).1;
+    let seconds: Vec<f64> = rows
+        .iter()
+        .map(|row| (row.unix_ms - recording.first_ms) as f64 / 1000.0)
+        .collect();
+    let series =
+        |value: &dyn Fn(&RecordedRow) -> f64| -> Vec<f64> { rows.iter().map(value).collect() };
+    let plots = [
+        (
+            "CPU (%)".to_string(),
+            settings.series_colors.cpu,
+            series(&|row| row.sample.cpu as f64),
+        ),
+        (
+            format!("{} ({unit})", tr("Memory")),
+            settings.series_colors.memory,
+            series(&|row| {
+                settings
+                    .memory_unit
+                    .format_value(row.sample.memory as f32)
+                    .0 as f64
+            }),
+        ),
+        (
+            tr("Processes").to_string(),
+            settings.series_colors.processes,
+            series(&|row| row.sample.processes as f64),
+        ),
+    ];
+    // Four points per bucket keep every spike while staying near two points per pixel
+    let buckets = (ui.available_width() / 2.0).max(1.0) as usize;
+    let link = ui.id().with("flight_log_plots");
+    for (y_label, color, values) in plots {
+        ui.label(&y_label);
+        egui_plot::Plot::new(("flight_log", &y_label))
+            .height(120.0)
+            .include_y(0.0)
+            .x_axis_label(tr("Seconds since the first sample"))
+            .link_axis(link, [true, false])
+            .link_cursor(link, [true, false].into())
+            .show(ui, |plot_ui| {
+                // Only the rows in view are downsampled, so zooming in brings back the detail
+                let bounds = plot_ui.plot_bounds();
+                let visible = if bounds.is_valid_x() {
+                    let start = seconds.partition_point(|&x| x < bounds.min()[0]);
+                    let end = seconds.partition_point(|&x| x <= bounds.max()[0]);
+                    start.saturating_sub(1)..(end + 1).min(seconds.len())
+                } else {
+                    0..seconds.len()
+                };
+                let points: Vec<[f64; 2]> = downsample_m4(&values[visible.clone()], buckets)
+                    .map(|(i, value)| [seconds[visible.start + i], value])
+                    .collect();
+                let marker = plot_ui.ctx().style().visuals.weak_text_color();
+                for (row, &x) in rows.iter().zip(&seconds) {
+                    if let Some(label) = &row.sample.annotation {
+                        plot_ui.vline(
+                            egui_plot::VLine::new(x)
+                                .color(marker)
+                                .style(egui_plot::LineStyle::dashed_loose())
+                                .name(label),
+                        );
+                    }
+                }
+                plot_ui.line(egui_plot::Line::new(points).color(color));
+            });
+    }
+}
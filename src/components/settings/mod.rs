@@ -1,6 +1,12 @@
 mod state;
 mod ui;
 
+pub use state::CpuScale;
+pub use state::DeadMatchAction;
+pub use state::MemoryUnit;
 pub use state::Settings;
+pub use state::Smoothing;
+pub use state::Theme;
 pub use state::UpdateMode;
-pub use ui::show_settings_window;
+pub use state::UPDATE_INTERVAL_MS;
+pub use ui::{show_guide_lines, show_interval_override, show_settings_ui, show_settings_window};
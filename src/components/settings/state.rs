@@ -1,9 +1,284 @@
+use crate::components::i18n::{self, Language};
+use crate::metrics::influx::InfluxPush;
+use crate::metrics::process::{ExcludeRule, MetricType, ProcessIdentifier, WatchRule};
+use crate::metrics::recorder::FlightRecorder;
+use crate::metrics::{AdaptiveSampling, LeakDetection, Metrics, OomProjection};
+use std::collections::HashMap;
+use sysinfo::Pid;
+
 #[derive(serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq)]
 pub enum UpdateMode {
     Reactive,
     Continuous,
 }
 
+/// What 100% CPU means on screen
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq, Default)]
+pub enum CpuScale {
+    /// As reported by the OS, can exceed 100% for multi-threaded processes
+    #[default]
+    PerCore,
+    /// Divided by the number of logical CPUs
+    Machine,
+}
+
+impl CpuScale {
+    pub fn apply(&self, cpu: f32, cpu_count: usize) -> f32 {
+        match self {
+            CpuScale::PerCore => cpu,
+            CpuScale::Machine => cpu / cpu_count.max(1) as f32,
+        }
+    }
+
+    /// Inverse of [`CpuScale::apply`], back to the per-core value the OS reports
+    pub fn to_per_core(&self, cpu: f32, cpu_count: usize) -> f32 {
+        match self {
+            CpuScale::PerCore => cpu,
+            CpuScale::Machine => cpu * cpu_count.max(1) as f32,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CpuScale::PerCore => "% of one core",
+            CpuScale::Machine => "% of machine",
+        }
+    }
+}
+
+/// What happens to a name-based entry once no process matches it
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq, Default)]
+pub enum DeadMatchAction {
+    /// Keep showing the last-known data, grayed out, until a process matches again
+    #[default]
+    Keep,
+    /// Save the history to a CSV file in the flight recorder directory, then remove the entry
+    Archive,
+    Remove,
+}
+
+impl DeadMatchAction {
+    pub const ALL: [DeadMatchAction; 3] = [
+        DeadMatchAction::Keep,
+        DeadMatchAction::Archive,
+        DeadMatchAction::Remove,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DeadMatchAction::Keep => "Keep grayed",
+            DeadMatchAction::Archive => "Archive to CSV",
+            DeadMatchAction::Remove => "Remove",
+        }
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct DeadMatchPolicy {
+    pub action: DeadMatchAction,
+    /// Time without a matching process before archiving or removing
+    pub after_minutes: u64,
+}
+
+impl Default for DeadMatchPolicy {
+    fn default() -> Self {
+        Self {
+            action: DeadMatchAction::Keep,
+            after_minutes: 10,
+        }
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+    /// White on black with strong outlines, for low vision and bright rooms
+    HighContrast,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::Dark, Theme::Light, Theme::HighContrast];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Theme::Dark => "🌙 Dark",
+            Theme::Light => "🌞 Light",
+            Theme::HighContrast => "◐ High contrast",
+        }
+    }
+
+    pub fn visuals(&self) -> egui::Visuals {
+        match self {
+            Theme::Dark => egui::Visuals::dark(),
+            Theme::Light => egui::Visuals::light(),
+            Theme::HighContrast => {
+                let mut visuals = egui::Visuals::dark();
+                visuals.override_text_color = Some(egui::Color32::WHITE);
+                visuals.panel_fill = egui::Color32::BLACK;
+                visuals.window_fill = egui::Color32::BLACK;
+                visuals.extreme_bg_color = egui::Color32::BLACK;
+                visuals.faint_bg_color = egui::Color32::from_gray(24);
+                visuals.window_stroke = egui::Stroke::new(2.0, egui::Color32::WHITE);
+                visuals.selection.bg_fill = egui::Color32::from_rgb(0x00, 0x5f, 0xcc);
+                visuals.selection.stroke = egui::Stroke::new(2.0, egui::Color32::WHITE);
+                visuals.hyperlink_color = egui::Color32::from_rgb(0x66, 0xcc, 0xff);
+                let widgets = &mut visuals.widgets;
+                for widget in [
+                    &mut widgets.noninteractive,
+                    &mut widgets.inactive,
+                    &mut widgets.hovered,
+                    &mut widgets.active,
+                    &mut widgets.open,
+                ] {
+                    widget.bg_stroke = egui::Stroke::new(1.5, egui::Color32::WHITE);
+                    widget.fg_stroke.color = egui::Color32::WHITE;
+                }
+                widgets.inactive.weak_bg_fill = egui::Color32::BLACK;
+                widgets.hovered.weak_bg_fill = egui::Color32::from_gray(48);
+                visuals
+            }
+        }
+    }
+}
+
+/// Line color of each plotted series. Children cycle through their own palette,
+/// picked by PID so a child keeps its color while others come and go.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct SeriesColors {
+    pub cpu: egui::Color32,
+    pub memory: egui::Color32,
+    pub gpu: egui::Color32,
+    pub processes: egui::Color32,
+    pub children: [egui::Color32; 6],
+}
+
+impl Default for SeriesColors {
+    fn default() -> Self {
+        Self {
+            cpu: egui::Color32::from_rgb(0x42, 0xa5, 0xf5),
+            memory: egui::Color32::from_rgb(0x66, 0xbb, 0x6a),
+            gpu: egui::Color32::from_rgb(0xab, 0x47, 0xbc),
+            processes: egui::Color32::from_rgb(0xff, 0xca, 0x28),
+            children: [
+                egui::Color32::from_rgb(0x26, 0xc6, 0xda),
+                egui::Color32::from_rgb(0xff, 0xa7, 0x26),
+                egui::Color32::from_rgb(0xec, 0x40, 0x7a),
+                egui::Color32::from_rgb(0x9c, 0xcc, 0x65),
+                egui::Color32::from_rgb(0x7e, 0x57, 0xc2),
+                egui::Color32::from_rgb(0x8d, 0x6e, 0x63),
+            ],
+        }
+    }
+}
+
+impl SeriesColors {
+    pub fn metric(&self, metric: MetricType) -> egui::Color32 {
+        match metric {
+            MetricType::Cpu => self.cpu,
+            MetricType::Memory => self.memory,
+            MetricType::Gpu => self.gpu,
+            MetricType::Processes => self.processes,
+        }
+    }
+
+    pub fn child(&self, pid: Pid) -> egui::Color32 {
+        self.children[pid.as_u32() as usize % self.children.len()]
+    }
+}
+
+/// Horizontal reference line on a plot of one identifier
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct GuideLine {
+    pub metric: MetricType,
+    /// Percent for CPU and GPU, bytes for memory, a count for processes
+    pub value: f64,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq, Default)]
+pub enum SmoothingKind {
+    #[default]
+    Off,
+    MovingAverage,
+    Ema,
+}
+
+/// Trend line drawn over the raw samples of every plot
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq)]
+pub struct Smoothing {
+    pub kind: SmoothingKind,
+    /// Samples averaged, or the EMA span
+    pub window: usize,
+}
+
+impl Default for Smoothing {
+    fn default() -> Self {
+        Self {
+            kind: SmoothingKind::Off,
+            window: 10,
+        }
+    }
+}
+
+impl Smoothing {
+    /// Smoothed copy of `values`, None when smoothing is off
+    pub fn apply(&self, values: &[f64]) -> Option<Vec<f64>> {
+        let window = self.window.max(1);
+        match self.kind {
+            SmoothingKind::Off => None,
+            SmoothingKind::MovingAverage => {
+                let mut sum = 0.0;
+                let smoothed = values
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &value)| {
+                        sum += value;
+                        if i >= window {
+                            sum -= values[i - window];
+                        }
+                        sum / (i + 1).min(window) as f64
+                    })
+                    .collect();
+                Some(smoothed)
+            }
+            SmoothingKind::Ema => {
+                let alpha = 2.0 / (window as f64 + 1.0);
+                let mut ema = None;
+                let smoothed = values
+                    .iter()
+                    .map(|&value| {
+                        let next = ema.map_or(value, |ema: f64| ema + alpha * (value - ema));
+                        ema = Some(next);
+                        next
+                    })
+                    .collect();
+                Some(smoothed)
+            }
+        }
+    }
+
+    /// Samples before the first shown one that bring the smoothing up to speed,
+    /// enough for older ones to weigh under 0.1% with EMA
+    pub fn warm_up(&self) -> usize {
+        match self.kind {
+            SmoothingKind::Off => 0,
+            SmoothingKind::MovingAverage => self.window.max(1) - 1,
+            SmoothingKind::Ema => self.window.max(1) * 4,
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self.kind {
+            SmoothingKind::Off => String::new(),
+            SmoothingKind::MovingAverage => i18n::tr_format("Average of {}", &[&self.window]),
+            SmoothingKind::Ema => format!("EMA {}", self.window),
+        }
+    }
+}
+
 #[derive(serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq)]
 pub enum MemoryUnit {
     Bytes,
@@ -23,17 +298,144 @@ impl MemoryUnit {
     }
 }
 
+/// Sampling intervals offered for the whole watch list and for a single identifier
+pub const UPDATE_INTERVAL_MS: std::ops::RangeInclusive<usize> = 200..=5000;
+
 #[derive(serde::Deserialize, serde::Serialize)]
 pub struct Settings {
     pub scale: f32,
     pub font_size: f32,
     pub graph_scale_margin: f32,
     pub update_interval_ms: usize,
+    #[serde(default)]
+    pub adaptive_sampling: AdaptiveSampling,
+    /// Appends every sample to a log file
+    #[serde(default)]
+    pub flight_recorder: FlightRecorder,
+    /// Samples kept per series, the retention of the history
     pub history_length: usize,
+    /// Newest samples plotted, the whole history when `None`
+    #[serde(default)]
+    pub display_length: Option<usize>,
+    /// Pause sampling identifiers that were not viewed for this many minutes
+    #[serde(default)]
+    pub auto_pause_minutes: Option<u64>,
+    #[serde(default)]
+    pub dead_matches: DeadMatchPolicy,
     pub memory_unit: MemoryUnit,
+    #[serde(default)]
+    pub cpu_scale: CpuScale,
+    /// Package temperature and CPU clock shown above the CPU plots
+    #[serde(default)]
+    pub show_cpu_thermals: bool,
+    #[serde(default)]
+    pub smoothing: Smoothing,
+    #[serde(default)]
+    pub oom_projection: OomProjection,
+    #[serde(default)]
+    pub leak_detection: LeakDetection,
+    /// Pushes every sample to an InfluxDB or Grafana Live endpoint
+    #[serde(default)]
+    pub influx_push: InfluxPush,
+    #[serde(default)]
+    pub theme: Theme,
+    /// Language of the UI and separators of the numbers it shows
+    #[serde(default = "Language::detect")]
+    pub language: Language,
+    #[serde(default)]
+    pub series_colors: SeriesColors,
     pub update_mode: UpdateMode,
+    /// Per-identifier limits on which children are discovered
+    #[serde(default)]
+    pub exclude_rules: Vec<ExcludeRule>,
+    /// Patterns whose matching processes are monitored as they appear
+    #[serde(default)]
+    pub watch_rules: Vec<WatchRule>,
+    /// Identifiers sampled at their own interval, in milliseconds
+    #[serde(default)]
+    pub interval_overrides: HashMap<ProcessIdentifier, usize>,
+    /// Reference lines drawn on the plots of each identifier
+    #[serde(default)]
+    pub guide_lines: HashMap<ProcessIdentifier, Vec<GuideLine>>,
+    /// Labels shown instead of the identifier, e.g. "ingest worker" for `pid:8412`
+    #[serde(default)]
+    pub aliases: HashMap<ProcessIdentifier, String>,
+    /// Monitored identifiers listed first that can't be removed until unpinned
+    #[serde(default)]
+    pub pinned: Vec<ProcessIdentifier>,
     #[serde(skip)]
     show_window: bool,
+    #[serde(skip)]
+    pub(super) exclude_draft: ExcludeDraft,
+    #[serde(skip)]
+    pub(super) watch_draft: WatchDraft,
+    /// Interval and history length being edited, sent to the sampler on "Apply"
+    #[serde(skip)]
+    pub(super) sampling_draft: Option<SamplingSettings>,
+    /// Values replaced by the last apply, for "Revert"
+    #[serde(skip)]
+    pub(super) sampling_previous: Option<SamplingSettings>,
+}
+
+/// Settings that change what the sampler records
+#[derive(Clone, Copy, PartialEq)]
+pub(super) struct SamplingSettings {
+    pub update_interval_ms: usize,
+    pub history_length: usize,
+}
+
+/// Text fields of the exclude rule being written in the settings UI
+#[derive(Default)]
+pub(super) struct ExcludeDraft {
+    pub identifier: String,
+    pub name_globs: String,
+    pub pids: String,
+    pub max_depth: Option<usize>,
+    pub ancestors: bool,
+}
+
+/// Watch rule being written in the settings UI
+#[derive(Default)]
+pub(super) struct WatchDraft {
+    pub pattern: String,
+    pub remove_on_exit: bool,
+}
+
+impl WatchDraft {
+    /// Builds the rule, returns None without a pattern
+    pub fn to_rule(&self) -> Option<WatchRule> {
+        let pattern = self.pattern.trim();
+        if pattern.is_empty() {
+            return None;
+        }
+        let mut rule = WatchRule::new(pattern);
+        rule.remove_on_exit = self.remove_on_exit;
+        Some(rule)
+    }
+}
+
+impl ExcludeDraft {
+    /// Builds the rule, returns None without an identifier
+    pub fn to_rule(&self) -> Option<ExcludeRule> {
+        let identifier = self.identifier.trim();
+        if identifier.is_empty() {
+            return None;
+        }
+        let mut rule = ExcludeRule::new(identifier.into());
+        rule.name_globs = split_list(&self.name_globs).map(str::to_string).collect();
+        rule.pids = split_list(&self.pids)
+            .filter_map(|pid| pid.parse().ok())
+            .collect();
+        rule.max_depth = self.max_depth;
+        rule.ancestors = self.ancestors;
+        Some(rule)
+    }
+}
+
+fn split_list(list: &str) -> impl Iterator<Item = &str> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
 }
 
 impl Default for Settings {
@@ -43,19 +445,94 @@ impl Default for Settings {
             font_size: 15.0,
             graph_scale_margin: 0.35,
             update_interval_ms: 1000,
-            history_length: 100,
+            adaptive_sampling: AdaptiveSampling::default(),
+            oom_projection: OomProjection::default(),
+            leak_detection: LeakDetection::default(),
+            flight_recorder: FlightRecorder::default(),
+            influx_push: InfluxPush::default(),
+            history_length: 3600,
+            display_length: Some(100),
+            auto_pause_minutes: None,
+            dead_matches: DeadMatchPolicy::default(),
             memory_unit: MemoryUnit::Megabytes,
+            cpu_scale: CpuScale::default(),
+            show_cpu_thermals: false,
+            smoothing: Smoothing::default(),
+            theme: Theme::default(),
+            language: Language::detect(),
+            series_colors: SeriesColors::default(),
             update_mode: UpdateMode::Continuous,
+            exclude_rules: Vec::new(),
+            watch_rules: Vec::new(),
+            interval_overrides: HashMap::new(),
+            guide_lines: HashMap::new(),
+            aliases: HashMap::new(),
+            pinned: Vec::new(),
             show_window: false,
+            exclude_draft: ExcludeDraft::default(),
+            watch_draft: WatchDraft::default(),
+            sampling_draft: None,
+            sampling_previous: None,
         }
     }
 }
 
 impl Settings {
+    pub(super) fn sampling(&self) -> SamplingSettings {
+        SamplingSettings {
+            update_interval_ms: self.update_interval_ms,
+            history_length: self.history_length,
+        }
+    }
+
+    pub(super) fn set_sampling(&mut self, sampling: SamplingSettings, metrics: &mut Metrics) {
+        self.update_interval_ms = sampling.update_interval_ms;
+        self.history_length = sampling.history_length;
+        metrics.set_update_interval(sampling.update_interval_ms as u64);
+        metrics.set_history_len(sampling.history_length);
+    }
+
     pub fn show(&mut self) {
         self.show_window = true;
     }
 
+    /// Samples plotted out of a history holding `history_len`
+    pub fn display_len(&self, history_len: usize) -> usize {
+        self.display_length.map_or(history_len, |display_length| {
+            display_length.min(history_len)
+        })
+    }
+
+    /// Alias of `identifier`, or the identifier itself without one
+    pub fn label(&self, identifier: &ProcessIdentifier) -> String {
+        self.aliases
+            .get(identifier)
+            .map(|alias| alias.trim())
+            .filter(|alias| !alias.is_empty())
+            .map_or_else(|| identifier.to_string(), str::to_string)
+    }
+
+    /// Sets the alias of `identifier`, a blank alias removes it
+    pub fn set_alias(&mut self, identifier: &ProcessIdentifier, alias: String) {
+        if alias.trim().is_empty() {
+            self.aliases.remove(identifier);
+        } else {
+            self.aliases.insert(identifier.clone(), alias);
+        }
+    }
+
+    pub fn is_pinned(&self, identifier: &ProcessIdentifier) -> bool {
+        self.pinned.contains(identifier)
+    }
+
+    pub fn toggle_pin(&mut self, identifier: &ProcessIdentifier) {
+        if self.is_pinned(identifier) {
+            self.pinned.retain(|pinned| pinned != identifier);
+        } else {
+            self.pinned.push(identifier.clone());
+        }
+    }
+
     pub fn is_visible(&self) -> bool {
         self.show_window
     }
@@ -91,15 +568,21 @@ impl Settings {
             ),
         ]
         .into();
+        style.visuals = self.theme.visuals();
         ctx.set_style(style);
+        i18n::set_language(self.language);
     }
+}
 
-    pub fn toggle_theme(&self, ctx: &egui::Context) {
-        let visuals = if ctx.style().visuals.dark_mode {
-            egui::Visuals::light()
-        } else {
-            egui::Visuals::dark()
-        };
-        ctx.set_visuals(visuals);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cpu_scale_converts_back_to_per_core() {
+        // A 50% machine-wide alert threshold on 8 cores is stored as 400% of one core
+        assert_eq!(CpuScale::Machine.to_per_core(50.0, 8), 400.0);
+        assert_eq!(CpuScale::Machine.apply(400.0, 8), 50.0);
+        assert_eq!(CpuScale::PerCore.to_per_core(150.0, 8), 150.0);
     }
 }
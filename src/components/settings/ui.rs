@@ -1,137 +1,959 @@
-use super::state::{MemoryUnit, Settings, UpdateMode};
+use super::state::{
+    CpuScale, DeadMatchAction, GuideLine, MemoryUnit, SamplingSettings, SeriesColors, Settings,
+    SmoothingKind, Theme, UpdateMode, UPDATE_INTERVAL_MS,
+};
+use crate::components::i18n::{number, percent, tr, tr_format, Language};
+use crate::metrics::process::{
+    ExcludeRule, MetricType, ProcessIdentifier, BYTES_PER_SAMPLE, BYTES_PER_TIERED_SERIES,
+    HISTORY_TIERS,
+};
+use crate::metrics::recorder::RecordFormat;
 use crate::metrics::Metrics;
-use std::sync::{Arc, RwLock};
+use std::time::Duration;
 
+/// Upper bound of the retention slider, an hour at 100 ms
+const MAX_RETENTION: usize = 36_000;
+
+/// Shows the floating settings window.
+/// Returns true when the user asked to dock it as a tab instead.
 pub fn show_settings_window(
     ctx: &egui::Context,
     settings: &mut Settings,
-    metrics: Arc<RwLock<Metrics>>,
-) {
+    metrics: &mut Metrics,
+) -> bool {
     if !settings.is_visible() {
-        return;
+        return false;
     }
 
-    egui::Window::new("⚙ Settings")
+    let mut dock = false;
+    egui::Window::new(tr("⚙ Settings"))
+        .id(egui::Id::new("settings_window"))
         .collapsible(false)
         .resizable(false)
         .show(ctx, |ui| {
-            ui.horizontal(|ui| {
-                ui.label("UI Scale:");
-                ui.add(egui::Slider::new(&mut settings.scale, 0.5..=2.0).step_by(0.1));
-            });
+            show_settings_ui(ui, settings, metrics);
+
+            ui.separator();
 
             ui.horizontal(|ui| {
-                ui.label("Font Size:");
-                ui.add(egui::Slider::new(&mut settings.font_size, 8.0..=32.0).step_by(1.0));
+                if ui.button(tr("Close")).clicked() {
+                    settings.hide();
+                }
+                if ui.button(tr("Dock as tab")).clicked() {
+                    settings.hide();
+                    dock = true;
+                }
             });
+        });
+    dock
+}
 
-            ui.separator();
+pub fn show_settings_ui(ui: &mut egui::Ui, settings: &mut Settings, metrics: &mut Metrics) {
+    let ctx = &ui.ctx().clone();
+    ui.horizontal(|ui| {
+        ui.label(tr("UI Scale:"));
+        ui.add(egui::Slider::new(&mut settings.scale, 0.5..=2.0).step_by(0.1));
+    });
 
-            ui.horizontal(|ui| {
-                ui.label("Graph Scale Margin:");
+    ui.horizontal(|ui| {
+        ui.label(tr("Font Size:"));
+        ui.add(egui::Slider::new(&mut settings.font_size, 8.0..=32.0).step_by(1.0));
+    });
+
+    ui.separator();
+
+    ui.horizontal(|ui| {
+        ui.label(tr("Graph Scale Margin:"));
+        ui.add(
+            egui::Slider::new(&mut settings.graph_scale_margin, 0.0..=0.5)
+                .step_by(0.01)
+                .suffix("%")
+                .text(tr("Extra margin above peak")),
+        );
+    });
+
+    ui.separator();
+
+    show_sampling(ui, settings, metrics);
+
+    ui.separator();
+
+    ui.horizontal(|ui| {
+        let adaptive = &mut settings.adaptive_sampling;
+        let mut changed = ui
+            .checkbox(&mut adaptive.enabled, tr("Adaptive:"))
+            .on_hover_text(tr("Sample faster while a monitored tree is busy"))
+            .changed();
+        ui.add_enabled_ui(adaptive.enabled, |ui| {
+            changed |= ui
+                .add(
+                    egui::DragValue::new(&mut adaptive.fast_interval_ms)
+                        .range(100..=2000)
+                        .speed(10)
+                        .suffix(" ms"),
+                )
+                .changed();
+            ui.label(tr("above"));
+            changed |= ui
+                .add(
+                    egui::DragValue::new(&mut adaptive.cpu_threshold)
+                        .range(0.0..=100.0)
+                        .suffix("% CPU"),
+                )
+                .changed();
+        });
+        if changed {
+            metrics.set_adaptive_sampling(*adaptive);
+        }
+    });
+    if settings.adaptive_sampling.enabled {
+        ui.weak(tr_format(
+            "Sampling every {} ms",
+            &[&metrics.current_interval.as_millis()],
+        ));
+    }
+
+    ui.horizontal(|ui| {
+        let mut enabled = settings.auto_pause_minutes.is_some();
+        if ui
+            .checkbox(&mut enabled, tr("Auto-pause hidden processes after"))
+            .on_hover_text(tr(
+                "Stop sampling processes whose tab was not viewed, keeping their history",
+            ))
+            .changed()
+        {
+            settings.auto_pause_minutes = enabled.then_some(30);
+        }
+        if let Some(minutes) = &mut settings.auto_pause_minutes {
+            ui.add(
+                egui::DragValue::new(minutes)
+                    .range(1..=24 * 60)
+                    .suffix(" min"),
+            );
+        }
+    });
+
+    ui.horizontal(|ui| {
+        let policy = &mut settings.dead_matches;
+        ui.label(tr("When no process matches a name:"))
+            .on_hover_text(tr("Pinned entries are always kept"));
+        for action in DeadMatchAction::ALL {
+            if action == DeadMatchAction::Archive && cfg!(target_arch = "wasm32") {
+                continue;
+            }
+            ui.selectable_value(&mut policy.action, action, tr(action.label()));
+        }
+        if policy.action != DeadMatchAction::Keep {
+            ui.label(tr("after"));
+            ui.add(
+                egui::DragValue::new(&mut policy.after_minutes)
+                    .range(0..=24 * 60)
+                    .suffix(" min"),
+            );
+        }
+    });
+
+    ui.separator();
+
+    ui.horizontal(|ui| {
+        ui.label(tr("Theme:"));
+        for theme in Theme::ALL {
+            ui.selectable_value(&mut settings.theme, theme, tr(theme.label()));
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.label(tr("Language:"))
+            .on_hover_text(tr("Also sets the decimal and thousands separators"));
+        for language in Language::ALL {
+            ui.selectable_value(&mut settings.language, language, language.label());
+        }
+    });
+    show_series_colors(ui, &mut settings.series_colors);
+
+    ui.separator();
+
+    ui.horizontal(|ui| {
+        ui.label(tr("Update Mode:"));
+        for mode in [UpdateMode::Continuous, UpdateMode::Reactive] {
+            let label = match mode {
+                UpdateMode::Continuous => tr("Continuous"),
+                UpdateMode::Reactive => tr("Reactive"),
+            };
+            if ui
+                .selectable_label(settings.update_mode == mode, label)
+                .clicked()
+            {
+                settings.update_mode = mode;
+                ctx.request_repaint();
+            }
+        }
+    });
+
+    ui.separator();
+
+    ui.horizontal(|ui| {
+        ui.label(tr("Memory Unit:"));
+        for unit in [
+            MemoryUnit::Bytes,
+            MemoryUnit::Kilobytes,
+            MemoryUnit::Megabytes,
+            MemoryUnit::Gigabytes,
+        ] {
+            let label = match unit {
+                MemoryUnit::Bytes => tr("Bytes"),
+                MemoryUnit::Kilobytes => "KB",
+                MemoryUnit::Megabytes => "MB",
+                MemoryUnit::Gigabytes => "GB",
+            };
+            if ui
+                .selectable_label(settings.memory_unit == unit, label)
+                .clicked()
+            {
+                settings.memory_unit = unit;
+            }
+        }
+    });
+
+    ui.horizontal(|ui| {
+        ui.label(tr("CPU Scale:"));
+        for scale in [CpuScale::PerCore, CpuScale::Machine] {
+            if ui
+                .selectable_label(settings.cpu_scale == scale, tr(scale.label()))
+                .clicked()
+            {
+                settings.cpu_scale = scale;
+            }
+        }
+    });
+
+    if ui
+        .checkbox(
+            &mut settings.show_cpu_thermals,
+            tr("CPU temperature and clock above the CPU plot"),
+        )
+        .on_hover_text(tr(
+            "Thermal throttling often explains a drop in CPU usage. \
+             Not every machine exposes a package temperature.",
+        ))
+        .changed()
+    {
+        metrics.set_sample_thermals(settings.show_cpu_thermals);
+    }
+
+    ui.horizontal(|ui| {
+        ui.label(tr("Smoothing:"));
+        let smoothing = &mut settings.smoothing;
+        for (kind, label) in [
+            (SmoothingKind::Off, tr("Off")),
+            (SmoothingKind::MovingAverage, tr("Moving average")),
+            (SmoothingKind::Ema, "EMA"),
+        ] {
+            ui.selectable_value(&mut smoothing.kind, kind, label);
+        }
+        if smoothing.kind != SmoothingKind::Off {
+            ui.add(
+                egui::DragValue::new(&mut smoothing.window)
+                    .range(2..=100)
+                    .suffix(format!(" {}", tr("samples"))),
+            )
+            .on_hover_text(tr("Drawn as a second line over the raw samples"));
+        }
+    });
+
+    show_oom_projection(ui, settings, metrics);
+    show_leak_detection(ui, settings, metrics);
+
+    if !cfg!(target_arch = "wasm32") {
+        show_flight_recorder(ui, settings, metrics);
+        show_influx_push(ui, settings, metrics);
+    }
+
+    ui.separator();
+
+    show_exclude_rules(ui, settings, metrics);
+    show_watch_rules(ui, settings, metrics);
+}
+
+/// Window the memory trend is fitted over and the limit it is projected to
+fn show_oom_projection(ui: &mut egui::Ui, settings: &mut Settings, metrics: &mut Metrics) {
+    const GB: f64 = 1024.0 * 1024.0 * 1024.0;
+    let projection = &mut settings.oom_projection;
+    let before = *projection;
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut projection.enabled, tr("Project memory growth over"))
+            .on_hover_text(tr("Fit the memory trend and estimate when it runs out"));
+        ui.add_enabled_ui(projection.enabled, |ui| {
+            let mut minutes = projection.window_secs / 60;
+            if ui
+                .add(
+                    egui::DragValue::new(&mut minutes)
+                        .range(1..=24 * 60)
+                        .suffix(" min"),
+                )
+                .changed()
+            {
+                projection.window_secs = minutes * 60;
+            }
+            ui.label(tr("to"));
+            let mut system = projection.limit_bytes.is_none();
+            ui.selectable_value(&mut system, true, tr("System OOM"));
+            ui.selectable_value(&mut system, false, tr("Limit"));
+            if system {
+                projection.limit_bytes = None;
+            } else {
+                let mut gigabytes = projection
+                    .limit_bytes
+                    .map_or(4.0, |bytes| bytes as f64 / GB);
                 ui.add(
-                    egui::Slider::new(&mut settings.graph_scale_margin, 0.0..=0.5)
-                        .step_by(0.01)
-                        .suffix("%")
-                        .text("Extra margin above peak"),
+                    egui::DragValue::new(&mut gigabytes)
+                        .range(0.1..=4096.0)
+                        .speed(0.1)
+                        .suffix(" GB"),
                 );
-            });
+                projection.limit_bytes = Some((gigabytes * GB) as usize);
+            }
+        });
+    });
+    if *projection != before {
+        metrics.set_oom_projection(*projection);
+    }
+}
 
-            ui.separator();
+fn show_leak_detection(ui: &mut egui::Ui, settings: &mut Settings, metrics: &mut Metrics) {
+    let detection = &mut settings.leak_detection;
+    let before = *detection;
+    ui.horizontal(|ui| {
+        ui.checkbox(
+            &mut detection.enabled,
+            tr("Flag possible leaks growing over"),
+        )
+        .on_hover_text(tr(
+            "Badge trees whose memory trend stays above the rate for the whole time",
+        ));
+        ui.add_enabled_ui(detection.enabled, |ui| {
+            ui.add(
+                egui::DragValue::new(&mut detection.mb_per_min)
+                    .range(0.1..=10_000.0)
+                    .speed(0.1)
+                    .suffix(" MB/min"),
+            );
+            ui.label(tr("for"));
+            let mut minutes = detection.duration_secs / 60;
+            if ui
+                .add(
+                    egui::DragValue::new(&mut minutes)
+                        .range(1..=24 * 60)
+                        .suffix(" min"),
+                )
+                .changed()
+            {
+                detection.duration_secs = minutes * 60;
+            }
+        });
+    });
+    let retention =
+        Duration::from_millis((settings.update_interval_ms * settings.history_length) as u64);
+    if detection.enabled && retention.as_secs() < detection.duration_secs {
+        ui.colored_label(
+            ui.visuals().warn_fg_color,
+            tr_format(
+                "⚠ The history only spans {} min, raise the retention to detect leaks",
+                &[&(retention.as_secs() / 60)],
+            ),
+        );
+    }
+    if *detection != before {
+        metrics.set_leak_detection(*detection);
+    }
+}
 
-            ui.horizontal(|ui| {
-                ui.label("Update Interval:");
-                let response = ui.add(
-                    egui::Slider::new(&mut settings.update_interval_ms, 200..=5000)
-                        .step_by(100.0)
-                        .suffix(" ms")
-                        .text("Time between updates"),
-                );
-                if response.changed() {
-                    if let Ok(mut metrics) = metrics.write() {
-                        metrics.set_update_interval(settings.update_interval_ms as u64);
-                    }
-                }
-            });
+/// Format, place and rotation of the log every sample is appended to
+fn show_flight_recorder(ui: &mut egui::Ui, settings: &mut Settings, metrics: &mut Metrics) {
+    const MB: f64 = 1024.0 * 1024.0;
+    let recorder = &mut settings.flight_recorder;
+    let mut editing = false;
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut recorder.enabled, tr("Flight recorder"))
+            .on_hover_text(tr(
+                "Append every sample to a log file as it is taken, flushed on every tick, \
+                 so the data leading up to a crash is kept",
+            ));
+        ui.add_enabled_ui(recorder.enabled, |ui| {
+            for format in [RecordFormat::JsonLines, RecordFormat::Csv] {
+                ui.selectable_value(&mut recorder.format, format, format.label());
+            }
+            ui.label(tr("in"));
+            editing = ui
+                .add(
+                    egui::TextEdit::singleline(&mut recorder.directory)
+                        .hint_text(tr("working directory"))
+                        .desired_width(160.0),
+                )
+                .has_focus();
+        });
+    });
+    if recorder.enabled {
+        ui.horizontal(|ui| {
+            ui.label(tr("Rotate at"));
+            let mut megabytes = recorder.max_file_bytes as f64 / MB;
+            if ui
+                .add(
+                    egui::DragValue::new(&mut megabytes)
+                        .range(0.1..=4096.0)
+                        .speed(0.1)
+                        .suffix(" MB"),
+                )
+                .changed()
+            {
+                recorder.max_file_bytes = (megabytes * MB) as u64;
+            }
+            ui.label(tr("keeping"));
+            ui.add(
+                egui::DragValue::new(&mut recorder.files)
+                    .range(1..=100)
+                    .suffix(format!(" {}", tr("files"))),
+            );
+        });
+        match &metrics.flight_recorder_error {
+            Some(err) => {
+                ui.colored_label(ui.visuals().error_fg_color, err);
+            }
+            None => {
+                ui.weak(format!(
+                    "{} {}",
+                    tr("Writing to"),
+                    recorder.path().display()
+                ));
+            }
+        }
+    }
+    // The directory is applied once typed, not reopened at every keystroke
+    if !editing && metrics.controls().flight_recorder != *recorder {
+        metrics.set_flight_recorder(recorder.clone());
+    }
+}
 
-            ui.separator();
+/// Endpoint, credentials and batching of the InfluxDB line-protocol push
+fn show_influx_push(ui: &mut egui::Ui, settings: &mut Settings, metrics: &mut Metrics) {
+    let push = &mut settings.influx_push;
+    let mut editing = false;
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut push.enabled, tr("Push to InfluxDB"))
+            .on_hover_text(tr(
+                "Send every sample in line protocol to an InfluxDB write endpoint \
+                 or a Grafana Live push endpoint",
+            ));
+        ui.add_enabled_ui(push.enabled, |ui| {
+            editing |= ui
+                .add(
+                    egui::TextEdit::singleline(&mut push.url)
+                        .hint_text("http://localhost:8086/api/v2/write?org=…&bucket=…")
+                        .desired_width(280.0),
+                )
+                .has_focus();
+        });
+    });
+    if push.enabled {
+        ui.horizontal(|ui| {
+            ui.label(tr("Authorization:"));
+            editing |= ui
+                .add(
+                    egui::TextEdit::singleline(&mut push.authorization)
+                        .hint_text("Token … or Bearer …")
+                        .password(true)
+                        .desired_width(200.0),
+                )
+                .has_focus();
+            ui.label(tr("Measurement:"));
+            editing |= ui
+                .add(egui::TextEdit::singleline(&mut push.measurement).desired_width(80.0))
+                .has_focus();
+        });
+        ui.horizontal(|ui| {
+            ui.label(tr("Batches of"));
+            ui.add(
+                egui::DragValue::new(&mut push.batch_lines)
+                    .range(1..=10_000)
+                    .suffix(format!(" {}", tr("lines"))),
+            );
+            ui.label(tr("or every"));
+            ui.add(
+                egui::DragValue::new(&mut push.flush_secs)
+                    .range(0..=3600)
+                    .suffix(" s"),
+            );
+            ui.checkbox(&mut push.per_process, tr("Per-process lines"))
+                .on_hover_text(tr(
+                    "Also send every process of each tree, tagged with its PID and name",
+                ));
+        });
+        match &metrics.controls().influx_status {
+            Some(Ok(status)) => {
+                ui.weak(status);
+            }
+            Some(Err(err)) => {
+                ui.colored_label(ui.visuals().error_fg_color, err);
+            }
+            None => {
+                ui.weak(tr("Nothing sent yet"));
+            }
+        }
+    }
+    // The endpoint and credentials are applied once typed
+    if !editing && metrics.controls().influx_push != *push {
+        metrics.set_influx_push(push.clone());
+    }
+}
+
+/// Interval and history length, edited as a draft that is previewed before applying
+fn show_sampling(ui: &mut egui::Ui, settings: &mut Settings, metrics: &mut Metrics) {
+    let current = settings.sampling();
+    let mut draft = settings.sampling_draft.unwrap_or(current);
+    ui.horizontal(|ui| {
+        ui.label(tr("Update Interval:"));
+        ui.add(
+            egui::Slider::new(&mut draft.update_interval_ms, UPDATE_INTERVAL_MS)
+                .step_by(100.0)
+                .suffix(" ms")
+                .text(tr("Time between updates")),
+        );
+    });
+    ui.horizontal(|ui| {
+        ui.label(tr("Retention:"));
+        ui.add(
+            egui::Slider::new(&mut draft.history_length, 10..=MAX_RETENTION)
+                .logarithmic(true)
+                .suffix(format!(" {}", tr("points")))
+                .text(tr("Samples kept per graph")),
+        );
+    });
+    settings.sampling_draft = (draft != current).then_some(draft);
+    show_display_window(ui, settings, draft.history_length);
+
+    show_overhead(ui, &draft, settings, metrics);
+    if draft.history_length < current.history_length {
+        ui.colored_label(
+            ui.visuals().warn_fg_color,
+            tr_format(
+                "⚠ Drops the oldest {} points of every graph",
+                &[&(current.history_length - draft.history_length)],
+            ),
+        );
+    }
+
+    let Some(draft) = settings.sampling_draft else {
+        if let Some(previous) = settings.sampling_previous {
+            if ui
+                .button(tr("↩ Revert"))
+                .on_hover_text(tr_format(
+                    "Back to {} ms, {} points",
+                    &[&previous.update_interval_ms, &previous.history_length],
+                ))
+                .clicked()
+            {
+                settings.set_sampling(previous, metrics);
+                settings.sampling_previous = None;
+            }
+        }
+        return;
+    };
+
+    ui.horizontal(|ui| {
+        if ui.button(tr("Apply")).clicked() {
+            settings.set_sampling(draft, metrics);
+            settings.sampling_previous = Some(current);
+            settings.sampling_draft = None;
+        }
+        if ui.button(tr("Cancel")).clicked() {
+            settings.sampling_draft = None;
+        }
+    });
+}
 
+fn show_series_colors(ui: &mut egui::Ui, colors: &mut SeriesColors) {
+    let color_button = |ui: &mut egui::Ui, color: &mut egui::Color32| {
+        egui::color_picker::color_edit_button_srgba(ui, color, egui::color_picker::Alpha::Opaque);
+    };
+    ui.horizontal(|ui| {
+        ui.label(tr("Line Colors:"));
+        for (label, color) in [
+            ("CPU", &mut colors.cpu),
+            ("Memory", &mut colors.memory),
+            ("GPU", &mut colors.gpu),
+            ("Processes", &mut colors.processes),
+        ] {
+            ui.label(tr(label));
+            color_button(ui, color);
+        }
+        if ui
+            .small_button("↺")
+            .on_hover_text(tr("Restore the default colors"))
+            .clicked()
+        {
+            *colors = SeriesColors::default();
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.label(tr("Children:"));
+        for color in &mut colors.children {
+            color_button(ui, color);
+        }
+    })
+    .response
+    .on_hover_text(tr("Cycled through by the plots of child processes"));
+}
+
+/// Slider over how much of the retained history the graphs plot.
+/// Only changes the view, so it applies right away.
+fn show_display_window(ui: &mut egui::Ui, settings: &mut Settings, history_length: usize) {
+    ui.horizontal(|ui| {
+        ui.label(tr("Display Window:"));
+        let mut whole = settings.display_length.is_none();
+        let mut display_length = settings.display_len(history_length);
+        ui.add_enabled(
+            !whole,
+            egui::Slider::new(&mut display_length, 10..=history_length.max(10))
+                .logarithmic(true)
+                .suffix(format!(" {}", tr("points")))
+                .text(tr("Newest samples plotted")),
+        );
+        ui.checkbox(&mut whole, tr("Whole history"));
+        settings.display_length = (!whole).then_some(display_length);
+    });
+}
+
+/// "Own interval" toggle of `identifier`, shown above its plots.
+/// Overrides the global interval for this identifier only.
+pub fn show_interval_override(
+    ui: &mut egui::Ui,
+    identifier: &ProcessIdentifier,
+    settings: &mut Settings,
+    metrics: &mut Metrics,
+) {
+    let current = settings.interval_overrides.get(identifier).copied();
+    let mut enabled = current.is_some();
+    let mut interval_ms = current.unwrap_or(settings.update_interval_ms);
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut enabled, tr("⏱ Own interval"))
+            .on_hover_text(tr(
+                "Sample this identifier at its own rate instead of the global interval",
+            ));
+        if enabled {
+            ui.add(
+                egui::DragValue::new(&mut interval_ms)
+                    .range(UPDATE_INTERVAL_MS)
+                    .speed(50.0)
+                    .suffix(" ms"),
+            );
+        }
+    });
+    let updated = enabled.then_some(interval_ms);
+    if updated == current {
+        return;
+    }
+    match updated {
+        Some(interval_ms) => settings
+            .interval_overrides
+            .insert(identifier.clone(), interval_ms),
+        None => settings.interval_overrides.remove(identifier),
+    };
+    metrics.set_interval_override(
+        identifier,
+        updated.map(|interval_ms| Duration::from_millis(interval_ms as u64)),
+    );
+}
+
+const GUIDE_METRICS: [(MetricType, &str); 4] = [
+    (MetricType::Cpu, "CPU"),
+    (MetricType::Memory, "Memory"),
+    (MetricType::Gpu, "GPU"),
+    (MetricType::Processes, "Processes"),
+];
+
+/// Guide lines of one identifier: a metric and a value per line, drawn dashed
+/// on the matching plot next to the thresholds of its alert rules
+pub fn show_guide_lines(
+    ui: &mut egui::Ui,
+    identifier: &ProcessIdentifier,
+    settings: &mut Settings,
+) {
+    let unit = settings.memory_unit;
+    let bytes_per_unit = 1.0 / unit.format_value(1.0).0 as f64;
+    let count = settings.guide_lines.get(identifier).map_or(0, Vec::len);
+    ui.menu_button(format!("📏 {} ({count})", tr("Guides")), |ui| {
+        let guides = settings.guide_lines.entry(identifier.clone()).or_default();
+        let mut to_remove = None;
+        for (i, guide) in guides.iter_mut().enumerate() {
             ui.horizontal(|ui| {
-                ui.label("History Length:");
-                let response = ui.add(
-                    egui::Slider::new(&mut settings.history_length, 10..=1000)
-                        .step_by(10.0)
-                        .suffix(" points")
-                        .text("Number of data points in graphs"),
-                );
-                if response.changed() {
-                    if let Ok(mut metrics) = metrics.write() {
-                        metrics.history_len = settings.history_length;
+                let selected = GUIDE_METRICS
+                    .iter()
+                    .find(|(metric, _)| *metric == guide.metric)
+                    .map_or("", |(_, label)| tr(label));
+                egui::ComboBox::from_id_salt(("guide_metric", i))
+                    .selected_text(selected)
+                    .show_ui(ui, |ui| {
+                        for (metric, label) in GUIDE_METRICS {
+                            ui.selectable_value(&mut guide.metric, metric, tr(label));
+                        }
+                    });
+                match guide.metric {
+                    MetricType::Cpu | MetricType::Gpu => {
+                        ui.add(egui::DragValue::new(&mut guide.value).suffix("%"));
+                    }
+                    MetricType::Processes => {
+                        ui.add(egui::DragValue::new(&mut guide.value).speed(1.0));
                     }
+                    MetricType::Memory => {
+                        let mut value = guide.value / bytes_per_unit;
+                        let suffix = format!(" {}", unit.format_value(0.0).1);
+                        if ui
+                            .add(egui::DragValue::new(&mut value).suffix(suffix))
+                            .changed()
+                        {
+                            guide.value = value * bytes_per_unit;
+                        }
+                    }
+                }
+                if ui.small_button("❌").clicked() {
+                    to_remove = Some(i);
                 }
             });
+        }
+        if let Some(i) = to_remove {
+            guides.remove(i);
+        }
+        if ui.button(tr("➕ Add guide")).clicked() {
+            guides.push(GuideLine {
+                metric: MetricType::Cpu,
+                value: 80.0,
+            });
+        }
+        ui.weak(tr("Thresholds of enabled alert rules are drawn too"));
+    });
+    if settings
+        .guide_lines
+        .get(identifier)
+        .is_some_and(Vec::is_empty)
+    {
+        settings.guide_lines.remove(identifier);
+    }
+}
 
-            ui.separator();
+/// Preview of what `sampling` costs: sample rate, graph span, history memory
+/// and tvis's own CPU, extrapolated from the time the last tick took
+fn show_overhead(
+    ui: &mut egui::Ui,
+    sampling: &SamplingSettings,
+    settings: &Settings,
+    metrics: &Metrics,
+) {
+    let interval = sampling.update_interval_ms as f64;
+    let span = std::time::Duration::from_millis(
+        (sampling.update_interval_ms * sampling.history_length) as u64,
+    );
+    let tiers_span = HISTORY_TIERS
+        .iter()
+        .map(|tier| tier.seconds * tier.len as u64)
+        .max()
+        .unwrap_or_default();
+    ui.weak(tr_format(
+        "{} samples/s, history spans {}m {}s, then tree averages up to {}h",
+        &[
+            &number(1000.0 / interval, 1),
+            &(span.as_secs() / 60),
+            &format!("{:02}", span.as_secs() % 60),
+            &(tiers_span / 3600),
+        ],
+    ));
 
+    let series = metrics.history_series_count();
+    let tiers = metrics.tiered_series_count() * BYTES_PER_TIERED_SERIES;
+    let (history_memory, unit) = settings
+        .memory_unit
+        .format_value((series * sampling.history_length * BYTES_PER_SAMPLE + tiers) as f32);
+    let cost_ms = metrics.sampling_cost.as_secs_f64() * 1000.0;
+    let cpu = cost_ms / interval * 100.0;
+    let text = tr_format(
+        "Estimated overhead: {} of one core ({} ms per sample), {} {} of history for {} series",
+        &[
+            &percent(cpu, 1),
+            &number(cost_ms, 0),
+            &number(history_memory, 2),
+            &unit,
+            &series,
+        ],
+    );
+    let hover = match metrics
+        .monitor
+        .get_process_by_pid(&sysinfo::Pid::from_u32(std::process::id()))
+    {
+        Some(tvis) => {
+            let (memory, unit) = settings.memory_unit.format_value(tvis.memory() as f32);
+            tr_format(
+                "tvis currently uses {} CPU and {} {}. Sampling cost grows with the number \
+                 of processes on the system; longer intervals and fewer monitored processes \
+                 reduce it.",
+                &[&percent(tvis.cpu_usage(), 1), &number(memory, 1), &unit],
+            )
+        }
+        None => tr("Sampling cost grows with the number of processes on the system").to_string(),
+    };
+    let response = if cpu > 10.0 {
+        ui.colored_label(ui.visuals().warn_fg_color, format!("⚠ {text}"))
+    } else {
+        ui.weak(text)
+    };
+    response.on_hover_text(hover);
+}
+
+fn show_exclude_rules(ui: &mut egui::Ui, settings: &mut Settings, metrics: &mut Metrics) {
+    egui::CollapsingHeader::new(tr("Child Discovery")).show(ui, |ui| {
+        let mut changed = false;
+        let mut to_remove = None;
+        for (i, rule) in settings.exclude_rules.iter().enumerate() {
             ui.horizontal(|ui| {
-                ui.label("Theme:");
-                let dark_mode = ui.ctx().style().visuals.dark_mode;
-                if ui
-                    .button(if dark_mode { "🌞 Light" } else { "🌙 Dark" })
-                    .clicked()
-                {
-                    settings.toggle_theme(ctx);
+                ui.label(describe_rule(rule));
+                if ui.small_button("❌").clicked() {
+                    to_remove = Some(i);
                 }
             });
+        }
+        if let Some(i) = to_remove {
+            settings.exclude_rules.remove(i);
+            changed = true;
+        }
 
-            ui.separator();
-
-            ui.horizontal(|ui| {
-                ui.label("Update Mode:");
-                for mode in [UpdateMode::Continuous, UpdateMode::Reactive] {
-                    let label = match mode {
-                        UpdateMode::Continuous => "Continuous",
-                        UpdateMode::Reactive => "Reactive",
-                    };
-                    if ui
-                        .selectable_label(settings.update_mode == mode, label)
-                        .clicked()
-                    {
-                        settings.update_mode = mode;
-                        ctx.request_repaint();
+        let draft = &mut settings.exclude_draft;
+        egui::Grid::new("exclude_draft")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label(tr("Identifier:"));
+                ui.add(
+                    egui::TextEdit::singleline(&mut draft.identifier)
+                        .hint_text("firefox or pid:1234"),
+                );
+                ui.end_row();
+                ui.label(tr("Name globs:"));
+                ui.add(
+                    egui::TextEdit::singleline(&mut draft.name_globs)
+                        .hint_text("*Helper*, crashpad*"),
+                );
+                ui.end_row();
+                ui.label(tr("PIDs:"));
+                ui.add(egui::TextEdit::singleline(&mut draft.pids).hint_text("1234, 5678"));
+                ui.end_row();
+                ui.label(tr("Children:"));
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut draft.max_depth, Some(0), tr("None"));
+                    ui.radio_value(&mut draft.max_depth, Some(1), tr("Direct"));
+                    ui.radio_value(&mut draft.max_depth, None, tr("Full tree"));
+                    let deeper = draft.max_depth.is_some_and(|depth| depth > 1);
+                    if ui.radio(deeper, tr("Depth")).clicked() && !deeper {
+                        draft.max_depth = Some(2);
                     }
-                }
+                    if let Some(max_depth) = draft.max_depth.as_mut().filter(|depth| **depth > 1) {
+                        ui.add(egui::DragValue::new(max_depth).range(2..=32));
+                    }
+                });
+                ui.end_row();
+                ui.label(tr("Parents:"));
+                ui.checkbox(&mut draft.ancestors, tr("Include parent chain"))
+                    .on_hover_text(tr(
+                        "Also monitor the processes that started the matched ones",
+                    ));
+                ui.end_row();
             });
 
-            ui.separator();
+        if let Some(rule) = draft.to_rule() {
+            if ui.button(tr("Add rule")).clicked() {
+                settings
+                    .exclude_rules
+                    .retain(|existing| existing.identifier != rule.identifier);
+                settings.exclude_rules.push(rule);
+                settings.exclude_draft = Default::default();
+                changed = true;
+            }
+        }
+
+        if changed {
+            metrics.set_exclude_rules(settings.exclude_rules.clone());
+        }
+    });
+}
 
+fn show_watch_rules(ui: &mut egui::Ui, settings: &mut Settings, metrics: &mut Metrics) {
+    egui::CollapsingHeader::new(tr("Watch Rules")).show(ui, |ui| {
+        ui.weak(tr(
+            "Processes whose name or command line matches are monitored as they appear, \
+             each as its own pid: entry",
+        ));
+        let mut changed = false;
+        let mut to_remove = None;
+        for (i, rule) in settings.watch_rules.iter_mut().enumerate() {
             ui.horizontal(|ui| {
-                ui.label("Memory Unit:");
-                for unit in [
-                    MemoryUnit::Bytes,
-                    MemoryUnit::Kilobytes,
-                    MemoryUnit::Megabytes,
-                    MemoryUnit::Gigabytes,
-                ] {
-                    let label = match unit {
-                        MemoryUnit::Bytes => "Bytes",
-                        MemoryUnit::Kilobytes => "KB",
-                        MemoryUnit::Megabytes => "MB",
-                        MemoryUnit::Gigabytes => "GB",
-                    };
-                    if ui
-                        .selectable_label(settings.memory_unit == unit, label)
-                        .clicked()
-                    {
-                        settings.memory_unit = unit;
-                    }
+                changed |= ui.checkbox(&mut rule.enabled, "").changed();
+                ui.monospace(&rule.pattern);
+                if rule.remove_on_exit {
+                    ui.weak(tr("removed on exit"));
+                }
+                if ui.small_button("❌").clicked() {
+                    to_remove = Some(i);
                 }
             });
+        }
+        if let Some(i) = to_remove {
+            settings.watch_rules.remove(i);
+            changed = true;
+        }
 
-            ui.separator();
-
-            if ui.button("Close").clicked() {
-                settings.hide();
-            }
+        let draft = &mut settings.watch_draft;
+        ui.horizontal(|ui| {
+            ui.label(tr("Pattern:"));
+            ui.add(
+                egui::TextEdit::singleline(&mut draft.pattern).hint_text("pytest or *cargo test*"),
+            )
+            .on_hover_text(tr("* matches any run of characters, ? a single one"));
+            ui.checkbox(&mut draft.remove_on_exit, tr("Remove on exit"))
+                .on_hover_text(tr("Drop the entry and its history once the process exited"));
         });
+        if let Some(rule) = draft.to_rule() {
+            if ui.button(tr("Add rule")).clicked() {
+                settings
+                    .watch_rules
+                    .retain(|existing| existing.pattern != rule.pattern);
+                settings.watch_rules.push(rule);
+                settings.watch_draft = Default::default();
+                changed = true;
+            }
+        }
+
+        if changed {
+            metrics.set_watch_rules(settings.watch_rules.clone());
+        }
+    });
+}
+
+fn describe_rule(rule: &ExcludeRule) -> String {
+    let mut parts = Vec::new();
+    if !rule.name_globs.is_empty() {
+        parts.push(format!("{} {}", tr("names"), rule.name_globs.join(", ")));
+    }
+    if !rule.pids.is_empty() {
+        let pids: Vec<String> = rule.pids.iter().map(u32::to_string).collect();
+        parts.push(format!("PIDs {}", pids.join(", ")));
+    }
+    match rule.max_depth {
+        Some(0) => parts.push(tr("all children").to_string()),
+        Some(1) => parts.push(tr("grandchildren").to_string()),
+        Some(max_depth) => parts.push(format!("{} > {max_depth}", tr("depth"))),
+        None => {}
+    }
+    let mut description = rule.identifier.to_string();
+    if !parts.is_empty() {
+        description = tr_format("{}: skip {}", &[&description, &parts.join("; ")]);
+    }
+    if rule.ancestors {
+        description.push_str(&format!(", {}", tr("with its parent chain")));
+    }
+    description
 }
@@ -0,0 +1,152 @@
+use crate::metrics::process::{MetricType, ProcessIdentifier};
+use crate::metrics::Metrics;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Burn values kept for the trend plot
+const BURN_HISTORY_LEN: usize = 300;
+
+/// Objective "average `metric` of `identifier` stays below `threshold` over
+/// the last `window_minutes`", evaluated on every sampling tick
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+pub struct SloTarget {
+    pub identifier: ProcessIdentifier,
+    pub metric: MetricType,
+    /// Percent for CPU and GPU, bytes for memory
+    pub threshold: f64,
+    pub window_minutes: u64,
+    pub enabled: bool,
+    #[serde(skip)]
+    tracking: SloTracking,
+}
+
+/// Runtime state of a target, restarted with the app
+#[derive(Debug, Clone, Default)]
+struct SloTracking {
+    samples: VecDeque<(Instant, f64)>,
+    evaluated: u64,
+    met: u64,
+    burn_history: VecDeque<f64>,
+}
+
+impl SloTarget {
+    pub fn new(identifier: ProcessIdentifier, metric: MetricType, threshold: f64) -> Self {
+        Self {
+            identifier,
+            metric,
+            threshold,
+            window_minutes: 60,
+            enabled: true,
+            tracking: SloTracking::default(),
+        }
+    }
+
+    fn record(&mut self, value: f64, now: Instant) {
+        let window = Duration::from_secs(self.window_minutes * 60);
+        let tracking = &mut self.tracking;
+        tracking.samples.push_back((now, value));
+        while tracking
+            .samples
+            .front()
+            .is_some_and(|(at, _)| now.duration_since(*at) > window)
+        {
+            tracking.samples.pop_front();
+        }
+
+        let Some(burn) = self.burn() else {
+            return;
+        };
+        let tracking = &mut self.tracking;
+        tracking.evaluated += 1;
+        if burn < 1.0 {
+            tracking.met += 1;
+        }
+        if tracking.burn_history.len() == BURN_HISTORY_LEN {
+            tracking.burn_history.pop_front();
+        }
+        tracking.burn_history.push_back(burn);
+    }
+
+    /// Average over the window so far
+    pub fn window_average(&self) -> Option<f64> {
+        let samples = &self.tracking.samples;
+        (!samples.is_empty())
+            .then(|| samples.iter().map(|(_, value)| value).sum::<f64>() / samples.len() as f64)
+    }
+
+    /// Share of the threshold used by the window average, failing at 1.0 and above
+    pub fn burn(&self) -> Option<f64> {
+        let average = self.window_average()?;
+        Some(if self.threshold > 0.0 {
+            average / self.threshold
+        } else if average > 0.0 {
+            f64::INFINITY
+        } else {
+            0.0
+        })
+    }
+
+    /// Percentage of ticks on which the objective held
+    pub fn compliance(&self) -> Option<f64> {
+        let tracking = &self.tracking;
+        (tracking.evaluated > 0).then(|| tracking.met as f64 / tracking.evaluated as f64 * 100.0)
+    }
+
+    pub fn burn_history(&self) -> impl Iterator<Item = f64> + '_ {
+        self.tracking.burn_history.iter().copied()
+    }
+
+    pub fn is_met(&self) -> bool {
+        self.burn().is_none_or(|burn| burn < 1.0)
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+#[serde(default)]
+pub struct Slos {
+    pub targets: Vec<SloTarget>,
+    #[serde(skip)]
+    pub(super) show_window: bool,
+    #[serde(skip)]
+    last_tick: u64,
+    #[serde(skip)]
+    pub(super) draft: Option<SloTarget>,
+}
+
+impl Slos {
+    pub fn toggle_window(&mut self) {
+        self.show_window = !self.show_window;
+    }
+
+    /// Records the current value of every target once per sampling tick
+    pub fn update(&mut self, metrics: &Metrics) {
+        if metrics.tick == self.last_tick {
+            return;
+        }
+        self.last_tick = metrics.tick;
+
+        let now = Instant::now();
+        for target in self.targets.iter_mut().filter(|target| target.enabled) {
+            let Some(process_data) = metrics.get_process_data(&target.identifier) else {
+                continue;
+            };
+            let stats = &process_data.genereal.stats;
+            let value = match target.metric {
+                MetricType::Cpu => stats.current_cpu as f64,
+                MetricType::Memory => stats.current_memory as f64,
+                MetricType::Gpu => stats.current_gpu as f64,
+                MetricType::Processes => stats.process_count as f64,
+            };
+            target.record(value, now);
+        }
+    }
+
+    pub fn targets_for<'a>(
+        &'a self,
+        identifier: &'a ProcessIdentifier,
+    ) -> impl Iterator<Item = &'a SloTarget> {
+        self.targets
+            .iter()
+            .filter(move |target| target.enabled && &target.identifier == identifier)
+    }
+}
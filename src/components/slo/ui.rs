@@ -0,0 +1,222 @@
+use super::state::{SloTarget, Slos};
+use crate::components::i18n::{percent, tr, tr_format};
+use crate::components::settings::Settings;
+use crate::metrics::process::{MetricType, ProcessIdentifier};
+
+impl Slos {
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        monitored_processes: &[ProcessIdentifier],
+        settings: &Settings,
+    ) {
+        if !self.show_window {
+            return;
+        }
+
+        let mut open = true;
+        egui::Window::new(tr("🎯 SLO Targets"))
+            .open(&mut open)
+            .resizable(true)
+            .show(ctx, |ui| {
+                self.show_targets(ui, settings);
+                ui.separator();
+                self.show_draft(ui, monitored_processes, settings);
+            });
+        self.show_window = open;
+    }
+
+    /// Compact gauges of the targets of `identifier`, shown above its plots
+    pub fn show_gauges(
+        &self,
+        ui: &mut egui::Ui,
+        identifier: &ProcessIdentifier,
+        settings: &Settings,
+    ) {
+        for target in self.targets_for(identifier) {
+            ui.horizontal(|ui| {
+                ui.label(describe(target, settings));
+                gauge(ui, target, settings);
+            });
+        }
+    }
+
+    fn show_targets(&mut self, ui: &mut egui::Ui, settings: &Settings) {
+        ui.heading(tr("Targets"));
+        if self.targets.is_empty() {
+            ui.weak(tr("No targets yet"));
+        }
+        let mut to_remove = None;
+        for (i, target) in self.targets.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut target.enabled, "");
+                ui.label(describe(target, settings));
+                if ui.small_button("❌").clicked() {
+                    to_remove = Some(i);
+                }
+            });
+            if !target.enabled {
+                continue;
+            }
+            gauge(ui, target, settings);
+            let burn: egui_plot::PlotPoints = target
+                .burn_history()
+                .enumerate()
+                .map(|(i, burn)| [i as f64, burn * 100.0])
+                .collect();
+            let limit_color = ui.visuals().error_fg_color;
+            egui_plot::Plot::new(("slo_burn", i))
+                .height(60.0)
+                .show_x(false)
+                .include_y(0.0)
+                .include_y(100.0)
+                .allow_drag(false)
+                .allow_zoom(false)
+                .allow_scroll(false)
+                .show(ui, |plot_ui| {
+                    plot_ui.hline(egui_plot::HLine::new(100.0).color(limit_color));
+                    plot_ui.line(egui_plot::Line::new(burn).name(tr("Burn %")));
+                });
+        }
+        if let Some(i) = to_remove {
+            self.targets.remove(i);
+        }
+    }
+
+    fn show_draft(
+        &mut self,
+        ui: &mut egui::Ui,
+        monitored_processes: &[ProcessIdentifier],
+        settings: &Settings,
+    ) {
+        let Some(draft) = &mut self.draft else {
+            if ui.button(tr("Add Target")).clicked() {
+                if let Some(identifier) = monitored_processes.first() {
+                    self.draft = Some(SloTarget::new(identifier.clone(), MetricType::Cpu, 50.0));
+                }
+            }
+            return;
+        };
+
+        let unit = settings.memory_unit;
+        let bytes_per_unit = 1.0 / unit.format_value(1.0).0 as f64;
+        ui.group(|ui| {
+            egui::ComboBox::from_label(tr("Process"))
+                .selected_text(draft.identifier.to_string())
+                .show_ui(ui, |ui| {
+                    for identifier in monitored_processes {
+                        ui.selectable_value(
+                            &mut draft.identifier,
+                            identifier.clone(),
+                            identifier.to_string(),
+                        );
+                    }
+                });
+            ui.horizontal(|ui| {
+                ui.label(tr("Metric:"));
+                ui.selectable_value(&mut draft.metric, MetricType::Cpu, tr("CPU"));
+                ui.selectable_value(&mut draft.metric, MetricType::Memory, tr("Memory"));
+                ui.selectable_value(&mut draft.metric, MetricType::Gpu, tr("GPU"));
+                ui.selectable_value(&mut draft.metric, MetricType::Processes, tr("Processes"));
+            });
+            ui.horizontal(|ui| {
+                ui.label(tr("Average below:"));
+                match draft.metric {
+                    MetricType::Cpu | MetricType::Gpu => {
+                        ui.add(egui::DragValue::new(&mut draft.threshold).suffix("%"));
+                    }
+                    MetricType::Processes => {
+                        ui.add(egui::DragValue::new(&mut draft.threshold).speed(1.0));
+                    }
+                    MetricType::Memory => {
+                        let mut value = draft.threshold / bytes_per_unit;
+                        let suffix = format!(" {}", unit.format_value(0.0).1);
+                        if ui
+                            .add(egui::DragValue::new(&mut value).suffix(suffix))
+                            .changed()
+                        {
+                            draft.threshold = value * bytes_per_unit;
+                        }
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label(tr("Over:"));
+                ui.add(
+                    egui::DragValue::new(&mut draft.window_minutes)
+                        .range(1..=24 * 60)
+                        .suffix(" min"),
+                );
+            });
+        });
+        ui.horizontal(|ui| {
+            if ui.button(tr("Save")).clicked() {
+                self.targets.extend(self.draft.take());
+            }
+            if ui.button(tr("Cancel")).clicked() {
+                self.draft = None;
+            }
+        });
+    }
+}
+
+/// Progress bar of the window average against the threshold, with compliance
+fn gauge(ui: &mut egui::Ui, target: &SloTarget, settings: &Settings) {
+    let Some(burn) = target.burn() else {
+        ui.weak(tr("waiting for samples"));
+        return;
+    };
+    let color = if target.is_met() {
+        egui::Color32::from_rgb(80, 170, 80)
+    } else {
+        ui.visuals().error_fg_color
+    };
+    let average = target.window_average().unwrap_or_default();
+    ui.add(
+        egui::ProgressBar::new(burn.min(1.0) as f32)
+            .desired_width(160.0)
+            .fill(color)
+            .text(tr_format(
+                "avg {} ({} burn)",
+                &[
+                    &format_value(target.metric, average, settings),
+                    &percent(burn * 100.0, 0),
+                ],
+            )),
+    );
+    if let Some(compliance) = target.compliance() {
+        ui.label(tr_format("{} compliant", &[&percent(compliance, 1)]))
+            .on_hover_text(tr(
+                "Share of samples on which the window average met the target",
+            ));
+    }
+}
+
+fn describe(target: &SloTarget, settings: &Settings) -> String {
+    let metric = match target.metric {
+        MetricType::Cpu => "CPU",
+        MetricType::Memory => "memory",
+        MetricType::Gpu => "GPU",
+        MetricType::Processes => "processes",
+    };
+    tr_format(
+        "{} avg {} < {} over {} min",
+        &[
+            &target.identifier,
+            &tr(metric),
+            &format_value(target.metric, target.threshold, settings),
+            &target.window_minutes,
+        ],
+    )
+}
+
+fn format_value(metric: MetricType, value: f64, settings: &Settings) -> String {
+    match metric {
+        MetricType::Cpu | MetricType::Gpu => format!("{value:.1}%"),
+        MetricType::Memory => {
+            let (value, unit) = settings.memory_unit.format_value(value as f32);
+            format!("{value:.1} {unit}")
+        }
+        MetricType::Processes => format!("{value:.1}"),
+    }
+}
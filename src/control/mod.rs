@@ -0,0 +1,469 @@
+use crate::metrics::annotations::labels_near;
+use crate::metrics::process::{ProcessData, ProcessIdentifier};
+use crate::metrics::{Metrics, GENERAL_STATS_PID};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long a client may take to send its request and read the reply
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Longest request read, the rest is cut off
+const MAX_REQUEST_BYTES: u64 = 4096;
+
+/// Control socket of the current user, overridable with `TVIS_CONTROL_SOCKET`.
+/// A Unix socket in `$XDG_RUNTIME_DIR` (or a private directory in the temp
+/// directory), a named pipe on Windows.
+pub fn control_path() -> PathBuf {
+    std::env::var_os("TVIS_CONTROL_SOCKET")
+        .map(PathBuf::from)
+        .unwrap_or_else(imp::default_path)
+}
+
+/// Serves text requests from `tvis status`, `tvis export`, `tvis add`,
+/// `tvis remove` and `tvis clear` on background threads
+pub fn spawn_server(metrics: Metrics) {
+    let path = control_path();
+    if let Err(err) = listen(&path, metrics) {
+        log::warn!("Control socket {} unavailable: {err}", path.display());
+    }
+}
+
+/// Accepts clients on `path`, each served on its own thread with its own handle
+/// on the worker. Only the current user can connect.
+pub fn listen(path: &Path, metrics: Metrics) -> io::Result<()> {
+    imp::listen(path, metrics)
+}
+
+/// Sends `request` to the running instance and returns its reply
+pub fn send_request(request: &str) -> io::Result<String> {
+    send_request_to(&control_path(), request)
+}
+
+/// Sends `request` to the instance listening on `path` and returns its reply.
+/// The empty line after it ends the identifiers of a multi-line request.
+pub fn send_request_to(path: &Path, request: &str) -> io::Result<String> {
+    let mut stream = imp::connect(path)?;
+    writeln!(stream, "{request}\n")?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    Ok(response)
+}
+
+fn handle_client(mut stream: impl Read + Write, metrics: &mut Metrics) -> io::Result<()> {
+    let request = read_request(BufReader::new((&mut stream).take(MAX_REQUEST_BYTES)))?;
+    let response = respond(&request, metrics);
+    stream.write_all(response.as_bytes())
+}
+
+/// A request is one line, except a bare `add` or `remove`, which is followed by
+/// one identifier per line up to an empty line, so identifiers may hold spaces
+fn read_request(mut reader: impl BufRead) -> io::Result<String> {
+    let mut request = String::new();
+    reader.read_line(&mut request)?;
+    if matches!(request.trim(), "add" | "remove") {
+        let mut line = String::new();
+        while reader.read_line(&mut line)? > 0 && !line.trim().is_empty() {
+            request.push_str(&line);
+            line.clear();
+        }
+    }
+    Ok(request.trim().to_string())
+}
+
+#[cfg(unix)]
+mod imp {
+    use super::{handle_client, REQUEST_TIMEOUT};
+    use crate::metrics::Metrics;
+    use std::fs;
+    use std::io;
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::{Path, PathBuf};
+    use std::thread;
+
+    pub fn default_path() -> PathBuf {
+        match std::env::var_os("XDG_RUNTIME_DIR") {
+            Some(dir) => PathBuf::from(dir).join("tvis.sock"),
+            None => std::env::temp_dir()
+                .join(format!("tvis-{}", uid()))
+                .join("control.sock"),
+        }
+    }
+
+    pub fn listen(path: &Path, mut metrics: Metrics) -> io::Result<()> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            private_dir(dir)?;
+        }
+        // Left behind by an instance that didn't exit cleanly
+        if path.exists() && UnixStream::connect(path).is_err() {
+            fs::remove_file(path)?;
+        }
+        let listener = UnixListener::bind(path)?;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                // The clone starts from the newest snapshot
+                metrics.update();
+                let mut metrics = metrics.clone();
+                thread::spawn(move || {
+                    let served = stream
+                        .set_read_timeout(Some(REQUEST_TIMEOUT))
+                        .and_then(|()| stream.set_write_timeout(Some(REQUEST_TIMEOUT)))
+                        .and_then(|()| handle_client(&stream, &mut metrics));
+                    if let Err(err) = served {
+                        log::warn!("Control request failed: {err}");
+                    }
+                });
+            }
+        });
+        Ok(())
+    }
+
+    pub fn connect(path: &Path) -> io::Result<UnixStream> {
+        let stream = UnixStream::connect(path)?;
+        stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+        Ok(stream)
+    }
+
+    /// Creates `dir` readable only by the current user, or checks that an
+    /// existing one is, so nobody else can take the socket's place
+    fn private_dir(dir: &Path) -> io::Result<()> {
+        match fs::DirBuilder::new().mode(0o700).create(dir) {
+            Err(err) if err.kind() != io::ErrorKind::AlreadyExists => return Err(err),
+            _ => {}
+        }
+        let metadata = fs::symlink_metadata(dir)?;
+        if !metadata.is_dir() || metadata.uid() != uid() || metadata.mode() & 0o022 != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("{} is writable by other users", dir.display()),
+            ));
+        }
+        Ok(())
+    }
+
+    fn uid() -> u32 {
+        // SAFETY: getuid only reads the user ID of the process
+        unsafe { libc::getuid() }
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use super::{handle_client, REQUEST_TIMEOUT};
+    use crate::metrics::Metrics;
+    use std::fs::{File, OpenOptions};
+    use std::io;
+    use std::os::windows::ffi::OsStrExt;
+    use std::os::windows::io::{AsRawHandle, FromRawHandle};
+    use std::path::{Path, PathBuf};
+    use std::sync::mpsc;
+    use std::thread;
+    use windows_sys::Win32::Foundation::{ERROR_PIPE_CONNECTED, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::Storage::FileSystem::{
+        FILE_FLAG_FIRST_PIPE_INSTANCE, PIPE_ACCESS_DUPLEX,
+    };
+    use windows_sys::Win32::System::Pipes::{
+        ConnectNamedPipe, CreateNamedPipeW, PIPE_READMODE_BYTE, PIPE_REJECT_REMOTE_CLIENTS,
+        PIPE_TYPE_BYTE, PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+    };
+    use windows_sys::Win32::System::IO::CancelSynchronousIo;
+
+    pub fn default_path() -> PathBuf {
+        let user = std::env::var("USERNAME").unwrap_or_default();
+        PathBuf::from(format!(r"\\.\pipe\tvis-{user}"))
+    }
+
+    /// The default security of a named pipe only lets its owner, the
+    /// administrators and the system write to it
+    pub fn listen(path: &Path, mut metrics: Metrics) -> io::Result<()> {
+        let name: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+        // Fails if another process, possibly of another user, owns the name
+        let mut pipe = create_pipe(&name, true)?;
+        thread::spawn(move || loop {
+            // SAFETY: the handle is owned by `pipe`, and no overlapped I/O is used
+            let connected = unsafe { ConnectNamedPipe(pipe.as_raw_handle(), std::ptr::null_mut()) }
+                != 0
+                || io::Error::last_os_error().raw_os_error() == Some(ERROR_PIPE_CONNECTED as i32);
+            let next = match create_pipe(&name, false) {
+                Ok(next) => next,
+                Err(err) => {
+                    log::warn!("Control socket closed: {err}");
+                    return;
+                }
+            };
+            let client = std::mem::replace(&mut pipe, next);
+            if connected {
+                // The clone starts from the newest snapshot
+                metrics.update();
+                let metrics = metrics.clone();
+                thread::spawn(move || serve(client, metrics));
+            }
+        });
+        Ok(())
+    }
+
+    pub fn connect(path: &Path) -> io::Result<File> {
+        OpenOptions::new().read(true).write(true).open(path)
+    }
+
+    fn create_pipe(name: &[u16], first: bool) -> io::Result<File> {
+        let first = if first {
+            FILE_FLAG_FIRST_PIPE_INSTANCE
+        } else {
+            0
+        };
+        // SAFETY: `name` is NUL-terminated and the handle is checked before use
+        let handle = unsafe {
+            CreateNamedPipeW(
+                name.as_ptr(),
+                PIPE_ACCESS_DUPLEX | first,
+                PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT | PIPE_REJECT_REMOTE_CLIENTS,
+                PIPE_UNLIMITED_INSTANCES,
+                4096,
+                4096,
+                0,
+                std::ptr::null(),
+            )
+        };
+        if handle == INVALID_HANDLE_VALUE {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: the handle was just created and nothing else owns it
+        Ok(unsafe { File::from_raw_handle(handle) })
+    }
+
+    /// Pipes have no read timeout, so a request still pending after
+    /// [`REQUEST_TIMEOUT`] is cancelled from this thread
+    fn serve(pipe: File, mut metrics: Metrics) {
+        let (done, finished) = mpsc::channel();
+        let handler = thread::spawn(move || {
+            let served = handle_client(&pipe, &mut metrics).and_then(|()| pipe.sync_all());
+            let _ = done.send(());
+            served
+        });
+        if finished.recv_timeout(REQUEST_TIMEOUT).is_err() {
+            // SAFETY: the join handle keeps the thread handle open
+            unsafe { CancelSynchronousIo(handler.as_raw_handle()) };
+        }
+        if let Ok(Err(err)) = handler.join() {
+            log::warn!("Control request failed: {err}");
+        }
+    }
+}
+
+/// Reply to one request, from the newest snapshot. `add`, `remove` and `clear`
+/// are sent to the worker, which the window and the terminal UI follow.
+pub fn respond(request: &str, metrics: &mut Metrics) -> String {
+    metrics.update();
+    let mut parts = request.split_whitespace();
+    match parts.next() {
+        Some(command @ ("add" | "remove")) => {
+            // The rest of the first line, or each line after a bare command
+            let identifiers: Vec<ProcessIdentifier> = request
+                .strip_prefix(command)
+                .unwrap_or_default()
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(ProcessIdentifier::from)
+                .collect();
+            if identifiers.is_empty() {
+                return format!("error: {command} needs an identifier\n");
+            }
+            identifiers
+                .iter()
+                .map(|identifier| match command {
+                    "add" => add(metrics, identifier),
+                    _ => remove(metrics, identifier),
+                })
+                .collect()
+        }
+        Some("clear") => {
+            let identifiers = match parts.next() {
+                Some(identifier) => vec![ProcessIdentifier::from(identifier)],
+                None => metrics.get_monitored_processes().to_vec(),
+            };
+            identifiers
+                .iter()
+                .map(|identifier| clear(metrics, identifier))
+                .collect()
+        }
+        _ => query(request, metrics),
+    }
+}
+
+fn query(request: &str, metrics: &Metrics) -> String {
+    let mut parts = request.split_whitespace();
+    match parts.next() {
+        Some("status") => match parts.next() {
+            Some(identifier) => status(metrics, &ProcessIdentifier::from(identifier)),
+            None => metrics
+                .get_monitored_processes()
+                .iter()
+                .map(|identifier| status(metrics, identifier))
+                .collect(),
+        },
+        Some("export") => {
+            let Some(identifier) = parts.next() else {
+                return "error: export needs an identifier\n".to_string();
+            };
+            let since = parts.next().and_then(|s| s.parse::<u64>().ok());
+            export(metrics, &ProcessIdentifier::from(identifier), since)
+        }
+        _ => format!("error: unknown request '{request}'\n"),
+    }
+}
+
+fn add(metrics: &mut Metrics, identifier: &ProcessIdentifier) -> String {
+    if metrics.get_monitored_processes().contains(identifier) {
+        return format!("{}: already monitored\n", identifier);
+    }
+    metrics.add_selected_process(identifier.clone());
+    format!("{}: monitored\n", identifier)
+}
+
+fn remove(metrics: &mut Metrics, identifier: &ProcessIdentifier) -> String {
+    if !metrics.get_monitored_processes().contains(identifier) {
+        return format!("error: {} is not monitored\n", identifier);
+    }
+    metrics.remove_selected_process(identifier);
+    format!("{}: removed\n", identifier)
+}
+
+/// Drops the samples of `identifier`, which keeps being monitored
+fn clear(metrics: &mut Metrics, identifier: &ProcessIdentifier) -> String {
+    if !metrics.get_monitored_processes().contains(identifier) {
+        return format!("error: {} is not monitored\n", identifier);
+    }
+    metrics.clear_process_data(identifier);
+    format!("{}: cleared\n", identifier)
+}
+
+fn status(metrics: &Metrics, identifier: &ProcessIdentifier) -> String {
+    let Some(process_data) = metrics.get_process_data(identifier) else {
+        return format!("{}: not found\n", identifier);
+    };
+    let stats = &process_data.genereal.stats;
+    format!(
+        "{}: cpu {:.1}% (avg {:.1}%, peak {:.1}%) | memory {:.1} MB (avg {:.1} MB, peak {:.1} MB) | {} processes, {} threads\n",
+        identifier,
+        stats.current_cpu,
+        stats.avg_cpu,
+        stats.peak_cpu,
+        megabytes(stats.current_memory),
+        megabytes(stats.avg_memory),
+        megabytes(stats.peak_memory),
+        stats.process_count,
+        stats.thread_count,
+    )
+}
+
+/// Aggregate history as CSV with the time each sample was taken, limited to
+/// the samples of the last `since_secs` seconds when given
+fn export(metrics: &Metrics, identifier: &ProcessIdentifier, since_secs: Option<u64>) -> String {
+    let Some(process_data) = metrics.get_process_data(identifier) else {
+        return format!("error: {} not found\n", identifier);
+    };
+    let interval = metrics.interval_of(identifier);
+    let now = SystemTime::now();
+    let since = since_secs.and_then(|secs| now.checked_sub(Duration::from_secs(secs)));
+
+    let mut csv = String::from("unix_ms,seconds_ago,cpu_percent,memory_bytes,annotation\n");
+    for (taken_at, cpu, memory) in history_rows(process_data) {
+        if since.is_some_and(|since| taken_at < since) {
+            continue;
+        }
+        let unix_ms = taken_at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let seconds_ago = now
+            .duration_since(taken_at)
+            .unwrap_or_default()
+            .as_secs_f64();
+        let annotation = labels_near(metrics.annotations(), identifier, interval, taken_at);
+        csv.push_str(&format!(
+            "{unix_ms},{seconds_ago:.1},{cpu:.2},{memory},{}\n",
+            escape_csv(&annotation)
+        ));
+    }
+    csv
+}
+
+/// Time, CPU and memory of each aggregate sample, oldest first. Aligned on the
+/// newest sample, as the times are recorded apart from the values.
+fn history_rows(process_data: &ProcessData) -> Vec<(SystemTime, f32, usize)> {
+    let history = &process_data.genereal.history;
+    let times = history
+        .get_sample_times(&GENERAL_STATS_PID)
+        .unwrap_or_default();
+    let cpu = history
+        .get_cpu_history(&GENERAL_STATS_PID)
+        .unwrap_or_default();
+    let memory = history
+        .get_memory_history(&GENERAL_STATS_PID)
+        .unwrap_or_default();
+    let mut rows: Vec<_> = times
+        .into_iter()
+        .rev()
+        .zip(cpu.into_iter().rev())
+        .zip(memory.into_iter().rev())
+        .map(|((taken_at, cpu), memory)| (taken_at, cpu, memory))
+        .collect();
+    rows.reverse();
+    rows
+}
+
+/// Quotes a field containing a separator or quote
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn megabytes(bytes: usize) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0)
+}
+
+/// Parses durations like `90`, `30s`, `10m` or `2h` into seconds, `None` when
+/// malformed or too long to count in seconds
+pub fn parse_duration(value: &str) -> Option<u64> {
+    let (number, multiplier) = match value.chars().last()? {
+        's' => (&value[..value.len() - 1], 1),
+        'm' => (&value[..value.len() - 1], 60),
+        'h' => (&value[..value.len() - 1], 60 * 60),
+        _ => (value, 1),
+    };
+    number.parse::<u64>().ok()?.checked_mul(multiplier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bare_add_reads_one_identifier_per_line() {
+        let request = |text: &str| read_request(io::Cursor::new(text)).unwrap();
+        assert_eq!(request("status pid:1\nignored\n"), "status pid:1");
+        assert_eq!(request("add name:my app\n"), "add name:my app");
+        assert_eq!(
+            request("add\nname:my app\npid:2\n\nignored\n"),
+            "add\nname:my app\npid:2"
+        );
+        assert_eq!(request("remove\npid:3"), "remove\npid:3");
+    }
+
+    #[test]
+    fn durations_that_overflow_are_rejected() {
+        assert_eq!(parse_duration("90"), Some(90));
+        assert_eq!(parse_duration("10m"), Some(600));
+        assert_eq!(parse_duration("2h"), Some(7200));
+        assert_eq!(parse_duration("m"), None);
+        assert_eq!(parse_duration(&format!("{}h", u64::MAX / 60)), None);
+        assert_eq!(parse_duration(&format!("{}s", u64::MAX)), Some(u64::MAX));
+    }
+}
@@ -0,0 +1,65 @@
+use crate::components::alerts::FiredAlert;
+use crate::metrics::process::{ProcessEvent, ProcessIdentifier};
+use std::collections::VecDeque;
+
+/// Something that happened in the app, published on the [`EventBus`]
+#[derive(Debug, Clone)]
+pub enum AppEvent {
+    /// The sampler finished tick `tick`
+    SampleAdded {
+        tick: u64,
+    },
+    /// A process joined or left the tree of `identifier`
+    Process {
+        identifier: ProcessIdentifier,
+        event: ProcessEvent,
+    },
+    AlertFired(FiredAlert),
+    /// The process shown in the main view changed
+    SelectionChanged(Option<ProcessIdentifier>),
+}
+
+/// Receiver of every event published on the bus, e.g. a logger or exporter
+pub trait EventSink {
+    fn on_event(&mut self, event: &AppEvent);
+}
+
+/// Logs every event, useful when debugging with `RUST_LOG=debug`
+pub struct LogSink;
+
+impl EventSink for LogSink {
+    fn on_event(&mut self, event: &AppEvent) {
+        log::debug!("{event:?}");
+    }
+}
+
+/// Queue of app events. Producers `publish`, the app drains the queue once
+/// per frame, reacting in its components and forwarding each event to the
+/// subscribed sinks.
+#[derive(Default)]
+pub struct EventBus {
+    pending: VecDeque<AppEvent>,
+    sinks: Vec<Box<dyn EventSink>>,
+}
+
+impl EventBus {
+    pub fn subscribe(&mut self, sink: impl EventSink + 'static) {
+        self.sinks.push(Box::new(sink));
+    }
+
+    pub fn publish(&mut self, event: AppEvent) {
+        self.pending.push_back(event);
+    }
+
+    /// Next pending event, including ones published while draining
+    pub fn pop(&mut self) -> Option<AppEvent> {
+        self.pending.pop_front()
+    }
+
+    /// Forwards `event` to every sink
+    pub fn notify(&mut self, event: &AppEvent) {
+        for sink in &mut self.sinks {
+            sink.on_event(event);
+        }
+    }
+}
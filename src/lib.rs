@@ -1,6 +1,17 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
+#[cfg(feature = "gui")]
 pub mod app;
+#[cfg(feature = "gui")]
 pub mod components;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod control;
+#[cfg(feature = "gui")]
+pub mod events;
 pub mod metrics;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod stream;
+#[cfg(all(feature = "gui", not(target_arch = "wasm32")))]
+pub mod tui;
+#[cfg(feature = "gui")]
 pub use app::ProcessMonitorApp;
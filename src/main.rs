@@ -6,7 +6,43 @@ use tvis::ProcessMonitorApp;
 // When compiling natively:
 #[cfg(not(target_arch = "wasm32"))]
 fn main() -> eframe::Result<()> {
-    use tvis::metrics::{process::ProcessIdentifier, Metrics};
+    use clap::Parser;
+    use tvis::metrics::process::ProcessIdentifier;
+
+    let cli = Cli::parse();
+    if let Some(command) = &cli.command {
+        match tvis::control::send_request(&command.request()) {
+            Ok(response) => print!("{response}"),
+            Err(err) => {
+                eprintln!("Failed to reach a running tvis instance: {err}");
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+    let identifiers: Vec<ProcessIdentifier> = cli
+        .watch
+        .iter()
+        .chain(&cli.identifiers)
+        .map(|identifier| ProcessIdentifier::from(identifier.as_str()))
+        .chain(pgrep_identifiers(&cli.pgrep))
+        .collect();
+    let interval_ms = cli.interval.map(|interval| interval as usize);
+    let history = cli.history.map(|history| history as usize);
+    if cli.tui {
+        let mut settings = tvis::components::settings::Settings::default();
+        settings.update_interval_ms = interval_ms.unwrap_or(settings.update_interval_ms);
+        settings.history_length = history.unwrap_or(settings.history_length);
+        let tui = tvis::tui::TuiApp::new(identifiers, settings);
+        if let Some(addr) = &cli.stream {
+            start_stream(addr, tui.metrics());
+        }
+        if let Err(err) = tui.run() {
+            eprintln!("Failed to run terminal UI: {err}");
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
 
     #[cfg(debug_assertions)]
     env_logger::builder()
@@ -27,10 +63,142 @@ fn main() -> eframe::Result<()> {
     eframe::run_native(
         "Process Monitor",
         native_options,
-        Box::new(|cc| Ok(Box::new(ProcessMonitorApp::new(cc)))),
+        Box::new(move |cc| {
+            let mut app = ProcessMonitorApp::new(cc);
+            app.set_sampling(interval_ms, history);
+            if let Some(addr) = &cli.stream {
+                start_stream(addr, app.metrics.clone());
+            }
+            for identifier in identifiers {
+                app.add_monitored_proc(identifier);
+            }
+            Ok(Box::new(app))
+        }),
     )
 }
 
+/// Process monitor with live graphs of CPU, memory and GPU usage
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(clap::Parser)]
+#[command(version, args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Identifier to monitor from the start: a name, pid:1234, user:jenkins, ...
+    #[arg(short, long, value_name = "IDENTIFIER")]
+    watch: Vec<String>,
+    /// Monitor every process whose name or command line matches the regular expression
+    #[arg(long, value_name = "PATTERN")]
+    pgrep: Vec<regex::Regex>,
+    /// Milliseconds between samples
+    #[arg(short, long, value_name = "MS", value_parser = clap::value_parser!(u64).range(100..))]
+    interval: Option<u64>,
+    /// Samples kept per graph
+    #[arg(long, value_name = "SAMPLES", value_parser = clap::value_parser!(u64).range(10..))]
+    history: Option<u64>,
+    /// Run in the terminal instead of opening a window
+    #[arg(long)]
+    tui: bool,
+    /// Push every sample as JSON to WebSocket clients, e.g. ws://0.0.0.0:9000
+    #[arg(long, value_name = "URL", value_parser = tvis::stream::parse_url)]
+    stream: Option<String>,
+    /// Identifiers to monitor, like --watch
+    #[arg(value_name = "IDENTIFIER")]
+    identifiers: Vec<String>,
+}
+
+/// Queries and commands for a running instance, sent over its control socket
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(clap::Subcommand)]
+enum Command {
+    /// Print the current usage of an identifier, or of every monitored one
+    Status {
+        /// Identifier as monitored, e.g. firefox or pid:1234
+        identifier: Option<String>,
+    },
+    /// Print the samples of an identifier as CSV
+    Export {
+        /// Identifier as monitored, e.g. firefox or pid:1234
+        identifier: String,
+        /// Only the samples of the last 30s, 10m, 2h, ...
+        #[arg(long, value_name = "DURATION", value_parser = parse_since)]
+        since: Option<u64>,
+    },
+    /// Start monitoring identifiers, e.g. the processes a test harness spawned
+    Add {
+        /// Identifiers to monitor, e.g. firefox or pid:1234
+        #[arg(required = true)]
+        identifiers: Vec<String>,
+    },
+    /// Stop monitoring identifiers and drop their data
+    Remove {
+        /// Identifiers as monitored, e.g. firefox or pid:1234
+        #[arg(required = true)]
+        identifiers: Vec<String>,
+    },
+    /// Drop the samples of an identifier, or of every monitored one, and keep sampling
+    Clear {
+        /// Identifier as monitored, e.g. firefox or pid:1234
+        identifier: Option<String>,
+    },
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Command {
+    fn request(&self) -> String {
+        match self {
+            Command::Status { identifier } => {
+                format!("status {}", identifier.as_deref().unwrap_or_default())
+            }
+            Command::Export {
+                identifier,
+                since: Some(seconds),
+            } => format!("export {identifier} {seconds}"),
+            Command::Export {
+                identifier,
+                since: None,
+            } => format!("export {identifier}"),
+            // One identifier per line, as names may hold spaces
+            Command::Add { identifiers } => format!("add\n{}", identifiers.join("\n")),
+            Command::Remove { identifiers } => format!("remove\n{}", identifiers.join("\n")),
+            Command::Clear { identifier } => {
+                format!("clear {}", identifier.as_deref().unwrap_or_default())
+            }
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn parse_since(value: &str) -> Result<u64, String> {
+    tvis::control::parse_duration(value)
+        .ok_or_else(|| format!("expected e.g. 30s, 10m or 2h, not '{value}'"))
+}
+
+/// Starts the WebSocket stream on `addr`, exiting when it can't listen
+#[cfg(not(target_arch = "wasm32"))]
+fn start_stream(addr: &str, metrics: tvis::metrics::Metrics) {
+    if let Err(err) = tvis::stream::spawn(addr, metrics) {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
+}
+
+/// PIDs whose name or command line matches one of `patterns`
+#[cfg(not(target_arch = "wasm32"))]
+fn pgrep_identifiers(patterns: &[regex::Regex]) -> Vec<tvis::metrics::process::ProcessIdentifier> {
+    use tvis::metrics::process::{ProcessIdentifier, ProcessMonitor};
+
+    if patterns.is_empty() {
+        return Vec::new();
+    }
+    let pids = ProcessMonitor::default()
+        .find_matching(|text| patterns.iter().any(|regex| regex.is_match(text)));
+    if pids.is_empty() {
+        eprintln!("No process matches the --pgrep pattern");
+    }
+    pids.into_iter().map(ProcessIdentifier::Pid).collect()
+}
+
 // When compiling to web using trunk:
 #[cfg(target_arch = "wasm32")]
 fn main() {
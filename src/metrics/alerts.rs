@@ -0,0 +1,298 @@
+//! Alert rules the worker checks after every tick, whether or not a UI is
+//! drawing, and the commands they run when they fire.
+
+use super::process::{MetricType, ProcessData, ProcessIdentifier};
+use super::SwapUsage;
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// Fired alerts kept in [`super::Snapshot::alerts`] for the handles to pick up
+pub const ALERT_BACKLOG: usize = 100;
+
+#[derive(
+    serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl Severity {
+    pub const ALL: [Severity; 3] = [Severity::Info, Severity::Warning, Severity::Critical];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Severity::Info => "Info",
+            Severity::Warning => "Warning",
+            Severity::Critical => "Critical",
+        }
+    }
+}
+
+/// Fires when the aggregate value of `metric` for `identifier` rises above `threshold`,
+/// and, with `min_swap_percent`, only while the system swaps
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+pub struct AlertRule {
+    pub identifier: ProcessIdentifier,
+    pub metric: MetricType,
+    /// Percent for CPU and GPU, bytes for memory
+    pub threshold: f64,
+    pub severity: Severity,
+    pub enabled: bool,
+    /// Shell command run when the rule fires, with `TVIS_*` variables describing the alert
+    #[serde(default)]
+    pub command: String,
+    /// Only fire while more than this share of the system swap is in use, so
+    /// a large tree alerts when it pushes the machine into swap, not for its size
+    #[serde(default)]
+    pub min_swap_percent: Option<f32>,
+}
+
+/// Condition of a rule: editing its severity, command or swap condition
+/// keeps a firing rule firing instead of firing it again
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertKey {
+    pub identifier: ProcessIdentifier,
+    pub metric: MetricType,
+    pub threshold: f64,
+}
+
+impl AlertRule {
+    pub fn new(identifier: ProcessIdentifier, metric: MetricType, threshold: f64) -> Self {
+        Self {
+            identifier,
+            metric,
+            threshold,
+            severity: Severity::Warning,
+            enabled: true,
+            command: String::new(),
+            min_swap_percent: None,
+        }
+    }
+
+    pub fn key(&self) -> AlertKey {
+        AlertKey {
+            identifier: self.identifier.clone(),
+            metric: self.metric,
+            threshold: self.threshold,
+        }
+    }
+
+    /// Whether the swap condition holds, always without one. Some swap
+    /// must be in use even at 0%.
+    pub fn swap_condition_met(&self, swap: &SwapUsage) -> bool {
+        self.min_swap_percent
+            .is_none_or(|min| swap.used > 0 && swap.percent() > min)
+    }
+
+    /// Current value of the rule's metric for the tree of `process_data`
+    fn value(&self, process_data: &ProcessData) -> f64 {
+        let stats = &process_data.genereal.stats;
+        match self.metric {
+            MetricType::Cpu => stats.current_cpu as f64,
+            MetricType::Memory => stats.current_memory as f64,
+            MetricType::Gpu => stats.current_gpu as f64,
+            MetricType::Processes => stats.process_count as f64,
+        }
+    }
+}
+
+/// Rule that went above its threshold on `tick`
+#[derive(Debug, Clone)]
+pub struct Alert {
+    pub identifier: ProcessIdentifier,
+    pub severity: Severity,
+    pub metric: MetricType,
+    /// Value that crossed the threshold, per core for CPU like `current_cpu`
+    pub value: f64,
+    pub threshold: f64,
+    /// Share of the swap in use, for rules with a swap condition
+    pub swap_percent: Option<f32>,
+    pub fired_at: Instant,
+    pub tick: u64,
+    /// Its command was held back by the command quiet hours
+    pub held: bool,
+}
+
+impl Alert {
+    /// English one-liner handed to the command as `TVIS_MESSAGE`
+    fn message(&self) -> String {
+        let mut message = match self.metric {
+            MetricType::Cpu => format!("CPU {:.1}% > {:.1}%", self.value, self.threshold),
+            MetricType::Memory => format!(
+                "Memory {:.1} MB > {:.1} MB",
+                self.value / (1024.0 * 1024.0),
+                self.threshold / (1024.0 * 1024.0)
+            ),
+            MetricType::Gpu => format!("GPU {:.1}% > {:.1}%", self.value, self.threshold),
+            MetricType::Processes => {
+                format!("Processes {:.0} > {:.0}", self.value, self.threshold)
+            }
+        };
+        if let Some(percent) = self.swap_percent {
+            message.push_str(&format!(" while swap is {percent:.0}% used"));
+        }
+        message
+    }
+}
+
+/// Daily window in local time during which an alert channel stays silent.
+/// A window ending before it starts wraps past midnight.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct QuietHours {
+    pub enabled: bool,
+    /// Minutes after local midnight
+    pub start: u32,
+    pub end: u32,
+}
+
+impl Default for QuietHours {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start: 22 * 60,
+            end: 7 * 60,
+        }
+    }
+}
+
+impl QuietHours {
+    pub fn contains(&self, minute: u32) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        if self.start <= self.end {
+            (self.start..self.end).contains(&minute)
+        } else {
+            minute >= self.start || minute < self.end
+        }
+    }
+
+    pub fn contains_now(&self) -> bool {
+        local_minute().is_some_and(|minute| self.contains(minute))
+    }
+}
+
+/// Minutes since local midnight
+#[cfg(not(target_arch = "wasm32"))]
+fn local_minute() -> Option<u32> {
+    use chrono::Timelike;
+    let now = chrono::Local::now();
+    Some(now.hour() * 60 + now.minute())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_minute() -> Option<u32> {
+    None
+}
+
+/// Checks `rules` against the processes of a tick. `firing` holds the rules
+/// above their threshold on the previous check, so a rule fires once when it
+/// crosses, not on every tick it stays above.
+pub(super) fn check(
+    rules: &[AlertRule],
+    firing: &mut Vec<AlertKey>,
+    processes: &HashMap<ProcessIdentifier, ProcessData>,
+    swap: &SwapUsage,
+    command_quiet_hours: &QuietHours,
+    tick: u64,
+) -> Vec<Alert> {
+    let mut fired = Vec::new();
+    let mut still_firing = Vec::new();
+    for rule in rules.iter().filter(|rule| rule.enabled) {
+        let Some(process_data) = processes.get(&rule.identifier) else {
+            continue;
+        };
+        let value = rule.value(process_data);
+        if value <= rule.threshold || !rule.swap_condition_met(swap) {
+            continue;
+        }
+        let key = rule.key();
+        if !firing.contains(&key) {
+            let mut alert = Alert {
+                identifier: rule.identifier.clone(),
+                severity: rule.severity,
+                metric: rule.metric,
+                value,
+                threshold: rule.threshold,
+                swap_percent: rule.min_swap_percent.map(|_| swap.percent()),
+                fired_at: Instant::now(),
+                tick,
+                held: false,
+            };
+            if !rule.command.trim().is_empty() {
+                if command_quiet_hours.contains_now() {
+                    alert.held = true;
+                } else {
+                    let env = command_env(rule, process_data, swap, &alert.message());
+                    run_command(&rule.command, env);
+                }
+            }
+            fired.push(alert);
+        }
+        still_firing.push(key);
+    }
+    *firing = still_firing;
+    fired
+}
+
+/// Environment of an alert command. `TVIS_PID` is the process contributing
+/// most to the alerting metric, `TVIS_PIDS` lists the whole tree.
+fn command_env(
+    rule: &AlertRule,
+    process_data: &ProcessData,
+    swap: &SwapUsage,
+    message: &str,
+) -> Vec<(&'static str, String)> {
+    let processes = process_data.processes_stats.iter().filter(|p| !p.is_thread);
+    let heaviest = processes.clone().max_by(|a, b| match rule.metric {
+        MetricType::Cpu | MetricType::Processes => a.current_cpu.total_cmp(&b.current_cpu),
+        MetricType::Memory => a.current_memory.cmp(&b.current_memory),
+        MetricType::Gpu => a.gpu.utilization.total_cmp(&b.gpu.utilization),
+    });
+    let pids: Vec<String> = processes.map(|p| p.pid.to_string()).collect();
+    let stats = &process_data.genereal.stats;
+    vec![
+        ("TVIS_IDENTIFIER", rule.identifier.to_string()),
+        ("TVIS_SEVERITY", rule.severity.label().to_string()),
+        ("TVIS_MESSAGE", message.to_string()),
+        (
+            "TVIS_PID",
+            heaviest.map(|p| p.pid.to_string()).unwrap_or_default(),
+        ),
+        ("TVIS_PIDS", pids.join(" ")),
+        ("TVIS_CPU", format!("{:.1}", stats.current_cpu)),
+        ("TVIS_MEMORY", stats.current_memory.to_string()),
+        ("TVIS_GPU", format!("{:.1}", stats.current_gpu)),
+        ("TVIS_SWAP_USED", swap.used.to_string()),
+    ]
+}
+
+/// Runs an alert's command through the platform shell on a thread of its own,
+/// so a slow command doesn't hold up sampling, with `env` added to its environment
+#[cfg(not(target_arch = "wasm32"))]
+fn run_command(command: &str, env: Vec<(&'static str, String)>) {
+    let command = command.to_string();
+    std::thread::spawn(move || {
+        let mut shell = if cfg!(target_os = "windows") {
+            let mut shell = std::process::Command::new("cmd");
+            shell.arg("/C");
+            shell
+        } else {
+            let mut shell = std::process::Command::new("sh");
+            shell.arg("-c");
+            shell
+        };
+        match shell.arg(&command).envs(env).status() {
+            Ok(status) if status.success() => {}
+            Ok(status) => log::warn!("Alert command `{command}` exited with {status}"),
+            Err(err) => log::warn!("Failed to run alert command `{command}`: {err}"),
+        }
+    });
+}
+
+#[cfg(target_arch = "wasm32")]
+fn run_command(_command: &str, _env: Vec<(&'static str, String)>) {
+    log::warn!("Alert commands are not supported on the web");
+}
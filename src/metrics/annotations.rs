@@ -0,0 +1,94 @@
+//! Labelled moments ("deployed v2.3", "started load test") dropped from the UI,
+//! drawn on the plots and written next to the samples in the exports.
+
+use super::process::ProcessIdentifier;
+use std::time::{Duration, SystemTime};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    pub label: String,
+    /// `Metrics::tick` at the moment the annotation was taken
+    pub tick: u64,
+    /// Process the annotation belongs to, every process when `None`
+    pub identifier: Option<ProcessIdentifier>,
+    pub created_at: SystemTime,
+}
+
+impl Annotation {
+    pub fn applies_to(&self, identifier: &ProcessIdentifier) -> bool {
+        self.identifier
+            .as_ref()
+            .is_none_or(|annotated| annotated == identifier)
+    }
+}
+
+/// Labels of the annotations of `identifier` taken about `samples_ago` samples
+/// of `interval` before now, joined with `; `
+pub fn labels_at(
+    annotations: &[Annotation],
+    identifier: &ProcessIdentifier,
+    interval: Duration,
+    samples_ago: usize,
+) -> String {
+    annotations
+        .iter()
+        .filter(|annotation| {
+            let elapsed = annotation.created_at.elapsed().unwrap_or_default();
+            annotation.applies_to(identifier)
+                && (elapsed.as_secs_f64() / interval.as_secs_f64()).round() as usize == samples_ago
+        })
+        .map(|annotation| annotation.label.as_str())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Labels of the annotations of `identifier` taken within half an `interval`
+/// of a sample taken at `taken_at`, joined with `; `
+pub fn labels_near(
+    annotations: &[Annotation],
+    identifier: &ProcessIdentifier,
+    interval: Duration,
+    taken_at: SystemTime,
+) -> String {
+    annotations
+        .iter()
+        .filter(|annotation| {
+            let distance = taken_at
+                .duration_since(annotation.created_at)
+                .or_else(|_| annotation.created_at.duration_since(taken_at))
+                .unwrap_or_default();
+            annotation.applies_to(identifier)
+                && distance.as_secs_f64() * 2.0 < interval.as_secs_f64()
+        })
+        .map(|annotation| annotation.label.as_str())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn annotations_are_matched_to_the_sample_they_were_taken_at() {
+        let firefox = ProcessIdentifier::from("firefox");
+        let annotation =
+            |label: &str, identifier: Option<&ProcessIdentifier>, secs_ago: u64| Annotation {
+                label: label.to_string(),
+                tick: 0,
+                identifier: identifier.cloned(),
+                created_at: SystemTime::now() - Duration::from_secs(secs_ago),
+            };
+        let annotations = [
+            annotation("deployed v2.3", Some(&firefox), 10),
+            annotation("load test", None, 10),
+            annotation("other", Some(&ProcessIdentifier::from("nginx")), 10),
+        ];
+        let interval = Duration::from_secs(2);
+        assert_eq!(
+            labels_at(&annotations, &firefox, interval, 5),
+            "deployed v2.3; load test"
+        );
+        assert_eq!(labels_at(&annotations, &firefox, interval, 4), "");
+    }
+}
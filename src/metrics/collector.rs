@@ -0,0 +1,193 @@
+//! Embeddable entry point to the collection engine, for programs that want the
+//! process-tree aggregation of tvis without its window.
+//!
+//! Building with `default-features = false` leaves out egui and eframe; this
+//! module and the rest of [`crate::metrics`] stay available. To feed a Grafana
+//! dashboard, pass a clone of [`Collector::metrics`] to [`super::influx::spawn`].
+//!
+//! ```no_run
+//! use tvis::metrics::collector::{Collector, CollectorConfig};
+//!
+//! let mut collector = Collector::new(CollectorConfig::default());
+//! collector.add("nginx".into());
+//! for snapshot in collector.subscribe() {
+//!     for (identifier, sample) in &snapshot.samples {
+//!         println!(
+//!             "{}: {:.1}% CPU, {} bytes over {} processes",
+//!             identifier.to_string(),
+//!             sample.stats.current_cpu,
+//!             sample.stats.current_memory,
+//!             sample.stats.process_count
+//!         );
+//!     }
+//! }
+//! ```
+
+use super::process::{
+    Availability, ExcludeRule, ProcessGeneralStats, ProcessIdentifier, ProcessInfo, WatchRule,
+};
+use super::Metrics;
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// Longest wait for the worker's next snapshot before polling anyway
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Debug, Clone)]
+pub struct CollectorConfig {
+    pub update_interval: Duration,
+    /// Samples kept per process, see [`super::Controls::history_len`]
+    pub history_len: usize,
+    pub exclude_rules: Vec<ExcludeRule>,
+    /// Processes added as they appear, on top of [`Collector::add`]
+    pub watch_rules: Vec<WatchRule>,
+}
+
+impl Default for CollectorConfig {
+    fn default() -> Self {
+        Self {
+            update_interval: Duration::from_secs(1),
+            history_len: 60,
+            exclude_rules: Vec::new(),
+            watch_rules: Vec::new(),
+        }
+    }
+}
+
+/// Aggregate and per-process figures of one identifier on one tick
+#[derive(Debug, Clone)]
+pub struct Sample {
+    /// Totals over the tree, with the averages and peaks of the retained history
+    pub stats: ProcessGeneralStats,
+    /// Every process and thread of the tree
+    pub processes: Vec<ProcessInfo>,
+    pub availability: Option<Availability>,
+}
+
+/// Identifiers sampled on one worker tick
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    /// See [`super::Snapshot::tick`]
+    pub tick: u64,
+    pub taken_at: SystemTime,
+    /// Only the identifiers sampled on this tick, ones with a longer interval
+    /// override or paused ones are left out
+    pub samples: HashMap<ProcessIdentifier, Sample>,
+}
+
+/// Samples the added identifiers on a worker thread and sends a [`Snapshot`]
+/// to every subscriber after each tick. The worker stops once the collector
+/// and the handles cloned from [`Collector::metrics`] are dropped.
+pub struct Collector {
+    metrics: Metrics,
+    subscribers: Arc<Mutex<Vec<Sender<Snapshot>>>>,
+}
+
+impl Collector {
+    /// Starts the worker, blocking for a few seconds while it warms up
+    pub fn new(config: CollectorConfig) -> Self {
+        let mut metrics = Metrics::new(
+            config.history_len,
+            config.update_interval.as_millis() as usize,
+        );
+        metrics.set_exclude_rules(config.exclude_rules);
+        metrics.set_watch_rules(config.watch_rules);
+        let subscribers: Arc<Mutex<Vec<Sender<Snapshot>>>> = Arc::default();
+
+        let notified = Arc::downgrade(&subscribers);
+        poll_snapshots(metrics.clone(), move |_, snapshot| {
+            // Ends once the collector is dropped
+            let Some(subscribers) = notified.upgrade() else {
+                return false;
+            };
+            if let Some(snapshot) = snapshot {
+                subscribers
+                    .lock()
+                    .unwrap()
+                    .retain(|subscriber| subscriber.send(snapshot.clone()).is_ok());
+            }
+            true
+        });
+        Self {
+            metrics,
+            subscribers,
+        }
+    }
+
+    /// Starts sampling `identifier` and its children
+    pub fn add(&mut self, identifier: ProcessIdentifier) {
+        self.metrics.add_selected_process(identifier);
+    }
+
+    /// Stops sampling `identifier` and drops its history
+    pub fn remove(&mut self, identifier: &ProcessIdentifier) {
+        self.metrics.remove_selected_process(identifier);
+    }
+
+    /// Snapshots of the ticks from now on; dropping the receiver unsubscribes
+    pub fn subscribe(&self) -> Receiver<Snapshot> {
+        let (sender, receiver) = mpsc::channel();
+        self.subscribers.lock().unwrap().push(sender);
+        receiver
+    }
+
+    /// The handle on the worker, for what the collector doesn't expose:
+    /// histories, events, limits, interval overrides. See [`Metrics::update`].
+    pub fn metrics(&mut self) -> &mut Metrics {
+        &mut self.metrics
+    }
+}
+
+/// Waits for the worker's snapshots on a new thread, calling `on_poll` with the
+/// [`Snapshot`] of the identifiers sampled on each tick, or with `None` when
+/// none came within [`POLL_INTERVAL`]. The thread ends once `on_poll` returns
+/// false.
+pub(crate) fn poll_snapshots(
+    mut metrics: Metrics,
+    mut on_poll: impl FnMut(&mut Metrics, Option<Snapshot>) -> bool + Send + 'static,
+) {
+    thread::spawn(move || {
+        // Samples already sent per identifier, so ones with their own interval
+        // are only sent when they were sampled
+        let mut sent: HashMap<ProcessIdentifier, u64> = HashMap::new();
+        loop {
+            let snapshot = metrics
+                .wait(POLL_INTERVAL)
+                .then(|| snapshot(&metrics, &mut sent));
+            if !on_poll(&mut metrics, snapshot) {
+                break;
+            }
+        }
+    });
+}
+
+/// Samples of the identifiers sampled since the last call, tracked in `sent`
+/// by their sample count
+fn snapshot(metrics: &Metrics, sent: &mut HashMap<ProcessIdentifier, u64>) -> Snapshot {
+    let monitored = metrics.get_monitored_processes();
+    sent.retain(|identifier, _| monitored.contains(identifier));
+    let samples = monitored
+        .iter()
+        .filter_map(|identifier| {
+            let data = metrics.get_process_data(identifier)?;
+            let samples = data.session.samples;
+            if sent.insert(identifier.clone(), samples) == Some(samples) {
+                return None;
+            }
+            let sample = Sample {
+                stats: data.genereal.stats.clone(),
+                processes: data.processes_stats.clone(),
+                availability: metrics.availability(identifier).cloned(),
+            };
+            Some((identifier.clone(), sample))
+        })
+        .collect();
+    Snapshot {
+        tick: metrics.tick,
+        taken_at: SystemTime::now(),
+        samples,
+    }
+}
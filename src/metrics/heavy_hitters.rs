@@ -0,0 +1,117 @@
+use super::process::ProcessMonitor;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How many processes are kept per sample
+pub const TOP_N: usize = 10;
+/// How long samples are retained
+pub const RETENTION: Duration = Duration::from_secs(15 * 60);
+
+/// Top CPU and memory consumers system-wide at one tick
+#[derive(Debug, Clone)]
+pub struct HeavyHitterSample {
+    pub at: Instant,
+    pub cpu: Vec<(String, f32)>,
+    pub memory: Vec<(String, usize)>,
+}
+
+impl HeavyHitterSample {
+    /// Collects the top consumers from the latest refresh, ignoring threads
+    pub fn collect(monitor: &ProcessMonitor) -> Self {
+        let processes: Vec<_> = monitor
+            .system
+            .processes()
+            .values()
+            .filter(|process| process.thread_kind().is_none())
+            .collect();
+
+        let mut cpu: Vec<(String, f32)> = processes
+            .iter()
+            .map(|p| (p.name().to_string_lossy().into_owned(), p.cpu_usage()))
+            .collect();
+        cpu.sort_by(|a, b| b.1.total_cmp(&a.1));
+        cpu.truncate(TOP_N);
+
+        let mut memory: Vec<(String, usize)> = processes
+            .iter()
+            .map(|p| (p.name().to_string_lossy().into_owned(), p.memory() as usize))
+            .collect();
+        memory.sort_by(|a, b| b.1.cmp(&a.1));
+        memory.truncate(TOP_N);
+
+        Self {
+            at: Instant::now(),
+            cpu,
+            memory,
+        }
+    }
+}
+
+/// Highest value a process name reached within a lookback window
+#[derive(Debug, Clone)]
+pub struct HeavyHitter<T> {
+    pub name: String,
+    pub peak: T,
+    pub at: Instant,
+}
+
+/// Rolling record of the heaviest processes, including unmonitored ones. Samples
+/// are shared, so the copy published with every snapshot doesn't copy them.
+#[derive(Debug, Clone, Default)]
+pub struct HeavyHitters {
+    samples: VecDeque<Arc<HeavyHitterSample>>,
+}
+
+impl HeavyHitters {
+    pub fn record(&mut self, sample: HeavyHitterSample) {
+        while self
+            .samples
+            .front()
+            .is_some_and(|oldest| oldest.at.elapsed() > RETENTION)
+        {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(Arc::new(sample));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Peak CPU per process name over the last `window`, highest first
+    pub fn cpu_peaks(&self, window: Duration) -> Vec<HeavyHitter<f32>> {
+        self.peaks(window, |sample| &sample.cpu, |a, b| a.total_cmp(b))
+    }
+
+    /// Peak memory per process name over the last `window`, highest first
+    pub fn memory_peaks(&self, window: Duration) -> Vec<HeavyHitter<usize>> {
+        self.peaks(window, |sample| &sample.memory, |a, b| a.cmp(b))
+    }
+
+    fn peaks<T: Copy>(
+        &self,
+        window: Duration,
+        values: impl Fn(&HeavyHitterSample) -> &Vec<(String, T)>,
+        cmp: impl Fn(&T, &T) -> std::cmp::Ordering,
+    ) -> Vec<HeavyHitter<T>> {
+        let mut peaks: HashMap<&str, HeavyHitter<T>> = HashMap::new();
+        for sample in self.samples.iter().filter(|s| s.at.elapsed() <= window) {
+            for (name, value) in values(sample) {
+                let entry = peaks.entry(name).or_insert_with(|| HeavyHitter {
+                    name: name.clone(),
+                    peak: *value,
+                    at: sample.at,
+                });
+                if cmp(value, &entry.peak).is_gt() {
+                    entry.peak = *value;
+                    entry.at = sample.at;
+                }
+            }
+        }
+        let mut peaks: Vec<_> = peaks.into_values().collect();
+        peaks.sort_by(|a, b| cmp(&b.peak, &a.peak));
+        peaks.truncate(TOP_N);
+        peaks
+    }
+}
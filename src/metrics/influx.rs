@@ -0,0 +1,257 @@
+//! Push of the samples in InfluxDB line protocol to an HTTP endpoint, so tvis
+//! can feed ad-hoc Grafana dashboards. Works with `/api/v2/write` of InfluxDB 2,
+//! `/write` of InfluxDB 1.x and the `/api/live/push/<stream>` of Grafana Live.
+//!
+//! Every sample of a monitored identifier becomes one line:
+//!
+//! ```text
+//! tvis,identifier=firefox cpu=12.5,memory=104857600i,gpu=0,processes=3i,threads=87i 1700000000000000000
+//! ```
+//!
+//! Lines are sent in batches; a failed batch is kept and retried with a growing
+//! delay, the oldest lines being dropped past [`MAX_BUFFERED_LINES`]. Only plain
+//! `http://` is supported, put a local proxy in front of a TLS endpoint.
+
+use super::collector::{poll_snapshots, Sample};
+use super::process::ProcessIdentifier;
+use super::Metrics;
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+/// Lines kept while the endpoint is unreachable
+pub const MAX_BUFFERED_LINES: usize = 100_000;
+
+/// Longest wait between two attempts at a failing endpoint
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct InfluxPush {
+    pub enabled: bool,
+    /// e.g. `http://localhost:8086/api/v2/write?org=me&bucket=tvis`
+    pub url: String,
+    /// Value of the `Authorization` header, `Token …` for InfluxDB or
+    /// `Bearer …` for Grafana; not sent when empty
+    pub authorization: String,
+    pub measurement: String,
+    /// Also send one line per process of each tree, under `<measurement>_process`
+    pub per_process: bool,
+    /// Lines sent in one request
+    pub batch_lines: usize,
+    /// Longest a sample waits before its batch is sent
+    pub flush_secs: u64,
+}
+
+impl Default for InfluxPush {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: String::new(),
+            authorization: String::new(),
+            measurement: "tvis".to_string(),
+            per_process: false,
+            batch_lines: 500,
+            flush_secs: 10,
+        }
+    }
+}
+
+/// Appends the lines of one sample of `identifier` to `out`
+pub fn write_lines(
+    out: &mut String,
+    config: &InfluxPush,
+    identifier: &ProcessIdentifier,
+    sample: &Sample,
+    unix_ns: u128,
+) {
+    let measurement = escape(config.measurement.trim(), &[',', ' ']);
+    let identifier = escape_tag(&identifier.to_string());
+    let stats = &sample.stats;
+    let _ = writeln!(
+        out,
+        "{measurement},identifier={identifier} cpu={},memory={}i,gpu={},processes={}i,threads={}i {unix_ns}",
+        stats.current_cpu, stats.current_memory, stats.current_gpu, stats.process_count, stats.thread_count
+    );
+    if !config.per_process {
+        return;
+    }
+    for process in sample.processes.iter().filter(|process| !process.is_thread) {
+        let _ = writeln!(
+            out,
+            "{measurement}_process,identifier={identifier},pid={},name={} cpu={},memory={}i {unix_ns}",
+            process.pid,
+            escape_tag(&process.name),
+            process.current_cpu,
+            process.current_memory
+        );
+    }
+}
+
+/// `text` as a tag key or value, which can't be empty
+fn escape_tag(text: &str) -> String {
+    if text.is_empty() {
+        return "unknown".to_string();
+    }
+    escape(text, &[',', '=', ' '])
+}
+
+fn escape(text: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\n' | '\r' => escaped.push(' '),
+            c if special.contains(&c) || c == '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Sends `body` to `url` in one HTTP/1.1 POST, failing on anything but a 2xx reply
+pub fn post(url: &str, authorization: &str, body: &str) -> Result<(), String> {
+    let rest = match url.trim().strip_prefix("http://") {
+        Some(rest) => rest,
+        None if url.trim().starts_with("https://") => {
+            return Err("https is not supported, use http:// or a local proxy".to_string())
+        }
+        None => return Err(format!("expected an http:// URL, not '{url}'")),
+    };
+    let (host, path) = match rest.find('/') {
+        Some(slash) => rest.split_at(slash),
+        None => (rest, "/"),
+    };
+    let addr = if host
+        .rsplit_once(':')
+        .is_some_and(|(_, port)| port.parse::<u16>().is_ok())
+    {
+        host.to_string()
+    } else {
+        format!("{host}:80")
+    };
+    let socket_addr = addr
+        .to_socket_addrs()
+        .map_err(|err| format!("Failed to resolve {host}: {err}"))?
+        .next()
+        .ok_or_else(|| format!("Failed to resolve {host}"))?;
+    let mut stream = TcpStream::connect_timeout(&socket_addr, TIMEOUT)
+        .map_err(|err| format!("Failed to connect to {host}: {err}"))?;
+    let _ = stream.set_read_timeout(Some(TIMEOUT));
+    let _ = stream.set_write_timeout(Some(TIMEOUT));
+
+    let mut request = format!(
+        "POST {path} HTTP/1.1\r\nHost: {host}\r\nContent-Type: text/plain; charset=utf-8\r\n\
+         Content-Length: {}\r\nConnection: close\r\n",
+        body.len()
+    );
+    if !authorization.trim().is_empty() {
+        let _ = write!(request, "Authorization: {}\r\n", authorization.trim());
+    }
+    request.push_str("\r\n");
+    stream
+        .write_all(request.as_bytes())
+        .and_then(|()| stream.write_all(body.as_bytes()))
+        .map_err(|err| format!("Failed to send to {host}: {err}"))?;
+
+    let mut reader = BufReader::new(stream);
+    let mut status_line = String::new();
+    reader
+        .read_line(&mut status_line)
+        .map_err(|err| format!("No reply from {host}: {err}"))?;
+    let status = status_line.split_whitespace().nth(1).unwrap_or_default();
+    if status.starts_with('2') {
+        return Ok(());
+    }
+    // The body usually says what was wrong with the lines or the token
+    let mut reply = String::new();
+    let _ = reader.take(4096).read_to_string(&mut reply);
+    let detail = reply.split("\r\n\r\n").nth(1).unwrap_or_default().trim();
+    Err(format!(
+        "{host} replied {}{}{detail}",
+        status_line.trim(),
+        if detail.is_empty() { "" } else { ": " }
+    ))
+}
+
+/// Pushes the samples of `metrics` as configured in [`super::Controls::influx_push`],
+/// reporting the outcome with [`Metrics::set_influx_status`]. Idles while disabled.
+pub fn spawn(metrics: Metrics) {
+    let mut buffered: VecDeque<String> = VecDeque::new();
+    let mut dropped = 0usize;
+    let mut last_flush = Instant::now();
+    let mut retry_at: Option<Instant> = None;
+    let mut retry_delay = Duration::from_secs(1);
+    poll_snapshots(metrics, move |metrics, snapshot| {
+        let config = metrics.controls().influx_push.clone();
+        if !config.enabled || config.url.trim().is_empty() {
+            buffered.clear();
+            return true;
+        }
+        if let Some(snapshot) = snapshot {
+            let unix_ns = snapshot
+                .taken_at
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos();
+            for (identifier, sample) in &snapshot.samples {
+                if sample.processes.is_empty() {
+                    continue;
+                }
+                let mut lines = String::new();
+                write_lines(&mut lines, &config, identifier, sample, unix_ns);
+                buffered.extend(lines.lines().map(str::to_string));
+            }
+            while buffered.len() > MAX_BUFFERED_LINES {
+                buffered.pop_front();
+                dropped += 1;
+            }
+        }
+
+        let batch_lines = config.batch_lines.max(1);
+        let due = buffered.len() >= batch_lines
+            || last_flush.elapsed() >= Duration::from_secs(config.flush_secs);
+        if buffered.is_empty() || !due || retry_at.is_some_and(|at| Instant::now() < at) {
+            return true;
+        }
+        last_flush = Instant::now();
+        let count = batch_lines.min(buffered.len());
+        let mut body = String::new();
+        for line in buffered.range(..count) {
+            body.push_str(line);
+            body.push('\n');
+        }
+        let status = match post(&config.url, &config.authorization, &body) {
+            Ok(()) => {
+                buffered.drain(..count);
+                retry_at = None;
+                retry_delay = Duration::from_secs(1);
+                let mut status = format!("Sent {count} lines");
+                if dropped > 0 {
+                    let _ = write!(status, ", {dropped} dropped while unreachable");
+                    dropped = 0;
+                }
+                Ok(status)
+            }
+            Err(err) => {
+                log::warn!("InfluxDB push failed: {err}");
+                retry_at = Some(Instant::now() + retry_delay);
+                let err = format!(
+                    "{err}; retrying in {}s, {} lines waiting",
+                    retry_delay.as_secs(),
+                    buffered.len()
+                );
+                retry_delay = (retry_delay * 2).min(MAX_RETRY_DELAY);
+                Err(err)
+            }
+        };
+        metrics.set_influx_status(status);
+        true
+    });
+}
@@ -1,134 +1,1100 @@
 use log::info;
+pub mod alerts;
+pub mod annotations;
+pub mod collector;
+pub mod heavy_hitters;
+pub mod influx;
 pub mod process;
+pub mod recorder;
+pub mod thermal;
+use alerts::{Alert, AlertKey, AlertRule, QuietHours, ALERT_BACKLOG};
+use annotations::Annotation;
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender, TrySendError};
+use heavy_hitters::{HeavyHitterSample, HeavyHitters};
+use influx::InfluxPush;
+use process::limits::{self, ResourceLimit};
 use process::{
-    ProcessData, ProcessGeneral, ProcessGeneralStats, ProcessHistory, ProcessIdentifier,
-    ProcessInfo, ProcessMonitor,
+    Availability, Crash, ExcludeRule, GpuMonitor, LeakSuspect, MemoryProjection, MissedCrashes,
+    NameCache, PriorityClass, ProcessData, ProcessEvent, ProcessEventKind, ProcessGeneral,
+    ProcessGeneralStats, ProcessHistory, ProcessIdentifier, ProcessInfo, ProcessMonitor,
+    SchedCounters, WatchRule, SAFE_MODE_PROCESS_COUNT,
 };
+use recorder::{FlightLog, FlightRecorder, FlightSample};
 use std::collections::HashMap;
-use std::sync::{Arc, LazyLock, RwLock};
+use std::ops::Deref;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock};
 use std::thread;
-use std::time::{Duration, Instant};
-use sysinfo::{Pid, System};
+use std::time::{Duration, Instant, SystemTime};
+use sysinfo::{Components, Pid, ProcessStatus};
+use thermal::{package_temperature, CpuThermals};
 
 pub static GENERAL_STATS_PID: LazyLock<Pid> = LazyLock::new(|| Pid::from_u32(0));
 
+/// In safe mode, ticks between full scans that pick up new processes
+const SAFE_MODE_RESCAN_TICKS: u64 = 10;
+
+/// Samples faster while a monitored tree is busy, backing off to the
+/// configured interval once every tree is below the CPU threshold
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct AdaptiveSampling {
+    pub enabled: bool,
+    pub fast_interval_ms: u64,
+    /// CPU % of a monitored tree that switches to the fast interval
+    pub cpu_threshold: f32,
+}
+
+impl Default for AdaptiveSampling {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            fast_interval_ms: 250,
+            cpu_threshold: 10.0,
+        }
+    }
+}
+
+/// Fits the memory growth of each tree over a trailing window and projects when
+/// it reaches a limit, by default the system running out of memory
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct OomProjection {
+    pub enabled: bool,
+    pub window_secs: u64,
+    /// Memory to project to, the system's available memory when `None`
+    pub limit_bytes: Option<usize>,
+}
+
+impl Default for OomProjection {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window_secs: 300,
+            limit_bytes: None,
+        }
+    }
+}
+
+/// Flags trees whose memory kept growing faster than a rate for a whole
+/// window, a likely leak rather than a warm-up or a burst
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct LeakDetection {
+    pub enabled: bool,
+    /// Growth of the fitted trend above which memory counts as leaking
+    pub mb_per_min: f64,
+    /// Time the growth must last, also the window the trend is fitted over
+    pub duration_secs: u64,
+}
+
+impl Default for LeakDetection {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            mb_per_min: 5.0,
+            duration_secs: 600,
+        }
+    }
+}
+
+impl LeakDetection {
+    /// Suspected leak of the tree whose aggregate history is `history`, carrying
+    /// over when it started from `previous`. `None` until the history covers
+    /// the whole duration.
+    pub fn check(
+        &self,
+        history: &ProcessHistory,
+        interval: Duration,
+        previous: Option<LeakSuspect>,
+        now: Instant,
+    ) -> Option<LeakSuspect> {
+        if !self.enabled {
+            return None;
+        }
+        // Points spanning the duration, one more than the intervals between them
+        let samples =
+            (self.duration_secs as f64 / interval.as_secs_f64().max(f64::EPSILON)) as usize + 1;
+        let recorded = history
+            .get_memory_history(&GENERAL_STATS_PID)
+            .map_or(0, |history| history.len());
+        if recorded < samples.max(3) {
+            return None;
+        }
+        let bytes_per_second = history.memory_trend(&GENERAL_STATS_PID, samples, interval)?;
+        if bytes_per_second * 60.0 < self.mb_per_min * 1024.0 * 1024.0 {
+            return None;
+        }
+        let since = previous.map_or_else(
+            || {
+                now.checked_sub(Duration::from_secs(self.duration_secs))
+                    .unwrap_or(now)
+            },
+            |previous| previous.since,
+        );
+        Some(LeakSuspect {
+            bytes_per_second,
+            since,
+        })
+    }
+}
+
+impl OomProjection {
+    fn project(
+        &self,
+        history: &ProcessHistory,
+        current: usize,
+        interval: Duration,
+        available_memory: usize,
+    ) -> Option<MemoryProjection> {
+        if !self.enabled {
+            return None;
+        }
+        let samples = (self.window_secs as f64 / interval.as_secs_f64().max(f64::EPSILON)) as usize;
+        let bytes_per_second = history.memory_trend(&GENERAL_STATS_PID, samples, interval)?;
+        let limit = self
+            .limit_bytes
+            .unwrap_or(current.saturating_add(available_memory));
+        Some(MemoryProjection::new(
+            current,
+            bytes_per_second,
+            interval,
+            limit,
+        ))
+    }
+}
+
+/// System swap on the last tick, with how fast it is filling
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SwapUsage {
+    pub used: u64,
+    pub total: u64,
+    /// Change of `used` since the previous tick, in bytes per second
+    pub growth: f64,
+    sampled_at: Option<Instant>,
+}
+
+impl SwapUsage {
+    fn update(&mut self, used: u64, total: u64) {
+        let now = Instant::now();
+        self.growth = match self.sampled_at {
+            Some(at) if now > at => (used as f64 - self.used as f64) / (now - at).as_secs_f64(),
+            _ => 0.0,
+        };
+        self.used = used;
+        self.total = total;
+        self.sampled_at = Some(now);
+    }
+
+    /// Share of the swap space in use, 0 without swap
+    pub fn percent(&self) -> f32 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.used as f32 / self.total as f32 * 100.0
+        }
+    }
+}
+
+/// What the handles asked the worker for. Both sides apply the same commands:
+/// the worker as they come in, a handle to its own copy until a snapshot shows
+/// the worker got them.
+#[derive(Debug, Clone, Default)]
+pub struct Controls {
+    pub monitored_processes: Vec<ProcessIdentifier>,
+    /// Monitored identifiers that keep their data but are not sampled
+    pub paused_processes: Vec<ProcessIdentifier>,
+    pub update_interval: Duration,
+    pub history_len: usize,
+    /// Identifiers sampled at their own interval instead of `update_interval`
+    pub interval_overrides: HashMap<ProcessIdentifier, Duration>,
+    pub exclude_rules: Vec<ExcludeRule>,
+    pub watch_rules: Vec<WatchRule>,
+    pub adaptive_sampling: AdaptiveSampling,
+    pub oom_projection: OomProjection,
+    pub leak_detection: LeakDetection,
+    pub flight_recorder: FlightRecorder,
+    /// Read by the pusher of [`influx::spawn`], not by the worker
+    pub influx_push: InfluxPush,
+    /// Outcome of the last push, reported by the pusher
+    pub influx_status: Option<Result<String, String>>,
+    /// Read on each tick while set, for the strip above the CPU plots
+    pub sample_thermals: bool,
+    /// OS-enforced limits a handle set, lifted by the worker once their entry is removed
+    pub limits: HashMap<ProcessIdentifier, ResourceLimit>,
+    /// Labelled moments, oldest first
+    pub annotations: Vec<Annotation>,
+    /// Checked by the worker after every tick
+    pub alert_rules: Vec<AlertRule>,
+    /// When alert commands hold back
+    pub command_quiet_hours: QuietHours,
+}
+
+/// Change a handle sends to the worker
+#[derive(Debug, Clone)]
+enum Command {
+    Add(ProcessIdentifier),
+    Remove(ProcessIdentifier),
+    /// Drops the data sampled so far, sampling goes on
+    Clear(ProcessIdentifier),
+    Pause(ProcessIdentifier),
+    Resume(ProcessIdentifier),
+    UpdateInterval(Duration),
+    HistoryLen(usize),
+    IntervalOverride(ProcessIdentifier, Option<Duration>),
+    ExcludeRules(Vec<ExcludeRule>),
+    WatchRules(Vec<WatchRule>),
+    AdaptiveSampling(AdaptiveSampling),
+    OomProjection(OomProjection),
+    LeakDetection(LeakDetection),
+    FlightRecorder(FlightRecorder),
+    InfluxPush(InfluxPush),
+    InfluxStatus(Result<String, String>),
+    SampleThermals(bool),
+    /// Limit the handle applied, None once it lifted it
+    Limit(ProcessIdentifier, Option<ResourceLimit>),
+    Annotate(Annotation),
+    RemoveAnnotation(Annotation),
+    AlertRules(Vec<AlertRule>),
+    /// Of the alert commands
+    QuietHours(QuietHours),
+}
+
+impl Controls {
+    fn apply(&mut self, command: &Command) {
+        match command {
+            Command::Add(identifier) => {
+                if !self.monitored_processes.contains(identifier) {
+                    self.monitored_processes.push(identifier.clone());
+                }
+            }
+            Command::Remove(identifier) => {
+                self.monitored_processes
+                    .retain(|monitored| monitored != identifier);
+                self.paused_processes.retain(|paused| paused != identifier);
+                self.limits.remove(identifier);
+            }
+            Command::Clear(_) => {}
+            Command::Pause(identifier) => {
+                if !self.paused_processes.contains(identifier) {
+                    self.paused_processes.push(identifier.clone());
+                }
+            }
+            Command::Resume(identifier) => {
+                self.paused_processes.retain(|paused| paused != identifier);
+            }
+            Command::UpdateInterval(interval) => self.update_interval = *interval,
+            Command::HistoryLen(history_len) => self.history_len = *history_len,
+            Command::IntervalOverride(identifier, Some(interval)) => {
+                self.interval_overrides
+                    .insert(identifier.clone(), *interval);
+            }
+            Command::IntervalOverride(identifier, None) => {
+                self.interval_overrides.remove(identifier);
+            }
+            Command::ExcludeRules(rules) => self.exclude_rules = rules.clone(),
+            Command::WatchRules(rules) => self.watch_rules = rules.clone(),
+            Command::AdaptiveSampling(adaptive) => self.adaptive_sampling = *adaptive,
+            Command::OomProjection(projection) => self.oom_projection = *projection,
+            Command::LeakDetection(leak) => self.leak_detection = *leak,
+            Command::FlightRecorder(recorder) => self.flight_recorder = recorder.clone(),
+            Command::InfluxPush(push) => {
+                self.influx_push = push.clone();
+                self.influx_status = None;
+            }
+            Command::InfluxStatus(status) => self.influx_status = Some(status.clone()),
+            Command::SampleThermals(sample) => self.sample_thermals = *sample,
+            Command::Limit(identifier, Some(limit)) => {
+                self.limits.insert(identifier.clone(), *limit);
+            }
+            Command::Limit(identifier, None) => {
+                self.limits.remove(identifier);
+            }
+            Command::Annotate(annotation) => self.annotations.push(annotation.clone()),
+            Command::RemoveAnnotation(annotation) => {
+                self.annotations.retain(|kept| kept != annotation);
+            }
+            Command::AlertRules(rules) => self.alert_rules = rules.clone(),
+            Command::QuietHours(hours) => self.command_quiet_hours = *hours,
+        }
+    }
+}
+
+/// What goes from the handles to the worker
+#[derive(Debug)]
+enum Message {
+    /// The `seq`th command sent by `handle`
+    Command {
+        handle: u64,
+        seq: u64,
+        command: Command,
+    },
+    /// A new handle, sent every snapshot from then on. The worker keeps a
+    /// receiver too, to take back a snapshot the handle didn't pick up in time.
+    Subscribe {
+        handle: u64,
+        sender: Sender<Arc<Snapshot>>,
+        receiver: Receiver<Arc<Snapshot>>,
+    },
+    Unsubscribe(u64),
+}
+
+/// Results of one worker tick, published whole to every handle. Histories
+/// share their storage with the worker's, so publishing doesn't copy them.
 #[derive(Debug, Default)]
-pub struct Metrics {
-    monitored_processes: Vec<ProcessIdentifier>,
+pub struct Snapshot {
+    /// Number of sampling ticks completed by the worker thread
+    pub tick: u64,
     processes: HashMap<ProcessIdentifier, ProcessData>,
+    availability: HashMap<ProcessIdentifier, Availability>,
     pub monitor: ProcessMonitor,
-    pub update_interval: Duration,
-    pub history_len: usize,
-    processes_to_clear: Vec<ProcessIdentifier>,
+    /// Top consumers system-wide
+    pub heavy_hitters: HeavyHitters,
+    /// Set when the system runs more than [`SAFE_MODE_PROCESS_COUNT`] processes:
+    /// only the monitored trees are refreshed, with a full scan every
+    /// few ticks to discover new processes
+    pub safe_mode: bool,
+    /// Processes seen on the last full scan
+    pub system_process_count: usize,
+    pub swap: SwapUsage,
+    pub cpu_thermals: CpuThermals,
+    /// Time the worker spent refreshing and processing the tick
+    pub sampling_cost: Duration,
+    /// Interval the worker is sleeping until the next tick
+    pub current_interval: Duration,
+    /// Why the last samples could not be recorded
+    pub flight_recorder_error: Option<String>,
+    /// Latest alerts fired, oldest first, at most [`ALERT_BACKLOG`]
+    pub alerts: Vec<Alert>,
+    /// Conditions of the rules above their threshold on this tick
+    pub firing: Vec<AlertKey>,
+    /// What the tick was taken with
+    controls: Controls,
+    /// Number of commands of each handle applied by then
+    applied: HashMap<u64, u64>,
+}
+
+/// Source of the ids telling handles apart
+static NEXT_HANDLE: AtomicU64 = AtomicU64::new(0);
+
+/// Handle on the sampling worker thread. Changes go to the worker as commands
+/// over a channel, and the worker publishes an immutable [`Snapshot`] after
+/// every tick, which the handle derefs to once picked up by [`Metrics::update`].
+/// A clone is a handle of its own, e.g. for another thread; the worker stops
+/// once every handle is dropped.
+#[derive(Debug)]
+pub struct Metrics {
+    handle: u64,
+    /// Commands sent so far
+    sent: u64,
+    messages: Sender<Message>,
+    snapshots: Receiver<Arc<Snapshot>>,
+    snapshot: Arc<Snapshot>,
+    /// Commands the worker hadn't applied by `snapshot`, with their number
+    pending: Vec<(u64, Command)>,
+    /// The controls of `snapshot` with `pending` applied
+    controls: Controls,
 }
 
 impl Metrics {
-    pub fn new(history_len: usize, update_interval_ms: usize) -> Arc<RwLock<Self>> {
-        let metrics = Arc::new(RwLock::new(Self {
-            update_interval: Duration::from_millis(update_interval_ms as u64),
+    pub fn new(history_len: usize, update_interval_ms: usize) -> Self {
+        let update_interval = Duration::from_millis(update_interval_ms as u64);
+        let controls = Controls {
+            update_interval,
             history_len,
-            processes: HashMap::new(),
-            processes_to_clear: Vec::new(),
             ..Default::default()
-        }));
-
-        let metrics_clone = Arc::clone(&metrics);
-        let mut update_interval = Duration::from_millis(3000);
-        let mut metrics_thread = Metrics {
-            monitor: ProcessMonitor::new(Duration::from_millis(update_interval_ms as u64)),
-            update_interval: update_interval,
-            history_len: 10,
-            processes_to_clear: Vec::new(),
+        };
+        let (messages, inbox) = crossbeam_channel::unbounded();
+        let metrics = Self::subscribe(
+            messages,
+            Arc::new(Snapshot {
+                controls: controls.clone(),
+                ..Default::default()
+            }),
+            controls.clone(),
+        );
+
+        let worker = Worker {
+            monitor: ProcessMonitor::new(update_interval),
+            controls,
             ..Default::default()
         };
-        thread::sleep(update_interval);
-        thread::spawn(move || loop {
-            {
-                let metrics_read = metrics_clone.read().unwrap();
-                update_interval = metrics_read.update_interval;
-                metrics_thread.update_interval = metrics_read.update_interval;
-                metrics_thread.history_len = metrics_read.history_len;
-                metrics_thread.monitored_processes = metrics_read.monitored_processes.clone();
-                for identifier in &metrics_read.processes_to_clear {
-                    metrics_thread.processes.remove(&identifier);
-                }
+        thread::sleep(Duration::from_millis(3000));
+        thread::spawn(move || worker.run(inbox, update_interval));
+
+        metrics
+    }
+
+    fn subscribe(messages: Sender<Message>, snapshot: Arc<Snapshot>, controls: Controls) -> Self {
+        let handle = NEXT_HANDLE.fetch_add(1, Ordering::Relaxed);
+        // One slot: a handle only ever needs the newest snapshot
+        let (sender, snapshots) = crossbeam_channel::bounded(1);
+        // Fails only once the worker is gone, and then nothing is published anyway
+        messages
+            .send(Message::Subscribe {
+                handle,
+                sender,
+                receiver: snapshots.clone(),
+            })
+            .ok();
+        Self {
+            handle,
+            sent: 0,
+            messages,
+            snapshots,
+            snapshot,
+            pending: Vec::new(),
+            controls,
+        }
+    }
+
+    fn send(&mut self, command: Command) {
+        self.sent += 1;
+        self.controls.apply(&command);
+        self.messages
+            .send(Message::Command {
+                handle: self.handle,
+                seq: self.sent,
+                command: command.clone(),
+            })
+            .ok();
+        self.pending.push((self.sent, command));
+    }
+
+    /// Picks up the newest snapshot, if the worker published one since the last
+    /// call. Returns whether it did.
+    pub fn update(&mut self) -> bool {
+        match self.snapshots.try_recv() {
+            Ok(snapshot) => {
+                self.take(snapshot);
+                true
             }
-            {
-                metrics_thread.update_metrics();
-                let mut metrics_write = metrics_clone.write().unwrap();
-                metrics_write.processes = metrics_thread.processes.clone();
-                metrics_write.processes_to_clear = vec![];
-                metrics_write.monitor = metrics_thread.monitor;
-            }
-            metrics_thread.monitor =
-                ProcessMonitor::new(Duration::from_millis(update_interval_ms as u64));
-            thread::sleep(update_interval);
-            metrics_thread.monitor.update();
-        });
+            Err(_) => false,
+        }
+    }
 
-        metrics.clone()
+    /// Waits up to `timeout` for the next snapshot, see [`Metrics::update`]
+    pub fn wait(&mut self, timeout: Duration) -> bool {
+        match self.snapshots.recv_timeout(timeout) {
+            Ok(snapshot) => {
+                self.take(snapshot);
+                true
+            }
+            Err(_) => false,
+        }
     }
 
-    pub fn add_selected_process(&mut self, identifier: ProcessIdentifier) {
-        if !self.monitored_processes.contains(&identifier) {
-            self.monitored_processes.push(identifier.clone());
+    fn take(&mut self, snapshot: Arc<Snapshot>) {
+        let applied = snapshot.applied.get(&self.handle).copied().unwrap_or(0);
+        self.pending.retain(|(seq, _)| *seq > applied);
+        self.controls = snapshot.controls.clone();
+        for (_, command) in &self.pending {
+            self.controls.apply(command);
         }
+        self.snapshot = snapshot;
     }
 
+    /// What the worker was asked for, including commands it hasn't got to yet
+    pub fn controls(&self) -> &Controls {
+        &self.controls
+    }
+
+    pub fn add_selected_process(&mut self, identifier: ProcessIdentifier) {
+        self.send(Command::Add(identifier));
+    }
+
+    /// Stops sampling `identifier`, forgets its data and lifts its limit
     pub fn remove_selected_process(&mut self, identifier: &ProcessIdentifier) {
-        if let Some(pos) = self
-            .monitored_processes
-            .iter()
-            .position(|x| x == identifier)
-        {
-            self.monitored_processes.remove(pos);
-            self.processes.remove(identifier);
+        if self.controls.monitored_processes.contains(identifier) {
+            self.send(Command::Remove(identifier.clone()));
         }
     }
 
+    /// Limits the current processes of `identifier`, later children inherit the limit
+    pub fn set_limit(
+        &mut self,
+        identifier: &ProcessIdentifier,
+        limit: ResourceLimit,
+    ) -> Result<(), String> {
+        if limit.is_empty() {
+            return self.remove_limit(identifier);
+        }
+        let pids: Vec<Pid> = self
+            .get_process_data(identifier)
+            .map(|data| {
+                data.processes_stats
+                    .iter()
+                    .filter(|process| !process.is_thread)
+                    .map(|process| process.pid)
+                    .collect()
+            })
+            .unwrap_or_default();
+        if pids.is_empty() {
+            return Err("No running processes to limit".to_string());
+        }
+        limits::apply_limit(identifier, &pids, &limit, self.monitor.cpu_count())?;
+        self.send(Command::Limit(identifier.clone(), Some(limit)));
+        Ok(())
+    }
+
+    pub fn remove_limit(&mut self, identifier: &ProcessIdentifier) -> Result<(), String> {
+        if self.controls.limits.contains_key(identifier) {
+            self.send(Command::Limit(identifier.clone(), None));
+            limits::remove_limit(identifier)?;
+        }
+        Ok(())
+    }
+
+    pub fn limit(&self, identifier: &ProcessIdentifier) -> Option<&ResourceLimit> {
+        self.controls.limits.get(identifier)
+    }
+
+    /// Stores `annotation` and has the worker write it with the next sample of
+    /// its identifier to the flight recorder's log
+    pub fn annotate(&mut self, annotation: Annotation) {
+        self.send(Command::Annotate(annotation));
+    }
+
+    pub fn remove_annotation(&mut self, index: usize) {
+        if let Some(annotation) = self.controls.annotations.get(index) {
+            self.send(Command::RemoveAnnotation(annotation.clone()));
+        }
+    }
+
+    /// Annotations, oldest first
+    pub fn annotations(&self) -> &[Annotation] {
+        &self.controls.annotations
+    }
+
     pub fn clear_process_data(&mut self, identifier: &ProcessIdentifier) {
-        self.processes_to_clear.push(identifier.clone());
+        self.send(Command::Clear(identifier.clone()));
+    }
+
+    pub fn pause_process(&mut self, identifier: &ProcessIdentifier) {
+        self.send(Command::Pause(identifier.clone()));
+    }
+
+    pub fn resume_process(&mut self, identifier: &ProcessIdentifier) {
+        self.send(Command::Resume(identifier.clone()));
+    }
+
+    pub fn is_paused(&self, identifier: &ProcessIdentifier) -> bool {
+        self.controls.paused_processes.contains(identifier)
     }
 
     pub fn get_monitored_processes(&self) -> &[ProcessIdentifier] {
-        &self.monitored_processes
+        &self.controls.monitored_processes
     }
 
     pub fn get_process_data(&self, identifier: &ProcessIdentifier) -> Option<&ProcessData> {
-        self.processes.get(identifier)
+        self.snapshot.processes.get(identifier)
+    }
+
+    pub fn availability(&self, identifier: &ProcessIdentifier) -> Option<&Availability> {
+        self.snapshot.availability.get(identifier)
+    }
+
+    /// Number of sample histories kept, one per process plus one aggregate per identifier
+    pub fn history_series_count(&self) -> usize {
+        self.snapshot
+            .processes
+            .values()
+            .map(|data| data.processes_stats.len() + 1)
+            .sum()
+    }
+
+    /// Number of aggregate histories, each also keeping the averages of
+    /// [`process::HISTORY_TIERS`]
+    pub fn tiered_series_count(&self) -> usize {
+        self.snapshot.processes.len()
     }
 
     pub fn set_update_interval(&mut self, update_interval_ms: u64) {
-        self.update_interval = Duration::from_millis(update_interval_ms);
+        self.send(Command::UpdateInterval(Duration::from_millis(
+            update_interval_ms,
+        )));
+    }
+
+    pub fn set_history_len(&mut self, history_len: usize) {
+        self.send(Command::HistoryLen(history_len));
+    }
+
+    pub fn set_exclude_rules(&mut self, exclude_rules: Vec<ExcludeRule>) {
+        self.send(Command::ExcludeRules(exclude_rules));
+    }
+
+    /// Monitors processes matching `watch_rules` from the next tick on, see [`WatchRule`]
+    pub fn set_watch_rules(&mut self, watch_rules: Vec<WatchRule>) {
+        self.send(Command::WatchRules(watch_rules));
+    }
+
+    /// Samples `identifier` every `interval` instead of the global interval, None restores it
+    pub fn set_interval_override(
+        &mut self,
+        identifier: &ProcessIdentifier,
+        interval: Option<Duration>,
+    ) {
+        self.send(Command::IntervalOverride(identifier.clone(), interval));
+    }
+
+    pub fn interval_override(&self, identifier: &ProcessIdentifier) -> Option<Duration> {
+        self.controls.interval_overrides.get(identifier).copied()
+    }
+
+    /// Interval between samples of `identifier`, its override or the global one
+    pub fn interval_of(&self, identifier: &ProcessIdentifier) -> Duration {
+        self.interval_override(identifier)
+            .unwrap_or(self.controls.update_interval)
+    }
+
+    pub fn set_adaptive_sampling(&mut self, adaptive_sampling: AdaptiveSampling) {
+        self.send(Command::AdaptiveSampling(adaptive_sampling));
+    }
+
+    pub fn set_oom_projection(&mut self, oom_projection: OomProjection) {
+        self.send(Command::OomProjection(oom_projection));
+    }
+
+    pub fn set_leak_detection(&mut self, leak_detection: LeakDetection) {
+        self.send(Command::LeakDetection(leak_detection));
+    }
+
+    pub fn set_flight_recorder(&mut self, flight_recorder: FlightRecorder) {
+        self.send(Command::FlightRecorder(flight_recorder));
+    }
+
+    pub fn set_influx_push(&mut self, influx_push: InfluxPush) {
+        self.send(Command::InfluxPush(influx_push));
+    }
+
+    /// Reports the outcome of a push to every handle
+    pub fn set_influx_status(&mut self, status: Result<String, String>) {
+        self.send(Command::InfluxStatus(status));
+    }
+
+    pub fn set_sample_thermals(&mut self, sample_thermals: bool) {
+        self.send(Command::SampleThermals(sample_thermals));
+    }
+
+    pub fn set_alert_rules(&mut self, alert_rules: Vec<AlertRule>) {
+        self.send(Command::AlertRules(alert_rules));
+    }
+
+    pub fn set_command_quiet_hours(&mut self, hours: QuietHours) {
+        self.send(Command::QuietHours(hours));
+    }
+}
+
+impl Clone for Metrics {
+    fn clone(&self) -> Self {
+        Self::subscribe(
+            self.messages.clone(),
+            Arc::clone(&self.snapshot),
+            self.controls.clone(),
+        )
+    }
+}
+
+/// A handle on no worker, which keeps its empty snapshot
+impl Default for Metrics {
+    fn default() -> Self {
+        let (messages, _) = crossbeam_channel::unbounded();
+        Self::subscribe(messages, Arc::default(), Controls::default())
+    }
+}
+
+impl Drop for Metrics {
+    fn drop(&mut self) {
+        self.messages.send(Message::Unsubscribe(self.handle)).ok();
+    }
+}
+
+impl Deref for Metrics {
+    type Target = Snapshot;
+
+    fn deref(&self) -> &Snapshot {
+        &self.snapshot
+    }
+}
+
+type Subscribers = Vec<(u64, Sender<Arc<Snapshot>>, Receiver<Arc<Snapshot>>)>;
+
+/// State of the sampling thread, owned by it alone
+#[derive(Default)]
+struct Worker {
+    controls: Controls,
+    processes: HashMap<ProcessIdentifier, ProcessData>,
+    monitor: ProcessMonitor,
+    tick: u64,
+    gpu: GpuMonitor,
+    heavy_hitters: HeavyHitters,
+    /// Log the worker appends its samples to
+    flight_log: FlightLog,
+    flight_recorder_error: Option<String>,
+    current_interval: Duration,
+    last_tick_at: Option<Instant>,
+    safe_mode: bool,
+    system_process_count: usize,
+    swap: SwapUsage,
+    cpu_thermals: CpuThermals,
+    /// Temperature sensors
+    components: Components,
+    /// Annotations not written to the flight recorder's log yet
+    unrecorded_annotations: Vec<Annotation>,
+    /// When each identifier was last sampled
+    sampled_at: HashMap<ProcessIdentifier, Instant>,
+    availability: HashMap<ProcessIdentifier, Availability>,
+    /// Names of the sampled processes
+    names: NameCache,
+    /// PIDs added by watch rules, with whether their rule removes them on exit.
+    /// A PID is added once, even if removed by hand.
+    watched: HashMap<Pid, bool>,
+    /// Exits noticed without a status, searched for in the logs
+    missed_crashes: MissedCrashes,
+    alerts: Vec<Alert>,
+    /// Alert rules above their threshold on the last tick
+    firing: Vec<AlertKey>,
+    /// Slot of each handle, see [`Message::Subscribe`]
+    subscribers: Subscribers,
+    /// Number of commands of each handle applied so far
+    applied: HashMap<u64, u64>,
+}
+
+impl Worker {
+    /// Samples until every handle is gone. Commands are applied as they come in
+    /// between ticks.
+    fn run(mut self, inbox: Receiver<Message>, base_interval: Duration) {
+        let mut refresh_time = Duration::ZERO;
+        // Ticks are scheduled against a deadline, so the time spent sampling
+        // doesn't stretch the interval between samples
+        let mut deadline = Instant::now();
+        loop {
+            while let Ok(message) = inbox.try_recv() {
+                self.receive(message);
+            }
+            let work_started = Instant::now();
+            self.update_metrics();
+            let update_interval = self.tick_interval();
+            self.current_interval = update_interval;
+            let next_monitor = self.next_monitor(base_interval);
+            self.publish(next_monitor, refresh_time + work_started.elapsed());
+            deadline += update_interval;
+            let now = Instant::now();
+            if deadline < now {
+                // Too late for this deadline, the next tick counts the skipped ones
+                // as missed instead of sampling them in a burst
+                deadline = now;
+            }
+            loop {
+                match inbox.recv_deadline(deadline) {
+                    Ok(message) => self.receive(message),
+                    Err(RecvTimeoutError::Timeout) => break,
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            }
+            let refresh_started = Instant::now();
+            self.monitor.update();
+            refresh_time = refresh_started.elapsed();
+        }
+    }
+
+    fn receive(&mut self, message: Message) {
+        match message {
+            Message::Command {
+                handle,
+                seq,
+                command,
+            } => {
+                match &command {
+                    Command::Remove(identifier) => self.forget(identifier),
+                    Command::Clear(identifier) => {
+                        self.processes.remove(identifier);
+                        self.availability.remove(identifier);
+                    }
+                    Command::Annotate(annotation) => {
+                        self.unrecorded_annotations.push(annotation.clone());
+                    }
+                    Command::RemoveAnnotation(annotation) => {
+                        self.unrecorded_annotations
+                            .retain(|unrecorded| unrecorded != annotation);
+                    }
+                    _ => {}
+                }
+                self.controls.apply(&command);
+                self.applied.insert(handle, seq);
+            }
+            Message::Subscribe {
+                handle,
+                sender,
+                receiver,
+            } => self.subscribers.push((handle, sender, receiver)),
+            Message::Unsubscribe(handle) => {
+                self.subscribers
+                    .retain(|(subscriber, ..)| *subscriber != handle);
+                self.applied.remove(&handle);
+            }
+        }
+    }
+
+    /// Sends the results of the tick to every handle. The monitor is handed over
+    /// whole and replaced by `next_monitor`.
+    fn publish(&mut self, next_monitor: ProcessMonitor, sampling_cost: Duration) {
+        let snapshot = Arc::new(Snapshot {
+            tick: self.tick,
+            processes: self.processes.clone(),
+            availability: self.availability.clone(),
+            monitor: std::mem::replace(&mut self.monitor, next_monitor),
+            heavy_hitters: self.heavy_hitters.clone(),
+            safe_mode: self.safe_mode,
+            system_process_count: self.system_process_count,
+            swap: self.swap,
+            cpu_thermals: self.cpu_thermals.clone(),
+            sampling_cost,
+            current_interval: self.current_interval,
+            flight_recorder_error: self.flight_recorder_error.clone(),
+            alerts: self.alerts.clone(),
+            firing: self.firing.clone(),
+            controls: self.controls.clone(),
+            applied: self.applied.clone(),
+        });
+        for (_, sender, receiver) in &self.subscribers {
+            let mut snapshot = Arc::clone(&snapshot);
+            // A snapshot the handle didn't pick up is replaced, never queued
+            while let Err(TrySendError::Full(unsent)) = sender.try_send(snapshot) {
+                receiver.try_recv().ok();
+                snapshot = unsent;
+            }
+        }
+    }
+
+    /// Drops the data of a removed entry and lifts its limit
+    fn forget(&mut self, identifier: &ProcessIdentifier) {
+        self.processes.remove(identifier);
+        self.availability.remove(identifier);
+        if self.controls.limits.contains_key(identifier) {
+            if let Err(err) = limits::remove_limit(identifier) {
+                log::warn!(
+                    "Failed to lift the limit of {}: {err}",
+                    identifier.to_string()
+                );
+            }
+        }
+    }
+
+    fn interval_override(&self, identifier: &ProcessIdentifier) -> Option<Duration> {
+        self.controls.interval_overrides.get(identifier).copied()
+    }
+
+    /// Interval until the next tick: the global interval or the shortest override
+    /// of a sampled identifier, whichever is sooner
+    fn tick_interval(&self) -> Duration {
+        self.controls
+            .monitored_processes
+            .iter()
+            .filter(|identifier| !self.controls.paused_processes.contains(identifier))
+            .filter_map(|identifier| self.controls.interval_overrides.get(identifier))
+            .fold(self.sampling_interval(), |shortest, interval| {
+                shortest.min(*interval)
+            })
+    }
+
+    /// Whether `identifier` is due for a sample on this tick. Half a tick of slack
+    /// keeps an identifier that is due just after this tick from waiting a whole one.
+    fn is_due(&self, identifier: &ProcessIdentifier, now: Instant, base: Duration) -> bool {
+        let interval = self.interval_override(identifier).unwrap_or(base);
+        self.sampled_at
+            .get(identifier)
+            .is_none_or(|at| now.duration_since(*at) + self.current_interval / 2 >= interval)
+    }
+
+    /// Interval until the next sample, see [`AdaptiveSampling`]
+    fn sampling_interval(&self) -> Duration {
+        let adaptive = &self.controls.adaptive_sampling;
+        let busy = self
+            .processes
+            .values()
+            .any(|data| data.genereal.stats.current_cpu >= adaptive.cpu_threshold);
+        if adaptive.enabled && busy {
+            Duration::from_millis(adaptive.fast_interval_ms).min(self.controls.update_interval)
+        } else {
+            self.controls.update_interval
+        }
+    }
+
+    /// Monitor for the next tick, scoped to the monitored trees in safe mode
+    fn next_monitor(&self, update_interval: Duration) -> ProcessMonitor {
+        if !self.safe_mode {
+            return ProcessMonitor::new(update_interval);
+        }
+        if (self.tick + 1) % SAFE_MODE_RESCAN_TICKS == 0 {
+            return ProcessMonitor::safe_mode(update_interval, None);
+        }
+        let mut pids: Vec<Pid> = self
+            .processes
+            .values()
+            .flat_map(|data| data.processes_stats.iter().map(|process| process.pid))
+            .chain(
+                self.controls
+                    .monitored_processes
+                    .iter()
+                    .filter_map(ProcessIdentifier::to_pid),
+            )
+            .collect();
+        pids.sort();
+        pids.dedup();
+        ProcessMonitor::safe_mode(update_interval, Some(pids))
+    }
+
+    fn update_thermals(&mut self) {
+        if !self.controls.sample_thermals {
+            self.cpu_thermals.clear();
+            return;
+        }
+        // Listed until a sensor shows up, then only the known sensors are read
+        if self.components.list().is_empty() {
+            self.components.refresh(true);
+        } else {
+            for component in self.components.list_mut() {
+                component.refresh();
+            }
+        }
+        let temperature = package_temperature(&self.components);
+        let frequency = self.monitor.cpu_frequency();
+        self.cpu_thermals
+            .record(temperature, frequency, self.controls.history_len);
+    }
+
+    /// Samples skipped since the previous tick because the worker ran late
+    fn missed_samples(&mut self) -> u64 {
+        let now = Instant::now();
+        let elapsed = self.last_tick_at.replace(now).map(|at| now - at);
+        match elapsed {
+            Some(elapsed) if !self.current_interval.is_zero() => {
+                let intervals = elapsed.as_secs_f64() / self.current_interval.as_secs_f64();
+                // Sampling itself takes time, so only clearly late ticks count as gaps
+                if intervals > 2.0 {
+                    intervals.round() as u64 - 1
+                } else {
+                    0
+                }
+            }
+            _ => 0,
+        }
     }
 
     fn update_metrics(&mut self) {
+        self.tick += 1;
+        let missed = self.missed_samples();
+        self.gpu.begin_tick();
+        if !self.monitor.is_scoped() {
+            self.system_process_count = self.monitor.process_count();
+            self.safe_mode = self.system_process_count > SAFE_MODE_PROCESS_COUNT;
+            self.heavy_hitters
+                .record(HeavyHitterSample::collect(&self.monitor));
+        }
         // Очистка процессов, которые больше не отслеживаются
         self.cleanup_unmonitored_processes();
+        self.apply_watch_rules();
 
-        for process_identifier in &self.monitored_processes {
-            self.processes
+        let now = Instant::now();
+        let taken_at = SystemTime::now();
+        let base = self.sampling_interval();
+        let due: Vec<ProcessIdentifier> = self
+            .controls
+            .monitored_processes
+            .iter()
+            .filter(|identifier| !self.controls.paused_processes.contains(identifier))
+            .filter(|identifier| self.is_due(identifier, now, base))
+            .cloned()
+            .collect();
+        let available_memory = self.monitor.available_memory();
+        let (swap_used, swap_total) = self.monitor.swap();
+        self.swap.update(swap_used, swap_total);
+        let system_cpu = self.monitor.system_cpu();
+        self.update_thermals();
+        for process_identifier in &due {
+            self.sampled_at.insert(process_identifier.clone(), now);
+            let interval = self.interval_override(process_identifier).unwrap_or(base);
+            let process_data = self
+                .processes
                 .entry(process_identifier.clone())
                 .or_insert_with(|| ProcessData {
-                    history: ProcessHistory::new(self.history_len),
+                    history: ProcessHistory::new(self.controls.history_len),
                     genereal: ProcessGeneral {
-                        history: ProcessHistory::new(self.history_len),
+                        history: ProcessHistory::tiered(self.controls.history_len),
                         ..Default::default()
                     },
                     ..Default::default()
                 });
-            if let Some(processes) = self.monitor.find_all_relation(process_identifier) {
+            if process_data.finished.is_some() {
+                continue;
+            }
+            if let ProcessIdentifier::Pid(pid) = process_identifier {
+                let exited = self
+                    .monitor
+                    .get_process_by_pid(pid)
+                    .is_none_or(|process| process.status() == ProcessStatus::Zombie);
+                if exited && process_data.session.samples > 0 {
+                    info!(
+                        "{} exited, freezing its data",
+                        process_identifier.to_string()
+                    );
+                    let crash = Crash::read(*pid).or(process_data.crashes.remove(pid));
+                    if crash.is_none() {
+                        self.missed_crashes.watch(*pid);
+                    }
+                    if let Some(crash) = crash {
+                        let name = self
+                            .monitor
+                            .get_process_by_pid(pid)
+                            .map(|process| process.name().to_string_lossy().into_owned())
+                            .unwrap_or_default();
+                        process_data.push_event(ProcessEvent {
+                            kind: ProcessEventKind::Exited,
+                            pid: *pid,
+                            name,
+                            tick: self.tick,
+                            at: Instant::now(),
+                            crash: Some(crash),
+                        });
+                    }
+                    process_data.finish();
+                    continue;
+                }
+            }
+            let exclude = self
+                .controls
+                .exclude_rules
+                .iter()
+                .find(|rule| rule.identifier == *process_identifier);
+            if let Some(processes) = self.monitor.find_all_relation(process_identifier, exclude) {
                 // update history
                 if let Some(process_data) = self.processes.get_mut(process_identifier) {
+                    process_data.inactive_since = None;
                     // Update history size if it changed
-                    if process_data.history.history_len != self.history_len {
-                        process_data.history = ProcessHistory::new(self.history_len);
-                        process_data.genereal.history = ProcessHistory::new(self.history_len);
+                    if process_data.history.history_len != self.controls.history_len {
+                        process_data.history.resize(self.controls.history_len);
+                        process_data
+                            .genereal
+                            .history
+                            .resize(self.controls.history_len);
+                    }
+                    record_events(process_data, &processes, &self.monitor, self.tick);
+                    for event in process_data
+                        .events
+                        .iter()
+                        .rev()
+                        .take_while(|event| event.tick == self.tick)
+                        .filter(|event| event.kind == ProcessEventKind::Exited)
+                        .filter(|event| event.crash.is_none())
+                    {
+                        self.missed_crashes.watch(event.pid);
                     }
+                    record_starts(process_data, &processes, &self.monitor, self.tick);
                     // Remove inactive processes from history
                     process_data.history.cleanup_histories(&processes);
                     let mut general_stats = ProcessGeneralStats::default();
-                    let mut processes_stats = Vec::with_capacity(processes.len());
+                    // The previous tick's buffer, its names are already shared with the snapshot
+                    let mut processes_stats = std::mem::take(&mut process_data.processes_stats);
+                    processes_stats.clear();
+                    processes_stats.reserve(processes.len());
                     // Update process data
                     for process_pid in &processes {
                         if let Some(process) = self.monitor.get_process_by_pid(process_pid) {
@@ -139,15 +1105,46 @@ impl Metrics {
                             process_data
                                 .history
                                 .update_memory(process.pid(), process.memory() as usize);
+                            let gpu = if process.thread_kind().is_some() {
+                                Default::default()
+                            } else {
+                                self.gpu.usage(process.pid())
+                            };
+                            process_data
+                                .history
+                                .update_gpu(process.pid(), gpu.utilization);
+                            let sched = if process.thread_kind().is_some() {
+                                None
+                            } else {
+                                SchedCounters::read(process.pid()).and_then(|counters| {
+                                    process_data.history.record_sched_counters(
+                                        process.pid(),
+                                        counters,
+                                        now,
+                                    )
+                                })
+                            };
                             // collect process info
-                            let process_info = self
-                                .monitor
-                                .collect_process_info(process, &process_data.history);
+                            let mut process_info = self.monitor.collect_process_info(
+                                process,
+                                &process_data.history,
+                                &mut self.names,
+                            );
+                            process_info.gpu = gpu;
+                            process_info.sched = sched;
                             update_general_stats(&mut general_stats, &process_info);
                             processes_stats.push(process_info);
                         }
                     }
                     // update general history
+                    process_data
+                        .session
+                        .record(!processes_stats.is_empty(), missed);
+                    if !processes_stats.is_empty() {
+                        process_data
+                            .session
+                            .record_usage(general_stats.current_cpu, general_stats.current_memory);
+                    }
                     process_data.processes_stats = processes_stats;
                     process_data
                         .genereal
@@ -157,29 +1154,383 @@ impl Metrics {
                         .genereal
                         .history
                         .update_memory(*GENERAL_STATS_PID, general_stats.current_memory);
+                    process_data
+                        .genereal
+                        .history
+                        .update_gpu(*GENERAL_STATS_PID, general_stats.current_gpu);
+                    general_stats.cpu_share = cpu_share(general_stats.current_cpu, system_cpu);
+                    process_data
+                        .genereal
+                        .history
+                        .update_cpu_share(*GENERAL_STATS_PID, general_stats.cpu_share);
+                    process_data.genereal.history.update_counts(
+                        *GENERAL_STATS_PID,
+                        general_stats.process_count,
+                        general_stats.thread_count,
+                    );
+                    process_data.genereal.history.update_timing(
+                        *GENERAL_STATS_PID,
+                        taken_at,
+                        missed,
+                    );
+                    process_data
+                        .genereal
+                        .history
+                        .roll_up(*GENERAL_STATS_PID, interval);
+                    if let Some(sched) = general_stats.sched {
+                        process_data
+                            .genereal
+                            .history
+                            .update_sched(*GENERAL_STATS_PID, sched);
+                    }
+                    let (peak_gpu, avg_gpu) = process_data
+                        .genereal
+                        .history
+                        .get_gpu_data_history(&GENERAL_STATS_PID);
+                    general_stats.peak_gpu = peak_gpu;
+                    general_stats.avg_gpu = avg_gpu;
                     // get general stats
                     let (peak_cpu, peak_memory, avg_cpu, avg_memory) = process_data
                         .genereal
                         .history
-                        .get_data_history(&*GENERAL_STATS_PID);
+                        .get_data_history(&GENERAL_STATS_PID);
                     general_stats.peak_cpu = peak_cpu;
                     general_stats.peak_memory = peak_memory;
                     general_stats.avg_cpu = avg_cpu;
                     general_stats.avg_memory = avg_memory;
+                    let (cpu_distribution, memory_distribution) = process_data
+                        .genereal
+                        .history
+                        .get_distribution(&GENERAL_STATS_PID);
+                    general_stats.cpu_distribution = cpu_distribution;
+                    general_stats.memory_distribution = memory_distribution;
+                    general_stats.memory_projection = self.controls.oom_projection.project(
+                        &process_data.genereal.history,
+                        general_stats.current_memory,
+                        interval,
+                        available_memory,
+                    );
+                    general_stats.leak = self.controls.leak_detection.check(
+                        &process_data.genereal.history,
+                        interval,
+                        process_data.genereal.stats.leak,
+                        now,
+                    );
                     process_data.genereal.stats = general_stats;
                 }
+            } else if let Some(process_data) = self
+                .processes
+                .get_mut(process_identifier)
+                .filter(|data| data.session.samples > 0)
+                .filter(|_| matches!(process_identifier, ProcessIdentifier::Name(_)))
+            {
+                if process_data.inactive_since.is_none() {
+                    info!(
+                        "No process matches {} anymore, keeping its data",
+                        process_identifier.to_string()
+                    );
+                }
+                process_data.deactivate();
             } else {
-                self.processes.remove(&process_identifier);
+                self.processes.remove(process_identifier);
+            }
+            let up = self
+                .processes
+                .get(process_identifier)
+                .is_some_and(|data| !data.processes_stats.is_empty());
+            let availability = self
+                .availability
+                .entry(process_identifier.clone())
+                .or_insert_with(|| Availability::new(self.controls.history_len));
+            availability.resize(self.controls.history_len);
+            availability.record(up);
+        }
+        self.attach_missed_crashes();
+        self.check_alerts();
+        self.record_flight(&due);
+        self.names.end_tick();
+    }
+
+    /// Fires the alert rules that crossed their threshold on this tick
+    fn check_alerts(&mut self) {
+        let fired = alerts::check(
+            &self.controls.alert_rules,
+            &mut self.firing,
+            &self.processes,
+            &self.swap,
+            &self.controls.command_quiet_hours,
+            self.tick,
+        );
+        self.alerts.extend(fired);
+        let excess = self.alerts.len().saturating_sub(ALERT_BACKLOG);
+        self.alerts.drain(..excess);
+    }
+
+    /// Adds the crashes found in the logs to the exit events that had no status
+    fn attach_missed_crashes(&mut self) {
+        for (pid, crash, noticed) in self.missed_crashes.poll() {
+            let exit = self
+                .processes
+                .values_mut()
+                .flat_map(|data| data.events.iter_mut().rev())
+                .find(|event| {
+                    event.pid == pid
+                        && event.kind == ProcessEventKind::Exited
+                        && event.crash.is_none()
+                });
+            if let Some(exit) = exit {
+                exit.crash = Some(crash);
+                continue;
+            }
+            // A monitored PID has no exit event of its own unless it crashed
+            if let Some(data) = self.processes.get_mut(&ProcessIdentifier::Pid(pid)) {
+                let name = data
+                    .processes_stats
+                    .iter()
+                    .find(|process| process.pid == pid)
+                    .map(|process| process.name.to_string())
+                    .unwrap_or_default();
+                let now = Instant::now();
+                data.push_event(ProcessEvent {
+                    kind: ProcessEventKind::Exited,
+                    pid,
+                    name,
+                    tick: self.tick,
+                    at: now
+                        .checked_sub(noticed.elapsed().unwrap_or_default())
+                        .unwrap_or(now),
+                    crash: Some(crash),
+                });
+            }
+        }
+    }
+
+    /// Appends the samples just taken of `sampled` to the flight recorder's log,
+    /// with the labels of the new annotations of their identifiers. Annotations
+    /// still waiting for a sample are kept in `unrecorded_annotations`.
+    fn record_flight(&mut self, sampled: &[ProcessIdentifier]) {
+        let mut attached =
+            vec![!self.controls.flight_recorder.enabled; self.unrecorded_annotations.len()];
+        let samples: Vec<FlightSample> = sampled
+            .iter()
+            .filter_map(|identifier| {
+                let data = self.processes.get(identifier)?;
+                if data.processes_stats.is_empty() {
+                    return None;
+                }
+                let mut labels = Vec::new();
+                for (annotation, attached) in self.unrecorded_annotations.iter().zip(&mut attached)
+                {
+                    if annotation.applies_to(identifier) {
+                        labels.push(annotation.label.as_str());
+                        *attached = true;
+                    }
+                }
+                let stats = &data.genereal.stats;
+                Some(FlightSample {
+                    identifier: identifier.to_string(),
+                    cpu: stats.current_cpu,
+                    memory: stats.current_memory,
+                    gpu: stats.current_gpu,
+                    processes: stats.process_count,
+                    threads: stats.thread_count,
+                    annotation: (!labels.is_empty()).then(|| labels.join("; ")),
+                })
+            })
+            .collect();
+        // Annotations of identifiers no longer monitored have nothing to wait for
+        let monitored = &self.controls.monitored_processes;
+        let mut attached = attached.into_iter();
+        self.unrecorded_annotations.retain(|annotation| {
+            let attached = attached.next().unwrap_or_default();
+            !attached
+                && annotation
+                    .identifier
+                    .as_ref()
+                    .is_none_or(|identifier| monitored.contains(identifier))
+        });
+        let result = self
+            .flight_log
+            .record(&self.controls.flight_recorder, &samples);
+        if let Err(err) = &result {
+            if self.flight_recorder_error.as_ref() != Some(err) {
+                log::warn!("Flight recorder: {err}");
+            }
+        }
+        self.flight_recorder_error = result.err();
+    }
+
+    /// Monitors new processes matching a watch rule, sampled from this tick on,
+    /// and drops the entries of exited ones whose rule asks for it. In safe mode
+    /// new processes are only seen on the full scans.
+    fn apply_watch_rules(&mut self) {
+        let mut departed = Vec::new();
+        self.watched.retain(|pid, remove_on_exit| {
+            let alive = self
+                .monitor
+                .get_process_by_pid(pid)
+                .is_some_and(|process| process.status() != ProcessStatus::Zombie);
+            if !alive && *remove_on_exit {
+                departed.push(ProcessIdentifier::Pid(*pid));
+            }
+            alive
+        });
+        for identifier in departed {
+            self.forget(&identifier);
+            self.controls.apply(&Command::Remove(identifier));
+        }
+        if self.monitor.is_scoped() {
+            return;
+        }
+        for rule in &self.controls.watch_rules {
+            for pid in self.monitor.find_matching(|text| rule.matches(text)) {
+                let identifier = ProcessIdentifier::Pid(pid);
+                if self.watched.contains_key(&pid)
+                    || self.controls.monitored_processes.contains(&identifier)
+                {
+                    continue;
+                }
+                info!(
+                    "Watch rule {} matched {}, monitoring it",
+                    rule.pattern,
+                    identifier.to_string()
+                );
+                self.watched.insert(pid, rule.remove_on_exit);
+                self.controls.monitored_processes.push(identifier);
             }
         }
     }
 
     fn cleanup_unmonitored_processes(&mut self) {
         self.processes
-            .retain(|pid, _| self.monitored_processes.contains(pid));
+            .retain(|pid, _| self.controls.monitored_processes.contains(pid));
+        self.sampled_at
+            .retain(|identifier, _| self.controls.monitored_processes.contains(identifier));
+        self.availability
+            .retain(|identifier, _| self.controls.monitored_processes.contains(identifier));
     }
 }
 
+/// Tracks the start times of the root processes among `processes` and logs a
+/// restart when one with a start time not seen before shows up
+fn record_starts(
+    process_data: &mut ProcessData,
+    processes: &[Pid],
+    monitor: &ProcessMonitor,
+    tick: u64,
+) {
+    let roots: Vec<&sysinfo::Process> = processes
+        .iter()
+        .filter_map(|pid| monitor.get_process_by_pid(pid))
+        .filter(|process| process.thread_kind().is_none())
+        .filter(|process| {
+            process
+                .parent()
+                .is_none_or(|parent| !processes.contains(&parent))
+        })
+        .collect();
+    let start_times: Vec<u64> = roots.iter().map(|process| process.start_time()).collect();
+    let restarted = process_data.session.record_start_times(&start_times);
+    for process in roots {
+        if restarted.contains(&process.start_time()) {
+            process_data.push_event(ProcessEvent {
+                kind: ProcessEventKind::Restarted,
+                pid: process.pid(),
+                name: process.name().to_string_lossy().into_owned(),
+                tick,
+                at: Instant::now(),
+                crash: None,
+            });
+        }
+    }
+}
+
+/// Compares the previous tick's children with `processes` and logs starts and exits.
+/// Threads are ignored, and nothing is logged on the first sample of an identifier.
+fn record_events(
+    process_data: &mut ProcessData,
+    processes: &[Pid],
+    monitor: &ProcessMonitor,
+    tick: u64,
+) {
+    // A child is a process whose parent is part of the same tree
+    let is_child = |process: &sysinfo::Process| {
+        process.thread_kind().is_none()
+            && process
+                .parent()
+                .is_some_and(|parent| processes.contains(&parent))
+    };
+    if process_data.processes_stats.is_empty() {
+        if process_data.session.samples == 0 {
+            process_data.session.children_seen = processes
+                .iter()
+                .filter_map(|pid| monitor.get_process_by_pid(pid))
+                .filter(|process| is_child(process))
+                .count() as u64;
+        }
+        return;
+    }
+    // A zombie's exit status is gone once its parent reaps it
+    for pid in processes {
+        let zombie = monitor
+            .get_process_by_pid(pid)
+            .is_some_and(|process| process.status() == ProcessStatus::Zombie);
+        if zombie && !process_data.crashes.contains_key(pid) {
+            if let Some(crash) = Crash::read(*pid) {
+                process_data.crashes.insert(*pid, crash);
+            }
+        }
+    }
+    let now = Instant::now();
+    let crashes = &mut process_data.crashes;
+    let exited: Vec<ProcessEvent> = process_data
+        .processes_stats
+        .iter()
+        .filter(|process| !process.is_thread && !processes.contains(&process.pid))
+        .map(|process| ProcessEvent {
+            kind: ProcessEventKind::Exited,
+            pid: process.pid,
+            name: process.name.to_string(),
+            tick,
+            at: now,
+            crash: crashes.remove(&process.pid),
+        })
+        .collect();
+    crashes.retain(|pid, _| processes.contains(pid));
+    let started: Vec<ProcessEvent> = processes
+        .iter()
+        .filter(|pid| !process_data.processes_stats.iter().any(|p| p.pid == **pid))
+        .filter_map(|pid| monitor.get_process_by_pid(pid))
+        .filter(|process| process.thread_kind().is_none())
+        .map(|process| ProcessEvent {
+            kind: ProcessEventKind::Started,
+            pid: process.pid(),
+            name: process.name().to_string_lossy().into_owned(),
+            tick,
+            at: now,
+            crash: None,
+        })
+        .collect();
+    process_data.session.children_seen += processes
+        .iter()
+        .filter(|pid| !process_data.processes_stats.iter().any(|p| p.pid == **pid))
+        .filter_map(|pid| monitor.get_process_by_pid(pid))
+        .filter(|process| is_child(process))
+        .count() as u64;
+    for event in exited.into_iter().chain(started) {
+        process_data.push_event(event);
+    }
+}
+
+/// `tree_cpu` in percent of `system_cpu`. The two are read a moment apart,
+/// so the share is capped at 100 for a tree doing nearly all the work.
+pub fn cpu_share(tree_cpu: f32, system_cpu: f32) -> f32 {
+    if system_cpu <= 0.0 {
+        return 0.0;
+    }
+    (tree_cpu / system_cpu * 100.0).clamp(0.0, 100.0)
+}
+
 fn update_general_stats(general_stats: &mut ProcessGeneralStats, process: &ProcessInfo) {
     if process.is_thread {
         general_stats.thread_count += 1;
@@ -187,5 +1538,58 @@ fn update_general_stats(general_stats: &mut ProcessGeneralStats, process: &Proce
         general_stats.process_count += 1;
         general_stats.current_cpu += process.current_cpu;
         general_stats.current_memory += process.current_memory;
+        general_stats.current_gpu += process.gpu.utilization;
+        general_stats.gpu_memory += process.gpu.memory;
+        general_stats.os_peak_memory += process.os_peak_memory.unwrap_or_default();
+        general_stats.restricted_processes += usize::from(process.restricted);
+        match process.scheduling {
+            Some((_, PriorityClass::Realtime)) => general_stats.realtime_processes += 1,
+            Some((_, PriorityClass::Idle)) => general_stats.idle_processes += 1,
+            _ => {}
+        }
+        if let Some(sched) = process.sched {
+            *general_stats.sched.get_or_insert_default() += sched;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leak_detection_needs_steady_growth_for_the_whole_duration() {
+        const MB: usize = 1024 * 1024;
+        let detection = LeakDetection {
+            enabled: true,
+            mb_per_min: 5.0,
+            duration_secs: 60,
+        };
+        let interval = Duration::from_secs(10);
+        let now = Instant::now();
+        let mut history = ProcessHistory::new(100);
+        // 1 MB per 10 s sample is 6 MB/min, but only 50 s of it so far
+        for i in 0..6 {
+            history.update_memory(*GENERAL_STATS_PID, 100 * MB + i * MB);
+        }
+        assert_eq!(detection.check(&history, interval, None, now), None);
+        history.update_memory(*GENERAL_STATS_PID, 106 * MB);
+        let leak = detection.check(&history, interval, None, now).unwrap();
+        assert!((leak.mb_per_min() - 6.0).abs() < 1e-6);
+        // The start carries over while the growth goes on
+        let later = now + Duration::from_secs(10);
+        history.update_memory(*GENERAL_STATS_PID, 107 * MB);
+        assert_eq!(
+            detection
+                .check(&history, interval, Some(leak), later)
+                .map(|leak| leak.since),
+            Some(leak.since)
+        );
+
+        let mut flat = ProcessHistory::new(100);
+        for i in 0..10 {
+            flat.update_memory(*GENERAL_STATS_PID, 100 * MB + i % 2 * MB);
+        }
+        assert_eq!(detection.check(&flat, interval, None, now), None);
     }
 }
@@ -0,0 +1,293 @@
+//! What an unprivileged tvis can't read, and relaunching it with the privileges it lacks.
+//!
+//! Reads of other users' processes don't fail loudly: the OS hands back empty
+//! paths and zeroes. [`restricted`] tells those processes apart so the UI can
+//! say the figure is missing rather than show it as zero.
+
+use sysinfo::Pid;
+
+/// Figures left empty for a [`restricted`] process
+pub const UNAVAILABLE: &str = imp::UNAVAILABLE;
+
+/// Whether tvis runs as root or as an elevated administrator, checked once
+pub fn is_elevated() -> bool {
+    use std::sync::OnceLock;
+
+    static ELEVATED: OnceLock<bool> = OnceLock::new();
+    *ELEVATED.get_or_init(imp::is_elevated)
+}
+
+/// Whether some metrics of `pid` can't be read without elevation, false for
+/// a process that doesn't exist
+pub fn restricted(pid: Pid) -> bool {
+    !is_elevated() && imp::restricted(pid)
+}
+
+/// Whether [`relaunch_elevated`] is supported here
+pub fn can_relaunch() -> bool {
+    imp::CAN_RELAUNCH && !is_elevated()
+}
+
+/// Starts another tvis with the same arguments as root or administrator,
+/// blocking while the OS asks for the password, so call it off the UI thread.
+/// Returns once the new instance runs, the caller is expected to quit.
+pub fn relaunch_elevated() -> Result<(), String> {
+    if is_elevated() {
+        return Err("tvis already runs elevated".to_string());
+    }
+    imp::relaunch_elevated()
+}
+
+/// Exit status of a privilege prompt, "cancelled" being the usual failure
+#[cfg(any(unix, windows))]
+fn prompt_status(mut command: std::process::Command) -> Result<(), String> {
+    let status = command
+        .status()
+        .map_err(|err| format!("Failed to ask for elevation: {err}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err("Elevation was cancelled or refused".to_string())
+    }
+}
+
+/// `arg` quoted so `CommandLineToArgvW` splits it back out unchanged: inside
+/// quotes, backslashes only escape when they precede a quote, so those runs
+/// and the run before the closing quote are doubled
+#[cfg(any(windows, test))]
+fn argv_quote(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains([' ', '\t', '\n', '\x0b', '"']) {
+        return arg.to_string();
+    }
+    let mut quoted = String::from('"');
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            '"' => {
+                quoted.push_str(&"\\".repeat(backslashes * 2 + 1));
+                quoted.push('"');
+                backslashes = 0;
+            }
+            _ => {
+                quoted.push_str(&"\\".repeat(backslashes));
+                quoted.push(c);
+                backslashes = 0;
+            }
+        }
+    }
+    quoted.push_str(&"\\".repeat(backslashes * 2));
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(unix)]
+mod imp {
+    use super::prompt_status;
+    use std::process::Command;
+    use sysinfo::Pid;
+
+    #[cfg(target_os = "macos")]
+    pub const UNAVAILABLE: &str =
+        "command line, working directory, executable, environment and some memory figures";
+    #[cfg(not(target_os = "macos"))]
+    pub const UNAVAILABLE: &str = "working directory, executable and environment";
+
+    pub const CAN_RELAUNCH: bool = cfg!(any(target_os = "linux", target_os = "macos"));
+
+    pub fn is_elevated() -> bool {
+        // SAFETY: no arguments, always succeeds
+        unsafe { libc::geteuid() == 0 }
+    }
+
+    /// Signal 0 only checks the right to signal, which is what the other
+    /// per-process reads need too
+    pub fn restricted(pid: Pid) -> bool {
+        // SAFETY: signal 0 is never delivered
+        let result = unsafe { libc::kill(pid.as_u32() as libc::pid_t, 0) };
+        result != 0 && std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+    }
+
+    /// `arg` quoted for `sh`
+    fn shell_quote(arg: &str) -> String {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+
+    /// The current command line, quoted for `sh` and detached from the prompt
+    fn detached_command() -> Result<String, String> {
+        let exe = std::env::current_exe()
+            .map_err(|err| format!("Failed to find the tvis executable: {err}"))?;
+        let command = std::iter::once(exe.to_string_lossy().into_owned())
+            .chain(std::env::args().skip(1))
+            .map(|arg| shell_quote(&arg))
+            .collect::<Vec<_>>()
+            .join(" ");
+        Ok(format!("{command} >/dev/null 2>&1 &"))
+    }
+
+    /// pkexec clears the environment, so the display variables are passed
+    /// through `env` for the window to open
+    #[cfg(not(target_os = "macos"))]
+    pub fn relaunch_elevated() -> Result<(), String> {
+        let mut command = Command::new("pkexec");
+        command.arg("env");
+        for var in [
+            "DISPLAY",
+            "XAUTHORITY",
+            "WAYLAND_DISPLAY",
+            "XDG_RUNTIME_DIR",
+        ] {
+            if let Some(value) = std::env::var_os(var) {
+                let mut assignment = std::ffi::OsString::from(format!("{var}="));
+                assignment.push(value);
+                command.arg(assignment);
+            }
+        }
+        command.args(["sh", "-c", &detached_command()?]);
+        prompt_status(command)
+    }
+
+    #[cfg(target_os = "macos")]
+    pub fn relaunch_elevated() -> Result<(), String> {
+        let script = format!(
+            "do shell script \"{}\" with administrator privileges",
+            detached_command()?
+                .replace('\\', r"\\")
+                .replace('"', "\\\"")
+        );
+        let mut command = Command::new("osascript");
+        command.args(["-e", &script]);
+        prompt_status(command)
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use super::prompt_status;
+    use std::process::Command;
+    use sysinfo::Pid;
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows_sys::Win32::Security::{
+        GetTokenInformation, TokenElevation, TOKEN_ELEVATION, TOKEN_QUERY,
+    };
+    use windows_sys::Win32::System::Threading::{
+        GetCurrentProcess, OpenProcess, OpenProcessToken, PROCESS_QUERY_INFORMATION,
+        PROCESS_VM_READ,
+    };
+
+    pub const UNAVAILABLE: &str = "command line, working directory and environment";
+
+    pub const CAN_RELAUNCH: bool = true;
+
+    pub fn is_elevated() -> bool {
+        // SAFETY: the token is checked before use and closed before returning,
+        // `elevation` is sized as passed
+        unsafe {
+            let mut token: HANDLE = std::ptr::null_mut();
+            if OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token) == 0 {
+                return false;
+            }
+            let mut elevation = TOKEN_ELEVATION { TokenIsElevated: 0 };
+            let mut size = 0;
+            let ok = GetTokenInformation(
+                token,
+                TokenElevation,
+                (&mut elevation as *mut TOKEN_ELEVATION).cast(),
+                std::mem::size_of::<TOKEN_ELEVATION>() as u32,
+                &mut size,
+            );
+            CloseHandle(token);
+            ok != 0 && elevation.TokenIsElevated != 0
+        }
+    }
+
+    /// Reading the command line and environment needs this access, which
+    /// other users' and system processes refuse
+    pub fn restricted(pid: Pid) -> bool {
+        // SAFETY: the handle is checked before use and closed right away
+        unsafe {
+            let handle = OpenProcess(PROCESS_QUERY_INFORMATION | PROCESS_VM_READ, 0, pid.as_u32());
+            if handle.is_null() {
+                return std::io::Error::last_os_error().kind()
+                    == std::io::ErrorKind::PermissionDenied;
+            }
+            CloseHandle(handle);
+            false
+        }
+    }
+
+    /// `arg` quoted for PowerShell
+    fn quote(arg: &str) -> String {
+        format!("'{}'", arg.replace('\'', "''"))
+    }
+
+    /// `Start-Process -Verb RunAs` shows the UAC prompt and fails when it's declined.
+    /// It joins `-ArgumentList` with bare spaces, so the arguments are passed as
+    /// one command line already quoted for the new process to split.
+    pub fn relaunch_elevated() -> Result<(), String> {
+        let exe = std::env::current_exe()
+            .map_err(|err| format!("Failed to find the tvis executable: {err}"))?;
+        let mut script = format!(
+            "Start-Process -FilePath {} -Verb RunAs",
+            quote(&exe.to_string_lossy())
+        );
+        let args: Vec<String> = std::env::args()
+            .skip(1)
+            .map(|arg| super::argv_quote(&arg))
+            .collect();
+        if !args.is_empty() {
+            script.push_str(&format!(" -ArgumentList {}", quote(&args.join(" "))));
+        }
+        let mut command = Command::new("powershell");
+        command.args(["-NoProfile", "-NonInteractive", "-Command", &script]);
+        prompt_status(command)
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod imp {
+    use sysinfo::Pid;
+
+    pub const UNAVAILABLE: &str = "";
+
+    pub const CAN_RELAUNCH: bool = false;
+
+    pub fn is_elevated() -> bool {
+        false
+    }
+
+    pub fn restricted(_pid: Pid) -> bool {
+        false
+    }
+
+    pub fn relaunch_elevated() -> Result<(), String> {
+        Err("Relaunching is not supported on this platform".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn own_processes_are_not_restricted() {
+        assert!(!restricted(Pid::from_u32(std::process::id())));
+        // A process that doesn't exist isn't reported as needing elevation
+        assert!(!restricted(Pid::from_u32(u32::MAX / 2)));
+        if cfg!(unix) && !is_elevated() {
+            // init belongs to root
+            assert!(restricted(Pid::from_u32(1)));
+        }
+    }
+
+    #[test]
+    fn arguments_are_quoted_for_command_line_to_argv() {
+        assert_eq!(argv_quote("--interval"), "--interval");
+        assert_eq!(argv_quote(""), r#""""#);
+        assert_eq!(argv_quote("C:\\Program Files\\"), r#""C:\Program Files\\""#);
+        assert_eq!(argv_quote(r#"say "hi""#), r#""say \"hi\"""#);
+        assert_eq!(argv_quote(r#"a\"b"#), r#""a\\\"b""#);
+        // Backslashes not before a quote stay as they are
+        assert_eq!(argv_quote(r"C:\temp"), r"C:\temp");
+    }
+}
@@ -0,0 +1,167 @@
+//! Membership of a cgroup, read from its `cgroup.procs` files.
+//!
+//! Paths are relative to the cgroup hierarchy root (`system.slice/nginx.service`).
+//! The unified (v2) hierarchy is preferred; on hybrid systems the v1 `systemd`
+//! hierarchy is used, which has the same layout of slices, services and scopes.
+
+use sysinfo::Pid;
+
+/// Hierarchy roots tried in order, the first containing a path wins
+#[cfg(target_os = "linux")]
+const ROOTS: [&str; 3] = [
+    "/sys/fs/cgroup",
+    "/sys/fs/cgroup/unified",
+    "/sys/fs/cgroup/systemd",
+];
+
+/// Slice holding system services
+#[cfg(target_os = "linux")]
+const SYSTEM_SLICE: &str = "system.slice";
+
+/// Processes in the cgroup at `path` and every cgroup below it
+pub fn cgroup_pids(path: &str) -> Vec<Pid> {
+    #[cfg(target_os = "linux")]
+    {
+        let mut pids = Vec::new();
+        if let Some(dir) = resolve(path) {
+            collect_pids(&dir, &mut pids);
+        }
+        pids
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = path;
+        Vec::new()
+    }
+}
+
+/// Control group of the systemd unit `unit`, `.service` is assumed without a suffix.
+/// Asks `systemctl`, falling back to the usual place of system services.
+pub fn unit_cgroup(unit: &str) -> String {
+    let unit = if unit.contains('.') {
+        unit.to_string()
+    } else {
+        format!("{unit}.service")
+    };
+    #[cfg(target_os = "linux")]
+    {
+        let control_group = std::process::Command::new("systemctl")
+            .args(["show", "--property=ControlGroup", "--value", &unit])
+            .stderr(std::process::Stdio::null())
+            .output()
+            .ok()
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .filter(|control_group| !control_group.is_empty());
+        if let Some(control_group) = control_group {
+            return normalize(&control_group).to_string();
+        }
+        format!("{SYSTEM_SLICE}/{unit}")
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        unit
+    }
+}
+
+/// Services and scopes of the system slice with at least one process,
+/// as cgroup paths, refreshed at most every few seconds
+pub fn running_services() -> Vec<String> {
+    #[cfg(target_os = "linux")]
+    {
+        use std::sync::Mutex;
+        use std::time::{Duration, Instant};
+
+        const TTL: Duration = Duration::from_secs(5);
+        static CACHE: Mutex<Option<(Instant, Vec<String>)>> = Mutex::new(None);
+
+        let mut cache = CACHE.lock().unwrap();
+        match &*cache {
+            Some((at, services)) if at.elapsed() < TTL => services.clone(),
+            _ => {
+                let services = list_services();
+                *cache = Some((Instant::now(), services.clone()));
+                services
+            }
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        Vec::new()
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn list_services() -> Vec<String> {
+    let Some(slice) = resolve(SYSTEM_SLICE) else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(&slice) else {
+        return Vec::new();
+    };
+    let mut services: Vec<String> = entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name.ends_with(".service") || name.ends_with(".scope"))
+        .map(|name| format!("{SYSTEM_SLICE}/{name}"))
+        .filter(|path| !cgroup_pids(path).is_empty())
+        .collect();
+    services.sort();
+    services
+}
+
+/// `path` without the leading slash systemd and `/proc/<pid>/cgroup` use
+pub fn normalize(path: &str) -> &str {
+    path.trim().trim_matches('/')
+}
+
+/// Unified (v2) cgroup path in the contents of `/proc/<pid>/cgroup`, the
+/// `0::/user.slice/…` line, normalized. Empty for the root.
+pub fn parse_unified(contents: &str) -> Option<&str> {
+    contents
+        .lines()
+        .find_map(|line| line.strip_prefix("0::"))
+        .map(normalize)
+}
+
+#[cfg(target_os = "linux")]
+fn resolve(path: &str) -> Option<std::path::PathBuf> {
+    let path = normalize(path);
+    ROOTS
+        .iter()
+        .map(|root| std::path::Path::new(root).join(path))
+        .find(|dir| dir.join("cgroup.procs").is_file())
+}
+
+#[cfg(target_os = "linux")]
+fn collect_pids(dir: &std::path::Path, pids: &mut Vec<Pid>) {
+    if let Ok(procs) = std::fs::read_to_string(dir.join("cgroup.procs")) {
+        pids.extend(
+            procs
+                .lines()
+                .filter_map(|line| line.trim().parse::<u32>().ok())
+                .map(Pid::from_u32),
+        );
+    }
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.filter_map(Result::ok) {
+        if entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+            collect_pids(&entry.path(), pids);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unified_cgroup_is_read_from_proc() {
+        let hybrid = "12:cpu,cpuacct:/user.slice\n1:name=systemd:/user.slice/session-2.scope\n0::/user.slice/session-2.scope\n";
+        assert_eq!(parse_unified(hybrid), Some("user.slice/session-2.scope"));
+        assert_eq!(parse_unified("0::/\n"), Some(""));
+        assert_eq!(parse_unified("1:name=systemd:/init.scope\n"), None);
+    }
+}
@@ -1,17 +1,22 @@
 use std::fmt;
+use std::sync::Arc;
+
+/// Items per chunk. A clone shares every chunk, and a push after it copies only
+/// the chunk it writes to, so publishing a history every tick stays cheap.
+const CHUNK_LEN: usize = 64;
 
 #[derive(Clone)]
 pub struct CircularBuffer<T> {
-    buffer: Vec<T>,
+    chunks: Vec<Arc<Vec<T>>>,
     write_pos: usize,
     len: usize,
     capacity: usize,
 }
 
-impl<T> CircularBuffer<T> {
+impl<T: Clone> CircularBuffer<T> {
     pub fn new(capacity: usize) -> Self {
         Self {
-            buffer: Vec::with_capacity(capacity),
+            chunks: Vec::with_capacity(capacity.div_ceil(CHUNK_LEN)),
             write_pos: 0,
             len: 0,
             capacity,
@@ -19,37 +24,61 @@ impl<T> CircularBuffer<T> {
     }
 
     pub fn push(&mut self, item: T) {
+        let (chunk, offset) = (self.write_pos / CHUNK_LEN, self.write_pos % CHUNK_LEN);
         if self.len < self.capacity {
-            self.buffer.push(item);
+            if chunk == self.chunks.len() {
+                let len = CHUNK_LEN.min(self.capacity - self.write_pos);
+                self.chunks.push(Arc::new(Vec::with_capacity(len)));
+            }
+            Arc::make_mut(&mut self.chunks[chunk]).push(item);
             self.len += 1;
         } else {
-            self.buffer[self.write_pos] = item;
+            Arc::make_mut(&mut self.chunks[chunk])[offset] = item;
         }
         self.write_pos = (self.write_pos + 1) % self.capacity;
     }
 
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    fn get(&self, index: usize) -> &T {
+        &self.chunks[index / CHUNK_LEN][index % CHUNK_LEN]
+    }
+
+    /// The item pushed last
+    pub fn newest(&self) -> Option<&T> {
+        if self.len == 0 {
+            return None;
+        }
+        Some(self.get((self.write_pos + self.capacity - 1) % self.capacity))
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = &T> {
-        let (head, tail) = if self.len < self.capacity {
-            (0, self.len)
+        let head = if self.len < self.capacity {
+            0
         } else {
-            (self.write_pos, self.capacity)
+            self.write_pos
         };
 
-        self.buffer[head..]
-            .iter()
-            .chain(&self.buffer[..head])
-            .take(tail)
+        (0..self.len).map(move |i| self.get((head + i) % self.capacity))
+    }
+
+    /// Changes the capacity, keeping the newest items that still fit
+    pub fn resize(&mut self, capacity: usize) {
+        let mut resized = Self::new(capacity);
+        for item in self.iter().skip(self.len.saturating_sub(capacity)) {
+            resized.push(item.clone());
+        }
+        *self = resized;
     }
 
-    pub fn as_vec(&self) -> Vec<T>
-    where
-        T: Clone,
-    {
+    pub fn as_vec(&self) -> Vec<T> {
         self.iter().cloned().collect()
     }
 }
 
-impl<T: fmt::Debug + Clone + Default> fmt::Debug for CircularBuffer<T> {
+impl<T: fmt::Debug + Clone> fmt::Debug for CircularBuffer<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_list().entries(self.iter()).finish()
     }
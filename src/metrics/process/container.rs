@@ -0,0 +1,95 @@
+//! Container membership via the cgroup filesystem.
+//!
+//! A process belongs to a container when one of its cgroup path segments carries the
+//! container id (`docker-<id>.scope`, `/docker/<id>`, `libpod-<id>.scope`, ...).
+//! Names are looked up through the `docker`/`podman` CLIs when they are available;
+//! without them containers can still be addressed by id or id prefix.
+
+use std::collections::HashMap;
+use sysinfo::Pid;
+
+/// Length of a full container id
+#[cfg(target_os = "linux")]
+const ID_LEN: usize = 64;
+/// Length of an id as shown by `docker ps`
+pub const SHORT_ID_LEN: usize = 12;
+
+/// Id of the container `pid` runs in, if any
+pub fn container_id(pid: Pid) -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        let cgroup = std::fs::read_to_string(format!("/proc/{pid}/cgroup")).ok()?;
+        parse_container_id(&cgroup).map(str::to_string)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+/// Finds a container id among the segments of a `/proc/<pid>/cgroup` file
+#[cfg(target_os = "linux")]
+fn parse_container_id(cgroup: &str) -> Option<&str> {
+    cgroup
+        .lines()
+        .filter_map(|line| line.rsplit(':').next())
+        .flat_map(|path| path.split('/'))
+        // podman's conmon monitor sits next to the container, not inside it
+        .filter(|segment| !segment.contains("conmon"))
+        .map(|segment| {
+            let segment = segment.strip_suffix(".scope").unwrap_or(segment);
+            segment.rsplit('-').next().unwrap_or(segment)
+        })
+        .find(|segment| segment.len() == ID_LEN && segment.bytes().all(|b| b.is_ascii_hexdigit()))
+}
+
+/// Known container names by full id, refreshed at most every few seconds
+pub fn container_names() -> HashMap<String, String> {
+    #[cfg(target_os = "linux")]
+    {
+        use std::sync::Mutex;
+        use std::time::{Duration, Instant};
+
+        const TTL: Duration = Duration::from_secs(5);
+        static CACHE: Mutex<Option<(Instant, HashMap<String, String>)>> = Mutex::new(None);
+
+        let mut cache = CACHE.lock().unwrap();
+        match &*cache {
+            Some((at, names)) if at.elapsed() < TTL => names.clone(),
+            _ => {
+                let names = ["docker", "podman"]
+                    .into_iter()
+                    .flat_map(list_containers)
+                    .collect::<HashMap<_, _>>();
+                *cache = Some((Instant::now(), names.clone()));
+                names
+            }
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        HashMap::new()
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn list_containers(cli: &str) -> Vec<(String, String)> {
+    let Ok(output) = std::process::Command::new(cli)
+        .args(["ps", "--no-trunc", "--format", "{{.ID}} {{.Names}}"])
+        .stderr(std::process::Stdio::null())
+        .output()
+    else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split_once(' '))
+        .map(|(id, name)| (id.to_string(), name.to_string()))
+        .collect()
+}
+
+/// Whether `container` (a name, full id or id prefix) refers to the container `id`
+pub fn matches(container: &str, id: &str, names: &HashMap<String, String>) -> bool {
+    id.starts_with(container) || names.get(id).is_some_and(|name| name == container)
+}
@@ -0,0 +1,397 @@
+//! Processes killed by a signal. On Linux the exit status of a zombie is read
+//! from `/proc/<pid>/stat` until its parent reaps it, and systemd-coredump is
+//! asked for the core file on demand. A process reaped between two ticks leaves
+//! no status behind, so its exit is looked up in the systemd-coredump list and
+//! the kernel log a moment later, see [`MissedCrashes`].
+
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+use sysinfo::Pid;
+
+/// Wait before searching the logs for a missed exit, so systemd-coredump has
+/// written its entry
+const LOOKUP_DELAY: Duration = Duration::from_secs(2);
+
+/// How long a missed exit is searched for before it stays a plain exit
+const LOOKUP_PATIENCE: Duration = Duration::from_secs(30);
+
+/// Minimum time between two searches of the logs
+const LOOKUP_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How far before an exit was noticed the logs are searched, the crash itself
+/// happened up to a sampling interval earlier
+const LOOKUP_LOOKBACK: Duration = Duration::from_secs(60);
+
+/// Signal that ended a process
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crash {
+    pub signal: i32,
+    /// The kernel wrote a core dump, see [`core_dump`]
+    pub core_dumped: bool,
+}
+
+impl Crash {
+    /// How `pid` ended if it's a zombie killed by a signal, `None` for a live
+    /// process, a normal exit or where the OS doesn't report it
+    pub fn read(pid: Pid) -> Option<Self> {
+        imp::read(pid)
+    }
+
+    /// Decodes a `waitpid` status, `None` for a normal exit
+    pub fn from_wait_status(status: i32) -> Option<Self> {
+        let signal = status & 0x7f;
+        // 0x7f marks a stopped process, not a terminated one
+        (signal != 0 && signal != 0x7f).then_some(Self {
+            signal,
+            core_dumped: status & 0x80 != 0,
+        })
+    }
+
+    /// e.g. "SIGSEGV, core dumped"
+    pub fn describe(&self) -> String {
+        let name = signal_name(self.signal)
+            .map_or_else(|| format!("signal {}", self.signal), str::to_string);
+        if self.core_dumped {
+            format!("{name}, core dumped")
+        } else {
+            name
+        }
+    }
+}
+
+/// Linux name of `signal`, `None` for real-time and unknown signals
+pub fn signal_name(signal: i32) -> Option<&'static str> {
+    const NAMES: [&str; 31] = [
+        "SIGHUP",
+        "SIGINT",
+        "SIGQUIT",
+        "SIGILL",
+        "SIGTRAP",
+        "SIGABRT",
+        "SIGBUS",
+        "SIGFPE",
+        "SIGKILL",
+        "SIGUSR1",
+        "SIGSEGV",
+        "SIGUSR2",
+        "SIGPIPE",
+        "SIGALRM",
+        "SIGTERM",
+        "SIGSTKFLT",
+        "SIGCHLD",
+        "SIGCONT",
+        "SIGSTOP",
+        "SIGTSTP",
+        "SIGTTIN",
+        "SIGTTOU",
+        "SIGURG",
+        "SIGXCPU",
+        "SIGXFSZ",
+        "SIGVTALRM",
+        "SIGPROF",
+        "SIGWINCH",
+        "SIGIO",
+        "SIGPWR",
+        "SIGSYS",
+    ];
+    NAMES
+        .get(usize::try_from(signal).ok()?.checked_sub(1)?)
+        .copied()
+}
+
+/// `exit_code` of a zombie from `/proc/<pid>/stat`, 0 while the process is alive
+pub fn parse_exit_code(stat: &str) -> Option<i32> {
+    // The command name can contain spaces and parentheses, fields resume after the last ')'
+    let fields: Vec<&str> = stat
+        .get(stat.rfind(')')? + 1..)?
+        .split_whitespace()
+        .collect();
+    // `exit_code` is field 52 of proc(5), since Linux 3.5
+    fields.get(52 - 3)?.parse().ok()
+}
+
+/// Crashed processes found in the logs
+type FoundCrashes = Vec<(Pid, Crash)>;
+
+/// Exits noticed after the parent reaped the process, so without a status.
+/// The logs are searched for all of them at once on a background thread.
+#[derive(Debug, Default)]
+pub struct MissedCrashes {
+    /// PID and when it was noticed gone
+    pending: Vec<(Pid, SystemTime)>,
+    /// Result of the running search, behind a lock as `Metrics` is shared
+    search: Option<Mutex<Receiver<FoundCrashes>>>,
+    last_search: Option<Instant>,
+}
+
+impl MissedCrashes {
+    /// Looks for a crash of `pid`, which just exited without a status
+    pub fn watch(&mut self, pid: Pid) {
+        // Only Linux has logs to search
+        if cfg!(target_os = "linux") {
+            self.pending.push((pid, SystemTime::now()));
+        }
+    }
+
+    /// Crashes found since the last call with when their exit was noticed,
+    /// starting a search when one is due
+    pub fn poll(&mut self) -> Vec<(Pid, Crash, SystemTime)> {
+        let mut found: Vec<(Pid, Crash, SystemTime)> = Vec::new();
+        if let Some(search) = &self.search {
+            let received = search.lock().unwrap().try_recv();
+            match received {
+                Ok(crashes) => {
+                    // A later entry of the same PID knows more
+                    for (pid, crash) in crashes {
+                        let Some(&(_, noticed)) =
+                            self.pending.iter().find(|(pending, _)| *pending == pid)
+                        else {
+                            continue;
+                        };
+                        found.retain(|(crashed, ..)| *crashed != pid);
+                        found.push((pid, crash, noticed));
+                    }
+                    self.pending.retain(|(pid, noticed)| {
+                        !found.iter().any(|(crashed, ..)| crashed == pid)
+                            && noticed.elapsed().unwrap_or_default() < LOOKUP_PATIENCE
+                    });
+                    self.search = None;
+                }
+                Err(TryRecvError::Empty) => return found,
+                Err(TryRecvError::Disconnected) => self.search = None,
+            }
+        }
+        let oldest_due = self
+            .pending
+            .iter()
+            .map(|(_, noticed)| *noticed)
+            .filter(|noticed| noticed.elapsed().unwrap_or_default() >= LOOKUP_DELAY)
+            .min();
+        let searched_recently = self
+            .last_search
+            .is_some_and(|last| last.elapsed() < LOOKUP_INTERVAL);
+        if let Some(oldest) = oldest_due.filter(|_| !searched_recently) {
+            self.last_search = Some(Instant::now());
+            let since = oldest.checked_sub(LOOKUP_LOOKBACK).unwrap_or(oldest);
+            let (sender, receiver) = mpsc::channel();
+            std::thread::spawn(move || {
+                let _ = sender.send(imp::recent_crashes(since));
+            });
+            self.search = Some(Mutex::new(receiver));
+        }
+        found
+    }
+}
+
+/// Crashes listed by `coredumpctl --no-legend list`, oldest first, e.g.
+/// `Mon 2024-03-04 10:12:13 CET 12345 1000 1000 SIGSEGV present /usr/bin/app 1.2M`
+pub fn parse_coredump_list(list: &str) -> Vec<(Pid, Crash)> {
+    list.lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            // The time takes a varying number of fields, the signal is the first known name
+            let at = fields
+                .iter()
+                .position(|field| signal_number(field).is_some())?;
+            // PID UID GID SIG COREFILE
+            let pid = fields.get(at.checked_sub(3)?)?.parse().ok()?;
+            let core_dumped = fields
+                .get(at + 1)
+                .is_some_and(|corefile| *corefile != "none");
+            Some((
+                Pid::from_u32(pid),
+                Crash {
+                    signal: signal_number(fields[at])?,
+                    core_dumped,
+                },
+            ))
+        })
+        .collect()
+}
+
+/// Crashes reported by the kernel in `journalctl -k` or `dmesg` output, e.g.
+/// `app[12345]: segfault at 0 ip …` or `traps: app[12345] trap divide error …`
+pub fn parse_kernel_log(log: &str) -> Vec<(Pid, Crash)> {
+    const TRAPS: [(&str, &str); 5] = [
+        ("segfault at", "SIGSEGV"),
+        ("general protection fault", "SIGSEGV"),
+        ("trap divide error", "SIGFPE"),
+        ("trap invalid opcode", "SIGILL"),
+        ("trap int3", "SIGTRAP"),
+    ];
+    log.lines()
+        .filter_map(|line| {
+            let (_, name) = TRAPS.iter().find(|(trap, _)| line.contains(trap))?;
+            // The PID in brackets right after the command name
+            let pid = line.split('[').skip(1).find_map(|part| {
+                let (pid, rest) = part.split_once(']')?;
+                rest.starts_with([':', ' ']).then(|| pid.parse().ok())?
+            })?;
+            Some((
+                Pid::from_u32(pid),
+                Crash {
+                    signal: signal_number(name)?,
+                    core_dumped: false,
+                },
+            ))
+        })
+        .collect()
+}
+
+/// Inverse of [`signal_name`]
+fn signal_number(name: &str) -> Option<i32> {
+    (1..=31).find(|&signal| signal_name(signal) == Some(name))
+}
+
+/// Where the core dump of `pid` is stored, as reported by `coredumpctl info`.
+/// Dumps are written a moment after the process died, so a missing one may
+/// still appear.
+pub fn core_dump(pid: Pid) -> Result<String, String> {
+    imp::core_dump(pid)
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use super::{parse_coredump_list, parse_exit_code, parse_kernel_log, Crash};
+    use std::process::Command;
+    use std::time::{SystemTime, UNIX_EPOCH};
+    use sysinfo::Pid;
+
+    /// Crashes since `since` in the kernel log, then in the systemd-coredump
+    /// list, which knows about core dumps and so takes precedence for a PID
+    pub fn recent_crashes(since: SystemTime) -> Vec<(Pid, Crash)> {
+        let since = format!(
+            "--since=@{}",
+            since
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs()
+        );
+        let output = |program: &str, args: &[&str]| {
+            Command::new(program)
+                .args(args)
+                .output()
+                .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+                .unwrap_or_default()
+        };
+        let mut crashes =
+            parse_kernel_log(&output("journalctl", &["-k", "--no-pager", "-q", &since]));
+        crashes.extend(parse_coredump_list(&output(
+            "coredumpctl",
+            &["--no-pager", "--no-legend", &since, "list"],
+        )));
+        crashes
+    }
+
+    pub fn read(pid: Pid) -> Option<Crash> {
+        let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+        // Only a zombie has its exit status filled in
+        let state = stat
+            .get(stat.rfind(')')? + 1..)?
+            .split_whitespace()
+            .next()?;
+        if state != "Z" {
+            return None;
+        }
+        Crash::from_wait_status(parse_exit_code(&stat)?)
+    }
+
+    pub fn core_dump(pid: Pid) -> Result<String, String> {
+        let output = Command::new("coredumpctl")
+            .args(["--no-pager", "info", &pid.to_string()])
+            .output()
+            .map_err(|err| format!("Could not run coredumpctl: {err}"))?;
+        if !output.status.success() {
+            return Err(format!("No core dump of PID {pid} found"));
+        }
+        let info = String::from_utf8_lossy(&output.stdout);
+        let field = |name: &str| {
+            info.lines()
+                .find_map(|line| line.trim_start().strip_prefix(name))
+                .map(|value| value.trim().to_string())
+        };
+        // e.g. "Storage: /var/lib/systemd/coredump/core.app.1000.….zst (present)"
+        field("Storage:")
+            .and_then(|storage| {
+                storage
+                    .strip_suffix("(present)")
+                    .or((!storage.ends_with(')') && storage != "none").then_some(&storage))
+                    .map(|path| path.trim().to_string())
+            })
+            .ok_or_else(|| format!("The core dump of PID {pid} was not stored"))
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod imp {
+    use super::Crash;
+    use std::time::SystemTime;
+    use sysinfo::Pid;
+
+    pub fn read(_pid: Pid) -> Option<Crash> {
+        None
+    }
+
+    pub fn core_dump(_pid: Pid) -> Result<String, String> {
+        Err("Core dumps are only looked up on Linux".to_string())
+    }
+
+    pub fn recent_crashes(_since: SystemTime) -> Vec<(Pid, Crash)> {
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exit_status_is_decoded_from_proc_stat() {
+        let stat = format!("4242 (my (app) x) Z 1{} 139", " 0".repeat(47));
+        assert_eq!(parse_exit_code(&stat), Some(139));
+        assert_eq!(parse_exit_code("4242 (app) S 1 0"), None);
+
+        let crash = Crash::from_wait_status(139).unwrap();
+        assert_eq!(crash.signal, 11);
+        assert!(crash.core_dumped);
+        assert_eq!(crash.describe(), "SIGSEGV, core dumped");
+        assert_eq!(Crash::from_wait_status(9).unwrap().describe(), "SIGKILL");
+        // Exit code 1, not a signal
+        assert_eq!(Crash::from_wait_status(1 << 8), None);
+        assert_eq!(signal_name(34), None);
+    }
+
+    #[test]
+    fn missed_crashes_are_found_in_the_logs() {
+        let list = "Mon 2024-03-04 10:12:13 CET 4242 1000 1000 SIGSEGV present /usr/bin/app 1.2M\n\
+                    Mon 2024-03-04 10:12:20 CET 4243 1000 1000 SIGABRT none /usr/bin/app -\n";
+        assert_eq!(
+            parse_coredump_list(list),
+            vec![
+                (
+                    Pid::from_u32(4242),
+                    Crash {
+                        signal: 11,
+                        core_dumped: true
+                    }
+                ),
+                (
+                    Pid::from_u32(4243),
+                    Crash {
+                        signal: 6,
+                        core_dumped: false
+                    }
+                ),
+            ]
+        );
+        let log = "Mar 04 10:12:13 host kernel: my app[4244]: segfault at 0 ip 000055d1 sp 00007ffd error 4\n\
+                   Mar 04 10:12:14 host kernel: traps: app[4245] trap divide error ip:55d1 sp:7ffd error:0\n\
+                   Mar 04 10:12:15 host kernel: usb 1-1: new device [1234]\n";
+        let crashed: Vec<(u32, i32)> = parse_kernel_log(log)
+            .into_iter()
+            .map(|(pid, crash)| (pid.as_u32(), crash.signal))
+            .collect();
+        assert_eq!(crashed, vec![(4244, 11), (4245, 8)]);
+    }
+}
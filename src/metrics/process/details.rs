@@ -0,0 +1,58 @@
+use super::Priority;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind, Users};
+
+/// What identifies a running instance of a binary, read on demand
+/// since the sampler does not keep it
+#[derive(Debug, Clone, Default)]
+pub struct ProcessDetails {
+    pub cmd: Vec<String>,
+    pub cwd: Option<PathBuf>,
+    pub exe: Option<PathBuf>,
+    pub user: Option<String>,
+    pub started_at: Option<SystemTime>,
+    /// `KEY=value` pairs, only read when asked for
+    pub environ: Option<Vec<String>>,
+    pub priority: Priority,
+}
+
+impl ProcessDetails {
+    /// Reads the details of `pid`, None once it exited.
+    /// Fields the OS does not let tvis read are left empty.
+    pub fn collect(pid: Pid, with_environ: bool) -> Option<Self> {
+        let mut refresh_kind = ProcessRefreshKind::nothing()
+            .with_cmd(UpdateKind::Always)
+            .with_cwd(UpdateKind::Always)
+            .with_exe(UpdateKind::Always)
+            .with_user(UpdateKind::Always);
+        if with_environ {
+            refresh_kind = refresh_kind.with_environ(UpdateKind::Always);
+        }
+        let mut system = System::new();
+        system.refresh_processes_specifics(ProcessesToUpdate::Some(&[pid]), true, refresh_kind);
+        let process = system.process(pid)?;
+
+        let user = process.user_id().map(|uid| {
+            Users::new_with_refreshed_list()
+                .get_user_by_id(uid)
+                .map_or_else(|| uid.to_string(), |user| user.name().to_string())
+        });
+        let lossy = |values: &[std::ffi::OsString]| {
+            values
+                .iter()
+                .map(|value| value.to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+        };
+        Some(Self {
+            cmd: lossy(process.cmd()),
+            cwd: process.cwd().map(PathBuf::from),
+            exe: process.exe().map(PathBuf::from),
+            user,
+            started_at: (process.start_time() > 0)
+                .then(|| UNIX_EPOCH + Duration::from_secs(process.start_time())),
+            environ: with_environ.then(|| lossy(process.environ())),
+            priority: Priority::read(pid),
+        })
+    }
+}
@@ -0,0 +1,406 @@
+//! Syscall and TCP byte counters per process from small eBPF programs (Linux,
+//! `ebpf` feature). Needs root, or `CAP_BPF` and `CAP_PERFMON`.
+//!
+//! Each program adds to a hash map keyed by the thread group id: 1 on every
+//! `sys_enter` raw tracepoint, the size passed to `tcp_sendmsg` and the bytes
+//! copied to user space by `tcp_cleanup_rbuf`, both through kprobes like bcc's
+//! `tcptop`. The programs are assembled here and loaded with the `bpf` syscall,
+//! so no BPF toolchain is needed to build tvis. The maps evict the least
+//! recently updated processes once full.
+//!
+//! The programs are loaded on the first read and stay attached until tvis exits.
+
+use std::ffi::CString;
+use std::io;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::sync::OnceLock;
+use sysinfo::Pid;
+
+/// Processes tracked by each map before the least recently updated are evicted
+const MAX_PROCESSES: u32 = 16384;
+
+const BPF_MAP_CREATE: libc::c_long = 0;
+const BPF_MAP_LOOKUP_ELEM: libc::c_long = 1;
+const BPF_PROG_LOAD: libc::c_long = 5;
+const BPF_RAW_TRACEPOINT_OPEN: libc::c_long = 17;
+
+const BPF_MAP_TYPE_LRU_HASH: u32 = 9;
+const BPF_PROG_TYPE_KPROBE: u32 = 2;
+const BPF_PROG_TYPE_RAW_TRACEPOINT: u32 = 17;
+
+const BPF_FUNC_MAP_LOOKUP_ELEM: i32 = 1;
+const BPF_FUNC_MAP_UPDATE_ELEM: i32 = 2;
+const BPF_FUNC_GET_CURRENT_PID_TGID: i32 = 14;
+
+const PERF_FLAG_FD_CLOEXEC: libc::c_ulong = 8;
+const PERF_EVENT_IOC_ENABLE: libc::c_ulong = 0x2400;
+const PERF_EVENT_IOC_SET_BPF: libc::c_ulong = 0x4004_2408;
+
+/// Offsets in `struct pt_regs` of the third and second function arguments
+#[cfg(target_arch = "x86_64")]
+const ARG_OFFSETS: Option<(i16, i16)> = Some((96, 104));
+#[cfg(target_arch = "aarch64")]
+const ARG_OFFSETS: Option<(i16, i16)> = Some((16, 8));
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+const ARG_OFFSETS: Option<(i16, i16)> = None;
+
+/// Cumulative counts of one process since the programs were loaded
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EbpfCounts {
+    pub syscalls: u64,
+    /// `None` when the TCP probes could not be attached
+    pub tcp_sent_bytes: Option<u64>,
+    pub tcp_received_bytes: Option<u64>,
+}
+
+/// Attached programs with the maps they count into
+#[derive(Debug)]
+pub struct Probes {
+    syscalls: Counter,
+    tcp: Result<(Counter, Counter), String>,
+}
+
+impl Probes {
+    fn load() -> Result<Self, String> {
+        let syscalls = Counter::raw_tracepoint("sys_enter")
+            .map_err(|err| format!("Failed to count syscalls: {err}"))?;
+        let tcp = match ARG_OFFSETS {
+            Some((size, copied)) => Counter::kprobe("tcp_sendmsg", Source::Argument(size))
+                .and_then(|sent| {
+                    let received =
+                        Counter::kprobe("tcp_cleanup_rbuf", Source::PositiveInt(copied))?;
+                    Ok((sent, received))
+                })
+                .map_err(|err| format!("Failed to count TCP bytes: {err}")),
+            None => Err("TCP counters are not supported on this architecture".to_string()),
+        };
+        if let Err(err) = &tcp {
+            log::warn!("{err}");
+        }
+        Ok(Self { syscalls, tcp })
+    }
+
+    pub fn read(&self, pid: Pid) -> EbpfCounts {
+        let tcp = self.tcp.as_ref().ok();
+        EbpfCounts {
+            syscalls: self.syscalls.get(pid),
+            tcp_sent_bytes: tcp.map(|(sent, _)| sent.get(pid)),
+            tcp_received_bytes: tcp.map(|(_, received)| received.get(pid)),
+        }
+    }
+
+    /// Why the TCP counters are missing, e.g. a kernel built without kprobes
+    pub fn tcp_error(&self) -> Option<&str> {
+        self.tcp.as_ref().err().map(String::as_str)
+    }
+}
+
+/// The loaded programs, or why they could not be loaded. Loads them on the first call.
+pub fn probes() -> Result<&'static Probes, &'static str> {
+    static PROBES: OnceLock<Result<Probes, String>> = OnceLock::new();
+    PROBES
+        .get_or_init(|| {
+            let probes = Probes::load();
+            if let Err(err) = &probes {
+                log::warn!("eBPF counters unavailable: {err}");
+            }
+            probes
+        })
+        .as_ref()
+        .map_err(String::as_str)
+}
+
+/// What a program adds to the count of the current process
+enum Source {
+    One,
+    /// Function argument at this offset of the registers
+    Argument(i16),
+    /// `int` argument at this offset, skipped unless positive
+    PositiveInt(i16),
+}
+
+#[derive(Debug)]
+struct Counter {
+    map: OwnedFd,
+    _program: OwnedFd,
+    /// Raw tracepoint or perf event the program is attached through
+    _attachment: OwnedFd,
+}
+
+impl Counter {
+    fn raw_tracepoint(name: &str) -> io::Result<Self> {
+        let map = create_map()?;
+        let program = load_program(BPF_PROG_TYPE_RAW_TRACEPOINT, &program(&map, Source::One))?;
+        let name = CString::new(name).map_err(io::Error::other)?;
+        let attr = RawTracepointAttr {
+            name: name.as_ptr() as u64,
+            prog_fd: program.as_raw_fd() as u32,
+            _pad: 0,
+        };
+        let attachment = bpf_fd(BPF_RAW_TRACEPOINT_OPEN, &attr)?;
+        Ok(Self {
+            map,
+            _program: program,
+            _attachment: attachment,
+        })
+    }
+
+    fn kprobe(function: &str, source: Source) -> io::Result<Self> {
+        let map = create_map()?;
+        let program = load_program(BPF_PROG_TYPE_KPROBE, &program(&map, source))?;
+        let kprobe_type = std::fs::read_to_string("/sys/bus/event_source/devices/kprobe/type")
+            .map_err(|err| io::Error::new(err.kind(), format!("no kprobe support: {err}")))?;
+        let function = CString::new(function).map_err(io::Error::other)?;
+        let attr = PerfEventAttr {
+            kind: kprobe_type.trim().parse().map_err(io::Error::other)?,
+            size: std::mem::size_of::<PerfEventAttr>() as u32,
+            config1: function.as_ptr() as u64,
+            ..Default::default()
+        };
+        // SAFETY: `attr` outlives the call and `size` is its own size
+        let fd = unsafe {
+            libc::syscall(
+                libc::SYS_perf_event_open,
+                &attr as *const PerfEventAttr,
+                -1,
+                0,
+                -1,
+                PERF_FLAG_FD_CLOEXEC,
+            )
+        };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: the kernel just returned this descriptor
+        let attachment = unsafe { OwnedFd::from_raw_fd(fd as i32) };
+        for (request, arg) in [
+            (PERF_EVENT_IOC_SET_BPF, program.as_raw_fd()),
+            (PERF_EVENT_IOC_ENABLE, 0),
+        ] {
+            // SAFETY: both requests take an integer argument
+            if unsafe { libc::ioctl(attachment.as_raw_fd(), request, arg) } < 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        Ok(Self {
+            map,
+            _program: program,
+            _attachment: attachment,
+        })
+    }
+
+    /// Count of `pid`, 0 when it has none
+    fn get(&self, pid: Pid) -> u64 {
+        let key = pid.as_u32();
+        let mut value = 0u64;
+        let attr = MapElemAttr {
+            map_fd: self.map.as_raw_fd() as u32,
+            _pad: 0,
+            key: &key as *const u32 as u64,
+            value: &mut value as *mut u64 as u64,
+            flags: 0,
+        };
+        bpf(BPF_MAP_LOOKUP_ELEM, &attr).map_or(0, |_| value)
+    }
+}
+
+#[repr(C)]
+struct MapCreateAttr {
+    map_type: u32,
+    key_size: u32,
+    value_size: u32,
+    max_entries: u32,
+    map_flags: u32,
+}
+
+#[repr(C)]
+struct ProgLoadAttr {
+    prog_type: u32,
+    insn_cnt: u32,
+    insns: u64,
+    license: u64,
+    log_level: u32,
+    log_size: u32,
+    log_buf: u64,
+    kern_version: u32,
+    prog_flags: u32,
+}
+
+#[repr(C)]
+struct MapElemAttr {
+    map_fd: u32,
+    _pad: u32,
+    key: u64,
+    value: u64,
+    flags: u64,
+}
+
+#[repr(C)]
+struct RawTracepointAttr {
+    name: u64,
+    prog_fd: u32,
+    _pad: u32,
+}
+
+/// `struct perf_event_attr` up to `config2`, the fields a kprobe needs
+#[repr(C)]
+#[derive(Default)]
+struct PerfEventAttr {
+    kind: u32,
+    size: u32,
+    config: u64,
+    sample_period: u64,
+    sample_type: u64,
+    read_format: u64,
+    flags: u64,
+    wakeup_events: u32,
+    bp_type: u32,
+    config1: u64,
+    config2: u64,
+}
+
+/// Runs the `bpf` command `cmd`
+fn bpf<T>(cmd: libc::c_long, attr: &T) -> io::Result<libc::c_long> {
+    // SAFETY: `attr` is one of the `#[repr(C)]` prefixes of `union bpf_attr`
+    // above, the kernel reads only `size_of::<T>()` bytes of it
+    let result = unsafe {
+        libc::syscall(
+            libc::SYS_bpf,
+            cmd,
+            attr as *const T,
+            std::mem::size_of::<T>() as u32,
+        )
+    };
+    if result < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(result)
+}
+
+/// Runs a `bpf` command that creates a descriptor
+fn bpf_fd<T>(cmd: libc::c_long, attr: &T) -> io::Result<OwnedFd> {
+    let fd = bpf(cmd, attr)?;
+    // SAFETY: the kernel just returned this descriptor
+    Ok(unsafe { OwnedFd::from_raw_fd(fd as i32) })
+}
+
+fn create_map() -> io::Result<OwnedFd> {
+    bpf_fd(
+        BPF_MAP_CREATE,
+        &MapCreateAttr {
+            map_type: BPF_MAP_TYPE_LRU_HASH,
+            key_size: 4,
+            value_size: 8,
+            max_entries: MAX_PROCESSES,
+            map_flags: 0,
+        },
+    )
+}
+
+fn load_program(prog_type: u32, insns: &[Insn]) -> io::Result<OwnedFd> {
+    let license = c"GPL";
+    let mut log = vec![0u8; 4096];
+    let attr = ProgLoadAttr {
+        prog_type,
+        insn_cnt: insns.len() as u32,
+        insns: insns.as_ptr() as u64,
+        license: license.as_ptr() as u64,
+        log_level: 1,
+        log_size: log.len() as u32,
+        log_buf: log.as_mut_ptr() as u64,
+        kern_version: 0,
+        prog_flags: 0,
+    };
+    bpf_fd(BPF_PROG_LOAD, &attr).map_err(|err| {
+        let verifier = String::from_utf8_lossy(&log);
+        let verifier = verifier.trim_end_matches('\0').trim();
+        if verifier.is_empty() {
+            err
+        } else {
+            io::Error::new(err.kind(), format!("{err}: {verifier}"))
+        }
+    })
+}
+
+/// One BPF instruction, see `struct bpf_insn`
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Insn {
+    code: u8,
+    /// Destination register in the low nibble, source in the high one
+    regs: u8,
+    off: i16,
+    imm: i32,
+}
+
+const fn insn(code: u8, dst: u8, src: u8, off: i16, imm: i32) -> Insn {
+    Insn {
+        code,
+        regs: dst | (src << 4),
+        off,
+        imm,
+    }
+}
+
+/// `map[current tgid] += value`, inserting the key on its first event:
+///
+/// ```text
+/// r6 = value
+/// r0 = get_current_pid_tgid() >> 32; *(u32 *)(r10 - 4) = r0
+/// r0 = map_lookup_elem(map, r10 - 4)
+/// if r0 { lock *(u64 *)r0 += r6 } else { *(u64 *)(r10 - 16) = r6; map_update_elem(map, r10 - 4, r10 - 16, BPF_ANY) }
+/// return 0
+/// ```
+fn program(map: &OwnedFd, source: Source) -> Vec<Insn> {
+    const R0: u8 = 0;
+    const R1: u8 = 1;
+    const R2: u8 = 2;
+    const R3: u8 = 3;
+    const R4: u8 = 4;
+    const R6: u8 = 6;
+    const R10: u8 = 10;
+    let map_fd = map.as_raw_fd();
+    let load_map = |dst| {
+        // 64-bit immediate over two instructions, src 1 marks it as a map descriptor
+        [insn(0x18, dst, 1, 0, map_fd), insn(0, 0, 0, 0, 0)]
+    };
+
+    let mut insns = match source {
+        Source::One => vec![insn(0xb7, R6, 0, 0, 1)],
+        Source::Argument(offset) => vec![insn(0x79, R6, R1, offset, 0)],
+        Source::PositiveInt(offset) => vec![
+            insn(0x79, R6, R1, offset, 0),
+            // Sign-extend the low 32 bits, then skip to the exit unless positive
+            insn(0x67, R6, 0, 0, 32),
+            insn(0xc7, R6, 0, 0, 32),
+            insn(0xd5, R6, 0, 20, 0),
+        ],
+    };
+    insns.extend([
+        insn(0x85, 0, 0, 0, BPF_FUNC_GET_CURRENT_PID_TGID),
+        insn(0x77, R0, 0, 0, 32),
+        insn(0x63, R10, R0, -4, 0),
+    ]);
+    insns.extend(load_map(R1));
+    insns.extend([
+        insn(0xbf, R2, R10, 0, 0),
+        insn(0x07, R2, 0, 0, -4),
+        insn(0x85, 0, 0, 0, BPF_FUNC_MAP_LOOKUP_ELEM),
+        insn(0x15, R0, 0, 2, 0),
+        insn(0xdb, R0, R6, 0, 0),
+        insn(0x05, 0, 0, 9, 0),
+        insn(0x7b, R10, R6, -16, 0),
+    ]);
+    insns.extend(load_map(R1));
+    insns.extend([
+        insn(0xbf, R2, R10, 0, 0),
+        insn(0x07, R2, 0, 0, -4),
+        insn(0xbf, R3, R10, 0, 0),
+        insn(0x07, R3, 0, 0, -16),
+        insn(0xb7, R4, 0, 0, 0),
+        insn(0x85, 0, 0, 0, BPF_FUNC_MAP_UPDATE_ELEM),
+        insn(0xb7, R0, 0, 0, 0),
+        insn(0x95, 0, 0, 0, 0),
+    ]);
+    insns
+}
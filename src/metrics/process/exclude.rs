@@ -0,0 +1,74 @@
+use super::ProcessIdentifier;
+use serde::{Deserialize, Serialize};
+use sysinfo::Process;
+
+/// Limits which descendants are pulled in for a monitored identifier, and
+/// optionally adds its ancestors. An excluded child is skipped together with
+/// its own descendants.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExcludeRule {
+    pub identifier: ProcessIdentifier,
+    /// Glob patterns (`*` and `?`) matched against child process names
+    pub name_globs: Vec<String>,
+    pub pids: Vec<u32>,
+    /// Deepest descendant level kept; the matched processes are level 0
+    pub max_depth: Option<usize>,
+    /// Also monitor the parent chain of the matched processes, up to the root
+    #[serde(default)]
+    pub ancestors: bool,
+}
+
+impl ExcludeRule {
+    pub fn new(identifier: ProcessIdentifier) -> Self {
+        Self {
+            identifier,
+            name_globs: Vec::new(),
+            pids: Vec::new(),
+            max_depth: None,
+            ancestors: false,
+        }
+    }
+
+    /// Whether `process`, found at `depth` below a matched process, should be skipped
+    pub fn excludes(&self, process: &Process, depth: usize) -> bool {
+        if self.max_depth.is_some_and(|max_depth| depth > max_depth) {
+            return true;
+        }
+        if self.pids.contains(&process.pid().as_u32()) {
+            return true;
+        }
+        let name = process.name().to_string_lossy();
+        self.name_globs
+            .iter()
+            .any(|pattern| glob_match(pattern, &name))
+    }
+}
+
+/// Matches `text` against a pattern where `*` is any run and `?` is any single char
+pub(super) fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, star_t)) => {
+                    p = star + 1;
+                    t = star_t + 1;
+                    backtrack = Some((star, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
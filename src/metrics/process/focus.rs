@@ -0,0 +1,100 @@
+//! Process owning the focused window, from the platform window system.
+
+use sysinfo::Pid;
+
+/// Owner of the focused window, `None` when it can't be determined
+/// (no window focused, Wayland without XWayland, unsupported platform)
+pub fn focused_pid() -> Option<Pid> {
+    imp::focused_pid()
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use std::process::Command;
+    use sysinfo::Pid;
+
+    /// `_NET_WM_PID` of the `_NET_ACTIVE_WINDOW`, read with `xprop`
+    pub fn focused_pid() -> Option<Pid> {
+        // _NET_ACTIVE_WINDOW(WINDOW): window id # 0x3a00007
+        let active = xprop(&["-root", "_NET_ACTIVE_WINDOW"])?;
+        let window = active.rsplit(' ').next()?.trim();
+        if window == "0x0" {
+            return None;
+        }
+        // _NET_WM_PID(CARDINAL) = 12345
+        let pid = xprop(&["-id", window, "_NET_WM_PID"])?;
+        pid.rsplit('=')
+            .next()?
+            .trim()
+            .parse()
+            .ok()
+            .map(Pid::from_u32)
+    }
+
+    fn xprop(args: &[&str]) -> Option<String> {
+        let output = Command::new("xprop").args(args).output().ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod imp {
+    use std::process::Command;
+    use sysinfo::Pid;
+
+    /// PID of the frontmost application, read with `lsappinfo`
+    pub fn focused_pid() -> Option<Pid> {
+        let front = lsappinfo(&["front"])?;
+        // "pid"=12345
+        let info = lsappinfo(&["info", "-only", "pid", front.trim()])?;
+        info.rsplit('=')
+            .next()?
+            .trim()
+            .parse()
+            .ok()
+            .map(Pid::from_u32)
+    }
+
+    fn lsappinfo(args: &[&str]) -> Option<String> {
+        let output = Command::new("lsappinfo").args(args).output().ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use sysinfo::Pid;
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        GetForegroundWindow, GetWindowThreadProcessId,
+    };
+
+    /// Owner of `GetForegroundWindow`
+    pub fn focused_pid() -> Option<Pid> {
+        // SAFETY: the window handle is checked before use and the PID is
+        // written to a local the call outlives.
+        unsafe {
+            let window = GetForegroundWindow();
+            if window.is_null() {
+                return None;
+            }
+            let mut pid = 0;
+            GetWindowThreadProcessId(window, &mut pid);
+            (pid != 0).then(|| Pid::from_u32(pid))
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+mod imp {
+    use sysinfo::Pid;
+
+    pub fn focused_pid() -> Option<Pid> {
+        None
+    }
+}
@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+#[cfg(target_os = "linux")]
+use std::time::Instant;
+use sysinfo::Pid;
+
+/// GPU load of a single process
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GpuUsage {
+    /// Busy share of the GPU engines in percent
+    pub utilization: f32,
+    /// Video memory in bytes
+    pub memory: usize,
+}
+
+/// Per-process GPU sampling.
+/// Uses NVML when built natively with the `nvml` feature and falls back to the DRM `fdinfo`
+/// counters on Linux (amdgpu, i915, ...).
+#[derive(Default)]
+pub struct GpuMonitor {
+    #[cfg(all(feature = "nvml", not(target_arch = "wasm32")))]
+    nvml: Option<nvml::NvmlBackend>,
+    #[cfg(target_os = "linux")]
+    drm: drm::DrmBackend,
+    /// Usage collected on the current tick
+    usage: HashMap<Pid, GpuUsage>,
+}
+
+impl std::fmt::Debug for GpuMonitor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GpuMonitor")
+            .field("usage", &self.usage)
+            .finish()
+    }
+}
+
+impl GpuMonitor {
+    /// Drops the previous tick's readings and refreshes the device-wide backends
+    pub fn begin_tick(&mut self) {
+        self.usage.clear();
+        #[cfg(all(feature = "nvml", not(target_arch = "wasm32")))]
+        {
+            let nvml = self.nvml.get_or_insert_with(nvml::NvmlBackend::new);
+            self.usage.extend(nvml.sample());
+        }
+    }
+
+    pub fn usage(&mut self, pid: Pid) -> GpuUsage {
+        if let Some(usage) = self.usage.get(&pid) {
+            return *usage;
+        }
+        #[cfg(target_os = "linux")]
+        {
+            let usage = self.drm.sample(pid);
+            self.usage.insert(pid, usage);
+            usage
+        }
+        #[cfg(not(target_os = "linux"))]
+        GpuUsage::default()
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod drm {
+    use super::{GpuUsage, Instant};
+    use std::collections::HashMap;
+    use sysinfo::Pid;
+
+    /// Busy-time counters from the previous sample of a process
+    struct EngineSample {
+        busy_ns: u64,
+        taken_at: Instant,
+    }
+
+    #[derive(Default)]
+    pub struct DrmBackend {
+        previous: HashMap<Pid, EngineSample>,
+    }
+
+    impl DrmBackend {
+        /// Reads `/proc/<pid>/fdinfo` DRM client stats and turns busy-time deltas into a percentage
+        pub fn sample(&mut self, pid: Pid) -> GpuUsage {
+            let Ok(entries) = std::fs::read_dir(format!("/proc/{pid}/fdinfo")) else {
+                self.previous.remove(&pid);
+                return GpuUsage::default();
+            };
+
+            // Several fds can share one DRM client, count each client once
+            let mut clients: HashMap<String, (u64, usize)> = HashMap::new();
+            for entry in entries.flatten() {
+                let Ok(content) = std::fs::read_to_string(entry.path()) else {
+                    continue;
+                };
+                let mut client_id = None;
+                let mut busy_ns = 0;
+                let mut memory = 0;
+                for line in content.lines() {
+                    let Some((key, value)) = line.split_once(':') else {
+                        continue;
+                    };
+                    let value = value.trim();
+                    if key == "drm-client-id" {
+                        client_id = Some(value.to_string());
+                    } else if key.starts_with("drm-engine-")
+                        && !key.starts_with("drm-engine-capacity")
+                    {
+                        busy_ns += parse_number(value);
+                    } else if key == "drm-memory-vram" || key == "drm-resident-vram" {
+                        memory += parse_number(value) as usize * unit_multiplier(value);
+                    }
+                }
+                if let Some(client_id) = client_id {
+                    clients.insert(client_id, (busy_ns, memory));
+                }
+            }
+            if clients.is_empty() {
+                self.previous.remove(&pid);
+                return GpuUsage::default();
+            }
+
+            let busy_ns: u64 = clients.values().map(|(busy, _)| busy).sum();
+            let memory = clients.values().map(|(_, memory)| memory).sum();
+            let now = Instant::now();
+            let utilization = self
+                .previous
+                .get(&pid)
+                .map(|previous| {
+                    let elapsed = now.duration_since(previous.taken_at).as_nanos() as f64;
+                    let busy = busy_ns.saturating_sub(previous.busy_ns) as f64;
+                    if elapsed > 0.0 {
+                        (busy / elapsed * 100.0) as f32
+                    } else {
+                        0.0
+                    }
+                })
+                .unwrap_or(0.0);
+            self.previous.insert(
+                pid,
+                EngineSample {
+                    busy_ns,
+                    taken_at: now,
+                },
+            );
+
+            GpuUsage {
+                utilization,
+                memory,
+            }
+        }
+    }
+
+    fn parse_number(value: &str) -> u64 {
+        value
+            .split_whitespace()
+            .next()
+            .and_then(|n| n.parse().ok())
+            .unwrap_or(0)
+    }
+
+    fn unit_multiplier(value: &str) -> usize {
+        match value.split_whitespace().nth(1) {
+            Some("KiB") => 1024,
+            Some("MiB") => 1024 * 1024,
+            Some("GiB") => 1024 * 1024 * 1024,
+            _ => 1,
+        }
+    }
+}
+
+#[cfg(all(feature = "nvml", not(target_arch = "wasm32")))]
+mod nvml {
+    use super::GpuUsage;
+    use nvml_wrapper::enums::device::UsedGpuMemory;
+    use nvml_wrapper::Nvml;
+    use std::collections::HashMap;
+    use sysinfo::Pid;
+
+    pub struct NvmlBackend {
+        nvml: Option<Nvml>,
+        /// NVML timestamp of the newest utilization sample already seen
+        last_seen: u64,
+    }
+
+    impl NvmlBackend {
+        pub fn new() -> Self {
+            let nvml = Nvml::init()
+                .map_err(|err| log::warn!("NVML unavailable, falling back to fdinfo: {err}"))
+                .ok();
+            Self { nvml, last_seen: 0 }
+        }
+
+        pub fn sample(&mut self) -> HashMap<Pid, GpuUsage> {
+            let mut usage: HashMap<Pid, GpuUsage> = HashMap::new();
+            let Some(nvml) = &self.nvml else {
+                return usage;
+            };
+            let device_count = nvml.device_count().unwrap_or(0);
+            let mut newest = self.last_seen;
+            for index in 0..device_count {
+                let Ok(device) = nvml.device_by_index(index) else {
+                    continue;
+                };
+                let processes = device
+                    .running_compute_processes()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .chain(device.running_graphics_processes().unwrap_or_default());
+                for process in processes {
+                    if let UsedGpuMemory::Used(bytes) = process.used_gpu_memory {
+                        usage.entry(Pid::from_u32(process.pid)).or_default().memory +=
+                            bytes as usize;
+                    }
+                }
+                for sample in device
+                    .process_utilization_stats(self.last_seen)
+                    .unwrap_or_default()
+                {
+                    newest = newest.max(sample.timestamp);
+                    usage
+                        .entry(Pid::from_u32(sample.pid))
+                        .or_default()
+                        .utilization += sample.sm_util as f32;
+                }
+            }
+            self.last_seen = newest;
+            usage
+        }
+    }
+}
@@ -1,7 +1,41 @@
 use std::collections::HashMap;
+use std::time::{Duration, Instant, SystemTime};
 use sysinfo::Pid;
 
 use super::circular_buffer::CircularBuffer;
+use super::sched::{SchedCounters, SchedRates};
+
+/// Memory used by one sample of CPU, memory, GPU, scheduler rates, counts, CPU share and timing
+pub const BYTES_PER_SAMPLE: usize = 3 * size_of::<f32>()
+    + size_of::<usize>()
+    + size_of::<SchedRates>()
+    + 3 * size_of::<u32>()
+    + size_of::<SystemTime>();
+
+/// Averages kept behind the full-resolution samples of a tiered history, see
+/// [`ProcessHistory::tiered`]. Each tier takes over where the previous one ends.
+pub const HISTORY_TIERS: [HistoryTier; 2] = [
+    HistoryTier {
+        seconds: 10,
+        len: 6 * 360,
+    },
+    HistoryTier {
+        seconds: 60,
+        len: 24 * 60,
+    },
+];
+
+/// Memory used by the tiers of one tiered series
+pub const BYTES_PER_TIERED_SERIES: usize =
+    (HISTORY_TIERS[0].len + HISTORY_TIERS[1].len) * size_of::<TierPoint>();
+
+#[derive(Debug, Clone, Copy)]
+pub struct HistoryTier {
+    /// Time averaged into one point
+    pub seconds: u64,
+    /// Points kept, 6 hours of 10-second averages and a day of 1-minute ones
+    pub len: usize,
+}
 
 /// Stores historical data for processes and their children
 #[derive(Default, Debug, Clone)]
@@ -10,23 +44,128 @@ pub struct ProcessHistory {
     histories: HashMap<Pid, ProcessMetrics>,
     /// Maximum number of data points to store in history
     pub history_len: usize,
+    /// CPU, memory and GPU older than `history_len` are kept as averages
+    tiered: bool,
+}
+
+/// Average CPU, memory and GPU of `samples` consecutive samples
+#[derive(Debug, Clone, Copy, Default)]
+struct TierPoint {
+    cpu: f32,
+    memory: usize,
+    gpu: f32,
+    samples: u32,
+}
+
+/// Points of one [`HistoryTier`] and the sums of the one being filled
+#[derive(Debug, Clone)]
+struct Tier {
+    points: CircularBuffer<TierPoint>,
+    cpu: f64,
+    memory: f64,
+    gpu: f64,
+    samples: u32,
+}
+
+impl Tier {
+    fn new(len: usize) -> Self {
+        Self {
+            points: CircularBuffer::new(len),
+            cpu: 0.0,
+            memory: 0.0,
+            gpu: 0.0,
+            samples: 0,
+        }
+    }
+
+    fn add(&mut self, point: TierPoint) {
+        let weight = point.samples as f64;
+        self.cpu += point.cpu as f64 * weight;
+        self.memory += point.memory as f64 * weight;
+        self.gpu += point.gpu as f64 * weight;
+        self.samples += point.samples;
+    }
+
+    /// Closes the point being filled once it holds `per_point` samples
+    fn close(&mut self, per_point: u32) -> Option<TierPoint> {
+        if self.samples < per_point {
+            return None;
+        }
+        let samples = self.samples as f64;
+        let point = TierPoint {
+            cpu: (self.cpu / samples) as f32,
+            memory: (self.memory / samples) as usize,
+            gpu: (self.gpu / samples) as f32,
+            samples: self.samples,
+        };
+        self.points.push(point);
+        (self.cpu, self.memory, self.gpu, self.samples) = (0.0, 0.0, 0.0, 0);
+        Some(point)
+    }
 }
 
-/// Stores CPU and memory metrics for a process
+/// Stores CPU, memory and GPU metrics for a process
 #[derive(Debug, Clone)]
 pub struct ProcessMetrics {
     cpu: CircularBuffer<f32>,
     memory: CircularBuffer<usize>,
+    gpu: CircularBuffer<f32>,
+    sched: CircularBuffer<SchedRates>,
+    /// Processes and threads of a tree, only kept for its general stats
+    processes: CircularBuffer<u32>,
+    threads: CircularBuffer<u32>,
+    /// Share of the CPU used system-wide, only kept for the general stats of a tree
+    cpu_share: CircularBuffer<f32>,
+    /// Wall-clock time of each sample and the samples the worker missed right
+    /// before it, only kept for the general stats of a tree
+    taken_at: CircularBuffer<SystemTime>,
+    missed: CircularBuffer<u32>,
+    /// Last counters read, the base of the next rates
+    counters: Option<(Instant, SchedCounters)>,
+    /// Coarser CPU, memory and GPU of a tiered history, one per [`HISTORY_TIERS`]
+    tiers: Vec<Tier>,
+    /// Samples rolled up into the tiers so far
+    rolled_up: u64,
 }
 
 impl ProcessMetrics {
-    fn new(size: usize) -> Self {
+    fn new(size: usize, tiered: bool) -> Self {
+        let tiers = if tiered {
+            HISTORY_TIERS
+                .iter()
+                .map(|tier| Tier::new(tier.len))
+                .collect()
+        } else {
+            Vec::new()
+        };
         Self {
             cpu: CircularBuffer::new(size),
             memory: CircularBuffer::new(size),
+            gpu: CircularBuffer::new(size),
+            sched: CircularBuffer::new(size),
+            processes: CircularBuffer::new(size),
+            threads: CircularBuffer::new(size),
+            cpu_share: CircularBuffer::new(size),
+            taken_at: CircularBuffer::new(size),
+            missed: CircularBuffer::new(size),
+            counters: None,
+            tiers,
+            rolled_up: 0,
         }
     }
 
+    fn resize(&mut self, size: usize) {
+        self.cpu.resize(size);
+        self.memory.resize(size);
+        self.gpu.resize(size);
+        self.sched.resize(size);
+        self.processes.resize(size);
+        self.threads.resize(size);
+        self.cpu_share.resize(size);
+        self.taken_at.resize(size);
+        self.missed.resize(size);
+    }
+
     fn update_cpu(&mut self, value: f32) {
         self.cpu.push(value);
     }
@@ -35,13 +174,85 @@ impl ProcessMetrics {
         self.memory.push(value);
     }
 
+    fn update_gpu(&mut self, value: f32) {
+        self.gpu.push(value);
+    }
+
     pub fn get_cpu_history(&self) -> Vec<f32> {
         self.cpu.as_vec()
     }
 
+    pub fn get_gpu_history(&self) -> Vec<f32> {
+        self.gpu.as_vec()
+    }
+
     pub fn get_memory_history(&self) -> Vec<usize> {
         self.memory.as_vec()
     }
+
+    pub fn get_sched_history(&self) -> Vec<SchedRates> {
+        self.sched.as_vec()
+    }
+
+    pub fn get_count_history(&self) -> (Vec<u32>, Vec<u32>) {
+        (self.processes.as_vec(), self.threads.as_vec())
+    }
+
+    /// Adds the newest sample to the tiers, closing a point of each tier once
+    /// it spans the tier's time at `interval`
+    fn roll_up(&mut self, interval: Duration) {
+        if self.tiers.is_empty() {
+            return;
+        }
+        let mut point = Some(TierPoint {
+            cpu: self.cpu.newest().copied().unwrap_or_default(),
+            memory: self.memory.newest().copied().unwrap_or_default(),
+            gpu: self.gpu.newest().copied().unwrap_or_default(),
+            samples: 1,
+        });
+        self.rolled_up += 1;
+        let interval_ms = interval.as_millis().max(1);
+        for (tier, config) in self.tiers.iter_mut().zip(HISTORY_TIERS) {
+            let Some(closed) = point else {
+                break;
+            };
+            tier.add(closed);
+            let per_point = (config.seconds as u128 * 1000 / interval_ms).max(1);
+            point = tier.close(per_point.min(u32::MAX as u128) as u32);
+        }
+    }
+
+    /// Tier values older than the `retained` newest samples, oldest first and
+    /// repeated over the samples they average, so they continue the full-resolution
+    /// history on the same axis
+    fn tail<T: Copy>(&self, retained: usize, value: impl Fn(&TierPoint) -> T) -> Vec<T> {
+        let mut cut = self.rolled_up.saturating_sub(retained as u64);
+        // The newest closed point of each tier ends where the samples still being
+        // averaged into it start
+        let mut end = self.rolled_up;
+        let mut segments = Vec::new();
+        for tier in &self.tiers {
+            end -= (tier.samples as u64).min(end);
+            let points = tier.points.as_vec();
+            let mut tier_end = end;
+            for point in points.iter().rev() {
+                let start = tier_end.saturating_sub(point.samples as u64);
+                if start < cut {
+                    segments.push((value(point), cut.min(tier_end) - start));
+                }
+                tier_end = start;
+            }
+            // A coarser tier only covers what is older than this one
+            if !points.is_empty() {
+                cut = cut.min(tier_end);
+            }
+        }
+        segments
+            .into_iter()
+            .rev()
+            .flat_map(|(value, samples)| std::iter::repeat_n(value, samples as usize))
+            .collect()
+    }
 }
 
 impl ProcessHistory {
@@ -49,23 +260,196 @@ impl ProcessHistory {
         Self {
             histories: HashMap::new(),
             history_len: max_points,
+            tiered: false,
         }
     }
 
+    /// A history that keeps averages of the CPU, memory and GPU older than
+    /// `max_points`, rolled up by [`Self::roll_up`], for day-long sessions at
+    /// a bounded cost. Used for the general stats of a tree.
+    pub fn tiered(max_points: usize) -> Self {
+        Self {
+            tiered: true,
+            ..Self::new(max_points)
+        }
+    }
+
+    /// Changes the number of points kept, preserving the newest samples
+    pub fn resize(&mut self, max_points: usize) {
+        for metrics in self.histories.values_mut() {
+            metrics.resize(max_points);
+        }
+        self.history_len = max_points;
+    }
+
     pub fn update_cpu(&mut self, pid: Pid, cpu_usage: f32) {
         self.histories
             .entry(pid)
-            .or_insert_with(|| ProcessMetrics::new(self.history_len))
+            .or_insert_with(|| ProcessMetrics::new(self.history_len, self.tiered))
             .update_cpu(cpu_usage);
     }
 
     pub fn update_memory(&mut self, pid: Pid, memory: usize) {
         self.histories
             .entry(pid)
-            .or_insert_with(|| ProcessMetrics::new(self.history_len))
+            .or_insert_with(|| ProcessMetrics::new(self.history_len, self.tiered))
             .update_memory(memory);
     }
 
+    pub fn update_gpu(&mut self, pid: Pid, gpu_usage: f32) {
+        self.histories
+            .entry(pid)
+            .or_insert_with(|| ProcessMetrics::new(self.history_len, self.tiered))
+            .update_gpu(gpu_usage);
+    }
+
+    pub fn update_sched(&mut self, pid: Pid, rates: SchedRates) {
+        self.histories
+            .entry(pid)
+            .or_insert_with(|| ProcessMetrics::new(self.history_len, self.tiered))
+            .sched
+            .push(rates);
+    }
+
+    pub fn update_counts(&mut self, pid: Pid, processes: usize, threads: usize) {
+        let metrics = self
+            .histories
+            .entry(pid)
+            .or_insert_with(|| ProcessMetrics::new(self.history_len, self.tiered));
+        metrics.processes.push(processes as u32);
+        metrics.threads.push(threads as u32);
+    }
+
+    pub fn update_cpu_share(&mut self, pid: Pid, share: f32) {
+        self.histories
+            .entry(pid)
+            .or_insert_with(|| ProcessMetrics::new(self.history_len, self.tiered))
+            .cpu_share
+            .push(share);
+    }
+
+    /// Records when a sample of a tree was taken and how many samples were
+    /// missed before it because the worker ran late
+    pub fn update_timing(&mut self, pid: Pid, taken_at: SystemTime, missed: u64) {
+        let metrics = self
+            .histories
+            .entry(pid)
+            .or_insert_with(|| ProcessMetrics::new(self.history_len, self.tiered));
+        metrics.taken_at.push(taken_at);
+        metrics.missed.push(missed.min(u32::MAX as u64) as u32);
+    }
+
+    /// Adds the newest CPU, memory and GPU of `pid` to the coarser tiers of a
+    /// tiered history, once per tick after they were updated. `interval` is the
+    /// time between samples.
+    pub fn roll_up(&mut self, pid: Pid, interval: Duration) {
+        if let Some(metrics) = self.histories.get_mut(&pid) {
+            metrics.roll_up(interval);
+        }
+    }
+
+    /// CPU of `pid` over everything retained: the tier averages older than the
+    /// full-resolution samples, then the samples, see [`Self::tiered`]
+    pub fn get_cpu_history_tiered(&self, pid: &Pid) -> Option<Vec<f32>> {
+        let metrics = self.histories.get(pid)?;
+        let mut history = metrics.tail(metrics.cpu.len(), |point| point.cpu);
+        history.extend(metrics.cpu.iter());
+        Some(history)
+    }
+
+    pub fn get_memory_history_tiered(&self, pid: &Pid) -> Option<Vec<usize>> {
+        let metrics = self.histories.get(pid)?;
+        let mut history = metrics.tail(metrics.memory.len(), |point| point.memory);
+        history.extend(metrics.memory.iter());
+        Some(history)
+    }
+
+    pub fn get_gpu_history_tiered(&self, pid: &Pid) -> Option<Vec<f32>> {
+        let metrics = self.histories.get(pid)?;
+        let mut history = metrics.tail(metrics.gpu.len(), |point| point.gpu);
+        history.extend(metrics.gpu.iter());
+        Some(history)
+    }
+
+    /// Samples covered by the tiered CPU history of `pid`, the tiers included
+    pub fn retained_len(&self, pid: &Pid) -> usize {
+        self.histories.get(pid).map_or(0, |metrics| {
+            let full = metrics.cpu.len();
+            metrics.tail(full, |_| ()).len() + full
+        })
+    }
+
+    /// Records `counters` read at `now` and returns the rates since the
+    /// previous read, which are also added to the history
+    pub fn record_sched_counters(
+        &mut self,
+        pid: Pid,
+        counters: SchedCounters,
+        now: Instant,
+    ) -> Option<SchedRates> {
+        let metrics = self
+            .histories
+            .entry(pid)
+            .or_insert_with(|| ProcessMetrics::new(self.history_len, self.tiered));
+        let rates = metrics
+            .counters
+            .replace((now, counters))
+            .map(|(at, previous)| counters.rates_since(&previous, now.duration_since(at)))?;
+        metrics.sched.push(rates);
+        Some(rates)
+    }
+
+    pub fn get_sched_history(&self, pid: &Pid) -> Option<Vec<SchedRates>> {
+        self.histories
+            .get(pid)
+            .map(|metrics| metrics.get_sched_history())
+    }
+
+    /// `(processes, threads)` of a tree over time
+    pub fn get_count_history(&self, pid: &Pid) -> Option<(Vec<u32>, Vec<u32>)> {
+        self.histories
+            .get(pid)
+            .map(|metrics| metrics.get_count_history())
+    }
+
+    /// Percent of the system's CPU usage taken by a tree, oldest first
+    pub fn get_cpu_share_history(&self, pid: &Pid) -> Option<Vec<f32>> {
+        self.histories
+            .get(pid)
+            .map(|metrics| metrics.cpu_share.as_vec())
+    }
+
+    /// Wall-clock times of the samples of a tree, oldest first
+    pub fn get_sample_times(&self, pid: &Pid) -> Option<Vec<SystemTime>> {
+        self.histories
+            .get(pid)
+            .map(|metrics| metrics.taken_at.as_vec())
+    }
+
+    /// Samples missed right before each sample of a tree
+    pub fn get_missed_history(&self, pid: &Pid) -> Option<Vec<u32>> {
+        self.histories
+            .get(pid)
+            .map(|metrics| metrics.missed.as_vec())
+    }
+
+    pub fn get_gpu_history(&self, pid: &Pid) -> Option<Vec<f32>> {
+        self.histories
+            .get(pid)
+            .map(|metrics| metrics.get_gpu_history())
+    }
+
+    /// Peak and average GPU utilization over the history window
+    pub fn get_gpu_data_history(&self, pid: &Pid) -> (f32, f32) {
+        let history = self.get_gpu_history(pid).unwrap_or_default();
+        if history.is_empty() {
+            return (0.0, 0.0);
+        }
+        let peak = history.iter().copied().fold(0.0, f32::max);
+        let avg = history.iter().sum::<f32>() / history.len() as f32;
+        (peak, avg)
+    }
+
     pub fn get_cpu_history(&self, pid: &Pid) -> Option<Vec<f32>> {
         self.histories
             .get(pid)
@@ -78,6 +462,71 @@ impl ProcessHistory {
             .map(|metrics| metrics.get_memory_history())
     }
 
+    /// Memory growth between consecutive samples in bytes per second,
+    /// one value shorter than the memory history. Divided by the time between
+    /// the recorded samples where `pid` has them, `interval` otherwise.
+    pub fn get_memory_rate(&self, pid: &Pid, interval: Duration) -> Option<Vec<f32>> {
+        let history = self.get_memory_history(pid)?;
+        let times = self
+            .get_sample_times(pid)
+            .filter(|times| times.len() == history.len());
+        let seconds = |n: usize| {
+            times
+                .as_ref()
+                .and_then(|times| times[n + 1].duration_since(times[n]).ok())
+                .unwrap_or(interval)
+                .as_secs_f64()
+                .max(f64::EPSILON)
+        };
+        Some(
+            history
+                .windows(2)
+                .enumerate()
+                .map(|(n, pair)| ((pair[1] as f64 - pair[0] as f64) / seconds(n)) as f32)
+                .collect(),
+        )
+    }
+
+    /// Least-squares memory growth in bytes per second over the newest `samples`,
+    /// `None` with fewer than three of them. Fitted against the recorded sample
+    /// times where `pid` has them, samples `interval` apart otherwise.
+    pub fn memory_trend(&self, pid: &Pid, samples: usize, interval: Duration) -> Option<f64> {
+        let history = self.get_memory_history(pid)?;
+        let start = history.len().saturating_sub(samples);
+        let window = &history[start..];
+        if window.len() < 3 {
+            return None;
+        }
+        let times = self
+            .get_sample_times(pid)
+            .filter(|times| times.len() == history.len());
+        let seconds: Vec<f64> = match &times {
+            Some(times) => times[start..]
+                .iter()
+                .map(|time| {
+                    time.duration_since(times[start])
+                        .unwrap_or_default()
+                        .as_secs_f64()
+                })
+                .collect(),
+            None => (0..window.len())
+                .map(|n| n as f64 * interval.as_secs_f64())
+                .collect(),
+        };
+        let n = window.len() as f64;
+        let mean_x = seconds.iter().sum::<f64>() / n;
+        let mean_y = window.iter().map(|&y| y as f64).sum::<f64>() / n;
+        let (covariance, variance) =
+            seconds
+                .iter()
+                .zip(window)
+                .fold((0.0, 0.0), |(covariance, variance), (&x, &y)| {
+                    let dx = x - mean_x;
+                    (covariance + dx * (y as f64 - mean_y), variance + dx * dx)
+                });
+        Some(covariance / variance.max(f64::EPSILON))
+    }
+
     pub fn get_data_history(&self, pid: &Pid) -> (f32, usize, f32, usize) {
         if let (Some(cpu_history), Some(mem_history)) =
             (self.get_cpu_history(pid), self.get_memory_history(pid))
@@ -106,7 +555,220 @@ impl ProcessHistory {
         }
     }
 
+    /// Percentiles and standard deviation of CPU and memory over the history window
+    pub fn get_distribution(&self, pid: &Pid) -> (Distribution, Distribution) {
+        let cpu = self
+            .get_cpu_history(pid)
+            .map(|history| Distribution::from_samples(history.into_iter().map(f64::from)))
+            .unwrap_or_default();
+        let memory = self
+            .get_memory_history(pid)
+            .map(|history| Distribution::from_samples(history.into_iter().map(|x| x as f64)))
+            .unwrap_or_default();
+        (cpu, memory)
+    }
+
+    /// Pids with a history, in no particular order
+    pub fn pids(&self) -> impl Iterator<Item = &Pid> {
+        self.histories.keys()
+    }
+
     pub fn cleanup_histories(&mut self, active_pids: &[Pid]) {
         self.histories.retain(|pid, _| active_pids.contains(pid));
     }
 }
+
+/// Spread of the samples in a history window
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Distribution {
+    pub p50: f64,
+    pub p95: f64,
+    pub p99: f64,
+    pub stddev: f64,
+}
+
+impl Distribution {
+    /// Computes nearest-rank percentiles on a sorted copy of the samples
+    pub fn from_samples(samples: impl Iterator<Item = f64>) -> Self {
+        let mut sorted: Vec<f64> = samples.collect();
+        if sorted.is_empty() {
+            return Self::default();
+        }
+        sorted.sort_by(f64::total_cmp);
+
+        let len = sorted.len() as f64;
+        let mean = sorted.iter().sum::<f64>() / len;
+        let variance = sorted.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / len;
+        let percentile = |p: f64| {
+            let rank = ((p / 100.0) * len).ceil() as usize;
+            sorted[rank.clamp(1, sorted.len()) - 1]
+        };
+
+        Self {
+            p50: percentile(50.0),
+            p95: percentile(95.0),
+            p99: percentile(99.0),
+            stddev: variance.sqrt(),
+        }
+    }
+}
+
+/// Samples counted into buckets of equal width starting at 0
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Histogram {
+    pub bucket_width: f64,
+    /// Samples in `[i * bucket_width, (i + 1) * bucket_width)`, the last bucket
+    /// also holds the samples at its upper bound
+    pub counts: Vec<usize>,
+}
+
+impl Histogram {
+    /// Splits `[0, max]` into `buckets`, samples outside of it are clamped in
+    pub fn from_samples(samples: &[f64], max: f64, buckets: usize) -> Self {
+        let buckets = buckets.max(1);
+        let bucket_width = max.max(f64::EPSILON) / buckets as f64;
+        let mut counts = vec![0; buckets];
+        for sample in samples {
+            let bucket = (sample.max(0.0) / bucket_width) as usize;
+            counts[bucket.min(buckets - 1)] += 1;
+        }
+        Self {
+            bucket_width,
+            counts,
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiered_history_continues_with_averages() {
+        let pid = Pid::from_u32(1);
+        let interval = Duration::from_secs(1);
+        let mut history = ProcessHistory::tiered(5);
+        for i in 0..40 {
+            history.update_cpu(pid, i as f32);
+            history.update_memory(pid, i);
+            history.update_gpu(pid, 0.0);
+            history.roll_up(pid, interval);
+        }
+        // Four 10-second averages, the newest cut where the full resolution starts
+        let cpu = history.get_cpu_history_tiered(&pid).unwrap();
+        assert_eq!(cpu.len(), 40);
+        assert_eq!(history.retained_len(&pid), 40);
+        assert_eq!(&cpu[..2], &[4.5, 4.5]);
+        assert_eq!(
+            &cpu[30..],
+            &[34.5, 34.5, 34.5, 34.5, 34.5, 35.0, 36.0, 37.0, 38.0, 39.0]
+        );
+        assert_eq!(history.get_memory_history_tiered(&pid).unwrap()[10], 14);
+        // An untiered history has no tail
+        let mut plain = ProcessHistory::new(5);
+        for i in 0..40 {
+            plain.update_cpu(pid, i as f32);
+            plain.roll_up(pid, interval);
+        }
+        assert_eq!(plain.get_cpu_history_tiered(&pid).unwrap().len(), 5);
+
+        // Past six hours, 1-minute averages take over from the 10-second ones
+        let mut day = ProcessHistory::tiered(100);
+        for i in 0..30_000 {
+            day.update_cpu(pid, (i / 60) as f32);
+            day.roll_up(pid, interval);
+        }
+        let cpu = day.get_cpu_history_tiered(&pid).unwrap();
+        assert_eq!(cpu.len(), 30_000);
+        assert_eq!(cpu[0], 0.0);
+        assert_eq!(cpu[8399], 139.0);
+        assert_eq!(cpu[29_999], 499.0);
+    }
+
+    #[test]
+    fn history_resize_keeps_newest_samples() {
+        let pid = Pid::from_u32(1);
+        let mut history = ProcessHistory::new(5);
+        for i in 0..5 {
+            history.update_cpu(pid, i as f32);
+        }
+        history.resize(3);
+        assert_eq!(history.get_cpu_history(&pid).unwrap(), vec![2.0, 3.0, 4.0]);
+        history.resize(6);
+        history.update_cpu(pid, 5.0);
+        assert_eq!(
+            history.get_cpu_history(&pid).unwrap(),
+            vec![2.0, 3.0, 4.0, 5.0]
+        );
+    }
+
+    #[test]
+    fn memory_rate_is_growth_per_second() {
+        let pid = Pid::from_u32(1);
+        let mut history = ProcessHistory::new(5);
+        for memory in [1000, 1500, 1500, 1000] {
+            history.update_memory(pid, memory);
+        }
+        assert_eq!(
+            history
+                .get_memory_rate(&pid, Duration::from_millis(500))
+                .unwrap(),
+            vec![1000.0, 0.0, -1000.0]
+        );
+        assert!(history
+            .get_memory_rate(&Pid::from_u32(2), Duration::from_secs(1))
+            .is_none());
+    }
+
+    #[test]
+    fn memory_rate_follows_the_sample_times() {
+        let pid = Pid::from_u32(1);
+        let mut history = ProcessHistory::new(5);
+        let start = std::time::UNIX_EPOCH;
+        for (memory, seconds) in [(1000, 0), (3000, 2), (3000, 3), (2000, 7)] {
+            history.update_memory(pid, memory);
+            history.update_timing(pid, start + Duration::from_secs(seconds), 0);
+        }
+        // The sample times win over the passed interval
+        assert_eq!(
+            history
+                .get_memory_rate(&pid, Duration::from_secs(1))
+                .unwrap(),
+            vec![1000.0, 0.0, -250.0]
+        );
+    }
+
+    #[test]
+    fn memory_trend_follows_the_sample_times() {
+        let pid = Pid::from_u32(1);
+        let mut history = ProcessHistory::new(5);
+        let start = std::time::UNIX_EPOCH;
+        // 100 bytes per second, sampled at an uneven pace
+        for seconds in [0, 1, 5, 6, 10] {
+            history.update_memory(pid, 1000 + 100 * seconds as usize);
+            history.update_timing(pid, start + Duration::from_secs(seconds), 0);
+        }
+        let trend = history
+            .memory_trend(&pid, 5, Duration::from_secs(1))
+            .unwrap();
+        assert!((trend - 100.0).abs() < 1e-6, "trend was {trend}");
+    }
+
+    #[test]
+    fn histogram_buckets_clamp_out_of_range_samples() {
+        let samples = [0.0, 4.9, 5.0, 42.0, 100.0, 250.0, -1.0];
+        let histogram = Histogram::from_samples(&samples, 100.0, 20);
+        assert_eq!(histogram.bucket_width, 5.0);
+        assert_eq!(histogram.total(), samples.len());
+        // 0, 4.9 and the negative sample share the first bucket
+        assert_eq!(histogram.counts[0], 3);
+        assert_eq!(histogram.counts[1], 1);
+        assert_eq!(histogram.counts[8], 1);
+        // 100 and everything above land in the last one
+        assert_eq!(histogram.counts[19], 2);
+    }
+}
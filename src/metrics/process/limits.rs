@@ -0,0 +1,303 @@
+//! CPU and memory limits enforced by the OS on a monitored tree: a cgroup v2
+//! group on Linux, a Job Object on Windows. Processes started later by a
+//! limited process inherit the limit from their parent.
+
+use super::ProcessIdentifier;
+use sysinfo::Pid;
+
+/// Limit applied to every process of an identifier together
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ResourceLimit {
+    /// Percent of one core, so 200 allows two full cores
+    pub cpu_percent: Option<f32>,
+    pub memory_bytes: Option<usize>,
+}
+
+impl ResourceLimit {
+    pub fn is_empty(&self) -> bool {
+        self.cpu_percent.is_none() && self.memory_bytes.is_none()
+    }
+}
+
+/// Places `pids` under `limit`, replacing any earlier limit of `identifier`
+pub fn apply_limit(
+    identifier: &ProcessIdentifier,
+    pids: &[Pid],
+    limit: &ResourceLimit,
+    cpu_count: usize,
+) -> Result<(), String> {
+    imp::apply_limit(&group_name(identifier), pids, limit, cpu_count)
+}
+
+/// Lifts the limit of `identifier`; on Linux its processes go back to the cgroup
+/// they were in before
+pub fn remove_limit(identifier: &ProcessIdentifier) -> Result<(), String> {
+    imp::remove_limit(&group_name(identifier))
+}
+
+/// cgroup directory or Job Object name for `identifier`
+fn group_name(identifier: &ProcessIdentifier) -> String {
+    let name: String = identifier
+        .to_string()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("tvis-{name}")
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use super::ResourceLimit;
+    use crate::metrics::process::cgroup::parse_unified;
+    use std::collections::HashMap;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::sync::{LazyLock, Mutex};
+    use sysinfo::Pid;
+
+    const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+    /// `cpu.max` period in microseconds
+    const CPU_PERIOD: u64 = 100_000;
+
+    /// cgroup each process was in before it was limited, per group, so it's
+    /// handed back there when the limit is lifted
+    static ORIGINS: LazyLock<Mutex<HashMap<String, HashMap<Pid, PathBuf>>>> =
+        LazyLock::new(Default::default);
+
+    fn write(path: PathBuf, value: &str) -> Result<(), String> {
+        fs::write(&path, value).map_err(|err| format!("{}: {err}", path.display()))
+    }
+
+    /// Current cgroup directory of `pid`
+    fn cgroup_of(pid: Pid) -> Option<PathBuf> {
+        let contents = fs::read_to_string(format!("/proc/{pid}/cgroup")).ok()?;
+        Some(Path::new(CGROUP_ROOT).join(parse_unified(&contents)?))
+    }
+
+    fn parent_of(pid: Pid) -> Option<Pid> {
+        let stat = fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+        // The command name can contain spaces and parentheses, fields resume after the last ')'
+        let ppid = stat
+            .get(stat.rfind(')')? + 1..)?
+            .split_whitespace()
+            .nth(1)?;
+        ppid.parse().ok().map(Pid::from_u32)
+    }
+
+    pub fn apply_limit(
+        name: &str,
+        pids: &[Pid],
+        limit: &ResourceLimit,
+        _cpu_count: usize,
+    ) -> Result<(), String> {
+        let root = PathBuf::from(CGROUP_ROOT);
+        if !root.join("cgroup.controllers").exists() {
+            return Err("cgroup v2 is not mounted at /sys/fs/cgroup".to_string());
+        }
+        let group = root.join(name);
+        fs::create_dir_all(&group).map_err(|err| {
+            format!(
+                "{}: {err} (limits need root or a delegated cgroup)",
+                group.display()
+            )
+        })?;
+        // Usually enabled already by systemd, so a failure here is not fatal
+        let _ = fs::write(root.join("cgroup.subtree_control"), "+cpu +memory");
+
+        let cpu_max = match limit.cpu_percent {
+            Some(percent) => {
+                let quota = (percent as f64 / 100.0 * CPU_PERIOD as f64).max(1000.0) as u64;
+                format!("{quota} {CPU_PERIOD}")
+            }
+            None => format!("max {CPU_PERIOD}"),
+        };
+        write(group.join("cpu.max"), &cpu_max)?;
+        let memory_max = limit
+            .memory_bytes
+            .map_or_else(|| "max".to_string(), |bytes| bytes.to_string());
+        write(group.join("memory.max"), &memory_max)?;
+
+        let mut origins = ORIGINS.lock().unwrap();
+        let origins = origins.entry(name.to_string()).or_default();
+        for pid in pids {
+            // Processes limited on an earlier call keep their first origin
+            if !origins.contains_key(pid) {
+                if let Some(origin) = cgroup_of(*pid).filter(|origin| *origin != group) {
+                    origins.insert(*pid, origin);
+                }
+            }
+            write(group.join("cgroup.procs"), &pid.to_string())?;
+        }
+        Ok(())
+    }
+
+    pub fn remove_limit(name: &str) -> Result<(), String> {
+        let root = PathBuf::from(CGROUP_ROOT);
+        let group = root.join(name);
+        let origins = ORIGINS.lock().unwrap().remove(name).unwrap_or_default();
+        if !group.exists() {
+            return Ok(());
+        }
+        // A cgroup can only be removed once empty, so the processes go back to
+        // where they were. Children started under the limit follow their
+        // nearest recorded ancestor, the rest go to the root.
+        let origin = |pid: Pid| {
+            let mut ancestor = Some(pid);
+            while let Some(pid) = ancestor {
+                if let Some(origin) = origins.get(&pid) {
+                    return origin.clone();
+                }
+                ancestor = parent_of(pid).filter(|parent| *parent != pid);
+            }
+            root.clone()
+        };
+        let procs = fs::read_to_string(group.join("cgroup.procs")).unwrap_or_default();
+        for pid in procs.lines().filter_map(|line| line.trim().parse().ok()) {
+            let pid = Pid::from_u32(pid);
+            // The original group may be gone by now
+            if write(origin(pid).join("cgroup.procs"), &pid.to_string()).is_err() {
+                write(root.join("cgroup.procs"), &pid.to_string())?;
+            }
+        }
+        fs::remove_dir(&group).map_err(|err| format!("{}: {err}", group.display()))
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use super::ResourceLimit;
+    use std::collections::HashMap;
+    use std::sync::{LazyLock, Mutex};
+    use sysinfo::Pid;
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows_sys::Win32::System::JobObjects::{
+        AssignProcessToJobObject, CreateJobObjectW, JobObjectCpuRateControlInformation,
+        JobObjectExtendedLimitInformation, SetInformationJobObject,
+        JOBOBJECT_CPU_RATE_CONTROL_INFORMATION, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+        JOB_OBJECT_CPU_RATE_CONTROL_ENABLE, JOB_OBJECT_CPU_RATE_CONTROL_HARD_CAP,
+        JOB_OBJECT_LIMIT_JOB_MEMORY,
+    };
+    use windows_sys::Win32::System::Threading::{
+        OpenProcess, PROCESS_SET_QUOTA, PROCESS_TERMINATE,
+    };
+
+    /// Job handle per group, kept open so the job outlives this call.
+    /// Stored as `usize` because raw handles are not `Send`.
+    static JOBS: LazyLock<Mutex<HashMap<String, usize>>> = LazyLock::new(Default::default);
+
+    fn last_error(action: &str) -> String {
+        format!("{action}: {}", std::io::Error::last_os_error())
+    }
+
+    /// Sets the job's limits, `None` fields lift that limit
+    ///
+    /// SAFETY: `job` must be a valid job handle.
+    unsafe fn set_limits(
+        job: HANDLE,
+        limit: &ResourceLimit,
+        cpu_count: usize,
+    ) -> Result<(), String> {
+        let mut cpu: JOBOBJECT_CPU_RATE_CONTROL_INFORMATION = std::mem::zeroed();
+        if let Some(percent) = limit.cpu_percent {
+            cpu.ControlFlags =
+                JOB_OBJECT_CPU_RATE_CONTROL_ENABLE | JOB_OBJECT_CPU_RATE_CONTROL_HARD_CAP;
+            // Hundredths of a percent of the whole machine
+            cpu.Anonymous.CpuRate =
+                (percent / cpu_count.max(1) as f32 * 100.0).clamp(1.0, 10_000.0) as u32;
+        }
+        if SetInformationJobObject(
+            job,
+            JobObjectCpuRateControlInformation,
+            &cpu as *const _ as *const _,
+            std::mem::size_of_val(&cpu) as u32,
+        ) == 0
+        {
+            return Err(last_error("Setting the CPU rate"));
+        }
+
+        let mut memory: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = std::mem::zeroed();
+        if let Some(bytes) = limit.memory_bytes {
+            memory.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_JOB_MEMORY;
+            memory.JobMemoryLimit = bytes;
+        }
+        if SetInformationJobObject(
+            job,
+            JobObjectExtendedLimitInformation,
+            &memory as *const _ as *const _,
+            std::mem::size_of_val(&memory) as u32,
+        ) == 0
+        {
+            return Err(last_error("Setting the memory limit"));
+        }
+        Ok(())
+    }
+
+    pub fn apply_limit(
+        name: &str,
+        pids: &[Pid],
+        limit: &ResourceLimit,
+        cpu_count: usize,
+    ) -> Result<(), String> {
+        let mut jobs = JOBS.lock().unwrap();
+        // SAFETY: handles are checked after creation and only closed in
+        // `remove_limit`, process handles are closed right after assignment.
+        unsafe {
+            let job = match jobs.get(name) {
+                Some(&job) => job as HANDLE,
+                None => {
+                    let job = CreateJobObjectW(std::ptr::null(), std::ptr::null());
+                    if job.is_null() {
+                        return Err(last_error("Creating a job object"));
+                    }
+                    jobs.insert(name.to_string(), job as usize);
+                    job
+                }
+            };
+            set_limits(job, limit, cpu_count)?;
+            for pid in pids {
+                let process = OpenProcess(PROCESS_SET_QUOTA | PROCESS_TERMINATE, 0, pid.as_u32());
+                if process.is_null() {
+                    return Err(last_error(&format!("Opening process {pid}")));
+                }
+                let assigned = AssignProcessToJobObject(job, process);
+                CloseHandle(process);
+                if assigned == 0 {
+                    return Err(last_error(&format!("Adding process {pid} to the job")));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Processes can't leave a job, so the limits are lifted instead
+    pub fn remove_limit(name: &str) -> Result<(), String> {
+        let Some(job) = JOBS.lock().unwrap().remove(name) else {
+            return Ok(());
+        };
+        // SAFETY: the handle came from `CreateJobObjectW` and is closed once
+        unsafe {
+            let result = set_limits(job as HANDLE, &ResourceLimit::default(), 1);
+            CloseHandle(job as HANDLE);
+            result
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+mod imp {
+    use super::ResourceLimit;
+    use sysinfo::Pid;
+
+    pub fn apply_limit(
+        _name: &str,
+        _pids: &[Pid],
+        _limit: &ResourceLimit,
+        _cpu_count: usize,
+    ) -> Result<(), String> {
+        Err("Limits are only supported on Linux and Windows".to_string())
+    }
+
+    pub fn remove_limit(_name: &str) -> Result<(), String> {
+        Ok(())
+    }
+}
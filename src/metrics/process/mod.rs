@@ -1,15 +1,281 @@
+pub mod access;
+pub mod cgroup;
 mod circular_buffer;
+pub mod container;
+mod crash;
+mod details;
+#[cfg(all(target_os = "linux", feature = "ebpf"))]
+pub mod ebpf;
+mod exclude;
+mod focus;
+mod gpu;
 mod history;
+pub mod limits;
 mod monitor;
+mod names;
+mod peak_memory;
+mod priority;
+mod sched;
+pub mod service;
+mod sockets;
+pub mod user;
+mod watch;
+use circular_buffer::CircularBuffer;
+pub use crash::*;
+pub use details::ProcessDetails;
+pub use exclude::*;
+pub use focus::focused_pid;
+pub use gpu::*;
 pub use history::*;
 pub use monitor::*;
+pub use names::NameCache;
+pub use peak_memory::os_peak_memory;
+pub use priority::*;
+pub use sched::*;
 use serde::{Deserialize, Serialize};
+pub use sockets::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+pub use watch::WatchRule;
+
+/// Maximum number of events kept per monitored identifier
+pub const MAX_EVENTS: usize = 1000;
 
 #[derive(Debug, Clone, Default)]
 pub struct ProcessData {
     pub history: ProcessHistory,
     pub genereal: ProcessGeneral,
     pub processes_stats: Vec<ProcessInfo>,
+    /// Child processes appearing and disappearing, oldest first
+    pub events: Vec<ProcessEvent>,
+    /// Signals that killed processes still waiting to be reaped, attached to
+    /// their exit event once they're gone
+    pub crashes: HashMap<sysinfo::Pid, Crash>,
+    pub session: SessionStats,
+    /// Set once the process of a `Pid` identifier exited, the data is frozen from then on
+    pub finished: Option<RunSummary>,
+    /// Since when no process matches a `Name` identifier. The last-known data is
+    /// kept, frozen, until a matching process starts again.
+    pub inactive_since: Option<Instant>,
+}
+
+/// Totals of a `Pid` identifier whose process exited
+#[derive(Debug, Clone)]
+pub struct RunSummary {
+    /// Time from the first to the last sample
+    pub duration: Duration,
+    pub ended_at: SystemTime,
+    pub peak_cpu: f32,
+    pub avg_cpu: f32,
+    pub peak_memory: usize,
+    pub avg_memory: usize,
+    pub children: u64,
+}
+
+/// How long an identifier has been sampled and how complete its data is
+#[derive(Debug, Clone, Default)]
+pub struct SessionStats {
+    pub started_at: Option<Instant>,
+    /// Ticks on which at least one matching process was alive
+    pub samples: u64,
+    /// Samples that should have been collected, including ones missed by a stalled worker
+    pub expected: u64,
+    /// Time of the last sample with a live process
+    pub last_sampled_at: Option<Instant>,
+    /// Highest and summed aggregate usage over every sample, not only the history window
+    pub peak_cpu: f32,
+    pub cpu_sum: f64,
+    pub peak_memory: usize,
+    pub memory_sum: f64,
+    /// Child processes seen alive, including ones already running when sampling started
+    pub children_seen: u64,
+    /// Distinct start times of the tree's root processes in Unix seconds, oldest first.
+    /// A root is a matched process whose parent is outside the tree.
+    pub start_times: Vec<u64>,
+    /// Start times seen after the first sample, e.g. a service coming back after a crash
+    pub restarts: usize,
+    /// Start time of the oldest live root process, `None` while nothing matches
+    pub running_since: Option<u64>,
+}
+
+impl SessionStats {
+    pub fn duration(&self) -> Duration {
+        self.started_at
+            .map(|started_at| started_at.elapsed())
+            .unwrap_or_default()
+    }
+
+    /// Share of expected samples actually collected, in percent
+    pub fn coverage(&self) -> f32 {
+        if self.expected == 0 {
+            return 0.0;
+        }
+        self.samples as f32 / self.expected as f32 * 100.0
+    }
+
+    /// Takes the start times of the live root processes, returning the ones not
+    /// seen before that count as restarts. The roots found on the first sample
+    /// are the baseline, however many there are.
+    pub fn record_start_times(&mut self, start_times: &[u64]) -> Vec<u64> {
+        self.running_since = start_times.iter().copied().min();
+        let first = self.start_times.is_empty();
+        let mut restarted = Vec::new();
+        for &start_time in start_times {
+            if let Err(index) = self.start_times.binary_search(&start_time) {
+                self.start_times.insert(index, start_time);
+                if !first {
+                    restarted.push(start_time);
+                }
+            }
+        }
+        self.restarts += restarted.len();
+        restarted
+    }
+
+    /// Time since the oldest live root process started
+    pub fn uptime(&self) -> Option<Duration> {
+        let since = SystemTime::UNIX_EPOCH + Duration::from_secs(self.running_since?);
+        Some(since.elapsed().unwrap_or_default())
+    }
+
+    /// Counts one tick, plus `missed` ticks the worker failed to run on time
+    pub fn record(&mut self, sampled: bool, missed: u64) {
+        self.started_at.get_or_insert_with(Instant::now);
+        self.expected += 1 + missed;
+        if sampled {
+            self.samples += 1;
+            self.last_sampled_at = Some(Instant::now());
+        }
+    }
+
+    /// Adds the aggregate usage of a sample with a live process
+    pub fn record_usage(&mut self, cpu: f32, memory: usize) {
+        self.peak_cpu = self.peak_cpu.max(cpu);
+        self.cpu_sum += cpu as f64;
+        self.peak_memory = self.peak_memory.max(memory);
+        self.memory_sum += memory as f64;
+    }
+}
+
+/// Whether an identifier had a live process on each of its recent samples.
+/// Kept apart from [`ProcessData`], which is dropped while nothing matches.
+#[derive(Debug, Clone)]
+pub struct Availability {
+    samples: CircularBuffer<bool>,
+    history_len: usize,
+    /// Samples with a live process since monitoring started
+    pub up: u64,
+    pub total: u64,
+}
+
+impl Availability {
+    pub fn new(history_len: usize) -> Self {
+        Self {
+            samples: CircularBuffer::new(history_len),
+            history_len,
+            up: 0,
+            total: 0,
+        }
+    }
+
+    pub fn record(&mut self, up: bool) {
+        self.samples.push(up);
+        self.total += 1;
+        self.up += u64::from(up);
+    }
+
+    /// Changes the window length, keeping the newest samples
+    pub fn resize(&mut self, history_len: usize) {
+        if history_len != self.history_len {
+            self.samples.resize(history_len);
+            self.history_len = history_len;
+        }
+    }
+
+    /// Up/down state of the samples in the history window, oldest first
+    pub fn history(&self) -> Vec<bool> {
+        self.samples.as_vec()
+    }
+
+    /// Share of samples in the history window with a live process, in percent
+    pub fn uptime(&self) -> Option<f32> {
+        let history = self.history();
+        if history.is_empty() {
+            return None;
+        }
+        let up = history.iter().filter(|up| **up).count();
+        Some(up as f32 / history.len() as f32 * 100.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessEventKind {
+    Started,
+    Exited,
+    /// A root process with a new start time replaced or joined the previous ones
+    Restarted,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProcessEvent {
+    pub kind: ProcessEventKind,
+    pub pid: sysinfo::Pid,
+    pub name: String,
+    /// `Metrics::tick` the change was detected on
+    pub tick: u64,
+    pub at: Instant,
+    /// Signal that killed the process, on `Exited` events only
+    pub crash: Option<Crash>,
+}
+
+impl ProcessData {
+    /// Freezes the data after the process exited, keeping the history for inspection
+    pub fn finish(&mut self) {
+        let session = &self.session;
+        let samples = session.samples.max(1) as f64;
+        let duration = match (session.started_at, session.last_sampled_at) {
+            (Some(started_at), Some(last)) => last.duration_since(started_at),
+            _ => Duration::ZERO,
+        };
+        self.finished = Some(RunSummary {
+            duration,
+            ended_at: SystemTime::now(),
+            peak_cpu: session.peak_cpu,
+            avg_cpu: (session.cpu_sum / samples) as f32,
+            peak_memory: session.peak_memory,
+            avg_memory: (session.memory_sum / samples) as usize,
+            children: session.children_seen,
+        });
+        self.clear_current();
+    }
+
+    /// Marks a `Name` identifier without matching processes as inactive
+    pub fn deactivate(&mut self) {
+        self.inactive_since.get_or_insert_with(Instant::now);
+        self.clear_current();
+    }
+
+    /// Zeroes the live values, leaving history and peaks for inspection
+    fn clear_current(&mut self) {
+        self.processes_stats.clear();
+        self.session.running_since = None;
+        let stats = &mut self.genereal.stats;
+        stats.process_count = 0;
+        stats.thread_count = 0;
+        stats.current_cpu = 0.0;
+        stats.current_memory = 0;
+        stats.current_gpu = 0.0;
+        stats.gpu_memory = 0;
+        stats.sched = None;
+    }
+
+    pub fn push_event(&mut self, event: ProcessEvent) {
+        if self.events.len() >= MAX_EVENTS {
+            self.events.remove(0);
+        }
+        self.events.push(event);
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Hash, Eq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -17,13 +283,31 @@ pub enum ProcessIdentifier {
     Name(String),
     #[serde(serialize_with = "serialize_pid", deserialize_with = "deserialize_pid")]
     Pid(sysinfo::Pid),
+    /// Docker/Podman container by name, id or id prefix
+    Container(String),
+    /// Every process in a cgroup and its sub-cgroups, by path below the hierarchy root
+    Cgroup(String),
+    /// Windows service by service name or launchd job by label, followed across restarts
+    Service(String),
+    /// Every process owned by an account, by user name or numeric id
+    User(String),
+    /// Several identifiers sampled as one aggregate tree
+    Group(Vec<ProcessIdentifier>),
 }
 
+/// Separates the members of a group in its string form, `group:a + pid:12`
+const GROUP_SEPARATOR: &str = " + ";
+
 impl ProcessIdentifier {
     pub fn to_pid(&self) -> Option<sysinfo::Pid> {
         match self {
             ProcessIdentifier::Pid(pid) => Some(*pid),
-            ProcessIdentifier::Name(_) => None,
+            ProcessIdentifier::Name(_)
+            | ProcessIdentifier::Container(_)
+            | ProcessIdentifier::Cgroup(_)
+            | ProcessIdentifier::Service(_)
+            | ProcessIdentifier::User(_)
+            | ProcessIdentifier::Group(_) => None,
         }
     }
 }
@@ -45,8 +329,35 @@ where
 
 impl From<&str> for ProcessIdentifier {
     fn from(s: &str) -> Self {
-        if s.starts_with("pid:") {
-            if let Ok(pid) = s[4..].parse::<usize>() {
+        if let Some(members) = s.strip_prefix("group:") {
+            return ProcessIdentifier::Group(
+                members
+                    .split(GROUP_SEPARATOR)
+                    .map(ProcessIdentifier::from)
+                    .collect(),
+            );
+        }
+        if let Some(container) = s.strip_prefix("container:") {
+            return ProcessIdentifier::Container(container.to_string());
+        }
+        if let Some(path) = s.strip_prefix("cgroup:") {
+            return ProcessIdentifier::Cgroup(cgroup::normalize(path).to_string());
+        }
+        if let Some(service) = s.strip_prefix("service:") {
+            return ProcessIdentifier::Service(service.trim().to_string());
+        }
+        if let Some(user) = s.strip_prefix("user:") {
+            return ProcessIdentifier::User(user.trim().to_string());
+        }
+        if let Some(unit) = s.strip_prefix("unit:") {
+            return ProcessIdentifier::Cgroup(cgroup::unit_cgroup(unit.trim()));
+        }
+        // Spelled out for names that would otherwise read as another kind
+        if let Some(name) = s.strip_prefix("name:") {
+            return ProcessIdentifier::Name(name.to_string());
+        }
+        if let Some(pid) = s.strip_prefix("pid:") {
+            if let Ok(pid) = pid.parse::<usize>() {
                 return ProcessIdentifier::Pid(sysinfo::Pid::from(pid));
             }
         }
@@ -54,18 +365,32 @@ impl From<&str> for ProcessIdentifier {
     }
 }
 
-impl ToString for ProcessIdentifier {
-    fn to_string(&self) -> String {
-        match self {
+impl std::fmt::Display for ProcessIdentifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
             ProcessIdentifier::Name(name) => name.clone(),
             ProcessIdentifier::Pid(pid) => format!("pid:{}", pid),
-        }
+            ProcessIdentifier::Container(container) => format!("container:{}", container),
+            ProcessIdentifier::Cgroup(path) => format!("cgroup:{}", path),
+            ProcessIdentifier::Service(service) => format!("service:{}", service),
+            ProcessIdentifier::User(user) => format!("user:{}", user),
+            ProcessIdentifier::Group(members) => format!(
+                "group:{}",
+                members
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(GROUP_SEPARATOR)
+            ),
+        };
+        f.write_str(&s)
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct ProcessInfo {
-    pub name: String,
+    /// Shared with the previous samples of the process, see [`NameCache`]
+    pub name: Arc<str>,
     pub pid: sysinfo::Pid,
     pub parent_pid: Option<sysinfo::Pid>,
     pub is_thread: bool,
@@ -75,18 +400,76 @@ pub struct ProcessInfo {
     pub current_memory: usize,
     pub peak_memory: usize,
     pub avg_memory: usize,
+    /// Lifetime peak RSS reported by the OS, see [`os_peak_memory`]
+    pub os_peak_memory: Option<usize>,
+    pub gpu: GpuUsage,
+    /// Context switch and page fault rates, `None` for threads, on the first
+    /// sample and where the OS doesn't report them
+    pub sched: Option<SchedRates>,
+    /// Stopped by SIGSTOP, see [`ProcessMonitor::signal`]
+    pub suspended: bool,
+    /// Owned by another user, so some figures read empty, see [`access::restricted`]
+    pub restricted: bool,
+    /// Nice value and priority class, `None` for threads and where unreadable
+    pub scheduling: Option<(i32, PriorityClass)>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
 pub enum SortType {
     AvgCpu,
+    /// Current memory
     Memory,
+    CurrentCpu,
+    PeakCpu,
+    PeakMemory,
+    Pid,
+    Name,
+    /// Threads listed under a process
+    ThreadCount,
+    /// Priority class, then nice value
+    Priority,
+}
+
+impl SortType {
+    pub const ALL: [SortType; 9] = [
+        SortType::AvgCpu,
+        SortType::CurrentCpu,
+        SortType::PeakCpu,
+        SortType::Memory,
+        SortType::PeakMemory,
+        SortType::ThreadCount,
+        SortType::Priority,
+        SortType::Pid,
+        SortType::Name,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortType::AvgCpu => "Average CPU",
+            SortType::Memory => "Memory",
+            SortType::CurrentCpu => "Current CPU",
+            SortType::PeakCpu => "Peak CPU",
+            SortType::PeakMemory => "Peak memory",
+            SortType::Pid => "PID",
+            SortType::Name => "Name",
+            SortType::ThreadCount => "Threads",
+            SortType::Priority => "Priority",
+        }
+    }
+
+    /// Whether the order starts from the smallest value when this sort is picked
+    pub fn ascending_by_default(self) -> bool {
+        matches!(self, SortType::Pid | SortType::Name | SortType::Priority)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
 pub enum MetricType {
     Cpu,
     Memory,
+    Gpu,
+    /// Processes and threads of the tree
+    Processes,
 }
 
 impl Default for MetricType {
@@ -112,9 +495,113 @@ pub struct ProcessGeneralStats {
     pub current_cpu: f32,
     pub peak_cpu: f32,
     pub avg_cpu: f32,
+    /// `current_cpu` in percent of what every process on the system used, not of capacity
+    pub cpu_share: f32,
     pub current_memory: usize,
     pub peak_memory: usize,
     pub avg_memory: usize,
     pub process_count: usize,
     pub thread_count: usize,
+    pub cpu_distribution: Distribution,
+    pub memory_distribution: Distribution,
+    pub current_gpu: f32,
+    pub peak_gpu: f32,
+    pub avg_gpu: f32,
+    pub gpu_memory: usize,
+    /// Sum of the live processes' OS-reported lifetime peaks, 0 when unavailable
+    pub os_peak_memory: usize,
+    /// Processes whose figures are partly unavailable, see [`access::restricted`]
+    pub restricted_processes: usize,
+    /// Processes running at [`PriorityClass::Realtime`]
+    pub realtime_processes: usize,
+    /// Processes running at [`PriorityClass::Idle`]
+    pub idle_processes: usize,
+    /// Sum over the processes that report rates, `None` when none does
+    pub sched: Option<SchedRates>,
+    /// Where the memory trend leads, `None` while projection is off or lacks samples
+    pub memory_projection: Option<MemoryProjection>,
+    /// Set while memory grows like a leak, see [`crate::metrics::LeakDetection`]
+    pub leak: Option<LeakSuspect>,
+}
+
+/// Memory growth that lasted long enough to look like a leak
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LeakSuspect {
+    /// Fitted over the detection window
+    pub bytes_per_second: f64,
+    /// When the growth started, as far as the window tells
+    pub since: Instant,
+}
+
+impl LeakSuspect {
+    pub fn mb_per_min(&self) -> f64 {
+        self.bytes_per_second * 60.0 / (1024.0 * 1024.0)
+    }
+}
+
+/// Memory growth fitted over a trailing window, extrapolated to a limit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MemoryProjection {
+    /// Negative while the tree shrinks
+    pub bytes_per_second: f64,
+    /// The same growth per sample, the slope on the plots
+    pub bytes_per_sample: f64,
+    /// Configured limit, or the current memory plus what the system has available
+    pub limit: usize,
+    /// Time until `limit` is reached at this rate, `None` while not growing
+    pub eta: Option<Duration>,
+}
+
+impl MemoryProjection {
+    pub fn new(current: usize, bytes_per_second: f64, interval: Duration, limit: usize) -> Self {
+        let eta = (bytes_per_second > 0.0)
+            .then(|| limit.saturating_sub(current) as f64 / bytes_per_second)
+            .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok());
+        Self {
+            bytes_per_second,
+            bytes_per_sample: bytes_per_second * interval.as_secs_f64(),
+            limit,
+            eta,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sysinfo::Pid;
+
+    #[test]
+    fn restarts_count_start_times_after_the_first_sample() {
+        let mut session = SessionStats::default();
+        // Two workers already running are the baseline, not restarts
+        assert!(session.record_start_times(&[100, 90]).is_empty());
+        assert_eq!(session.running_since, Some(90));
+        assert!(session.record_start_times(&[100, 90]).is_empty());
+        // One worker crashed and came back
+        assert_eq!(session.record_start_times(&[100, 150]), vec![150]);
+        assert_eq!(session.record_start_times(&[150]), Vec::<u64>::new());
+        assert_eq!(session.restarts, 1);
+        assert_eq!(session.running_since, Some(150));
+        assert_eq!(session.start_times, vec![90, 100, 150]);
+    }
+
+    #[test]
+    fn memory_trend_projects_time_to_limit() {
+        let pid = Pid::from_u32(1);
+        let mut history = ProcessHistory::new(10);
+        // An old plateau outside the window must not bend the fit
+        for memory in [9000, 9000, 1000, 1200, 1400, 1600, 1800] {
+            history.update_memory(pid, memory);
+        }
+        let interval = Duration::from_millis(500);
+        let trend = history.memory_trend(&pid, 5, interval).unwrap();
+        assert!((trend - 400.0).abs() < 1e-6, "trend was {trend}");
+        assert!(history.memory_trend(&pid, 2, interval).is_none());
+
+        let projection = MemoryProjection::new(1800, trend, interval, 5800);
+        assert_eq!(projection.bytes_per_sample, 200.0);
+        assert_eq!(projection.eta, Some(Duration::from_secs(10)));
+        assert_eq!(MemoryProjection::new(1800, -5.0, interval, 5800).eta, None);
+    }
 }
@@ -1,14 +1,54 @@
-use super::{ProcessHistory, ProcessIdentifier, ProcessInfo};
-use log::info;
+use super::{cgroup, container, service, user};
+use super::{ExcludeRule, NameCache, ProcessHistory, ProcessIdentifier, ProcessInfo};
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::Path;
 use std::time::{Duration, Instant};
-use sysinfo::{Pid, Process, System};
+use sysinfo::{
+    CpuRefreshKind, Pid, Process, ProcessRefreshKind, ProcessStatus, ProcessesToUpdate, Signal,
+    System, UpdateKind,
+};
+
+/// Above this many processes the sampler switches to safe mode
+pub const SAFE_MODE_PROCESS_COUNT: usize = 10_000;
+
+/// Linux truncates process names (`/proc/<pid>/comm`) to this many bytes
+pub const COMM_MAX_LEN: usize = 15;
+
+/// Whether `process` is called `name`, looking at the executable file name and
+/// the first command line argument when the kernel name could be truncated
+pub fn matches_name(process: &Process, name: &str) -> bool {
+    if process.name().to_string_lossy() == name {
+        return true;
+    }
+    if name.len() <= COMM_MAX_LEN {
+        return false;
+    }
+    let file_name = |path: &Path| path.file_name().is_some_and(|file| file == name);
+    process.exe().is_some_and(file_name)
+        || process
+            .cmd()
+            .first()
+            .is_some_and(|arg| file_name(Path::new(arg)))
+}
+
+/// What [`ProcessMonitor::update`] refreshes
+#[derive(Debug, Clone, Default)]
+enum RefreshScope {
+    /// Everything sysinfo knows about
+    #[default]
+    All,
+    /// Only what sampling needs, for every process
+    Light,
+    /// Only what sampling needs, for the given processes
+    Pids(Vec<Pid>),
+}
 
 #[derive(Debug)]
 pub struct ProcessMonitor {
     pub system: System,
     last_update: Instant,
     update_interval: Duration,
+    scope: RefreshScope,
 }
 
 impl Default for ProcessMonitor {
@@ -17,6 +57,7 @@ impl Default for ProcessMonitor {
             system: System::new_all(),
             last_update: Instant::now(),
             update_interval: Duration::from_millis(1000),
+            scope: RefreshScope::All,
         }
     }
 }
@@ -27,18 +68,147 @@ impl ProcessMonitor {
             system: System::new_all(),
             last_update: Instant::now(),
             update_interval,
+            scope: RefreshScope::All,
         }
     }
 
+    /// Monitor for systems with a huge process count: loads only `pids`,
+    /// or every process when `None`, skipping data tvis doesn't sample
+    pub fn safe_mode(update_interval: Duration, pids: Option<Vec<Pid>>) -> Self {
+        let mut monitor = Self {
+            system: System::new(),
+            last_update: Instant::now(),
+            update_interval,
+            scope: pids.map_or(RefreshScope::Light, RefreshScope::Pids),
+        };
+        monitor
+            .system
+            .refresh_cpu_list(CpuRefreshKind::nothing().with_cpu_usage());
+        monitor.update();
+        monitor
+    }
+
     pub fn should_update(&self) -> bool {
         self.last_update.elapsed() >= self.update_interval
     }
 
     pub fn update(&mut self) {
-        self.system.refresh_all();
+        let refresh_kind = ProcessRefreshKind::nothing()
+            .with_cpu()
+            .with_memory()
+            .with_exe(UpdateKind::OnlyIfNotSet)
+            .with_cmd(UpdateKind::OnlyIfNotSet)
+            .with_user(UpdateKind::OnlyIfNotSet);
+        match &self.scope {
+            RefreshScope::All => self.system.refresh_all(),
+            RefreshScope::Light => {
+                self.system
+                    .refresh_processes_specifics(ProcessesToUpdate::All, true, refresh_kind);
+                self.system.refresh_memory();
+                self.system.refresh_cpu_usage();
+            }
+            RefreshScope::Pids(pids) => {
+                self.system.refresh_processes_specifics(
+                    ProcessesToUpdate::Some(pids),
+                    true,
+                    refresh_kind,
+                );
+                self.system.refresh_memory();
+                self.system.refresh_cpu_usage();
+            }
+        }
         self.last_update = Instant::now();
     }
 
+    /// Whether only some processes are loaded, so system-wide views are incomplete
+    pub fn is_scoped(&self) -> bool {
+        matches!(self.scope, RefreshScope::Pids(_))
+    }
+
+    /// Number of loaded processes, not counting threads
+    pub fn process_count(&self) -> usize {
+        self.system
+            .processes()
+            .values()
+            .filter(|p| p.thread_kind().is_none())
+            .count()
+    }
+
+    /// Names and PIDs of every process from a one-off minimal scan,
+    /// for searching when the monitor is scoped
+    pub fn list_processes() -> Vec<(String, Pid)> {
+        let mut system = System::new();
+        system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::nothing(),
+        );
+        let mut processes: Vec<_> = system
+            .processes()
+            .values()
+            .filter(|p| p.thread_kind().is_none())
+            .map(|p| (p.name().to_string_lossy().into_owned(), p.pid()))
+            .collect();
+        processes.sort_by(|a, b| a.0.cmp(&b.0));
+        processes
+    }
+
+    /// Processes (not threads) whose name or command line satisfies `matches`,
+    /// like `pgrep -f`. The calling process is never included.
+    pub fn find_matching(&self, matches: impl Fn(&str) -> bool) -> Vec<Pid> {
+        let own_pid = sysinfo::get_current_pid().ok();
+        let mut pids: Vec<Pid> = self
+            .system
+            .processes()
+            .values()
+            .filter(|p| p.thread_kind().is_none() && Some(p.pid()) != own_pid)
+            .filter(|p| {
+                let cmd = p
+                    .cmd()
+                    .iter()
+                    .map(|arg| arg.to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                matches(&p.name().to_string_lossy()) || (!cmd.is_empty() && matches(&cmd))
+            })
+            .map(|p| p.pid())
+            .collect();
+        pids.sort();
+        pids
+    }
+
+    /// Number of logical CPUs, at least 1
+    pub fn cpu_count(&self) -> usize {
+        self.system.cpus().len().max(1)
+    }
+
+    /// CPU used by the whole system on the last refresh, in percent of one
+    /// core like the usage of a process
+    pub fn system_cpu(&self) -> f32 {
+        self.system.global_cpu_usage() * self.cpu_count() as f32
+    }
+
+    /// Memory the system can still hand out without swapping, in bytes
+    pub fn available_memory(&self) -> usize {
+        self.system.available_memory() as usize
+    }
+
+    /// Average clock of the cores in MHz, refreshed on each call since the
+    /// scoped refreshes of safe mode skip the CPUs
+    pub fn cpu_frequency(&mut self) -> u64 {
+        self.system.refresh_cpu_frequency();
+        let cpus = self.system.cpus();
+        if cpus.is_empty() {
+            return 0;
+        }
+        cpus.iter().map(|cpu| cpu.frequency()).sum::<u64>() / cpus.len() as u64
+    }
+
+    /// Used and total swap space, in bytes
+    pub fn swap(&self) -> (u64, u64) {
+        (self.system.used_swap(), self.system.total_swap())
+    }
+
     pub fn get_process_by_pid(&self, pid: &Pid) -> Option<&Process> {
         self.system.process(*pid)
     }
@@ -55,11 +225,16 @@ impl ProcessMonitor {
         processes
     }
 
-    pub fn collect_process_info(&self, process: &Process, history: &ProcessHistory) -> ProcessInfo {
+    pub fn collect_process_info(
+        &self,
+        process: &Process,
+        history: &ProcessHistory,
+        names: &mut NameCache,
+    ) -> ProcessInfo {
         let (peak_cpu, peak_memory, avg_cpu, avg_memory) = history.get_data_history(&process.pid());
         let is_thread = process.thread_kind().is_some();
         ProcessInfo {
-            name: process.name().to_string_lossy().into_owned(),
+            name: names.intern(process),
             pid: process.pid(),
             parent_pid: process.parent(),
             current_cpu: process.cpu_usage(),
@@ -69,22 +244,42 @@ impl ProcessMonitor {
             avg_memory,
             peak_cpu,
             peak_memory,
+            os_peak_memory: if is_thread {
+                None
+            } else {
+                super::os_peak_memory(process.pid())
+            },
+            gpu: Default::default(),
+            sched: None,
+            suspended: process.status() == ProcessStatus::Stop,
+            restricted: !is_thread && super::access::restricted(process.pid()),
+            scheduling: if is_thread {
+                None
+            } else {
+                super::scheduling(process.pid())
+            },
         }
     }
 
-    pub fn find_all_relation(&self, identifier: &ProcessIdentifier) -> Option<Vec<Pid>> {
-        let target_pids = match identifier {
-            ProcessIdentifier::Pid(pid) => {
-                vec![*pid]
-            }
-            ProcessIdentifier::Name(name) => self
-                .system
-                .processes()
-                .iter()
-                .filter(|(_, p)| p.name().to_string_lossy() == *name)
-                .map(|(pid, _)| *pid)
-                .collect(),
-        };
+    /// Sends `signal` to `pid`, e.g. [`Signal::Stop`] and [`Signal::Continue`]
+    /// to suspend and resume it
+    pub fn signal(&self, pid: Pid, signal: Signal) -> Result<(), String> {
+        let process = self
+            .get_process_by_pid(&pid)
+            .ok_or_else(|| format!("Process {pid} not found"))?;
+        match process.kill_with(signal) {
+            Some(true) => Ok(()),
+            Some(false) => Err(format!("Failed to send {signal} to {pid}")),
+            None => Err(format!("{signal} is not supported on this platform")),
+        }
+    }
+
+    pub fn find_all_relation(
+        &self,
+        identifier: &ProcessIdentifier,
+        exclude: Option<&ExcludeRule>,
+    ) -> Option<Vec<Pid>> {
+        let target_pids = self.target_pids(identifier);
         if target_pids.is_empty() {
             return None;
         }
@@ -101,27 +296,88 @@ impl ProcessMonitor {
         let mut visited = HashSet::new();
         let mut result = Vec::new();
 
-        for pid in target_pids {
+        for &pid in &target_pids {
             if visited.insert(pid) {
-                queue.push_back(pid);
+                queue.push_back((pid, 0));
             }
         }
 
-        while let Some(current_pid) = queue.pop_front() {
+        while let Some((current_pid, depth)) = queue.pop_front() {
             result.push(current_pid);
 
             if let Some(children) = parent_to_children.get(&Some(current_pid)) {
                 for &child_pid in children {
-                    if visited.insert(child_pid) {
-                        queue.push_back(child_pid);
+                    let excluded = exclude.is_some_and(|rule| {
+                        self.system
+                            .process(child_pid)
+                            .is_some_and(|child| rule.excludes(child, depth + 1))
+                    });
+                    if !excluded && visited.insert(child_pid) {
+                        queue.push_back((child_pid, depth + 1));
                     }
                 }
             }
         }
 
+        if exclude.is_some_and(|rule| rule.ancestors) {
+            for pid in target_pids {
+                let mut parent = self.system.process(pid).and_then(Process::parent);
+                while let Some(pid) = parent.filter(|pid| visited.insert(*pid)) {
+                    result.push(pid);
+                    parent = self.system.process(pid).and_then(Process::parent);
+                }
+            }
+        }
+
         (!result.is_empty()).then_some(result)
     }
 
+    /// Processes `identifier` matches directly, before adding their children
+    fn target_pids(&self, identifier: &ProcessIdentifier) -> Vec<Pid> {
+        match identifier {
+            ProcessIdentifier::Pid(pid) => {
+                vec![*pid]
+            }
+            ProcessIdentifier::Name(name) => self
+                .system
+                .processes()
+                .iter()
+                .filter(|(_, p)| matches_name(p, name))
+                .map(|(pid, _)| *pid)
+                .collect(),
+            ProcessIdentifier::Container(container) => self.container_processes(container),
+            ProcessIdentifier::Cgroup(path) => cgroup::cgroup_pids(path)
+                .into_iter()
+                .filter(|pid| self.system.process(*pid).is_some())
+                .collect(),
+            ProcessIdentifier::Service(name) => service::service_pid(name)
+                .filter(|pid| self.system.process(*pid).is_some())
+                .into_iter()
+                .collect(),
+            ProcessIdentifier::User(user) => self.user_processes(user),
+            ProcessIdentifier::Group(members) => members
+                .iter()
+                .flat_map(|member| self.target_pids(member))
+                .collect(),
+        }
+    }
+
+    /// CPU and memory in bytes of the processes (not threads) of each name, summed
+    pub fn usage_by_name(&self) -> HashMap<String, (f32, u64)> {
+        let mut usage: HashMap<String, (f32, u64)> = HashMap::new();
+        for process in self.system.processes().values() {
+            if process.thread_kind().is_some() {
+                continue;
+            }
+            let entry = usage
+                .entry(process.name().to_string_lossy().into_owned())
+                .or_default();
+            entry.0 += process.cpu_usage();
+            entry.1 += process.memory();
+        }
+        usage
+    }
+
     pub fn get_all_processes_with_pid(&self) -> Vec<(String, sysinfo::Pid)> {
         let mut processes: Vec<_> = self
             .system
@@ -140,7 +396,86 @@ impl ProcessMonitor {
                 .system
                 .processes()
                 .values()
-                .any(|p| p.name().to_string_lossy() == *name),
+                .any(|p| matches_name(p, name)),
+            ProcessIdentifier::Container(container) => {
+                !self.container_processes(container).is_empty()
+            }
+            ProcessIdentifier::Cgroup(_)
+            | ProcessIdentifier::Service(_)
+            | ProcessIdentifier::User(_) => !self.target_pids(identifier).is_empty(),
+            ProcessIdentifier::Group(members) => {
+                members.iter().any(|member| self.process_exists(member))
+            }
+        }
+    }
+
+    /// Processes whose cgroup belongs to `container` (name, id or id prefix)
+    pub fn container_processes(&self, container: &str) -> Vec<Pid> {
+        let names = container::container_names();
+        self.system
+            .processes()
+            .values()
+            .filter(|process| process.thread_kind().is_none())
+            .filter(|process| {
+                container::container_id(process.pid())
+                    .is_some_and(|id| container::matches(container, &id, &names))
+            })
+            .map(|process| process.pid())
+            .collect()
+    }
+
+    /// Processes owned by `user`, a name or numeric id
+    pub fn user_processes(&self, user: &str) -> Vec<Pid> {
+        let users = user::users();
+        self.system
+            .processes()
+            .values()
+            .filter(|process| process.thread_kind().is_none())
+            .filter(|process| {
+                process
+                    .user_id()
+                    .is_some_and(|uid| user::matches(user, uid, &users))
+            })
+            .map(|process| process.pid())
+            .collect()
+    }
+
+    /// Accounts owning at least one process as (name, process count), by name
+    pub fn get_all_users(&self) -> Vec<(String, usize)> {
+        let known = user::users();
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for process in self.system.processes().values() {
+            if process.thread_kind().is_some() {
+                continue;
+            }
+            if let Some(uid) = process.user_id() {
+                *counts.entry(user::user_name(uid, &known)).or_default() += 1;
+            }
         }
+        let mut users: Vec<(String, usize)> = counts.into_iter().collect();
+        users.sort();
+        users
+    }
+
+    /// Running containers as (id, name), with the name falling back to the short id
+    pub fn get_all_containers(&self) -> Vec<(String, String)> {
+        let names = container::container_names();
+        let mut containers: Vec<(String, String)> = self
+            .system
+            .processes()
+            .keys()
+            .filter_map(|pid| container::container_id(*pid))
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .map(|id| {
+                let name = names
+                    .get(&id)
+                    .cloned()
+                    .unwrap_or_else(|| id[..container::SHORT_ID_LEN].to_string());
+                (id, name)
+            })
+            .collect();
+        containers.sort_by(|a, b| a.1.cmp(&b.1));
+        containers
     }
 }
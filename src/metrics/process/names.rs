@@ -0,0 +1,38 @@
+//! Process names shared between ticks instead of converted afresh for every sample.
+//!
+//! A name is keyed by PID and start time, so a reused PID gets its own entry.
+//! Names of processes not looked up during a tick are dropped at its end.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use sysinfo::{Pid, Process};
+
+#[derive(Debug, Default)]
+pub struct NameCache {
+    /// Names looked up during the current tick
+    current: HashMap<(Pid, u64), Arc<str>>,
+    /// Names of the previous tick, moved to `current` when looked up again
+    previous: HashMap<(Pid, u64), Arc<str>>,
+}
+
+impl NameCache {
+    /// Name of `process`, allocated only the first time it is seen
+    pub fn intern(&mut self, process: &Process) -> Arc<str> {
+        let key = (process.pid(), process.start_time());
+        if let Some(name) = self.current.get(&key) {
+            return Arc::clone(name);
+        }
+        let name = self
+            .previous
+            .remove(&key)
+            .unwrap_or_else(|| Arc::from(process.name().to_string_lossy()));
+        self.current.insert(key, Arc::clone(&name));
+        name
+    }
+
+    /// Forgets the names not looked up since the last call, keeping both maps' capacity
+    pub fn end_tick(&mut self) {
+        self.previous.clear();
+        std::mem::swap(&mut self.current, &mut self.previous);
+    }
+}
@@ -0,0 +1,64 @@
+//! Lifetime peak resident memory as reported by the OS, which also covers
+//! peaks from before monitoring started or between two samples.
+
+use sysinfo::Pid;
+
+/// Peak resident set size of `pid` in bytes, `None` where the OS doesn't report it
+pub fn os_peak_memory(pid: Pid) -> Option<usize> {
+    imp::os_peak_memory(pid)
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use sysinfo::Pid;
+
+    /// `VmHWM` from `/proc/<pid>/status`
+    pub fn os_peak_memory(pid: Pid) -> Option<usize> {
+        let status = std::fs::read_to_string(format!("/proc/{pid}/status")).ok()?;
+        let kilobytes = status
+            .lines()
+            .find_map(|line| line.strip_prefix("VmHWM:"))?
+            .trim()
+            .strip_suffix("kB")?
+            .trim()
+            .parse::<usize>()
+            .ok()?;
+        Some(kilobytes * 1024)
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use sysinfo::Pid;
+    use windows_sys::Win32::Foundation::CloseHandle;
+    use windows_sys::Win32::System::ProcessStatus::{
+        GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS,
+    };
+    use windows_sys::Win32::System::Threading::{OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION};
+
+    /// `PeakWorkingSetSize` from `GetProcessMemoryInfo`
+    pub fn os_peak_memory(pid: Pid) -> Option<usize> {
+        // SAFETY: the handle is checked before use and closed before returning,
+        // and the counters struct is sized as the API expects.
+        unsafe {
+            let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid.as_u32());
+            if handle.is_null() {
+                return None;
+            }
+            let mut counters: PROCESS_MEMORY_COUNTERS = std::mem::zeroed();
+            counters.cb = std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+            let ok = GetProcessMemoryInfo(handle, &mut counters, counters.cb);
+            CloseHandle(handle);
+            (ok != 0).then_some(counters.PeakWorkingSetSize)
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+mod imp {
+    use sysinfo::Pid;
+
+    pub fn os_peak_memory(_pid: Pid) -> Option<usize> {
+        None
+    }
+}
@@ -0,0 +1,369 @@
+//! Nice value and CPU affinity of a process: `setpriority` and
+//! `sched_setaffinity` on Linux, priority classes and affinity masks on Windows.
+
+use std::ops::RangeInclusive;
+use sysinfo::Pid;
+
+/// Nice values from the highest priority to the lowest
+pub const NICE_RANGE: RangeInclusive<i32> = -20..=19;
+
+/// Scheduling settings of a process, `None` where the OS doesn't report one
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Priority {
+    /// On Windows, the priority class mapped onto [`NICE_RANGE`]
+    pub nice: Option<i32>,
+    /// Logical CPUs the process may run on, ascending
+    pub affinity: Option<Vec<usize>>,
+}
+
+impl Priority {
+    pub fn read(pid: Pid) -> Self {
+        Self {
+            nice: imp::nice(pid),
+            affinity: imp::affinity(pid),
+        }
+    }
+}
+
+/// Coarse scheduling priority, ordered from the highest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PriorityClass {
+    /// A realtime scheduling policy, or the realtime class on Windows
+    Realtime,
+    High,
+    Normal,
+    Low,
+    /// Runs only when nothing else wants the CPU: `SCHED_IDLE`, nice 19 or the idle class
+    Idle,
+}
+
+impl PriorityClass {
+    /// Class of a process under the default scheduling policy, the thresholds
+    /// match the above- and below-normal classes of Windows
+    pub fn from_nice(nice: i32) -> Self {
+        match nice {
+            ..=-5 => Self::High,
+            -4..=4 => Self::Normal,
+            5..=18 => Self::Low,
+            _ => Self::Idle,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Realtime => "Realtime",
+            Self::High => "High",
+            Self::Normal => "Normal",
+            Self::Low => "Low",
+            Self::Idle => "Idle",
+        }
+    }
+
+    /// Priorities that starve others or get starved, the usual suspects of a priority inversion
+    pub fn is_extreme(self) -> bool {
+        matches!(self, Self::Realtime | Self::Idle)
+    }
+}
+
+/// Nice value and class of `pid`, cheap enough to read on every sample
+pub fn scheduling(pid: Pid) -> Option<(i32, PriorityClass)> {
+    imp::scheduling(pid)
+}
+
+/// Changes the nice value of every thread of `pid`
+pub fn set_nice(pid: Pid, nice: i32) -> Result<(), String> {
+    if !NICE_RANGE.contains(&nice) {
+        return Err(format!("Nice must be between -20 and 19, not {nice}"));
+    }
+    imp::set_nice(pid, nice)
+}
+
+/// Restricts every thread of `pid` to `cpus`
+pub fn set_affinity(pid: Pid, cpus: &[usize]) -> Result<(), String> {
+    if cpus.is_empty() {
+        return Err("Select at least one CPU".to_string());
+    }
+    imp::set_affinity(pid, cpus)
+}
+
+/// OS error with a hint at the privilege it likely lacked
+#[cfg(any(unix, windows))]
+fn describe(err: std::io::Error) -> String {
+    if err.kind() == std::io::ErrorKind::PermissionDenied {
+        let hint = if cfg!(windows) {
+            "run tvis as administrator to change processes of other users"
+        } else {
+            "raising priority, or changing another user's process, needs root or CAP_SYS_NICE"
+        };
+        format!("Permission denied: {hint}")
+    } else {
+        err.to_string()
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use super::{describe, PriorityClass};
+    use std::io;
+    use sysinfo::Pid;
+
+    /// Threads of `pid`, Linux keeps the nice value and affinity per thread
+    fn tasks(pid: Pid) -> Vec<libc::pid_t> {
+        let tasks: Vec<libc::pid_t> = std::fs::read_dir(format!("/proc/{pid}/task"))
+            .into_iter()
+            .flatten()
+            .filter_map(Result::ok)
+            .filter_map(|task| task.file_name().to_str()?.parse().ok())
+            .collect();
+        if tasks.is_empty() {
+            vec![pid.as_u32() as libc::pid_t]
+        } else {
+            tasks
+        }
+    }
+
+    /// Field 19 of `/proc/<pid>/stat`, unlike `getpriority` it can't be confused with an error
+    pub fn nice(pid: Pid) -> Option<i32> {
+        let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+        // Fields resume after the command name, whose last ')' ends it; `state` is field 3
+        stat.get(stat.rfind(')')? + 1..)?
+            .split_whitespace()
+            .nth(19 - 3)?
+            .parse()
+            .ok()
+    }
+
+    /// Fields 19 and 41 of `/proc/<pid>/stat`, the nice value and the policy of the main thread
+    pub fn scheduling(pid: Pid) -> Option<(i32, PriorityClass)> {
+        let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+        let mut fields = stat.get(stat.rfind(')')? + 1..)?.split_whitespace();
+        let nice: i32 = fields.nth(19 - 3)?.parse().ok()?;
+        let policy: i32 = fields.nth(41 - 19 - 1)?.parse().ok()?;
+        let class = match policy {
+            libc::SCHED_FIFO | libc::SCHED_RR | libc::SCHED_DEADLINE => PriorityClass::Realtime,
+            libc::SCHED_IDLE => PriorityClass::Idle,
+            _ => PriorityClass::from_nice(nice),
+        };
+        Some((nice, class))
+    }
+
+    pub fn set_nice(pid: Pid, nice: i32) -> Result<(), String> {
+        for task in tasks(pid) {
+            // SAFETY: plain syscall on an id, no memory is passed
+            let result = unsafe { libc::setpriority(libc::PRIO_PROCESS, task as libc::id_t, nice) };
+            if result != 0 {
+                return Err(describe(io::Error::last_os_error()));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn affinity(pid: Pid) -> Option<Vec<usize>> {
+        // SAFETY: the set is a plain bit array sized as passed to the kernel
+        unsafe {
+            let mut set: libc::cpu_set_t = std::mem::zeroed();
+            let size = std::mem::size_of::<libc::cpu_set_t>();
+            if libc::sched_getaffinity(pid.as_u32() as libc::pid_t, size, &mut set) != 0 {
+                return None;
+            }
+            Some(
+                (0..libc::CPU_SETSIZE as usize)
+                    .filter(|&cpu| libc::CPU_ISSET(cpu, &set))
+                    .collect(),
+            )
+        }
+    }
+
+    pub fn set_affinity(pid: Pid, cpus: &[usize]) -> Result<(), String> {
+        // SAFETY: as in `affinity`, CPU_SET ignores CPUs past the end of the set
+        unsafe {
+            let mut set: libc::cpu_set_t = std::mem::zeroed();
+            for &cpu in cpus {
+                libc::CPU_SET(cpu, &mut set);
+            }
+            let size = std::mem::size_of::<libc::cpu_set_t>();
+            for task in tasks(pid) {
+                if libc::sched_setaffinity(task, size, &set) != 0 {
+                    return Err(describe(io::Error::last_os_error()));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod imp {
+    use super::{describe, PriorityClass};
+    use std::io;
+    use sysinfo::Pid;
+
+    pub fn nice(pid: Pid) -> Option<i32> {
+        // SAFETY: errno is thread-local; it is cleared first since -1 is also a valid nice value
+        unsafe {
+            *libc::__error() = 0;
+            let nice = libc::getpriority(libc::PRIO_PROCESS, pid.as_u32() as libc::id_t);
+            (nice != -1 || *libc::__error() == 0).then_some(nice)
+        }
+    }
+
+    pub fn scheduling(pid: Pid) -> Option<(i32, PriorityClass)> {
+        let nice = nice(pid)?;
+        Some((nice, PriorityClass::from_nice(nice)))
+    }
+
+    pub fn set_nice(pid: Pid, nice: i32) -> Result<(), String> {
+        // SAFETY: plain syscall on an id, no memory is passed
+        let result =
+            unsafe { libc::setpriority(libc::PRIO_PROCESS, pid.as_u32() as libc::id_t, nice) };
+        if result != 0 {
+            return Err(describe(io::Error::last_os_error()));
+        }
+        Ok(())
+    }
+
+    pub fn affinity(_pid: Pid) -> Option<Vec<usize>> {
+        None
+    }
+
+    pub fn set_affinity(_pid: Pid, _cpus: &[usize]) -> Result<(), String> {
+        Err("macOS does not support CPU affinity".to_string())
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use super::{describe, PriorityClass};
+    use std::io;
+    use sysinfo::Pid;
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows_sys::Win32::System::Threading::{
+        GetPriorityClass, GetProcessAffinityMask, OpenProcess, SetPriorityClass,
+        SetProcessAffinityMask, ABOVE_NORMAL_PRIORITY_CLASS, BELOW_NORMAL_PRIORITY_CLASS,
+        HIGH_PRIORITY_CLASS, IDLE_PRIORITY_CLASS, NORMAL_PRIORITY_CLASS,
+        PROCESS_QUERY_LIMITED_INFORMATION, PROCESS_SET_INFORMATION, REALTIME_PRIORITY_CLASS,
+    };
+
+    /// Priority classes and the nice value they stand for, highest priority first
+    const CLASSES: [(u32, i32); 6] = [
+        (REALTIME_PRIORITY_CLASS, -20),
+        (HIGH_PRIORITY_CLASS, -10),
+        (ABOVE_NORMAL_PRIORITY_CLASS, -5),
+        (NORMAL_PRIORITY_CLASS, 0),
+        (BELOW_NORMAL_PRIORITY_CLASS, 10),
+        (IDLE_PRIORITY_CLASS, 19),
+    ];
+
+    /// Runs `f` on a handle of `pid` opened with `access`, closing it afterwards
+    fn with_process<T>(
+        pid: Pid,
+        access: u32,
+        f: impl FnOnce(HANDLE) -> Result<T, String>,
+    ) -> Result<T, String> {
+        // SAFETY: the handle is checked before use and closed before returning
+        unsafe {
+            let handle = OpenProcess(access, 0, pid.as_u32());
+            if handle.is_null() {
+                return Err(describe(io::Error::last_os_error()));
+            }
+            let result = f(handle);
+            CloseHandle(handle);
+            result
+        }
+    }
+
+    pub fn nice(pid: Pid) -> Option<i32> {
+        with_process(pid, PROCESS_QUERY_LIMITED_INFORMATION, |handle| {
+            // SAFETY: `handle` is open for querying
+            let class = unsafe { GetPriorityClass(handle) };
+            CLASSES
+                .iter()
+                .find(|(known, _)| *known == class)
+                .map(|(_, nice)| *nice)
+                .ok_or_else(String::new)
+        })
+        .ok()
+    }
+
+    /// Only the realtime class maps to -20 and only the idle class to 19
+    pub fn scheduling(pid: Pid) -> Option<(i32, PriorityClass)> {
+        let nice = nice(pid)?;
+        let class = match nice {
+            -20 => PriorityClass::Realtime,
+            nice => PriorityClass::from_nice(nice),
+        };
+        Some((nice, class))
+    }
+
+    /// Picks the class closest to `nice`
+    pub fn set_nice(pid: Pid, nice: i32) -> Result<(), String> {
+        let (class, _) = CLASSES
+            .iter()
+            .min_by_key(|(_, class_nice)| (class_nice - nice).abs())
+            .copied()
+            .unwrap_or((NORMAL_PRIORITY_CLASS, 0));
+        with_process(pid, PROCESS_SET_INFORMATION, |handle| {
+            // SAFETY: `handle` is open for setting information
+            if unsafe { SetPriorityClass(handle, class) } == 0 {
+                return Err(describe(io::Error::last_os_error()));
+            }
+            Ok(())
+        })
+    }
+
+    /// Covers the CPUs of the process's processor group, at most 64
+    pub fn affinity(pid: Pid) -> Option<Vec<usize>> {
+        with_process(pid, PROCESS_QUERY_LIMITED_INFORMATION, |handle| {
+            let mut process_mask = 0usize;
+            let mut system_mask = 0usize;
+            // SAFETY: `handle` is open for querying, both masks are valid out pointers
+            if unsafe { GetProcessAffinityMask(handle, &mut process_mask, &mut system_mask) } == 0 {
+                return Err(String::new());
+            }
+            Ok((0..usize::BITS as usize)
+                .filter(|&cpu| process_mask & (1 << cpu) != 0)
+                .collect())
+        })
+        .ok()
+    }
+
+    pub fn set_affinity(pid: Pid, cpus: &[usize]) -> Result<(), String> {
+        let mask = cpus
+            .iter()
+            .filter(|&&cpu| cpu < usize::BITS as usize)
+            .fold(0usize, |mask, &cpu| mask | (1 << cpu));
+        with_process(pid, PROCESS_SET_INFORMATION, |handle| {
+            // SAFETY: `handle` is open for setting information
+            if unsafe { SetProcessAffinityMask(handle, mask) } == 0 {
+                return Err(describe(io::Error::last_os_error()));
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+mod imp {
+    use super::PriorityClass;
+    use sysinfo::Pid;
+
+    pub fn nice(_pid: Pid) -> Option<i32> {
+        None
+    }
+
+    pub fn scheduling(_pid: Pid) -> Option<(i32, PriorityClass)> {
+        None
+    }
+
+    pub fn set_nice(_pid: Pid, _nice: i32) -> Result<(), String> {
+        Err("Changing priority is not supported on this platform".to_string())
+    }
+
+    pub fn affinity(_pid: Pid) -> Option<Vec<usize>> {
+        None
+    }
+
+    pub fn set_affinity(_pid: Pid, _cpus: &[usize]) -> Result<(), String> {
+        Err("CPU affinity is not supported on this platform".to_string())
+    }
+}
@@ -0,0 +1,263 @@
+//! Context switch, page fault and CPU time counters, turned into per-second rates.
+//!
+//! Involuntary switches climbing with the CPU flat point at scheduler contention,
+//! major faults at a process thrashing on swap or evicted file pages. CPU time is
+//! split into user, system and I/O wait, which tells a compute-bound process from
+//! one waiting on disk. With the `ebpf` feature, syscalls and TCP bytes are
+//! counted too, see [`super::ebpf`].
+
+use std::ops::AddAssign;
+use std::time::Duration;
+use sysinfo::Pid;
+
+/// Cumulative counters of a process since it started
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SchedCounters {
+    pub voluntary_switches: u64,
+    pub involuntary_switches: u64,
+    pub minor_faults: u64,
+    pub major_faults: u64,
+    /// CPU time in clock ticks, see [`clock_ticks`]
+    pub user_ticks: u64,
+    pub system_ticks: u64,
+    /// Time blocked on block I/O, only counted with delay accounting enabled
+    /// (`kernel.task_delayacct`)
+    pub iowait_ticks: u64,
+    /// Counted by the eBPF programs since they were loaded, 0 without them
+    pub syscalls: u64,
+    pub tcp_sent_bytes: u64,
+    pub tcp_received_bytes: u64,
+}
+
+/// Events per second between two reads of [`SchedCounters`]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SchedRates {
+    pub voluntary_switches: f32,
+    pub involuntary_switches: f32,
+    pub minor_faults: f32,
+    pub major_faults: f32,
+    /// Percent of one CPU, like the CPU usage
+    pub user_cpu: f32,
+    pub system_cpu: f32,
+    pub iowait: f32,
+    pub syscalls: f32,
+    /// Bytes per second
+    pub tcp_sent: f32,
+    pub tcp_received: f32,
+}
+
+impl AddAssign for SchedRates {
+    fn add_assign(&mut self, other: Self) {
+        self.voluntary_switches += other.voluntary_switches;
+        self.involuntary_switches += other.involuntary_switches;
+        self.minor_faults += other.minor_faults;
+        self.major_faults += other.major_faults;
+        self.user_cpu += other.user_cpu;
+        self.system_cpu += other.system_cpu;
+        self.iowait += other.iowait;
+        self.syscalls += other.syscalls;
+        self.tcp_sent += other.tcp_sent;
+        self.tcp_received += other.tcp_received;
+    }
+}
+
+impl SchedCounters {
+    /// Counters of `pid` summed over its threads, `None` where the OS doesn't report them
+    pub fn read(pid: Pid) -> Option<Self> {
+        imp::read(pid)
+    }
+
+    /// Rates since `previous`, read `elapsed` earlier. A counter going
+    /// backwards (an exited thread, a reused PID) counts as no events.
+    pub fn rates_since(&self, previous: &Self, elapsed: Duration) -> SchedRates {
+        let seconds = elapsed.as_secs_f64().max(f64::EPSILON);
+        let rate = |now: u64, before: u64| (now.saturating_sub(before) as f64 / seconds) as f32;
+        let percent = |now: u64, before: u64| rate(now, before) / clock_ticks() as f32 * 100.0;
+        SchedRates {
+            voluntary_switches: rate(self.voluntary_switches, previous.voluntary_switches),
+            involuntary_switches: rate(self.involuntary_switches, previous.involuntary_switches),
+            minor_faults: rate(self.minor_faults, previous.minor_faults),
+            major_faults: rate(self.major_faults, previous.major_faults),
+            user_cpu: percent(self.user_ticks, previous.user_ticks),
+            system_cpu: percent(self.system_ticks, previous.system_ticks),
+            iowait: percent(self.iowait_ticks, previous.iowait_ticks),
+            syscalls: rate(self.syscalls, previous.syscalls),
+            tcp_sent: rate(self.tcp_sent_bytes, previous.tcp_sent_bytes),
+            tcp_received: rate(self.tcp_received_bytes, previous.tcp_received_bytes),
+        }
+    }
+}
+
+/// `(voluntary, involuntary)` context switches from `/proc/<pid>/status`
+pub fn parse_status(status: &str) -> Option<(u64, u64)> {
+    let field = |name: &str| {
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix(name))?
+            .trim()
+            .parse::<u64>()
+            .ok()
+    };
+    Some((
+        field("voluntary_ctxt_switches:")?,
+        field("nonvoluntary_ctxt_switches:")?,
+    ))
+}
+
+/// `(minor, major)` page faults from `/proc/<pid>/stat`
+pub fn parse_stat(stat: &str) -> Option<(u64, u64)> {
+    // The command name can contain spaces and parentheses, fields resume after the last ')'
+    let fields: Vec<&str> = stat
+        .get(stat.rfind(')')? + 1..)?
+        .split_whitespace()
+        .collect();
+    // `state` is field 3 of proc(5), `minflt` field 10 and `majflt` field 12
+    let field = |number: usize| fields.get(number - 3)?.parse::<u64>().ok();
+    Some((field(10)?, field(12)?))
+}
+
+/// `(user, system, iowait)` clock ticks from `/proc/<pid>/stat`. I/O wait is
+/// 0 on kernels too old to report it.
+pub fn parse_cpu_times(stat: &str) -> Option<(u64, u64, u64)> {
+    let fields: Vec<&str> = stat
+        .get(stat.rfind(')')? + 1..)?
+        .split_whitespace()
+        .collect();
+    // `utime` is field 14, `stime` 15 and `delayacct_blkio_ticks` 42
+    let field = |number: usize| fields.get(number - 3)?.parse::<u64>().ok();
+    Some((field(14)?, field(15)?, field(42).unwrap_or(0)))
+}
+
+/// Clock ticks per second, the unit of the CPU times in [`SchedCounters`]
+pub fn clock_ticks() -> u64 {
+    imp::clock_ticks()
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use super::{parse_cpu_times, parse_stat, parse_status, SchedCounters};
+    use sysinfo::Pid;
+
+    /// `stat` already covers the whole process, `status` only the one task,
+    /// so switches are added up from every entry of `task`
+    pub fn read(pid: Pid) -> Option<SchedCounters> {
+        let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+        let (minor_faults, major_faults) = parse_stat(&stat)?;
+        let (user_ticks, system_ticks, iowait_ticks) = parse_cpu_times(&stat).unwrap_or_default();
+        let tasks = std::fs::read_dir(format!("/proc/{pid}/task")).ok()?;
+        let (voluntary_switches, involuntary_switches) = tasks
+            .filter_map(Result::ok)
+            .filter_map(|task| std::fs::read_to_string(task.path().join("status")).ok())
+            .filter_map(|status| parse_status(&status))
+            .fold(
+                (0, 0),
+                |(voluntary, involuntary), (task_voluntary, task_involuntary)| {
+                    (voluntary + task_voluntary, involuntary + task_involuntary)
+                },
+            );
+        let (syscalls, tcp_sent_bytes, tcp_received_bytes) = ebpf_counts(pid);
+        Some(SchedCounters {
+            voluntary_switches,
+            involuntary_switches,
+            minor_faults,
+            major_faults,
+            user_ticks,
+            system_ticks,
+            iowait_ticks,
+            syscalls,
+            tcp_sent_bytes,
+            tcp_received_bytes,
+        })
+    }
+
+    /// `(syscalls, TCP bytes sent, TCP bytes received)` of `pid`
+    #[cfg(feature = "ebpf")]
+    fn ebpf_counts(pid: Pid) -> (u64, u64, u64) {
+        super::super::ebpf::probes().map_or((0, 0, 0), |probes| {
+            let counts = probes.read(pid);
+            (
+                counts.syscalls,
+                counts.tcp_sent_bytes.unwrap_or_default(),
+                counts.tcp_received_bytes.unwrap_or_default(),
+            )
+        })
+    }
+
+    #[cfg(not(feature = "ebpf"))]
+    fn ebpf_counts(_pid: Pid) -> (u64, u64, u64) {
+        (0, 0, 0)
+    }
+
+    pub fn clock_ticks() -> u64 {
+        // SAFETY: sysconf only reads a configuration value
+        let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+        if ticks > 0 {
+            ticks as u64
+        } else {
+            100
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod imp {
+    use super::SchedCounters;
+    use sysinfo::Pid;
+
+    pub fn read(_pid: Pid) -> Option<SchedCounters> {
+        None
+    }
+
+    pub fn clock_ticks() -> u64 {
+        100
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::process::clock_ticks;
+
+    #[test]
+    fn sched_counters_parse_proc_files() {
+        let status =
+            "Name:\tworker\nvoluntary_ctxt_switches:\t150\nnonvoluntary_ctxt_switches:\t7\n";
+        assert_eq!(parse_status(status), Some((150, 7)));
+        // A command name with a space and a parenthesis must not shift the fields
+        let stat = "4242 (my (app) x) S 1 4242 4242 0 -1 4194560 1200 0 3 0 10 5";
+        assert_eq!(parse_stat(stat), Some((1200, 3)));
+        assert_eq!(parse_stat("4242 (truncated"), None);
+        // Too short to reach I/O wait, as on old kernels
+        assert_eq!(parse_cpu_times(stat), Some((10, 5, 0)));
+        let full = format!("{stat}{} 7", " 0".repeat(41 - 15));
+        assert_eq!(parse_cpu_times(&full), Some((10, 5, 7)));
+
+        let before = SchedCounters {
+            voluntary_switches: 10,
+            minor_faults: 100,
+            ..Default::default()
+        };
+        let after = SchedCounters {
+            voluntary_switches: 30,
+            minor_faults: 500,
+            ..Default::default()
+        };
+        let rates = after.rates_since(&before, Duration::from_secs(2));
+        assert_eq!(rates.voluntary_switches, 10.0);
+        assert_eq!(rates.minor_faults, 200.0);
+        let busy = SchedCounters {
+            user_ticks: clock_ticks(),
+            iowait_ticks: clock_ticks() / 2,
+            ..Default::default()
+        };
+        let rates = busy.rates_since(&SchedCounters::default(), Duration::from_secs(1));
+        assert_eq!(rates.user_cpu, 100.0);
+        assert_eq!(rates.iowait, 50.0);
+        assert_eq!(
+            before
+                .rates_since(&after, Duration::from_secs(2))
+                .minor_faults,
+            0.0
+        );
+    }
+}
@@ -0,0 +1,215 @@
+//! Services by name: Windows services from the service control manager, launchd
+//! jobs from `launchctl list`.
+//!
+//! A service is resolved to its current PID on every refresh of the list, so a
+//! restarted service is followed to its new process. On Linux, services are
+//! addressed by systemd unit through their cgroup instead (`unit:nginx`).
+
+use sysinfo::Pid;
+
+/// A registered service and the process it runs in, if it is running
+#[derive(Debug, Clone, PartialEq)]
+pub struct Service {
+    /// Service name on Windows, job label on macOS
+    pub name: String,
+    /// Name shown in the Services console, the label again on macOS
+    pub display_name: String,
+    pub pid: Option<Pid>,
+}
+
+/// Services known to the OS, refreshed at most every few seconds
+pub fn services() -> Vec<Service> {
+    #[cfg(any(windows, target_os = "macos"))]
+    {
+        use std::sync::Mutex;
+        use std::time::{Duration, Instant};
+
+        // Short enough that a restarted service is picked up within a couple of samples
+        const TTL: Duration = Duration::from_secs(2);
+        static CACHE: Mutex<Option<(Instant, Vec<Service>)>> = Mutex::new(None);
+
+        let mut cache = CACHE.lock().unwrap();
+        match &*cache {
+            Some((at, services)) if at.elapsed() < TTL => services.clone(),
+            _ => {
+                let services = imp::list();
+                *cache = Some((Instant::now(), services.clone()));
+                services
+            }
+        }
+    }
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        Vec::new()
+    }
+}
+
+/// Running services, sorted by display name
+pub fn running_services() -> Vec<Service> {
+    let mut running: Vec<Service> = services()
+        .into_iter()
+        .filter(|service| service.pid.is_some())
+        .collect();
+    running.sort_by(|a, b| a.display_name.cmp(&b.display_name));
+    running
+}
+
+/// Process of the service `name`. Windows service names ignore case, launchd labels don't.
+pub fn service_pid(name: &str) -> Option<Pid> {
+    services()
+        .into_iter()
+        .find(|service| {
+            if cfg!(windows) {
+                service.name.eq_ignore_ascii_case(name)
+            } else {
+                service.name == name
+            }
+        })?
+        .pid
+}
+
+/// `PID  Status  Label` lines of `launchctl list`, with `-` for jobs not running
+pub fn parse_launchctl_list(output: &str) -> Vec<Service> {
+    output
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let pid = fields.next()?.trim();
+            let label = fields.nth(1)?.trim();
+            if label.is_empty() {
+                return None;
+            }
+            Some(Service {
+                name: label.to_string(),
+                display_name: label.to_string(),
+                pid: pid.parse::<usize>().ok().map(Pid::from),
+            })
+        })
+        .collect()
+}
+
+#[cfg(target_os = "macos")]
+mod imp {
+    use super::{parse_launchctl_list, Service};
+
+    /// Jobs of the caller's domain; run as root to see system daemons
+    pub fn list() -> Vec<Service> {
+        std::process::Command::new("launchctl")
+            .arg("list")
+            .stderr(std::process::Stdio::null())
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| parse_launchctl_list(&String::from_utf8_lossy(&output.stdout)))
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use super::Service;
+    use sysinfo::Pid;
+    use windows_sys::Win32::Foundation::{GetLastError, ERROR_MORE_DATA};
+    use windows_sys::Win32::System::Services::{
+        CloseServiceHandle, EnumServicesStatusExW, OpenSCManagerW, ENUM_SERVICE_STATUS_PROCESSW,
+        SC_ENUM_PROCESS_INFO, SC_MANAGER_ENUMERATE_SERVICE, SERVICE_STATE_ALL, SERVICE_WIN32,
+    };
+
+    /// Reads a NUL-terminated UTF-16 string owned by the enumeration buffer
+    ///
+    /// # Safety
+    /// `text` must be null or point to a NUL-terminated string
+    unsafe fn from_wide(text: *const u16) -> String {
+        if text.is_null() {
+            return String::new();
+        }
+        let len = (0..).take_while(|&i| *text.add(i) != 0).count();
+        String::from_utf16_lossy(std::slice::from_raw_parts(text, len))
+    }
+
+    pub fn list() -> Vec<Service> {
+        // SAFETY: the manager handle is checked and closed, the buffer is sized as
+        // the API asks and only read up to the number of entries it returned
+        unsafe {
+            let manager = OpenSCManagerW(
+                std::ptr::null(),
+                std::ptr::null(),
+                SC_MANAGER_ENUMERATE_SERVICE,
+            );
+            if manager.is_null() {
+                return Vec::new();
+            }
+            let mut services = Vec::new();
+            let mut resume = 0u32;
+            // u64 words keep the entries aligned
+            let mut buffer: Vec<u64> = vec![0; 8 * 1024];
+            loop {
+                let mut needed = 0u32;
+                let mut returned = 0u32;
+                let ok = EnumServicesStatusExW(
+                    manager,
+                    SC_ENUM_PROCESS_INFO,
+                    SERVICE_WIN32,
+                    SERVICE_STATE_ALL,
+                    buffer.as_mut_ptr().cast(),
+                    (buffer.len() * 8) as u32,
+                    &mut needed,
+                    &mut returned,
+                    &mut resume,
+                    std::ptr::null(),
+                );
+                let more = ok == 0 && GetLastError() == ERROR_MORE_DATA;
+                if ok == 0 && !more {
+                    break;
+                }
+                let entries = std::slice::from_raw_parts(
+                    buffer.as_ptr().cast::<ENUM_SERVICE_STATUS_PROCESSW>(),
+                    returned as usize,
+                );
+                services.extend(entries.iter().map(|entry| {
+                    let pid = entry.ServiceStatusProcess.dwProcessId;
+                    Service {
+                        name: from_wide(entry.lpServiceName),
+                        display_name: from_wide(entry.lpDisplayName),
+                        pid: (pid != 0).then(|| Pid::from_u32(pid)),
+                    }
+                }));
+                if !more {
+                    break;
+                }
+                if returned == 0 {
+                    buffer.resize(needed as usize / 8 + 1, 0);
+                }
+            }
+            CloseServiceHandle(manager);
+            services
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::process::ProcessIdentifier;
+
+    #[test]
+    fn service_identifier_reads_launchd_jobs() {
+        let identifier = ProcessIdentifier::from("service:com.apple.Finder");
+        assert_eq!(
+            identifier,
+            ProcessIdentifier::Service("com.apple.Finder".to_string())
+        );
+        assert_eq!(identifier.to_string(), "service:com.apple.Finder");
+
+        let jobs = parse_launchctl_list(
+            "PID\tStatus\tLabel\n\
+             512\t0\tcom.apple.Finder\n\
+             -\t78\tcom.apple.ReportCrash\n",
+        );
+        assert_eq!(jobs.len(), 2);
+        assert_eq!(jobs[0].name, "com.apple.Finder");
+        assert_eq!(jobs[0].pid, Some(Pid::from_u32(512)));
+        assert_eq!(jobs[1].pid, None);
+    }
+}
@@ -0,0 +1,358 @@
+//! Open TCP and UDP sockets of a process, read on demand: the socket inodes
+//! among `/proc/<pid>/fd` looked up in the process's `/proc/<pid>/net` tables
+//! on Linux, `GetExtendedTcpTable` and `GetExtendedUdpTable` on Windows.
+
+use std::net::SocketAddr;
+use sysinfo::Pid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SocketProtocol {
+    Tcp,
+    Udp,
+}
+
+impl SocketProtocol {
+    pub fn label(self) -> &'static str {
+        match self {
+            SocketProtocol::Tcp => "TCP",
+            SocketProtocol::Udp => "UDP",
+        }
+    }
+}
+
+/// State of a TCP connection, listening sockets first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TcpState {
+    Listen,
+    Established,
+    SynSent,
+    SynReceived,
+    FinWait1,
+    FinWait2,
+    CloseWait,
+    Closing,
+    LastAck,
+    TimeWait,
+    Closed,
+}
+
+impl TcpState {
+    pub fn label(self) -> &'static str {
+        match self {
+            TcpState::Listen => "LISTEN",
+            TcpState::Established => "ESTABLISHED",
+            TcpState::SynSent => "SYN_SENT",
+            TcpState::SynReceived => "SYN_RECV",
+            TcpState::FinWait1 => "FIN_WAIT1",
+            TcpState::FinWait2 => "FIN_WAIT2",
+            TcpState::CloseWait => "CLOSE_WAIT",
+            TcpState::Closing => "CLOSING",
+            TcpState::LastAck => "LAST_ACK",
+            TcpState::TimeWait => "TIME_WAIT",
+            TcpState::Closed => "CLOSED",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Socket {
+    pub protocol: SocketProtocol,
+    /// None for UDP
+    pub state: Option<TcpState>,
+    pub local: SocketAddr,
+    /// None while listening or for an unconnected UDP socket
+    pub remote: Option<SocketAddr>,
+}
+
+/// Open sockets of `pid`, TCP before UDP and listening sockets first
+pub fn sockets(pid: Pid) -> Result<Vec<Socket>, String> {
+    let mut sockets = imp::sockets(pid)?;
+    sockets.sort();
+    sockets.dedup();
+    Ok(sockets)
+}
+
+/// The peer address, unless it is the all-zero one of an unconnected socket
+#[cfg(any(target_os = "linux", windows))]
+fn connected(remote: SocketAddr) -> Option<SocketAddr> {
+    (remote.port() != 0 || !remote.ip().is_unspecified()).then_some(remote)
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use super::{connected, Socket, SocketProtocol, TcpState};
+    use std::collections::HashSet;
+    use std::io::ErrorKind;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+    use sysinfo::Pid;
+
+    const TABLES: [(&str, SocketProtocol); 4] = [
+        ("tcp", SocketProtocol::Tcp),
+        ("tcp6", SocketProtocol::Tcp),
+        ("udp", SocketProtocol::Udp),
+        ("udp6", SocketProtocol::Udp),
+    ];
+
+    pub fn sockets(pid: Pid) -> Result<Vec<Socket>, String> {
+        let inodes: HashSet<u64> = std::fs::read_dir(format!("/proc/{pid}/fd"))
+            .map_err(|err| match err.kind() {
+                ErrorKind::PermissionDenied => {
+                    "Permission denied: the sockets of another user's process need root".to_string()
+                }
+                ErrorKind::NotFound => "Process exited".to_string(),
+                _ => err.to_string(),
+            })?
+            .filter_map(Result::ok)
+            .filter_map(|fd| std::fs::read_link(fd.path()).ok())
+            .filter_map(|target| {
+                target
+                    .to_str()?
+                    .strip_prefix("socket:[")?
+                    .strip_suffix(']')?
+                    .parse()
+                    .ok()
+            })
+            .collect();
+        let mut sockets = Vec::new();
+        if inodes.is_empty() {
+            return Ok(sockets);
+        }
+        // The tables of the process's network namespace, which may not be tvis's
+        for (table, protocol) in TABLES {
+            // tcp6 and udp6 are missing with IPv6 disabled
+            let Ok(table) = std::fs::read_to_string(format!("/proc/{pid}/net/{table}")) else {
+                continue;
+            };
+            sockets.extend(
+                table
+                    .lines()
+                    .skip(1)
+                    .filter_map(|line| parse_line(line, protocol))
+                    .filter(|(inode, _)| inodes.contains(inode))
+                    .map(|(_, socket)| socket),
+            );
+        }
+        Ok(sockets)
+    }
+
+    /// `sl local_address rem_address st tx_queue:rx_queue tr:tm->when retrnsmt uid timeout inode …`
+    fn parse_line(line: &str, protocol: SocketProtocol) -> Option<(u64, Socket)> {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let local = parse_address(fields.get(1)?)?;
+        let remote = connected(parse_address(fields.get(2)?)?);
+        let state = u8::from_str_radix(fields.get(3)?, 16).ok()?;
+        let inode = fields.get(9)?.parse().ok()?;
+        let state = match protocol {
+            SocketProtocol::Tcp => Some(match state {
+                0x01 => TcpState::Established,
+                0x02 => TcpState::SynSent,
+                0x03 => TcpState::SynReceived,
+                0x04 => TcpState::FinWait1,
+                0x05 => TcpState::FinWait2,
+                0x06 => TcpState::TimeWait,
+                0x08 => TcpState::CloseWait,
+                0x09 => TcpState::LastAck,
+                0x0A => TcpState::Listen,
+                0x0B => TcpState::Closing,
+                _ => TcpState::Closed,
+            }),
+            SocketProtocol::Udp => None,
+        };
+        let socket = Socket {
+            protocol,
+            state,
+            local,
+            remote,
+        };
+        Some((inode, socket))
+    }
+
+    /// `0100007F:1F90`: the address as the kernel's 32-bit words in host byte
+    /// order, then the port
+    fn parse_address(field: &str) -> Option<SocketAddr> {
+        let (address, port) = field.split_once(':')?;
+        let port = u16::from_str_radix(port, 16).ok()?;
+        let mut bytes = Vec::with_capacity(16);
+        for word in 0..address.len() / 8 {
+            let word = u32::from_str_radix(address.get(word * 8..word * 8 + 8)?, 16).ok()?;
+            bytes.extend_from_slice(&word.to_ne_bytes());
+        }
+        let ip = match bytes.len() {
+            4 => IpAddr::V4(Ipv4Addr::from(<[u8; 4]>::try_from(bytes).ok()?)),
+            16 => {
+                let ip = Ipv6Addr::from(<[u8; 16]>::try_from(bytes).ok()?);
+                // IPv4 clients of a dual-stack socket show up as ::ffff:a.b.c.d
+                ip.to_ipv4_mapped().map_or(IpAddr::V6(ip), IpAddr::V4)
+            }
+            _ => return None,
+        };
+        Some(SocketAddr::new(ip, port))
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use super::{connected, Socket, SocketProtocol, TcpState};
+    use std::ffi::c_void;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+    use sysinfo::Pid;
+    use windows_sys::Win32::Foundation::{ERROR_INSUFFICIENT_BUFFER, NO_ERROR};
+    use windows_sys::Win32::NetworkManagement::IpHelper::{
+        GetExtendedTcpTable, GetExtendedUdpTable, MIB_TCP6ROW_OWNER_PID, MIB_TCP6TABLE_OWNER_PID,
+        MIB_TCPROW_OWNER_PID, MIB_TCPTABLE_OWNER_PID, MIB_UDP6ROW_OWNER_PID,
+        MIB_UDP6TABLE_OWNER_PID, MIB_UDPROW_OWNER_PID, MIB_UDPTABLE_OWNER_PID,
+        TCP_TABLE_OWNER_PID_ALL, UDP_TABLE_OWNER_PID,
+    };
+    use windows_sys::Win32::Networking::WinSock::{AF_INET, AF_INET6};
+
+    /// Calls `get` with a buffer grown until the table fits, returned as u32
+    /// words so the rows are aligned
+    fn read_table(get: impl Fn(*mut c_void, *mut u32) -> u32) -> Result<Vec<u32>, String> {
+        let mut size = 0u32;
+        let mut buffer: Vec<u32> = Vec::new();
+        // The table can grow between the size query and the read
+        for _ in 0..4 {
+            let result = get(buffer.as_mut_ptr().cast(), &mut size);
+            match result {
+                NO_ERROR => return Ok(buffer),
+                ERROR_INSUFFICIENT_BUFFER => buffer = vec![0; (size as usize).div_ceil(4)],
+                code => {
+                    return Err(format!(
+                        "Failed to list sockets: {}",
+                        std::io::Error::from_raw_os_error(code as i32)
+                    ))
+                }
+            }
+        }
+        Err("Failed to list sockets: the table kept growing".to_string())
+    }
+
+    /// Rows of a `MIB_*TABLE_OWNER_PID`, a count followed by the rows
+    ///
+    /// # Safety
+    ///
+    /// `buffer` must hold such a table whose rows, of type `Row`, start at `offset`
+    unsafe fn table_rows<Row>(buffer: &[u32], offset: usize) -> &[Row] {
+        if buffer.is_empty() {
+            return &[];
+        }
+        let count = buffer[0] as usize;
+        let first = buffer.as_ptr().cast::<u8>().add(offset).cast::<Row>();
+        std::slice::from_raw_parts(first, count)
+    }
+
+    fn tcp_state(state: u32) -> TcpState {
+        match state {
+            2 => TcpState::Listen,
+            3 => TcpState::SynSent,
+            4 => TcpState::SynReceived,
+            5 => TcpState::Established,
+            6 => TcpState::FinWait1,
+            7 => TcpState::FinWait2,
+            8 => TcpState::CloseWait,
+            9 => TcpState::Closing,
+            10 => TcpState::LastAck,
+            11 => TcpState::TimeWait,
+            _ => TcpState::Closed,
+        }
+    }
+
+    fn v4(address: u32, port: u32) -> SocketAddr {
+        SocketAddr::new(
+            IpAddr::V4(Ipv4Addr::from(address.to_ne_bytes())),
+            u16::from_be(port as u16),
+        )
+    }
+
+    fn v6(address: [u8; 16], port: u32) -> SocketAddr {
+        SocketAddr::new(
+            IpAddr::V6(Ipv6Addr::from(address)),
+            u16::from_be(port as u16),
+        )
+    }
+
+    pub fn sockets(pid: Pid) -> Result<Vec<Socket>, String> {
+        let pid = pid.as_u32();
+        let mut sockets = Vec::new();
+        let tcp = |family: u16| {
+            // SAFETY: the buffer and its size come from `read_table`
+            read_table(|buffer, size| unsafe {
+                GetExtendedTcpTable(buffer, size, 0, family as u32, TCP_TABLE_OWNER_PID_ALL, 0)
+            })
+        };
+        let udp = |family: u16| {
+            // SAFETY: as above
+            read_table(|buffer, size| unsafe {
+                GetExtendedUdpTable(buffer, size, 0, family as u32, UDP_TABLE_OWNER_PID, 0)
+            })
+        };
+
+        let table = tcp(AF_INET)?;
+        // SAFETY: filled by GetExtendedTcpTable for AF_INET
+        let rows: &[MIB_TCPROW_OWNER_PID] =
+            unsafe { table_rows(&table, std::mem::offset_of!(MIB_TCPTABLE_OWNER_PID, table)) };
+        sockets.extend(
+            rows.iter()
+                .filter(|row| row.dwOwningPid == pid)
+                .map(|row| Socket {
+                    protocol: SocketProtocol::Tcp,
+                    state: Some(tcp_state(row.dwState)),
+                    local: v4(row.dwLocalAddr, row.dwLocalPort),
+                    remote: connected(v4(row.dwRemoteAddr, row.dwRemotePort)),
+                }),
+        );
+        let table = tcp(AF_INET6)?;
+        // SAFETY: filled by GetExtendedTcpTable for AF_INET6
+        let rows: &[MIB_TCP6ROW_OWNER_PID] =
+            unsafe { table_rows(&table, std::mem::offset_of!(MIB_TCP6TABLE_OWNER_PID, table)) };
+        sockets.extend(
+            rows.iter()
+                .filter(|row| row.dwOwningPid == pid)
+                .map(|row| Socket {
+                    protocol: SocketProtocol::Tcp,
+                    state: Some(tcp_state(row.dwState)),
+                    local: v6(row.ucLocalAddr, row.dwLocalPort),
+                    remote: connected(v6(row.ucRemoteAddr, row.dwRemotePort)),
+                }),
+        );
+        let table = udp(AF_INET)?;
+        // SAFETY: filled by GetExtendedUdpTable for AF_INET
+        let rows: &[MIB_UDPROW_OWNER_PID] =
+            unsafe { table_rows(&table, std::mem::offset_of!(MIB_UDPTABLE_OWNER_PID, table)) };
+        sockets.extend(
+            rows.iter()
+                .filter(|row| row.dwOwningPid == pid)
+                .map(|row| Socket {
+                    protocol: SocketProtocol::Udp,
+                    state: None,
+                    local: v4(row.dwLocalAddr, row.dwLocalPort),
+                    remote: None,
+                }),
+        );
+        let table = udp(AF_INET6)?;
+        // SAFETY: filled by GetExtendedUdpTable for AF_INET6
+        let rows: &[MIB_UDP6ROW_OWNER_PID] =
+            unsafe { table_rows(&table, std::mem::offset_of!(MIB_UDP6TABLE_OWNER_PID, table)) };
+        sockets.extend(
+            rows.iter()
+                .filter(|row| row.dwOwningPid == pid)
+                .map(|row| Socket {
+                    protocol: SocketProtocol::Udp,
+                    state: None,
+                    local: v6(row.ucLocalAddr, row.dwLocalPort),
+                    remote: None,
+                }),
+        );
+        Ok(sockets)
+    }
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+mod imp {
+    use super::Socket;
+    use sysinfo::Pid;
+
+    pub fn sockets(_pid: Pid) -> Result<Vec<Socket>, String> {
+        Err("Listing sockets is not supported on this platform".to_string())
+    }
+}
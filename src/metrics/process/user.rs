@@ -0,0 +1,46 @@
+//! Owners of processes, by user name or numeric id.
+//!
+//! The account list is read at most once a minute; a user created after that is
+//! still matched by id until the next read.
+
+use sysinfo::{Uid, Users};
+
+/// Accounts as (id, name)
+pub type Accounts = Vec<(Uid, String)>;
+
+/// Known accounts as (id, name), refreshed at most every minute
+pub fn users() -> Accounts {
+    use std::sync::Mutex;
+    use std::time::{Duration, Instant};
+
+    const TTL: Duration = Duration::from_secs(60);
+    static CACHE: Mutex<Option<(Instant, Accounts)>> = Mutex::new(None);
+
+    let mut cache = CACHE.lock().unwrap();
+    match &*cache {
+        Some((at, users)) if at.elapsed() < TTL => users.clone(),
+        _ => {
+            let users: Accounts = Users::new_with_refreshed_list()
+                .list()
+                .iter()
+                .map(|user| (user.id().clone(), user.name().to_string()))
+                .collect();
+            *cache = Some((Instant::now(), users.clone()));
+            users
+        }
+    }
+}
+
+/// Name of the account `uid` among `users`, the id itself for an unknown one
+pub fn user_name(uid: &Uid, users: &[(Uid, String)]) -> String {
+    users
+        .iter()
+        .find(|(id, _)| id == uid)
+        .map_or_else(|| uid.to_string(), |(_, name)| name.clone())
+}
+
+/// Whether `user`, a name or a numeric id (a SID on Windows), is the account `uid`.
+/// `users` is the list from [`users`], read once for a whole scan.
+pub fn matches(user: &str, uid: &Uid, users: &[(Uid, String)]) -> bool {
+    uid.to_string() == user || users.iter().any(|(id, name)| id == uid && name == user)
+}
@@ -0,0 +1,35 @@
+//! Watch rules: processes matching a pattern are monitored as soon as the
+//! sampler sees them, each under its own `pid:` entry, so short-lived jobs are
+//! captured without adding them by hand.
+
+use super::exclude::glob_match;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WatchRule {
+    /// Glob (`*` and `?`) matched against the process name or its command line
+    pub pattern: String,
+    /// Drops the entry, with its history, once its process exited
+    pub remove_on_exit: bool,
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+impl WatchRule {
+    pub fn new(pattern: impl Into<String>) -> Self {
+        Self {
+            pattern: pattern.into(),
+            remove_on_exit: false,
+            enabled: true,
+        }
+    }
+
+    /// Whether `text`, a process name or command line, is matched
+    pub fn matches(&self, text: &str) -> bool {
+        self.enabled && glob_match(self.pattern.trim(), text)
+    }
+}
@@ -0,0 +1,356 @@
+//! Flight recorder: every sample of every monitored identifier appended to a log
+//! file as it is taken, so the telemetry leading up to a crash of tvis or the
+//! machine survives it.
+//!
+//! The file is flushed after every tick and rotated by size: `tvis_flight.jsonl`
+//! becomes `tvis_flight.jsonl.1`, which becomes `.2`, up to the number of files kept.
+//! [`read_logs`] reads them back, for another instance to inspect what a crashed
+//! one collected.
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, serde::Deserialize, serde::Serialize)]
+pub enum RecordFormat {
+    /// One JSON object per line
+    #[default]
+    JsonLines,
+    Csv,
+}
+
+impl RecordFormat {
+    pub fn label(self) -> &'static str {
+        match self {
+            RecordFormat::JsonLines => "JSON lines",
+            RecordFormat::Csv => "CSV",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            RecordFormat::JsonLines => "jsonl",
+            RecordFormat::Csv => "csv",
+        }
+    }
+}
+
+const CSV_HEADER: &str =
+    "unix_ms,identifier,cpu_percent,memory_bytes,gpu_percent,processes,threads,annotation\n";
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct FlightRecorder {
+    pub enabled: bool,
+    pub format: RecordFormat,
+    /// Directory of the log, the working directory when empty
+    pub directory: String,
+    /// Size at which the log is rotated
+    pub max_file_bytes: u64,
+    /// Files kept, the current one included
+    pub files: usize,
+}
+
+impl Default for FlightRecorder {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            format: RecordFormat::JsonLines,
+            directory: String::new(),
+            max_file_bytes: 10 * 1024 * 1024,
+            files: 5,
+        }
+    }
+}
+
+impl FlightRecorder {
+    /// File currently written to
+    pub fn path(&self) -> PathBuf {
+        Path::new(self.directory.trim()).join(format!("tvis_flight.{}", self.format.extension()))
+    }
+}
+
+/// `path` with `.<n>` appended, the name of the n-th oldest rotated file
+pub fn rotated_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{n}"));
+    PathBuf::from(name)
+}
+
+/// Aggregate values of one identifier on one tick
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlightSample {
+    pub identifier: String,
+    pub cpu: f32,
+    pub memory: usize,
+    pub gpu: f32,
+    pub processes: usize,
+    pub threads: usize,
+    /// Labels of the annotations taken since the previous sample
+    pub annotation: Option<String>,
+}
+
+impl FlightSample {
+    fn write(
+        &self,
+        format: RecordFormat,
+        unix_ms: u64,
+        out: &mut impl Write,
+    ) -> std::io::Result<()> {
+        match format {
+            RecordFormat::JsonLines => {
+                let row = JsonRow {
+                    unix_ms,
+                    identifier: self.identifier.clone(),
+                    cpu_percent: hundredths(self.cpu),
+                    memory_bytes: self.memory,
+                    gpu_percent: hundredths(self.gpu),
+                    processes: self.processes,
+                    threads: self.threads,
+                    annotation: self.annotation.clone(),
+                };
+                serde_json::to_writer(&mut *out, &row)?;
+                writeln!(out)
+            }
+            RecordFormat::Csv => writeln!(
+                out,
+                "{unix_ms},{},{:.2},{},{:.2},{},{},{}",
+                escape_csv(&self.identifier),
+                self.cpu,
+                self.memory,
+                self.gpu,
+                self.processes,
+                self.threads,
+                self.annotation
+                    .as_deref()
+                    .map(escape_csv)
+                    .unwrap_or_default()
+            ),
+        }
+    }
+}
+
+/// One line of the JSON lines log
+#[derive(serde::Deserialize, serde::Serialize)]
+struct JsonRow {
+    unix_ms: u64,
+    identifier: String,
+    cpu_percent: f32,
+    memory_bytes: usize,
+    gpu_percent: f32,
+    processes: usize,
+    threads: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    annotation: Option<String>,
+}
+
+/// `value` rounded to two decimals, all a percentage needs in a log or a frame
+pub fn hundredths(value: f32) -> f32 {
+    (value * 100.0).round() / 100.0
+}
+
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Open log of the worker, reopened whenever the configuration changes
+#[derive(Debug, Default)]
+pub struct FlightLog {
+    /// Configuration the file was opened with
+    config: Option<FlightRecorder>,
+    file: Option<BufWriter<File>>,
+    /// Size of the open file
+    written: u64,
+}
+
+impl FlightLog {
+    /// Appends `samples` and flushes them, rotating first if the file is full.
+    /// Closes the file when the recorder is disabled.
+    pub fn record(
+        &mut self,
+        config: &FlightRecorder,
+        samples: &[FlightSample],
+    ) -> Result<(), String> {
+        if !config.enabled {
+            self.config = None;
+            self.file = None;
+            return Ok(());
+        }
+        // A file that failed to open is retried on the next tick
+        if self.config.as_ref() != Some(config) || self.file.is_none() {
+            self.open(config)?;
+        }
+        if self.written >= config.max_file_bytes {
+            self.file = None;
+            rotate(&config.path(), config.files).map_err(|err| describe(config, err))?;
+            self.open(config)?;
+        }
+        let unix_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        let mut rows = Vec::new();
+        for sample in samples {
+            sample
+                .write(config.format, unix_ms, &mut rows)
+                .map_err(|err| describe(config, err))?;
+        }
+        let Some(file) = &mut self.file else {
+            return Ok(());
+        };
+        file.write_all(&rows)
+            .and_then(|_| file.flush())
+            .map_err(|err| describe(config, err))?;
+        self.written += rows.len() as u64;
+        Ok(())
+    }
+
+    fn open(&mut self, config: &FlightRecorder) -> Result<(), String> {
+        self.config = Some(config.clone());
+        self.file = None;
+        let path = config.path();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|err| describe(config, err))?;
+        self.written = file.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+        let mut file = BufWriter::new(file);
+        if self.written == 0 && config.format == RecordFormat::Csv {
+            file.write_all(CSV_HEADER.as_bytes())
+                .map_err(|err| describe(config, err))?;
+            self.written = CSV_HEADER.len() as u64;
+        }
+        self.file = Some(file);
+        Ok(())
+    }
+}
+
+/// Shifts every rotated file one up, dropping the oldest, and moves `path` to `.1`
+fn rotate(path: &Path, files: usize) -> std::io::Result<()> {
+    if files <= 1 {
+        return std::fs::remove_file(path);
+    }
+    let _ = std::fs::remove_file(rotated_path(path, files - 1));
+    for n in (1..files - 1).rev() {
+        let from = rotated_path(path, n);
+        if from.exists() {
+            std::fs::rename(&from, rotated_path(path, n + 1))?;
+        }
+    }
+    std::fs::rename(path, rotated_path(path, 1))
+}
+
+/// One line of a flight log read back
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedRow {
+    pub unix_ms: u64,
+    pub sample: FlightSample,
+}
+
+/// Every row of the flight logs in `directory`, both formats, oldest rotated
+/// file first. Only reads, so the logs of a running instance can be opened too.
+/// Lines that don't parse are skipped: a crash can leave the last one torn.
+pub fn read_logs(directory: &Path) -> Result<Vec<RecordedRow>, String> {
+    let mut rows = Vec::new();
+    let mut found = false;
+    for format in [RecordFormat::JsonLines, RecordFormat::Csv] {
+        let path = FlightRecorder {
+            directory: directory.to_string_lossy().into_owned(),
+            format,
+            ..Default::default()
+        }
+        .path();
+        let rotated = (1..)
+            .take_while(|&n| rotated_path(&path, n).exists())
+            .count();
+        let mut files: Vec<PathBuf> = (1..=rotated)
+            .rev()
+            .map(|n| rotated_path(&path, n))
+            .collect();
+        if path.exists() {
+            files.push(path);
+        }
+        for file in files {
+            found = true;
+            let reader = File::open(&file)
+                .map(BufReader::new)
+                .map_err(|err| format!("Failed to read {}: {err}", file.display()))?;
+            for line in reader.lines() {
+                let line =
+                    line.map_err(|err| format!("Failed to read {}: {err}", file.display()))?;
+                let row = match format {
+                    RecordFormat::JsonLines => parse_json_row(&line),
+                    RecordFormat::Csv => parse_csv_row(&line),
+                };
+                rows.extend(row);
+            }
+        }
+    }
+    if !found {
+        return Err(format!("No flight log in {}", directory.display()));
+    }
+    // Both formats may have been written over time
+    rows.sort_by_key(|row| row.unix_ms);
+    Ok(rows)
+}
+
+/// A line as written by [`FlightSample::write`]
+fn parse_json_row(line: &str) -> Option<RecordedRow> {
+    let row: JsonRow = serde_json::from_str(line).ok()?;
+    Some(RecordedRow {
+        unix_ms: row.unix_ms,
+        sample: FlightSample {
+            identifier: row.identifier,
+            cpu: row.cpu_percent,
+            memory: row.memory_bytes,
+            gpu: row.gpu_percent,
+            processes: row.processes,
+            threads: row.threads,
+            annotation: row.annotation,
+        },
+    })
+}
+
+/// A row below [`CSV_HEADER`], which itself yields None
+fn parse_csv_row(line: &str) -> Option<RecordedRow> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    let [unix_ms, identifier, cpu, memory, gpu, processes, threads, annotation] =
+        <[String; 8]>::try_from(fields).ok()?;
+    Some(RecordedRow {
+        unix_ms: unix_ms.parse().ok()?,
+        sample: FlightSample {
+            identifier,
+            cpu: cpu.parse().ok()?,
+            memory: memory.parse().ok()?,
+            gpu: gpu.parse().ok()?,
+            processes: processes.parse().ok()?,
+            threads: threads.parse().ok()?,
+            annotation: (!annotation.is_empty()).then_some(annotation),
+        },
+    })
+}
+
+fn describe(config: &FlightRecorder, err: std::io::Error) -> String {
+    format!("Failed to write {}: {err}", config.path().display())
+}
@@ -0,0 +1,58 @@
+//! CPU package temperature and clock frequency, context for the CPU plots:
+//! a tree whose usage drops while the package is hot is often throttled.
+
+use std::collections::VecDeque;
+use sysinfo::Components;
+
+/// Sensor labels tried in order, from the whole package to any CPU sensor
+const PACKAGE_LABELS: [&str; 6] = ["package", "tctl", "tdie", "cpu", "coretemp", "k10temp"];
+
+/// Temperature and average clock of the CPU on recent ticks, sampled only
+/// while [`super::Controls::sample_thermals`] is set, see
+/// [`super::Metrics::set_sample_thermals`]
+#[derive(Debug, Clone, Default)]
+pub struct CpuThermals {
+    /// Package temperature in °C, `None` where no sensor is exposed
+    pub temperature: Option<f32>,
+    /// Average clock of the cores in MHz, 0 where it can't be read
+    pub frequency: u64,
+    /// Values of the recent ticks, oldest first
+    pub temperatures: VecDeque<f32>,
+    pub frequencies: VecDeque<u64>,
+}
+
+impl CpuThermals {
+    pub(super) fn record(&mut self, temperature: Option<f32>, frequency: u64, history_len: usize) {
+        self.temperature = temperature;
+        self.frequency = frequency;
+        if let Some(temperature) = temperature {
+            self.temperatures.push_back(temperature);
+        }
+        if frequency > 0 {
+            self.frequencies.push_back(frequency);
+        }
+        for _ in history_len..self.temperatures.len() {
+            self.temperatures.pop_front();
+        }
+        for _ in history_len..self.frequencies.len() {
+            self.frequencies.pop_front();
+        }
+    }
+
+    /// Forgets the readings, e.g. once the strip is turned off
+    pub(super) fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Temperature of the sensor named most like the CPU package
+pub fn package_temperature(components: &Components) -> Option<f32> {
+    PACKAGE_LABELS.iter().find_map(|label| {
+        components
+            .list()
+            .iter()
+            .filter(|component| component.label().to_lowercase().contains(label))
+            .find_map(|component| component.temperature())
+            .filter(|temperature| temperature.is_finite() && *temperature > 0.0)
+    })
+}
@@ -0,0 +1,142 @@
+//! Live stream of the samples over WebSocket, for dashboards fed by tvis.
+//!
+//! Every tick, each monitored identifier that was sampled is sent to every
+//! connected client as one JSON text frame:
+//!
+//! ```json
+//! {"identifier":"firefox","ts":1700000000000,"tick":42,"cpu":12.5,"memory":104857600,
+//!  "children":[{"pid":1234,"name":"firefox","cpu":10.0,"memory":83886080}]}
+//! ```
+//!
+//! CPU is in percent of one core, memory in bytes, `ts` in Unix milliseconds.
+
+use crate::metrics::collector::{poll_snapshots, Sample, Snapshot};
+use crate::metrics::process::ProcessIdentifier;
+use crate::metrics::recorder::hundredths;
+use crate::metrics::Metrics;
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, UNIX_EPOCH};
+use tungstenite::{Message, WebSocket};
+
+/// A client that doesn't read its frames for this long is dropped
+const WRITE_TIMEOUT: Duration = Duration::from_secs(2);
+
+type Clients = Arc<Mutex<Vec<WebSocket<TcpStream>>>>;
+
+/// Address to listen on from a `ws://host:port` URL
+pub fn parse_url(url: &str) -> Result<String, String> {
+    let addr = url
+        .strip_prefix("ws://")
+        .ok_or_else(|| format!("expected ws://host:port, not '{url}'"))?
+        .trim_end_matches('/');
+    if addr
+        .rsplit_once(':')
+        .is_none_or(|(_, port)| port.parse::<u16>().is_err())
+    {
+        return Err(format!("'{url}' has no port"));
+    }
+    Ok(addr.to_string())
+}
+
+/// Listens on `addr` and streams the samples of `metrics` to every client
+/// that connects. Returns the bound address, useful with port 0.
+pub fn spawn(addr: &str, metrics: Metrics) -> Result<SocketAddr, String> {
+    let listener =
+        TcpListener::bind(addr).map_err(|err| format!("Failed to listen on {addr}: {err}"))?;
+    let local_addr = listener
+        .local_addr()
+        .map_err(|err| format!("Failed to listen on {addr}: {err}"))?;
+    let clients = Clients::default();
+
+    let accepted = Arc::clone(&clients);
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let _ = stream.set_write_timeout(Some(WRITE_TIMEOUT));
+            let _ = stream.set_read_timeout(Some(WRITE_TIMEOUT));
+            // A client that connects but stalls the handshake would otherwise
+            // hold up every connection behind it
+            let accepted = Arc::clone(&accepted);
+            thread::spawn(move || match tungstenite::accept(stream) {
+                Ok(socket) => accepted.lock().unwrap().push(socket),
+                Err(err) => log::warn!("WebSocket handshake failed: {err}"),
+            });
+        }
+    });
+
+    poll_snapshots(metrics, move |_, snapshot| {
+        let Some(snapshot) = snapshot else {
+            return true;
+        };
+        let frames = frames(&snapshot);
+        let mut clients = clients.lock().unwrap();
+        if frames.is_empty() || clients.is_empty() {
+            return true;
+        }
+        clients.retain_mut(|client| {
+            frames
+                .iter()
+                .all(|frame| client.send(Message::text(frame.as_str())).is_ok())
+        });
+        true
+    });
+    Ok(local_addr)
+}
+
+/// Frames of the identifiers sampled on the tick of `snapshot`
+fn frames(snapshot: &Snapshot) -> Vec<String> {
+    let ts = snapshot
+        .taken_at
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    snapshot
+        .samples
+        .iter()
+        .filter(|(_, sample)| !sample.processes.is_empty())
+        .map(|(identifier, sample)| frame(identifier, sample, ts, snapshot.tick))
+        .collect()
+}
+
+/// JSON text frame of one identifier, see the module docs
+#[derive(serde::Serialize)]
+struct Frame<'a> {
+    identifier: String,
+    ts: u128,
+    tick: u64,
+    cpu: f32,
+    memory: usize,
+    children: Vec<Child<'a>>,
+}
+
+#[derive(serde::Serialize)]
+struct Child<'a> {
+    pid: u32,
+    name: &'a str,
+    cpu: f32,
+    memory: usize,
+}
+
+fn frame(identifier: &ProcessIdentifier, sample: &Sample, ts: u128, tick: u64) -> String {
+    let stats = &sample.stats;
+    let frame = Frame {
+        identifier: identifier.to_string(),
+        ts,
+        tick,
+        cpu: hundredths(stats.current_cpu),
+        memory: stats.current_memory,
+        children: sample
+            .processes
+            .iter()
+            .filter(|process| !process.is_thread)
+            .map(|process| Child {
+                pid: process.pid.as_u32(),
+                name: &process.name,
+                cpu: hundredths(process.current_cpu),
+                memory: process.current_memory,
+            })
+            .collect(),
+    };
+    serde_json::to_string(&frame).unwrap_or_default()
+}
@@ -0,0 +1,288 @@
+use crate::components::settings::Settings;
+use crate::metrics::process::{MetricType, ProcessData, ProcessIdentifier};
+use crate::metrics::{Metrics, GENERAL_STATS_PID};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::symbols::Marker;
+use ratatui::text::Line;
+use ratatui::widgets::{
+    Axis, Block, Chart, Dataset, GraphType, List, ListItem, ListState, Paragraph,
+};
+use ratatui::{DefaultTerminal, Frame};
+use std::io;
+use std::time::Duration;
+
+/// Terminal frontend for headless machines, driven by the same `Metrics` worker as the GUI
+pub struct TuiApp {
+    metrics: Metrics,
+    monitored_processes: Vec<ProcessIdentifier>,
+    list_state: ListState,
+    current_metric: MetricType,
+    settings: Settings,
+}
+
+impl TuiApp {
+    /// Samples at the interval and history length of `settings`
+    pub fn new(monitored_processes: Vec<ProcessIdentifier>, settings: Settings) -> Self {
+        let mut metrics = Metrics::new(settings.history_length, settings.update_interval_ms);
+        for process in &monitored_processes {
+            metrics.add_selected_process(process.clone());
+        }
+        crate::control::spawn_server(metrics.clone());
+        let mut list_state = ListState::default();
+        if !monitored_processes.is_empty() {
+            list_state.select(Some(0));
+        }
+        Self {
+            metrics,
+            monitored_processes,
+            list_state,
+            current_metric: MetricType::Cpu,
+            settings,
+        }
+    }
+
+    /// A handle of its own on the worker, e.g. for the WebSocket stream
+    pub fn metrics(&self) -> Metrics {
+        self.metrics.clone()
+    }
+
+    pub fn run(mut self) -> io::Result<()> {
+        let mut terminal = ratatui::init();
+        let result = self.event_loop(&mut terminal);
+        ratatui::restore();
+        result
+    }
+
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        loop {
+            self.metrics.update();
+            self.sync_monitored();
+            terminal.draw(|frame| self.draw(frame))?;
+
+            if !event::poll(Duration::from_millis(250))? {
+                continue;
+            }
+            if let Event::Key(key) = event::read()? {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                    KeyCode::Down | KeyCode::Char('j') | KeyCode::Tab => {
+                        self.list_state.select_next()
+                    }
+                    KeyCode::Up | KeyCode::Char('k') | KeyCode::BackTab => {
+                        self.list_state.select_previous()
+                    }
+                    KeyCode::Char('c') => self.current_metric = MetricType::Cpu,
+                    KeyCode::Char('m') => self.current_metric = MetricType::Memory,
+                    KeyCode::Char('g') => self.current_metric = MetricType::Gpu,
+                    KeyCode::Char('p') => self.current_metric = MetricType::Processes,
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Follows identifiers added or removed over the control socket, keeping
+    /// the selected one when it is still monitored
+    fn sync_monitored(&mut self) {
+        let monitored = self.metrics.get_monitored_processes().to_vec();
+        if monitored == self.monitored_processes {
+            return;
+        }
+        let selected = self.active_process().cloned();
+        self.monitored_processes = monitored;
+        let index = selected
+            .and_then(|selected| {
+                self.monitored_processes
+                    .iter()
+                    .position(|identifier| *identifier == selected)
+            })
+            .or((!self.monitored_processes.is_empty()).then_some(0));
+        self.list_state.select(index);
+    }
+
+    fn active_process(&self) -> Option<&ProcessIdentifier> {
+        self.list_state
+            .selected()
+            .and_then(|i| self.monitored_processes.get(i))
+    }
+
+    fn draw(&mut self, frame: &mut Frame<'_>) {
+        let [main_area, help_area] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [list_area, view_area] =
+            Layout::horizontal([Constraint::Length(28), Constraint::Min(0)]).areas(main_area);
+
+        let items: Vec<ListItem<'_>> = self
+            .monitored_processes
+            .iter()
+            .map(|process| ListItem::new(process.to_string()))
+            .collect();
+        let list = List::new(items)
+            .block(Block::bordered().title("Monitored Processes"))
+            .highlight_style(Style::new().reversed());
+        frame.render_stateful_widget(list, list_area, &mut self.list_state);
+
+        let mut help =
+            Line::from("q: quit | ↑/↓: select | c: CPU | m: memory | g: GPU | p: processes").dim();
+        if self.metrics.safe_mode {
+            help.push_span(
+                format!(
+                    " | ⚠ safe mode: {} processes, refreshing monitored trees only",
+                    self.metrics.system_process_count
+                )
+                .yellow(),
+            );
+        }
+        frame.render_widget(help, help_area);
+
+        let Some(identifier) = self.active_process().cloned() else {
+            frame.render_widget(
+                Block::bordered().title("Pass identifiers: tvis --tui firefox pid:1234"),
+                view_area,
+            );
+            return;
+        };
+        let process_data = self.metrics.get_process_data(&identifier).cloned();
+        match process_data {
+            Some(process_data) => self.draw_process(frame, view_area, &identifier, &process_data),
+            None => frame.render_widget(
+                Block::bordered()
+                    .title(identifier.to_string())
+                    .title_bottom("Process not found"),
+                view_area,
+            ),
+        }
+    }
+
+    fn draw_process(
+        &self,
+        frame: &mut Frame<'_>,
+        area: Rect,
+        identifier: &ProcessIdentifier,
+        process_data: &ProcessData,
+    ) {
+        let stats = &process_data.genereal.stats;
+        let history = &process_data.genereal.history;
+        let [header_area, chart_area] =
+            Layout::vertical([Constraint::Length(4), Constraint::Min(0)]).areas(area);
+
+        let session = &process_data.session;
+        let mut counts = format!(
+            "Total Processes: {} | Total Threads: {} | Monitored: {}s | Samples: {} | Coverage: {:.1}%",
+            stats.process_count,
+            stats.thread_count,
+            session.duration().as_secs(),
+            session.samples,
+            session.coverage()
+        );
+        if let Some(summary) = &process_data.finished {
+            counts.push_str(&format!(
+                " | Finished after {}s, {} children",
+                summary.duration.as_secs(),
+                summary.children
+            ));
+        }
+        let (summary, values, max_value, unit) = match self.current_metric {
+            MetricType::Cpu => (
+                format!(
+                    "CPU Usage: {:.1}% | Peak: {:.1}% | AVG CPU: {:.1}%",
+                    stats.current_cpu, stats.peak_cpu, stats.avg_cpu
+                ),
+                history
+                    .get_cpu_history(&GENERAL_STATS_PID)
+                    .unwrap_or_default(),
+                stats.peak_cpu,
+                "%",
+            ),
+            MetricType::Memory => {
+                let unit = self.settings.memory_unit;
+                let format = |bytes: usize| unit.format_value(bytes as f32);
+                let (current_memory, label) = format(stats.current_memory);
+                (
+                    format!(
+                        "Memory Usage: {:.1} {label} | Peak: {:.1} {label} | AVG memory: {:.1} {label} | OS peak: {:.1} {label}",
+                        current_memory,
+                        format(stats.peak_memory).0,
+                        format(stats.avg_memory).0,
+                        format(stats.os_peak_memory).0,
+                    ),
+                    history
+                        .get_memory_history(&GENERAL_STATS_PID)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|x| format(x).0)
+                        .collect(),
+                    format(stats.peak_memory).0,
+                    label,
+                )
+            }
+            MetricType::Gpu => (
+                format!(
+                    "GPU Usage: {:.1}% | Peak: {:.1}% | AVG GPU: {:.1}% | VRAM: {:.1} MB",
+                    stats.current_gpu,
+                    stats.peak_gpu,
+                    stats.avg_gpu,
+                    stats.gpu_memory as f64 / (1024.0 * 1024.0)
+                ),
+                history
+                    .get_gpu_history(&GENERAL_STATS_PID)
+                    .unwrap_or_default(),
+                stats.peak_gpu,
+                "%",
+            ),
+            MetricType::Processes => {
+                let (processes, threads) = history
+                    .get_count_history(&GENERAL_STATS_PID)
+                    .unwrap_or_default();
+                let peak = processes.iter().copied().max().unwrap_or_default();
+                (
+                    format!(
+                        "Processes: {} | Threads: {} | Peak processes: {} | Peak threads: {}",
+                        stats.process_count,
+                        stats.thread_count,
+                        peak,
+                        threads.iter().copied().max().unwrap_or_default()
+                    ),
+                    processes.into_iter().map(|count| count as f32).collect(),
+                    peak as f32,
+                    "",
+                )
+            }
+        };
+
+        frame.render_widget(
+            Paragraph::new(vec![Line::from(counts), Line::from(summary)])
+                .block(Block::bordered().title(identifier.to_string())),
+            header_area,
+        );
+
+        let max_points = self.settings.display_len(history.history_len);
+        let values = &values[values.len().saturating_sub(max_points)..];
+        let start_x = max_points.saturating_sub(values.len()) as f64;
+        let points: Vec<(f64, f64)> = values
+            .iter()
+            .enumerate()
+            .map(|(i, &y)| (start_x + i as f64, y as f64))
+            .collect();
+        let max_y = (max_value * (1.0 + self.settings.graph_scale_margin)).max(1.0) as f64;
+        let dataset = Dataset::default()
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::new().fg(Color::Cyan))
+            .data(&points);
+        let chart = Chart::new(vec![dataset])
+            .block(Block::bordered())
+            .x_axis(Axis::default().bounds([0.0, max_points as f64]))
+            .y_axis(
+                Axis::default()
+                    .bounds([0.0, max_y])
+                    .labels([format!("0 {unit}"), format!("{:.1} {unit}", max_y)]),
+            );
+        frame.render_widget(chart, chart_area);
+    }
+}
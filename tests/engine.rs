@@ -0,0 +1,1309 @@
+//! End-to-end checks of the collection engine against real child processes.
+
+mod fixtures;
+
+use fixtures::{wait_until, Workload};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+use sysinfo::Pid;
+#[cfg(feature = "gui")]
+use tvis::components::bulk::save_parquet;
+use tvis::control;
+use tvis::metrics::alerts::{AlertRule, Severity};
+use tvis::metrics::collector::{Collector, CollectorConfig, Sample};
+use tvis::metrics::influx::{self, InfluxPush};
+#[cfg(target_os = "linux")]
+use tvis::metrics::process::{scheduling, set_affinity, set_nice, Priority, PriorityClass};
+use tvis::metrics::process::{
+    sockets, user, ExcludeRule, MetricType, ProcessData, ProcessDetails, ProcessEventKind,
+    ProcessIdentifier, ProcessInfo, ProcessMonitor, SocketProtocol, TcpState, WatchRule,
+};
+use tvis::metrics::recorder::{
+    read_logs, rotated_path, FlightLog, FlightRecorder, FlightSample, RecordFormat,
+};
+use tvis::metrics::{cpu_share, AdaptiveSampling, Metrics, SwapUsage, GENERAL_STATS_PID};
+
+const TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Entry point of the workload fixtures, see `fixtures::run_from_env`
+#[test]
+#[ignore = "spawned by the other tests as a workload"]
+fn fixture_entry() {
+    fixtures::run_from_env();
+}
+
+/// A process card with the given figures, the rest left empty
+fn process_info(pid: u32, name: &str, cpu: f32, memory: usize) -> ProcessInfo {
+    ProcessInfo {
+        name: name.into(),
+        pid: Pid::from_u32(pid),
+        parent_pid: None,
+        is_thread: false,
+        current_cpu: cpu,
+        avg_cpu: cpu,
+        peak_cpu: cpu,
+        current_memory: memory,
+        peak_memory: memory,
+        avg_memory: memory,
+        os_peak_memory: None,
+        gpu: Default::default(),
+        sched: None,
+        suspended: false,
+        restricted: false,
+        scheduling: None,
+    }
+}
+
+/// Processes (not threads) related to `identifier` on a fresh snapshot
+fn related_processes(identifier: &ProcessIdentifier, exclude: Option<&ExcludeRule>) -> Vec<Pid> {
+    let monitor = ProcessMonitor::default();
+    let mut pids: Vec<Pid> = monitor
+        .find_all_relation(identifier, exclude)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|pid| {
+            monitor
+                .get_process_by_pid(pid)
+                .is_some_and(|process| process.thread_kind().is_none())
+        })
+        .collect();
+    pids.sort();
+    pids
+}
+
+fn monitor(identifier: &ProcessIdentifier) -> Metrics {
+    let mut metrics = Metrics::new(50, 200);
+    metrics.add_selected_process(identifier.clone());
+    metrics
+}
+
+/// Waits until the monitored data for `identifier` satisfies `condition`
+fn wait_for_data(
+    metrics: &mut Metrics,
+    identifier: &ProcessIdentifier,
+    mut condition: impl FnMut(&ProcessData) -> bool,
+) -> bool {
+    wait_until(TIMEOUT, || {
+        metrics.update();
+        metrics
+            .get_process_data(identifier)
+            .is_some_and(&mut condition)
+    })
+}
+
+fn process_count(data: &ProcessData) -> usize {
+    data.processes_stats.iter().filter(|p| !p.is_thread).count()
+}
+
+#[test]
+fn relation_includes_forked_children() {
+    let forker = Workload::forker(3);
+    let identifier = forker.identifier();
+    assert!(
+        wait_until(TIMEOUT, || related_processes(&identifier, None).len() == 4),
+        "expected the forker and 3 children, got {:?}",
+        related_processes(&identifier, None)
+    );
+    assert!(related_processes(&identifier, None).contains(&forker.pid()));
+}
+
+#[test]
+fn exclude_rule_limits_discovery() {
+    let forker = Workload::forker(2);
+    let identifier = forker.identifier();
+    assert!(wait_until(TIMEOUT, || {
+        related_processes(&identifier, None).len() == 3
+    }));
+
+    let mut depth_rule = ExcludeRule::new(identifier.clone());
+    depth_rule.max_depth = Some(0);
+    assert_eq!(
+        related_processes(&identifier, Some(&depth_rule)),
+        vec![forker.pid()]
+    );
+    // The chain goes from the test binary that started the forker up to the root
+    depth_rule.ancestors = true;
+    let with_parents = related_processes(&identifier, Some(&depth_rule));
+    assert!(with_parents.contains(&forker.pid()));
+    assert!(with_parents.contains(&Pid::from_u32(std::process::id())));
+    assert!(with_parents.len() > 2);
+
+    let child = related_processes(&identifier, None)
+        .into_iter()
+        .find(|pid| *pid != forker.pid())
+        .unwrap();
+    let mut pid_rule = ExcludeRule::new(identifier.clone());
+    pid_rule.pids = vec![child.as_u32()];
+    let remaining = related_processes(&identifier, Some(&pid_rule));
+    assert_eq!(remaining.len(), 2);
+    assert!(!remaining.contains(&child));
+}
+
+#[test]
+fn collector_sends_snapshots_to_subscribers() {
+    let forker = Workload::forker(2);
+    let identifier = forker.identifier();
+    let mut collector = Collector::new(CollectorConfig {
+        update_interval: Duration::from_millis(200),
+        ..Default::default()
+    });
+    let snapshots = collector.subscribe();
+    collector.add(identifier.clone());
+    let deadline = Instant::now() + TIMEOUT;
+    let sample = loop {
+        let snapshot = snapshots
+            .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            .expect("no snapshot with the forker's tree");
+        match snapshot.samples.get(&identifier) {
+            Some(sample) if sample.stats.process_count == 3 => break sample.clone(),
+            _ => {}
+        }
+    };
+    assert!(sample.processes.iter().any(|p| p.pid == forker.pid()));
+
+    // Snapshots taken before the removal may still be queued
+    collector.remove(&identifier);
+    assert!(wait_until(TIMEOUT, || {
+        snapshots
+            .recv_timeout(TIMEOUT)
+            .is_ok_and(|snapshot| !snapshot.samples.contains_key(&identifier))
+    }));
+}
+
+#[test]
+fn watch_rules_add_and_remove_matching_processes() {
+    let mut metrics = Metrics::new(50, 200);
+    let mut rule = WatchRule::new("*fixture_entry*");
+    rule.remove_on_exit = true;
+    metrics.set_watch_rules(vec![rule]);
+    let workload = Workload::busy_loop();
+    let identifier = workload.identifier();
+    let monitored = |metrics: &mut Metrics| {
+        metrics.update();
+        metrics.get_monitored_processes().contains(&identifier)
+    };
+    assert!(
+        wait_until(TIMEOUT, || monitored(&mut metrics)),
+        "the workload was not added"
+    );
+    assert!(wait_for_data(&mut metrics, &identifier, |data| data
+        .session
+        .samples
+        > 0));
+
+    drop(workload);
+    assert!(
+        wait_until(TIMEOUT, || !monitored(&mut metrics)),
+        "the exited workload was not removed"
+    );
+}
+
+#[test]
+fn uptime_of_a_pid_entry_follows_its_start_time() {
+    let busy = Workload::busy_loop();
+    let identifier = busy.identifier();
+    let mut metrics = monitor(&identifier);
+    assert!(wait_for_data(&mut metrics, &identifier, |data| {
+        data.session.running_since.is_some()
+    }));
+    let session = &metrics.get_process_data(&identifier).unwrap().session;
+    assert!(session.uptime().unwrap() < TIMEOUT);
+    assert_eq!(session.restarts, 0);
+}
+
+#[test]
+fn aggregates_cpu_of_busy_loop() {
+    let busy = Workload::busy_loop();
+    let identifier = busy.identifier();
+    let mut metrics = monitor(&identifier);
+    assert!(
+        wait_for_data(&mut metrics, &identifier, |data| {
+            data.genereal.stats.current_cpu > 20.0 && process_count(data) == 1
+        }),
+        "busy loop CPU never showed up"
+    );
+}
+
+#[test]
+fn process_names_are_shared_between_ticks() {
+    let busy = Workload::busy_loop();
+    let identifier = busy.identifier();
+    let mut metrics = monitor(&identifier);
+    let name = |data: &ProcessData| {
+        data.processes_stats
+            .iter()
+            .find(|process| !process.is_thread)
+            .map(|process| Arc::clone(&process.name))
+    };
+    let mut first = None;
+    assert!(wait_for_data(&mut metrics, &identifier, |data| {
+        first = name(data);
+        first.is_some()
+    }));
+    let first = first.unwrap();
+    let tick = metrics.tick;
+    assert!(wait_until(TIMEOUT, || {
+        metrics.update();
+        metrics.tick > tick + 1
+    }));
+    let later = name(metrics.get_process_data(&identifier).unwrap()).unwrap();
+    assert!(Arc::ptr_eq(&first, &later), "the name was allocated again");
+}
+
+#[test]
+fn usage_by_name_sums_the_processes_of_a_name() {
+    let first = Workload::allocator(16);
+    let second = Workload::allocator(16);
+    let monitor = ProcessMonitor::default();
+    let process = |workload: &Workload| monitor.get_process_by_pid(&workload.pid()).unwrap();
+    let name = process(&first).name().to_string_lossy().into_owned();
+    let (_, memory) = monitor.usage_by_name()[&name];
+    assert!(memory >= process(&first).memory() + process(&second).memory());
+}
+
+#[test]
+fn stream_pushes_samples_to_websocket_clients() {
+    let busy = Workload::busy_loop();
+    let identifier = busy.identifier();
+    let metrics = monitor(&identifier);
+    assert_eq!(
+        tvis::stream::parse_url("ws://0.0.0.0:9000/"),
+        Ok("0.0.0.0:9000".to_string())
+    );
+    assert!(tvis::stream::parse_url("http://0.0.0.0:9000").is_err());
+    assert!(tvis::stream::parse_url("ws://0.0.0.0").is_err());
+
+    let addr = tvis::stream::spawn("127.0.0.1:0", metrics).unwrap();
+    // A client that never sends its handshake doesn't hold up the others
+    let _stalled = std::net::TcpStream::connect(addr).unwrap();
+    let started = Instant::now();
+    let (mut client, _) = tungstenite::connect(format!("ws://{addr}")).unwrap();
+    assert!(started.elapsed() < Duration::from_secs(1));
+    let frame = client.read().unwrap().into_text().unwrap();
+    assert!(frame.starts_with(&format!("{{\"identifier\":\"{}\"", identifier)));
+    assert!(frame.contains(&format!("\"pid\":{}", busy.pid())));
+}
+
+#[test]
+fn aggregates_memory_of_allocator() {
+    let allocator = Workload::allocator(64);
+    let identifier = allocator.identifier();
+    let mut metrics = monitor(&identifier);
+    assert!(
+        wait_for_data(&mut metrics, &identifier, |data| {
+            data.genereal.stats.current_memory >= 60 * 1024 * 1024
+        }),
+        "allocated memory never showed up"
+    );
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+fn reports_os_peak_memory() {
+    let allocator = Workload::allocator(64);
+    let identifier = allocator.identifier();
+    let mut metrics = monitor(&identifier);
+    assert!(
+        wait_for_data(&mut metrics, &identifier, |data| {
+            data.genereal.stats.os_peak_memory >= 60 * 1024 * 1024
+        }),
+        "OS peak memory never showed up"
+    );
+}
+
+#[test]
+fn history_grows_with_ticks() {
+    let busy = Workload::busy_loop();
+    let identifier = busy.identifier();
+    let mut metrics = monitor(&identifier);
+    assert!(wait_for_data(&mut metrics, &identifier, |data| {
+        let general = data
+            .genereal
+            .history
+            .get_cpu_history(&GENERAL_STATS_PID)
+            .unwrap_or_default();
+        let process = data
+            .history
+            .get_memory_history(&busy.pid())
+            .unwrap_or_default();
+        general.len() >= 3 && process.len() >= 3
+    }));
+}
+
+#[test]
+fn counts_tree_processes_over_time() {
+    let forker = Workload::forker(3);
+    let identifier = forker.identifier();
+    let mut metrics = monitor(&identifier);
+    assert!(
+        wait_for_data(&mut metrics, &identifier, |data| {
+            let Some((processes, threads)) =
+                data.genereal.history.get_count_history(&GENERAL_STATS_PID)
+            else {
+                return false;
+            };
+            processes.last() == Some(&4) && threads.len() == processes.len()
+        }),
+        "count history never reached the forker and its 3 children"
+    );
+}
+
+#[test]
+fn exited_child_is_cleaned_up() {
+    let forker = Workload::forker(2);
+    let identifier = forker.identifier();
+    let mut metrics = monitor(&identifier);
+    assert!(wait_for_data(&mut metrics, &identifier, |data| {
+        process_count(data) == 3
+    }));
+
+    let child = related_processes(&identifier, None)
+        .into_iter()
+        .find(|pid| *pid != forker.pid())
+        .unwrap();
+    assert!(ProcessMonitor::default()
+        .get_process_by_pid(&child)
+        .unwrap()
+        .kill());
+
+    assert!(
+        wait_for_data(&mut metrics, &identifier, |data| {
+            process_count(data) == 2
+                && data.history.get_cpu_history(&child).is_none()
+                && data
+                    .events
+                    .iter()
+                    .any(|event| event.kind == ProcessEventKind::Exited && event.pid == child)
+        }),
+        "killed child was not cleaned up"
+    );
+}
+
+#[test]
+fn exited_pid_is_frozen_with_a_summary() {
+    let forker = Workload::forker(2);
+    let identifier = forker.identifier();
+    let mut metrics = monitor(&identifier);
+    assert!(wait_for_data(&mut metrics, &identifier, |data| {
+        process_count(data) == 3
+    }));
+
+    drop(forker);
+    assert!(
+        wait_for_data(&mut metrics, &identifier, |data| {
+            data.processes_stats.is_empty()
+                && data.genereal.stats.process_count == 0
+                && data
+                    .finished
+                    .as_ref()
+                    .is_some_and(|summary| summary.children == 2 && summary.peak_memory > 0)
+        }),
+        "exited PID did not leave a finished summary"
+    );
+    let samples = |metrics: &mut Metrics| {
+        metrics.update();
+        metrics
+            .get_process_data(&identifier)
+            .map(|data| data.session.samples)
+    };
+    let frozen_at = samples(&mut metrics);
+    std::thread::sleep(Duration::from_millis(500));
+    assert_eq!(samples(&mut metrics), frozen_at);
+}
+
+#[test]
+fn scoped_monitor_loads_only_given_pids() {
+    let busy = Workload::busy_loop();
+    let monitor = ProcessMonitor::safe_mode(Duration::from_millis(200), Some(vec![busy.pid()]));
+    assert!(monitor.is_scoped());
+    assert_eq!(monitor.process_count(), 1);
+    assert!(monitor.get_process_by_pid(&busy.pid()).is_some());
+
+    let full = ProcessMonitor::safe_mode(Duration::from_millis(200), None);
+    assert!(!full.is_scoped());
+    assert!(full.process_count() > 1);
+}
+
+#[test]
+fn long_name_matches_executable() {
+    let busy = Workload::busy_loop();
+    let exe = std::env::current_exe().unwrap();
+    let name = exe.file_name().unwrap().to_string_lossy().into_owned();
+    let identifier = ProcessIdentifier::Name(name);
+    assert!(wait_until(TIMEOUT, || {
+        related_processes(&identifier, None).contains(&busy.pid())
+    }));
+}
+
+#[test]
+fn flight_recorder_appends_and_rotates() {
+    use std::io::Write;
+
+    let directory = std::env::temp_dir().join(format!("tvis_flight_{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let config = FlightRecorder {
+        enabled: true,
+        format: RecordFormat::Csv,
+        directory: directory.to_string_lossy().into_owned(),
+        max_file_bytes: 200,
+        files: 2,
+    };
+    let sample = FlightSample {
+        identifier: "group:a, b".to_string(),
+        cpu: 12.5,
+        memory: 1024,
+        gpu: 0.0,
+        processes: 3,
+        threads: 7,
+        annotation: None,
+    };
+    let mut log = FlightLog::default();
+    for _ in 0..10 {
+        log.record(&config, &[sample.clone(), sample.clone()])
+            .unwrap();
+    }
+    let path = config.path();
+    let current = std::fs::read_to_string(&path).unwrap();
+    let mut lines = current.lines();
+    assert!(lines.next().unwrap().starts_with("unix_ms,identifier"));
+    assert!(lines
+        .next()
+        .unwrap()
+        .ends_with(",\"group:a, b\",12.50,1024,0.00,3,7,"));
+    // Two files are kept: the current one and `.1`, never `.2`
+    assert!(rotated_path(&path, 1).exists());
+    assert!(!rotated_path(&path, 2).exists());
+    assert!(std::fs::metadata(rotated_path(&path, 1)).unwrap().len() >= 200);
+
+    let json = FlightRecorder {
+        format: RecordFormat::JsonLines,
+        ..config
+    };
+    let annotated = FlightSample {
+        annotation: Some("deployed \"v2.3\"".to_string()),
+        ..sample.clone()
+    };
+    log.record(&json, &[sample, annotated]).unwrap();
+    let log = std::fs::read_to_string(json.path()).unwrap();
+    let mut lines = log.lines();
+    let line = lines.next().unwrap();
+    assert!(line.contains("\"identifier\":\"group:a, b\",\"cpu_percent\":12.5,"));
+    assert!(!line.contains("annotation"));
+    assert!(lines
+        .next()
+        .unwrap()
+        .ends_with(",\"threads\":7,\"annotation\":\"deployed \\\"v2.3\\\"\"}"));
+
+    // Read back from both formats, rotated files first, skipping a line torn by a crash
+    let mut torn = std::fs::OpenOptions::new()
+        .append(true)
+        .open(json.path())
+        .unwrap();
+    write!(torn, "{{\"unix_ms\":1,\"identif").unwrap();
+    let rows = read_logs(&directory).unwrap();
+    let csv_rows = std::fs::read_to_string(rotated_path(&path, 1))
+        .unwrap()
+        .lines()
+        .chain(current.lines())
+        .count()
+        - 2;
+    assert_eq!(rows.len(), csv_rows + 2);
+    assert!(rows
+        .windows(2)
+        .all(|pair| pair[0].unix_ms <= pair[1].unix_ms));
+    assert!(rows.iter().all(|row| row.sample.identifier == "group:a, b"));
+    let annotated: Vec<_> = rows
+        .iter()
+        .filter_map(|row| row.sample.annotation.as_deref())
+        .collect();
+    assert_eq!(annotated, ["deployed \"v2.3\""]);
+    assert!(rows
+        .iter()
+        .all(|row| (row.sample.cpu, row.sample.memory, row.sample.threads) == (12.5, 1024, 7)));
+    std::fs::remove_dir_all(&directory).unwrap();
+    assert!(read_logs(&directory).is_err());
+}
+
+#[test]
+fn adaptive_sampling_speeds_up_when_busy() {
+    let busy = Workload::busy_loop();
+    let identifier = busy.identifier();
+    let mut metrics = Metrics::new(50, 1000);
+    metrics.set_adaptive_sampling(AdaptiveSampling {
+        enabled: true,
+        fast_interval_ms: 200,
+        cpu_threshold: 20.0,
+    });
+    metrics.add_selected_process(identifier);
+    assert!(wait_until(TIMEOUT, || {
+        metrics.update();
+        metrics.current_interval == Duration::from_millis(200)
+    }));
+
+    drop(busy);
+    assert!(wait_until(TIMEOUT, || {
+        metrics.update();
+        metrics.current_interval == Duration::from_millis(1000)
+    }));
+}
+
+#[test]
+fn paused_identifier_keeps_its_history() {
+    let busy = Workload::busy_loop();
+    let identifier = busy.identifier();
+    let mut metrics = monitor(&identifier);
+    assert!(wait_for_data(&mut metrics, &identifier, |data| {
+        data.session.samples >= 2
+    }));
+
+    metrics.pause_process(&identifier);
+    std::thread::sleep(Duration::from_millis(500));
+    let samples = |metrics: &mut Metrics| {
+        metrics.update();
+        metrics
+            .get_process_data(&identifier)
+            .map(|data| data.session.samples)
+    };
+    let paused_at = samples(&mut metrics);
+    std::thread::sleep(Duration::from_millis(1000));
+    assert_eq!(samples(&mut metrics), paused_at);
+
+    metrics.resume_process(&identifier);
+    assert!(wait_until(TIMEOUT, || samples(&mut metrics) > paused_at));
+}
+
+#[test]
+fn interval_override_samples_at_its_own_rate() {
+    let fast = Workload::allocator(1);
+    let slow = Workload::allocator(1);
+    let mut metrics = monitor(&fast.identifier());
+    metrics.add_selected_process(slow.identifier());
+    metrics.set_interval_override(&slow.identifier(), Some(Duration::from_millis(1000)));
+    assert!(wait_for_data(&mut metrics, &fast.identifier(), |data| {
+        data.session.samples >= 10
+    }));
+
+    let samples = |identifier: &ProcessIdentifier| {
+        metrics
+            .get_process_data(identifier)
+            .map_or(0, |data| data.session.samples)
+    };
+    let (fast, slow) = (samples(&fast.identifier()), samples(&slow.identifier()));
+    assert!(slow >= 1 && slow * 2 <= fast, "fast {fast}, slow {slow}");
+}
+
+#[test]
+fn control_requests_add_clear_and_remove_identifiers() {
+    let workload = Workload::allocator(1);
+    let identifier = workload.identifier();
+    let name = identifier.to_string();
+    let mut metrics = Metrics::new(50, 200);
+    let request = |metrics: &mut Metrics, request: &str| control::respond(request, metrics);
+
+    assert!(request(&mut metrics, "add").starts_with("error:"));
+    assert_eq!(
+        request(&mut metrics, &format!("add {name}")),
+        format!("{name}: monitored\n")
+    );
+    assert_eq!(
+        request(&mut metrics, &format!("add {name}")),
+        format!("{name}: already monitored\n")
+    );
+    assert!(wait_for_data(&mut metrics, &identifier, |data| data
+        .session
+        .samples
+        >= 2));
+    // Rows carry the time their sample was taken
+    let export = request(&mut metrics, &format!("export {name} 3600"));
+    let mut lines = export.lines();
+    assert_eq!(
+        lines.next(),
+        Some("unix_ms,seconds_ago,cpu_percent,memory_bytes,annotation")
+    );
+    let now_ms = SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis();
+    let rows: Vec<u128> = lines
+        .map(|line| line.split(',').next().unwrap().parse().unwrap())
+        .collect();
+    assert!(rows.len() >= 2, "{export}");
+    assert!(rows.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(rows.iter().all(|&ms| now_ms - ms < 60_000), "{export}");
+    assert_eq!(
+        request(&mut metrics, &format!("export {name} 0"))
+            .lines()
+            .count(),
+        1
+    );
+    assert_eq!(request(&mut metrics, "clear"), format!("{name}: cleared\n"));
+    assert!(metrics.get_monitored_processes().contains(&identifier));
+
+    assert_eq!(
+        request(&mut metrics, &format!("remove\n{name}\nname:no such app")),
+        format!("{name}: removed\nerror: no such app is not monitored\n")
+    );
+    assert!(request(&mut metrics, &format!("remove {name}")).starts_with("error:"));
+    assert!(metrics.get_monitored_processes().is_empty());
+    assert!(wait_until(TIMEOUT, || {
+        metrics.update();
+        metrics.get_process_data(&identifier).is_none()
+    }));
+    assert_eq!(
+        ProcessIdentifier::from("name:pid:1"),
+        ProcessIdentifier::Name("pid:1".to_string())
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn control_socket_is_private_and_serves_clients_concurrently() {
+    use std::os::unix::fs::PermissionsExt;
+    let dir = std::env::temp_dir().join(format!("tvis_control_{}", std::process::id()));
+    let path = dir.join("control.sock");
+    control::listen(&path, Metrics::new(50, 200)).unwrap();
+    let mode = |path: &std::path::Path| std::fs::metadata(path).unwrap().permissions().mode();
+    assert_eq!(mode(&path) & 0o777, 0o600);
+    assert_eq!(mode(&dir) & 0o777, 0o700);
+
+    // A client that never sends its request doesn't hold up the others
+    let _stalled = std::os::unix::net::UnixStream::connect(&path).unwrap();
+    let started = Instant::now();
+    assert!(control::send_request_to(&path, "add")
+        .unwrap()
+        .starts_with("error:"));
+    assert!(started.elapsed() < Duration::from_secs(2));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn samples_record_their_time_and_the_ticks_missed_before_them() {
+    let workload = Workload::allocator(1);
+    let identifier = workload.identifier();
+    let mut metrics = monitor(&identifier);
+    assert!(wait_for_data(&mut metrics, &identifier, |data| data
+        .session
+        .samples
+        >= 6));
+
+    let history = &metrics
+        .get_process_data(&identifier)
+        .unwrap()
+        .genereal
+        .history;
+    let cpu = history.get_cpu_history(&GENERAL_STATS_PID).unwrap();
+    let times = history.get_sample_times(&GENERAL_STATS_PID).unwrap();
+    let missed = history.get_missed_history(&GENERAL_STATS_PID).unwrap();
+    assert_eq!(times.len(), cpu.len());
+    assert_eq!(missed.len(), cpu.len());
+    assert!(times.windows(2).all(|pair| pair[0] < pair[1]));
+    // A tick that runs late is not caught up in a burst of samples
+    let span = times[times.len() - 1].duration_since(times[0]).unwrap();
+    let ticks = times.len() as u32 - 1 + missed.iter().skip(1).sum::<u32>();
+    assert!(
+        span >= Duration::from_millis(200) * ticks * 3 / 4,
+        "{span:?} for {ticks} ticks"
+    );
+}
+
+#[test]
+fn details_identify_the_instance() {
+    let workload = Workload::allocator(1);
+    let details = || ProcessDetails::collect(workload.pid(), true).unwrap_or_default();
+    assert!(wait_until(TIMEOUT, || {
+        details().cmd.iter().any(|arg| arg == "fixture_entry")
+    }));
+
+    let details = details();
+    assert_eq!(details.exe, std::env::current_exe().ok());
+    assert!(details.started_at.is_some());
+    assert!(details
+        .environ
+        .unwrap_or_default()
+        .iter()
+        .any(|variable| variable == "TVIS_FIXTURE=allocator:1"));
+
+    let pid = workload.pid();
+    drop(workload);
+    assert!(ProcessDetails::collect(pid, false).is_none());
+}
+
+#[test]
+fn availability_tracks_presence_per_sample() {
+    let workload = Workload::allocator(1);
+    let missing = ProcessIdentifier::Name("tvis-no-such-process".to_string());
+    let mut metrics = monitor(&workload.identifier());
+    metrics.add_selected_process(missing.clone());
+    assert!(wait_until(TIMEOUT, || {
+        metrics.update();
+        metrics
+            .availability(&workload.identifier())
+            .is_some_and(|availability| availability.total >= 3)
+    }));
+
+    let availability = |identifier: &ProcessIdentifier| metrics.availability(identifier).cloned();
+
+    assert_eq!(
+        availability(&workload.identifier()).unwrap().uptime(),
+        Some(100.0)
+    );
+    let missing = availability(&missing).expect("absent identifiers are tracked too");
+    assert!(missing.total >= 1);
+    assert_eq!(missing.uptime(), Some(0.0));
+}
+
+#[cfg(unix)]
+#[test]
+fn suspended_process_is_flagged_until_resumed() {
+    let busy = Workload::busy_loop();
+    let identifier = busy.identifier();
+    let mut metrics = monitor(&identifier);
+    let mut suspended = |expected: bool| {
+        wait_for_data(&mut metrics, &identifier, |data| {
+            data.processes_stats
+                .iter()
+                .any(|process| process.pid == busy.pid() && process.suspended == expected)
+        })
+    };
+    assert!(suspended(false));
+
+    let signal = |signal| ProcessMonitor::default().signal(busy.pid(), signal);
+    signal(sysinfo::Signal::Stop).unwrap();
+    assert!(suspended(true), "SIGSTOP never showed up");
+    signal(sysinfo::Signal::Continue).unwrap();
+    assert!(suspended(false), "SIGCONT never showed up");
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn cgroup_identifier_covers_its_members() {
+    let workload = Workload::allocator(1);
+    // The workload inherits the cgroup of the test process
+    let cgroup = std::fs::read_to_string("/proc/self/cgroup").unwrap();
+    let path = cgroup
+        .lines()
+        .find_map(|line| line.strip_prefix("0::"))
+        .or_else(|| {
+            cgroup
+                .lines()
+                .find_map(|line| line.split_once(":name=systemd:"))
+                .map(|(_, path)| path)
+        })
+        .expect("no unified or systemd cgroup");
+    let identifier = ProcessIdentifier::from(format!("cgroup:{path}").as_str());
+    assert_eq!(
+        identifier,
+        ProcessIdentifier::Cgroup(path.trim_matches('/').to_string())
+    );
+    assert!(
+        wait_until(TIMEOUT, || related_processes(&identifier, None)
+            .contains(&workload.pid())),
+        "{} does not contain the workload",
+        identifier
+    );
+}
+
+#[test]
+fn user_identifier_covers_processes_of_the_account() {
+    let workload = Workload::allocator(1);
+    let monitor = ProcessMonitor::default();
+    let uid = monitor
+        .get_process_by_pid(&workload.pid())
+        .and_then(|process| process.user_id().cloned())
+        .expect("workload has no owner");
+    let name = user::user_name(&uid, &user::users());
+    for user in [uid.to_string(), name] {
+        let identifier = ProcessIdentifier::from(format!("user:{user}").as_str());
+        assert_eq!(identifier, ProcessIdentifier::User(user.clone()));
+        assert!(
+            related_processes(&identifier, None).contains(&workload.pid()),
+            "user:{user} does not contain the workload"
+        );
+    }
+    assert!(monitor.user_processes("no_such_user_tvis").is_empty());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn sleeping_loop_reports_context_switches() {
+    // The forker wakes up every 50 ms to reap its children
+    let workload = Workload::forker(1);
+    let identifier = workload.identifier();
+    let mut metrics = monitor(&identifier);
+    assert!(
+        wait_for_data(&mut metrics, &identifier, |data| {
+            data.genereal
+                .history
+                .get_sched_history(&GENERAL_STATS_PID)
+                .is_some_and(|history| history.iter().any(|rates| rates.voluntary_switches > 0.0))
+        }),
+        "no context switch rates recorded"
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn dead_name_match_keeps_its_history() {
+    // A copy of `sleep` under a name nothing else runs as
+    let name = format!("tvisdead{}", std::process::id());
+    let exe = std::env::temp_dir().join(&name);
+    std::fs::copy("/bin/sleep", &exe).unwrap();
+    let mut child = std::process::Command::new(&exe).arg("60").spawn().unwrap();
+    let identifier = ProcessIdentifier::Name(name);
+    let mut metrics = monitor(&identifier);
+    assert!(wait_for_data(&mut metrics, &identifier, |data| {
+        data.session.samples >= 2
+    }));
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+    let _ = std::fs::remove_file(&exe);
+    assert!(
+        wait_for_data(&mut metrics, &identifier, |data| {
+            data.inactive_since.is_some()
+                && data.processes_stats.is_empty()
+                && data
+                    .genereal
+                    .history
+                    .get_cpu_history(&GENERAL_STATS_PID)
+                    .is_some_and(|history| history.len() >= 2)
+        }),
+        "data of the dead match was not kept"
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn signal_of_a_crashed_process_is_recorded() {
+    let mut child = std::process::Command::new("/bin/sleep")
+        .arg("60")
+        .spawn()
+        .unwrap();
+    let pid = Pid::from_u32(child.id());
+    let identifier = ProcessIdentifier::Pid(pid);
+    let mut metrics = monitor(&identifier);
+    assert!(wait_for_data(&mut metrics, &identifier, |data| {
+        data.session.samples >= 2
+    }));
+
+    // Not waited for yet, so it stays a zombie with its exit status
+    assert!(ProcessMonitor::default()
+        .get_process_by_pid(&pid)
+        .unwrap()
+        .kill_with(sysinfo::Signal::Segv)
+        .unwrap());
+    assert!(
+        wait_for_data(&mut metrics, &identifier, |data| {
+            data.finished.is_some()
+                && data.events.iter().any(|event| {
+                    event.kind == ProcessEventKind::Exited
+                        && event.pid == pid
+                        && event.crash.is_some_and(|crash| crash.signal == 11)
+                })
+        }),
+        "the crash was not recorded"
+    );
+    child.wait().unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn renice_and_pin_a_workload() {
+    let workload = Workload::busy_loop();
+    let pid = workload.pid();
+    let before = Priority::read(pid);
+    let nice = before.nice.expect("nice value is readable");
+    let cpus = before.affinity.expect("affinity is readable");
+    assert!(!cpus.is_empty());
+
+    // Lowering the priority and narrowing the CPUs needs no privileges
+    let lower = (nice + 1).min(19);
+    set_nice(pid, lower).expect("renice to a lower priority");
+    set_affinity(pid, &cpus[..1]).expect("pin to one CPU");
+    let after = Priority::read(pid);
+    assert_eq!(after.nice, Some(lower));
+    assert_eq!(after.affinity.as_deref(), Some(&cpus[..1]));
+
+    assert!(set_affinity(pid, &[]).is_err());
+    assert!(set_nice(pid, 40).is_err());
+
+    assert_eq!(
+        scheduling(pid).map(|(_, class)| class),
+        Some(PriorityClass::from_nice(lower))
+    );
+    set_nice(pid, 19).expect("renice to the lowest priority");
+    assert_eq!(scheduling(pid), Some((19, PriorityClass::Idle)));
+}
+
+#[test]
+#[cfg(all(target_os = "linux", feature = "ebpf"))]
+fn ebpf_counts_syscalls_of_a_process() {
+    use tvis::metrics::process::ebpf;
+    let probes = match ebpf::probes() {
+        Ok(probes) => probes,
+        // Loading needs root or CAP_BPF and CAP_PERFMON
+        Err(err) => return eprintln!("skipped: {err}"),
+    };
+    let own = Pid::from_u32(std::process::id());
+    let before = probes.read(own).syscalls;
+    for _ in 0..1000 {
+        let _ = std::fs::metadata("/");
+    }
+    assert!(probes.read(own).syscalls >= before + 1000);
+    assert_eq!(
+        probes.read(own).tcp_sent_bytes.is_none(),
+        probes.tcp_error().is_some()
+    );
+}
+
+#[test]
+fn cleared_data_restarts_and_keeps_sampling() {
+    let workload = Workload::allocator(1);
+    let identifier = workload.identifier();
+    let mut metrics = monitor(&identifier);
+    let samples = |data: &ProcessData| {
+        data.genereal
+            .history
+            .get_cpu_history(&GENERAL_STATS_PID)
+            .map_or(0, |history| history.len())
+    };
+    assert!(wait_for_data(&mut metrics, &identifier, |data| samples(
+        data
+    ) >= 10));
+    metrics.clear_process_data(&identifier);
+    assert!(
+        wait_for_data(&mut metrics, &identifier, |data| samples(data) < 10),
+        "the cleared history was published again"
+    );
+    assert!(
+        wait_for_data(&mut metrics, &identifier, |data| samples(data) >= 3),
+        "sampling stopped after clearing"
+    );
+}
+
+#[test]
+fn group_relation_covers_every_member() {
+    let first = Workload::busy_loop();
+    let second = Workload::forker(1);
+    let group = ProcessIdentifier::Group(vec![first.identifier(), second.identifier()]);
+    assert_eq!(
+        ProcessIdentifier::from(group.to_string().as_str()),
+        group,
+        "group should round-trip through its string form"
+    );
+    assert!(
+        wait_until(TIMEOUT, || related_processes(&group, None).len() == 3),
+        "expected both members and the forked child, got {:?}",
+        related_processes(&group, None)
+    );
+    let related = related_processes(&group, None);
+    assert!(related.contains(&first.pid()) && related.contains(&second.pid()));
+}
+
+#[test]
+fn find_matching_checks_command_lines() {
+    let busy = Workload::busy_loop();
+    assert!(wait_until(TIMEOUT, || {
+        ProcessMonitor::default()
+            .find_matching(|text| text.contains("fixture_entry --exact"))
+            .contains(&busy.pid())
+    }));
+    let own = sysinfo::get_current_pid().unwrap();
+    assert!(!ProcessMonitor::default()
+        .find_matching(|_| true)
+        .contains(&own));
+}
+
+#[test]
+fn influx_lines_are_escaped_and_posted() {
+    use std::io::{BufRead, BufReader, Read, Write};
+
+    let mut sample = Sample {
+        stats: Default::default(),
+        processes: vec![process_info(42, "web worker", 12.5, 1024)],
+        availability: None,
+    };
+    sample.stats.current_cpu = 12.5;
+    sample.stats.current_memory = 1024;
+    sample.stats.process_count = 1;
+    sample.stats.thread_count = 3;
+    let config = InfluxPush {
+        per_process: true,
+        ..Default::default()
+    };
+    let mut lines = String::new();
+    influx::write_lines(&mut lines, &config, &"a=b, c".into(), &sample, 7);
+    assert_eq!(
+        lines,
+        "tvis,identifier=a\\=b\\,\\ c cpu=12.5,memory=1024i,gpu=0,processes=1i,threads=3i 7\n\
+         tvis_process,identifier=a\\=b\\,\\ c,pid=42,name=web\\ worker cpu=12.5,memory=1024i 7\n"
+    );
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!(
+        "http://{}/api/v2/write?bucket=tvis",
+        listener.local_addr().unwrap()
+    );
+    let server = std::thread::spawn(move || {
+        let mut replies = Vec::new();
+        for status in ["204 No Content", "401 Unauthorized"] {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut head = Vec::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line == "\r\n" {
+                    break;
+                }
+                head.push(line.trim_end().to_string());
+            }
+            let length: usize = head
+                .iter()
+                .find_map(|line| line.strip_prefix("Content-Length: "))
+                .unwrap()
+                .parse()
+                .unwrap();
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            let reply = format!("HTTP/1.1 {status}\r\nContent-Length: 11\r\n\r\nbad token\r\n");
+            reader.get_mut().write_all(reply.as_bytes()).unwrap();
+            replies.push((head, String::from_utf8(body).unwrap()));
+        }
+        replies
+    });
+    influx::post(&url, "Token secret", &lines).unwrap();
+    let err = influx::post(&url, "", &lines).unwrap_err();
+    assert!(err.contains("401 Unauthorized: bad token"), "{err}");
+    let replies = server.join().unwrap();
+    assert_eq!(replies[0].0[0], "POST /api/v2/write?bucket=tvis HTTP/1.1");
+    assert!(replies[0]
+        .0
+        .contains(&"Authorization: Token secret".to_string()));
+    assert!(!replies[1]
+        .0
+        .iter()
+        .any(|line| line.starts_with("Authorization")));
+    assert_eq!(replies[0].1, lines);
+    assert!(influx::post("https://example.com/write", "", "").is_err());
+}
+
+#[test]
+fn system_swap_is_sampled_with_the_processes() {
+    assert_eq!(SwapUsage::default().percent(), 0.0);
+
+    let mut metrics = Metrics::new(50, 200);
+    assert!(wait_until(TIMEOUT, || {
+        metrics.update();
+        metrics.tick > 1
+    }));
+    let mut system = sysinfo::System::new();
+    system.refresh_memory();
+    let swap = metrics.swap;
+    assert_eq!(swap.total, system.total_swap());
+    assert!(swap.used <= swap.total);
+    assert!((0.0..=100.0).contains(&swap.percent()));
+}
+
+#[test]
+fn cpu_share_is_relative_to_system_usage() {
+    assert_eq!(cpu_share(50.0, 200.0), 25.0);
+    assert_eq!(cpu_share(210.0, 200.0), 100.0);
+    assert_eq!(cpu_share(5.0, 0.0), 0.0);
+
+    let busy = Workload::busy_loop();
+    let identifier = busy.identifier();
+    let mut metrics = monitor(&identifier);
+    assert!(wait_for_data(&mut metrics, &identifier, |data| {
+        data.genereal.stats.cpu_share > 0.0
+    }));
+    let history = &metrics
+        .get_process_data(&identifier)
+        .unwrap()
+        .genereal
+        .history;
+    let share = history.get_cpu_share_history(&GENERAL_STATS_PID).unwrap();
+    assert_eq!(
+        share.len(),
+        history.get_cpu_history(&GENERAL_STATS_PID).unwrap().len()
+    );
+    assert!(share.iter().all(|share| (0.0..=100.0).contains(share)));
+}
+
+#[test]
+fn cpu_thermals_are_sampled_only_while_enabled() {
+    let mut metrics = Metrics::new(3, 200);
+    metrics.set_sample_thermals(true);
+    assert!(wait_until(TIMEOUT, || {
+        metrics.update();
+        metrics.tick > 5
+    }));
+    {
+        let thermals = &metrics.cpu_thermals;
+        assert!(thermals.temperatures.len() <= 3 && thermals.frequencies.len() <= 3);
+        assert!(thermals
+            .temperature
+            .is_none_or(|celsius| (1.0..150.0).contains(&celsius)));
+        assert!(
+            thermals.frequency == 0 || thermals.frequencies.back() == Some(&thermals.frequency)
+        );
+    }
+
+    metrics.set_sample_thermals(false);
+    assert!(wait_until(TIMEOUT, || {
+        metrics.update();
+        let thermals = &metrics.cpu_thermals;
+        thermals.frequency == 0 && thermals.temperatures.is_empty()
+    }));
+}
+
+#[test]
+fn alert_rules_fire_without_a_handle_updating() {
+    let busy = Workload::busy_loop();
+    let identifier = busy.identifier();
+    let rule = AlertRule::new(identifier.clone(), MetricType::Cpu, 20.0);
+    let mut metrics = monitor(&identifier);
+    metrics.set_alert_rules(vec![rule.clone()]);
+
+    // Checked by the worker while nothing picks up snapshots
+    std::thread::sleep(Duration::from_secs(3));
+    assert!(wait_until(TIMEOUT, || {
+        metrics.update();
+        metrics.firing.contains(&rule.key())
+    }));
+    let alert = metrics
+        .alerts
+        .iter()
+        .find(|alert| alert.identifier == identifier);
+    assert!(alert.is_some_and(|alert| alert.value > 20.0 && alert.tick <= metrics.tick));
+}
+
+#[test]
+fn editing_a_firing_rule_keeps_it_firing() {
+    let busy = Workload::busy_loop();
+    let identifier = busy.identifier();
+    // Unlike the CPU of the busy loop, its process count never dips
+    let mut rule = AlertRule::new(identifier.clone(), MetricType::Processes, 0.0);
+    let mut metrics = monitor(&identifier);
+    metrics.set_alert_rules(vec![rule.clone()]);
+    assert!(wait_until(TIMEOUT, || {
+        metrics.update();
+        metrics.firing.contains(&rule.key())
+    }));
+    let fired = |metrics: &Metrics| {
+        metrics
+            .alerts
+            .iter()
+            .filter(|alert| alert.identifier == identifier)
+            .count()
+    };
+    let before = fired(&metrics);
+
+    // Same condition, so it does not cross the threshold again
+    rule.severity = Severity::Critical;
+    rule.command = "true".to_string();
+    metrics.set_alert_rules(vec![rule.clone()]);
+    std::thread::sleep(Duration::from_secs(2));
+    metrics.update();
+    assert!(metrics.firing.contains(&rule.key()));
+    assert_eq!(fired(&metrics), before);
+}
+
+#[cfg(unix)]
+#[test]
+fn alert_commands_run_from_the_worker() {
+    let busy = Workload::busy_loop();
+    let identifier = busy.identifier();
+    let marker = std::env::temp_dir().join(format!("tvis_alert_{}", std::process::id()));
+    std::fs::remove_file(&marker).ok();
+    let mut rule = AlertRule::new(identifier.clone(), MetricType::Cpu, 20.0);
+    rule.command = format!("echo \"$TVIS_PID\" > {}", marker.display());
+    let mut metrics = monitor(&identifier);
+    metrics.set_alert_rules(vec![rule]);
+
+    // No snapshot is picked up, the worker runs the command on its own
+    assert!(
+        wait_until(TIMEOUT, || std::fs::read_to_string(&marker)
+            .is_ok_and(|pid| pid.trim() == busy.pid().to_string())),
+        "alert command never ran"
+    );
+    std::fs::remove_file(&marker).ok();
+}
+
+#[cfg(feature = "gui")]
+#[test]
+fn parquet_export_has_a_row_per_sample_of_each_process() {
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use parquet::record::RowAccessor;
+
+    let workload = Workload::busy_loop();
+    let identifier = workload.identifier();
+    let mut metrics = monitor(&identifier);
+    assert!(wait_for_data(&mut metrics, &identifier, |data| data
+        .session
+        .samples
+        >= 3));
+
+    let path = std::env::temp_dir().join(format!("tvis_export_{}.parquet", std::process::id()));
+    let path = path.to_string_lossy().to_string();
+    let rows = save_parquet(&metrics, &[identifier.clone()], &path).unwrap();
+    let reader = SerializedFileReader::new(std::fs::File::open(&path).unwrap()).unwrap();
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(reader.metadata().file_metadata().num_rows(), rows as i64);
+    let fields: Vec<String> = reader
+        .metadata()
+        .file_metadata()
+        .schema()
+        .get_fields()
+        .iter()
+        .map(|field| field.name().to_string())
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            "identifier",
+            "pid",
+            "ts",
+            "cpu_percent",
+            "memory_bytes",
+            "gpu_percent"
+        ]
+    );
+    let rows: Vec<_> = reader
+        .get_row_iter(None)
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    let pids: Vec<u32> = rows.iter().map(|row| row.get_uint(1).unwrap()).collect();
+    // The aggregate of the tree comes first, under pid 0
+    assert_eq!(pids[0], 0);
+    assert!(pids.contains(&workload.pid().as_u32()));
+    assert_eq!(rows[0].get_string(0).unwrap(), &identifier.to_string());
+    let timestamps: Vec<i64> = rows
+        .iter()
+        .filter(|row| row.get_uint(1).unwrap() == 0)
+        .map(|row| row.get_timestamp_millis(2).unwrap())
+        .collect();
+    assert!(timestamps.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[test]
+#[cfg(any(target_os = "linux", windows))]
+fn sockets_of_a_process_are_listed_with_their_state() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let client = std::net::TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let udp = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+
+    let listed = sockets(Pid::from_u32(std::process::id())).unwrap();
+    let find = |local| listed.iter().find(|socket| socket.local == local);
+    let listening = find(listener.local_addr().unwrap()).expect("the listener is listed");
+    assert_eq!(listening.state, Some(TcpState::Listen));
+    assert_eq!(listening.remote, None);
+    let connected = find(client.local_addr().unwrap()).expect("the client is listed");
+    assert_eq!(connected.state, Some(TcpState::Established));
+    assert_eq!(connected.remote, Some(listener.local_addr().unwrap()));
+    let unconnected = find(udp.local_addr().unwrap()).expect("the UDP socket is listed");
+    assert_eq!(unconnected.protocol, SocketProtocol::Udp);
+    assert_eq!(unconnected.state, None);
+}
@@ -0,0 +1,128 @@
+//! Controlled child workloads for the integration tests.
+//!
+//! The test binary re-runs itself with [`FIXTURE_ENV`] set and only the
+//! ignored `fixture_entry` test selected, which then calls [`run_from_env`].
+//! Every workload exits on its own after [`LIFETIME`] so nothing outlives a crashed run.
+
+use std::hint::black_box;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+use sysinfo::Pid;
+use tvis::metrics::process::{ProcessIdentifier, ProcessMonitor};
+
+pub const FIXTURE_ENV: &str = "TVIS_FIXTURE";
+pub const LIFETIME: Duration = Duration::from_secs(60);
+
+/// A spawned workload, killed together with its descendants on drop
+pub struct Workload {
+    child: Child,
+}
+
+impl Workload {
+    /// Spins one core
+    pub fn busy_loop() -> Self {
+        Self::spawn("busy-loop")
+    }
+
+    /// Allocates and touches `megabytes` of memory, then idles
+    pub fn allocator(megabytes: usize) -> Self {
+        Self::spawn(&format!("allocator:{megabytes}"))
+    }
+
+    /// Starts `children` idle child workloads, then idles
+    pub fn forker(children: usize) -> Self {
+        Self::spawn(&format!("forker:{children}"))
+    }
+
+    fn spawn(spec: &str) -> Self {
+        let child = Command::new(std::env::current_exe().expect("test binary path"))
+            .args([
+                "fixture_entry",
+                "--exact",
+                "--ignored",
+                "--nocapture",
+                "--test-threads=1",
+            ])
+            .env(FIXTURE_ENV, spec)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("spawn workload fixture");
+        Self { child }
+    }
+
+    pub fn pid(&self) -> Pid {
+        Pid::from_u32(self.child.id())
+    }
+
+    pub fn identifier(&self) -> ProcessIdentifier {
+        ProcessIdentifier::Pid(self.pid())
+    }
+}
+
+impl Drop for Workload {
+    fn drop(&mut self) {
+        let monitor = ProcessMonitor::default();
+        for pid in monitor
+            .find_all_relation(&self.identifier(), None)
+            .unwrap_or_default()
+        {
+            if pid != self.pid() {
+                if let Some(process) = monitor.get_process_by_pid(&pid) {
+                    process.kill();
+                }
+            }
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Runs the workload named by [`FIXTURE_ENV`]; does nothing when it is unset
+pub fn run_from_env() {
+    let Ok(spec) = std::env::var(FIXTURE_ENV) else {
+        return;
+    };
+    let (kind, arg) = spec.split_once(':').unwrap_or((&spec, "0"));
+    let arg: usize = arg.parse().expect("numeric fixture argument");
+    let started = Instant::now();
+    match kind {
+        "busy-loop" => {
+            let mut counter = 0u64;
+            while started.elapsed() < LIFETIME {
+                counter = black_box(counter.wrapping_add(1));
+            }
+        }
+        "allocator" => {
+            let memory = vec![0x5au8; arg * 1024 * 1024];
+            black_box(&memory);
+            std::thread::sleep(LIFETIME);
+            black_box(&memory);
+        }
+        "forker" => {
+            let mut children: Vec<Workload> = (0..arg).map(|_| Workload::spawn("sleep")).collect();
+            // Reap children killed by the tests so they don't linger as zombies
+            while started.elapsed() < LIFETIME {
+                for child in &mut children {
+                    let _ = child.child.try_wait();
+                }
+                std::thread::sleep(Duration::from_millis(50));
+            }
+        }
+        "sleep" => std::thread::sleep(LIFETIME),
+        _ => panic!("unknown fixture {spec}"),
+    }
+}
+
+/// Polls `condition` until it holds or `timeout` passes
+pub fn wait_until(timeout: Duration, mut condition: impl FnMut() -> bool) -> bool {
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        if condition() {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    condition()
+}